@@ -49,5 +49,5 @@ CREATE TABLE characters (
   stealth_proficiency TEXT NOT NULL DEFAULT 'Normal',
   survival_proficiency TEXT NOT NULL DEFAULT 'Normal',
 
-  PRIMARY KEY (channel_id, user_id),
+  PRIMARY KEY (channel_id, user_id)
 );
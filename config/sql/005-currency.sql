@@ -0,0 +1,12 @@
+CREATE TABLE character_coins (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+
+  copper INTEGER NOT NULL DEFAULT 0,
+  silver INTEGER NOT NULL DEFAULT 0,
+  electrum INTEGER NOT NULL DEFAULT 0,
+  gold INTEGER NOT NULL DEFAULT 0,
+  platinum INTEGER NOT NULL DEFAULT 0,
+
+  PRIMARY KEY (channel_id, user_id)
+);
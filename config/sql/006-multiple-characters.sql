@@ -0,0 +1,190 @@
+-- Allow each user to have multiple characters in a channel, identified by name. Existing characters
+-- are given the name "default".
+
+CREATE TABLE characters_new (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+
+  avatar_url TEXT NULL,
+
+  -- Level
+  level INTEGER NULL,
+
+  -- Jack of All Trades
+  jack_of_all_trades BOOLEAN NOT NULL DEFAULT false,
+
+  -- Martial Arts (Monk trait)
+  martial_arts BOOLEAN NOT NULL DEFAULT false,
+
+  -- Abilities
+  strength INTEGER NULL,
+  dexterity INTEGER NULL,
+  constitution INTEGER NULL,
+  intelligence INTEGER NULL,
+  wisdom INTEGER NULL,
+  charisma INTEGER NULL,
+
+  -- Saving Throws
+  strength_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  dexterity_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  constitution_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  intelligence_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  wisdom_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  charisma_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+
+  -- Skills
+  acrobatics_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  animal_handling_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  arcana_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  athletics_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  deception_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  history_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  insight_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  intimidation_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  investigation_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  medicine_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  nature_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  perception_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  performance_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  persuasion_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  religion_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  sleight_of_hand_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  stealth_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  survival_proficiency TEXT NOT NULL DEFAULT 'Normal',
+
+  PRIMARY KEY (channel_id, user_id, name)
+);
+
+INSERT INTO characters_new (
+  channel_id,
+  user_id,
+  name,
+  avatar_url,
+  level,
+  jack_of_all_trades,
+  martial_arts,
+  strength,
+  dexterity,
+  constitution,
+  intelligence,
+  wisdom,
+  charisma,
+  strength_saving_proficiency,
+  dexterity_saving_proficiency,
+  constitution_saving_proficiency,
+  intelligence_saving_proficiency,
+  wisdom_saving_proficiency,
+  charisma_saving_proficiency,
+  acrobatics_proficiency,
+  animal_handling_proficiency,
+  arcana_proficiency,
+  athletics_proficiency,
+  deception_proficiency,
+  history_proficiency,
+  insight_proficiency,
+  intimidation_proficiency,
+  investigation_proficiency,
+  medicine_proficiency,
+  nature_proficiency,
+  perception_proficiency,
+  performance_proficiency,
+  persuasion_proficiency,
+  religion_proficiency,
+  sleight_of_hand_proficiency,
+  stealth_proficiency,
+  survival_proficiency
+)
+SELECT
+  channel_id,
+  user_id,
+  'default',
+  avatar_url,
+  level,
+  jack_of_all_trades,
+  martial_arts,
+  strength,
+  dexterity,
+  constitution,
+  intelligence,
+  wisdom,
+  charisma,
+  strength_saving_proficiency,
+  dexterity_saving_proficiency,
+  constitution_saving_proficiency,
+  intelligence_saving_proficiency,
+  wisdom_saving_proficiency,
+  charisma_saving_proficiency,
+  acrobatics_proficiency,
+  animal_handling_proficiency,
+  arcana_proficiency,
+  athletics_proficiency,
+  deception_proficiency,
+  history_proficiency,
+  insight_proficiency,
+  intimidation_proficiency,
+  investigation_proficiency,
+  medicine_proficiency,
+  nature_proficiency,
+  perception_proficiency,
+  performance_proficiency,
+  persuasion_proficiency,
+  religion_proficiency,
+  sleight_of_hand_proficiency,
+  stealth_proficiency,
+  survival_proficiency
+FROM characters;
+
+DROP TABLE characters;
+
+ALTER TABLE characters_new RENAME TO characters;
+
+CREATE TABLE character_weapon_proficiencies_new (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  weapon_name TEXT NULL REFERENCES weapons (name),
+  weapon_category TEXT NULL,
+  FOREIGN KEY (channel_id, user_id, name) REFERENCES characters (channel_id, user_id, name),
+  CHECK (weapon_category = 'Simple' OR weapon_category = 'Martial' or weapon_category is null),
+  CHECK ((weapon_name is null) <> ( weapon_category is null)),
+  UNIQUE (channel_id, user_id, name, weapon_name),
+  UNIQUE (channel_id, user_id, name, weapon_category)
+);
+
+INSERT INTO character_weapon_proficiencies_new (channel_id, user_id, name, weapon_name, weapon_category)
+SELECT channel_id, user_id, 'default', weapon_name, weapon_category
+FROM character_weapon_proficiencies;
+
+DROP TABLE character_weapon_proficiencies;
+
+ALTER TABLE character_weapon_proficiencies_new RENAME TO character_weapon_proficiencies;
+
+CREATE TABLE character_coins_new (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+
+  copper INTEGER NOT NULL DEFAULT 0,
+  silver INTEGER NOT NULL DEFAULT 0,
+  electrum INTEGER NOT NULL DEFAULT 0,
+  gold INTEGER NOT NULL DEFAULT 0,
+  platinum INTEGER NOT NULL DEFAULT 0,
+
+  PRIMARY KEY (channel_id, user_id, name)
+);
+
+INSERT INTO character_coins_new (channel_id, user_id, name, copper, silver, electrum, gold, platinum)
+SELECT channel_id, user_id, 'default', copper, silver, electrum, gold, platinum
+FROM character_coins;
+
+DROP TABLE character_coins;
+
+ALTER TABLE character_coins_new RENAME TO character_coins;
+
+CREATE TABLE active_characters (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  PRIMARY KEY (channel_id, user_id)
+);
@@ -0,0 +1 @@
+ALTER TABLE characters ADD COLUMN display_name TEXT NULL;
@@ -0,0 +1 @@
+ALTER TABLE channels ADD COLUMN duplicate_window INTEGER NOT NULL DEFAULT 2;
@@ -0,0 +1,149 @@
+-- Store each character's class and subclass, from which features such as Jack of All Trades and
+-- Martial Arts are derived. The existing feature columns become nullable overrides, where null
+-- means the feature is derived from the class. Features that were previously turned off are reset
+-- to be derived, while features that were turned on remain overridden on.
+
+CREATE TABLE characters_new (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+
+  display_name TEXT NULL,
+  avatar_url TEXT NULL,
+
+  -- Level
+  level INTEGER NULL,
+
+  -- Class
+  class TEXT NULL,
+  subclass TEXT NULL,
+
+  -- Jack of All Trades (Bard feature), or null to derive from class
+  jack_of_all_trades BOOLEAN NULL,
+
+  -- Martial Arts (Monk feature), or null to derive from class
+  martial_arts BOOLEAN NULL,
+
+  -- Abilities
+  strength INTEGER NULL,
+  dexterity INTEGER NULL,
+  constitution INTEGER NULL,
+  intelligence INTEGER NULL,
+  wisdom INTEGER NULL,
+  charisma INTEGER NULL,
+
+  -- Saving Throws
+  strength_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  dexterity_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  constitution_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  intelligence_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  wisdom_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+  charisma_saving_proficiency BOOLEAN NOT NULL DEFAULT false,
+
+  -- Skills
+  acrobatics_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  animal_handling_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  arcana_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  athletics_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  deception_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  history_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  insight_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  intimidation_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  investigation_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  medicine_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  nature_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  perception_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  performance_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  persuasion_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  religion_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  sleight_of_hand_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  stealth_proficiency TEXT NOT NULL DEFAULT 'Normal',
+  survival_proficiency TEXT NOT NULL DEFAULT 'Normal',
+
+  PRIMARY KEY (channel_id, user_id, name)
+);
+
+INSERT INTO characters_new (
+  channel_id,
+  user_id,
+  name,
+  display_name,
+  avatar_url,
+  level,
+  jack_of_all_trades,
+  martial_arts,
+  strength,
+  dexterity,
+  constitution,
+  intelligence,
+  wisdom,
+  charisma,
+  strength_saving_proficiency,
+  dexterity_saving_proficiency,
+  constitution_saving_proficiency,
+  intelligence_saving_proficiency,
+  wisdom_saving_proficiency,
+  charisma_saving_proficiency,
+  acrobatics_proficiency,
+  animal_handling_proficiency,
+  arcana_proficiency,
+  athletics_proficiency,
+  deception_proficiency,
+  history_proficiency,
+  insight_proficiency,
+  intimidation_proficiency,
+  investigation_proficiency,
+  medicine_proficiency,
+  nature_proficiency,
+  perception_proficiency,
+  performance_proficiency,
+  persuasion_proficiency,
+  religion_proficiency,
+  sleight_of_hand_proficiency,
+  stealth_proficiency,
+  survival_proficiency
+)
+SELECT
+  channel_id,
+  user_id,
+  name,
+  display_name,
+  avatar_url,
+  level,
+  NULLIF(jack_of_all_trades, false),
+  NULLIF(martial_arts, false),
+  strength,
+  dexterity,
+  constitution,
+  intelligence,
+  wisdom,
+  charisma,
+  strength_saving_proficiency,
+  dexterity_saving_proficiency,
+  constitution_saving_proficiency,
+  intelligence_saving_proficiency,
+  wisdom_saving_proficiency,
+  charisma_saving_proficiency,
+  acrobatics_proficiency,
+  animal_handling_proficiency,
+  arcana_proficiency,
+  athletics_proficiency,
+  deception_proficiency,
+  history_proficiency,
+  insight_proficiency,
+  intimidation_proficiency,
+  investigation_proficiency,
+  medicine_proficiency,
+  nature_proficiency,
+  perception_proficiency,
+  performance_proficiency,
+  persuasion_proficiency,
+  religion_proficiency,
+  sleight_of_hand_proficiency,
+  stealth_proficiency,
+  survival_proficiency
+FROM characters;
+
+DROP TABLE characters;
+
+ALTER TABLE characters_new RENAME TO characters;
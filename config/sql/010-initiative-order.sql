@@ -0,0 +1,7 @@
+CREATE TABLE initiative_order (
+  channel_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  initiative INTEGER NOT NULL,
+  position INTEGER NOT NULL,
+  PRIMARY KEY (channel_id, name)
+);
@@ -0,0 +1 @@
+ALTER TABLE characters ADD COLUMN experience INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+CREATE TABLE character_conditions (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  condition TEXT NOT NULL,
+  PRIMARY KEY (channel_id, user_id, name, condition)
+);
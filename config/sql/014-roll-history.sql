@@ -0,0 +1,11 @@
+CREATE TABLE roll_history (
+  message_id TEXT PRIMARY KEY,
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  character_name TEXT NULL,
+  description TEXT NOT NULL,
+  result INTEGER NOT NULL,
+  tag TEXT NULL,
+  purpose TEXT NULL,
+  rolled TIMESTAMP NOT NULL
+);
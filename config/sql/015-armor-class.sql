@@ -0,0 +1 @@
+ALTER TABLE characters ADD COLUMN armor_class INTEGER NULL;
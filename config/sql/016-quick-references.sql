@@ -0,0 +1,9 @@
+CREATE TABLE character_quick_references (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  message_id TEXT NOT NULL,
+  author_name TEXT NOT NULL,
+  description TEXT NOT NULL,
+  PRIMARY KEY (channel_id, user_id, name)
+);
@@ -0,0 +1,16 @@
+CREATE TABLE initiative_turns (
+  channel_id TEXT NOT NULL PRIMARY KEY,
+  position INTEGER NOT NULL,
+  round INTEGER NOT NULL
+);
+
+CREATE TABLE ongoing_effects (
+  id INTEGER PRIMARY KEY,
+  channel_id TEXT NOT NULL,
+  target TEXT NOT NULL COLLATE NOCASE,
+  rolls INTEGER NOT NULL,
+  sides INTEGER NOT NULL,
+  modifier INTEGER NOT NULL,
+  damage_type TEXT NULL,
+  rounds INTEGER NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE characters ADD COLUMN inspiration INTEGER NOT NULL DEFAULT 0;
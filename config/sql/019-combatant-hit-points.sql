@@ -0,0 +1,16 @@
+CREATE TABLE combatant_hit_points (
+  channel_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  hit_points INTEGER NOT NULL,
+  PRIMARY KEY (channel_id, name)
+);
+
+CREATE TABLE combatant_damage_taken (
+  channel_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  damage_type TEXT NOT NULL COLLATE NOCASE,
+  PRIMARY KEY (channel_id, name, damage_type)
+);
+
+ALTER TABLE ongoing_effects ADD COLUMN kind TEXT NOT NULL DEFAULT 'Damage';
+ALTER TABLE ongoing_effects ADD COLUMN suppressed_by TEXT NULL;
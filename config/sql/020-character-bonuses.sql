@@ -0,0 +1,9 @@
+CREATE TABLE character_bonuses (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  target TEXT NOT NULL COLLATE NOCASE,
+  amount INTEGER NOT NULL,
+  source TEXT NOT NULL COLLATE NOCASE,
+  PRIMARY KEY (channel_id, user_id, name, target, source)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE characters ADD COLUMN initiative_bonus INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE characters ADD COLUMN alert INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,8 @@
+CREATE TABLE encounter_combatants (
+  channel_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  damage_taken INTEGER NOT NULL DEFAULT 0,
+  defeated INTEGER NOT NULL DEFAULT 0,
+  experience INTEGER NULL,
+  PRIMARY KEY (channel_id, name)
+);
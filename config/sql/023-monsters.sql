@@ -0,0 +1,11 @@
+CREATE TABLE monsters (
+  channel_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  armor_class INTEGER NOT NULL,
+  hit_points INTEGER NOT NULL,
+  attack_bonus INTEGER NOT NULL,
+  damage TEXT NOT NULL,
+  attack_name TEXT NULL,
+  challenge_rating TEXT NOT NULL,
+  PRIMARY KEY (channel_id, name)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE character_counters (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  counter TEXT NOT NULL COLLATE NOCASE,
+  value INTEGER NOT NULL,
+  maximum INTEGER NOT NULL,
+  reset TEXT NULL,
+  PRIMARY KEY (channel_id, user_id, name, counter)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE lore (
+  channel_id TEXT NOT NULL,
+  subject TEXT NOT NULL COLLATE NOCASE,
+  skill TEXT NOT NULL,
+  dc INTEGER NOT NULL,
+  text TEXT NOT NULL,
+  revealed INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (channel_id, subject)
+);
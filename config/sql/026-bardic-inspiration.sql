@@ -0,0 +1 @@
+ALTER TABLE characters ADD COLUMN bardic_inspiration INTEGER NULL;
@@ -0,0 +1,20 @@
+-- Allow lore to be attached to an NPC, quest or location, and to be kept hidden until the DM
+-- reveals it, without a check that recalls it.
+
+CREATE TABLE lore_new (
+  channel_id TEXT NOT NULL,
+  subject TEXT NOT NULL COLLATE NOCASE,
+  kind TEXT NULL,
+  skill TEXT NULL,
+  dc INTEGER NULL,
+  text TEXT NOT NULL,
+  revealed INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (channel_id, subject)
+);
+
+INSERT INTO lore_new (channel_id, subject, skill, dc, text, revealed)
+SELECT channel_id, subject, skill, dc, text, revealed FROM lore;
+
+DROP TABLE lore;
+
+ALTER TABLE lore_new RENAME TO lore;
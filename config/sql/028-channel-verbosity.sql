@@ -0,0 +1 @@
+ALTER TABLE channels ADD COLUMN verbosity TEXT NOT NULL DEFAULT 'normal';
@@ -0,0 +1,15 @@
+CREATE TABLE character_effects (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  effect TEXT NOT NULL COLLATE NOCASE,
+  rolls INTEGER NOT NULL,
+  sides INTEGER NOT NULL,
+  penalty INTEGER NOT NULL DEFAULT 0,
+  attack_rolls INTEGER NOT NULL DEFAULT 0,
+  saving_throws INTEGER NOT NULL DEFAULT 0,
+  checks INTEGER NOT NULL DEFAULT 0,
+  rounds INTEGER NULL,
+  single_use INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (channel_id, user_id, name, effect)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE character_audit (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  attribute TEXT NOT NULL,
+  old_value TEXT NULL,
+  new_value TEXT NULL,
+  changed_by TEXT NOT NULL,
+  changed TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,3 @@
+ALTER TABLE messages ADD COLUMN parsed_content TEXT NULL;
+
+ALTER TABLE slots ADD COLUMN entity TEXT NULL;
@@ -0,0 +1 @@
+ALTER TABLE characters ADD COLUMN two_weapon_fighting INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE roll_history ADD COLUMN signature TEXT NULL;
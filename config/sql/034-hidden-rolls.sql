@@ -0,0 +1,9 @@
+CREATE TABLE hidden_rolls (
+  code TEXT PRIMARY KEY,
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  roll TEXT NOT NULL,
+  details TEXT NOT NULL,
+  result INTEGER NOT NULL,
+  rolled TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
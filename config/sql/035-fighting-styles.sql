@@ -0,0 +1,3 @@
+ALTER TABLE characters ADD COLUMN fighting_style TEXT NULL;
+
+UPDATE characters SET fighting_style = 'Two-Weapon Fighting' WHERE two_weapon_fighting = 1;
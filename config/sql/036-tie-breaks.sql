@@ -0,0 +1,5 @@
+ALTER TABLE channels ADD COLUMN tie_break TEXT NOT NULL DEFAULT 'simultaneous';
+
+ALTER TABLE initiative_order ADD COLUMN tie TEXT NULL;
+
+ALTER TABLE initiative_order ADD COLUMN tie_value INTEGER NULL;
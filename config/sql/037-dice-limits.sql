@@ -0,0 +1,3 @@
+ALTER TABLE channels ADD COLUMN maximum_rolls INTEGER NULL;
+
+ALTER TABLE channels ADD COLUMN maximum_sides INTEGER NULL;
@@ -0,0 +1,7 @@
+CREATE TABLE combatant_resistances (
+  channel_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  damage_type TEXT NOT NULL COLLATE NOCASE,
+  resistance TEXT NOT NULL,
+  PRIMARY KEY (channel_id, name, damage_type)
+);
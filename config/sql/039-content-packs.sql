@@ -0,0 +1,19 @@
+CREATE TABLE content_packs (
+  guild_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  enabled BOOLEAN NOT NULL DEFAULT true,
+  PRIMARY KEY (guild_id, name)
+);
+
+CREATE TABLE content_pack_monsters (
+  guild_id TEXT NOT NULL,
+  pack_name TEXT NOT NULL COLLATE NOCASE,
+  name TEXT NOT NULL COLLATE NOCASE,
+  armor_class INTEGER NOT NULL,
+  hit_points INTEGER NOT NULL,
+  attack_bonus INTEGER NOT NULL,
+  damage TEXT NOT NULL,
+  attack_name TEXT NULL,
+  challenge_rating TEXT NOT NULL,
+  PRIMARY KEY (guild_id, pack_name, name)
+);
@@ -0,0 +1 @@
+ALTER TABLE channels ADD COLUMN edition TEXT NOT NULL DEFAULT '2014';
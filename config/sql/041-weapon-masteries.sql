@@ -0,0 +1,7 @@
+CREATE TABLE character_weapon_masteries (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  weapon_name TEXT NOT NULL REFERENCES weapons (name),
+  PRIMARY KEY (channel_id, user_id, name, weapon_name)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE user_time_zones (
+  user_id TEXT NOT NULL PRIMARY KEY,
+  utc_offset INTEGER NOT NULL
+);
+
+CREATE TABLE guild_time_zones (
+  guild_id TEXT NOT NULL PRIMARY KEY,
+  utc_offset INTEGER NOT NULL
+);
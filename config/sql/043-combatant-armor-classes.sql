@@ -0,0 +1,6 @@
+CREATE TABLE combatant_armor_classes (
+  channel_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  armor_class INTEGER NOT NULL,
+  PRIMARY KEY (channel_id, name)
+);
@@ -0,0 +1 @@
+INSERT INTO weapons (name) VALUES ('Net');
@@ -0,0 +1 @@
+ALTER TABLE character_conditions ADD COLUMN rounds INTEGER;
@@ -0,0 +1,3 @@
+ALTER TABLE combatant_hit_points ADD COLUMN maximum_hit_points INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE combatant_hit_points ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT false;
+UPDATE combatant_hit_points SET maximum_hit_points = hit_points;
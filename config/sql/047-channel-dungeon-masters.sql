@@ -0,0 +1 @@
+ALTER TABLE channels ADD COLUMN dm_user_id TEXT NULL;
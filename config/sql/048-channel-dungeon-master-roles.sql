@@ -0,0 +1 @@
+ALTER TABLE channels ADD COLUMN dm_role_id TEXT NULL;
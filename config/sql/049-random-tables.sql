@@ -0,0 +1,8 @@
+CREATE TABLE random_table_entries (
+  channel_id TEXT NOT NULL,
+  table_name TEXT NOT NULL COLLATE NOCASE,
+  position INTEGER NOT NULL,
+  weight INTEGER NOT NULL,
+  text TEXT NOT NULL,
+  PRIMARY KEY (channel_id, table_name, position)
+);
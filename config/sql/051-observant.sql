@@ -0,0 +1 @@
+ALTER TABLE characters ADD COLUMN observant INTEGER NOT NULL DEFAULT 0;
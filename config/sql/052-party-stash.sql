@@ -0,0 +1,19 @@
+CREATE TABLE party_coins (
+  channel_id TEXT NOT NULL,
+
+  copper INTEGER NOT NULL DEFAULT 0,
+  silver INTEGER NOT NULL DEFAULT 0,
+  electrum INTEGER NOT NULL DEFAULT 0,
+  gold INTEGER NOT NULL DEFAULT 0,
+  platinum INTEGER NOT NULL DEFAULT 0,
+
+  PRIMARY KEY (channel_id)
+);
+
+CREATE TABLE party_items (
+  channel_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  quantity INTEGER NOT NULL,
+
+  PRIMARY KEY (channel_id, name)
+);
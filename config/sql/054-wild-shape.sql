@@ -0,0 +1,16 @@
+CREATE TABLE character_wild_shape_forms (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  form TEXT NOT NULL COLLATE NOCASE,
+  PRIMARY KEY (channel_id, user_id, name, form)
+);
+
+CREATE TABLE character_wild_shapes (
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL COLLATE NOCASE,
+  form TEXT NOT NULL,
+  hit_points INTEGER NOT NULL,
+  PRIMARY KEY (channel_id, user_id, name)
+);
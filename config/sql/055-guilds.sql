@@ -0,0 +1,9 @@
+CREATE TABLE guilds (
+  guild_id TEXT NOT NULL PRIMARY KEY,
+  enabled BOOLEAN NOT NULL DEFAULT false,
+  locked BOOLEAN NOT NULL DEFAULT false,
+  dice_only BOOLEAN NOT NULL DEFAULT false,
+  prefix TEXT NOT NULL DEFAULT '!'
+);
+
+ALTER TABLE channels ADD COLUMN prefix TEXT NOT NULL DEFAULT '!';
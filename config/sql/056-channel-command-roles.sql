@@ -0,0 +1,6 @@
+CREATE TABLE channel_command_roles (
+  channel_id TEXT NOT NULL,
+  category TEXT NOT NULL,
+  role_id TEXT NOT NULL,
+  PRIMARY KEY (channel_id, category)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE channels ADD COLUMN keep_roll_messages BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE guilds ADD COLUMN keep_roll_messages BOOLEAN NOT NULL DEFAULT false;
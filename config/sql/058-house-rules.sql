@@ -0,0 +1,6 @@
+CREATE TABLE channel_house_rules (
+  channel_id TEXT NOT NULL,
+  rule TEXT NOT NULL,
+  value TEXT NOT NULL,
+  PRIMARY KEY (channel_id, rule)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE channels ADD COLUMN spelling_correction BOOLEAN NOT NULL DEFAULT true;
+
+ALTER TABLE guilds ADD COLUMN spelling_correction BOOLEAN NOT NULL DEFAULT true;
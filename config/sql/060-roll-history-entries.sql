@@ -0,0 +1,26 @@
+-- A message can make several rolls, such as an attack made twice, or several commands in one
+-- message, so each roll in the roll history is keyed by its position in the message as well as
+-- the message. Previously only the first roll of each message could be recorded.
+
+CREATE TABLE roll_history_new (
+  message_id TEXT NOT NULL,
+  entry_index INTEGER NOT NULL DEFAULT 0,
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  character_name TEXT NULL,
+  description TEXT NOT NULL,
+  result INTEGER NOT NULL,
+  tag TEXT NULL,
+  purpose TEXT NULL,
+  rolled TIMESTAMP NOT NULL,
+  signature TEXT NULL,
+  PRIMARY KEY (message_id, entry_index)
+);
+
+INSERT INTO roll_history_new (message_id, entry_index, channel_id, user_id, character_name, description, result, tag, purpose, rolled, signature)
+  SELECT message_id, 0, channel_id, user_id, character_name, description, result, tag, purpose, rolled, signature
+  FROM roll_history;
+
+DROP TABLE roll_history;
+
+ALTER TABLE roll_history_new RENAME TO roll_history;
@@ -0,0 +1,46 @@
+-- A message can hold several commands, such as "roll stealth and perception", each of which is
+-- parsed and logged separately, so each logged message is keyed by the index of the part it was
+-- parsed from as well as the message. Previously only the first part of each message was logged.
+
+CREATE TABLE messages_new (
+  message_id TEXT NOT NULL,
+  part_index INTEGER NOT NULL DEFAULT 0,
+  channel_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  content TEXT NOT NULL,
+  corrected_content TEXT NULL,
+  posted TIMESTAMP NOT NULL,
+  intent_name TEXT NULL,
+  confidence_score REAL NOT NULL,
+  parsed_content TEXT NULL,
+  PRIMARY KEY (message_id, part_index)
+);
+
+INSERT INTO messages_new (message_id, part_index, channel_id, user_id, content, corrected_content, posted, intent_name, confidence_score, parsed_content)
+  SELECT message_id, 0, channel_id, user_id, content, corrected_content, posted, intent_name, confidence_score, parsed_content
+  FROM messages;
+
+CREATE TABLE slots_new (
+  message_id TEXT NOT NULL,
+  part_index INTEGER NOT NULL DEFAULT 0,
+  slot_index TEXT NOT NULL,
+  raw_value TEXT NOT NULL,
+  value TEXT NULL,
+  slot_name TEXT NOT NULL,
+  confidence_score REAL NULL,
+  entity TEXT NULL,
+  PRIMARY KEY (message_id, part_index, slot_index),
+  FOREIGN KEY (message_id, part_index) REFERENCES messages (message_id, part_index)
+);
+
+INSERT INTO slots_new (message_id, part_index, slot_index, raw_value, value, slot_name, confidence_score, entity)
+  SELECT message_id, 0, slot_index, raw_value, value, slot_name, confidence_score, entity
+  FROM slots;
+
+DROP TABLE slots;
+
+DROP TABLE messages;
+
+ALTER TABLE messages_new RENAME TO messages;
+
+ALTER TABLE slots_new RENAME TO slots;
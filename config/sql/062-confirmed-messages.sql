@@ -0,0 +1,5 @@
+-- A message that was parsed with too little confidence to be run is confirmed when the user
+-- answers "yes" to being asked whether they meant the command it was parsed as, so that it can be
+-- exported as training data whatever its confidence.
+
+ALTER TABLE messages ADD COLUMN confirmed BOOLEAN NOT NULL DEFAULT false;
@@ -1,6 +1,15 @@
-use crate::roll::{Condition, ConditionalRoll, Roll};
-use crate::weapon::{Classification, WeaponName};
+use crate::character::FightingStyle;
+use crate::character_effect::EffectRoll;
+use crate::combatant::HitPoints;
+use crate::edition::Edition;
+use crate::roll::{Condition, ConditionalRoll, ConditionalRollResult, Critical, Roll, RollResult};
+use crate::weapon::{Classification, DamageType, Mastery, WeaponName};
+use regex::Regex;
 use std::cmp::max;
+use std::fmt;
+
+/// The most attacks that can be made with a single command.
+pub const MAXIMUM_ATTACKS: usize = 8;
 
 #[derive(Debug)]
 pub enum AttackRoll {
@@ -17,6 +26,7 @@ impl AttackRoll {
         proficiency_bonus: Option<i32>,
         proficiency: bool,
         martial_arts: bool,
+        fighting_style: Option<FightingStyle>,
     ) -> Option<ConditionalRoll> {
         match self {
             AttackRoll::ImprovisedWeapon(roll) => roll.to_attack_roll(strength, dexterity),
@@ -29,6 +39,7 @@ impl AttackRoll {
                 proficiency_bonus,
                 proficiency,
                 martial_arts,
+                fighting_style,
             ),
         }
     }
@@ -39,6 +50,8 @@ impl AttackRoll {
         dexterity: Option<i32>,
         critical_hit: bool,
         martial_arts_damage_die: Option<i32>,
+        fighting_style: Option<FightingStyle>,
+        edition: Edition,
     ) -> Option<Roll> {
         match self {
             AttackRoll::ImprovisedWeapon(roll) => {
@@ -47,9 +60,14 @@ impl AttackRoll {
             AttackRoll::UnarmedStrike(roll) => {
                 roll.to_damage_roll(strength, dexterity, critical_hit, martial_arts_damage_die)
             }
-            AttackRoll::Weapon(roll) => {
-                roll.to_damage_roll(strength, dexterity, critical_hit, martial_arts_damage_die)
-            }
+            AttackRoll::Weapon(roll) => roll.to_damage_roll(
+                strength,
+                dexterity,
+                critical_hit,
+                martial_arts_damage_die,
+                fighting_style,
+                edition,
+            ),
         }
     }
 
@@ -72,8 +90,73 @@ impl AttackRoll {
         }
     }
 
+    /// The type of damage the attack deals, or None for an improvised weapon, whose damage type
+    /// depends on what it is.
+    pub fn get_damage_type(&self) -> Option<DamageType> {
+        match self {
+            AttackRoll::ImprovisedWeapon(_) => None,
+            AttackRoll::UnarmedStrike(_) => Some(DamageType::Bludgeoning),
+            AttackRoll::Weapon(WeaponAttackRoll { weapon, .. }) => weapon.to_weapon().damage_type,
+        }
+    }
+
+    /// Whether the attack deals damage when it hits. A net doesn't, unless it's used as an
+    /// improvised weapon.
+    pub fn deals_damage(&self) -> bool {
+        match self {
+            AttackRoll::Weapon(roll) => {
+                roll.weapon.to_weapon().damage.is_some() || !roll.is_used_correctly()
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether only one attack can be made with the weapon per action, because it's a loading
+    /// weapon or a net.
+    pub fn is_single_attack(&self) -> bool {
+        match self {
+            AttackRoll::Weapon(roll) => roll.weapon.to_weapon().is_single_attack(),
+            _ => false,
+        }
+    }
+
+    /// The rules for the weapon's special property, if it has one.
+    pub fn special(&self) -> Option<&'static str> {
+        match self {
+            AttackRoll::Weapon(roll) => roll.weapon.to_weapon().special.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The same attack roll with advantage, on top of any advantage or disadvantage it already
+    /// has.
+    pub fn with_advantage(mut self) -> AttackRoll {
+        let condition = match &mut self {
+            AttackRoll::ImprovisedWeapon(roll) => &mut roll.condition,
+            AttackRoll::UnarmedStrike(roll) => &mut roll.condition,
+            AttackRoll::Weapon(roll) => &mut roll.condition,
+        };
+        *condition = Condition::add_advantage(*condition);
+        self
+    }
+
+    /// The same attack roll against a target within 5 feet, which has disadvantage with a weapon
+    /// like a lance.
+    pub fn within_5_feet(mut self) -> AttackRoll {
+        if let AttackRoll::Weapon(roll) = &mut self {
+            if roll.weapon.to_weapon().has_disadvantage_within_5_feet() {
+                roll.condition = Condition::add_disadvantage(roll.condition);
+            }
+        }
+        self
+    }
+
     pub fn get_handedness(&self) -> Option<Handedness> {
         match self {
+            AttackRoll::Weapon(WeaponAttackRoll {
+                handedness: Some(Handedness::OffHand),
+                ..
+            }) => Some(Handedness::OffHand),
             AttackRoll::Weapon(WeaponAttackRoll {
                 handedness: Some(handedness),
                 weapon,
@@ -84,6 +167,216 @@ impl AttackRoll {
     }
 }
 
+/// How many attacks to make with a single command.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttackCount {
+    /// A number of attacks given in the command, such as "attack twice".
+    Times(usize),
+    /// As many attacks as the character makes with the Attack action, which depends on their class
+    /// levels.
+    ExtraAttack,
+}
+
+impl AttackCount {
+    /// Parse a number of attacks, such as "twice", "3 times" or "using extra attack".
+    pub fn parse(string: &str) -> Option<AttackCount> {
+        lazy_static! {
+            static ref TIMES_REGEX: Regex = Regex::new(r"^(?i)(\w+) +times$").unwrap();
+            static ref EXTRA_ATTACK_REGEX: Regex =
+                Regex::new(r"^(?i)(?:(?:using|with|use) +(?:my +)?)?extra +attacks?$").unwrap();
+        }
+        let string = string.trim();
+        if EXTRA_ATTACK_REGEX.is_match(string) {
+            return Some(AttackCount::ExtraAttack);
+        }
+        let times = match string.to_lowercase().as_ref() {
+            "once" => Some(1),
+            "twice" => Some(2),
+            "thrice" => Some(3),
+            _ => TIMES_REGEX
+                .captures(string)
+                .and_then(|captures| captures.get(1))
+                .and_then(|m| match m.as_str().to_lowercase().as_ref() {
+                    "one" => Some(1),
+                    "two" => Some(2),
+                    "three" => Some(3),
+                    "four" => Some(4),
+                    count => count.parse().ok(),
+                }),
+        };
+        times.filter(|times| *times > 0).map(AttackCount::Times)
+    }
+
+    /// The number of attacks for a character who makes the given number of attacks with the Attack
+    /// action.
+    pub fn times(self, attacks: usize) -> usize {
+        match self {
+            AttackCount::Times(times) => times,
+            AttackCount::ExtraAttack => attacks,
+        }
+    }
+}
+
+/// The rolls for one of several attacks made with a single command.
+#[derive(Debug)]
+pub struct Attack {
+    pub to_hit_result: ConditionalRollResult,
+    pub damage_roll: Roll,
+    pub damage_result: RollResult,
+    /// The dice rolled for the effects on the character that add to the attack roll.
+    pub effects: Vec<EffectRoll>,
+    /// The radiant damage of a Divine Smite, if it was added to this attack.
+    pub smite: Option<SmiteDamage>,
+    /// Whether the attack hit its target, if it was made against a combatant with an armour
+    /// class.
+    pub hit: Option<bool>,
+}
+
+impl Attack {
+    /// Whether an attack roll, with the total of the effects added to it, hits an armour class. A
+    /// critical hit always hits and a critical failure always misses.
+    pub fn hits(to_hit_result: &ConditionalRollResult, effects: i32, armor_class: i32) -> bool {
+        match to_hit_result.critical() {
+            Some(Critical::Success) => true,
+            Some(Critical::Failure) => false,
+            None => to_hit_result.result() + effects >= armor_class,
+        }
+    }
+}
+
+/// The combatant an attack is made against, and the damage they took from the attacks that hit.
+#[derive(Debug)]
+pub struct AttackTarget {
+    pub name: String,
+    pub armor_class: i32,
+    /// The damage the target took, described along with any resistance. Damage is only dealt to
+    /// a target whose hit points are being tracked.
+    pub damage: Vec<String>,
+    /// The target's hit points before the attacks, if they're being tracked.
+    pub hit_points_before: Option<HitPoints>,
+    /// The target's hit points after the attacks, if they're being tracked.
+    pub hit_points: Option<HitPoints>,
+}
+
+/// Cover that the target of an attack is behind, which adds to their armour class against the
+/// attack. A target behind total cover can't be targeted at all.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Cover {
+    Half,
+    ThreeQuarters,
+}
+
+impl Cover {
+    pub fn parse(string: &str) -> Option<Cover> {
+        match string.to_lowercase().replace('-', " ").as_str() {
+            "half" => Some(Cover::Half),
+            "three quarters" | "three quarter" | "3/4" => Some(Cover::ThreeQuarters),
+            _ => None,
+        }
+    }
+
+    pub fn armor_class_bonus(self) -> i32 {
+        match self {
+            Cover::Half => 2,
+            Cover::ThreeQuarters => 5,
+        }
+    }
+}
+
+impl fmt::Display for Cover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cover::Half => write!(f, "half cover"),
+            Cover::ThreeQuarters => write!(f, "three-quarters cover"),
+        }
+    }
+}
+
+/// A Divine Smite added to an attack, which expends a spell slot to deal extra radiant damage.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Smite {
+    /// The level of the spell slot expended.
+    pub level: i32,
+    /// Whether the target is an undead or a fiend, which takes an extra d8.
+    pub undead_or_fiend: bool,
+}
+
+impl Smite {
+    /// Parse the end of an attack that smites, such as "at 2nd level" or "using a 3rd level spell
+    /// slot against a fiend". A smite without a level expends a 1st level spell slot.
+    pub fn parse(string: &str) -> Option<Smite> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^(?i)(?:(?:at|using|with|use) +(?:an? +)?(?:(\d)(?:st|nd|rd|th)[ -]+level|level +(\d))(?: +(?:spell +)?slot)?)? *(?:(?:against|versus|vs\.?|on) +(?:an +|a +|the +)?(undead|fiends?))?$"
+            )
+            .unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        let level = match captures.get(1).or_else(|| captures.get(2)) {
+            Some(m) => m.as_str().parse().ok()?,
+            None => 1,
+        };
+        if level < 1 || level > 9 {
+            return None;
+        }
+        Some(Smite {
+            level,
+            undead_or_fiend: captures.get(3).is_some(),
+        })
+    }
+
+    /// The radiant damage of the smite: 2d8 for a 1st level spell slot and 1d8 for each level
+    /// above that, plus 1d8 against an undead or a fiend. The 2014 rules allow at most 5d8 before
+    /// the extra die.
+    pub fn to_damage_roll(self, critical_hit: bool, edition: Edition) -> Roll {
+        let dice = match edition {
+            Edition::Rules2014 => (self.level + 1).min(5),
+            Edition::Rules2024 => self.level + 1,
+        };
+        let dice = if self.undead_or_fiend { dice + 1 } else { dice };
+        Roll::new_unsafe(dice as usize * critical_hit_multiplier(critical_hit), 8, 0)
+    }
+}
+
+/// The rolled damage of a Divine Smite.
+#[derive(Debug)]
+pub struct SmiteDamage {
+    pub level: i32,
+    pub roll: Roll,
+    pub result: RollResult,
+}
+
+/// The mastery property of the weapon used for an attack, with the numbers it needs worked out
+/// where they're known.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MasteryEffect {
+    /// The damage the target takes even if the attack misses, and its type.
+    Graze(i32, DamageType),
+    /// The DC of the Constitution saving throw the target makes to avoid falling prone.
+    Topple(i32),
+    Other(Mastery),
+}
+
+impl fmt::Display for MasteryEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MasteryEffect::Graze(damage, damage_type) => write!(
+                f,
+                "**Graze**: If it misses, the target takes {} {} damage anyway.",
+                damage, damage_type
+            ),
+            MasteryEffect::Topple(dc) => write!(
+                f,
+                "**Topple**: If it hits, the target makes a DC {} Constitution saving throw or falls prone.",
+                dc
+            ),
+            MasteryEffect::Other(mastery) => {
+                write!(f, "**{}**: {}", mastery, mastery.description())
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ImprovisedWeaponAttackRoll {
     pub classification: Classification,
@@ -188,6 +481,7 @@ impl WeaponAttackRoll {
         proficiency_bonus: Option<i32>,
         proficiency: bool,
         martial_arts: bool,
+        fighting_style: Option<FightingStyle>,
     ) -> Option<ConditionalRoll> {
         let modifier = self.attack_modifier(
             strength,
@@ -195,6 +489,7 @@ impl WeaponAttackRoll {
             proficiency_bonus,
             proficiency,
             martial_arts,
+            fighting_style,
         );
         Some(ConditionalRoll::new_unsafe(
             1,
@@ -210,16 +505,19 @@ impl WeaponAttackRoll {
         dexterity: Option<i32>,
         critical_hit: bool,
         martial_arts_damage_die: Option<i32>,
+        fighting_style: Option<FightingStyle>,
+        edition: Edition,
     ) -> Option<Roll> {
         let weapon = self.weapon.to_weapon();
-        let used_with_correct_classification = self.classification.iter().all(|c| {
-            *c == weapon.classification || (*c == Classification::Ranged && weapon.thrown)
-        });
-        let roll = if used_with_correct_classification {
-            let base = weapon
-                .versatile
-                .filter(|_| self.handedness == Some(Handedness::TwoHanded))
-                .unwrap_or(weapon.damage);
+        let roll = if self.is_used_correctly() {
+            let base = match weapon.damage {
+                Some(damage) => weapon
+                    .versatile
+                    .filter(|_| self.handedness == Some(Handedness::TwoHanded))
+                    .unwrap_or(damage),
+                // A weapon that deals no damage doesn't add anything to it either
+                None => return Some(Roll::new_unsafe(0, 1, 0)),
+            };
             martial_arts_damage_die
                 .filter(|_| weapon.is_monk_weapon())
                 .map(|sides| {
@@ -229,10 +527,81 @@ impl WeaponAttackRoll {
         } else {
             Roll::new_clamped(1, 4, 0)
         };
+        let two_handed = weapon.two_handed
+            || (weapon.versatile.is_some() && self.handedness == Some(Handedness::TwoHanded));
+        let roll = if fighting_style == Some(FightingStyle::GreatWeaponFighting)
+            && self.is_used_as(Classification::Melee)
+            && two_handed
+        {
+            edition.great_weapon_fighting(roll)
+        } else {
+            roll
+        };
 
         let multiplier = critical_hit_multiplier(critical_hit);
-        let modifier = self.damage_modifier(strength, dexterity, martial_arts_damage_die.is_some());
-        Some(roll.multiply_rolls(multiplier).add_modifier(modifier?))
+        let modifier =
+            self.damage_modifier(strength, dexterity, martial_arts_damage_die.is_some())?;
+        // An off-hand attack doesn't add a positive ability modifier to its damage, unless the
+        // character has the Two-Weapon Fighting style
+        let modifier = if self.handedness == Some(Handedness::OffHand)
+            && fighting_style != Some(FightingStyle::TwoWeaponFighting)
+        {
+            modifier.min(0)
+        } else {
+            modifier
+        };
+        // The Dueling style can't tell whether the character is holding another weapon, so it
+        // applies to any melee weapon attack made with one hand that isn't an off-hand attack
+        let modifier = if fighting_style == Some(FightingStyle::Dueling)
+            && self.is_used_as(Classification::Melee)
+            && !two_handed
+            && self.handedness != Some(Handedness::OffHand)
+        {
+            modifier + 2
+        } else {
+            modifier
+        };
+        Some(roll.multiply_rolls(multiplier).add_modifier(modifier))
+    }
+
+    /// The effect of the weapon's mastery property, for a character who has mastered it.
+    pub fn to_mastery_effect(
+        &self,
+        strength: Option<i32>,
+        dexterity: Option<i32>,
+        proficiency_bonus: Option<i32>,
+        martial_arts: bool,
+    ) -> Option<MasteryEffect> {
+        let weapon = self.weapon.to_weapon();
+        let modifier = self.damage_modifier(strength, dexterity, martial_arts);
+        weapon.mastery.map(|mastery| {
+            match (mastery, modifier, proficiency_bonus, weapon.damage_type) {
+                (Mastery::Graze, Some(modifier), _, Some(damage_type)) => {
+                    MasteryEffect::Graze(modifier.max(0), damage_type)
+                }
+                (Mastery::Topple, Some(modifier), Some(proficiency_bonus), _) => {
+                    MasteryEffect::Topple(8 + modifier + proficiency_bonus)
+                }
+                (mastery, _, _, _) => MasteryEffect::Other(mastery),
+            }
+        })
+    }
+
+    /// Whether the weapon is being used as the kind of weapon it is, or thrown if it's a thrown
+    /// weapon, rather than as an improvised weapon.
+    fn is_used_correctly(&self) -> bool {
+        let weapon = self.weapon.to_weapon();
+        self.classification
+            .iter()
+            .all(|c| *c == weapon.classification || (*c == Classification::Ranged && weapon.thrown))
+    }
+
+    /// Whether the weapon is of the given classification and is being used as one, rather than
+    /// being thrown or used as an improvised weapon.
+    fn is_used_as(&self, classification: Classification) -> bool {
+        let weapon = self.weapon.to_weapon();
+        weapon.classification == classification
+            && self.classification.unwrap_or(weapon.classification) == classification
     }
 
     fn attack_modifier(
@@ -242,6 +611,7 @@ impl WeaponAttackRoll {
         proficiency_bonus: Option<i32>,
         proficiency: bool,
         martial_arts: bool,
+        fighting_style: Option<FightingStyle>,
     ) -> Option<i32> {
         let weapon = self.weapon.to_weapon();
         let proficiency_bonus =
@@ -266,7 +636,12 @@ impl WeaponAttackRoll {
             // Use a melee weapon as a ranged weapon (counts as improvised)
             (Classification::Ranged, Classification::Melee, false, _, _) => dexterity?,
         };
-        Some(modifier)
+        if fighting_style == Some(FightingStyle::Archery) && self.is_used_as(Classification::Ranged)
+        {
+            Some(modifier + 2)
+        } else {
+            Some(modifier)
+        }
     }
 
     fn damage_modifier(
@@ -311,6 +686,9 @@ fn critical_hit_multiplier(critical_hit: bool) -> usize {
 pub enum Handedness {
     OneHanded,
     TwoHanded,
+    /// A one-handed attack with a light weapon held in the other hand, as the bonus action attack
+    /// for two-weapon fighting.
+    OffHand,
 }
 
 impl Handedness {
@@ -318,15 +696,165 @@ impl Handedness {
         match string.to_lowercase().as_str() {
             "one handed" => Some(Handedness::OneHanded),
             "two handed" => Some(Handedness::TwoHanded),
+            "off hand" => Some(Handedness::OffHand),
+            _ => None,
+        }
+    }
+}
+
+/// Which rolls of an attack to make. Many tables resolve whether an attack hits before its damage
+/// is rolled, so the attack roll and the damage roll can be made separately.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttackPart {
+    /// The attack roll and the damage roll together.
+    Both,
+    ToHit,
+    /// The damage roll, with the damage dice doubled for a critical hit.
+    Damage {
+        critical_hit: bool,
+    },
+}
+
+impl AttackPart {
+    pub fn rolls_to_hit(self) -> bool {
+        match self {
+            AttackPart::Damage { .. } => false,
+            _ => true,
+        }
+    }
+
+    pub fn rolls_damage(self) -> bool {
+        self != AttackPart::ToHit
+    }
+}
+
+/// How far away the target of a ranged attack is. An attack at long range has disadvantage.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttackRange {
+    Normal,
+    Long,
+}
+
+impl AttackRange {
+    pub fn parse(string: &str) -> Option<AttackRange> {
+        match string.to_lowercase().as_str() {
+            "normal range" => Some(AttackRange::Normal),
+            "long range" => Some(AttackRange::Long),
             _ => None,
         }
     }
+
+    /// The condition of an attack at this range, given the condition it would otherwise have.
+    pub fn apply(self, condition: Option<Condition>) -> Option<Condition> {
+        match self {
+            AttackRange::Normal => condition,
+            AttackRange::Long => Condition::add_disadvantage(condition),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_cover() {
+        assert_eq!(Cover::parse("Half"), Some(Cover::Half));
+        assert_eq!(Cover::parse("three-quarters"), Some(Cover::ThreeQuarters));
+        assert_eq!(Cover::parse("3/4"), Some(Cover::ThreeQuarters));
+        assert_eq!(Cover::parse("total"), None);
+        assert_eq!(Cover::ThreeQuarters.armor_class_bonus(), 5);
+        assert_eq!(Cover::Half.to_string(), "half cover");
+    }
+
+    #[test]
+    fn test_attack_range() {
+        assert_eq!(AttackRange::parse("Long range"), Some(AttackRange::Long));
+        assert_eq!(
+            AttackRange::parse("normal range"),
+            Some(AttackRange::Normal)
+        );
+        assert_eq!(AttackRange::parse("melee range"), None);
+        assert_eq!(AttackRange::Long.apply(None), Some(Condition::Disadvantage));
+        assert_eq!(AttackRange::Long.apply(Some(Condition::Advantage)), None);
+        assert_eq!(
+            AttackRange::Normal.apply(Some(Condition::Advantage)),
+            Some(Condition::Advantage)
+        );
+    }
+
+    #[test]
+    fn test_parse_attack_count() {
+        assert_eq!(AttackCount::parse("twice"), Some(AttackCount::Times(2)));
+        assert_eq!(AttackCount::parse("3 times"), Some(AttackCount::Times(3)));
+        assert_eq!(
+            AttackCount::parse("Four times"),
+            Some(AttackCount::Times(4))
+        );
+        assert_eq!(
+            AttackCount::parse("using extra attack"),
+            Some(AttackCount::ExtraAttack)
+        );
+        assert_eq!(
+            AttackCount::parse("with my extra attacks"),
+            Some(AttackCount::ExtraAttack)
+        );
+        assert_eq!(AttackCount::parse("0 times"), None);
+        assert_eq!(AttackCount::parse("sometimes"), None);
+        assert_eq!(AttackCount::ExtraAttack.times(3), 3);
+        assert_eq!(AttackCount::Times(2).times(3), 2);
+    }
+
+    #[test]
+    fn test_parse_smite() {
+        let smite = |level, undead_or_fiend| {
+            Some(Smite {
+                level,
+                undead_or_fiend,
+            })
+        };
+        assert_eq!(Smite::parse(""), smite(1, false));
+        assert_eq!(Smite::parse("at 2nd level"), smite(2, false));
+        assert_eq!(
+            Smite::parse("using a 3rd level spell slot against a fiend"),
+            smite(3, true)
+        );
+        assert_eq!(Smite::parse("at level 4 vs undead"), smite(4, true));
+        assert_eq!(Smite::parse("at 0th level"), None);
+        assert_eq!(Smite::parse("the goblin"), None);
+    }
+
+    #[test]
+    fn test_smite_damage_roll() {
+        let roll = |level, undead_or_fiend, critical_hit, edition| {
+            Smite {
+                level,
+                undead_or_fiend,
+            }
+            .to_damage_roll(critical_hit, edition)
+        };
+        assert_eq!(
+            roll(1, false, false, Edition::Rules2014),
+            Roll::new_unsafe(2, 8, 0)
+        );
+        assert_eq!(
+            roll(2, true, false, Edition::Rules2014),
+            Roll::new_unsafe(4, 8, 0)
+        );
+        assert_eq!(
+            roll(3, false, true, Edition::Rules2014),
+            Roll::new_unsafe(8, 8, 0)
+        );
+        assert_eq!(
+            roll(5, true, false, Edition::Rules2014),
+            Roll::new_unsafe(6, 8, 0)
+        );
+        assert_eq!(
+            roll(5, false, false, Edition::Rules2024),
+            Roll::new_unsafe(6, 8, 0)
+        );
+    }
+
     #[test]
     fn test_improvised_melee_weapon_roll() {
         let roll = ImprovisedWeaponAttackRoll {
@@ -797,8 +1325,16 @@ mod test {
             Some(proficiency_bonus),
             false,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -817,7 +1353,14 @@ mod test {
 
         let expected_damage = Some(Roll::new_unsafe(4, 6, 2));
 
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), true, None);
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            true,
+            None,
+            None,
+            Edition::Rules2014,
+        );
 
         assert_eq!(actual_damage, expected_damage);
     }
@@ -848,8 +1391,16 @@ mod test {
             Some(proficiency_bonus),
             false,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -881,8 +1432,16 @@ mod test {
             Some(proficiency_bonus),
             false,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -909,8 +1468,16 @@ mod test {
             Some(proficiency_bonus),
             false,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -937,8 +1504,16 @@ mod test {
             Some(proficiency_bonus),
             false,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -965,8 +1540,16 @@ mod test {
             Some(proficiency_bonus),
             false,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -993,8 +1576,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             true,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            Some(8),
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, Some(8));
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1021,8 +1612,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             true,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            Some(8),
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, Some(8));
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1049,8 +1648,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             true,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            Some(4),
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, Some(4));
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1077,8 +1684,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             true,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            Some(4),
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, Some(4));
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1105,8 +1720,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             true,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            Some(8),
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, Some(8));
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1133,8 +1756,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1161,8 +1792,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1189,8 +1828,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             true,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            Some(4),
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, Some(4));
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1217,8 +1864,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1245,8 +1900,16 @@ mod test {
             Some(proficiency_bonus),
             true,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
@@ -1273,10 +1936,328 @@ mod test {
             Some(proficiency_bonus),
             true,
             false,
+            None,
+        );
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
         );
-        let actual_damage = roll.to_damage_roll(Some(strength), Some(dexterity), false, None);
 
         assert_eq!(actual_attack, expected_attack);
         assert_eq!(actual_damage, expected_damage);
     }
+
+    #[test]
+    fn test_weapon_roll_off_hand() {
+        let roll = WeaponAttackRoll {
+            weapon: WeaponName::Dagger,
+            classification: None,
+            condition: None,
+            handedness: Some(Handedness::OffHand),
+        };
+        let strength = 2;
+        let dexterity = 3;
+
+        let expected_damage = Some(Roll::new_unsafe(1, 4, 0));
+        let expected_two_weapon_fighting_damage = Some(Roll::new_unsafe(1, 4, 3));
+
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
+        );
+        let actual_two_weapon_fighting_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            Some(FightingStyle::TwoWeaponFighting),
+            Edition::Rules2014,
+        );
+
+        assert_eq!(actual_damage, expected_damage);
+        assert_eq!(
+            actual_two_weapon_fighting_damage,
+            expected_two_weapon_fighting_damage
+        );
+    }
+
+    #[test]
+    fn test_weapon_roll_off_hand_negative_modifier() {
+        let roll = WeaponAttackRoll {
+            weapon: WeaponName::Handaxe,
+            classification: None,
+            condition: None,
+            handedness: Some(Handedness::OffHand),
+        };
+        let strength = -1;
+        let dexterity = 0;
+
+        let expected_damage = Some(Roll::new_unsafe(1, 6, -1));
+
+        let actual_damage = roll.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            None,
+            Edition::Rules2014,
+        );
+
+        assert_eq!(actual_damage, expected_damage);
+    }
+
+    #[test]
+    fn test_weapon_roll_archery() {
+        let longbow = WeaponAttackRoll {
+            weapon: WeaponName::Longbow,
+            classification: None,
+            condition: None,
+            handedness: None,
+        };
+        let dagger = WeaponAttackRoll {
+            weapon: WeaponName::Dagger,
+            classification: Some(Classification::Ranged),
+            condition: None,
+            handedness: None,
+        };
+        let strength = 0;
+        let dexterity = 3;
+        let proficiency_bonus = 2;
+        let archery = Some(FightingStyle::Archery);
+
+        let expected_longbow_attack = Some(ConditionalRoll::new_unsafe(1, 20, 7, None));
+        let expected_longbow_damage = Some(Roll::new_unsafe(1, 8, 3));
+        let expected_dagger_attack = Some(ConditionalRoll::new_unsafe(1, 20, 5, None));
+
+        let actual_longbow_attack = longbow.to_attack_roll(
+            Some(strength),
+            Some(dexterity),
+            Some(proficiency_bonus),
+            true,
+            false,
+            archery,
+        );
+        let actual_longbow_damage = longbow.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            archery,
+            Edition::Rules2014,
+        );
+        let actual_dagger_attack = dagger.to_attack_roll(
+            Some(strength),
+            Some(dexterity),
+            Some(proficiency_bonus),
+            true,
+            false,
+            archery,
+        );
+
+        assert_eq!(actual_longbow_attack, expected_longbow_attack);
+        assert_eq!(actual_longbow_damage, expected_longbow_damage);
+        assert_eq!(actual_dagger_attack, expected_dagger_attack);
+    }
+
+    #[test]
+    fn test_weapon_roll_dueling() {
+        let one_handed = WeaponAttackRoll {
+            weapon: WeaponName::Longsword,
+            classification: None,
+            condition: None,
+            handedness: Some(Handedness::OneHanded),
+        };
+        let two_handed = WeaponAttackRoll {
+            handedness: Some(Handedness::TwoHanded),
+            ..one_handed
+        };
+        let strength = 3;
+        let dexterity = 0;
+        let dueling = Some(FightingStyle::Dueling);
+
+        let expected_one_handed_damage = Some(Roll::new_unsafe(1, 8, 5));
+        let expected_two_handed_damage = Some(Roll::new_unsafe(1, 10, 3));
+
+        let actual_one_handed_damage = one_handed.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            dueling,
+            Edition::Rules2014,
+        );
+        let actual_two_handed_damage = two_handed.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            dueling,
+            Edition::Rules2014,
+        );
+
+        assert_eq!(actual_one_handed_damage, expected_one_handed_damage);
+        assert_eq!(actual_two_handed_damage, expected_two_handed_damage);
+    }
+
+    #[test]
+    fn test_weapon_roll_great_weapon_fighting() {
+        let greatsword = WeaponAttackRoll {
+            weapon: WeaponName::Greatsword,
+            classification: None,
+            condition: None,
+            handedness: None,
+        };
+        let longsword = WeaponAttackRoll {
+            weapon: WeaponName::Longsword,
+            classification: None,
+            condition: None,
+            handedness: Some(Handedness::OneHanded),
+        };
+        let strength = 3;
+        let dexterity = 0;
+        let great_weapon_fighting = Some(FightingStyle::GreatWeaponFighting);
+
+        let expected_greatsword_damage = Some(Roll::new_unsafe(4, 6, 3).with_reroll(2));
+        let expected_longsword_damage = Some(Roll::new_unsafe(1, 8, 3));
+
+        let actual_greatsword_damage = greatsword.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            true,
+            None,
+            great_weapon_fighting,
+            Edition::Rules2014,
+        );
+        let actual_longsword_damage = longsword.to_damage_roll(
+            Some(strength),
+            Some(dexterity),
+            false,
+            None,
+            great_weapon_fighting,
+            Edition::Rules2014,
+        );
+
+        assert_eq!(actual_greatsword_damage, expected_greatsword_damage);
+        assert_eq!(actual_longsword_damage, expected_longsword_damage);
+        assert_eq!(
+            greatsword.to_damage_roll(
+                Some(strength),
+                Some(dexterity),
+                false,
+                None,
+                great_weapon_fighting,
+                Edition::Rules2024
+            ),
+            Some(Roll::new_unsafe(2, 6, 3).with_minimum(3))
+        );
+    }
+
+    #[test]
+    fn test_weapon_mastery_effect() {
+        let weapon = |weapon| WeaponAttackRoll {
+            weapon,
+            classification: None,
+            condition: None,
+            handedness: None,
+        };
+
+        assert_eq!(
+            weapon(WeaponName::Greatsword).to_mastery_effect(Some(3), Some(1), Some(2), false),
+            Some(MasteryEffect::Graze(3, DamageType::Slashing))
+        );
+        assert_eq!(
+            weapon(WeaponName::Greatsword).to_mastery_effect(Some(-1), Some(1), Some(2), false),
+            Some(MasteryEffect::Graze(0, DamageType::Slashing))
+        );
+        assert_eq!(
+            weapon(WeaponName::Maul).to_mastery_effect(Some(4), Some(0), Some(3), false),
+            Some(MasteryEffect::Topple(15))
+        );
+        assert_eq!(
+            weapon(WeaponName::Maul).to_mastery_effect(Some(4), Some(0), None, false),
+            Some(MasteryEffect::Other(Mastery::Topple))
+        );
+        assert_eq!(
+            weapon(WeaponName::Rapier).to_mastery_effect(Some(0), Some(4), Some(2), false),
+            Some(MasteryEffect::Other(Mastery::Vex))
+        );
+    }
+
+    #[test]
+    fn test_damage_type() {
+        let rapier = AttackRoll::Weapon(WeaponAttackRoll {
+            weapon: WeaponName::Rapier,
+            classification: None,
+            condition: None,
+            handedness: None,
+        });
+        let unarmed_strike = AttackRoll::UnarmedStrike(UnarmedStrikeAttackRoll { condition: None });
+        let improvised_weapon = AttackRoll::ImprovisedWeapon(ImprovisedWeaponAttackRoll {
+            classification: Classification::Melee,
+            condition: None,
+        });
+
+        assert_eq!(rapier.get_damage_type(), Some(DamageType::Piercing));
+        assert_eq!(
+            unarmed_strike.get_damage_type(),
+            Some(DamageType::Bludgeoning)
+        );
+        assert_eq!(improvised_weapon.get_damage_type(), None);
+    }
+
+    #[test]
+    fn test_weapon_roll_net() {
+        let net = |classification| WeaponAttackRoll {
+            weapon: WeaponName::Net,
+            classification,
+            condition: None,
+            handedness: None,
+        };
+        let damage = |roll: &WeaponAttackRoll| {
+            roll.to_damage_roll(Some(3), Some(2), false, None, None, Edition::Rules2014)
+        };
+
+        assert_eq!(damage(&net(None)), Some(Roll::new_unsafe(0, 1, 0)));
+        assert_eq!(AttackRoll::Weapon(net(None)).deals_damage(), false);
+        assert_eq!(AttackRoll::Weapon(net(None)).is_single_attack(), true);
+        assert_eq!(AttackRoll::Weapon(net(None)).get_damage_type(), None);
+        // Used as an improvised melee weapon
+        let melee = net(Some(Classification::Melee));
+        assert_eq!(damage(&melee), Some(Roll::new_unsafe(1, 4, 3)));
+        assert_eq!(AttackRoll::Weapon(melee).deals_damage(), true);
+    }
+
+    #[test]
+    fn test_within_5_feet() {
+        let attack = |weapon, condition| {
+            AttackRoll::Weapon(WeaponAttackRoll {
+                weapon,
+                classification: None,
+                condition,
+                handedness: None,
+            })
+        };
+        let condition = |roll: AttackRoll| match roll.within_5_feet() {
+            AttackRoll::Weapon(roll) => roll.condition,
+            _ => None,
+        };
+
+        assert_eq!(
+            condition(attack(WeaponName::Lance, None)),
+            Some(Condition::Disadvantage)
+        );
+        assert_eq!(
+            condition(attack(WeaponName::Lance, Some(Condition::Advantage))),
+            None
+        );
+        assert_eq!(condition(attack(WeaponName::Longsword, None)), None);
+    }
 }
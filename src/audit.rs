@@ -0,0 +1,109 @@
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serenity::model::id::{ChannelId, UserId};
+
+/// The number of changes shown when reviewing the audit history.
+pub const RECENT_CHANGES: i64 = 20;
+
+/// A change to one of a character's attributes, such as their Dexterity going from 14 to 16.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Change {
+    pub attribute: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// A change recorded in the audit history, kept so that the DM can see who changed a character
+/// and when.
+#[derive(Debug, Eq, PartialEq)]
+pub struct AuditEntry {
+    /// The owner of the character.
+    pub user_id: UserId,
+    pub name: String,
+    pub change: Change,
+    /// The user who made the change.
+    pub changed_by: UserId,
+    /// When the change was made, in UTC, such as "2020-06-01 19:30:00".
+    pub changed: String,
+}
+
+impl Change {
+    pub fn new(attribute: &str, old_value: Option<String>, new_value: Option<String>) -> Change {
+        Change {
+            attribute: attribute.to_owned(),
+            old_value,
+            new_value,
+        }
+    }
+}
+
+/// Record a change to one of a user's characters in the audit history. Changes that leave the
+/// attribute as it was aren't recorded.
+pub fn record(
+    connection: &Connection,
+    channel_id: ChannelId,
+    user_id: UserId,
+    name: &str,
+    changed_by: UserId,
+    change: &Change,
+) -> RusqliteResult<usize> {
+    if change.old_value == change.new_value {
+        return Ok(0);
+    }
+    let params: &[&dyn ToSql] = &[
+        &channel_id.to_string(),
+        &user_id.to_string(),
+        &name,
+        &change.attribute,
+        &change.old_value,
+        &change.new_value,
+        &changed_by.to_string(),
+    ];
+    connection.execute(
+        "INSERT INTO character_audit (channel_id, user_id, name, attribute, old_value, new_value, changed_by) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        params,
+    )
+}
+
+/// Retrieve the most recent changes to the characters in a channel, or to one user's characters,
+/// newest first.
+pub fn get_recent(
+    connection: &Connection,
+    channel_id: ChannelId,
+    user_id: Option<UserId>,
+    limit: i64,
+) -> RusqliteResult<Vec<AuditEntry>> {
+    let params: &[&dyn ToSql] = &[
+        &channel_id.to_string(),
+        &user_id.map(|user_id| user_id.to_string()),
+        &limit,
+    ];
+    let mut statement = connection.prepare(
+        "SELECT user_id, name, attribute, old_value, new_value, changed_by, changed \
+         FROM character_audit \
+         WHERE channel_id = $1 \
+         AND ($2 IS NULL OR user_id = $2) \
+         ORDER BY changed DESC, rowid DESC \
+         LIMIT $3",
+    )?;
+    let rows = statement.query_map(params, from_row)?;
+    rows.collect()
+}
+
+fn from_row(row: &Row) -> RusqliteResult<AuditEntry> {
+    let user_id: String = row.get("user_id")?;
+    let changed_by: String = row.get("changed_by")?;
+    Ok(AuditEntry {
+        user_id: UserId(user_id.parse().unwrap_or_default()),
+        name: row.get("name")?,
+        change: Change {
+            attribute: row.get("attribute")?,
+            old_value: row.get("old_value")?,
+            new_value: row.get("new_value")?,
+        },
+        changed_by: UserId(changed_by.parse().unwrap_or_default()),
+        changed: row.get("changed")?,
+    })
+}
@@ -0,0 +1,329 @@
+use crate::content;
+use crate::monster::{self, ChallengeRating, Monster};
+use crate::roll::Roll;
+use crate::weapon::DamageType;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The name of the monster stat block table, which is embedded from `src/data/`, and can be
+/// overridden by a file of the same name in the content directory.
+const MONSTERS_FILE_NAME: &str = "monsters.json";
+
+/// The abbreviations of the abilities, in the order they're listed in a stat block.
+pub const ABILITY_ABBREVIATIONS: [&str; 6] = ["STR", "DEX", "CON", "INT", "WIS", "CHA"];
+
+lazy_static! {
+    static ref STAT_BLOCKS: Result<HashMap<String, StatBlock>, content::Error> = load_stat_blocks();
+}
+
+/// A monster's stat block from the SRD, such as a goblin's.
+#[derive(Debug)]
+pub struct StatBlock {
+    pub name: String,
+    /// The monster's size and type, such as "Small humanoid (goblinoid)".
+    pub kind: String,
+    pub armor_class: i32,
+    pub hit_points: i32,
+    pub hit_dice: Roll,
+    /// The monster's speeds, such as "30 ft., fly 60 ft.".
+    pub speed: String,
+    /// The monster's ability scores, in the order of `ABILITY_ABBREVIATIONS`.
+    pub abilities: [i32; 6],
+    pub challenge_rating: ChallengeRating,
+    pub actions: Vec<Action>,
+}
+
+/// One of the actions in a monster's stat block, which is either an attack, such as "Scimitar: +4
+/// to hit, 1d6+2 slashing damage", or described in words, such as a Multiattack.
+#[derive(Debug)]
+pub struct Action {
+    pub name: String,
+    pub attack: Option<ActionAttack>,
+    /// What the action does, or what else happens when an attack hits.
+    pub description: Option<String>,
+}
+
+/// The attack bonus and damage of an action that's an attack.
+#[derive(Clone, Copy, Debug)]
+pub struct ActionAttack {
+    pub attack_bonus: i32,
+    pub damage: Roll,
+    pub damage_type: DamageType,
+}
+
+impl StatBlock {
+    /// Find a stat block by name, ignoring case, where the name may be plural, such as "goblins".
+    pub fn find(name: &str) -> Option<&'static StatBlock> {
+        let stat_blocks = STAT_BLOCKS.as_ref().ok()?;
+        monster::singular_forms(name)
+            .iter()
+            .find_map(|singular| stat_blocks.get(singular))
+    }
+
+    /// Find the stat block for a combatant, whose name may have a label to tell them apart from
+    /// other monsters of the same kind, such as "Goblin B" or "Zombie 2".
+    pub fn find_combatant(name: &str) -> Option<&'static StatBlock> {
+        lazy_static! {
+            static ref LABEL_REGEX: Regex = Regex::new(r"^(.+?) +(?:[A-Za-z]|\d+)$").unwrap();
+        }
+        StatBlock::find(name).or_else(|| {
+            LABEL_REGEX
+                .captures(name.trim())
+                .and_then(|captures| captures.get(1))
+                .and_then(|m| StatBlock::find(m.as_str()))
+        })
+    }
+
+    /// The modifier for one of the monster's ability scores, in the order of
+    /// `ABILITY_ABBREVIATIONS`.
+    pub fn modifier(&self, index: usize) -> i32 {
+        self.abilities[index] / 2 - 5
+    }
+
+    /// The monster as a saved monster with a single attack, which is the first attack in its
+    /// stat block, so that it can attack in the same way. Returns None if it has no attacks.
+    pub fn to_monster(&self) -> Option<Monster> {
+        self.to_monster_attacking_with(None)
+    }
+
+    /// The monster as a saved monster that attacks with one of the attacks in its stat block,
+    /// such as "claws", or with its first attack if none is given. A trailing "s" is ignored, so
+    /// "claw" finds "Claws". Returns None if it has no such attack.
+    pub fn to_monster_attacking_with(&self, attack_name: Option<&str>) -> Option<Monster> {
+        let singular = |name: &str| name.trim().to_lowercase().trim_end_matches('s').to_owned();
+        let attack_name = attack_name.map(singular);
+        self.actions
+            .iter()
+            .filter(|action| {
+                attack_name.as_ref().map_or(true, |attack_name| {
+                    singular(action.name.as_str()) == *attack_name
+                })
+            })
+            .find_map(|action| {
+                action.attack.map(|attack| Monster {
+                    name: self.name.clone(),
+                    armor_class: self.armor_class,
+                    hit_points: self.hit_points,
+                    attack_bonus: attack.attack_bonus,
+                    damage: attack.damage,
+                    attack_name: Some(action.name.to_lowercase()),
+                    challenge_rating: self.challenge_rating,
+                })
+            })
+    }
+}
+
+/// An entry in the monster stat block table, which is validated when it's turned into a stat
+/// block.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StatBlockEntry {
+    name: String,
+    kind: String,
+    armor_class: i32,
+    hit_points: i32,
+    hit_dice: String,
+    speed: String,
+    abilities: [i32; 6],
+    challenge_rating: String,
+    actions: Vec<ActionEntry>,
+}
+
+/// An action in a stat block entry. An attack has an attack bonus, damage and damage type, and an
+/// action that isn't an attack needs a description.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ActionEntry {
+    name: String,
+    attack_bonus: Option<i32>,
+    damage: Option<String>,
+    damage_type: Option<String>,
+    description: Option<String>,
+}
+
+impl StatBlockEntry {
+    /// Validate the entry, returning the stat block it describes, or a message explaining what's
+    /// wrong with it.
+    fn to_stat_block(&self) -> Result<StatBlock, String> {
+        let invalid =
+            |field: &str, value: &str| format!("{}: invalid {} \"{}\"", self.name, field, value);
+        if self.armor_class < 1 || self.armor_class > 30 {
+            return Err(invalid("armor class", &self.armor_class.to_string()));
+        }
+        if self.hit_points < 1 {
+            return Err(invalid("hit points", &self.hit_points.to_string()));
+        }
+        if let Some(score) = self
+            .abilities
+            .iter()
+            .find(|score| **score < 1 || **score > 30)
+        {
+            return Err(invalid("ability score", &score.to_string()));
+        }
+        let actions = self
+            .actions
+            .iter()
+            .map(|action| action.to_action(&self.name))
+            .collect::<Result<Vec<Action>, String>>()?;
+        Ok(StatBlock {
+            name: self.name.clone(),
+            kind: self.kind.clone(),
+            armor_class: self.armor_class,
+            hit_points: self.hit_points,
+            hit_dice: Roll::parse(&self.hit_dice)
+                .map_err(|_| invalid("hit dice", &self.hit_dice))?,
+            speed: self.speed.clone(),
+            abilities: self.abilities,
+            challenge_rating: ChallengeRating::parse(&self.challenge_rating)
+                .ok_or_else(|| invalid("challenge rating", &self.challenge_rating))?,
+            actions,
+        })
+    }
+}
+
+impl ActionEntry {
+    fn to_action(&self, monster: &str) -> Result<Action, String> {
+        let invalid = |field: &str, value: &str| {
+            format!(
+                "{}: {}: invalid {} \"{}\"",
+                monster, self.name, field, value
+            )
+        };
+        let attack = match (self.attack_bonus, &self.damage, &self.damage_type) {
+            (Some(attack_bonus), Some(damage), Some(damage_type)) => Some(ActionAttack {
+                attack_bonus,
+                damage: Roll::parse(damage).map_err(|_| invalid("damage", damage))?,
+                damage_type: DamageType::parse(damage_type)
+                    .ok_or_else(|| invalid("damage type", damage_type))?,
+            }),
+            (None, None, None) => None,
+            _ => {
+                return Err(format!(
+                    "{}: {}: attack bonus, damage and damage type must be given together",
+                    monster, self.name
+                ))
+            }
+        };
+        if attack.is_none() && self.description.is_none() {
+            return Err(format!(
+                "{}: {}: actions that aren't attacks need a description",
+                monster, self.name
+            ));
+        }
+        Ok(Action {
+            name: self.name.clone(),
+            attack,
+            description: self.description.clone(),
+        })
+    }
+}
+
+/// Load the monster stat block table, checking that every entry is valid. Entries from the content
+/// directory replace the embedded entries for monsters with the same names.
+fn load_stat_blocks() -> Result<HashMap<String, StatBlock>, content::Error> {
+    let entries: Vec<StatBlockEntry> =
+        content::load(MONSTERS_FILE_NAME, include_str!("data/monsters.json"))?;
+    let mut stat_blocks = HashMap::new();
+    for entry in entries {
+        let stat_block = entry.to_stat_block().map_err(|message| {
+            content::Error::InvalidEntry(MONSTERS_FILE_NAME.to_owned(), message)
+        })?;
+        stat_blocks.insert(stat_block.name.to_lowercase(), stat_block);
+    }
+    Ok(stat_blocks)
+}
+
+/// Check that the monster stat block table loads, so that any problems with it are found at
+/// startup rather than the first time someone looks up a monster.
+pub fn check_content() -> Result<(), &'static content::Error> {
+    STAT_BLOCKS.as_ref().map(|_| ())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load_stat_blocks() {
+        assert_eq!(check_content().is_ok(), true);
+        let goblin = StatBlock::find("goblins").unwrap();
+        assert_eq!(goblin.name, "Goblin");
+        assert_eq!(goblin.armor_class, 15);
+        assert_eq!(goblin.hit_points, 7);
+        assert_eq!(goblin.modifier(1), 2);
+        assert_eq!(goblin.challenge_rating, ChallengeRating::Quarter);
+        assert_eq!(StatBlock::find("Dire Wolves").unwrap().name, "Dire Wolf");
+        assert_eq!(StatBlock::find("Tarrasque").is_none(), true);
+    }
+
+    #[test]
+    fn test_find_combatant() {
+        let name = |combatant: &str| {
+            StatBlock::find_combatant(combatant).map(|stat_block| stat_block.name.as_str())
+        };
+        assert_eq!(name("Goblin B"), Some("Goblin"));
+        assert_eq!(name("zombie 12"), Some("Zombie"));
+        assert_eq!(name("Goblin Boss"), Some("Goblin Boss"));
+        assert_eq!(name("Bob"), None);
+    }
+
+    #[test]
+    fn test_to_monster() {
+        let troll = StatBlock::find("troll")
+            .and_then(StatBlock::to_monster)
+            .unwrap();
+        assert_eq!(troll.attack_name, Some("bite".to_owned()));
+        assert_eq!(troll.attack_bonus, 7);
+        assert_eq!(troll.damage, Roll::new_unsafe(1, 6, 4));
+        assert_eq!(troll.challenge_rating, ChallengeRating::Whole(5));
+        let claws = StatBlock::find("brown bear")
+            .and_then(|stat_block| stat_block.to_monster_attacking_with(Some("claw")))
+            .unwrap();
+        assert_eq!(claws.attack_name, Some("claws".to_owned()));
+        assert_eq!(claws.damage, Roll::new_unsafe(2, 6, 4));
+        assert_eq!(
+            StatBlock::find("brown bear")
+                .and_then(|stat_block| stat_block.to_monster_attacking_with(Some("tail")))
+                .is_none(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_stat_block_entry_to_stat_block() {
+        fn entry(actions: &str) -> Result<String, String> {
+            let json = format!(
+                r#"{{"name": "Goblin", "kind": "Small humanoid", "armor_class": 15, "hit_points": 7, "hit_dice": "2d6", "speed": "30 ft.", "abilities": [8, 14, 10, 10, 8, 8], "challenge_rating": "1/4", "actions": [{}]}}"#,
+                actions
+            );
+            serde_json::from_str::<StatBlockEntry>(&json)
+                .map_err(|error| error.to_string())
+                .and_then(|entry| entry.to_stat_block())
+                .map(|stat_block| stat_block.name)
+        }
+        assert_eq!(
+            entry(
+                r#"{"name": "Scimitar", "attack_bonus": 4, "damage": "1d6+2", "damage_type": "slashing"}"#
+            ),
+            Ok("Goblin".to_owned())
+        );
+        assert_eq!(
+            entry(r#"{"name": "Scimitar", "attack_bonus": 4, "damage": "1d6+2"}"#),
+            Err(
+                "Goblin: Scimitar: attack bonus, damage and damage type must be given together"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            entry(
+                r#"{"name": "Scimitar", "attack_bonus": 4, "damage": "lots", "damage_type": "slashing"}"#
+            ),
+            Err("Goblin: Scimitar: invalid damage \"lots\"".to_owned())
+        );
+        assert_eq!(
+            entry(r#"{"name": "Nimble Escape"}"#),
+            Err("Goblin: Nimble Escape: actions that aren't attacks need a description".to_owned())
+        );
+    }
+}
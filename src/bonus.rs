@@ -0,0 +1,316 @@
+use crate::character::{AbilityName, SkillName};
+use crate::character_roll::Check;
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, UserId};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// A flat bonus to some of a character's rolls from a magic item or feature, such as "+2 to
+/// Stealth from Cloak of Elvenkind".
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Bonus {
+    pub target: BonusTarget,
+    pub amount: i32,
+    /// Where the bonus comes from, which is also how it's removed.
+    pub source: String,
+}
+
+/// The rolls that a bonus applies to.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum BonusTarget {
+    Ability(AbilityName),
+    AttackRolls,
+    Initiative,
+    SavingThrow(AbilityName),
+    SavingThrows,
+    Skill(SkillName),
+}
+
+impl Bonus {
+    /// Parse a bonus such as "+2 to Stealth from Cloak of Elvenkind".
+    pub fn parse(string: &str) -> Option<Bonus> {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)([+-]) *(\d+)(?: +bonus)? +to +(.+?) +from +(.+?) *\.?$")
+                    .unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        let amount: i32 = captures.get(2)?.as_str().parse().ok()?;
+        let amount = if captures.get(1)?.as_str() == "-" {
+            -amount
+        } else {
+            amount
+        };
+        let target = BonusTarget::parse(captures.get(3)?.as_str())?;
+        let source = captures.get(4)?.as_str().trim();
+        if amount == 0 || source.is_empty() {
+            None
+        } else {
+            Some(Bonus {
+                target,
+                amount,
+                source: source.to_owned(),
+            })
+        }
+    }
+
+    /// Retrieve a character's bonuses, in the order they were added.
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Vec<Bonus>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let mut statement = connection.prepare(
+            "SELECT target, amount, source \
+             FROM character_bonuses \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(params, |row| {
+            let target: String = row.get("target")?;
+            let amount = row.get("amount")?;
+            let source = row.get("source")?;
+            Ok(BonusTarget::parse(&target).map(|target| Bonus {
+                target,
+                amount,
+                source,
+            }))
+        })?;
+        rows.filter_map(|row| row.transpose()).collect()
+    }
+
+    /// Give a character the bonus, replacing any bonus to the same rolls from the same source.
+    pub fn add(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &self.target.to_string(),
+            &self.amount,
+            &self.source,
+        ];
+        connection.execute(
+            "INSERT INTO character_bonuses (channel_id, user_id, name, target, amount, source) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (channel_id, user_id, name, target, source) DO UPDATE SET amount = $5",
+            params,
+        )
+    }
+
+    /// Remove every bonus a character has from a source. Returns the number of bonuses removed.
+    pub fn remove(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        source: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &source,
+        ];
+        connection.execute(
+            "DELETE FROM character_bonuses \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             AND source = $4",
+            params,
+        )
+    }
+}
+
+impl fmt::Display for Bonus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:+} to {} from {}",
+            self.amount, self.target, self.source
+        )
+    }
+}
+
+impl BonusTarget {
+    pub fn parse(string: &str) -> Option<BonusTarget> {
+        lazy_static! {
+            static ref SAVING_THROW_REGEX: Regex =
+                Regex::new(r"^(?i)(.+?) +(?:saves?|saving +throws?)$").unwrap();
+            static ref ABILITY_CHECK_REGEX: Regex = Regex::new(r"^(?i)(.+?) +checks?$").unwrap();
+        }
+        let string = string.trim();
+        match string.to_lowercase().as_ref() {
+            "attack" | "attacks" | "attack roll" | "attack rolls" => {
+                return Some(BonusTarget::AttackRolls)
+            }
+            "initiative" => return Some(BonusTarget::Initiative),
+            "saves" | "saving throws" | "all saves" | "all saving throws" => {
+                return Some(BonusTarget::SavingThrows)
+            }
+            _ => (),
+        }
+        SkillName::parse(string)
+            .map(BonusTarget::Skill)
+            .or_else(|| AbilityName::parse(string).map(BonusTarget::Ability))
+            .or_else(|| {
+                SAVING_THROW_REGEX
+                    .captures(string)
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|m| AbilityName::parse(m.as_str()))
+                    .map(BonusTarget::SavingThrow)
+            })
+            .or_else(|| {
+                ABILITY_CHECK_REGEX
+                    .captures(string)
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|m| AbilityName::parse(m.as_str()))
+                    .map(BonusTarget::Ability)
+            })
+    }
+
+    /// Whether the bonus applies to a roll for an ability check, skill check or saving throw.
+    pub fn applies_to(self, check: Check) -> bool {
+        match (self, check) {
+            (BonusTarget::Ability(target), Check::Ability(name)) => target == name,
+            (BonusTarget::Initiative, Check::Initiative) => true,
+            (BonusTarget::SavingThrow(target), Check::SavingThrow(name)) => target == name,
+            (BonusTarget::SavingThrows, Check::SavingThrow(_)) => true,
+            (BonusTarget::Skill(target), Check::Skill(name)) => target == name,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for BonusTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BonusTarget::Ability(name) => write!(f, "{} checks", name.as_str()),
+            BonusTarget::AttackRolls => write!(f, "attack rolls"),
+            BonusTarget::Initiative => write!(f, "Initiative"),
+            BonusTarget::SavingThrow(name) => write!(f, "{} saving throws", name.as_str()),
+            BonusTarget::SavingThrows => write!(f, "all saving throws"),
+            BonusTarget::Skill(name) => name.as_str().fmt(f),
+        }
+    }
+}
+
+impl From<BonusTarget> for String {
+    fn from(target: BonusTarget) -> String {
+        target.to_string()
+    }
+}
+
+impl TryFrom<String> for BonusTarget {
+    type Error = String;
+
+    fn try_from(string: String) -> Result<BonusTarget, String> {
+        BonusTarget::parse(&string)
+            .ok_or_else(|| format!("Invalid value for bonus target (value = {})", string))
+    }
+}
+
+/// The total of the bonuses that apply to a roll.
+pub fn total<F>(bonuses: &[Bonus], applies: F) -> i32
+where
+    F: Fn(BonusTarget) -> bool,
+{
+    bonuses
+        .iter()
+        .filter(|bonus| applies(bonus.target))
+        .map(|bonus| bonus.amount)
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bonus(target: BonusTarget, amount: i32, source: &str) -> Bonus {
+        Bonus {
+            target,
+            amount,
+            source: source.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Bonus::parse("+2 to Stealth from Cloak of Elvenkind"),
+            Some(bonus(
+                BonusTarget::Skill(SkillName::Stealth),
+                2,
+                "Cloak of Elvenkind"
+            ))
+        );
+        assert_eq!(
+            Bonus::parse("+1 to all saves from Ring of Protection"),
+            Some(bonus(BonusTarget::SavingThrows, 1, "Ring of Protection"))
+        );
+        assert_eq!(
+            Bonus::parse("-1 to Dex saving throws from a cursed amulet."),
+            Some(bonus(
+                BonusTarget::SavingThrow(AbilityName::Dexterity),
+                -1,
+                "a cursed amulet"
+            ))
+        );
+        assert_eq!(
+            Bonus::parse("+1 bonus to attack rolls from +1 Longsword"),
+            Some(bonus(BonusTarget::AttackRolls, 1, "+1 Longsword"))
+        );
+        assert_eq!(Bonus::parse("+2 to Stealth"), None);
+        assert_eq!(Bonus::parse("+0 to Stealth from nothing"), None);
+        assert_eq!(Bonus::parse("+2 to cooking from an apron"), None);
+    }
+
+    #[test]
+    fn test_target_round_trip() {
+        for target in [
+            BonusTarget::Ability(AbilityName::Strength),
+            BonusTarget::AttackRolls,
+            BonusTarget::Initiative,
+            BonusTarget::SavingThrow(AbilityName::Wisdom),
+            BonusTarget::SavingThrows,
+            BonusTarget::Skill(SkillName::SleightOfHand),
+        ]
+        .iter()
+        {
+            assert_eq!(BonusTarget::parse(&target.to_string()), Some(*target));
+        }
+    }
+
+    #[test]
+    fn test_total() {
+        let bonuses = [
+            bonus(BonusTarget::Skill(SkillName::Stealth), 2, "Cloak"),
+            bonus(BonusTarget::SavingThrows, 1, "Ring"),
+            bonus(BonusTarget::SavingThrow(AbilityName::Wisdom), 2, "Amulet"),
+        ];
+        let check_total = |check| total(&bonuses, |target| target.applies_to(check));
+        assert_eq!(check_total(Check::Skill(SkillName::Stealth)), 2);
+        assert_eq!(check_total(Check::SavingThrow(AbilityName::Wisdom)), 3);
+        assert_eq!(check_total(Check::SavingThrow(AbilityName::Strength)), 1);
+        assert_eq!(check_total(Check::Ability(AbilityName::Dexterity)), 0);
+        assert_eq!(
+            total(&bonuses, |target| target == BonusTarget::AttackRolls),
+            0
+        );
+    }
+}
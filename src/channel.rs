@@ -1,18 +1,112 @@
+use crate::edition::Edition;
+use crate::roll::Limits;
+use crate::tie_break::TieBreak;
+use rusqlite::types::ToSql;
 use rusqlite::Result as RusqliteResult;
 use rusqlite::{Connection, OptionalExtension, Row};
-use serenity::model::id::ChannelId;
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
+use std::fmt;
+use std::time::Duration;
+
+/// The default time within which an identical command from the same user is treated as an
+/// accidental duplicate, for channels that aren't configured.
+pub const DEFAULT_DUPLICATE_WINDOW: Duration = Duration::from_secs(2);
+
+/// The longest a channel's duplicate window can be, so that a roll that's meant to be repeated,
+/// such as a second attack, isn't ignored.
+pub const MAXIMUM_DUPLICATE_WINDOW: Duration = Duration::from_secs(60);
 
 pub struct Channel {
     pub enabled: bool,
     pub locked: bool,
     pub dice_only: bool,
+    /// Identical commands from the same user within this window are ignored. Zero disables
+    /// duplicate suppression.
+    pub duplicate_window: Duration,
+    /// How much detail to give when a command can't be understood.
+    pub verbosity: Verbosity,
+    /// How to order combatants or characters with the same result.
+    pub tie_break: TieBreak,
+    /// The most dice that can be rolled at once, and the most sides they can have.
+    pub limits: Limits,
+    /// The edition of the rules to use for rules that changed between editions.
+    pub edition: Edition,
+    /// The user running the game in the channel, who is sent the results of blind rolls.
+    pub dm_user_id: Option<UserId>,
+    /// The role whose members can run the game in the channel, as well as the DM user.
+    pub dm_role_id: Option<RoleId>,
+    /// The prefix for shorthand commands, such as "?" for "?r d20".
+    pub prefix: String,
+    /// Whether to leave roll commands in the channel, instead of deleting them once they've been
+    /// answered.
+    pub keep_roll_messages: bool,
+    /// Whether to correct the spelling of natural language commands before they're parsed.
+    pub spelling_correction: bool,
+}
+
+/// Who runs the game in a channel, which is either a user or everyone with a role.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DungeonMaster {
+    User(UserId),
+    Role(RoleId),
+}
+
+impl fmt::Display for DungeonMaster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DungeonMaster::User(user_id) => write!(f, "<@{}>", user_id),
+            DungeonMaster::Role(role_id) => write!(f, "<@&{}>", role_id),
+        }
+    }
+}
+
+/// How much detail to give when a command can't be understood. Terse clarifications only suggest
+/// what to try instead, while verbose ones also point to the help.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Verbosity {
+    Terse,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn parse(string: &str) -> Option<Verbosity> {
+        match string.trim().to_lowercase().as_str() {
+            "terse" => Some(Verbosity::Terse),
+            "normal" => Some(Verbosity::Normal),
+            "verbose" => Some(Verbosity::Verbose),
+            _ => None,
+        }
+    }
+
+    /// The verbosity as it's stored.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verbosity::Terse => "terse",
+            Verbosity::Normal => "normal",
+            Verbosity::Verbose => "verbose",
+        }
+    }
 }
 
 impl Channel {
+    /// Whether a user can use the commands for running the game in the channel, such as awarding
+    /// experience points or saving monsters. Once the channel has a DM, only the DM user and the
+    /// members of the DM role can, otherwise the administrators can.
+    pub fn is_dm(&self, user_id: UserId, role_ids: &[RoleId], is_admin: bool) -> bool {
+        match (self.dm_user_id, self.dm_role_id) {
+            (None, None) => is_admin,
+            (dm_user_id, dm_role_id) => {
+                dm_user_id == Some(user_id)
+                    || dm_role_id.map_or(false, |role_id| role_ids.contains(&role_id))
+            }
+        }
+    }
+
     pub fn get(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<Option<Channel>> {
         connection
             .query_row(
-                "SELECT enabled, locked, dice_only FROM channels WHERE channel_id = $1",
+                "SELECT enabled, locked, dice_only, duplicate_window, verbosity, tie_break, maximum_rolls, maximum_sides, edition, dm_user_id, dm_role_id, prefix, keep_roll_messages, spelling_correction FROM channels WHERE channel_id = $1",
                 &[&channel_id.to_string()],
                 Channel::from_row,
             )
@@ -20,10 +114,260 @@ impl Channel {
     }
 
     fn from_row(row: &Row) -> RusqliteResult<Channel> {
+        let maximum_rolls: Option<i64> = row.get("maximum_rolls")?;
+        let maximum_sides: Option<i32> = row.get("maximum_sides")?;
         Ok(Channel {
             enabled: row.get("enabled")?,
             locked: row.get("locked")?,
             dice_only: row.get("dice_only")?,
+            duplicate_window: Duration::from_secs(
+                row.get::<_, i64>("duplicate_window")?.max(0) as u64
+            ),
+            verbosity: Verbosity::parse(&row.get::<_, String>("verbosity")?)
+                .unwrap_or(Verbosity::Normal),
+            tie_break: TieBreak::parse(&row.get::<_, String>("tie_break")?)
+                .unwrap_or(TieBreak::Simultaneous),
+            limits: match (maximum_rolls, maximum_sides) {
+                (Some(rolls), Some(sides)) if rolls > 0 => Limits::new(rolls as usize, sides),
+                _ => None,
+            }
+            .unwrap_or(Limits::MAXIMUM),
+            edition: Edition::parse(&row.get::<_, String>("edition")?)
+                .unwrap_or(Edition::Rules2014),
+            dm_user_id: row
+                .get::<_, Option<String>>("dm_user_id")?
+                .and_then(|user_id| user_id.parse().ok())
+                .map(UserId),
+            dm_role_id: row
+                .get::<_, Option<String>>("dm_role_id")?
+                .and_then(|role_id| role_id.parse().ok())
+                .map(RoleId),
+            prefix: row.get("prefix")?,
+            keep_roll_messages: row.get("keep_roll_messages")?,
+            spelling_correction: row.get("spelling_correction")?,
         })
     }
+
+    /// Start using a channel with the settings of the server it's in, if the server has changed
+    /// them. Does nothing if the channel has already been used.
+    pub fn inherit(
+        connection: &Connection,
+        channel_id: ChannelId,
+        guild_id: GuildId,
+    ) -> RusqliteResult<usize> {
+        connection.execute(
+            "INSERT OR IGNORE INTO channels \
+             (channel_id, enabled, locked, dice_only, prefix, keep_roll_messages, spelling_correction) \
+             SELECT $1, enabled, locked, dice_only, prefix, keep_roll_messages, spelling_correction \
+             FROM guilds WHERE guild_id = $2",
+            &[&channel_id.to_string(), &guild_id.to_string()],
+        )
+    }
+
+    /// Enable or disable Dungeon Helper in a channel. Server administrators can use it in a
+    /// disabled channel.
+    pub fn set_enabled(
+        connection: &Connection,
+        channel_id: ChannelId,
+        enabled: bool,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &enabled];
+        connection.execute(
+            "INSERT INTO channels (channel_id, enabled) VALUES ($1, $2) \
+             ON CONFLICT (channel_id) DO UPDATE SET enabled = $2",
+            params,
+        )
+    }
+
+    /// Lock or unlock the characters in a channel, so that they can't be edited.
+    pub fn set_locked(
+        connection: &Connection,
+        channel_id: ChannelId,
+        locked: bool,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &locked];
+        connection.execute(
+            "INSERT INTO channels (channel_id, locked) VALUES ($1, $2) \
+             ON CONFLICT (channel_id) DO UPDATE SET locked = $2",
+            params,
+        )
+    }
+
+    /// Set whether a channel is dice only, where messages are treated as commands without
+    /// mentioning Dungeon Helper.
+    pub fn set_dice_only(
+        connection: &Connection,
+        channel_id: ChannelId,
+        dice_only: bool,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &dice_only];
+        connection.execute(
+            "INSERT INTO channels (channel_id, dice_only) VALUES ($1, $2) \
+             ON CONFLICT (channel_id) DO UPDATE SET dice_only = $2",
+            params,
+        )
+    }
+
+    /// Set how long identical commands from the same user are ignored for in a channel, or turn
+    /// duplicate suppression off with zero.
+    pub fn set_duplicate_window(
+        connection: &Connection,
+        channel_id: ChannelId,
+        duplicate_window: Duration,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &(duplicate_window.as_secs() as i64),
+        ];
+        connection.execute(
+            "INSERT INTO channels (channel_id, duplicate_window) VALUES ($1, $2) \
+             ON CONFLICT (channel_id) DO UPDATE SET duplicate_window = $2",
+            params,
+        )
+    }
+
+    /// Set the prefix for shorthand commands in a channel.
+    pub fn set_prefix(
+        connection: &Connection,
+        channel_id: ChannelId,
+        prefix: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &prefix];
+        connection.execute(
+            "INSERT INTO channels (channel_id, prefix) VALUES ($1, $2) \
+             ON CONFLICT (channel_id) DO UPDATE SET prefix = $2",
+            params,
+        )
+    }
+
+    /// Set whether to leave roll commands in a channel instead of deleting them.
+    pub fn set_keep_roll_messages(
+        connection: &Connection,
+        channel_id: ChannelId,
+        keep_roll_messages: bool,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &keep_roll_messages];
+        connection.execute(
+            "INSERT INTO channels (channel_id, keep_roll_messages) VALUES ($1, $2) \
+             ON CONFLICT (channel_id) DO UPDATE SET keep_roll_messages = $2",
+            params,
+        )
+    }
+
+    /// Set whether to correct the spelling of natural language commands in a channel.
+    pub fn set_spelling_correction(
+        connection: &Connection,
+        channel_id: ChannelId,
+        spelling_correction: bool,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &spelling_correction];
+        connection.execute(
+            "INSERT INTO channels (channel_id, spelling_correction) VALUES ($1, $2) \
+             ON CONFLICT (channel_id) DO UPDATE SET spelling_correction = $2",
+            params,
+        )
+    }
+
+    /// Set the most dice that can be rolled at once in a channel, and the most sides they can
+    /// have, or go back to the maximum allowed values.
+    pub fn set_limits(
+        connection: &Connection,
+        channel_id: ChannelId,
+        limits: Option<Limits>,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &limits.map(|limits| limits.rolls as i64),
+            &limits.map(|limits| limits.sides),
+        ];
+        connection.execute(
+            "INSERT INTO channels (channel_id, maximum_rolls, maximum_sides) \
+             VALUES ($1, $2, $3) \
+             ON CONFLICT (channel_id) DO UPDATE SET maximum_rolls = $2, maximum_sides = $3",
+            params,
+        )
+    }
+
+    /// Set the edition of the rules used in a channel.
+    pub fn set_edition(
+        connection: &Connection,
+        channel_id: ChannelId,
+        edition: Edition,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &edition.as_str()];
+        connection.execute(
+            "INSERT INTO channels (channel_id, edition) VALUES ($1, $2) \
+             ON CONFLICT (channel_id) DO UPDATE SET edition = $2",
+            params,
+        )
+    }
+
+    /// Set the user or role running the game in a channel, replacing the previous one of the same
+    /// kind, or clear both.
+    pub fn set_dungeon_master(
+        connection: &Connection,
+        channel_id: ChannelId,
+        dungeon_master: Option<DungeonMaster>,
+    ) -> RusqliteResult<usize> {
+        let channel_id = channel_id.to_string();
+        match dungeon_master {
+            Some(DungeonMaster::User(user_id)) => connection.execute(
+                "INSERT INTO channels (channel_id, dm_user_id) VALUES ($1, $2) \
+                 ON CONFLICT (channel_id) DO UPDATE SET dm_user_id = $2",
+                &[&channel_id, &user_id.to_string()],
+            ),
+            Some(DungeonMaster::Role(role_id)) => connection.execute(
+                "INSERT INTO channels (channel_id, dm_role_id) VALUES ($1, $2) \
+                 ON CONFLICT (channel_id) DO UPDATE SET dm_role_id = $2",
+                &[&channel_id, &role_id.to_string()],
+            ),
+            None => connection.execute(
+                "UPDATE channels SET dm_user_id = NULL, dm_role_id = NULL WHERE channel_id = $1",
+                &[&channel_id],
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_dm() {
+        let channel = |dm_user_id: Option<u64>, dm_role_id: Option<u64>| Channel {
+            enabled: true,
+            locked: false,
+            dice_only: false,
+            duplicate_window: DEFAULT_DUPLICATE_WINDOW,
+            verbosity: Verbosity::Normal,
+            tie_break: TieBreak::Simultaneous,
+            limits: Limits::MAXIMUM,
+            edition: Edition::Rules2014,
+            dm_user_id: dm_user_id.map(UserId),
+            dm_role_id: dm_role_id.map(RoleId),
+            prefix: "!".to_owned(),
+            keep_roll_messages: false,
+            spelling_correction: true,
+        };
+        let roles = [RoleId(789)];
+        assert_eq!(channel(None, None).is_dm(UserId(123), &[], true), true);
+        assert_eq!(channel(None, None).is_dm(UserId(123), &[], false), false);
+        assert_eq!(
+            channel(Some(456), None).is_dm(UserId(123), &[], true),
+            false
+        );
+        assert_eq!(
+            channel(Some(456), None).is_dm(UserId(456), &[], false),
+            true
+        );
+        assert_eq!(
+            channel(None, Some(789)).is_dm(UserId(123), &roles, false),
+            true
+        );
+        assert_eq!(
+            channel(Some(456), Some(789)).is_dm(UserId(123), &[], true),
+            false
+        );
+    }
 }
@@ -0,0 +1,312 @@
+use crate::channel::{Channel, Verbosity};
+use crate::edition::Edition;
+use crate::guild::Guild;
+use crate::house_rule::{CriticalHits, HouseRules};
+use crate::random_table::RandomTable;
+use crate::roll::Limits;
+use crate::tie_break::TieBreak;
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::ChannelId;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// The name of the file a channel's configuration is exported to.
+pub const FILE_NAME: &str = "channel-settings.json";
+
+/// A channel's configuration, which can be exported and imported into another channel, such as
+/// for a group running several campaigns with the same rules. The channel's DM and command
+/// restrictions aren't included, since they refer to the users and roles of one server.
+#[derive(Debug, PartialEq)]
+pub struct ChannelExport {
+    pub enabled: bool,
+    pub locked: bool,
+    pub dice_only: bool,
+    pub keep_roll_messages: bool,
+    pub spelling_correction: bool,
+    pub prefix: String,
+    pub duplicate_window: Duration,
+    pub verbosity: Verbosity,
+    pub tie_break: TieBreak,
+    pub limits: Limits,
+    pub edition: Edition,
+    pub house_rules: HouseRules,
+    pub tables: Vec<RandomTable>,
+}
+
+/// A channel's configuration as it's written, with each setting as it's stored.
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct ChannelExportEntry {
+    settings: SettingsEntry,
+    house_rules: HouseRulesEntry,
+    #[serde(default)]
+    tables: Vec<RandomTable>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct SettingsEntry {
+    enabled: bool,
+    locked: bool,
+    dice_only: bool,
+    keep_roll_messages: bool,
+    spelling_correction: bool,
+    prefix: String,
+    /// The duplicate window, in seconds.
+    duplicate_window: u64,
+    verbosity: String,
+    tie_break: String,
+    maximum_rolls: usize,
+    maximum_sides: i32,
+    edition: String,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct HouseRulesEntry {
+    critical_hits: String,
+    flanking: bool,
+    potion_bonus_action: bool,
+}
+
+impl ChannelExport {
+    /// Retrieve the configuration of a channel, given its settings.
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        channel: &Channel,
+    ) -> RusqliteResult<ChannelExport> {
+        Ok(ChannelExport {
+            enabled: channel.enabled,
+            locked: channel.locked,
+            dice_only: channel.dice_only,
+            keep_roll_messages: channel.keep_roll_messages,
+            spelling_correction: channel.spelling_correction,
+            prefix: channel.prefix.clone(),
+            duplicate_window: channel.duplicate_window,
+            verbosity: channel.verbosity,
+            tie_break: channel.tie_break,
+            limits: channel.limits,
+            edition: channel.edition,
+            house_rules: HouseRules::get(connection, channel_id)?,
+            tables: RandomTable::get_all(connection, channel_id)?,
+        })
+    }
+
+    /// Import the configuration into a channel. The channel's settings and house rules are
+    /// replaced, along with any of its tables with the same names as the imported ones. Its other
+    /// tables are kept.
+    pub fn save(&self, connection: &mut Connection, channel_id: ChannelId) -> RusqliteResult<()> {
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &self.enabled,
+            &self.locked,
+            &self.dice_only,
+            &self.keep_roll_messages,
+            &self.prefix,
+            &(self.duplicate_window.as_secs() as i64),
+            &self.verbosity.as_str(),
+            &self.tie_break.as_str(),
+            &(self.limits.rolls as i64),
+            &self.limits.sides,
+            &self.edition.as_str(),
+            &self.spelling_correction,
+        ];
+        transaction.execute(
+            "INSERT INTO channels \
+             (channel_id, enabled, locked, dice_only, keep_roll_messages, prefix, duplicate_window, verbosity, tie_break, maximum_rolls, maximum_sides, edition, spelling_correction) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13) \
+             ON CONFLICT (channel_id) DO UPDATE SET \
+             enabled = $2, locked = $3, dice_only = $4, keep_roll_messages = $5, prefix = $6, duplicate_window = $7, verbosity = $8, tie_break = $9, maximum_rolls = $10, maximum_sides = $11, edition = $12, spelling_correction = $13",
+            params,
+        )?;
+        HouseRules::reset(&transaction, channel_id)?;
+        for house_rule in self.house_rules.rules() {
+            HouseRules::set(&transaction, channel_id, house_rule)?;
+        }
+        for table in self.tables.iter() {
+            table.replace(&transaction, channel_id)?;
+        }
+        transaction.commit()
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let entry = ChannelExportEntry {
+            settings: SettingsEntry {
+                enabled: self.enabled,
+                locked: self.locked,
+                dice_only: self.dice_only,
+                keep_roll_messages: self.keep_roll_messages,
+                spelling_correction: self.spelling_correction,
+                prefix: self.prefix.clone(),
+                duplicate_window: self.duplicate_window.as_secs(),
+                verbosity: self.verbosity.as_str().to_owned(),
+                tie_break: self.tie_break.as_str().to_owned(),
+                maximum_rolls: self.limits.rolls,
+                maximum_sides: self.limits.sides,
+                edition: self.edition.as_str().to_owned(),
+            },
+            house_rules: HouseRulesEntry {
+                critical_hits: self.house_rules.critical_hits.as_str().to_owned(),
+                flanking: self.house_rules.flanking,
+                potion_bonus_action: self.house_rules.potion_bonus_action,
+            },
+            tables: self.tables.clone(),
+        };
+        serde_json::to_string_pretty(&entry)
+    }
+
+    /// Read a configuration previously exported with `to_json`, checking each setting in it.
+    /// Returns a description of each problem with the configuration if it's invalid.
+    pub fn from_json(json: &str) -> Result<ChannelExport, Vec<String>> {
+        let entry: ChannelExportEntry =
+            serde_json::from_str(json).map_err(|error| vec![error.to_string()])?;
+        let settings = &entry.settings;
+        let mut errors = Vec::new();
+        if !Guild::is_valid_prefix(&settings.prefix) {
+            errors.push(format!(
+                "the prefix \"{}\" must be one to three symbols",
+                settings.prefix
+            ));
+        }
+        let verbosity = Verbosity::parse(&settings.verbosity);
+        if verbosity.is_none() {
+            errors.push(format!("unknown verbosity \"{}\"", settings.verbosity));
+        }
+        let tie_break = TieBreak::parse(&settings.tie_break);
+        if tie_break.is_none() {
+            errors.push(format!("unknown tie break \"{}\"", settings.tie_break));
+        }
+        let limits = Limits::new(settings.maximum_rolls, settings.maximum_sides);
+        if limits.is_none() {
+            errors.push(format!(
+                "the dice limits {}d{} are outside the allowed values",
+                settings.maximum_rolls, settings.maximum_sides
+            ));
+        }
+        let edition = Edition::parse(&settings.edition);
+        if edition.is_none() {
+            errors.push(format!("unknown edition \"{}\"", settings.edition));
+        }
+        let critical_hits = CriticalHits::parse(&entry.house_rules.critical_hits);
+        if critical_hits.is_none() {
+            errors.push(format!(
+                "unknown critical hit rule \"{}\"",
+                entry.house_rules.critical_hits
+            ));
+        }
+        let mut names = HashSet::new();
+        for table in entry.tables.iter() {
+            errors.extend(table.validate());
+            if !names.insert(table.name.trim().to_lowercase()) {
+                errors.push(format!("{} is in the file more than once", table.name));
+            }
+        }
+        match (verbosity, tie_break, limits, edition, critical_hits) {
+            (
+                Some(verbosity),
+                Some(tie_break),
+                Some(limits),
+                Some(edition),
+                Some(critical_hits),
+            ) if errors.is_empty() => Ok(ChannelExport {
+                enabled: settings.enabled,
+                locked: settings.locked,
+                dice_only: settings.dice_only,
+                keep_roll_messages: settings.keep_roll_messages,
+                spelling_correction: settings.spelling_correction,
+                prefix: settings.prefix.clone(),
+                duplicate_window: Duration::from_secs(settings.duplicate_window),
+                verbosity,
+                tie_break,
+                limits,
+                edition,
+                house_rules: HouseRules {
+                    critical_hits,
+                    flanking: entry.house_rules.flanking,
+                    potion_bonus_action: entry.house_rules.potion_bonus_action,
+                },
+                tables: entry.tables,
+            }),
+            _ => Err(errors),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::random_table::TableEntry;
+
+    #[test]
+    fn test_json() {
+        let export = ChannelExport {
+            enabled: true,
+            locked: true,
+            dice_only: false,
+            keep_roll_messages: true,
+            spelling_correction: false,
+            prefix: "?".to_owned(),
+            duplicate_window: Duration::from_secs(5),
+            verbosity: Verbosity::Terse,
+            tie_break: TieBreak::Dexterity,
+            limits: Limits::new(20, 100).unwrap(),
+            edition: Edition::Rules2024,
+            house_rules: HouseRules {
+                critical_hits: CriticalHits::MaximiseDice,
+                flanking: true,
+                potion_bonus_action: false,
+            },
+            tables: vec![RandomTable {
+                name: "Forest Encounters".to_owned(),
+                entries: vec![TableEntry {
+                    weight: 3,
+                    text: "1d4 goblins".to_owned(),
+                }],
+            }],
+        };
+        assert_eq!(
+            ChannelExport::from_json(&export.to_json().unwrap()),
+            Ok(export)
+        );
+    }
+
+    #[test]
+    fn test_from_json_invalid() {
+        let json = r#"{
+            "settings": {
+                "enabled": true,
+                "locked": false,
+                "dice_only": false,
+                "keep_roll_messages": false,
+                "spelling_correction": true,
+                "prefix": "@",
+                "duplicate_window": 2,
+                "verbosity": "normal",
+                "tie_break": "simultaneous",
+                "maximum_rolls": 20,
+                "maximum_sides": 100,
+                "edition": "2030"
+            },
+            "house_rules": {
+                "critical_hits": "triple",
+                "flanking": false,
+                "potion_bonus_action": false
+            },
+            "tables": [{"name": "Loot", "entries": [{"weight": 0, "text": "a copper piece"}]}]
+        }"#;
+        assert_eq!(
+            ChannelExport::from_json(json),
+            Err(vec![
+                "the prefix \"@\" must be one to three symbols".to_owned(),
+                "unknown edition \"2030\"".to_owned(),
+                "unknown critical hit rule \"triple\"".to_owned(),
+                "Loot has an entry with a weight that isn't from 1 to 100".to_owned(),
+            ])
+        );
+    }
+}
@@ -1,21 +1,85 @@
+use crate::bonus;
+use crate::bonus::{Bonus, BonusTarget};
+use crate::character_roll::Check;
+use crate::class::{Class, ClassLevel, SpellSlots};
+use crate::edition::Edition;
+use crate::experience;
+use crate::roll::Roll;
+use crate::suggestion;
 use crate::weapon::{Category, WeaponName};
 use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use rusqlite::Result as RusqliteResult;
 use rusqlite::{Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
 use serenity::model::id::{ChannelId, UserId};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error;
 use std::fmt;
 
+/// The name given to a user's character when they haven't created or switched to a named character.
+pub const DEFAULT_CHARACTER_NAME: &str = "default";
+
+/// The longest name shown in rolls, in characters. Discord rejects embeds whose titles or author
+/// names are longer than 256 characters, so this leaves room for the rest of a roll's title.
+pub const MAXIMUM_DISPLAY_NAME_LENGTH: usize = 100;
+
+/// The tables that store parts of a character other than their attributes, keyed by channel, user
+/// and character name.
+const CHARACTER_TABLES: [&str; 14] = [
+    "active_characters",
+    "character_audit",
+    "character_bonuses",
+    "character_classes",
+    "character_coins",
+    "character_companions",
+    "character_conditions",
+    "character_counters",
+    "character_effects",
+    "character_quick_references",
+    "character_weapon_masteries",
+    "character_weapon_proficiencies",
+    "character_wild_shape_forms",
+    "character_wild_shapes",
+];
+
 /// A character in a Dungeons and Dragons campaign.
 ///
 /// The character has a number of base abilities and proficiencies, from which ability and
 /// skill modifiers are calculated.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
 pub struct Character {
+    name: String,
+    display_name: Option<String>,
     avatar_url: Option<String>,
     level: Option<i32>,
-    jack_of_all_trades: bool,
-    martial_arts: bool,
+    experience: i32,
+    /// The character's armour class, if they've set it. Otherwise it is worked out as if they
+    /// weren't wearing armour.
+    armor_class: Option<i32>,
+    /// The classes the character has taken levels in, in the order they were first taken.
+    classes: Vec<ClassLevel>,
+    /// Flat bonuses to the character's rolls from magic items and features.
+    bonuses: Vec<Bonus>,
+    /// The weapons whose mastery properties the character can use, under the 2024 rules.
+    weapon_masteries: Vec<WeaponName>,
+    /// Overrides whether the character has Jack of All Trades, which is otherwise derived from
+    /// their class.
+    jack_of_all_trades: Option<bool>,
+    /// Overrides whether the character has Martial Arts, which is otherwise derived from their
+    /// class.
+    martial_arts: Option<bool>,
+    /// A flat bonus to the character's initiative from features other than the Alert feat.
+    initiative_bonus: i32,
+    /// Whether the character has the Alert feat, which gives them +5 to initiative.
+    alert: bool,
+    /// Whether the character has the Observant feat, which gives them +5 to their passive
+    /// Perception and Investigation scores.
+    observant: bool,
+    /// The character's fighting style, if they have one, which changes their weapon attacks.
+    fighting_style: Option<FightingStyle>,
 
     // Abilities
     strength: Option<i32>,
@@ -55,18 +119,38 @@ pub struct Character {
 }
 
 impl Character {
+    /// Retrieve the user's active character in a channel.
     pub fn get(
         connection: &Connection,
         channel_id: ChannelId,
         user_id: UserId,
     ) -> RusqliteResult<Option<Character>> {
-        connection
+        Character::get_active_name(connection, channel_id, user_id)
+            .and_then(|name| Character::get_by_name(connection, channel_id, user_id, &name))
+    }
+
+    pub fn get_by_name(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Option<Character>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let character = connection
             .query_row(
                 "SELECT \
+                 name, \
+                 display_name, \
                  avatar_url, \
                  level, \
+                 experience, \
+                 armor_class, \
                  jack_of_all_trades, \
                  martial_arts, \
+                 initiative_bonus, \
+                 alert, \
+                 observant, \
+                 fighting_style, \
                  strength, \
                  dexterity, \
                  constitution, \
@@ -99,19 +183,621 @@ impl Character {
                  survival_proficiency \
                  FROM characters \
                  WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3",
+                params,
+                Character::from_row,
+            )
+            .optional()?;
+        character
+            .map(|mut character| -> RusqliteResult<Character> {
+                character.classes =
+                    Character::get_classes(connection, channel_id, user_id, &character.name)?;
+                character.bonuses = Bonus::get(connection, channel_id, user_id, &character.name)?;
+                character.weapon_masteries = Character::get_weapon_masteries(
+                    connection,
+                    channel_id,
+                    user_id,
+                    &character.name,
+                )?;
+                Ok(character)
+            })
+            .transpose()
+    }
+
+    fn get_classes(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Vec<ClassLevel>> {
+        let mut statement = connection.prepare(
+            "SELECT class, subclass, level \
+             FROM character_classes \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             ORDER BY position",
+        )?;
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let rows = statement.query_map(params, |row| {
+            Ok(ClassLevel {
+                class: row.get("class")?,
+                subclass: row.get("subclass")?,
+                level: row.get("level")?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    fn get_weapon_masteries(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Vec<WeaponName>> {
+        let mut statement = connection.prepare(
+            "SELECT weapon_name \
+             FROM character_weapon_masteries \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             ORDER BY weapon_name",
+        )?;
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let rows = statement.query_map(params, |row| row.get::<_, WeaponName>("weapon_name"))?;
+        rows.collect()
+    }
+
+    /// Retrieve the name of the user's active character in a channel, which is the character they
+    /// last created or switched to.
+    pub fn get_active_name(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<String> {
+        connection
+            .query_row(
+                "SELECT name \
+                 FROM active_characters \
+                 WHERE channel_id = $1 \
                  AND user_id = $2",
                 &[&channel_id.to_string(), &user_id.to_string()],
-                Character::from_row,
+                |row| row.get(0),
+            )
+            .optional()
+            .map(|name| name.unwrap_or_else(|| DEFAULT_CHARACTER_NAME.to_owned()))
+    }
+
+    /// Retrieve the active character of every user with a character in a channel, along with the
+    /// name to show for each character.
+    pub fn get_all_active_names(
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<Vec<(UserId, String)>> {
+        let mut statement = connection.prepare(
+            "SELECT characters.user_id, \
+             COALESCE(characters.display_name, characters.name) AS name \
+             FROM characters \
+             LEFT JOIN active_characters \
+             ON active_characters.channel_id = characters.channel_id \
+             AND active_characters.user_id = characters.user_id \
+             WHERE characters.channel_id = $1 \
+             AND characters.name = COALESCE(active_characters.name, $2) \
+             ORDER BY characters.user_id",
+        )?;
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &DEFAULT_CHARACTER_NAME];
+        let rows = statement.query_map(params, |row| {
+            let user_id: String = row.get("user_id")?;
+            Ok((user_id, row.get("name")?))
+        })?;
+        rows.map(|row| {
+            row.map(|(user_id, name)| (UserId(user_id.parse().unwrap_or_default()), name))
+        })
+        .collect()
+    }
+
+    /// Retrieve the Dexterity score of each user's active character in a channel, by the name
+    /// they're shown as, for breaking ties in the initiative order and rolls for the table.
+    pub fn get_all_active_dexterity_scores(
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<HashMap<String, i32>> {
+        let mut statement = connection.prepare(
+            "SELECT COALESCE(characters.display_name, characters.name) AS name, \
+             characters.dexterity \
+             FROM characters \
+             LEFT JOIN active_characters \
+             ON active_characters.channel_id = characters.channel_id \
+             AND active_characters.user_id = characters.user_id \
+             WHERE characters.channel_id = $1 \
+             AND characters.name = COALESCE(active_characters.name, $2) \
+             AND characters.dexterity IS NOT NULL",
+        )?;
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &DEFAULT_CHARACTER_NAME];
+        let rows = statement.query_map(params, |row| {
+            let name: String = row.get("name")?;
+            Ok((name.to_lowercase(), row.get("dexterity")?))
+        })?;
+        rows.collect()
+    }
+
+    /// Find the user's characters in channels other than the given channel, as (channel, name)
+    /// pairs. If no name is given, the active character in each channel is found instead.
+    pub fn find_in_other_channels(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        source_channel_id: Option<ChannelId>,
+        name: Option<&str>,
+    ) -> RusqliteResult<Vec<(ChannelId, String)>> {
+        let mut statement = connection.prepare(
+            "SELECT characters.channel_id, characters.name \
+             FROM characters \
+             LEFT JOIN active_characters \
+             ON active_characters.channel_id = characters.channel_id \
+             AND active_characters.user_id = characters.user_id \
+             WHERE characters.user_id = $1 \
+             AND characters.channel_id != $2 \
+             AND ($3 IS NULL OR characters.channel_id = $3) \
+             AND characters.name = COALESCE($4, active_characters.name, $5) \
+             ORDER BY characters.channel_id",
+        )?;
+        let params: &[&dyn ToSql] = &[
+            &user_id.to_string(),
+            &channel_id.to_string(),
+            &source_channel_id.map(|channel_id| channel_id.to_string()),
+            &name,
+            &DEFAULT_CHARACTER_NAME,
+        ];
+        let rows = statement.query_map(params, |row| {
+            let channel_id: String = row.get("channel_id")?;
+            Ok((channel_id, row.get("name")?))
+        })?;
+        rows.map(|row| {
+            row.map(|(channel_id, name)| (ChannelId(channel_id.parse().unwrap_or_default()), name))
+        })
+        .collect()
+    }
+
+    /// Create a new character with no attributes set. Returns false if the user already has a
+    /// character with the same name in the channel.
+    pub fn create(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO characters (channel_id, user_id, name) VALUES ($1, $2, $3)",
+                params,
+            )
+            .map(|rows| rows > 0)
+    }
+
+    /// Find the name of one of the user's characters, as it was originally written. Character
+    /// names are case insensitive.
+    pub fn find_name(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Option<String>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection
+            .query_row(
+                "SELECT name \
+                 FROM characters \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3",
+                params,
+                |row| row.get(0),
             )
             .optional()
     }
 
+    /// Delete one of the user's characters, along with everything stored about them. If it was the
+    /// user's active character, they switch back to their default character. Returns false if the
+    /// user doesn't have a character with the name.
+    pub fn delete(
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        for table in CHARACTER_TABLES.iter() {
+            transaction.execute(
+                &format!(
+                    "DELETE FROM {} WHERE channel_id = $1 AND user_id = $2 AND name = $3",
+                    table
+                ),
+                params,
+            )?;
+        }
+        let deleted = transaction.execute(
+            "DELETE FROM characters WHERE channel_id = $1 AND user_id = $2 AND name = $3",
+            params,
+        )?;
+        transaction.commit()?;
+        Ok(deleted > 0)
+    }
+
+    /// Delete all of the user's characters in a channel, along with everything stored about them.
+    /// Returns the number of characters deleted.
+    pub fn delete_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string()];
+        for table in CHARACTER_TABLES.iter() {
+            connection.execute(
+                &format!(
+                    "DELETE FROM {} WHERE channel_id = $1 AND user_id = $2",
+                    table
+                ),
+                params,
+            )?;
+        }
+        connection.execute(
+            "DELETE FROM characters WHERE channel_id = $1 AND user_id = $2",
+            params,
+        )
+    }
+
+    pub fn set_active_name(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection.execute(
+            "INSERT INTO active_characters (channel_id, user_id, name) VALUES ($1, $2, $3) \
+             ON CONFLICT (channel_id, user_id) DO UPDATE SET name = $3",
+            params,
+        )
+    }
+
+    /// Let one of the user's characters use the mastery property of a weapon.
+    pub fn add_weapon_mastery(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        weapon_name: WeaponName,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &weapon_name.as_str(),
+        ];
+        connection.execute(
+            "INSERT OR IGNORE INTO character_weapon_masteries (channel_id, user_id, name, weapon_name) \
+             VALUES ($1, $2, $3, $4)",
+            params,
+        )
+    }
+
+    /// Stop one of the user's characters using the mastery property of a weapon. Returns the
+    /// number of masteries removed.
+    pub fn remove_weapon_mastery(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        weapon_name: WeaponName,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &weapon_name.as_str(),
+        ];
+        connection.execute(
+            "DELETE FROM character_weapon_masteries \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             AND weapon_name = $4",
+            params,
+        )
+    }
+
+    /// Set the name shown in rolls for one of the user's characters, creating the character if it
+    /// doesn't exist yet.
+    pub fn set_display_name(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        display_name: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &display_name,
+        ];
+        connection.execute(
+            "INSERT INTO characters (channel_id, user_id, name, display_name) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET display_name = $4",
+            params,
+        )
+    }
+
+    /// Set the portrait shown in rolls for one of the user's characters, creating the character if
+    /// it doesn't exist yet.
+    pub fn set_avatar_url(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        avatar_url: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &avatar_url,
+        ];
+        connection.execute(
+            "INSERT INTO characters (channel_id, user_id, name, avatar_url) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET avatar_url = $4",
+            params,
+        )
+    }
+
+    /// Give one of the user's characters inspiration, creating the character if it doesn't exist
+    /// yet. Returns whether the character didn't already have inspiration.
+    pub fn grant_inspiration(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection
+            .execute(
+                "INSERT INTO characters (channel_id, user_id, name, inspiration) VALUES ($1, $2, $3, 1) \
+                 ON CONFLICT (channel_id, user_id, name) DO UPDATE SET inspiration = 1 \
+                 WHERE inspiration = 0",
+                params,
+            )
+            .map(|count| count > 0)
+    }
+
+    /// Spend the inspiration of one of the user's characters. Returns whether the character had
+    /// inspiration to spend.
+    pub fn use_inspiration(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection
+            .execute(
+                "UPDATE characters SET inspiration = 0 \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3 \
+                 AND inspiration = 1",
+                params,
+            )
+            .map(|count| count > 0)
+    }
+
+    /// Give one of the user's characters a Bardic Inspiration die with the given number of sides,
+    /// creating the character if it doesn't exist yet. A character can only have one Bardic
+    /// Inspiration die at a time, so returns false if they already have one.
+    pub fn grant_bardic_inspiration(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        die: i32,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name, &die];
+        connection
+            .execute(
+                "INSERT INTO characters (channel_id, user_id, name, bardic_inspiration) VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT (channel_id, user_id, name) DO UPDATE SET bardic_inspiration = $4 \
+                 WHERE bardic_inspiration IS NULL",
+                params,
+            )
+            .map(|count| count > 0)
+    }
+
+    /// Spend the Bardic Inspiration die of one of the user's characters. Returns the number of
+    /// sides on the die, or None if the character doesn't have one to spend.
+    pub fn use_bardic_inspiration(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Option<i32>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let die: Option<i32> = connection
+            .query_row(
+                "SELECT bardic_inspiration \
+                 FROM characters \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3",
+                params,
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        if die.is_some() {
+            connection.execute(
+                "UPDATE characters SET bardic_inspiration = NULL \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3",
+                params,
+            )?;
+        }
+        Ok(die)
+    }
+
+    /// Find the user and name of the active character that something is being given to. A
+    /// character given by name is matched against the name or display name of each active
+    /// character in the channel, so that "Connie" finds a character shown as "Connie the Monk".
+    pub fn find_recipient(
+        connection: &Connection,
+        channel_id: ChannelId,
+        recipient: &Recipient,
+    ) -> RusqliteResult<Option<(UserId, String)>> {
+        match recipient {
+            Recipient::User(user_id) => {
+                Character::get_active_name(connection, channel_id, *user_id)
+                    .map(|name| Some((*user_id, name)))
+            }
+            Recipient::Character(name) => {
+                let params: &[&dyn ToSql] =
+                    &[&channel_id.to_string(), &DEFAULT_CHARACTER_NAME, &name];
+                connection
+                    .query_row(
+                        "SELECT characters.user_id, characters.name \
+                         FROM characters \
+                         LEFT JOIN active_characters \
+                         ON active_characters.channel_id = characters.channel_id \
+                         AND active_characters.user_id = characters.user_id \
+                         WHERE characters.channel_id = $1 \
+                         AND characters.name = COALESCE(active_characters.name, $2) \
+                         AND (characters.name = $3 \
+                         OR characters.display_name = $3 COLLATE NOCASE \
+                         OR characters.display_name LIKE $3 || ' %') \
+                         ORDER BY characters.user_id \
+                         LIMIT 1",
+                        params,
+                        |row| {
+                            let user_id: String = row.get("user_id")?;
+                            Ok((
+                                UserId(user_id.parse().unwrap_or_default()),
+                                row.get("name")?,
+                            ))
+                        },
+                    )
+                    .optional()
+            }
+        }
+    }
+
+    /// Make one of the user's characters a single class character, replacing any other classes.
+    /// All of the character's levels are given to the class, or a single level if their level
+    /// isn't set yet.
+    pub fn set_class(
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        class: Class,
+        subclass: Option<&str>,
+    ) -> RusqliteResult<()> {
+        let channel_id = channel_id.to_string();
+        let user_id = user_id.to_string();
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[&channel_id, &user_id, &name];
+        transaction.execute(
+            "INSERT INTO characters (channel_id, user_id, name, level) VALUES ($1, $2, $3, 1) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET level = COALESCE(level, 1)",
+            params,
+        )?;
+        transaction.execute(
+            "DELETE FROM character_classes \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )?;
+        let params: &[&dyn ToSql] = &[&channel_id, &user_id, &name, &class, &subclass];
+        transaction.execute(
+            "INSERT INTO character_classes (channel_id, user_id, name, class, subclass, level, position) \
+             SELECT channel_id, user_id, name, $4, $5, level, 0 \
+             FROM characters \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )?;
+        transaction.commit()
+    }
+
+    /// Set the number of levels one of the user's characters has in a class, keeping any other
+    /// classes, and update their character level to the total of their class levels. The subclass
+    /// is kept if none is given.
+    pub fn set_class_level(
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        class: Class,
+        level: i32,
+        subclass: Option<&str>,
+    ) -> RusqliteResult<()> {
+        let channel_id = channel_id.to_string();
+        let user_id = user_id.to_string();
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[&channel_id, &user_id, &name];
+        transaction.execute(
+            "INSERT OR IGNORE INTO characters (channel_id, user_id, name) VALUES ($1, $2, $3)",
+            params,
+        )?;
+        let params: &[&dyn ToSql] = &[&channel_id, &user_id, &name, &class, &subclass, &level];
+        transaction.execute(
+            "INSERT INTO character_classes (channel_id, user_id, name, class, subclass, level, position) \
+             VALUES ($1, $2, $3, $4, $5, $6, ( \
+               SELECT COUNT(*) FROM character_classes \
+               WHERE channel_id = $1 \
+               AND user_id = $2 \
+               AND name = $3)) \
+             ON CONFLICT (channel_id, user_id, name, class) DO UPDATE SET \
+             subclass = COALESCE(excluded.subclass, subclass), \
+             level = excluded.level",
+            params,
+        )?;
+        let params: &[&dyn ToSql] = &[&channel_id, &user_id, &name];
+        transaction.execute(
+            "UPDATE characters \
+             SET level = ( \
+               SELECT SUM(level) FROM character_classes \
+               WHERE channel_id = $1 \
+               AND user_id = $2 \
+               AND name = $3) \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )?;
+        transaction.commit()
+    }
+
     pub fn from_row(row: &Row) -> RusqliteResult<Character> {
         Ok(Character {
+            name: row.get("name")?,
+            display_name: row.get("display_name")?,
             avatar_url: row.get("avatar_url")?,
             level: row.get("level")?,
+            experience: row.get("experience")?,
+            armor_class: row.get("armor_class")?,
+            classes: Vec::new(),
+            bonuses: Vec::new(),
+            weapon_masteries: Vec::new(),
             jack_of_all_trades: row.get("jack_of_all_trades")?,
             martial_arts: row.get("martial_arts")?,
+            initiative_bonus: row.get("initiative_bonus")?,
+            alert: row.get("alert")?,
+            observant: row.get("observant")?,
+            fighting_style: row.get("fighting_style")?,
 
             strength: row.get("strength")?,
             dexterity: row.get("dexterity")?,
@@ -148,24 +834,452 @@ impl Character {
         })
     }
 
-    pub fn avatar_url(&self) -> Option<&str> {
-        self.avatar_url.as_deref()
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name shown in rolls in place of the user's nickname, such as "Connie the Monk".
+    pub fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref()
+    }
+
+    pub fn avatar_url(&self) -> Option<&str> {
+        self.avatar_url.as_deref()
+    }
+
+    pub fn classes(&self) -> &[ClassLevel] {
+        &self.classes
+    }
+
+    /// The class the character has the most levels in, or the first class they took if there's a
+    /// tie.
+    pub fn class(&self) -> Option<Class> {
+        self.classes
+            .iter()
+            .fold(None, |main: Option<&ClassLevel>, class| match main {
+                Some(main) if main.level >= class.level => Some(main),
+                _ => Some(class),
+            })
+            .map(|main| main.class)
+    }
+
+    /// The number of levels the character has in a class.
+    pub fn class_level(&self, class: Class) -> i32 {
+        self.classes
+            .iter()
+            .filter(|class_level| class_level.class == class)
+            .map(|class_level| class_level.level)
+            .sum()
+    }
+
+    /// The character's total level, or the sum of their class levels if the level isn't set.
+    pub fn level(&self) -> Option<i32> {
+        if self.level.is_none() && !self.classes.is_empty() {
+            Some(self.classes.iter().map(|class| class.level).sum())
+        } else {
+            self.level
+        }
+    }
+
+    /// Whether the character adds half their proficiency bonus to checks they aren't proficient
+    /// in, which bards gain at 2nd level.
+    pub fn jack_of_all_trades(&self) -> bool {
+        self.jack_of_all_trades
+            .unwrap_or_else(|| self.class_level(Class::Bard) >= 2)
+    }
+
+    /// Whether the character can use Martial Arts, which monks gain at 1st level.
+    pub fn martial_arts(&self) -> bool {
+        self.martial_arts
+            .unwrap_or_else(|| self.class_level(Class::Monk) >= 1)
+    }
+
+    /// The Martial Arts damage die, which depends on the character's monk levels and the edition
+    /// of the rules. A character with Martial Arts turned on but no monk levels uses their total
+    /// level instead.
+    pub fn martial_arts_damage_die(&self, edition: Edition) -> Option<i32> {
+        if self.martial_arts() {
+            let monk_level = match self.class_level(Class::Monk) {
+                0 => self.level()?,
+                monk_level => monk_level,
+            };
+            Some(edition.martial_arts_damage_die(monk_level))
+        } else {
+            None
+        }
+    }
+
+    /// The number of sides on the Bardic Inspiration die the character gives, which depends on
+    /// their bard levels: a d6 at 1st level, increasing to a d8 at 5th, a d10 at 10th, and a d12
+    /// at 15th.
+    pub fn bardic_inspiration_die(&self) -> Option<i32> {
+        match self.class_level(Class::Bard) {
+            0 => None,
+            bard_level => Some((2 * (bard_level / 5) + 6).min(12)),
+        }
+    }
+
+    /// The number of attacks the character makes when they take the Attack action. Barbarians,
+    /// fighters, monks, paladins and rangers gain Extra Attack at 5th level, and fighters gain a
+    /// third attack at 11th level and a fourth at 20th. Extra Attack from more than one class
+    /// doesn't add together.
+    pub fn attacks(&self) -> usize {
+        let fighter_attacks = match self.class_level(Class::Fighter) {
+            level if level >= 20 => 4,
+            level if level >= 11 => 3,
+            level if level >= 5 => 2,
+            _ => 1,
+        };
+        let extra_attack = [Class::Barbarian, Class::Monk, Class::Paladin, Class::Ranger]
+            .iter()
+            .any(|class| self.class_level(*class) >= 5);
+        if extra_attack {
+            fighter_attacks.max(2)
+        } else {
+            fighter_attacks
+        }
+    }
+
+    /// The Sneak Attack damage roll for a rogue, which starts at 1d6 and increases by 1d6 at every
+    /// odd rogue level.
+    pub fn sneak_attack(&self) -> Option<Roll> {
+        match self.class_level(Class::Rogue) {
+            0 => None,
+            rogue_level => Some(Roll::new_unsafe(((rogue_level + 1) / 2) as usize, 6, 0)),
+        }
+    }
+
+    /// The character's hit dice, one roll for each size of hit die from their classes, largest
+    /// first.
+    pub fn hit_dice(&self) -> Vec<Roll> {
+        let mut dice: Vec<(i32, i32)> = Vec::new();
+        for class in self.classes.iter() {
+            let sides = class.class.hit_die();
+            match dice.iter_mut().find(|(die, _)| *die == sides) {
+                Some((_, count)) => *count += class.level,
+                None => dice.push((sides, class.level)),
+            }
+        }
+        dice.sort_by_key(|(sides, _)| Reverse(*sides));
+        dice.into_iter()
+            .map(|(sides, count)| Roll::new_unsafe(count as usize, sides, 0))
+            .collect()
+    }
+
+    /// Change the character's level, keeping their class levels in step. Levels are added to the
+    /// main class, and removed from the most recently taken classes first, with every class
+    /// keeping at least one level until there are more classes than levels.
+    pub fn scale_to_level(&mut self, level: i32) {
+        let class_levels: i32 = self.classes.iter().map(|class| class.level).sum();
+        if class_levels < level {
+            let main_class = self.class();
+            if let Some(main) = self
+                .classes
+                .iter_mut()
+                .find(|class| Some(class.class) == main_class)
+            {
+                main.level += level - class_levels;
+            }
+        } else {
+            let mut excess = class_levels - level;
+            for class in self.classes.iter_mut().rev() {
+                let removed = excess.min(class.level - 1);
+                class.level -= removed;
+                excess -= removed;
+            }
+            while excess > 0 {
+                match self.classes.pop() {
+                    Some(class) => excess -= class.level,
+                    None => break,
+                }
+            }
+        }
+        self.level = Some(level);
+    }
+
+    pub fn experience(&self) -> i32 {
+        self.experience
+    }
+
+    /// Add experience points, levelling the character up if they reach the threshold for a higher
+    /// level. New levels are added to the character's main class. Returns the new level if the
+    /// character levelled up.
+    pub fn gain_experience(&mut self, amount: i32) -> Option<i32> {
+        self.experience += amount;
+        let level = experience::level_for_experience(self.experience);
+        if level > self.level().unwrap_or(1) {
+            self.scale_to_level(level);
+            Some(level)
+        } else {
+            None
+        }
+    }
+
+    /// Save the character's experience points, along with their level and class levels, which
+    /// change when they level up.
+    pub fn set_experience(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<()> {
+        let channel_id = channel_id.to_string();
+        let user_id = user_id.to_string();
+        let params: &[&dyn ToSql] = &[
+            &channel_id,
+            &user_id,
+            &self.name,
+            &self.experience,
+            &self.level,
+        ];
+        connection.execute(
+            "UPDATE characters \
+             SET experience = $4, level = $5 \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )?;
+        for class in self.classes.iter() {
+            let params: &[&dyn ToSql] = &[
+                &channel_id,
+                &user_id,
+                &self.name,
+                &class.class,
+                &class.level,
+            ];
+            connection.execute(
+                "UPDATE character_classes \
+                 SET level = $5 \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3 \
+                 AND class = $4",
+                params,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The character's spell slots, combining the levels of all of their spellcasting classes.
+    pub fn spell_slots(&self) -> SpellSlots {
+        SpellSlots::new(&self.classes)
+    }
+
+    /// The character's armour class. Unless they've set it, this is their armour class without
+    /// armour, using Unarmored Defense if they are a barbarian or monk.
+    pub fn armor_class(&self) -> Option<i32> {
+        self.armor_class.or_else(|| {
+            let dexterity = self.dexterity()?.modifier;
+            let unarmored_defense = [
+                (Class::Barbarian, AbilityName::Constitution),
+                (Class::Monk, AbilityName::Wisdom),
+            ]
+            .iter()
+            .filter(|(class, _)| self.class_level(*class) > 0)
+            .filter_map(|(_, ability)| self.ability(*ability).map(|ability| ability.modifier))
+            .max();
+            Some(10 + dexterity + unarmored_defense.unwrap_or(0).max(0))
+        })
+    }
+
+    /// Set the armour class of one of the user's characters, creating the character if it doesn't
+    /// exist yet.
+    pub fn set_armor_class(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        armor_class: i32,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &armor_class,
+        ];
+        connection.execute(
+            "INSERT INTO characters (channel_id, user_id, name, armor_class) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET armor_class = $4",
+            params,
+        )
+    }
+
+    /// Set one of the ability scores of one of the user's characters, creating the character if it
+    /// doesn't exist yet.
+    pub fn set_ability(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        ability: AbilityName,
+        score: i32,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name, &score];
+        connection.execute(
+            &format!(
+                "INSERT INTO characters (channel_id, user_id, name, {0}) VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT (channel_id, user_id, name) DO UPDATE SET {0} = $4",
+                ability.as_str().to_lowercase()
+            ),
+            params,
+        )
+    }
+
+    /// Set the level of one of the user's characters who hasn't taken levels in a class, creating
+    /// the character if it doesn't exist yet.
+    pub fn set_level(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        level: i32,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name, &level];
+        connection.execute(
+            "INSERT INTO characters (channel_id, user_id, name, level) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET level = $4",
+            params,
+        )
+    }
+
+    /// The modifier to the character's initiative rolls: their Dexterity modifier, plus their
+    /// initiative bonus, +5 if they have the Alert feat, and half their proficiency bonus if they
+    /// have Jack of All Trades.
+    pub fn initiative(&self) -> Option<i32> {
+        let jack_of_all_trades = if self.jack_of_all_trades() {
+            self.proficiency_bonus()
+                .map_or(0, |proficiency_bonus| proficiency_bonus / 2)
+        } else {
+            0
+        };
+        let alert = if self.alert { 5 } else { 0 };
+        Some(self.dexterity()?.modifier + self.initiative_bonus + alert + jack_of_all_trades)
+    }
+
+    /// The character's bonus to initiative rolls, not counting their Dexterity or the Alert feat.
+    pub fn initiative_bonus(&self) -> i32 {
+        self.initiative_bonus
+    }
+
+    /// Whether the character has the Alert feat.
+    pub fn alert(&self) -> bool {
+        self.alert
+    }
+
+    /// Whether the character has the Observant feat.
+    pub fn observant(&self) -> bool {
+        self.observant
+    }
+
+    pub fn fighting_style(&self) -> Option<FightingStyle> {
+        self.fighting_style
+    }
+
+    /// Set the initiative bonus of one of the user's characters, creating the character if it
+    /// doesn't exist yet.
+    pub fn set_initiative_bonus(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        initiative_bonus: i32,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &initiative_bonus,
+        ];
+        connection.execute(
+            "INSERT INTO characters (channel_id, user_id, name, initiative_bonus) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET initiative_bonus = $4",
+            params,
+        )
+    }
+
+    /// Set whether one of the user's characters has the Alert feat, creating the character if it
+    /// doesn't exist yet.
+    pub fn set_alert(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        alert: bool,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name, &alert];
+        connection.execute(
+            "INSERT INTO characters (channel_id, user_id, name, alert) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET alert = $4",
+            params,
+        )
+    }
+
+    /// Set whether one of the user's characters has the Observant feat, creating the character if
+    /// it doesn't exist yet.
+    pub fn set_observant(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        observant: bool,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &observant,
+        ];
+        connection.execute(
+            "INSERT INTO characters (channel_id, user_id, name, observant) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET observant = $4",
+            params,
+        )
+    }
+
+    /// Set the fighting style of one of the user's characters, or remove it, creating the
+    /// character if it doesn't exist yet.
+    pub fn set_fighting_style(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        fighting_style: Option<FightingStyle>,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &fighting_style,
+        ];
+        connection.execute(
+            "INSERT INTO characters (channel_id, user_id, name, fighting_style) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET fighting_style = $4",
+            params,
+        )
+    }
+
+    pub fn bonuses(&self) -> &[Bonus] {
+        &self.bonuses
+    }
+
+    /// The weapons whose mastery properties the character can use.
+    pub fn weapon_masteries(&self) -> &[WeaponName] {
+        &self.weapon_masteries
     }
 
-    pub fn martial_arts(&self) -> bool {
-        self.martial_arts
+    /// The total of the character's bonuses to an ability check, skill check or saving throw.
+    pub fn check_bonus(&self, check: Check) -> i32 {
+        bonus::total(&self.bonuses, |target| target.applies_to(check))
     }
 
-    pub fn martial_arts_damage_die(&self) -> Option<i32> {
-        if self.martial_arts {
-            Some(2 * ((self.level? + 1) / 6) + 4)
-        } else {
-            None
-        }
+    /// The total of the character's bonuses to attack rolls.
+    pub fn attack_bonus(&self) -> i32 {
+        bonus::total(&self.bonuses, |target| target == BonusTarget::AttackRolls)
     }
 
     pub fn proficiency_bonus(&self) -> Option<i32> {
-        self.level.map(|level| (level - 1) / 4 + 2)
+        self.level().map(|level| (level - 1) / 4 + 2)
     }
 
     // Abilities
@@ -205,6 +1319,15 @@ impl Character {
         Character::make_ability(self.charisma)
     }
 
+    /// Take on the Strength, Dexterity and Constitution of a beast form, in the order of the
+    /// abilities in a stat block, as with Wild Shape. The character keeps their own Intelligence,
+    /// Wisdom and Charisma, and their proficiencies.
+    pub fn assume_form(&mut self, abilities: &[i32; 6]) {
+        self.strength = Some(abilities[0]);
+        self.dexterity = Some(abilities[1]);
+        self.constitution = Some(abilities[2]);
+    }
+
     fn make_ability(score: Option<i32>) -> Option<Ability> {
         Some(Ability {
             score: score?,
@@ -365,7 +1488,7 @@ impl Character {
     fn make_skill(&self, ability: Option<Ability>, proficiency: Proficiency) -> Option<Skill> {
         let proficiency_bonus = self.proficiency_bonus()?;
         let bonus = match proficiency {
-            Proficiency::Normal if !self.jack_of_all_trades => 0,
+            Proficiency::Normal if !self.jack_of_all_trades() => 0,
             Proficiency::Normal => proficiency_bonus / 2,
             Proficiency::Proficient => proficiency_bonus,
             Proficiency::Expert => 2 * proficiency_bonus,
@@ -377,6 +1500,7 @@ impl Character {
     }
 
     pub fn has_weapon_proficiency(
+        &self,
         connection: &Connection,
         channel_id: ChannelId,
         user_id: UserId,
@@ -386,6 +1510,7 @@ impl Character {
         let params: &[&dyn ToSql] = &[
             &channel_id.to_string(),
             &user_id.to_string(),
+            &self.name,
             &name.as_str(),
             &category.as_str(),
         ];
@@ -394,17 +1519,490 @@ impl Character {
                 "SELECT true \
              FROM character_weapon_proficiencies \
              WHERE channel_id = $1 \
-             AND user_id = $2
-             AND (weapon_name = $3 OR weapon_category = $4)",
+             AND user_id = $2 \
+             AND name = $3 \
+             AND (weapon_name = $4 OR weapon_category = $5)",
                 params,
                 |row| row.get(0),
             )
             .optional()
             .map(|result| result.unwrap_or(false))
     }
+
+    pub fn weapon_proficiencies(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<Vec<WeaponProficiency>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &self.name];
+        let mut statement = connection.prepare(
+            "SELECT weapon_name, weapon_category \
+             FROM character_weapon_proficiencies \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             ORDER BY weapon_category, weapon_name",
+        )?;
+        let rows = statement.query_map(params, |row| {
+            let weapon_name: Option<WeaponName> = row.get("weapon_name")?;
+            let weapon_category: Option<Category> = row.get("weapon_category")?;
+            Ok(weapon_name
+                .map(WeaponProficiency::Weapon)
+                .or_else(|| weapon_category.map(WeaponProficiency::Category)))
+        })?;
+        rows.filter_map(|row| row.transpose()).collect()
+    }
+}
+
+/// A proficiency with either a specific weapon, or a category of weapons.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum WeaponProficiency {
+    Weapon(WeaponName),
+    Category(Category),
+}
+
+impl WeaponProficiency {
+    pub fn parse(string: &str) -> Option<WeaponProficiency> {
+        WeaponName::parse(string)
+            .map(WeaponProficiency::Weapon)
+            .or_else(|| Category::parse(string).map(WeaponProficiency::Category))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            WeaponProficiency::Weapon(name) => name.as_str(),
+            WeaponProficiency::Category(category) => category.as_str(),
+        }
+    }
+}
+
+impl From<WeaponProficiency> for String {
+    fn from(proficiency: WeaponProficiency) -> String {
+        proficiency.as_str().to_owned()
+    }
+}
+
+impl TryFrom<String> for WeaponProficiency {
+    type Error = String;
+
+    fn try_from(string: String) -> Result<WeaponProficiency, String> {
+        WeaponProficiency::parse(&string)
+            .ok_or_else(|| format!("Invalid value for weapon proficiency (value = {})", string))
+    }
+}
+
+/// Which of the user's characters to copy into the current channel, and the level to copy them at.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CharacterCopy {
+    /// The character's name, or the active character if not given.
+    pub name: Option<String>,
+    /// The channel to copy the character from, if the user has characters in more than one.
+    pub channel_id: Option<ChannelId>,
+    pub level: Option<i32>,
 }
 
+/// Where to import a character sheet from.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImportSource {
+    /// A file exported by Dungeon Helper, attached to the message.
+    Attachment,
+    /// A public character on D&D Beyond, by character ID.
+    DndBeyond(u64),
+}
+
+/// Who is being given something, such as Bardic Inspiration: the active character of a mentioned
+/// user, or an active character in the channel by name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Recipient {
+    User(UserId),
+    Character(String),
+}
+
+/// A portable copy of a character sheet, used to back up a character or move it between servers.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CharacterExport {
+    pub character: Character,
+    pub weapon_proficiencies: Vec<WeaponProficiency>,
+}
+
+impl CharacterExport {
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<Option<CharacterExport>> {
+        Character::get_active_name(connection, channel_id, user_id)
+            .and_then(|name| CharacterExport::get_by_name(connection, channel_id, user_id, &name))
+    }
+
+    pub fn get_by_name(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Option<CharacterExport>> {
+        Character::get_by_name(connection, channel_id, user_id, name)?
+            .map(|character| {
+                character
+                    .weapon_proficiencies(connection, channel_id, user_id)
+                    .map(|weapon_proficiencies| CharacterExport {
+                        character,
+                        weapon_proficiencies,
+                    })
+            })
+            .transpose()
+    }
+
+    /// A file name for the export, derived from the character's name.
+    pub fn file_name(&self) -> String {
+        let name: String = self
+            .character
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("{}.json", name)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parse a character sheet previously exported with `to_json`.
+    pub fn from_json(json: &str) -> serde_json::Result<CharacterExport> {
+        serde_json::from_str(json)
+    }
+
+    /// Check that the exported attributes are within the ranges allowed by the rules, returning a
+    /// description of each invalid field.
+    pub fn validate(&self) -> Vec<String> {
+        let character = &self.character;
+        let mut errors = Vec::new();
+        if character.name.trim().is_empty() {
+            errors.push("name must not be empty".to_owned());
+        }
+        let names = [
+            ("name", Some(&character.name)),
+            ("display_name", character.display_name.as_ref()),
+        ];
+        for (field, name) in names.iter() {
+            if let Some(name) = name {
+                let length = name.chars().count();
+                if length > MAXIMUM_DISPLAY_NAME_LENGTH {
+                    errors.push(format!(
+                        "{} must be at most {} characters, but was {}",
+                        field, MAXIMUM_DISPLAY_NAME_LENGTH, length
+                    ));
+                }
+            }
+        }
+        if let Some(level) = character.level() {
+            if level < 1 || level > 20 {
+                errors.push(format!("level must be between 1 and 20, but was {}", level));
+            }
+        }
+        for (index, class) in character.classes.iter().enumerate() {
+            if class.level < 1 {
+                errors.push(format!(
+                    "{} level must be at least 1, but was {}",
+                    class.class, class.level
+                ));
+            }
+            if character.classes[..index]
+                .iter()
+                .any(|previous| previous.class == class.class)
+            {
+                errors.push(format!("{} must only be listed once", class.class));
+            }
+        }
+        if let Some(level) = character.level {
+            let class_levels: i32 = character.classes.iter().map(|class| class.level).sum();
+            if class_levels > level {
+                errors.push(format!(
+                    "class levels add up to {}, which is more than level {}",
+                    class_levels, level
+                ));
+            }
+        }
+        if let Some(armor_class) = character.armor_class {
+            if armor_class < 1 || armor_class > 30 {
+                errors.push(format!(
+                    "armor_class must be between 1 and 30, but was {}",
+                    armor_class
+                ));
+            }
+        }
+        if character.initiative_bonus < -10 || character.initiative_bonus > 10 {
+            errors.push(format!(
+                "initiative_bonus must be between -10 and 10, but was {}",
+                character.initiative_bonus
+            ));
+        }
+        let abilities = [
+            ("strength", character.strength),
+            ("dexterity", character.dexterity),
+            ("constitution", character.constitution),
+            ("intelligence", character.intelligence),
+            ("wisdom", character.wisdom),
+            ("charisma", character.charisma),
+        ];
+        for (field, score) in abilities.iter() {
+            if let Some(score) = score {
+                if *score < 1 || *score > 30 {
+                    errors.push(format!(
+                        "{} must be between 1 and 30, but was {}",
+                        field, score
+                    ));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Save the character and their weapon proficiencies, replacing any existing character with
+    /// the same name, and switch to the character. Either everything is saved, or nothing is.
+    pub fn save(
+        &self,
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<()> {
+        let channel_id = channel_id.to_string();
+        let user_id = user_id.to_string();
+        let name = &self.character.name;
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[
+            &channel_id,
+            &user_id,
+            name,
+            &self.character.display_name,
+            &self.character.avatar_url,
+            &self.character.level(),
+            &self.character.experience,
+            &self.character.armor_class,
+            &self.character.jack_of_all_trades,
+            &self.character.martial_arts,
+            &self.character.initiative_bonus,
+            &self.character.alert,
+            &self.character.observant,
+            &self.character.fighting_style,
+            &self.character.strength,
+            &self.character.dexterity,
+            &self.character.constitution,
+            &self.character.intelligence,
+            &self.character.wisdom,
+            &self.character.charisma,
+            &self.character.strength_saving_proficiency,
+            &self.character.dexterity_saving_proficiency,
+            &self.character.constitution_saving_proficiency,
+            &self.character.intelligence_saving_proficiency,
+            &self.character.wisdom_saving_proficiency,
+            &self.character.charisma_saving_proficiency,
+            &self.character.acrobatics_proficiency,
+            &self.character.animal_handling_proficiency,
+            &self.character.arcana_proficiency,
+            &self.character.athletics_proficiency,
+            &self.character.deception_proficiency,
+            &self.character.history_proficiency,
+            &self.character.insight_proficiency,
+            &self.character.intimidation_proficiency,
+            &self.character.investigation_proficiency,
+            &self.character.medicine_proficiency,
+            &self.character.nature_proficiency,
+            &self.character.perception_proficiency,
+            &self.character.performance_proficiency,
+            &self.character.persuasion_proficiency,
+            &self.character.religion_proficiency,
+            &self.character.sleight_of_hand_proficiency,
+            &self.character.stealth_proficiency,
+            &self.character.survival_proficiency,
+        ];
+        transaction.execute(
+            "INSERT INTO characters ( \
+             channel_id, \
+             user_id, \
+             name, \
+             display_name, \
+             avatar_url, \
+             level, \
+             experience, \
+             armor_class, \
+             jack_of_all_trades, \
+             martial_arts, \
+             initiative_bonus, \
+             alert, \
+             observant, \
+             fighting_style, \
+             strength, \
+             dexterity, \
+             constitution, \
+             intelligence, \
+             wisdom, \
+             charisma, \
+             strength_saving_proficiency, \
+             dexterity_saving_proficiency, \
+             constitution_saving_proficiency, \
+             intelligence_saving_proficiency, \
+             wisdom_saving_proficiency, \
+             charisma_saving_proficiency, \
+             acrobatics_proficiency, \
+             animal_handling_proficiency, \
+             arcana_proficiency, \
+             athletics_proficiency, \
+             deception_proficiency, \
+             history_proficiency, \
+             insight_proficiency, \
+             intimidation_proficiency, \
+             investigation_proficiency, \
+             medicine_proficiency, \
+             nature_proficiency, \
+             perception_proficiency, \
+             performance_proficiency, \
+             persuasion_proficiency, \
+             religion_proficiency, \
+             sleight_of_hand_proficiency, \
+             stealth_proficiency, \
+             survival_proficiency) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32, $33, $34, $35, $36, $37, $38, $39, $40, $41, $42, $43, $44) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET \
+             display_name = excluded.display_name, \
+             avatar_url = excluded.avatar_url, \
+             level = excluded.level, \
+             experience = excluded.experience, \
+             armor_class = excluded.armor_class, \
+             jack_of_all_trades = excluded.jack_of_all_trades, \
+             martial_arts = excluded.martial_arts, \
+             initiative_bonus = excluded.initiative_bonus, \
+             alert = excluded.alert, \
+             observant = excluded.observant, \
+             fighting_style = excluded.fighting_style, \
+             strength = excluded.strength, \
+             dexterity = excluded.dexterity, \
+             constitution = excluded.constitution, \
+             intelligence = excluded.intelligence, \
+             wisdom = excluded.wisdom, \
+             charisma = excluded.charisma, \
+             strength_saving_proficiency = excluded.strength_saving_proficiency, \
+             dexterity_saving_proficiency = excluded.dexterity_saving_proficiency, \
+             constitution_saving_proficiency = excluded.constitution_saving_proficiency, \
+             intelligence_saving_proficiency = excluded.intelligence_saving_proficiency, \
+             wisdom_saving_proficiency = excluded.wisdom_saving_proficiency, \
+             charisma_saving_proficiency = excluded.charisma_saving_proficiency, \
+             acrobatics_proficiency = excluded.acrobatics_proficiency, \
+             animal_handling_proficiency = excluded.animal_handling_proficiency, \
+             arcana_proficiency = excluded.arcana_proficiency, \
+             athletics_proficiency = excluded.athletics_proficiency, \
+             deception_proficiency = excluded.deception_proficiency, \
+             history_proficiency = excluded.history_proficiency, \
+             insight_proficiency = excluded.insight_proficiency, \
+             intimidation_proficiency = excluded.intimidation_proficiency, \
+             investigation_proficiency = excluded.investigation_proficiency, \
+             medicine_proficiency = excluded.medicine_proficiency, \
+             nature_proficiency = excluded.nature_proficiency, \
+             perception_proficiency = excluded.perception_proficiency, \
+             performance_proficiency = excluded.performance_proficiency, \
+             persuasion_proficiency = excluded.persuasion_proficiency, \
+             religion_proficiency = excluded.religion_proficiency, \
+             sleight_of_hand_proficiency = excluded.sleight_of_hand_proficiency, \
+             stealth_proficiency = excluded.stealth_proficiency, \
+             survival_proficiency = excluded.survival_proficiency",
+            params,
+        )?;
+        let params: &[&dyn ToSql] = &[&channel_id, &user_id, name];
+        transaction.execute(
+            "DELETE FROM character_weapon_proficiencies \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )?;
+        transaction.execute(
+            "DELETE FROM character_classes \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )?;
+        for (position, class) in self.character.classes.iter().enumerate() {
+            let position = position as i64;
+            let params: &[&dyn ToSql] = &[
+                &channel_id,
+                &user_id,
+                name,
+                &class.class,
+                &class.subclass,
+                &class.level,
+                &position,
+            ];
+            transaction.execute(
+                "INSERT INTO character_classes (channel_id, user_id, name, class, subclass, level, position) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                params,
+            )?;
+        }
+        transaction.execute(
+            "DELETE FROM character_bonuses \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )?;
+        for bonus in self.character.bonuses.iter() {
+            let params: &[&dyn ToSql] = &[
+                &channel_id,
+                &user_id,
+                name,
+                &bonus.target.to_string(),
+                &bonus.amount,
+                &bonus.source,
+            ];
+            transaction.execute(
+                "INSERT OR IGNORE INTO character_bonuses (channel_id, user_id, name, target, amount, source) \
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+                params,
+            )?;
+        }
+        transaction.execute(
+            "DELETE FROM character_weapon_masteries \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )?;
+        for weapon_name in self.character.weapon_masteries.iter() {
+            let params: &[&dyn ToSql] = &[&channel_id, &user_id, name, &weapon_name.as_str()];
+            transaction.execute(
+                "INSERT OR IGNORE INTO character_weapon_masteries (channel_id, user_id, name, weapon_name) \
+                 VALUES ($1, $2, $3, $4)",
+                params,
+            )?;
+        }
+        for proficiency in self.weapon_proficiencies.iter() {
+            let (weapon_name, weapon_category) = match proficiency {
+                WeaponProficiency::Weapon(weapon_name) => (Some(weapon_name.as_str()), None),
+                WeaponProficiency::Category(category) => (None, Some(category.as_str())),
+            };
+            let params: &[&dyn ToSql] =
+                &[&channel_id, &user_id, name, &weapon_name, &weapon_category];
+            transaction.execute(
+                "INSERT OR IGNORE INTO character_weapon_proficiencies \
+                 (channel_id, user_id, name, weapon_name, weapon_category) \
+                 VALUES ($1, $2, $3, $4, $5)",
+                params,
+            )?;
+        }
+        transaction.execute(
+            "INSERT INTO active_characters (channel_id, user_id, name) VALUES ($1, $2, $3) \
+             ON CONFLICT (channel_id, user_id) DO UPDATE SET name = $3",
+            params,
+        )?;
+        transaction.commit()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Proficiency {
     Normal,
     Proficient,
@@ -430,6 +2028,12 @@ impl Proficiency {
     }
 }
 
+impl Default for Proficiency {
+    fn default() -> Proficiency {
+        Proficiency::Normal
+    }
+}
+
 impl FromSql for Proficiency {
     fn column_result(value: ValueRef) -> FromSqlResult<Proficiency> {
         value.as_str().and_then(|string| {
@@ -465,6 +2069,84 @@ impl error::Error for InvalidProficiencyValueError {
     }
 }
 
+/// A fighting style, which a character learns from a class such as the fighter, paladin or
+/// ranger.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FightingStyle {
+    /// +2 to attack rolls with ranged weapons.
+    Archery,
+    /// +1 to AC while wearing armor.
+    Defense,
+    /// +2 to damage rolls with a melee weapon held in one hand, and no other weapons.
+    Dueling,
+    /// Rerolls 1s and 2s on the damage dice of melee weapons held in two hands.
+    GreatWeaponFighting,
+    /// Adds the ability modifier to the damage of off-hand attacks.
+    TwoWeaponFighting,
+}
+
+impl FightingStyle {
+    pub fn parse(string: &str) -> Option<FightingStyle> {
+        match string.trim().to_lowercase().replace('-', " ").as_str() {
+            "archery" => Some(FightingStyle::Archery),
+            "defense" | "defence" => Some(FightingStyle::Defense),
+            "dueling" | "duelling" => Some(FightingStyle::Dueling),
+            "great weapon fighting" | "gwf" => Some(FightingStyle::GreatWeaponFighting),
+            "two weapon fighting" | "twf" => Some(FightingStyle::TwoWeaponFighting),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            FightingStyle::Archery => "Archery",
+            FightingStyle::Defense => "Defense",
+            FightingStyle::Dueling => "Dueling",
+            FightingStyle::GreatWeaponFighting => "Great Weapon Fighting",
+            FightingStyle::TwoWeaponFighting => "Two-Weapon Fighting",
+        }
+    }
+}
+
+impl FromSql for FightingStyle {
+    fn column_result(value: ValueRef) -> FromSqlResult<FightingStyle> {
+        value.as_str().and_then(|string| {
+            FightingStyle::parse(string).ok_or_else(|| {
+                FromSqlError::Other(Box::new(InvalidFightingStyleValueError {
+                    value: string.to_owned(),
+                }))
+            })
+        })
+    }
+}
+
+impl ToSql for FightingStyle {
+    fn to_sql(&self) -> RusqliteResult<ToSqlOutput> {
+        self.as_str().to_sql()
+    }
+}
+
+#[derive(Debug)]
+struct InvalidFightingStyleValueError {
+    value: String,
+}
+
+impl fmt::Display for InvalidFightingStyleValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid value for fighting style (value = {})",
+            self.value
+        )
+    }
+}
+
+impl error::Error for InvalidFightingStyleValueError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Ability {
     pub score: i32,
@@ -540,6 +2222,28 @@ pub enum SkillName {
     Survival,
 }
 
+/// Every skill.
+const SKILL_NAMES: [SkillName; 18] = [
+    SkillName::Acrobatics,
+    SkillName::AnimalHandling,
+    SkillName::Arcana,
+    SkillName::Athletics,
+    SkillName::Deception,
+    SkillName::History,
+    SkillName::Insight,
+    SkillName::Intimidation,
+    SkillName::Investigation,
+    SkillName::Medicine,
+    SkillName::Nature,
+    SkillName::Perception,
+    SkillName::Performance,
+    SkillName::Persuasion,
+    SkillName::Religion,
+    SkillName::SleightOfHand,
+    SkillName::Stealth,
+    SkillName::Survival,
+];
+
 impl SkillName {
     pub fn parse(string: &str) -> Option<SkillName> {
         match string.to_lowercase().as_ref() {
@@ -565,6 +2269,12 @@ impl SkillName {
         }
     }
 
+    /// The skills with names closest in spelling to one that wasn't recognised, such as
+    /// "Stealth" for "stelth".
+    pub fn suggest(string: &str) -> Vec<SkillName> {
+        suggestion::closest(string, &SKILL_NAMES, SkillName::as_str)
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             SkillName::Acrobatics => "Acrobatics",
@@ -589,6 +2299,12 @@ impl SkillName {
     }
 }
 
+impl fmt::Display for SkillName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -597,10 +2313,21 @@ mod test {
     fn test_profiency_bonus() {
         fn character(level: Option<i32>) -> Character {
             Character {
+                name: DEFAULT_CHARACTER_NAME.to_owned(),
+                display_name: None,
                 avatar_url: None,
                 level,
-                jack_of_all_trades: false,
-                martial_arts: false,
+                experience: 0,
+                armor_class: None,
+                classes: Vec::new(),
+                bonuses: Vec::new(),
+                weapon_masteries: Vec::new(),
+                jack_of_all_trades: None,
+                martial_arts: None,
+                initiative_bonus: 0,
+                alert: false,
+                observant: false,
+                fighting_style: None,
 
                 strength: None,
                 dexterity: None,
@@ -649,10 +2376,21 @@ mod test {
     fn test_martial_arts_damage_die() {
         fn character(level: Option<i32>, martial_arts: bool) -> Character {
             Character {
+                name: DEFAULT_CHARACTER_NAME.to_owned(),
+                display_name: None,
                 avatar_url: None,
                 level,
-                jack_of_all_trades: false,
-                martial_arts,
+                experience: 0,
+                armor_class: None,
+                classes: Vec::new(),
+                bonuses: Vec::new(),
+                weapon_masteries: Vec::new(),
+                jack_of_all_trades: None,
+                martial_arts: Some(martial_arts),
+                initiative_bonus: 0,
+                alert: false,
+                observant: false,
+                fighting_style: None,
 
                 strength: None,
                 dexterity: None,
@@ -689,33 +2427,44 @@ mod test {
             }
         }
 
-        assert_eq!(character(None, false).martial_arts_damage_die(), None);
-        assert_eq!(character(Some(1), false).martial_arts_damage_die(), None);
-        assert_eq!(character(None, true).martial_arts_damage_die(), None);
-        assert_eq!(character(Some(1), true).martial_arts_damage_die(), Some(4));
-        assert_eq!(character(Some(4), true).martial_arts_damage_die(), Some(4));
-        assert_eq!(character(Some(5), true).martial_arts_damage_die(), Some(6));
-        assert_eq!(character(Some(10), true).martial_arts_damage_die(), Some(6));
-        assert_eq!(character(Some(11), true).martial_arts_damage_die(), Some(8));
-        assert_eq!(character(Some(16), true).martial_arts_damage_die(), Some(8));
-        assert_eq!(
-            character(Some(17), true).martial_arts_damage_die(),
-            Some(10)
-        );
-        assert_eq!(
-            character(Some(20), true).martial_arts_damage_die(),
-            Some(10)
-        );
+        let die = |level, martial_arts, edition| {
+            character(level, martial_arts).martial_arts_damage_die(edition)
+        };
+
+        assert_eq!(die(None, false, Edition::Rules2014), None);
+        assert_eq!(die(Some(1), false, Edition::Rules2014), None);
+        assert_eq!(die(None, true, Edition::Rules2014), None);
+        assert_eq!(die(Some(1), true, Edition::Rules2014), Some(4));
+        assert_eq!(die(Some(4), true, Edition::Rules2014), Some(4));
+        assert_eq!(die(Some(5), true, Edition::Rules2014), Some(6));
+        assert_eq!(die(Some(10), true, Edition::Rules2014), Some(6));
+        assert_eq!(die(Some(11), true, Edition::Rules2014), Some(8));
+        assert_eq!(die(Some(16), true, Edition::Rules2014), Some(8));
+        assert_eq!(die(Some(17), true, Edition::Rules2014), Some(10));
+        assert_eq!(die(Some(20), true, Edition::Rules2014), Some(10));
+        assert_eq!(die(Some(1), true, Edition::Rules2024), Some(6));
+        assert_eq!(die(Some(17), true, Edition::Rules2024), Some(12));
     }
 
     #[test]
     fn test_strength() {
         fn character(strength: Option<i32>) -> Character {
             Character {
+                name: DEFAULT_CHARACTER_NAME.to_owned(),
+                display_name: None,
                 avatar_url: None,
                 level: None,
-                jack_of_all_trades: false,
-                martial_arts: false,
+                experience: 0,
+                armor_class: None,
+                classes: Vec::new(),
+                bonuses: Vec::new(),
+                weapon_masteries: Vec::new(),
+                jack_of_all_trades: None,
+                martial_arts: None,
+                initiative_bonus: 0,
+                alert: false,
+                observant: false,
+                fighting_style: None,
 
                 strength,
                 dexterity: None,
@@ -836,10 +2585,21 @@ mod test {
     fn test_saving_throw() {
         fn character(strength: Option<i32>) -> Character {
             Character {
+                name: DEFAULT_CHARACTER_NAME.to_owned(),
+                display_name: None,
                 avatar_url: None,
                 level: None,
-                jack_of_all_trades: false,
-                martial_arts: false,
+                experience: 0,
+                armor_class: None,
+                classes: Vec::new(),
+                bonuses: Vec::new(),
+                weapon_masteries: Vec::new(),
+                jack_of_all_trades: None,
+                martial_arts: None,
+                initiative_bonus: 0,
+                alert: false,
+                observant: false,
+                fighting_style: None,
 
                 strength,
                 dexterity: None,
@@ -964,10 +2724,21 @@ mod test {
             athletics_proficiency: Proficiency,
         ) -> Character {
             Character {
+                name: DEFAULT_CHARACTER_NAME.to_owned(),
+                display_name: None,
                 avatar_url: None,
                 level,
-                jack_of_all_trades: false,
-                martial_arts: false,
+                experience: 0,
+                armor_class: None,
+                classes: Vec::new(),
+                bonuses: Vec::new(),
+                weapon_masteries: Vec::new(),
+                jack_of_all_trades: None,
+                martial_arts: None,
+                initiative_bonus: 0,
+                alert: false,
+                observant: false,
+                fighting_style: None,
 
                 strength,
                 dexterity: None,
@@ -1215,4 +2986,319 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn test_weapon_proficiency_json() {
+        let proficiencies = vec![
+            WeaponProficiency::Category(Category::Martial),
+            WeaponProficiency::Weapon(WeaponName::CrossbowHand),
+        ];
+        let json = serde_json::to_string(&proficiencies).unwrap();
+        assert_eq!(json, "[\"Martial\",\"Hand Crossbow\"]");
+        assert_eq!(
+            serde_json::from_str::<Vec<WeaponProficiency>>(&json).unwrap(),
+            proficiencies
+        );
+        assert!(serde_json::from_str::<WeaponProficiency>("\"Lightsaber\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_fighting_style() {
+        assert_eq!(
+            FightingStyle::parse("Great Weapon Fighting"),
+            Some(FightingStyle::GreatWeaponFighting)
+        );
+        assert_eq!(
+            FightingStyle::parse("two-weapon fighting"),
+            Some(FightingStyle::TwoWeaponFighting)
+        );
+        assert_eq!(
+            FightingStyle::parse("Defence"),
+            Some(FightingStyle::Defense)
+        );
+        assert_eq!(FightingStyle::parse("Protection"), None);
+    }
+
+    #[test]
+    fn test_character_export_validate() {
+        let export = CharacterExport::from_json(
+            r#"{
+                "character": { "name": "Connie", "level": 25, "strength": 0, "wisdom": 16 },
+                "weapon_proficiencies": ["Martial"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(export.character.wisdom, Some(16));
+        assert_eq!(export.character.stealth_proficiency, Proficiency::Normal);
+        assert_eq!(
+            export.validate(),
+            vec![
+                "level must be between 1 and 20, but was 25".to_owned(),
+                "strength must be between 1 and 30, but was 0".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_character_export_validate_names() {
+        let mut export = CharacterExport::from_json(
+            r#"{ "character": { "name": "Connie" }, "weapon_proficiencies": [] }"#,
+        )
+        .unwrap();
+        export.character.display_name = Some("C".repeat(MAXIMUM_DISPLAY_NAME_LENGTH));
+        assert!(export.validate().is_empty());
+        export.character.name = "C".repeat(MAXIMUM_DISPLAY_NAME_LENGTH + 1);
+        export.character.display_name = Some("C".repeat(MAXIMUM_DISPLAY_NAME_LENGTH + 2));
+        assert_eq!(
+            export.validate(),
+            vec![
+                "name must be at most 100 characters, but was 101".to_owned(),
+                "display_name must be at most 100 characters, but was 102".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_character_export_validate_classes() {
+        let export = CharacterExport::from_json(
+            r#"{
+                "character": {
+                    "name": "Connie",
+                    "classes": [
+                        { "class": "Monk", "level": 12 },
+                        { "class": "Rogue", "level": 0 },
+                        { "class": "Monk", "level": 9 }
+                    ]
+                },
+                "weapon_proficiencies": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            export.validate(),
+            vec![
+                "level must be between 1 and 20, but was 21".to_owned(),
+                "Rogue level must be at least 1, but was 0".to_owned(),
+                "Monk must only be listed once".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_character_export_json_round_trip() {
+        let export = CharacterExport {
+            character: Character {
+                name: "Connie".to_owned(),
+                level: Some(3),
+                classes: vec![ClassLevel {
+                    class: Class::Monk,
+                    subclass: Some("Way of Shadow".to_owned()),
+                    level: 3,
+                }],
+                dexterity: Some(16),
+                stealth_proficiency: Proficiency::Expert,
+                weapon_masteries: vec![WeaponName::Shortsword],
+                ..Character::default()
+            },
+            weapon_proficiencies: vec![WeaponProficiency::Category(Category::Simple)],
+        };
+        let json = export.to_json().unwrap();
+        assert_eq!(CharacterExport::from_json(&json).unwrap(), export);
+        assert!(CharacterExport::from_json("{\"character\": {\"level\": \"three\"}}").is_err());
+    }
+
+    fn character_with_classes(classes: &[(Class, i32)]) -> Character {
+        Character {
+            classes: classes
+                .iter()
+                .map(|(class, level)| ClassLevel {
+                    class: *class,
+                    subclass: None,
+                    level: *level,
+                })
+                .collect(),
+            ..Character::default()
+        }
+    }
+
+    #[test]
+    fn test_derived_class_features() {
+        let character = |class: Class, level: i32| character_with_classes(&[(class, level)]);
+        assert!(!character(Class::Bard, 1).jack_of_all_trades());
+        assert!(character(Class::Bard, 2).jack_of_all_trades());
+        assert!(!character(Class::Rogue, 2).jack_of_all_trades());
+        assert!(character(Class::Monk, 1).martial_arts());
+        assert!(!character(Class::Fighter, 1).martial_arts());
+        assert!(!character_with_classes(&[]).martial_arts());
+        assert_eq!(character(Class::Fighter, 3).sneak_attack(), None);
+        assert_eq!(character_with_classes(&[]).sneak_attack(), None);
+        assert_eq!(
+            character(Class::Rogue, 1).sneak_attack(),
+            Some(Roll::new_unsafe(1, 6, 0))
+        );
+        assert_eq!(
+            character(Class::Rogue, 4).sneak_attack(),
+            Some(Roll::new_unsafe(2, 6, 0))
+        );
+        assert_eq!(
+            character(Class::Rogue, 20).sneak_attack(),
+            Some(Roll::new_unsafe(10, 6, 0))
+        );
+    }
+
+    #[test]
+    fn test_multiclass_features() {
+        let character = character_with_classes(&[(Class::Monk, 4), (Class::Rogue, 5)]);
+        assert_eq!(character.level(), Some(9));
+        assert_eq!(character.proficiency_bonus(), Some(4));
+        assert_eq!(character.class(), Some(Class::Rogue));
+        assert_eq!(character.class_level(Class::Monk), 4);
+        // The martial arts die and sneak attack only count levels in their own class
+        assert_eq!(
+            character.martial_arts_damage_die(Edition::Rules2014),
+            Some(4)
+        );
+        assert_eq!(character.sneak_attack(), Some(Roll::new_unsafe(3, 6, 0)));
+        assert!(character.spell_slots().is_empty());
+
+        let character = character_with_classes(&[(Class::Bard, 1), (Class::Fighter, 1)]);
+        assert_eq!(character.class(), Some(Class::Bard));
+        assert!(!character.jack_of_all_trades());
+    }
+
+    #[test]
+    fn test_bardic_inspiration_die() {
+        assert_eq!(
+            character_with_classes(&[(Class::Fighter, 5)]).bardic_inspiration_die(),
+            None
+        );
+        assert_eq!(
+            character_with_classes(&[(Class::Bard, 1)]).bardic_inspiration_die(),
+            Some(6)
+        );
+        assert_eq!(
+            character_with_classes(&[(Class::Bard, 4), (Class::Warlock, 3)])
+                .bardic_inspiration_die(),
+            Some(6)
+        );
+        assert_eq!(
+            character_with_classes(&[(Class::Bard, 5)]).bardic_inspiration_die(),
+            Some(8)
+        );
+        assert_eq!(
+            character_with_classes(&[(Class::Bard, 10)]).bardic_inspiration_die(),
+            Some(10)
+        );
+        assert_eq!(
+            character_with_classes(&[(Class::Bard, 20)]).bardic_inspiration_die(),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn test_attacks() {
+        let attacks = |classes: &[(Class, i32)]| character_with_classes(classes).attacks();
+        assert_eq!(attacks(&[]), 1);
+        assert_eq!(attacks(&[(Class::Fighter, 4)]), 1);
+        assert_eq!(attacks(&[(Class::Fighter, 5)]), 2);
+        assert_eq!(attacks(&[(Class::Fighter, 11)]), 3);
+        assert_eq!(attacks(&[(Class::Fighter, 20)]), 4);
+        assert_eq!(attacks(&[(Class::Paladin, 5)]), 2);
+        assert_eq!(attacks(&[(Class::Wizard, 5)]), 1);
+        assert_eq!(attacks(&[(Class::Ranger, 5), (Class::Fighter, 5)]), 2);
+        assert_eq!(attacks(&[(Class::Monk, 5), (Class::Fighter, 11)]), 3);
+    }
+
+    #[test]
+    fn test_overridden_class_features() {
+        let bard = Character {
+            level: Some(5),
+            jack_of_all_trades: Some(false),
+            ..character_with_classes(&[(Class::Bard, 5)])
+        };
+        assert!(!bard.jack_of_all_trades());
+        let fighter = Character {
+            martial_arts: Some(true),
+            ..character_with_classes(&[(Class::Fighter, 5)])
+        };
+        assert!(fighter.martial_arts());
+        // Without any monk levels, the martial arts die uses the character's total level
+        assert_eq!(fighter.martial_arts_damage_die(Edition::Rules2014), Some(6));
+    }
+
+    #[test]
+    fn test_initiative() {
+        let fighter = Character {
+            dexterity: Some(14),
+            ..character_with_classes(&[(Class::Fighter, 5)])
+        };
+        assert_eq!(fighter.initiative(), Some(2));
+        let alert = Character {
+            initiative_bonus: 1,
+            alert: true,
+            ..fighter
+        };
+        assert_eq!(alert.initiative(), Some(8));
+        // Jack of All Trades adds half the bard's proficiency bonus, rounded down
+        let bard = Character {
+            dexterity: Some(14),
+            ..character_with_classes(&[(Class::Bard, 5)])
+        };
+        assert_eq!(bard.initiative(), Some(3));
+        assert_eq!(
+            character_with_classes(&[(Class::Bard, 5)]).initiative(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hit_dice() {
+        let character =
+            character_with_classes(&[(Class::Monk, 3), (Class::Fighter, 2), (Class::Rogue, 1)]);
+        assert_eq!(
+            character.hit_dice(),
+            vec![Roll::new_unsafe(2, 10, 0), Roll::new_unsafe(4, 8, 0)]
+        );
+        assert_eq!(Character::default().hit_dice(), vec![]);
+    }
+
+    #[test]
+    fn test_scale_to_level() {
+        let mut character = character_with_classes(&[(Class::Monk, 3), (Class::Rogue, 2)]);
+        character.scale_to_level(10);
+        assert_eq!(character.level(), Some(10));
+        assert_eq!(character.class_level(Class::Monk), 8);
+        assert_eq!(character.class_level(Class::Rogue), 2);
+        assert_eq!(
+            character.martial_arts_damage_die(Edition::Rules2014),
+            Some(6)
+        );
+
+        character.scale_to_level(3);
+        assert_eq!(character.class_level(Class::Monk), 2);
+        assert_eq!(character.class_level(Class::Rogue), 1);
+
+        character.scale_to_level(1);
+        assert_eq!(character.class_level(Class::Monk), 1);
+        assert_eq!(character.classes().len(), 1);
+
+        let mut character = Character::default();
+        character.scale_to_level(5);
+        assert_eq!(character.level(), Some(5));
+        assert_eq!(character.proficiency_bonus(), Some(3));
+    }
+
+    #[test]
+    fn test_gain_experience() {
+        let mut character = character_with_classes(&[(Class::Monk, 1)]);
+        assert_eq!(character.gain_experience(250), None);
+        assert_eq!(character.gain_experience(700), Some(3));
+        assert_eq!(character.experience(), 950);
+        assert_eq!(character.class_level(Class::Monk), 3);
+
+        // Characters above the level for their experience keep their level
+        let mut character = character_with_classes(&[(Class::Monk, 5)]);
+        assert_eq!(character.gain_experience(3000), None);
+        assert_eq!(character.level(), Some(5));
+    }
 }
@@ -0,0 +1,510 @@
+use crate::character_roll::Check;
+use crate::ongoing_effect::parse_rounds;
+use crate::roll::{Roll, RollResult};
+use rand::Rng;
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serenity::model::id::{ChannelId, UserId};
+use std::fmt;
+
+/// A spell or other effect that adds dice to some of a character's d20 rolls for a while, or
+/// subtracts them, such as Bless, Bane or Guidance.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CharacterEffect {
+    pub name: String,
+    pub roll: Roll,
+    /// Whether the dice are subtracted from the rolls, as they are for Bane.
+    pub penalty: bool,
+    pub targets: EffectTargets,
+    /// The number of rounds the effect lasts for, or None if it lasts until it's ended.
+    pub rounds: Option<i32>,
+    /// Whether the effect ends after the first roll it's added to, as Guidance does.
+    pub single_use: bool,
+}
+
+/// The kinds of d20 roll that an effect is added to.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct EffectTargets {
+    pub attack_rolls: bool,
+    pub saving_throws: bool,
+    pub checks: bool,
+}
+
+/// The dice rolled for an effect on a d20 roll.
+#[derive(Debug, Eq, PartialEq)]
+pub struct EffectRoll {
+    pub effect: CharacterEffect,
+    pub result: RollResult,
+}
+
+impl EffectTargets {
+    /// Whether an effect with these targets is added to a character roll. Initiative is a
+    /// Dexterity check, so it counts as a check.
+    pub fn applies_to(self, check: Check) -> bool {
+        match check {
+            Check::SavingThrow(_) => self.saving_throws,
+            Check::Ability(_) | Check::Initiative | Check::Skill(_) => self.checks,
+        }
+    }
+
+    fn parse(string: &str) -> Option<EffectTargets> {
+        lazy_static! {
+            static ref SEPARATOR: Regex = Regex::new(r"(?i) *(?:,|\band\b) *").unwrap();
+        }
+        let mut targets = EffectTargets::default();
+        for target in SEPARATOR.split(string.trim()) {
+            match target.trim().to_lowercase().as_str() {
+                "" => {}
+                "attack" | "attacks" | "attack roll" | "attack rolls" => {
+                    targets.attack_rolls = true
+                }
+                "save" | "saves" | "saving throw" | "saving throws" => targets.saving_throws = true,
+                "check" | "checks" | "ability check" | "ability checks" | "skill check"
+                | "skill checks" => targets.checks = true,
+                _ => return None,
+            }
+        }
+        if targets == EffectTargets::default() {
+            None
+        } else {
+            Some(targets)
+        }
+    }
+}
+
+impl fmt::Display for EffectTargets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut targets = Vec::new();
+        if self.attack_rolls {
+            targets.push("attack rolls");
+        }
+        if self.saving_throws {
+            targets.push("saving throws");
+        }
+        if self.checks {
+            targets.push("ability checks");
+        }
+        match targets.split_last() {
+            Some((last, [])) => write!(f, "{}", last),
+            Some((last, init)) => write!(f, "{} and {}", init.join(", "), last),
+            None => Ok(()),
+        }
+    }
+}
+
+impl CharacterEffect {
+    /// Parse an effect from the name of a spell that grants one, such as "bless" or "guidance for
+    /// 5 rounds", or from a description of a custom effect, such as "Emboldening Bond +1d4 to
+    /// attacks, saves and checks for 100 rounds".
+    pub fn parse(string: &str) -> Option<CharacterEffect> {
+        lazy_static! {
+            static ref CUSTOM_RE: Regex = Regex::new(
+                r"^(?i)(.+?) +([+-]) *(\d*d\d+) +to +([a-z ,]+?)(?: +for +(\d+) +rounds?)? *$"
+            )
+            .unwrap();
+            static ref SPELL_RE: Regex =
+                Regex::new(r"^(?i)([a-z ]+?)(?: +for +(\d+) +rounds?)? *$").unwrap();
+        }
+        let string = string.trim();
+        if let Some(captures) = CUSTOM_RE.captures(string) {
+            let name = captures.get(1)?.as_str().trim();
+            let roll = captures.get(3)?.as_str().to_lowercase();
+            let roll = if roll.starts_with('d') {
+                format!("1{}", roll)
+            } else {
+                roll
+            };
+            return Some(CharacterEffect {
+                name: name.to_owned(),
+                roll: Roll::parse(&roll).ok()?,
+                penalty: captures.get(2)?.as_str() == "-",
+                targets: EffectTargets::parse(captures.get(4)?.as_str())?,
+                rounds: parse_rounds(captures.get(5))?,
+                single_use: false,
+            });
+        }
+        let captures = SPELL_RE.captures(string)?;
+        let mut effect = CharacterEffect::spell(captures.get(1)?.as_str())?;
+        if let Some(rounds) = parse_rounds(captures.get(2))? {
+            effect.rounds = Some(rounds);
+        }
+        Some(effect)
+    }
+
+    /// The effect granted by a spell, lasting for as long as the spell does.
+    pub fn spell(name: &str) -> Option<CharacterEffect> {
+        let (name, penalty, targets, single_use) = match name.trim().to_lowercase().as_str() {
+            "bless" | "blessed" => (
+                "Bless",
+                false,
+                EffectTargets {
+                    attack_rolls: true,
+                    saving_throws: true,
+                    checks: false,
+                },
+                false,
+            ),
+            "bane" | "baned" => (
+                "Bane",
+                true,
+                EffectTargets {
+                    attack_rolls: true,
+                    saving_throws: true,
+                    checks: false,
+                },
+                false,
+            ),
+            "guidance" | "guided" => (
+                "Guidance",
+                false,
+                EffectTargets {
+                    attack_rolls: false,
+                    saving_throws: false,
+                    checks: true,
+                },
+                true,
+            ),
+            "resistance" => (
+                "Resistance",
+                false,
+                EffectTargets {
+                    attack_rolls: false,
+                    saving_throws: true,
+                    checks: false,
+                },
+                true,
+            ),
+            _ => return None,
+        };
+        Some(CharacterEffect {
+            name: name.to_owned(),
+            roll: Roll::new_unsafe(1, 4, 0),
+            penalty,
+            targets,
+            // Each of these spells lasts for up to a minute
+            rounds: Some(10),
+            single_use,
+        })
+    }
+
+    /// Retrieve the effects on a character, in the order they were added.
+    pub fn get_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Vec<CharacterEffect>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let mut statement = connection.prepare(
+            "SELECT effect, rolls, sides, penalty, attack_rolls, saving_throws, checks, rounds, single_use \
+             FROM character_effects \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(params, CharacterEffect::from_row)?;
+        rows.collect()
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<CharacterEffect> {
+        let rolls: i64 = row.get("rolls")?;
+        Ok(CharacterEffect {
+            name: row.get("effect")?,
+            roll: Roll::new_clamped(rolls as usize, row.get("sides")?, 0),
+            penalty: row.get("penalty")?,
+            targets: EffectTargets {
+                attack_rolls: row.get("attack_rolls")?,
+                saving_throws: row.get("saving_throws")?,
+                checks: row.get("checks")?,
+            },
+            rounds: row.get("rounds")?,
+            single_use: row.get("single_use")?,
+        })
+    }
+
+    /// Add the effect to a character, replacing any effect with the same name, so that casting a
+    /// spell again starts its duration over.
+    pub fn add(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<usize> {
+        let rolls = self.roll.rolls() as i64;
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &self.name,
+            &rolls,
+            &self.roll.sides(),
+            &self.penalty,
+            &self.targets.attack_rolls,
+            &self.targets.saving_throws,
+            &self.targets.checks,
+            &self.rounds,
+            &self.single_use,
+        ];
+        connection.execute(
+            "INSERT INTO character_effects (channel_id, user_id, name, effect, rolls, sides, penalty, attack_rolls, saving_throws, checks, rounds, single_use) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) \
+             ON CONFLICT (channel_id, user_id, name, effect) DO UPDATE SET \
+             effect = $4, rolls = $5, sides = $6, penalty = $7, attack_rolls = $8, saving_throws = $9, checks = $10, rounds = $11, single_use = $12",
+            params,
+        )
+    }
+
+    /// End one of the effects on a character. Returns the number of effects ended.
+    pub fn remove(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        effect: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &effect.trim(),
+        ];
+        connection.execute(
+            "DELETE FROM character_effects \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             AND effect = $4",
+            params,
+        )
+    }
+
+    /// End all of the effects on a character. Returns the number of effects ended.
+    pub fn clear(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection.execute(
+            "DELETE FROM character_effects WHERE channel_id = $1 AND user_id = $2 AND name = $3",
+            params,
+        )
+    }
+}
+
+impl fmt::Display for CharacterEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}{} to {}",
+            self.name,
+            if self.penalty { "-" } else { "+" },
+            self.roll,
+            self.targets
+        )?;
+        match self.rounds {
+            Some(1) => write!(f, " for 1 more round")?,
+            Some(rounds) => write!(f, " for {} more rounds", rounds)?,
+            None => {}
+        }
+        if self.single_use {
+            write!(f, " (once)")?;
+        }
+        Ok(())
+    }
+}
+
+impl EffectRoll {
+    /// The amount the effect adds to the d20 roll, which is negative for a penalty.
+    pub fn amount(&self) -> i32 {
+        if self.effect.penalty {
+            -self.result.result()
+        } else {
+            self.result.result()
+        }
+    }
+}
+
+impl fmt::Display for EffectRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}{}",
+            self.effect.name,
+            if self.effect.penalty { "-" } else { "+" },
+            self.result
+        )
+    }
+}
+
+/// The total amount that some effects add to a d20 roll.
+pub fn total(effects: &[EffectRoll]) -> i32 {
+    effects.iter().map(EffectRoll::amount).sum()
+}
+
+/// Roll the dice for each of the effects on a character that applies to a d20 roll, ending the
+/// single use effects that were rolled.
+pub fn apply<F, R>(
+    connection: &Connection,
+    channel_id: ChannelId,
+    user_id: UserId,
+    name: &str,
+    applies: F,
+    rng: &mut R,
+) -> RusqliteResult<Vec<EffectRoll>>
+where
+    F: Fn(EffectTargets) -> bool,
+    R: Rng + ?Sized,
+{
+    let mut effect_rolls = Vec::new();
+    for effect in CharacterEffect::get_all(connection, channel_id, user_id, name)? {
+        if applies(effect.targets) {
+            if effect.single_use {
+                CharacterEffect::remove(connection, channel_id, user_id, name, &effect.name)?;
+            }
+            let result = effect.roll.roll(rng);
+            effect_rolls.push(EffectRoll { effect, result });
+        }
+    }
+    Ok(effect_rolls)
+}
+
+/// Count down the rounds left on the effects on every character in a channel at the end of a
+/// round, ending the effects that have run out. Returns the effects that ended, as pairs of
+/// character name and effect name.
+pub fn end_round(
+    connection: &mut Connection,
+    channel_id: ChannelId,
+) -> RusqliteResult<Vec<(String, String)>> {
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "UPDATE character_effects SET rounds = rounds - 1 \
+         WHERE channel_id = $1 \
+         AND rounds IS NOT NULL",
+        &[&channel_id.to_string()],
+    )?;
+    let ended = {
+        let mut statement = transaction.prepare(
+            "SELECT name, effect FROM character_effects \
+             WHERE channel_id = $1 \
+             AND rounds <= 0 \
+             ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(&[&channel_id.to_string()], |row| {
+            Ok((
+                row.get::<_, String>("name")?,
+                row.get::<_, String>("effect")?,
+            ))
+        })?;
+        rows.collect::<RusqliteResult<Vec<(String, String)>>>()?
+    };
+    transaction.execute(
+        "DELETE FROM character_effects WHERE channel_id = $1 AND rounds <= 0",
+        &[&channel_id.to_string()],
+    )?;
+    transaction.commit()?;
+    Ok(ended)
+}
+
+/// End the effects with a duration on every character in a channel, as when an encounter ends.
+pub fn clear_timed(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+    connection.execute(
+        "DELETE FROM character_effects WHERE channel_id = $1 AND rounds IS NOT NULL",
+        &[&channel_id.to_string()],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::character::{AbilityName, SkillName};
+
+    #[test]
+    fn test_parse_spell() {
+        assert_eq!(
+            CharacterEffect::parse("bless"),
+            Some(CharacterEffect {
+                name: "Bless".to_owned(),
+                roll: Roll::new_unsafe(1, 4, 0),
+                penalty: false,
+                targets: EffectTargets {
+                    attack_rolls: true,
+                    saving_throws: true,
+                    checks: false,
+                },
+                rounds: Some(10),
+                single_use: false,
+            })
+        );
+        assert_eq!(
+            CharacterEffect::parse("Bane for 3 rounds")
+                .map(|effect| (effect.penalty, effect.rounds)),
+            Some((true, Some(3)))
+        );
+        assert_eq!(
+            CharacterEffect::parse("guidance").map(|effect| effect.single_use),
+            Some(true)
+        );
+        assert_eq!(CharacterEffect::parse("haste"), None);
+        assert_eq!(CharacterEffect::parse("bless for 0 rounds"), None);
+    }
+
+    #[test]
+    fn test_parse_custom() {
+        assert_eq!(
+            CharacterEffect::parse("Emboldening Bond +d4 to attacks, saves and checks"),
+            Some(CharacterEffect {
+                name: "Emboldening Bond".to_owned(),
+                roll: Roll::new_unsafe(1, 4, 0),
+                penalty: false,
+                targets: EffectTargets {
+                    attack_rolls: true,
+                    saving_throws: true,
+                    checks: true,
+                },
+                rounds: None,
+                single_use: false,
+            })
+        );
+        assert_eq!(
+            CharacterEffect::parse(
+                "Synaptic Static -1d6 to attack rolls and ability checks for 10 rounds"
+            )
+            .map(|effect| (
+                effect.penalty,
+                effect.targets.saving_throws,
+                effect.rounds
+            )),
+            Some((true, false, Some(10)))
+        );
+        assert_eq!(CharacterEffect::parse("Curse +1d4 to damage"), None);
+    }
+
+    #[test]
+    fn test_applies_to() {
+        let bless = CharacterEffect::spell("bless").unwrap().targets;
+        assert!(bless.applies_to(Check::SavingThrow(AbilityName::Wisdom)));
+        assert!(!bless.applies_to(Check::Skill(SkillName::Stealth)));
+        let guidance = CharacterEffect::spell("guidance").unwrap().targets;
+        assert!(guidance.applies_to(Check::Initiative));
+        assert!(guidance.applies_to(Check::Ability(AbilityName::Strength)));
+        assert!(!guidance.applies_to(Check::SavingThrow(AbilityName::Strength)));
+    }
+
+    #[test]
+    fn test_to_string() {
+        assert_eq!(
+            CharacterEffect::spell("bane").unwrap().to_string(),
+            "Bane: -1d4 to attack rolls and saving throws for 10 more rounds"
+        );
+        assert_eq!(
+            CharacterEffect::spell("guidance").unwrap().to_string(),
+            "Guidance: +1d4 to ability checks for 10 more rounds (once)"
+        );
+    }
+}
@@ -1,8 +1,16 @@
 use crate::character::{AbilityName, Character, SkillName};
-use crate::roll::{Condition, ConditionalRoll};
+use crate::character_effect::{self, EffectRoll};
+use crate::roll::{Condition, ConditionalRoll, ConditionalRollResult};
+use crate::status::Status;
 use regex::Regex;
+use serenity::model::id::UserId;
 use std::fmt;
 
+lazy_static! {
+    static ref CHARACTER_ROLL_REGEX: Regex =
+        Regex::new(r"^(.*?)(?: with (advantage|disadvantage))?$").unwrap();
+}
+
 #[derive(Debug)]
 pub struct CharacterRoll {
     pub check: Check,
@@ -11,12 +19,7 @@ pub struct CharacterRoll {
 
 impl CharacterRoll {
     pub fn parse(string: &str) -> Option<CharacterRoll> {
-        lazy_static! {
-            static ref RE: Regex =
-                Regex::new(r"^(.*?)(?: with (advantage|disadvantage))?$").unwrap();
-        }
-
-        RE.captures(string).and_then(|captures| {
+        CHARACTER_ROLL_REGEX.captures(string).and_then(|captures| {
             let check = captures.get(1).and_then(|m| Check::parse(m.as_str()))?;
             let condition = captures.get(2).and_then(|m| match m.as_str() {
                 "advantage" => Some(Condition::Advantage),
@@ -27,14 +30,30 @@ impl CharacterRoll {
         })
     }
 
+    /// The skills closest in spelling to the check of a roll that wasn't recognised, such as
+    /// "Stealth" for "stelth with advantage".
+    pub fn suggest(string: &str) -> Vec<SkillName> {
+        CHARACTER_ROLL_REGEX
+            .captures(string)
+            .and_then(|captures| captures.get(1))
+            .map_or_else(Vec::new, |m| SkillName::suggest(m.as_str()))
+    }
+
     pub fn to_roll(&self, character: &Character) -> Option<ConditionalRoll> {
-        let modifier = match self.check {
+        let modifier = CharacterRoll::modifier(self.check, character)?;
+        Some(ConditionalRoll::new(1, 20, modifier, self.condition).unwrap())
+    }
+
+    /// The character's modifier for a check, including their bonuses to it, or None if they don't
+    /// have the ability score it uses.
+    pub fn modifier(check: Check, character: &Character) -> Option<i32> {
+        let modifier = match check {
             Check::Ability(name) => character.ability(name)?.modifier,
-            Check::Initiative => character.ability(AbilityName::Dexterity)?.modifier,
+            Check::Initiative => character.initiative()?,
             Check::SavingThrow(name) => character.saving_throw(name)?.modifier,
             Check::Skill(name) => character.skill(name)?.modifier,
         };
-        Some(ConditionalRoll::new(1, 20, modifier, self.condition).unwrap())
+        Some(modifier + character.check_bonus(check))
     }
 }
 
@@ -69,6 +88,117 @@ impl Check {
     }
 }
 
+/// One character's saving throw when everyone at the table makes a saving throw, such as against
+/// an area spell.
+#[derive(Debug)]
+pub struct GroupSave {
+    pub user_id: UserId,
+    pub name: String,
+    /// The roll and its result, or None if the character doesn't have the ability score.
+    pub roll: Option<(ConditionalRoll, ConditionalRollResult)>,
+    /// The dice rolled for the effects on the character that add to the saving throw.
+    pub effects: Vec<EffectRoll>,
+}
+
+impl GroupSave {
+    /// The saving throw's total, including the effects on the character.
+    pub fn total(&self) -> Option<i32> {
+        self.roll
+            .as_ref()
+            .map(|(_, result)| result.result() + character_effect::total(&self.effects))
+    }
+
+    /// Whether the saving throw meets the DC.
+    pub fn passes(&self, dc: i32) -> Option<bool> {
+        self.total().map(|total| total >= dc)
+    }
+}
+
+/// Something one character tries to do to another that the other resists with a contested check,
+/// such as grappling them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Contest {
+    Grapple,
+    /// Shoving a creature to knock them prone or push them away.
+    Shove,
+}
+
+impl Contest {
+    pub fn parse(string: &str) -> Option<Contest> {
+        match string.to_lowercase().as_ref() {
+            "grapple" | "grapples" => Some(Contest::Grapple),
+            "shove" | "shoves" => Some(Contest::Shove),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Contest::Grapple => "grapple",
+            Contest::Shove => "shove",
+        }
+    }
+
+    /// The check made by the character starting the contest.
+    pub fn attacker_check(self) -> Check {
+        Check::Skill(SkillName::Athletics)
+    }
+
+    /// The check made by the character resisting the contest, which is whichever of Athletics or
+    /// Acrobatics has the higher modifier, or None if they have neither.
+    pub fn defender_check(self, character: &Character) -> Option<Check> {
+        let athletics = Check::Skill(SkillName::Athletics);
+        let acrobatics = Check::Skill(SkillName::Acrobatics);
+        match (
+            CharacterRoll::modifier(athletics, character),
+            CharacterRoll::modifier(acrobatics, character),
+        ) {
+            (Some(athletics_modifier), Some(acrobatics_modifier))
+                if acrobatics_modifier > athletics_modifier =>
+            {
+                Some(acrobatics)
+            }
+            (Some(_), _) => Some(athletics),
+            (None, Some(_)) => Some(acrobatics),
+            (None, None) => None,
+        }
+    }
+
+    /// The status the defender is given when they lose the contest.
+    pub fn status(self) -> Option<Status> {
+        match self {
+            Contest::Grapple => Some(Status::Grappled),
+            Contest::Shove => None,
+        }
+    }
+}
+
+/// One character's check in a contest, such as the Athletics check of a character trying to grapple
+/// another.
+#[derive(Debug)]
+pub struct ContestRoll {
+    pub user_id: UserId,
+    pub name: String,
+    pub check: Check,
+    pub roll: ConditionalRoll,
+    pub result: ConditionalRollResult,
+    /// The dice rolled for the effects on the character that add to the check.
+    pub effects: Vec<EffectRoll>,
+}
+
+impl ContestRoll {
+    /// The check's total, including the effects on the character.
+    pub fn total(&self) -> i32 {
+        self.result.result() + character_effect::total(&self.effects)
+    }
+
+    /// Whether this roll, made by the character starting the contest, beats the defender's roll. A
+    /// tie leaves things as they were, so the character starting the contest loses it.
+    pub fn beats(&self, defender: &ContestRoll) -> bool {
+        self.total() > defender.total()
+    }
+}
+
 impl fmt::Display for Check {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -0,0 +1,277 @@
+use crate::character::{
+    Ability, AbilityName, Character, Proficiency, SavingThrow, Skill, SkillName, WeaponProficiency,
+};
+use crate::character_roll::Check;
+use crate::class::ClassLevel;
+
+/// The skills that use each ability, in the order they're listed on a character sheet.
+pub const ABILITY_SKILLS: [(AbilityName, &[SkillName]); 6] = [
+    (AbilityName::Strength, &[SkillName::Athletics]),
+    (
+        AbilityName::Dexterity,
+        &[
+            SkillName::Acrobatics,
+            SkillName::SleightOfHand,
+            SkillName::Stealth,
+        ],
+    ),
+    (AbilityName::Constitution, &[]),
+    (
+        AbilityName::Intelligence,
+        &[
+            SkillName::Arcana,
+            SkillName::History,
+            SkillName::Investigation,
+            SkillName::Nature,
+            SkillName::Religion,
+        ],
+    ),
+    (
+        AbilityName::Wisdom,
+        &[
+            SkillName::AnimalHandling,
+            SkillName::Insight,
+            SkillName::Medicine,
+            SkillName::Perception,
+            SkillName::Survival,
+        ],
+    ),
+    (
+        AbilityName::Charisma,
+        &[
+            SkillName::Deception,
+            SkillName::Intimidation,
+            SkillName::Performance,
+            SkillName::Persuasion,
+        ],
+    ),
+];
+
+/// Everything Dungeon Helper knows about a character, shown together in a single message. Saving
+/// throw, skill and initiative modifiers include the character's bonuses, so that they match
+/// their rolls.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CharacterSheet {
+    pub level: Option<i32>,
+    pub classes: Vec<ClassLevel>,
+    pub proficiency_bonus: Option<i32>,
+    pub armor_class: Option<i32>,
+    /// The character's hit points, if they're being tracked in the channel's encounter.
+    pub hit_points: Option<i32>,
+    pub initiative: Option<i32>,
+    pub abilities: Vec<AbilitySummary>,
+    pub weapon_proficiencies: Vec<WeaponProficiency>,
+}
+
+/// An ability score along with its saving throw and the skills that use it.
+#[derive(Debug, Eq, PartialEq)]
+pub struct AbilitySummary {
+    pub name: AbilityName,
+    pub ability: Option<Ability>,
+    pub saving_throw: Option<SavingThrow>,
+    pub skills: Vec<(SkillName, Option<Skill>)>,
+}
+
+impl CharacterSheet {
+    pub fn new(
+        character: &Character,
+        hit_points: Option<i32>,
+        weapon_proficiencies: Vec<WeaponProficiency>,
+    ) -> CharacterSheet {
+        let abilities = ABILITY_SKILLS
+            .iter()
+            .map(|(name, skills)| AbilitySummary {
+                name: *name,
+                ability: character.ability(*name),
+                saving_throw: character
+                    .saving_throw(*name)
+                    .map(|saving_throw| SavingThrow {
+                        modifier: saving_throw.modifier
+                            + character.check_bonus(Check::SavingThrow(*name)),
+                        ..saving_throw
+                    }),
+                skills: skills
+                    .iter()
+                    .map(|skill_name| {
+                        let skill = character.skill(*skill_name).map(|skill| Skill {
+                            modifier: skill.modifier
+                                + character.check_bonus(Check::Skill(*skill_name)),
+                            ..skill
+                        });
+                        (*skill_name, skill)
+                    })
+                    .collect(),
+            })
+            .collect();
+        CharacterSheet {
+            level: character.level(),
+            classes: character.classes().to_vec(),
+            proficiency_bonus: character.proficiency_bonus(),
+            armor_class: character.armor_class(),
+            hit_points,
+            initiative: character
+                .initiative()
+                .map(|initiative| initiative + character.check_bonus(Check::Initiative)),
+            abilities,
+            weapon_proficiencies,
+        }
+    }
+
+    /// The character's level and classes, followed by their proficiency bonus, armour class, hit
+    /// points and initiative.
+    pub fn summary(&self) -> String {
+        let mut lines = Vec::new();
+        let classes = self
+            .classes
+            .iter()
+            .map(|class| class.to_string())
+            .collect::<Vec<String>>()
+            .join(" / ");
+        match self.level {
+            Some(level) if classes.is_empty() => lines.push(format!("Level {}", level)),
+            Some(level) => lines.push(format!("Level {} · {}", level, classes)),
+            None => (),
+        }
+        if let Some(proficiency_bonus) = self.proficiency_bonus {
+            lines.push(format!("Proficiency bonus {:+}", proficiency_bonus));
+        }
+        let combat: Vec<String> = [
+            self.armor_class
+                .map(|armor_class| format!("🛡️ AC {}", armor_class)),
+            self.hit_points
+                .map(|hit_points| format!("❤️ {} hit points", hit_points)),
+            self.initiative
+                .map(|initiative| format!("⚡ Initiative {:+}", initiative)),
+        ]
+        .iter()
+        .flatten()
+        .cloned()
+        .collect();
+        if !combat.is_empty() {
+            lines.push(combat.join(" · "));
+        }
+        if lines.is_empty() {
+            "Nothing to show yet. Try setting some ability scores and a character level first."
+                .to_owned()
+        } else {
+            lines.join("\n")
+        }
+    }
+
+    /// The weapon proficiencies as a comma separated list, if the character has any.
+    pub fn weapon_proficiencies(&self) -> Option<String> {
+        if self.weapon_proficiencies.is_empty() {
+            None
+        } else {
+            Some(
+                self.weapon_proficiencies
+                    .iter()
+                    .map(|proficiency| proficiency.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+            )
+        }
+    }
+}
+
+impl AbilitySummary {
+    /// The heading for the ability, such as "Dexterity 16 (+3)".
+    pub fn title(&self) -> String {
+        match self.ability {
+            Some(ability) => format!(
+                "{} {} ({:+})",
+                self.name.as_str(),
+                ability.score,
+                ability.modifier
+            ),
+            None => self.name.as_str().to_owned(),
+        }
+    }
+
+    /// The saving throw and skills for the ability, one per line. Proficiencies are marked with
+    /// ●, and expertise with ◆.
+    pub fn description(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(saving_throw) = self.saving_throw {
+            let marker = if saving_throw.proficiency {
+                "●"
+            } else {
+                "○"
+            };
+            lines.push(format!("{} Save {:+}", marker, saving_throw.modifier));
+        }
+        for (name, skill) in self.skills.iter() {
+            if let Some(skill) = skill {
+                let marker = match skill.proficiency {
+                    Proficiency::Normal => "○",
+                    Proficiency::Proficient => "●",
+                    Proficiency::Expert => "◆",
+                };
+                lines.push(format!("{} {} {:+}", marker, name.as_str(), skill.modifier));
+            }
+        }
+        if lines.is_empty() {
+            "—".to_owned()
+        } else {
+            lines.join("\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::class::Class;
+    use crate::weapon::Category;
+
+    fn character() -> Character {
+        serde_json::from_str(
+            r#"{
+                "name": "Connie",
+                "classes": [{ "class": "Monk", "level": 3 }, { "class": "Rogue", "level": 2 }],
+                "dexterity": 16,
+                "wisdom": 14,
+                "dexterity_saving_proficiency": true,
+                "stealth_proficiency": "Expert",
+                "bonuses": [{ "target": "Stealth", "amount": 2, "source": "Cloak" }]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let sheet = CharacterSheet::new(
+            &character(),
+            Some(27),
+            vec![WeaponProficiency::Category(Category::Simple)],
+        );
+        assert_eq!(sheet.level, Some(5));
+        assert_eq!(sheet.classes[0].class, Class::Monk);
+        assert_eq!(sheet.proficiency_bonus, Some(3));
+        assert_eq!(sheet.initiative, Some(3));
+        let dexterity = &sheet.abilities[1];
+        assert_eq!(dexterity.title(), "Dexterity 16 (+3)");
+        assert_eq!(
+            dexterity.description(),
+            "● Save +6\n○ Acrobatics +3\n○ Sleight Of Hand +3\n◆ Stealth +11"
+        );
+        assert_eq!(sheet.abilities[0].title(), "Strength");
+        assert_eq!(sheet.abilities[0].description(), "—");
+        assert_eq!(
+            sheet.summary(),
+            "Level 5 · Monk 3 / Rogue 2\nProficiency bonus +3\n🛡️ AC 15 · ❤️ 27 hit points · ⚡ Initiative +3"
+        );
+        assert_eq!(sheet.weapon_proficiencies(), Some("Simple".to_owned()));
+    }
+
+    #[test]
+    fn test_empty() {
+        let sheet = CharacterSheet::new(&Character::default(), None, Vec::new());
+        assert_eq!(
+            sheet.summary(),
+            "Nothing to show yet. Try setting some ability scores and a character level first."
+        );
+        assert_eq!(sheet.weapon_proficiencies(), None);
+    }
+}
@@ -0,0 +1,419 @@
+use crate::character::AbilityName;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::Result as RusqliteResult;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+/// A character class from the Player's Handbook.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Class {
+    Barbarian,
+    Bard,
+    Cleric,
+    Druid,
+    Fighter,
+    Monk,
+    Paladin,
+    Ranger,
+    Rogue,
+    Sorcerer,
+    Warlock,
+    Wizard,
+}
+
+impl Class {
+    pub fn parse(string: &str) -> Option<Class> {
+        match string.to_lowercase().as_ref() {
+            "barbarian" => Some(Class::Barbarian),
+            "bard" => Some(Class::Bard),
+            "cleric" => Some(Class::Cleric),
+            "druid" => Some(Class::Druid),
+            "fighter" => Some(Class::Fighter),
+            "monk" => Some(Class::Monk),
+            "paladin" => Some(Class::Paladin),
+            "ranger" => Some(Class::Ranger),
+            "rogue" => Some(Class::Rogue),
+            "sorcerer" => Some(Class::Sorcerer),
+            "warlock" => Some(Class::Warlock),
+            "wizard" => Some(Class::Wizard),
+            _ => None,
+        }
+    }
+
+    /// The number of sides on the class's hit die.
+    pub fn hit_die(self) -> i32 {
+        match self {
+            Class::Barbarian => 12,
+            Class::Fighter | Class::Paladin | Class::Ranger => 10,
+            Class::Bard
+            | Class::Cleric
+            | Class::Druid
+            | Class::Monk
+            | Class::Rogue
+            | Class::Warlock => 8,
+            Class::Sorcerer | Class::Wizard => 6,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Class::Barbarian => "Barbarian",
+            Class::Bard => "Bard",
+            Class::Cleric => "Cleric",
+            Class::Druid => "Druid",
+            Class::Fighter => "Fighter",
+            Class::Monk => "Monk",
+            Class::Paladin => "Paladin",
+            Class::Ranger => "Ranger",
+            Class::Rogue => "Rogue",
+            Class::Sorcerer => "Sorcerer",
+            Class::Warlock => "Warlock",
+            Class::Wizard => "Wizard",
+        }
+    }
+}
+
+/// A number of levels a character has taken in one class.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ClassLevel {
+    pub class: Class,
+    #[serde(default)]
+    pub subclass: Option<String>,
+    pub level: i32,
+}
+
+impl fmt::Display for ClassLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.subclass {
+            Some(subclass) => write!(f, "{} {} ({})", self.class, self.level, subclass),
+            None => write!(f, "{} {}", self.class, self.level),
+        }
+    }
+}
+
+/// How many spellcaster levels a class contributes when working out a character's spell slots.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Spellcasting {
+    Full,
+    Half,
+    /// The Eldritch Knight and Arcane Trickster subclasses, which cast spells from 3rd level.
+    Third,
+}
+
+impl ClassLevel {
+    /// The ability used for the class's spell save DC and spell attacks, if the class can cast
+    /// spells.
+    pub fn spellcasting_ability(&self) -> Option<AbilityName> {
+        if self.spellcasting().is_none() && self.class != Class::Warlock {
+            return None;
+        }
+        match self.class {
+            Class::Bard | Class::Paladin | Class::Sorcerer | Class::Warlock => {
+                Some(AbilityName::Charisma)
+            }
+            Class::Cleric | Class::Druid | Class::Ranger => Some(AbilityName::Wisdom),
+            Class::Fighter | Class::Rogue | Class::Wizard => Some(AbilityName::Intelligence),
+            Class::Barbarian | Class::Monk => None,
+        }
+    }
+
+    fn spellcasting(&self) -> Option<Spellcasting> {
+        let subclass = self
+            .subclass
+            .as_ref()
+            .map(|subclass| subclass.to_lowercase());
+        match (self.class, subclass.as_deref()) {
+            (Class::Bard, _)
+            | (Class::Cleric, _)
+            | (Class::Druid, _)
+            | (Class::Sorcerer, _)
+            | (Class::Wizard, _) => Some(Spellcasting::Full),
+            (Class::Paladin, _) | (Class::Ranger, _) => Some(Spellcasting::Half),
+            (Class::Fighter, Some("eldritch knight"))
+            | (Class::Rogue, Some("arcane trickster")) => Some(Spellcasting::Third),
+            _ => None,
+        }
+    }
+}
+
+/// The number of spell slots of each level, from 1st to 9th, for each spellcaster level in the
+/// multiclass spellcaster table.
+const SPELL_SLOTS: [[i32; 9]; 20] = [
+    [2, 0, 0, 0, 0, 0, 0, 0, 0],
+    [3, 0, 0, 0, 0, 0, 0, 0, 0],
+    [4, 2, 0, 0, 0, 0, 0, 0, 0],
+    [4, 3, 0, 0, 0, 0, 0, 0, 0],
+    [4, 3, 2, 0, 0, 0, 0, 0, 0],
+    [4, 3, 3, 0, 0, 0, 0, 0, 0],
+    [4, 3, 3, 1, 0, 0, 0, 0, 0],
+    [4, 3, 3, 2, 0, 0, 0, 0, 0],
+    [4, 3, 3, 3, 1, 0, 0, 0, 0],
+    [4, 3, 3, 3, 2, 0, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 1],
+    [4, 3, 3, 3, 3, 1, 1, 1, 1],
+    [4, 3, 3, 3, 3, 2, 1, 1, 1],
+    [4, 3, 3, 3, 3, 2, 2, 1, 1],
+];
+
+/// The spell slots a character has from their classes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SpellSlots {
+    /// The number of slots of each spell level, from 1st to 9th.
+    pub slots: [i32; 9],
+    /// The number of Pact Magic slots from warlock levels, and the level of those slots.
+    pub pact_magic: Option<(i32, i32)>,
+}
+
+impl SpellSlots {
+    pub fn new(classes: &[ClassLevel]) -> SpellSlots {
+        let spellcasting_classes: Vec<(&ClassLevel, Spellcasting)> = classes
+            .iter()
+            .filter_map(|class| {
+                class
+                    .spellcasting()
+                    .map(|spellcasting| (class, spellcasting))
+            })
+            .collect();
+        // A single class uses its own table, where half and third casters round up once they
+        // can cast spells. Multiclass characters round down for each class instead.
+        let spellcaster_level = match spellcasting_classes.as_slice() {
+            [(class, Spellcasting::Full)] => class.level,
+            [(class, Spellcasting::Half)] if class.level >= 2 => (class.level + 1) / 2,
+            [(class, Spellcasting::Third)] if class.level >= 3 => (class.level + 2) / 3,
+            [_] => 0,
+            multiclass => multiclass
+                .iter()
+                .map(|(class, spellcasting)| match spellcasting {
+                    Spellcasting::Full => class.level,
+                    Spellcasting::Half => class.level / 2,
+                    Spellcasting::Third => class.level / 3,
+                })
+                .sum(),
+        };
+        let slots = if spellcaster_level > 0 {
+            SPELL_SLOTS[(spellcaster_level.min(20) - 1) as usize]
+        } else {
+            [0; 9]
+        };
+
+        let warlock_level: i32 = classes
+            .iter()
+            .filter(|class| class.class == Class::Warlock)
+            .map(|class| class.level)
+            .sum();
+        let pact_magic = if warlock_level > 0 {
+            let count = match warlock_level {
+                1 => 1,
+                2..=10 => 2,
+                11..=16 => 3,
+                _ => 4,
+            };
+            Some((count, ((warlock_level + 1) / 2).min(5)))
+        } else {
+            None
+        };
+
+        SpellSlots { slots, pact_magic }
+    }
+
+    /// Whether there are any slots of a spell level, including Pact Magic slots.
+    pub fn has_slot(&self, level: i32) -> bool {
+        let slots = if level >= 1 && level <= 9 {
+            self.slots[(level - 1) as usize]
+        } else {
+            0
+        };
+        slots > 0
+            || self.pact_magic.map_or(false, |(count, pact_level)| {
+                count > 0 && pact_level == level
+            })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|count| *count == 0) && self.pact_magic.is_none()
+    }
+}
+
+impl fmt::Display for SpellSlots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut slots: Vec<String> = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| format!("{} × {}", count, ordinal(index as i32 + 1)))
+            .collect();
+        if let Some((count, level)) = self.pact_magic {
+            slots.push(format!("{} × {} (Pact Magic)", count, ordinal(level)));
+        }
+        slots.join(", ").fmt(f)
+    }
+}
+
+/// A spell level written as an ordinal, such as "1st" or "3rd".
+pub fn ordinal(level: i32) -> String {
+    let suffix = match level {
+        1 => "st",
+        2 => "nd",
+        3 => "rd",
+        _ => "th",
+    };
+    format!("{}{}", level, suffix)
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl From<Class> for String {
+    fn from(class: Class) -> String {
+        class.as_str().to_owned()
+    }
+}
+
+impl TryFrom<String> for Class {
+    type Error = String;
+
+    fn try_from(string: String) -> Result<Class, String> {
+        Class::parse(&string).ok_or_else(|| format!("Invalid value for class (value = {})", string))
+    }
+}
+
+impl FromSql for Class {
+    fn column_result(value: ValueRef) -> FromSqlResult<Class> {
+        value.as_str().and_then(|string| {
+            Class::parse(string).ok_or_else(|| {
+                FromSqlError::Other(Box::new(InvalidClassValueError {
+                    value: string.to_owned(),
+                }))
+            })
+        })
+    }
+}
+
+impl ToSql for Class {
+    fn to_sql(&self) -> RusqliteResult<ToSqlOutput> {
+        self.as_str().to_sql()
+    }
+}
+
+#[derive(Debug)]
+struct InvalidClassValueError {
+    value: String,
+}
+
+impl fmt::Display for InvalidClassValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid value for class (value = {})", self.value)
+    }
+}
+
+impl error::Error for InvalidClassValueError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn class_level(class: Class, subclass: Option<&str>, level: i32) -> ClassLevel {
+        ClassLevel {
+            class,
+            subclass: subclass.map(|subclass| subclass.to_owned()),
+            level,
+        }
+    }
+
+    #[test]
+    fn test_spell_slots_single_class() {
+        assert_eq!(
+            SpellSlots::new(&[class_level(Class::Wizard, None, 5)]).slots,
+            [4, 3, 2, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            SpellSlots::new(&[class_level(Class::Paladin, None, 1)]),
+            SpellSlots::default()
+        );
+        assert_eq!(
+            SpellSlots::new(&[class_level(Class::Paladin, None, 5)]).slots,
+            [4, 2, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            SpellSlots::new(&[class_level(Class::Fighter, Some("Eldritch Knight"), 7)]).slots,
+            [4, 2, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert!(SpellSlots::new(&[class_level(Class::Fighter, None, 7)]).is_empty());
+    }
+
+    #[test]
+    fn test_spellcasting_ability() {
+        assert_eq!(
+            class_level(Class::Cleric, None, 1).spellcasting_ability(),
+            Some(AbilityName::Wisdom)
+        );
+        assert_eq!(
+            class_level(Class::Warlock, None, 1).spellcasting_ability(),
+            Some(AbilityName::Charisma)
+        );
+        assert_eq!(
+            class_level(Class::Rogue, Some("Arcane Trickster"), 3).spellcasting_ability(),
+            Some(AbilityName::Intelligence)
+        );
+        assert_eq!(
+            class_level(Class::Rogue, None, 3).spellcasting_ability(),
+            None
+        );
+        assert_eq!(
+            class_level(Class::Monk, None, 3).spellcasting_ability(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_spell_slots_multiclass() {
+        // Half and third casters round down when multiclassing: 3 + 2 + 1 = 6
+        let slots = SpellSlots::new(&[
+            class_level(Class::Cleric, None, 3),
+            class_level(Class::Paladin, None, 5),
+            class_level(Class::Rogue, Some("Arcane Trickster"), 3),
+        ]);
+        assert_eq!(slots.slots, [4, 3, 3, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(slots.pact_magic, None);
+    }
+
+    #[test]
+    fn test_spell_slots_display() {
+        let slots = SpellSlots::new(&[
+            class_level(Class::Sorcerer, None, 4),
+            class_level(Class::Warlock, None, 3),
+        ]);
+        assert_eq!(slots.to_string(), "4 × 1st, 3 × 2nd, 2 × 2nd (Pact Magic)");
+    }
+
+    #[test]
+    fn test_spell_slots_pact_magic() {
+        let slots = SpellSlots::new(&[
+            class_level(Class::Sorcerer, None, 4),
+            class_level(Class::Warlock, None, 3),
+        ]);
+        assert_eq!(slots.slots, [4, 3, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(slots.pact_magic, Some((2, 2)));
+        assert_eq!(
+            SpellSlots::new(&[class_level(Class::Warlock, None, 20)]).pact_magic,
+            Some((4, 5))
+        );
+    }
+}
@@ -0,0 +1,689 @@
+use crate::encounter;
+use crate::initiative::InitiativeOrder;
+use crate::ongoing_effect::OngoingEffect;
+use regex::{Captures, Regex};
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension};
+use serenity::model::id::ChannelId;
+use std::fmt;
+
+/// The types of damage in the rules, which are the only types a combatant can be tagged as
+/// resisting.
+const DAMAGE_TYPES: [&str; 13] = [
+    "acid",
+    "bludgeoning",
+    "cold",
+    "fire",
+    "force",
+    "lightning",
+    "necrotic",
+    "piercing",
+    "poison",
+    "psychic",
+    "radiant",
+    "slashing",
+    "thunder",
+];
+
+/// Damage dealt to a combatant in an encounter, such as "Troll takes 12 fire damage".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Damage {
+    /// The combatant's name in the initiative order.
+    pub target: String,
+    pub amount: i32,
+    pub damage_type: Option<String>,
+}
+
+/// How a combatant is affected by a type of damage they've been tagged with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Resistance {
+    /// The combatant takes half damage of the type, rounded down.
+    Resistant,
+    /// The combatant takes no damage of the type.
+    Immune,
+}
+
+/// The types of damage a combatant resists or is immune to, such as "Troll is resistant to fire
+/// and cold".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Resistances {
+    /// The combatant's name in the initiative order.
+    pub target: String,
+    pub resistance: Resistance,
+    pub damage_types: Vec<String>,
+}
+
+/// A combatant's tracked hit points, such as a monster the DM has added to the encounter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HitPoints {
+    pub current: i32,
+    /// The hit points the combatant started the encounter with, which healing can't go above.
+    pub maximum: i32,
+    /// Whether the exact hit points are hidden from the players, who are only told how hurt the
+    /// combatant looks.
+    pub hidden: bool,
+}
+
+impl Damage {
+    /// Parse damage from a shorthand command such as "troll 12 fire".
+    pub fn parse_shorthand(string: &str) -> Option<Damage> {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)(.+?) +(\d+)(?: +([a-z]+))?(?: +damage)? *$").unwrap();
+        }
+        RE.captures(string.trim())
+            .and_then(|captures| Damage::from_captures(&captures))
+    }
+
+    /// Parse damage from a sentence such as "Troll takes 12 fire damage".
+    pub fn parse_natural_language(string: &str) -> Option<Damage> {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)(.+?) +takes +(\d+)(?: +([a-z]+))? +damage *\.?$").unwrap();
+        }
+        RE.captures(string.trim())
+            .and_then(|captures| Damage::from_captures(&captures))
+    }
+
+    fn from_captures(captures: &Captures) -> Option<Damage> {
+        let target = captures.get(1)?.as_str().trim();
+        let amount = captures.get(2)?.as_str().parse().ok()?;
+        let damage_type = captures
+            .get(3)
+            .map(|m| m.as_str().to_lowercase())
+            .filter(|damage_type| damage_type != "damage");
+        if target.is_empty() {
+            None
+        } else {
+            Some(Damage {
+                target: target.to_owned(),
+                amount,
+                damage_type,
+            })
+        }
+    }
+
+    /// A description of the damage, such as "12 fire damage".
+    pub fn description(&self) -> String {
+        match &self.damage_type {
+            Some(damage_type) => format!("{} {} damage", self.amount, damage_type),
+            None => format!("{} damage", self.amount),
+        }
+    }
+
+    /// Reduce the damage by the combatant's resistance or immunity to its type, if they have one.
+    /// Returns the damage they take, along with a note explaining how it was reduced.
+    pub fn resist(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<(Damage, Option<String>)> {
+        let resistance = match &self.damage_type {
+            Some(damage_type) => get_resistance(connection, channel_id, &self.target, damage_type)?
+                .map(|resistance| (resistance, damage_type)),
+            None => None,
+        };
+        Ok(match resistance {
+            Some((resistance, damage_type)) => (
+                Damage {
+                    amount: resistance.reduce(self.amount),
+                    ..self.clone()
+                },
+                Some(resistance.note(self.amount, damage_type)),
+            ),
+            None => (self.clone(), None),
+        })
+    }
+
+    /// Deal the damage to the combatant, remembering its type until the start of their next turn,
+    /// and adding it to the damage they've taken during the encounter. Returns the combatant's
+    /// remaining hit points, if their hit points are being tracked.
+    pub fn apply(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<Option<HitPoints>> {
+        if let Some(damage_type) = &self.damage_type {
+            let params: &[&dyn ToSql] = &[&channel_id.to_string(), &self.target, &damage_type];
+            connection.execute(
+                "INSERT OR IGNORE INTO combatant_damage_taken (channel_id, name, damage_type) \
+                 VALUES ($1, $2, $3)",
+                params,
+            )?;
+        }
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &self.target, &self.amount];
+        connection.execute(
+            "UPDATE combatant_hit_points SET hit_points = MAX(hit_points - $3, 0) \
+             WHERE channel_id = $1 \
+             AND name = $2",
+            params,
+        )?;
+        encounter::record_damage(connection, channel_id, &self.target, self.amount)?;
+        get_hit_points(connection, channel_id, &self.target)
+    }
+}
+
+impl HitPoints {
+    /// Whether the combatant is down to half their hit points or fewer, but not yet defeated.
+    pub fn is_bloodied(self) -> bool {
+        self.current > 0 && self.current * 2 <= self.maximum
+    }
+
+    /// How the combatant's hit points are described after a change, such as "has 42 hit points
+    /// left", or how hurt they look if their hit points are hidden, such as "looks bloodied".
+    pub fn description(self) -> String {
+        if !self.hidden {
+            format!("has {} hit points left", self.current)
+        } else if self.current >= self.maximum {
+            "looks unhurt".to_owned()
+        } else if self.is_bloodied() {
+            "looks bloodied".to_owned()
+        } else {
+            "looks wounded".to_owned()
+        }
+    }
+}
+
+/// A message describing damage a combatant took, such as "Troll takes 12 fire damage and has 72
+/// hit points left", given their hit points before and after the damage if they're being tracked.
+/// The message announces when the damage leaves the combatant bloodied or defeats them.
+pub fn damage_message(
+    target: &str,
+    damage: &str,
+    before: Option<HitPoints>,
+    after: Option<HitPoints>,
+) -> String {
+    let newly_bloodied = before.map_or(false, |before| !before.is_bloodied());
+    match after {
+        Some(after) if after.current == 0 => format!(
+            "💀 {} takes {} and is defeated! They've been removed from the initiative order.",
+            target, damage
+        ),
+        Some(after) if after.is_bloodied() && newly_bloodied && after.hidden => {
+            format!("🩸 {} takes {} and is bloodied!", target, damage)
+        }
+        Some(after) if after.is_bloodied() && newly_bloodied => format!(
+            "🩸 {} takes {} and is bloodied, with {} hit points left!",
+            target, damage, after.current
+        ),
+        Some(after) => format!("{} takes {} and {}.", target, damage, after.description()),
+        None => format!("{} takes {}.", target, damage),
+    }
+}
+
+impl Resistance {
+    pub fn parse(string: &str) -> Option<Resistance> {
+        match string.trim().to_lowercase().as_str() {
+            "resist" | "resists" | "resistant" | "resistance" => Some(Resistance::Resistant),
+            "immune" | "immunity" => Some(Resistance::Immune),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resistance::Resistant => "resistance",
+            Resistance::Immune => "immunity",
+        }
+    }
+
+    /// The damage taken from an amount of damage of a type the combatant resists or is immune to.
+    pub fn reduce(self, amount: i32) -> i32 {
+        match self {
+            Resistance::Resistant => amount / 2,
+            Resistance::Immune => 0,
+        }
+    }
+
+    /// Explain how an amount of damage of a type was reduced, such as "halved from 12 by
+    /// resistance to fire".
+    pub fn note(self, amount: i32, damage_type: &str) -> String {
+        match self {
+            Resistance::Resistant => {
+                format!("halved from {} by resistance to {}", amount, damage_type)
+            }
+            Resistance::Immune => format!("reduced from {} by immunity to {}", amount, damage_type),
+        }
+    }
+}
+
+impl fmt::Display for Resistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl Resistances {
+    /// Parse resistances from a shorthand command such as "troll fire, cold", where the kind of
+    /// resistance comes from the command.
+    pub fn parse_shorthand(string: &str, resistance: Resistance) -> Option<Resistances> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(&format!(
+                r"^(?i)(.+?) +((?:{0})(?:(?: *, *| +)(?:and +)?(?:{0}))*)(?: +damage)? *$",
+                DAMAGE_TYPES.join("|")
+            ))
+            .unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        Resistances::new(
+            captures.get(1)?.as_str(),
+            resistance,
+            captures.get(2)?.as_str(),
+        )
+    }
+
+    /// Parse resistances from a sentence such as "Troll is resistant to fire and cold", "Troll
+    /// resists fire", or "Skeleton is immune to poison".
+    pub fn parse_natural_language(string: &str) -> Option<Resistances> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(&format!(
+                r"^(?i)(.+?) +(?:is +(resistant|immune) +to|(resists)|has +(resistance|immunity) +to) +((?:{0})(?:(?: *, *| +)(?:and +)?(?:{0}))*)(?: +damage)? *\.?$",
+                DAMAGE_TYPES.join("|")
+            ))
+            .unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        let resistance = captures
+            .get(2)
+            .or_else(|| captures.get(3))
+            .or_else(|| captures.get(4))
+            .and_then(|m| Resistance::parse(m.as_str()))?;
+        Resistances::new(
+            captures.get(1)?.as_str(),
+            resistance,
+            captures.get(5)?.as_str(),
+        )
+    }
+
+    fn new(target: &str, resistance: Resistance, damage_types: &str) -> Option<Resistances> {
+        lazy_static! {
+            static ref SEPARATOR_REGEX: Regex =
+                Regex::new(r"(?i) *, *(?:and +)?| +and +| +").unwrap();
+        }
+        let target = target.trim();
+        if target.is_empty() {
+            None
+        } else {
+            Some(Resistances {
+                target: target.to_owned(),
+                resistance,
+                damage_types: SEPARATOR_REGEX
+                    .split(damage_types.trim())
+                    .map(|damage_type| damage_type.to_lowercase())
+                    .collect(),
+            })
+        }
+    }
+
+    /// A description of the resistances, such as "resistance to fire and cold".
+    pub fn description(&self) -> String {
+        let damage_types = match self.damage_types.split_last() {
+            Some((last, [])) => last.to_owned(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+            None => String::new(),
+        };
+        format!("{} to {}", self.resistance, damage_types)
+    }
+
+    /// Tag the combatant with the resistances, replacing any resistance or immunity they already
+    /// had to the same types of damage.
+    pub fn set(&self, connection: &Connection, channel_id: ChannelId) -> RusqliteResult<()> {
+        for damage_type in self.damage_types.iter() {
+            let params: &[&dyn ToSql] = &[
+                &channel_id.to_string(),
+                &self.target,
+                &damage_type,
+                &self.resistance.as_str(),
+            ];
+            connection.execute(
+                "INSERT INTO combatant_resistances (channel_id, name, damage_type, resistance) \
+                 VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT (channel_id, name, damage_type) DO UPDATE SET resistance = $4",
+                params,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Retrieve the combatant's resistance or immunity to a type of damage, if they have one.
+pub fn get_resistance(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+    damage_type: &str,
+) -> RusqliteResult<Option<Resistance>> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name, &damage_type];
+    connection
+        .query_row(
+            "SELECT resistance FROM combatant_resistances \
+             WHERE channel_id = $1 \
+             AND name = $2 \
+             AND damage_type = $3",
+            params,
+            |row| row.get::<_, String>("resistance"),
+        )
+        .optional()
+        .map(|resistance| resistance.as_deref().and_then(Resistance::parse))
+}
+
+/// Remove all of the combatant's resistances and immunities. Returns the number removed.
+pub fn clear_resistances(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name];
+    connection.execute(
+        "DELETE FROM combatant_resistances WHERE channel_id = $1 AND name = $2",
+        params,
+    )
+}
+
+pub fn get_hit_points(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+) -> RusqliteResult<Option<HitPoints>> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name];
+    connection
+        .query_row(
+            "SELECT hit_points, maximum_hit_points, hidden FROM combatant_hit_points \
+             WHERE channel_id = $1 \
+             AND name = $2",
+            params,
+            |row| {
+                Ok(HitPoints {
+                    current: row.get("hit_points")?,
+                    maximum: row.get("maximum_hit_points")?,
+                    hidden: row.get("hidden")?,
+                })
+            },
+        )
+        .optional()
+}
+
+/// Start tracking a combatant's hit points, or replace the hit points already being tracked. The
+/// combatant starts unhurt, and their exact hit points can be hidden from the players.
+pub fn set_hit_points(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+    hit_points: i32,
+    hidden: bool,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name, &hit_points, &hidden];
+    connection.execute(
+        "INSERT INTO combatant_hit_points (channel_id, name, hit_points, maximum_hit_points, hidden) \
+         VALUES ($1, $2, $3, $3, $4) \
+         ON CONFLICT (channel_id, name) DO UPDATE \
+         SET hit_points = $3, maximum_hit_points = $3, hidden = $4",
+        params,
+    )
+}
+
+pub fn get_armor_class(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+) -> RusqliteResult<Option<i32>> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name];
+    connection
+        .query_row(
+            "SELECT armor_class FROM combatant_armor_classes WHERE channel_id = $1 AND name = $2",
+            params,
+            |row| row.get("armor_class"),
+        )
+        .optional()
+}
+
+/// Set a combatant's armour class, which attacks against them are compared with.
+pub fn set_armor_class(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+    armor_class: i32,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name, &armor_class];
+    connection.execute(
+        "INSERT INTO combatant_armor_classes (channel_id, name, armor_class) VALUES ($1, $2, $3) \
+         ON CONFLICT (channel_id, name) DO UPDATE SET armor_class = $3",
+        params,
+    )
+}
+
+/// Restore some of a combatant's hit points, up to their maximum. Returns their new hit points, if
+/// their hit points are being tracked.
+pub fn heal(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+    amount: i32,
+) -> RusqliteResult<Option<HitPoints>> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name, &amount];
+    connection.execute(
+        "UPDATE combatant_hit_points \
+         SET hit_points = MAX(MIN(hit_points + $3, maximum_hit_points), hit_points) \
+         WHERE channel_id = $1 \
+         AND name = $2",
+        params,
+    )?;
+    get_hit_points(connection, channel_id, name)
+}
+
+/// Retrieve the types of damage a combatant has taken since the start of their last turn, and
+/// forget them so that they only count until the start of this turn.
+pub fn take_damage_types_taken(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+) -> RusqliteResult<Vec<String>> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name];
+    let damage_types = {
+        let mut statement = connection.prepare(
+            "SELECT damage_type FROM combatant_damage_taken WHERE channel_id = $1 AND name = $2",
+        )?;
+        let rows = statement.query_map(params, |row| row.get("damage_type"))?;
+        rows.collect::<RusqliteResult<Vec<String>>>()?
+    };
+    connection.execute(
+        "DELETE FROM combatant_damage_taken WHERE channel_id = $1 AND name = $2",
+        params,
+    )?;
+    Ok(damage_types)
+}
+
+/// Remove a combatant who has dropped to 0 hit points from the encounter, along with their
+/// ongoing effects, hit points, armour class and resistances, and record their defeat for the encounter summary. The current turn stays with the same combatant, or moves back
+/// if it was the defeated combatant's turn.
+pub fn defeat(
+    connection: &mut Connection,
+    channel_id: ChannelId,
+    name: &str,
+) -> RusqliteResult<()> {
+    let transaction = connection.transaction()?;
+    InitiativeOrder::remove(&transaction, channel_id, name)?;
+    OngoingEffect::clear(&transaction, channel_id, name)?;
+    encounter::record_defeat(&transaction, channel_id, name)?;
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name];
+    transaction.execute(
+        "DELETE FROM combatant_damage_taken WHERE channel_id = $1 AND name = $2",
+        params,
+    )?;
+    transaction.execute(
+        "DELETE FROM combatant_hit_points WHERE channel_id = $1 AND name = $2",
+        params,
+    )?;
+    transaction.execute(
+        "DELETE FROM combatant_resistances WHERE channel_id = $1 AND name = $2",
+        params,
+    )?;
+    transaction.execute(
+        "DELETE FROM combatant_armor_classes WHERE channel_id = $1 AND name = $2",
+        params,
+    )?;
+    transaction.commit()
+}
+
+/// Forget the hit points, damage taken, armour classes and resistances of every combatant in a
+/// channel, as when an encounter ends.
+pub fn clear(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+    connection.execute(
+        "DELETE FROM combatant_armor_classes WHERE channel_id = $1",
+        &[&channel_id.to_string()],
+    )?;
+    connection.execute(
+        "DELETE FROM combatant_damage_taken WHERE channel_id = $1",
+        &[&channel_id.to_string()],
+    )?;
+    connection.execute(
+        "DELETE FROM combatant_resistances WHERE channel_id = $1",
+        &[&channel_id.to_string()],
+    )?;
+    connection.execute(
+        "DELETE FROM combatant_hit_points WHERE channel_id = $1",
+        &[&channel_id.to_string()],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn damage(target: &str, amount: i32, damage_type: Option<&str>) -> Damage {
+        Damage {
+            target: target.to_owned(),
+            amount,
+            damage_type: damage_type.map(|damage_type| damage_type.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_parse_shorthand() {
+        assert_eq!(
+            Damage::parse_shorthand("troll 12 fire"),
+            Some(damage("troll", 12, Some("fire")))
+        );
+        assert_eq!(
+            Damage::parse_shorthand("goblin A 7"),
+            Some(damage("goblin A", 7, None))
+        );
+        assert_eq!(Damage::parse_shorthand("troll fire"), None);
+    }
+
+    #[test]
+    fn test_parse_natural_language() {
+        assert_eq!(
+            Damage::parse_natural_language("The troll takes 12 Acid damage."),
+            Some(damage("The troll", 12, Some("acid")))
+        );
+        assert_eq!(
+            Damage::parse_natural_language("Goblin A takes 3 damage"),
+            Some(damage("Goblin A", 3, None))
+        );
+        assert_eq!(
+            Damage::parse_natural_language(
+                "Goblin A takes 1d6 poison damage at the start of each turn"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_resistances() {
+        fn resistances(target: &str, resistance: Resistance, damage_types: &[&str]) -> Resistances {
+            Resistances {
+                target: target.to_owned(),
+                resistance,
+                damage_types: damage_types
+                    .iter()
+                    .map(|damage_type| (*damage_type).to_owned())
+                    .collect(),
+            }
+        }
+        assert_eq!(
+            Resistances::parse_shorthand("troll fire, cold", Resistance::Resistant),
+            Some(resistances(
+                "troll",
+                Resistance::Resistant,
+                &["fire", "cold"]
+            ))
+        );
+        assert_eq!(
+            Resistances::parse_natural_language("The troll is resistant to Fire and cold damage."),
+            Some(resistances(
+                "The troll",
+                Resistance::Resistant,
+                &["fire", "cold"]
+            ))
+        );
+        assert_eq!(
+            Resistances::parse_natural_language("Skeleton A is immune to poison"),
+            Some(resistances("Skeleton A", Resistance::Immune, &["poison"]))
+        );
+        assert_eq!(
+            Resistances::parse_natural_language("Troll resists fire, cold and acid"),
+            Some(resistances(
+                "Troll",
+                Resistance::Resistant,
+                &["fire", "cold", "acid"]
+            ))
+        );
+        assert_eq!(
+            Resistances::parse_natural_language("Troll is immune to charm"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resistance_reduce() {
+        assert_eq!(Resistance::Resistant.reduce(13), 6);
+        assert_eq!(Resistance::Immune.reduce(13), 0);
+        assert_eq!(
+            Resistance::Resistant.note(13, "fire"),
+            "halved from 13 by resistance to fire"
+        );
+    }
+
+    #[test]
+    fn test_damage_message() {
+        let hit_points = |current: i32, hidden: bool| {
+            Some(HitPoints {
+                current,
+                maximum: 84,
+                hidden,
+            })
+        };
+        let message = |before, after| damage_message("Troll", "12 fire damage", before, after);
+        assert_eq!(
+            message(hit_points(84, false), hit_points(72, false)),
+            "Troll takes 12 fire damage and has 72 hit points left."
+        );
+        assert_eq!(
+            message(hit_points(54, false), hit_points(42, false)),
+            "🩸 Troll takes 12 fire damage and is bloodied, with 42 hit points left!"
+        );
+        assert_eq!(
+            message(hit_points(42, false), hit_points(30, false)),
+            "Troll takes 12 fire damage and has 30 hit points left."
+        );
+        assert_eq!(
+            message(hit_points(84, true), hit_points(72, true)),
+            "Troll takes 12 fire damage and looks wounded."
+        );
+        assert_eq!(
+            message(hit_points(54, true), hit_points(42, true)),
+            "🩸 Troll takes 12 fire damage and is bloodied!"
+        );
+        assert_eq!(
+            message(hit_points(42, true), hit_points(30, true)),
+            "Troll takes 12 fire damage and looks bloodied."
+        );
+        assert_eq!(
+            message(hit_points(12, true), hit_points(0, true)),
+            "💀 Troll takes 12 fire damage and is defeated! They've been removed from the initiative order."
+        );
+        assert_eq!(message(None, None), "Troll takes 12 fire damage.");
+    }
+}
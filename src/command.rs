@@ -1,60 +1,561 @@
-use crate::character_roll::CharacterRoll;
+use crate::attack_roll::{AttackCount, AttackRoll, Cover, Handedness, Smite, WeaponAttackRoll};
+use crate::bonus::Bonus;
+use crate::channel::{DungeonMaster, Verbosity, MAXIMUM_DUPLICATE_WINDOW};
+use crate::character::{
+    AbilityName, CharacterCopy, FightingStyle, ImportSource, Recipient, SkillName,
+    MAXIMUM_DISPLAY_NAME_LENGTH,
+};
+use crate::character_effect::CharacterEffect;
+use crate::character_roll::{CharacterRoll, Check, Contest};
+use crate::class::Class;
+use crate::combatant::{Damage, Resistance, Resistances};
+use crate::command_role::CommandCategory;
+use crate::companion::CompanionKind;
+use crate::counter::{Counter, Rest};
+use crate::currency::MAXIMUM_COINS;
+use crate::dungeon;
+use crate::edition::Edition;
 use crate::error;
-use crate::intent_parser::parse_intent_result;
+use crate::guild::{ChannelDefault, DEFAULT_PREFIX};
+use crate::house_rule::HouseRule;
+use crate::initiative;
+use crate::initiative::InitiativeOrder;
+use crate::intent_parser::{parse_intent_result, parse_keyword_fallback};
+use crate::lore::Lore;
+use crate::monster::{self, ChallengeRating, Monster, MonsterRole};
+use crate::note;
+use crate::ongoing_effect::OngoingEffect;
+use crate::purpose::Purpose;
+use crate::random_table::RandomTable;
 use crate::response::Response;
 use crate::roll;
-use crate::roll::ConditionalRoll;
 use crate::roll::Error as RollError;
-use crate::weapon::AmbiguousWeaponName;
-use regex::Regex;
+use crate::roll::{Condition, ConditionalRoll, Limits};
+use crate::settlement::SettlementSize;
+use crate::stash::Loot;
+use crate::status::Status;
+use crate::suggestion;
+use crate::time::TimeZone;
+use crate::travel::Journey;
+use crate::treasure::{Treasure, TreasureKind, TreasureTier};
+use crate::weapon::{AmbiguousWeaponName, WeaponName};
+use crate::weather::Season;
+use regex::{Captures, Regex};
+use serenity::model::id::{ChannelId, MessageId, RoleId, UserId};
 use snips_nlu_lib::SnipsNluEngine;
 use snips_nlu_ontology::IntentParserResult;
+use std::convert::identity;
 use std::fmt;
+use std::time::Duration;
 use symspell::{SymSpell, UnicodeStringStrategy};
 
 #[derive(Debug)]
 pub enum Command {
-    AttackRoll(crate::attack_roll::AttackRoll),
-    CharacterRoll(crate::character_roll::CharacterRoll),
+    AddBonus(crate::bonus::Bonus),
+    AddCharacterEffect(crate::character_effect::CharacterEffect),
+    AddCombatant(crate::initiative::InitiativeEntry, Option<i32>, bool),
+    AddCompanion(crate::companion::CompanionKind, String),
+    AddMonsters(i32, String, bool),
+    AddNote(String),
+    AddOngoingEffect(crate::ongoing_effect::OngoingEffect),
+    AddStatus(crate::status::Status, Option<i32>),
+    AddWeaponMastery(crate::weapon::WeaponName),
+    AddWildShapeForm(String),
+    AsCompanion(String, Box<Command>),
+    AttackRoll(
+        crate::attack_roll::AttackRoll,
+        crate::attack_roll::AttackCount,
+        Option<crate::attack_roll::Smite>,
+        Option<String>,
+        Option<crate::attack_roll::Cover>,
+    ),
+    AwardExperience(i32, Option<String>),
+    BlindRoll(Box<Command>),
+    CastSpell(String, Option<i32>, bool),
+    CharacterRoll(
+        crate::character_roll::CharacterRoll,
+        Option<crate::purpose::Purpose>,
+    ),
+    Composite(Vec<Command>),
+    ContestedCheck(
+        crate::character_roll::Contest,
+        Option<crate::character::Recipient>,
+        crate::character::Recipient,
+    ),
+    CopyCharacter(crate::character::CharacterCopy),
+    CreateCharacter(String),
+    CreatePregen(crate::class::Class, i32),
+    DamageCombatant(crate::combatant::Damage),
+    DamageRoll(crate::attack_roll::AttackRoll, bool),
+    DamageWildShape(i32),
+    DeleteCharacter(String),
+    DeleteMonster(String),
+    DeleteNote(i64),
+    DeleteRandomTable(String),
+    DepositInStash(crate::stash::Loot),
+    DisableContentPack(String),
+    EnableContentPack(String),
+    EncounterDifficulty(String),
+    EndEncounter,
+    ExportChannelSettings,
+    ExportCharacter,
+    ExportTrainingData,
+    FlankingAttack(Box<Command>),
+    GenerateDungeon(usize),
+    GenerateSettlement(Option<crate::settlement::SettlementSize>),
+    GenerateTavern,
+    GainCurrency(crate::currency::Amount),
+    GrantBardicInspiration(crate::character::Recipient),
+    GrantInspiration(serenity::model::id::UserId),
+    GroupSavingThrow(crate::character::AbilityName, i32),
+    HealCombatant(String, i32),
+    HealWildShape(i32),
     Help,
     HelpShorthand,
-    Roll(crate::roll::ConditionalRoll),
+    CheckPermissions,
+    ClearCharacterEffects,
+    ClearInitiativeOrder,
+    ClearOngoingEffects(String),
+    ClearResistances(String),
+    ImportChannelSettings,
+    ImportCharacter(crate::character::ImportSource),
+    ImproviseMonster(crate::monster::ChallengeRating, crate::monster::MonsterRole),
+    InstallContentPack,
+    MonsterAttack(String, Option<crate::roll::Condition>, Option<String>),
+    NextRound,
+    NextTurn,
+    PinQuickReference,
+    PlanTravel(crate::travel::Journey),
+    PurgeUserFromChannel(serenity::model::id::UserId),
+    RecallLore(crate::character_roll::CharacterRoll, String),
+    ReloadModel,
+    RemoveBonus(String),
+    RemoveCharacterEffect(String),
+    RemoveContentPack(String),
+    RemoveCounter(String),
+    RemoveLore(String),
+    RemoveStatus(crate::status::Status),
+    RemoveWeaponMastery(crate::weapon::WeaponName),
+    RevealHiddenRolls,
+    RevealLore(String),
+    RevertWildShape,
+    ResetHouseRules,
+    Rest(crate::counter::Rest),
+    RestoreCounter(String, Option<i32>),
+    RestrictCommands(crate::command_role::CommandCategory, Option<RoleId>),
+    Roll(
+        crate::roll::ConditionalRoll,
+        Option<crate::purpose::Purpose>,
+    ),
+    RollForTable(crate::roll::ConditionalRoll),
+    RollHidden(crate::roll::ConditionalRoll),
+    RollRandomTable(String),
+    RollTreasure(crate::treasure::Treasure),
+    RollWeather(Option<crate::weather::Season>),
+    SaveMonster(crate::monster::Monster),
+    SetAbility(crate::character::AbilityName, i32),
+    SetAlert(bool),
+    SetArmorClass(i32),
+    SetChannelDiceOnly(bool),
+    /// How long identical commands are ignored for in the channel, or zero to not ignore them.
+    SetChannelDuplicateWindow(Duration),
+    SetChannelEnabled(bool),
+    SetChannelKeepRollMessages(bool),
+    SetChannelLocked(bool),
+    SetChannelSpellingCorrection(bool),
+    SetChannelPrefix(String),
+    SetCharacterName(String),
+    SetClass(crate::class::Class, Option<i32>, Option<String>),
+    SetCombatantArmorClass(String, i32),
+    SetCombatantExperience(String, i32),
+    SetCounter(crate::counter::Counter),
+    SetDiceLimits(Option<crate::roll::Limits>),
+    SetDungeonMaster(Option<DungeonMaster>),
+    SetEdition(crate::edition::Edition),
+    SetFightingStyle(Option<crate::character::FightingStyle>),
+    SetHouseRule(HouseRule),
+    SetHitPoints(String, i32, bool),
+    SetInitiativeBonus(i32),
+    SetInitiativeOrder(crate::initiative::InitiativeOrder),
+    SetLevel(i32),
+    SetLore(crate::lore::Lore),
+    SetObservant(bool),
+    SetPortrait(String),
+    SetRandomTable(crate::random_table::RandomTable),
+    SetResistances(crate::combatant::Resistances),
+    SetServerDefault(crate::guild::ChannelDefault),
+    SetServerTimeZone(Option<crate::time::TimeZone>),
+    SetTimeZone(Option<crate::time::TimeZone>),
+    ShowBonuses,
+    ShowChannelSettings,
+    ShowCharacterAudit(Option<serenity::model::id::UserId>),
+    ShowCharacterEffects,
+    ShowCharacterSheet,
+    ShowCommandRestrictions,
+    ShowCompanions,
+    ShowContentPacks,
+    ShowCounters,
+    ShowCurrency,
+    ShowDiceLimits,
+    ShowDungeonMaster,
+    ShowEdition,
+    ShowHouseRules,
+    ShowInitiativeOrder,
+    ShowLore,
+    ShowMonster(String),
+    ShowMonsters,
+    ShowNotes(Option<String>),
+    ShowPartyCurrency,
+    ShowPassiveScore(Check),
+    ShowPassiveScores,
+    ShowRandomTable(String),
+    ShowRandomTables,
+    ShowServerDefaults,
+    ShowSpellSlots,
+    ShowStash,
+    ShowStatuses,
+    ShowTimeZone,
+    ShowWeaponMasteries,
+    ShowWildShapes,
+    SneakAttackRoll,
+    SpendCounter(String, i32),
+    SpendCurrency(crate::currency::Amount),
+    SwitchCharacter(String),
+    ToHitRoll(
+        crate::attack_roll::AttackRoll,
+        Option<String>,
+        Option<crate::attack_roll::Cover>,
+    ),
+    UseBardicInspiration(Box<Command>),
+    UseInspiration(Box<Command>),
+    VerifyRoll(String),
+    WithdrawFromStash(crate::stash::Loot),
+    WildShape(String),
+    WildShapeAttack(
+        Option<String>,
+        Option<crate::roll::Condition>,
+        Option<String>,
+    ),
 }
 
 impl Command {
     pub fn description(&self) -> &str {
         match self {
-            Command::AttackRoll(_) => "perform an attack roll",
-            Command::CharacterRoll(_) => "perform a character roll",
+            Command::AddBonus(_) => "add a bonus to your character's rolls",
+            Command::AddCharacterEffect(_) => "add an effect such as Bless to your character",
+            Command::AddCombatant(_, _, _) => "add a combatant to the initiative order",
+            Command::AddCompanion(_, _) => "add a companion",
+            Command::AddMonsters(_, _, _) => "add monsters to the initiative order",
+            Command::AddNote(_) => "add a note",
+            Command::AddOngoingEffect(_) => "add ongoing damage or healing to a combatant",
+            Command::AddStatus(_, _) => "add a condition to your character",
+            Command::AddWeaponMastery(_) => "master a weapon",
+            Command::AddWildShapeForm(_) => "add a wild shape form",
+            Command::AsCompanion(_, command) => command.description(),
+            Command::AttackRoll(_, _, _, _, _) => "perform an attack roll",
+            Command::AwardExperience(_, _) => "award experience points",
+            Command::BlindRoll(_) => "roll blind for the DM",
+            Command::CastSpell(_, _, _) => "cast a spell",
+            Command::CharacterRoll(_, _) => "perform a character roll",
+            Command::Composite(_) => "perform several commands",
+            Command::ContestedCheck(contest, _, _) => match contest {
+                Contest::Grapple => "grapple another character",
+                Contest::Shove => "shove another character",
+            },
+            Command::CopyCharacter(_) => "copy a character from another channel",
+            Command::CreateCharacter(_) => "create a character",
+            Command::CreatePregen(_, _) => "create a pregenerated character",
+            Command::DamageCombatant(_) => "deal damage to a combatant",
+            Command::DamageRoll(_, _) => "roll the damage of an attack",
+            Command::DamageWildShape(_) => "deal damage to your wild shape",
+            Command::DeleteCharacter(_) => "delete a character",
+            Command::DeleteMonster(_) => "delete a saved monster",
+            Command::DeleteNote(_) => "delete a note",
+            Command::DeleteRandomTable(_) => "delete a random table",
+            Command::DepositInStash(_) => "put something in the party stash",
+            Command::DisableContentPack(_) => "disable a monster pack",
+            Command::EnableContentPack(_) => "enable a monster pack",
+            Command::EncounterDifficulty(_) => "work out how difficult an encounter would be",
+            Command::EndEncounter => "end the encounter",
+            Command::ExportChannelSettings => "export the channel's settings",
+            Command::ExportCharacter => "export your character",
+            Command::ExportTrainingData => {
+                "export the training data for the natural language parser"
+            }
+            Command::FlankingAttack(_) => "attack while flanking",
+            Command::GainCurrency(_) => "add coins to your purse",
+            Command::GenerateDungeon(_) => "generate a dungeon",
+            Command::GenerateSettlement(_) => "generate a settlement",
+            Command::GenerateTavern => "generate a tavern",
+            Command::GrantBardicInspiration(_) => "grant bardic inspiration",
+            Command::GrantInspiration(_) => "grant inspiration",
+            Command::GroupSavingThrow(_, _) => "ask everyone to make a saving throw",
+            Command::HealCombatant(_, _) => "heal a combatant",
+            Command::HealWildShape(_) => "heal your wild shape",
             Command::Help | Command::HelpShorthand => "ask for help",
-            Command::Roll(_) => "perform a roll",
+            Command::CheckPermissions => "check Dungeon Helper's permissions",
+            Command::ClearCharacterEffects => "end the effects on your character",
+            Command::ClearInitiativeOrder => "clear the initiative order",
+            Command::ClearOngoingEffects(_) => "clear a combatant's ongoing effects",
+            Command::ClearResistances(_) => "clear a combatant's resistances and immunities",
+            Command::ImportChannelSettings => "import settings into the channel",
+            Command::ImportCharacter(_) => "import a character",
+            Command::InstallContentPack => "install a monster pack",
+            Command::ImproviseMonster(_, _) => "improvise a monster's stat block",
+            Command::MonsterAttack(_, _, _) => "roll an attack for a saved monster",
+            Command::NextRound => "start the next round",
+            Command::NextTurn => "start the next turn",
+            Command::PinQuickReference => "pin your character's quick reference",
+            Command::PlanTravel(_) => "work out how far the party can travel",
+            Command::PurgeUserFromChannel(_) => {
+                "delete everything stored about a user in this channel"
+            }
+            Command::RecallLore(_, _) => "recall what your character knows about something",
+            Command::ReloadModel => "reload the model for the natural language parser",
+            Command::RemoveBonus(_) => "remove a bonus from your character's rolls",
+            Command::RemoveCharacterEffect(_) => "end an effect on your character",
+            Command::RemoveCounter(_) => "remove a counter from your character",
+            Command::RemoveLore(_) => "remove lore",
+            Command::RemoveContentPack(_) => "remove a monster pack",
+            Command::RemoveStatus(_) => "remove a condition from your character",
+            Command::RemoveWeaponMastery(_) => "forget a weapon mastery",
+            Command::RevealLore(_) => "reveal lore to the players",
+            Command::RevertWildShape => "revert to your normal form",
+            Command::ResetHouseRules => "go back to the rules as written in this channel",
+            Command::Rest(_) => "take a rest",
+            Command::RestoreCounter(_, _) => "restore one of your character's counters",
+            Command::RestrictCommands(_, Some(_)) => "restrict commands to a role",
+            Command::RestrictCommands(_, None) => "let everyone use commands",
+            Command::Roll(_, _) => "perform a roll",
+            Command::RollForTable(_) => "roll for everyone at the table",
+            Command::RollHidden(_) => "roll into the dice cup",
+            Command::RollRandomTable(_) => "roll on a random table",
+            Command::RollTreasure(_) => "roll treasure",
+            Command::RollWeather(_) => "roll the weather",
+            Command::SaveMonster(_) => "save a monster's stat block",
+            Command::SetAbility(_, _) => "set one of your character's ability scores",
+            Command::SetAlert(_) => "set whether your character has the Alert feat",
+            Command::SetArmorClass(_) => "set your character's armour class",
+            Command::SetChannelDiceOnly(_) => "change whether the channel is dice only",
+            Command::SetChannelDuplicateWindow(_) => {
+                "change how long duplicate commands are ignored for in the channel"
+            }
+            Command::SetChannelEnabled(true) => "enable Dungeon Helper in the channel",
+            Command::SetChannelEnabled(false) => "disable Dungeon Helper in the channel",
+            Command::SetChannelKeepRollMessages(_) => {
+                "change whether roll commands are kept in the channel"
+            }
+            Command::SetChannelLocked(true) => "lock the characters in the channel",
+            Command::SetChannelLocked(false) => "unlock the characters in the channel",
+            Command::SetChannelPrefix(_) => "change the prefix for shorthand commands",
+            Command::SetChannelSpellingCorrection(_) => {
+                "change whether commands in the channel are spelling corrected"
+            }
+            Command::SetCharacterName(_) => "set your character's name",
+            Command::SetClass(_, _, _) => "set your character's class",
+            Command::SetCombatantArmorClass(_, _) => "set a combatant's armour class",
+            Command::SetCombatantExperience(_, _) => {
+                "set how many experience points a combatant is worth"
+            }
+            Command::SetCounter(_) => "add a counter to your character",
+            Command::SetDiceLimits(_) => "set the most dice that can be rolled in this channel",
+            Command::SetDungeonMaster(_) => "set the DM of this channel",
+            Command::SetEdition(_) => "set the edition of the rules used in this channel",
+            Command::SetFightingStyle(_) => "set your character's fighting style",
+            Command::SetHouseRule(_) => "change the house rules used in this channel",
+            Command::SetHitPoints(_, _, _) => "set a combatant's hit points",
+            Command::SetInitiativeBonus(_) => "set your character's initiative bonus",
+            Command::SetInitiativeOrder(_) => "set the initiative order",
+            Command::SetLevel(_) => "set your character's level",
+            Command::SetLore(_) => "add lore for the characters to recall",
+            Command::SetRandomTable(_) => "add a random table",
+            Command::SetObservant(_) => "set whether your character has the Observant feat",
+            Command::SetPortrait(_) => "set your character's portrait",
+            Command::SetResistances(_) => "set the types of damage a combatant resists",
+            Command::SetServerDefault(_) => "change the settings that new channels start with",
+            Command::SetServerTimeZone(_) => "set the time zone used in this server",
+            Command::SetTimeZone(_) => "set your time zone",
+            Command::ShowBonuses => "view your character's bonuses",
+            Command::ShowChannelSettings => "view the channel's settings",
+            Command::ShowCharacterAudit(_) => "review recent changes to characters",
+            Command::ShowCharacterEffects => "view the effects on your character",
+            Command::ShowCharacterSheet => "view your character sheet",
+            Command::ShowCommandRestrictions => "view the roles that commands are restricted to",
+            Command::ShowCompanions => "view your companions",
+            Command::ShowCounters => "view your character's counters",
+            Command::ShowCurrency => "view your coins",
+            Command::ShowDiceLimits => "view the most dice that can be rolled in this channel",
+            Command::ShowDungeonMaster => "view the DM of this channel",
+            Command::ShowEdition => "view the edition of the rules used in this channel",
+            Command::ShowHouseRules => "view the house rules used in this channel",
+            Command::ShowContentPacks => "view the monster packs",
+            Command::ShowInitiativeOrder => "view the initiative order",
+            Command::ShowLore => "view the lore the characters have recalled",
+            Command::ShowMonster(_) => "view a saved monster's stat block",
+            Command::ShowMonsters => "view the saved monsters",
+            Command::ShowNotes(None) => "view the notes",
+            Command::ShowNotes(Some(_)) => "search the notes",
+            Command::ShowRandomTable(_) => "view a random table's entries",
+            Command::ShowRandomTables => "view the random tables",
+            Command::ShowServerDefaults => "view the settings that new channels start with",
+            Command::ShowPartyCurrency => "view the party's coins",
+            Command::ShowPassiveScore(_) => "view your character's passive score",
+            Command::ShowPassiveScores => "view your character's passive scores",
+            Command::ShowSpellSlots => "view your spell slots",
+            Command::ShowStash => "view the party stash",
+            Command::ShowStatuses => "view your character's conditions",
+            Command::ShowTimeZone => "view your time zone",
+            Command::ShowWeaponMasteries => "view the weapons your character has mastered",
+            Command::ShowWildShapes => "view your wild shape forms",
+            Command::SneakAttackRoll => "roll sneak attack",
+            Command::SpendCounter(_, _) => "spend one of your character's counters",
+            Command::SpendCurrency(_) => "spend coins from your purse",
+            Command::SwitchCharacter(_) => "switch characters",
+            Command::ToHitRoll(_, _, _) => "roll to hit with an attack",
+            Command::UseBardicInspiration(_) => "use your bardic inspiration",
+            Command::UseInspiration(_) => "use your inspiration",
+            Command::VerifyRoll(_) => "verify a roll",
+            Command::WithdrawFromStash(_) => "take something from the party stash",
+            Command::WildShape(_) => "wild shape into a beast",
+            Command::WildShapeAttack(_, _, _) => "attack in your wild shape",
         }
     }
 }
 
+/// The kind of inspiration used on a roll.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Inspiration {
+    /// Inspiration granted by the DM, which gives the roll advantage.
+    Inspiration,
+    /// A Bardic Inspiration die granted by a bard, which is added to the roll.
+    Bardic,
+}
+
 #[derive(Debug)]
 pub enum Error {
     // Shorthand commands
-    CharacterRollParserError,
+    AddCombatantParserError,
+    AddMonstersParserError,
+    AlertParserError,
+    ArmorClassParserError,
+    AuditParserError,
+    BardicInspirationParserError,
+    BlindRollParserError,
+    BonusParserError,
+    CastSpellParserError,
+    ChannelParserError,
+    CharacterEffectParserError,
+    CharacterRollParserError(Vec<SkillName>),
+    ClassParserError,
+    CombatantArmorClassParserError,
+    CompanionParserError,
+    ContentPackParserError,
+    ContestParserError,
+    CopyCharacterParserError,
+    CounterParserError,
+    DamageParserError,
+    DiceLimitsParserError,
+    DifficultyParserError,
+    DungeonMasterParserError,
+    DungeonParserError,
+    EditionParserError,
+    ExperienceParserError,
+    FightingStyleParserError,
+    GroupSavingThrowParserError,
+    HealParserError,
+    HitPointsParserError,
+    HouseRuleParserError,
+    ImportParserError,
+    ImproviseParserError,
+    InitiativeBonusParserError,
+    InitiativeOrderParserError(initiative::ParserError),
+    InspirationParserError,
+    LoreParserError,
+    MasteryParserError(Vec<WeaponName>),
+    MonsterParserError,
+    NoteParserError,
+    ObservantParserError,
+    OngoingEffectParserError,
+    PassiveParserError,
+    PortraitParserError,
+    PregenParserError,
+    PurgeParserError,
+    RandomTableParserError,
+    RegenerationParserError,
+    ResistanceParserError,
+    RestParserError,
+    RestrictParserError,
     RollParserError(roll::ParserError),
+    ServerParserError,
+    SettlementParserError,
+    StashParserError,
+    StatusParserError,
+    TimeZoneParserError,
+    TravelParserError,
+    TreasureParserError,
+    VerifyParserError,
+    WeatherParserError,
+    WildShapeParserError,
 
     // Natural language commands
+    AddStatusMissingStatus,
+    AttackTargetNotAttackRoll,
+    AwardExperienceMissingAmount,
+    CopyCharacterLevelOutOfRange(i32),
+    CreateCharacterMissingName,
+    CreatePregenMissingClass,
+    CreatePregenLevelOutOfRange(i32),
+    CurrencyAmountOutOfRange(i32),
+    DeleteCharacterMissingName,
+    ExperienceAmountNonPositive(i32),
+    GainCurrencyMissingAmount,
+    GainCurrencyMissingDenomination,
     IntentParserError(::failure::Error),
     NoIntent,
+    /// The intent parser isn't loaded, and the message isn't one of the few commands the
+    /// rule-based fallback understands.
+    NoRuleBasedIntent,
+    /// The intent parser wasn't confident enough in its best guess, with the description of its
+    /// command.
+    LowConfidence(String),
+    RemoveStatusMissingStatus,
     RollAbilityMissingAbility,
     RollAttackAmbiguousWeapon(AmbiguousWeaponName),
     RollAttackMissingClassification,
     RollAttackMissingHandedness,
-    RollAttackMissingWeapon,
+    RollAttackMissingWeapon(Vec<WeaponName>),
+    RollAttackOffHandWeaponNotLight(crate::weapon::WeaponName),
+    RollAttackRangeWeaponNotRanged(crate::weapon::WeaponName),
     RollDiceMissingSides,
     RollDiceInvalid(RollError, usize, i32),
     RollSavingThrowMissingAbility,
-    RollSkillMissingSkill,
+    RollSkillMissingSkill(Vec<SkillName>),
+    SetAbilityOutOfRange(AbilityName, i32),
+    SetArmorClassMissingValue,
+    SetArmorClassOutOfRange(i32),
+    SetCharacterNameMissingName,
+    SetCharacterNameTooLong(usize),
+    SetInitiativeBonusOutOfRange(i32),
+    SetClassLevelOutOfRange(i32),
+    SetClassMissingClass,
+    SetLevelOutOfRange(i32),
+    SmiteNotAttackRoll,
+    FlankingNotAttackRoll,
+    CompanionNotCharacterRoll,
+    SpendCurrencyMissingAmount,
+    SpendCurrencyMissingDenomination,
+    SwitchCharacterMissingName,
     UnknownIntent(String),
+    Within5FeetNotAttackRoll,
+    CoverNotAttackRoll,
+    UseBardicInspirationNotD20Roll,
+    UseInspirationNotD20Roll,
 }
 
 impl Error {
-    pub fn into_response(self) -> Response {
+    /// Convert the error into a response, phrasing any clarification with the channel's
+    /// verbosity.
+    pub fn into_response(self, verbosity: Verbosity) -> Response {
         match self {
             Error::IntentParserError(error) => {
                 Response::Error(error::Error::IntentParserError(error))
@@ -62,91 +563,1085 @@ impl Error {
             Error::UnknownIntent(intent_name) => {
                 Response::Error(error::Error::UnknownIntent(intent_name))
             }
-            error => Response::Clarification(error.to_string()),
+            error => Response::Clarification(error.clarification(verbosity)),
         }
     }
-}
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// Explain what went wrong and what to try instead. A terse clarification keeps only the
+    /// hint of what to try, and a verbose one also points to the help.
+    pub fn clarification(&self, verbosity: Verbosity) -> String {
+        let message = self.to_string();
+        match verbosity {
+            Verbosity::Terse => self.hint().unwrap_or(message),
+            Verbosity::Normal => message,
+            Verbosity::Verbose if message.contains("help") => message,
+            Verbosity::Verbose => format!(
+                "{} Type `!help` to see the shorthand commands, or mention me and say \"help\" to see what else you can ask.",
+                message
+            ),
+        }
+    }
+
+    /// What went wrong, such as that the syntax of a command is invalid.
+    fn problem(&self) -> String {
         match self {
-            Error::CharacterRollParserError => {
-                write!(f, "It looks like you're trying to roll a skill or ability check, but the syntax is invalid. Try typing `!help` for some examples.")
+            Error::AddCombatantParserError => {
+                "It looks like you're trying to add a combatant to the initiative order, but the syntax is invalid.".to_owned()
+            }
+            Error::AddMonstersParserError => {
+                "It looks like you're trying to add monsters to the encounter, but the syntax is invalid.".to_owned()
+            }
+            Error::AlertParserError => {
+                "It looks like you're trying to set whether your character has the Alert feat, but the syntax is invalid.".to_owned()
+            }
+            Error::ArmorClassParserError => {
+                "It looks like you're trying to set your character's armour class, but the syntax is invalid.".to_owned()
+            }
+            Error::AuditParserError => {
+                "It looks like you're trying to review recent changes to characters, but I'm not sure whose.".to_owned()
+            }
+            Error::CharacterRollParserError(_) => {
+                "It looks like you're trying to roll a skill or ability check, but the syntax is invalid.".to_owned()
+            }
+            Error::ClassParserError => {
+                "It looks like you're trying to set your character's class, but I don't recognise that class.".to_owned()
+            }
+            Error::CombatantArmorClassParserError => {
+                "It looks like you're trying to set a combatant's armour class, but the syntax is invalid. It must be between 1 and 30.".to_owned()
+            }
+            Error::CompanionParserError => {
+                "It looks like you're trying to add a companion, but the syntax is invalid.".to_owned()
+            }
+            Error::ContestParserError => {
+                "It looks like you're trying to grapple or shove another character, but the syntax is invalid.".to_owned()
+            }
+            Error::CopyCharacterParserError => {
+                "It looks like you're trying to copy a character into this channel, but the syntax is invalid.".to_owned()
+            }
+            Error::ContentPackParserError => {
+                "It looks like you're trying to manage the monster packs, but the syntax is invalid.".to_owned()
+            }
+            Error::CounterParserError => {
+                "It looks like you're trying to change your character's counters, but the syntax is invalid.".to_owned()
+            }
+            Error::DamageParserError => {
+                "It looks like you're trying to deal damage to a combatant, but the syntax is invalid.".to_owned()
+            }
+            Error::DiceLimitsParserError => {
+                format!("It looks like you're trying to set the most dice that can be rolled in this channel, but the syntax is invalid. The limits can be no more than {}.", Limits::MAXIMUM)
+            }
+            Error::DifficultyParserError => {
+                "It looks like you're trying to work out how difficult an encounter would be, but I'm not sure which monsters are in it.".to_owned()
+            }
+            Error::DungeonMasterParserError => {
+                "It looks like you're trying to set the DM of this channel, but I'm not sure who it is.".to_owned()
+            }
+            Error::DungeonParserError => {
+                format!("It looks like you're trying to generate a dungeon, but I'm not sure how many rooms it should have. A dungeon can have up to {} rooms.", dungeon::MAXIMUM_ROOMS)
+            }
+            Error::EditionParserError => {
+                "It looks like you're trying to set the edition of the rules used in this channel, but I'm not sure which edition.".to_owned()
+            }
+            Error::BardicInspirationParserError => {
+                "It looks like you're trying to give bardic inspiration, but I'm not sure who to give it to.".to_owned()
+            }
+            Error::BlindRollParserError => {
+                "It looks like you're trying to roll blind for the DM, but the syntax is invalid.".to_owned()
+            }
+            Error::BonusParserError => {
+                "It looks like you're trying to add a bonus to your character's rolls, but the syntax is invalid.".to_owned()
+            }
+            Error::CastSpellParserError => {
+                "It looks like you're trying to cast a spell, but the syntax is invalid. Spell slots go from 1st to 9th level, and rituals are cast without one.".to_owned()
+            }
+            Error::ChannelParserError => {
+                format!("It looks like you're trying to change the channel's settings, but I don't recognise that setting. Prefixes are one to three symbols, and the duplicate window is at most {} seconds.", MAXIMUM_DUPLICATE_WINDOW.as_secs())
+            }
+            Error::CharacterEffectParserError => {
+                "It looks like you're trying to add an effect to your character, but I don't recognise it.".to_owned()
+            }
+            Error::ExperienceParserError => {
+                "It looks like you're trying to award experience points, but the syntax is invalid.".to_owned()
+            }
+            Error::FightingStyleParserError => {
+                "It looks like you're trying to set your character's fighting style, but I don't recognise that style.".to_owned()
+            }
+            Error::GroupSavingThrowParserError => {
+                "It looks like you're trying to ask everyone to make a saving throw, but I'm not sure which ability or what the DC is.".to_owned()
+            }
+            Error::HealParserError => {
+                "It looks like you're trying to heal a combatant, but the syntax is invalid.".to_owned()
+            }
+            Error::HouseRuleParserError => {
+                "It looks like you're trying to change the house rules, but I don't recognise that rule.".to_owned()
+            }
+            Error::HitPointsParserError => {
+                "It looks like you're trying to set a combatant's hit points, but the syntax is invalid.".to_owned()
+            }
+            Error::ImportParserError => {
+                "It looks like you're trying to import a character, but I don't recognise where from.".to_owned()
+            }
+            Error::ImproviseParserError => {
+                "It looks like you're trying to improvise a monster's stat block, but the syntax is invalid.".to_owned()
+            }
+            Error::InitiativeBonusParserError => {
+                "It looks like you're trying to set your character's initiative bonus, but the syntax is invalid.".to_owned()
+            }
+            Error::InitiativeOrderParserError(error) => {
+                format!("It looks like you're trying to set the initiative order, but the list is invalid. {}", error)
+            }
+            Error::InspirationParserError => {
+                "It looks like you're trying to grant inspiration, but I'm not sure who to give it to.".to_owned()
+            }
+            Error::LoreParserError => {
+                "It looks like you're trying to change the lore, but the syntax is invalid.".to_owned()
+            }
+            Error::MasteryParserError(_) => {
+                "It looks like you're trying to change the weapons your character has mastered, but I'm not sure which weapon.".to_owned()
+            }
+            Error::MonsterParserError => {
+                "It looks like you're trying to save or use a monster, but the syntax is invalid.".to_owned()
+            }
+            Error::NoteParserError => {
+                format!("It looks like you're trying to add or delete a note, but I'm not sure what the note says, or which note it is. Notes can be up to {} characters long.", note::MAXIMUM_LENGTH)
+            }
+            Error::ObservantParserError => {
+                "It looks like you're trying to set whether your character has the Observant feat, but the syntax is invalid.".to_owned()
+            }
+            Error::OngoingEffectParserError => {
+                "It looks like you're trying to add ongoing damage to a combatant, but the syntax is invalid.".to_owned()
+            }
+            Error::PassiveParserError => {
+                "It looks like you're trying to view a passive score, but I don't recognise the ability or skill.".to_owned()
+            }
+            Error::PortraitParserError => {
+                "It looks like you're trying to set your character's portrait, but that isn't a valid image URL.".to_owned()
+            }
+            Error::PregenParserError => {
+                "It looks like you're trying to create a pregenerated character, but I don't recognise that class or level.".to_owned()
+            }
+            Error::PurgeParserError => {
+                "It looks like you're trying to delete everything stored about a user in this channel, but I'm not sure who.".to_owned()
+            }
+            Error::RandomTableParserError => {
+                "It looks like you're trying to add or use a random table, but the syntax is invalid.".to_owned()
+            }
+            Error::RegenerationParserError => {
+                "It looks like you're trying to add regeneration to a combatant, but the syntax is invalid.".to_owned()
+            }
+            Error::ResistanceParserError => {
+                "It looks like you're trying to set the types of damage a combatant resists, but the syntax is invalid.".to_owned()
+            }
+            Error::RestParserError => {
+                "It looks like you're trying to take a rest, but I'm not sure what kind.".to_owned()
+            }
+            Error::RestrictParserError => {
+                "It looks like you're trying to restrict commands to a role, but I don't recognise the commands or the role. The commands are editing, rolling and dm.".to_owned()
             }
             Error::RollParserError(error) => {
-                write!(f, "It looks like you're trying to some dice, but the syntax is invalid. {} Try typing `!help` for some examples.", error)
+                format!("It looks like you're trying to some dice, but the syntax is invalid. {}", error)
+            }
+            Error::AwardExperienceMissingAmount => {
+                "It looks like you're trying to award experience points, but I'm not sure how many.".to_owned()
+            }
+            Error::ServerParserError => {
+                "It looks like you're trying to change the settings that new channels in this server start with, but I don't recognise that setting. Prefixes are one to three symbols.".to_owned()
+            }
+            Error::SettlementParserError => {
+                "It looks like you're trying to generate a settlement, but I'm not sure how big it should be.".to_owned()
+            }
+            Error::StashParserError => {
+                "It looks like you're trying to add to or take from the party stash, but I'm not sure what.".to_owned()
+            }
+            Error::StatusParserError => {
+                "It looks like you're trying to change your character's conditions, but I don't recognise that condition.".to_owned()
+            }
+            Error::TimeZoneParserError => {
+                "It looks like you're trying to set your time zone, but I don't recognise it. Time zones are offsets from UTC.".to_owned()
+            }
+            Error::TravelParserError => {
+                "It looks like you're trying to plan overland travel, but the syntax is invalid. Travel can be at a fast, normal or slow pace, for up to 24 hours a day.".to_owned()
+            }
+            Error::TreasureParserError => {
+                "It looks like you're trying to roll treasure, but I'm not sure for which challenge rating.".to_owned()
+            }
+            Error::VerifyParserError => {
+                "It looks like you're trying to verify a roll, but I'm not sure which roll.".to_owned()
+            }
+            Error::WeatherParserError => {
+                "It looks like you're trying to roll the weather, but I don't recognise the season.".to_owned()
+            }
+            Error::WildShapeParserError => {
+                "It looks like you're trying to use Wild Shape, but the syntax is invalid.".to_owned()
+            }
+            Error::AddStatusMissingStatus => {
+                "It looks like you're trying to add a condition to your character, but I'm not sure which condition.".to_owned()
+            }
+            Error::AttackTargetNotAttackRoll => {
+                "It looks like you're trying to attack a combatant, but I'm not sure what you're attacking with.".to_owned()
+            }
+            Error::CopyCharacterLevelOutOfRange(level) => {
+                format!("It looks like you're trying to copy a character into this channel at level {}, but the level must be between 1 and 20.", level)
+            }
+            Error::CreateCharacterMissingName => {
+                "It looks like you're trying to create a character, but I'm not sure what you want to name them.".to_owned()
+            }
+            Error::CreatePregenMissingClass => {
+                "It looks like you're trying to create a pregenerated character, but I'm not sure which class.".to_owned()
+            }
+            Error::CreatePregenLevelOutOfRange(level) => {
+                format!("It looks like you're trying to create a level {} pregenerated character, but the level must be between 1 and 20.", level)
+            }
+            Error::CurrencyAmountOutOfRange(amount) => {
+                format!("It looks like you're trying to add or spend {} coins. I can only add or spend between 1 and {} coins at once.", amount, MAXIMUM_COINS)
+            }
+            Error::DeleteCharacterMissingName => {
+                "It looks like you're trying to delete a character, but I'm not sure which one.".to_owned()
+            }
+            Error::ExperienceAmountNonPositive(amount) => {
+                format!("It looks like you're trying to award {} experience points. I can only award a positive number of experience points.", amount)
+            }
+            Error::GainCurrencyMissingAmount => {
+                "It looks like you're trying to add some coins to your purse, but I'm not sure how many coins you want to add.".to_owned()
+            }
+            Error::GainCurrencyMissingDenomination => {
+                "It looks like you're trying to add some coins to your purse, but I'm not sure what kind of coins you want to add.".to_owned()
+            }
+            Error::RemoveStatusMissingStatus => {
+                "It looks like you're trying to remove a condition from your character, but I'm not sure which condition.".to_owned()
             }
             Error::RollAbilityMissingAbility => {
-                write!(f, "It looks like you're trying to roll an ability check, but I'm not sure which ability you want. Try \"Roll strength\", \"Dexterity check\", etc.")
+                "It looks like you're trying to roll an ability check, but I'm not sure which ability you want.".to_owned()
             }
             Error::RollAttackAmbiguousWeapon(ambiguous_weapon) => {
-                write!(f, "It looks like you're trying to roll an attack check with a {}, but that is an ambiguous weapon name. {}", ambiguous_weapon, ambiguous_weapon.message())
+                format!("It looks like you're trying to roll an attack check with a {}, but that is an ambiguous weapon name.", ambiguous_weapon)
             }
             Error::RollAttackMissingClassification => {
-                write!(f, "It looks like you're trying to roll an attack check with an improvised weapon, but I'm not sure whether it should be a melee or ranged attack. Try \"Attack improvised weapon as melee\", \"Roll ranged improvised weapon check\", etc.")
+                "It looks like you're trying to roll an attack check with an improvised weapon, but I'm not sure whether it should be a melee or ranged attack.".to_owned()
             }
             Error::RollAttackMissingHandedness => {
-                write!(f, "It looks like you're trying to roll an attack check with a weapon that has the versatile property, but I'm not sure whether you want to attack with one hand or two hands. Try \"One-handed attack quarterstaff\", \"Roll longsword weapon check with two hands\", etc.")
+                "It looks like you're trying to roll an attack check with a weapon that has the versatile property, but I'm not sure whether you want to attack with one hand or two hands.".to_owned()
             }
-            Error::RollAttackMissingWeapon => {
-                write!(f, "It looks like you're trying to roll an attack check, but I'm not sure which weapon you want to attack with. Try \"Attack club\", \"Dagger attack\", etc.")
+            Error::RollAttackMissingWeapon(_) => {
+                "It looks like you're trying to roll an attack check, but I'm not sure which weapon you want to attack with.".to_owned()
+            }
+            Error::RollAttackOffHandWeaponNotLight(weapon) => {
+                format!("It looks like you're trying to make an off-hand attack with a {}, but only light weapons can be used in your off hand.", weapon)
+            }
+            Error::RollAttackRangeWeaponNotRanged(weapon) => {
+                format!("It looks like you're trying to make a ranged attack with a {}, but it isn't a ranged or thrown weapon.", weapon)
             }
             Error::RollDiceMissingSides => {
-                write!(f, "It looks like you're trying to roll some dice, but I'm not sure what kind of dice you want. Try \"Roll a d20\", \"Throw two four-sided dice\", etc.")
+                "It looks like you're trying to roll some dice, but I'm not sure what kind of dice you want.".to_owned()
             }
             Error::RollDiceInvalid(error, rolls, sides) => match error {
-                RollError::RollsTooGreat => {
-                    write!(f, "It looks like you're trying to roll {} dice. That's too many dice! Try rolling 100 or fewer dice.", rolls)
+                RollError::RollsTooGreat(_) => {
+                    format!("It looks like you're trying to roll {} dice. That's too many dice!", rolls)
                 }
                 RollError::SidesNonPositive => {
-                    write!(f, "It looks like you're trying to roll dice with {} sides. I can only roll a positive number of sides. Try rolling dice with one or more sides.", sides)
+                    format!("It looks like you're trying to roll dice with {} sides. I can only roll a positive number of sides.", sides)
                 }
-                RollError::SidesTooGreat => {
-                    write!(f, "It looks like you're trying to roll dice with {} sides. That's too many sides! Try rolling dice with 100 or fewer sides.", sides)
+                RollError::SidesTooGreat(_) => {
+                    format!("It looks like you're trying to roll dice with {} sides. That's too many sides!", sides)
                 }
-            }
+            },
             Error::RollSavingThrowMissingAbility => {
-                write!(f, "It looks like you're trying to roll a saving throw, but I'm not sure what kind of saving throw you want. Try \"Roll strength saving throw\", \"Dexterity saving throw\", etc.")
+                "It looks like you're trying to roll a saving throw, but I'm not sure what kind of saving throw you want.".to_owned()
+            }
+            Error::RollSkillMissingSkill(_) => {
+                "It looks like you're trying to roll a skill check, but I'm not sure what skill you want.".to_owned()
+            }
+            Error::SetAbilityOutOfRange(ability, score) => {
+                format!("It looks like you're trying to set your character's {} score to {}, but it must be between 1 and 30.", ability.as_str(), score)
+            }
+            Error::SetArmorClassMissingValue => {
+                "It looks like you're trying to set your character's armour class, but I'm not sure what to set it to.".to_owned()
+            }
+            Error::SetArmorClassOutOfRange(armor_class) => {
+                format!("It looks like you're trying to set your character's armour class to {}, but it must be between 1 and 30.", armor_class)
             }
-            Error::RollSkillMissingSkill => {
-                write!(f, "It looks like you're trying to roll a skill check, but I'm not sure what skill you want. Try \"Roll stealth\", \"Athletics check\", etc.")
+            Error::SetCharacterNameMissingName => {
+                "It looks like you're trying to name your character, but I'm not sure what name you want.".to_owned()
+            }
+            Error::SetCharacterNameTooLong(length) => {
+                format!("It looks like you're trying to name your character, but the name is {} characters long, and it can be at most {}.", length, MAXIMUM_DISPLAY_NAME_LENGTH)
+            }
+            Error::SetInitiativeBonusOutOfRange(initiative_bonus) => {
+                format!("It looks like you're trying to set your character's initiative bonus to {:+}, but it must be between -10 and +10.", initiative_bonus)
+            }
+            Error::SetClassLevelOutOfRange(level) => {
+                format!("It looks like you're trying to set your character's level in a class to {}, but it must be between 1 and 20.", level)
+            }
+            Error::SetClassMissingClass => {
+                "It looks like you're trying to set your character's class, but I'm not sure which class.".to_owned()
+            }
+            Error::SetLevelOutOfRange(level) => {
+                format!("It looks like you're trying to set your character's level to {}, but it must be between 1 and 20.", level)
+            }
+            Error::CoverNotAttackRoll => {
+                "It looks like you're trying to attack a target behind cover, but I'm not sure what you're attacking with.".to_owned()
+            }
+            Error::Within5FeetNotAttackRoll => {
+                "It looks like you're trying to attack a target within 5 feet, but I'm not sure what you're attacking with.".to_owned()
+            }
+            Error::FlankingNotAttackRoll => {
+                "It looks like you're trying to attack while flanking, but I'm not sure what you're attacking with.".to_owned()
+            }
+            Error::SmiteNotAttackRoll => {
+                "It looks like you're trying to use Divine Smite, but it can only be added to an attack.".to_owned()
+            }
+            Error::CompanionNotCharacterRoll => {
+                "It looks like you're trying to roll as your companion, but only checks, saving throws and attacks can be rolled by a companion.".to_owned()
+            }
+            Error::SpendCurrencyMissingAmount => {
+                "It looks like you're trying to spend some coins, but I'm not sure how many coins you want to spend.".to_owned()
+            }
+            Error::SpendCurrencyMissingDenomination => {
+                "It looks like you're trying to spend some coins, but I'm not sure what kind of coins you want to spend.".to_owned()
+            }
+            Error::SwitchCharacterMissingName => {
+                "It looks like you're trying to switch characters, but I'm not sure which character you want to switch to.".to_owned()
+            }
+            Error::UseBardicInspirationNotD20Roll => {
+                "It looks like you're trying to use bardic inspiration, but it can only be added to a d20 roll.".to_owned()
+            }
+            Error::UseInspirationNotD20Roll => {
+                "It looks like you're trying to use inspiration, but it can only be used on a d20 roll.".to_owned()
             }
             Error::NoIntent => {
-                write!(f, "I'm not sure what you mean. Try asking again with a different or simpler phrasing. Try asking for help to see some examples.")
+                "I'm not sure what you mean.".to_owned()
+            }
+            Error::NoRuleBasedIntent => {
+                "I'm not sure what you mean. I can only understand a few kinds of messages right now, such as \"Roll 2d6+3\", \"Roll a stealth check with advantage\", \"Attack with my longsword two-handed\", or \"Set my strength to 16\".".to_owned()
+            }
+            Error::LowConfidence(_) => {
+                "I'm not sure what you mean.".to_owned()
             }
             Error::UnknownIntent(intent_name) => {
-                write!(f, "An unknown intent name was returned by the NLP engine: {}", intent_name)
+                format!("An unknown intent name was returned by the NLP engine: {}", intent_name)
             },
             Error::IntentParserError(error) => {
-                write!(f, "An unknown error was returned by the NLP engine: {}", error)
+                format!("An unknown error was returned by the NLP engine: {}", error)
+            }
+        }
+    }
+
+    /// What to try instead, such as an example of the command with valid syntax, or None if
+    /// there's nothing the user can try.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Error::AddCombatantParserError => {
+                Some("Try \"Add troll to the initiative at 12 with 84 hit points\", `!init add troll 12`, or `!init add troll 12 hp 84 hidden`.".to_owned())
+            }
+            Error::AddMonstersParserError => {
+                Some(format!("Try \"Add 3 goblins to the encounter\", `!monster add 3 goblins`, or `!monster add troll hidden` to hide its hit points. You can add up to {} monsters at once.", MAXIMUM_MONSTERS_ADDED))
+            }
+            Error::AlertParserError => {
+                Some("Try `!alert`, or `!alert off`.".to_owned())
+            }
+            Error::ArmorClassParserError => {
+                Some("Try `!ac 16`, or `!ac goblin A 15` to set a combatant's armour class.".to_owned())
+            }
+            Error::AuditParserError => {
+                Some("Try `!audit`, or `!audit @Alice`.".to_owned())
+            }
+            Error::CharacterRollParserError(suggestions) => {
+                Some(format!("Try typing `!help` for some examples.{}", suggestion::did_you_mean(suggestions)))
+            }
+            Error::ClassParserError => {
+                Some("Try `!class Rogue`, `!class Monk Way of Shadow` to include a subclass, or `!class Rogue 3` to set your levels in one class of a multiclass character.".to_owned())
+            }
+            Error::CombatantArmorClassParserError => {
+                Some("Try \"Goblin A has AC 15\", or `!ac goblin A 15`.".to_owned())
+            }
+            Error::CompanionParserError => {
+                Some("Try `!companion familiar Hoot`, or `!companion steel defender Clank`.".to_owned())
+            }
+            Error::ContestParserError => {
+                Some("Try \"Connie grapples Tocha\", `!grapple Tocha`, or `!shove Connie vs Tocha`.".to_owned())
+            }
+            Error::CopyCharacterParserError => {
+                Some("Try `!copy Connie`, `!copy Connie from #other-campaign`, or `!copy Connie at level 10`.".to_owned())
             }
+            Error::ContentPackParserError => {
+                Some("Try `!monsterpack install` with a monster pack attached, `!monsterpack enable Sunless Citadel`, `!monsterpack disable Sunless Citadel`, `!monsterpack remove Sunless Citadel`, or `!monsterpack` to see the installed packs.".to_owned())
+            }
+            Error::CounterParserError => {
+                Some("Try `!counter Ki 5 short rest` to add a counter, `!counter spend Ki 2`, `!counter restore Ki`, `!counter remove Ki`, or `!counter` to see your counters.".to_owned())
+            }
+            Error::DamageParserError => {
+                Some("Try \"Troll takes 12 fire damage\", or `!damage troll 12 fire`.".to_owned())
+            }
+            Error::DiceLimitsParserError => {
+                Some("Try `!limits 20d100` to allow up to 20 dice with up to 100 sides, `!limits reset`, or `!limits` to see the current limits.".to_owned())
+            }
+            Error::DifficultyParserError => {
+                Some("Try \"How hard is 4 goblins and an ogre for us?\", or `!difficulty 4 goblins, 1 ogre`.".to_owned())
+            }
+            Error::DungeonMasterParserError => {
+                Some("Try \"@Alice is the DM\", `!dm @Alice`, `!dm @Game Masters`, or `!dm clear`.".to_owned())
+            }
+            Error::DungeonParserError => {
+                Some("Try \"Generate a dungeon with 4 rooms\", `!dungeon room`, or `!dungeon 4`.".to_owned())
+            }
+            Error::EditionParserError => {
+                Some("Try `!edition 2014`, `!edition 2024`, or `!edition` to see the current edition.".to_owned())
+            }
+            Error::BardicInspirationParserError => {
+                Some("Try \"Give Connie bardic inspiration\", or `!bardic @Alice`.".to_owned())
+            }
+            Error::BlindRollParserError => {
+                Some("Try \"Roll Perception blind\", `!r deception blind`, or `!r 1d20+3 blind`.".to_owned())
+            }
+            Error::BonusParserError => {
+                Some("Try \"+2 to Stealth from Cloak of Elvenkind\", or `!bonus +1 to all saves from Ring of Protection`.".to_owned())
+            }
+            Error::CastSpellParserError => {
+                Some("Try \"Cast fireball at 4th level\", \"Ritual cast detect magic\", or `!cast fireball 4`.".to_owned())
+            }
+            Error::ChannelParserError => {
+                Some("Try `!channel enable`, `!channel disable`, `!channel lock`, `!channel unlock`, `!channel dice-only on`, `!channel keep-rolls on`, `!channel duplicate-window 5`, `!channel spelling off`, `!channel prefix ?`, `!channel export`, `!channel import`, or `!channel status`.".to_owned())
+            }
+            Error::CharacterEffectParserError => {
+                Some("Try \"I'm blessed\", `!effect guidance`, or `!effect Emboldening Bond +1d4 to attacks, saves and checks for 10 rounds`.".to_owned())
+            }
+            Error::ExperienceParserError => {
+                Some("Try `!xp 300`, `!xp 1200 for the ogre fight`, or `!xp troll 1800` to set how many experience points a combatant is worth.".to_owned())
+            }
+            Error::FightingStyleParserError => {
+                Some("Try `!style archery`, `!style great weapon fighting`, or `!style off`.".to_owned())
+            }
+            Error::GroupSavingThrowParserError => {
+                Some("Try \"Everyone make a DEX save DC 15\", or `!groupsave dex 15`.".to_owned())
+            }
+            Error::HealParserError => {
+                Some("Try \"Troll regains 10 hit points\", or `!heal troll 10`.".to_owned())
+            }
+            Error::HouseRuleParserError => {
+                Some("Try `!houserules set crits max`, `!houserules set flanking on`, `!houserules set potions bonus action on`, `!houserules set max dice 20d100`, or `!houserules reset`.".to_owned())
+            }
+            Error::HitPointsParserError => {
+                Some("Try \"Troll has 84 hit points\", `!hp troll 84`, or `!hp troll 84 hidden` to hide them from the players.".to_owned())
+            }
+            Error::ImportParserError => {
+                Some("Try `!import` with an exported character sheet attached, or `!import https://www.dndbeyond.com/characters/12345678`.".to_owned())
+            }
+            Error::ImproviseParserError => {
+                Some("Try \"Improvise a CR 3 brute\", or `!improvise 3 brute`. The challenge rating must be between 0 and 30, and the role can be a monster, brute, defender or striker.".to_owned())
+            }
+            Error::InitiativeBonusParserError => {
+                Some("Try `!initbonus +2`.".to_owned())
+            }
+            Error::InitiativeOrderParserError(_) => {
+                Some("Try \"Set initiative order: Alice 18, goblin A 15, Bob 12\", or `!init Alice 18, goblin A 15, Bob 12`.".to_owned())
+            }
+            Error::InspirationParserError => {
+                Some("Try `!inspire @Alice`.".to_owned())
+            }
+            Error::LoreParserError => {
+                Some("Try `!lore add The Shattered Crown DC 15 History: It was broken at the Battle of Three Rivers`, `!lore add Mayor Thorne (NPC): He's secretly a cultist`, `!lore reveal Mayor Thorne`, `!lore remove The Shattered Crown`, or `!lore` to see the lore.".to_owned())
+            }
+            Error::MasteryParserError(suggestions) => {
+                Some(format!("Try `!mastery greatsword`, `!mastery remove greatsword`, or `!mastery` to see your masteries.{}", suggestion::did_you_mean(suggestions)))
+            }
+            Error::MonsterParserError => {
+                Some("Try \"Save monster 'Bog Hag' AC 14 HP 52 attack +5 2d8+3 claws\", `!monster save Bog Hag AC 14 HP 52 attack +5 2d8+3 claws CR 3`, `!monster attack Bog Hag against Connie`, or `!monster` to see the saved monsters.".to_owned())
+            }
+            Error::NoteParserError => {
+                Some("Try \"Note: the duke is a vampire\", `!note the duke is a vampire`, `!notes search duke`, or `!note delete 3`.".to_owned())
+            }
+            Error::ObservantParserError => {
+                Some("Try `!observant`, or `!observant off`.".to_owned())
+            }
+            Error::OngoingEffectParserError => {
+                Some("Try \"Goblin A takes 1d6 poison at the start of each turn for 3 rounds\", or `!ongoing goblin A 1d6 poison for 3 rounds`.".to_owned())
+            }
+            Error::PassiveParserError => {
+                Some("Try \"What's my passive Perception?\", `!passive insight`, or `!passive all`.".to_owned())
+            }
+            Error::PortraitParserError => {
+                Some("Try `!portrait https://example.com/portrait.png`.".to_owned())
+            }
+            Error::PregenParserError => {
+                Some("Try `!pregen cleric 3`.".to_owned())
+            }
+            Error::PurgeParserError => {
+                Some("Try `!purge @Alice`.".to_owned())
+            }
+            Error::RandomTableParserError => {
+                Some("Try `!rtable add Forest Encounters: 3x 1d4 goblins; 2x a pack of wolves; {Fey Encounters}`, `!rtable Forest Encounters`, `!rtable show Forest Encounters`, `!rtable delete Forest Encounters`, or `!rtable` to see the random tables.".to_owned())
+            }
+            Error::RegenerationParserError => {
+                Some("Try \"Troll regains 10 hit points at the start of each turn unless it takes fire or acid damage\", or `!regen troll 10 unless fire, acid`.".to_owned())
+            }
+            Error::ResistanceParserError => {
+                Some("Try \"Troll is resistant to fire and cold\", `!resist troll fire, cold`, `!immune skeleton poison`, or `!resist troll none` to clear them.".to_owned())
+            }
+            Error::RestParserError => {
+                Some("Try \"I take a short rest\", `!rest short`, or `!rest long`.".to_owned())
+            }
+            Error::RestrictParserError => {
+                Some("Try `!restrict editing @Players`, `!restrict rolling everyone`, or `!restrict`.".to_owned())
+            }
+            Error::RollParserError(_) => {
+                Some("Try typing `!help` for some examples.".to_owned())
+            }
+            Error::AwardExperienceMissingAmount => {
+                Some("Try \"Award 300 XP\", \"The party gains 1200 experience\", etc.".to_owned())
+            }
+            Error::ServerParserError => {
+                Some("Try `!server enable`, `!server lock`, `!server dice-only on`, `!server prefix ?`, or `!server status`.".to_owned())
+            }
+            Error::SettlementParserError => {
+                Some("Try \"Generate a village\", `!settlement town`, or `!settlement`.".to_owned())
+            }
+            Error::StashParserError => {
+                Some("Try \"Put 150 gp in the party stash\", \"Take 3 potions of healing from the stash\", `!stash add a silver mirror`, or `!stash take 50 gp`.".to_owned())
+            }
+            Error::StatusParserError => {
+                Some("Try `!condition add poisoned`, `!condition add restrained for 2 rounds`, `!condition remove prone`, or `!condition` to see your conditions.".to_owned())
+            }
+            Error::TimeZoneParserError => {
+                Some("Try `!timezone UTC+10`, `!timezone server UTC-5`, `!timezone reset`, or `!timezone` to see your time zone.".to_owned())
+            }
+            Error::TravelParserError => {
+                Some("Try \"We travel 50 miles at a fast pace\", `!travel slow 10 hours`, or `!travel 60 miles difficult`.".to_owned())
+            }
+            Error::TreasureParserError => {
+                Some("Try \"Roll treasure for CR 5-10 hoard\", \"Roll individual treasure for CR 3\", or `!treasure hoard 17+`.".to_owned())
+            }
+            Error::VerifyParserError => {
+                Some("Try `!verify 3FA92C07D1` with the code shown under the roll.".to_owned())
+            }
+            Error::WeatherParserError => {
+                Some("Try \"Roll the weather for winter\", `!weather summer`, or `!weather`.".to_owned())
+            }
+            Error::WildShapeParserError => {
+                Some("Try \"Wild shape into a brown bear\", `!wildshape add brown bear`, `!wildshape attack claws`, `!wildshape damage 12`, or `!wildshape revert`.".to_owned())
+            }
+            Error::AddStatusMissingStatus => {
+                Some("Try \"I'm poisoned\", \"Add condition prone\", etc.".to_owned())
+            }
+            Error::AttackTargetNotAttackRoll => {
+                Some("Try \"Attack the goblin with my shortbow\".".to_owned())
+            }
+            Error::CopyCharacterLevelOutOfRange(_) => {
+                Some("Try \"Copy my character here at level 10\", or `!copy Connie at level 10`.".to_owned())
+            }
+            Error::CreateCharacterMissingName => {
+                Some("Try \"Create a character named Connie\", \"New character Sidekick\", etc.".to_owned())
+            }
+            Error::CreatePregenMissingClass => {
+                Some("Try \"Make me a level 3 pregen cleric\", \"Create a pregenerated rogue\", etc.".to_owned())
+            }
+            Error::CreatePregenLevelOutOfRange(_) => {
+                Some("Try \"Make me a level 3 pregen cleric\", or `!pregen cleric 3`.".to_owned())
+            }
+            Error::CurrencyAmountOutOfRange(_) => {
+                Some("Try \"Add 200 gp\", \"I spend 15 gold\", etc.".to_owned())
+            }
+            Error::DeleteCharacterMissingName => {
+                Some("Try \"Delete my character named Connie\", or `!delete Connie`.".to_owned())
+            }
+            Error::ExperienceAmountNonPositive(_) => {
+                Some("Try \"Award 300 XP\", or `!xp 300`.".to_owned())
+            }
+            Error::GainCurrencyMissingAmount => {
+                Some("Try \"Add 200 gp\", \"I found 30 silver pieces\", etc.".to_owned())
+            }
+            Error::GainCurrencyMissingDenomination => {
+                Some("Try \"Add 200 gp\", \"I found 30 silver pieces\", etc.".to_owned())
+            }
+            Error::RemoveStatusMissingStatus => {
+                Some("Try \"I'm no longer poisoned\", \"Remove condition prone\", etc.".to_owned())
+            }
+            Error::RollAbilityMissingAbility => {
+                Some("Try \"Roll strength\", \"Dexterity check\", etc.".to_owned())
+            }
+            Error::RollAttackAmbiguousWeapon(ambiguous_weapon) => {
+                Some(ambiguous_weapon.message().to_owned())
+            }
+            Error::RollAttackMissingClassification => {
+                Some("Try \"Attack improvised weapon as melee\", \"Roll ranged improvised weapon check\", etc.".to_owned())
+            }
+            Error::RollAttackMissingHandedness => {
+                Some("Try \"One-handed attack quarterstaff\", \"Roll longsword weapon check with two hands\", etc.".to_owned())
+            }
+            Error::RollAttackMissingWeapon(suggestions) => {
+                Some(format!("Try \"Attack club\", \"Dagger attack\", etc.{}", suggestion::did_you_mean(suggestions)))
+            }
+            Error::RollAttackOffHandWeaponNotLight(_) => {
+                Some("Try \"Attack with my off-hand dagger\", \"Off-hand shortsword attack\", etc.".to_owned())
+            }
+            Error::RollAttackRangeWeaponNotRanged(_) => {
+                Some("Try \"Throw my handaxe at long range\", \"Shoot my longbow at normal range\", etc.".to_owned())
+            }
+            Error::RollDiceMissingSides => {
+                Some("Try \"Roll a d20\", \"Throw two four-sided dice\", etc.".to_owned())
+            }
+            Error::RollDiceInvalid(error, _, _) => match error {
+                RollError::RollsTooGreat(limit) => {
+                    Some(format!("Try rolling {} or fewer dice.", limit))
+                }
+                RollError::SidesNonPositive => {
+                    Some("Try rolling dice with one or more sides.".to_owned())
+                }
+                RollError::SidesTooGreat(limit) => {
+                    Some(format!("Try rolling dice with {} or fewer sides.", limit))
+                }
+            },
+            Error::RollSavingThrowMissingAbility => {
+                Some("Try \"Roll strength saving throw\", \"Dexterity saving throw\", etc.".to_owned())
+            }
+            Error::RollSkillMissingSkill(suggestions) => {
+                Some(format!("Try \"Roll stealth\", \"Athletics check\", etc.{}", suggestion::did_you_mean(suggestions)))
+            }
+            Error::SetAbilityOutOfRange(ability, _) => {
+                Some(format!("Try \"Bump my {} to 16\".", ability.as_str().to_lowercase()))
+            }
+            Error::SetArmorClassMissingValue => {
+                Some("Try \"My AC is 16\", \"Set my armour class to 18\", etc.".to_owned())
+            }
+            Error::SetArmorClassOutOfRange(_) => {
+                Some("Try \"My AC is 16\", or `!ac 16`.".to_owned())
+            }
+            Error::SetCharacterNameMissingName => {
+                Some("Try \"My character's name is Connie the Monk\", or `!name Connie the Monk`.".to_owned())
+            }
+            Error::SetCharacterNameTooLong(_) => {
+                Some("Try a shorter name, such as `!name Connie the Monk`.".to_owned())
+            }
+            Error::SetInitiativeBonusOutOfRange(_) => {
+                Some("Try \"My initiative bonus is +2\", or `!initbonus +2`.".to_owned())
+            }
+            Error::SetClassLevelOutOfRange(_) => {
+                Some("Try \"I have 3 levels in rogue\", or `!class Rogue 3`.".to_owned())
+            }
+            Error::SetClassMissingClass => {
+                Some("Try \"My class is Rogue\", \"I'm a bard\", etc.".to_owned())
+            }
+            Error::SetLevelOutOfRange(_) => {
+                Some("Try \"I'm now level 5\".".to_owned())
+            }
+            Error::CoverNotAttackRoll => {
+                Some("Try \"Attack the archer behind half cover with my shortbow\".".to_owned())
+            }
+            Error::Within5FeetNotAttackRoll => {
+                Some("Try \"Attack with my lance within 5 feet\".".to_owned())
+            }
+            Error::FlankingNotAttackRoll => {
+                Some("Try \"Attack the goblin with my longsword while flanking\".".to_owned())
+            }
+            Error::SmiteNotAttackRoll => {
+                Some("Try \"Attack with my longsword and smite at 2nd level\".".to_owned())
+            }
+            Error::CompanionNotCharacterRoll => {
+                Some("Try \"Roll perception as my familiar\", or \"Attack with bite as my beast companion\".".to_owned())
+            }
+            Error::SpendCurrencyMissingAmount => {
+                Some("Try \"I spend 15 gold\", \"Pay 3 cp\", etc.".to_owned())
+            }
+            Error::SpendCurrencyMissingDenomination => {
+                Some("Try \"I spend 15 gold\", \"Pay 3 cp\", etc.".to_owned())
+            }
+            Error::SwitchCharacterMissingName => {
+                Some("Try \"Switch to Connie\", \"Play as Sidekick\", etc.".to_owned())
+            }
+            Error::UseBardicInspirationNotD20Roll => {
+                Some("Try \"Roll stealth using bardic inspiration\", or `!r perception using bardic inspiration`.".to_owned())
+            }
+            Error::UseInspirationNotD20Roll => {
+                Some("Try \"Roll stealth using inspiration\", or `!r perception using inspiration`.".to_owned())
+            }
+            Error::NoIntent => {
+                Some("Try asking again with a different or simpler phrasing. Try asking for help to see some examples.".to_owned())
+            }
+            Error::NoRuleBasedIntent => {
+                Some("Try a shorthand command instead, or type `!help` to see them.".to_owned())
+            }
+            Error::LowConfidence(description) => {
+                Some(format!("Did you mean to {}? If so, answer \"Yes\". Try asking again with a different or simpler phrasing, or asking for help to see some examples.", description))
+            }
+            Error::UnknownIntent(_) | Error::IntentParserError(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.hint() {
+            Some(hint) => write!(f, "{} {}", self.problem(), hint),
+            None => write!(f, "{}", self.problem()),
+        }
+    }
+}
+
+/// The ways in which a message may address the bot: a mention of the bot user, a mention of one of
+/// the bot's roles, or the bot's name written out as "@DungeonHelper".
+pub struct BotMentions<'a> {
+    pub user_id: Option<UserId>,
+    pub role_ids: &'a [RoleId],
+}
+
+impl<'a> BotMentions<'a> {
+    fn is_mentioned(&self, kind: &str, id: u64) -> bool {
+        if kind == "&" {
+            self.role_ids.iter().any(|role_id| role_id.0 == id)
+        } else {
+            self.user_id.map_or(false, |user_id| user_id.0 == id)
         }
     }
 }
 
-type NaturalLanguageCommandResult =
-    Option<Result<(Result<Command, Error>, IntentParserResult, Option<String>), Error>>;
+/// The most commands that can be combined in one message, so that a single message can't fill the
+/// channel with responses.
+const MAXIMUM_COMPOSITE_COMMANDS: usize = 5;
+
+/// The separators between the commands in a message with several commands, such as "!ac 16;
+/// !r stealth", or "set my dexterity to 16 then roll stealth".
+const SEPARATOR_PATTERN: &str = r" *; *| +then +";
+
+/// The conjunctions that can also separate the commands in a message, such as "roll initiative
+/// and attack the goblin with my spear".
+const CONJUNCTION_PATTERN: &str = r" *,? +and(?: +then)? +";
+
+lazy_static! {
+    static ref SEPARATOR_REGEX: Regex = Regex::new(&format!("(?i){}", SEPARATOR_PATTERN)).unwrap();
+    static ref CONJUNCTION_REGEX: Regex =
+        Regex::new(&format!("(?i){}", CONJUNCTION_PATTERN)).unwrap();
+}
+
+/// The most monsters that can be added to the initiative order at once, so that each of them can
+/// be told apart by a letter.
+const MAXIMUM_MONSTERS_ADDED: i32 = 20;
+
+type NaturalLanguageCommandResult = Option<
+    Result<
+        (
+            Result<Command, Error>,
+            Option<IntentParserResult>,
+            Option<String>,
+        ),
+        Error,
+    >,
+>;
 
 impl Command {
     pub fn is_private(&self) -> bool {
         match self {
-            Command::Help | Command::HelpShorthand | Command::Roll(_) => true,
+            Command::ExportTrainingData
+            | Command::Help
+            | Command::HelpShorthand
+            | Command::Roll(_, _)
+            | Command::VerifyRoll(_) => true,
+            Command::Composite(commands) => commands.iter().all(Command::is_private),
             _ => false,
         }
     }
 
-    pub fn parse(
-        engine: &SnipsNluEngine,
-        symspell: &SymSpell<UnicodeStringStrategy>,
+    /// Whether only server administrators can run the command, such as enabling Dungeon Helper in
+    /// a channel.
+    pub fn is_admin(&self) -> bool {
+        match self {
+            Command::ExportChannelSettings
+            | Command::ImportChannelSettings
+            | Command::SetChannelDiceOnly(_)
+            | Command::SetChannelDuplicateWindow(_)
+            | Command::SetChannelEnabled(_)
+            | Command::SetChannelKeepRollMessages(_)
+            | Command::SetChannelLocked(_)
+            | Command::SetChannelPrefix(_)
+            | Command::SetChannelSpellingCorrection(_)
+            | Command::SetServerDefault(_) => true,
+            Command::Composite(commands) => commands.iter().any(Command::is_admin),
+            _ => false,
+        }
+    }
+
+    /// Whether the command changes a character sheet, such as setting an ability score or
+    /// importing a character, which isn't allowed in a locked channel. Keeping track of things
+    /// during play, such as spending counters or taking a rest, isn't editing.
+    pub fn is_editing(&self) -> bool {
+        match self {
+            Command::AddBonus(_)
+            | Command::AddCompanion(_, _)
+            | Command::AddWeaponMastery(_)
+            | Command::AddWildShapeForm(_)
+            | Command::CopyCharacter(_)
+            | Command::CreateCharacter(_)
+            | Command::CreatePregen(_, _)
+            | Command::DeleteCharacter(_)
+            | Command::ImportCharacter(_)
+            | Command::RemoveBonus(_)
+            | Command::RemoveCounter(_)
+            | Command::RemoveWeaponMastery(_)
+            | Command::SetAbility(_, _)
+            | Command::SetAlert(_)
+            | Command::SetArmorClass(_)
+            | Command::SetCharacterName(_)
+            | Command::SetClass(_, _, _)
+            | Command::SetCounter(_)
+            | Command::SetFightingStyle(_)
+            | Command::SetInitiativeBonus(_)
+            | Command::SetLevel(_)
+            | Command::SetObservant(_)
+            | Command::SetPortrait(_) => true,
+            Command::Composite(commands) => commands.iter().any(Command::is_editing),
+            _ => false,
+        }
+    }
+
+    /// Whether the command rolls dice, such as a roll, a check or an attack.
+    pub fn is_rolling(&self) -> bool {
+        match self {
+            Command::AsCompanion(_, _)
+            | Command::AttackRoll(_, _, _, _, _)
+            | Command::BlindRoll(_)
+            | Command::CharacterRoll(_, _)
+            | Command::ContestedCheck(_, _, _)
+            | Command::DamageRoll(_, _)
+            | Command::FlankingAttack(_)
+            | Command::Roll(_, _)
+            | Command::RollForTable(_)
+            | Command::RollHidden(_)
+            | Command::SneakAttackRoll
+            | Command::ToHitRoll(_, _, _)
+            | Command::UseBardicInspiration(_)
+            | Command::UseInspiration(_)
+            | Command::WildShapeAttack(_, _, _) => true,
+            Command::Composite(commands) => commands.iter().any(Command::is_rolling),
+            _ => false,
+        }
+    }
+
+    /// Whether the command is one of the DM's tools for running the game, such as awarding
+    /// experience points or running monsters.
+    pub fn is_dungeon_master(&self) -> bool {
+        match self {
+            Command::AddMonsters(_, _, _)
+            | Command::AwardExperience(_, _)
+            | Command::DeleteMonster(_)
+            | Command::DeleteRandomTable(_)
+            | Command::EndEncounter
+            | Command::GrantInspiration(_)
+            | Command::GroupSavingThrow(_, _)
+            | Command::ImproviseMonster(_, _)
+            | Command::MonsterAttack(_, _, _)
+            | Command::RemoveLore(_)
+            | Command::RevealLore(_)
+            | Command::SaveMonster(_)
+            | Command::SetDiceLimits(_)
+            | Command::SetDungeonMaster(_)
+            | Command::ResetHouseRules
+            | Command::SetEdition(_)
+            | Command::SetHouseRule(_)
+            | Command::SetLore(_)
+            | Command::SetRandomTable(_)
+            | Command::ShowPartyCurrency
+            | Command::ShowRandomTable(_) => true,
+            Command::Composite(commands) => commands.iter().any(Command::is_dungeon_master),
+            _ => false,
+        }
+    }
+
+    /// Whether a user can run the command, given whether they're the DM of the channel. Only the
+    /// DM can use the DM's tools, except that an administrator can also hand the channel over to
+    /// a new DM.
+    pub fn is_allowed_for(&self, is_dm: bool, is_admin: bool) -> bool {
+        match self {
+            Command::SetDungeonMaster(_) => is_dm || is_admin,
+            Command::Composite(commands) => commands
+                .iter()
+                .all(|command| command.is_allowed_for(is_dm, is_admin)),
+            command => is_dm || !command.is_dungeon_master(),
+        }
+    }
+
+    /// Whether the command is in a category that can be restricted to a role.
+    pub fn is_in_category(&self, category: CommandCategory) -> bool {
+        match category {
+            CommandCategory::DungeonMaster => self.is_dungeon_master(),
+            CommandCategory::Editing => self.is_editing(),
+            CommandCategory::Rolling => self.is_rolling(),
+        }
+    }
+
+    /// Check that any dice the command rolls are within a channel's limits.
+    pub fn check_limits(&self, limits: Limits) -> Result<(), Error> {
+        match self {
+            Command::Roll(roll, _) | Command::RollForTable(roll) | Command::RollHidden(roll) => {
+                limits
+                    .check(roll.rolls(), roll.sides())
+                    .map_err(|error| Error::RollDiceInvalid(error, roll.rolls(), roll.sides()))
+            }
+            Command::Composite(commands) => commands
+                .iter()
+                .try_for_each(|command| command.check_limits(limits)),
+            Command::AsCompanion(_, command)
+            | Command::BlindRoll(command)
+            | Command::FlankingAttack(command)
+            | Command::UseBardicInspiration(command)
+            | Command::UseInspiration(command) => command.check_limits(limits),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn parse(
+        engine: Option<&SnipsNluEngine>,
+        symspell: Option<&SymSpell<UnicodeStringStrategy>>,
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<CommandResult, Error>> {
+        let parse_single =
+            |content: &str| Command::parse_single(engine, symspell, content, mentions, dice_only);
+        let (prefix, message) = match Command::split_addressee(content, mentions, dice_only) {
+            Some(split) => split,
+            None => return parse_single(content),
+        };
+        let parts: Vec<&str> = SEPARATOR_REGEX.split(message.trim()).collect();
+        if parts.len() > MAXIMUM_COMPOSITE_COMMANDS {
+            return parse_single(content);
+        }
+        let mut results = Vec::new();
+        for part in parts.iter() {
+            match parse_single(&format!("{}{}", prefix, part.trim())) {
+                Some(Ok(result)) if result.is_understood() => results.push(result),
+                result => {
+                    // "and" turns up inside single commands far more often than the separators,
+                    // so a part is only split on conjunctions if it isn't understood as a whole
+                    match Command::parse_conjunctions(part, prefix, &parse_single) {
+                        Some(conjunction_results) => results.extend(conjunction_results),
+                        // A message with a single command is answered as it was parsed
+                        None if parts.len() == 1 => return result,
+                        // ";", "then" and "and" also turn up inside single commands, such as in
+                        // lore, so the message is parsed as a whole if any part isn't understood
+                        None => return parse_single(content),
+                    }
+                }
+            }
+        }
+        match results.len() {
+            1 => results.pop().map(Ok),
+            count if count <= MAXIMUM_COMPOSITE_COMMANDS => {
+                Some(Ok(CommandResult::Composite(results)))
+            }
+            _ => parse_single(content),
+        }
+    }
+
+    /// Rewrite a message that uses a channel's prefix for shorthand commands, such as "?r d20" or
+    /// "?ac 16; ?r stealth", to use "!", which shorthand commands are parsed with. The prefix is
+    /// rewritten after the same separators and conjunctions that split a message into commands. Returns None for
+    /// a message starting with "!" in a channel with a different prefix, since it's probably meant
+    /// for another bot.
+    pub fn apply_prefix(content: &str, prefix: &str) -> Option<String> {
+        if prefix == DEFAULT_PREFIX {
+            Some(content.to_owned())
+        } else if content.starts_with(prefix) {
+            let regex = Regex::new(&format!(
+                r"(?i)(^|{}|{}){}",
+                SEPARATOR_PATTERN,
+                CONJUNCTION_PATTERN,
+                regex::escape(prefix)
+            ))
+            .ok()?;
+            Some(regex.replace_all(content, "${1}!").into_owned())
+        } else if content.starts_with('!') {
+            None
+        } else {
+            Some(content.to_owned())
+        }
+    }
+
+    /// Split the mention of the bot from the start of a message, such as "<@123> " from "<@123>
+    /// roll stealth", so that each command in a message with several commands keeps the mention
+    /// and can be parsed on its own. Shorthand commands have no mention. Returns None for a
+    /// message that isn't addressed to the bot.
+    fn split_addressee<'a>(
+        content: &'a str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<(&'a str, String)> {
+        if content.starts_with('!') {
+            Some(("", content.to_owned()))
+        } else {
+            let message = Command::extract_at_message(content, mentions, dice_only)?;
+            Some((&content[..content.len() - message.len()], message))
+        }
+    }
+
+    /// Parse a part of a message with several commands separated by conjunctions, such as "roll
+    /// initiative and attack the goblin with my spear", keeping the mention of the bot on each
+    /// command. Returns None if the part has no conjunctions, or if any of its commands isn't
+    /// understood.
+    fn parse_conjunctions(
+        part: &str,
+        prefix: &str,
+        parse_single: &impl Fn(&str) -> Option<Result<CommandResult, Error>>,
+    ) -> Option<Vec<CommandResult>> {
+        let part = part.trim();
+        if !CONJUNCTION_REGEX.is_match(part) {
+            return None;
+        }
+        CONJUNCTION_REGEX
+            .split(part)
+            .map(
+                |command| match parse_single(&format!("{}{}", prefix, command.trim())) {
+                    Some(Ok(result)) if result.is_understood() => Some(result),
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    fn parse_single(
+        engine: Option<&SnipsNluEngine>,
+        symspell: Option<&SymSpell<UnicodeStringStrategy>>,
         content: &str,
-        bot_id: Option<&str>,
+        mentions: &BotMentions,
         dice_only: bool,
     ) -> Option<Result<CommandResult, Error>> {
         Command::parse_shorthand(content)
+            .or_else(|| Command::parse_initiative_order(content, mentions, dice_only))
+            .or_else(|| Command::parse_ongoing_effect(content, mentions, dice_only))
+            .or_else(|| Command::parse_combatant_hit_points(content, mentions, dice_only))
+            .or_else(|| Command::parse_grant_inspiration(content, mentions, dice_only))
+            .or_else(|| Command::parse_group_saving_throw(content, mentions, dice_only))
+            .or_else(|| Command::parse_award_experience(content, mentions, dice_only))
+            .or_else(|| Command::parse_bardic_inspiration(content, mentions, dice_only))
+            .or_else(|| Command::parse_contest(content, mentions, dice_only))
+            .or_else(|| Command::parse_treasure(content, mentions, dice_only))
+            .or_else(|| Command::parse_random_table(content, mentions, dice_only))
+            .or_else(|| Command::parse_dungeon(content, mentions, dice_only))
+            .or_else(|| Command::parse_settlement(content, mentions, dice_only))
+            .or_else(|| Command::parse_weather(content, mentions, dice_only))
+            .or_else(|| Command::parse_travel(content, mentions, dice_only))
+            .or_else(|| Command::parse_note(content, mentions, dice_only))
+            .or_else(|| Command::parse_stash(content, mentions, dice_only))
+            .or_else(|| Command::parse_companion(content, mentions, dice_only))
+            .or_else(|| Command::parse_wild_shape(content, mentions, dice_only))
+            .or_else(|| Command::parse_bonus(content, mentions, dice_only))
+            .or_else(|| Command::parse_initiative_bonus(content, mentions, dice_only))
+            .or_else(|| Command::parse_passive_score(content, mentions, dice_only))
+            .or_else(|| Command::parse_fighting_style(content, mentions, dice_only))
+            .or_else(|| Command::parse_encounter_difficulty(content, mentions, dice_only))
+            .or_else(|| Command::parse_deletion(content, mentions, dice_only))
+            .or_else(|| Command::parse_improvise_monster(content, mentions, dice_only))
+            .or_else(|| Command::parse_monster(content, mentions, dice_only))
+            .or_else(|| Command::parse_counter(content, mentions, dice_only))
+            .or_else(|| Command::parse_character_effect(content, mentions, dice_only))
+            .or_else(|| Command::parse_recall_lore(content, mentions, dice_only))
+            .or_else(|| Command::parse_cast_spell(content, mentions, dice_only))
+            .or_else(|| Command::parse_character_audit(content, mentions, dice_only))
+            .or_else(|| Command::parse_verify_roll(content, mentions, dice_only))
+            .or_else(|| Command::parse_permission_check(content, mentions, dice_only))
+            .or_else(|| Command::parse_hidden_roll(content, mentions, dice_only))
+            .or_else(|| Command::parse_dungeon_master(content, mentions, dice_only))
             .map(CommandResult::Shorthand)
             .map(Ok)
             .or({
-                Command::parse_natural_language(engine, symspell, content, bot_id, dice_only).map(
+                Command::parse_natural_language(engine, symspell, content, mentions, dice_only).map(
                     |result| {
                         result.map(|(command, intent_result, corrected)| {
                             CommandResult::NaturalLanguage(command, intent_result, corrected)
@@ -156,77 +1651,6152 @@ impl Command {
             })
     }
 
-    fn parse_natural_language(
-        engine: &SnipsNluEngine,
-        symspell: &SymSpell<UnicodeStringStrategy>,
-        message: &str,
-        bot_id: Option<&str>,
+    /// Parse a free-text initiative order, such as "Set initiative order: Alice 18, Bob 12", which
+    /// is too free-form for the natural language parser.
+    fn parse_initiative_order(
+        content: &str,
+        mentions: &BotMentions,
         dice_only: bool,
-    ) -> NaturalLanguageCommandResult {
-        Command::extract_at_message(message, bot_id, dice_only)
-            .as_ref()
-            .map(|at_message| {
-                let corrected = Command::spelling_correction(symspell, at_message);
-                let used = corrected.as_ref().unwrap_or(at_message).as_str();
-                engine
-                    .parse(used, None, None)
-                    .map(|result| (parse_intent_result(&result), result, corrected))
-                    .map_err(Error::IntentParserError)
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref SET_INITIATIVE_ORDER_REGEX: Regex =
+                Regex::new(r"^(?i)set (?:the )?initiative(?: order)?(?: to)?:? *(.*)$").unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        SET_INITIATIVE_ORDER_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map(|m| {
+                InitiativeOrder::parse(m.as_str())
+                    .map(Command::SetInitiativeOrder)
+                    .map_err(Error::InitiativeOrderParserError)
+            })
+    }
+
+    /// Parse ongoing damage described in a sentence, such as "Goblin A takes 1d6 poison at the
+    /// start of each turn", which is too free-form for the natural language parser.
+    fn parse_ongoing_effect(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        OngoingEffect::parse_natural_language(&at_message)
+            .map(|effect| {
+                effect
+                    .map(Command::AddOngoingEffect)
+                    .ok_or(Error::OngoingEffectParserError)
+            })
+            .or_else(|| {
+                OngoingEffect::parse_regeneration_natural_language(&at_message).map(|effect| {
+                    effect
+                        .map(Command::AddOngoingEffect)
+                        .ok_or(Error::RegenerationParserError)
+                })
             })
     }
 
-    fn extract_at_message(message: &str, bot_id: Option<&str>, dice_only: bool) -> Option<String> {
+    /// Parse damage dealt to a combatant, such as "Troll takes 12 fire damage", healing, such as
+    /// "Troll regains 10 hit points", a combatant's hit points, such as "Troll has 84 hit points",
+    /// how many experience points a combatant is worth, such as "Troll is worth 1800 XP", a
+    /// combatant's armour class, such as "Troll has AC 15", the damage a combatant resists, such as
+    /// "Troll is resistant to fire", or a new combatant, such as "Add troll to the initiative at 12
+    /// with 84 hit points".
+    fn parse_combatant_hit_points(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
         lazy_static! {
-            static ref COMMAND_REGEX: Regex = Regex::new(r"^(?:<@!?(\d+)> *)?(.*)$").unwrap();
+            static ref SET_HIT_POINTS_REGEX: Regex =
+                Regex::new(r"^(?i)(.+?) +has +(\d+) +(hidden +)?(?:hit +points|hp) *\.?$").unwrap();
+            static ref HEAL_COMBATANT_REGEX: Regex =
+                Regex::new(r"^(?i)(.+?) +regains +(\d+) +(?:hit +points|hp) *\.?$").unwrap();
+            static ref ADD_COMBATANT_REGEX: Regex = Regex::new(
+                r"^(?i)add +(.+?) +to +(?:the +)?(?:initiative(?: +order)?|encounter) +(?:at|on|with +(?:an? +)?initiative(?: +of)?) +(-?\d+)(?:,? +with +(\d+) +(hidden +)?(?:hit +points|hp))? *\.?$"
+            )
+            .unwrap();
+            static ref COMBATANT_EXPERIENCE_REGEX: Regex =
+                Regex::new(r"^(?i)(.+?) +is +worth +(\d+) *(?:xp|experience(?: +points)?) *\.?$")
+                    .unwrap();
+            static ref COMBATANT_ARMOR_CLASS_REGEX: Regex = Regex::new(
+                r"^(?i)(.+?) +has +(?:an? +)?(?:ac|armou?r +class)(?: +of)? +(\d+) *\.?$"
+            )
+            .unwrap();
         }
 
-        COMMAND_REGEX.captures(&message).and_then(|c| {
-            let is_at_message = c
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        Damage::parse_natural_language(&at_message)
+            .map(|damage| Ok(Command::DamageCombatant(damage)))
+            .or_else(|| {
+                SET_HIT_POINTS_REGEX
+                    .captures(&at_message)
+                    .map(|captures| Command::set_hit_points(&captures))
+            })
+            .or_else(|| {
+                HEAL_COMBATANT_REGEX
+                    .captures(&at_message)
+                    .map(|captures| Command::heal_combatant(&captures))
+            })
+            .or_else(|| {
+                ADD_COMBATANT_REGEX
+                    .captures(&at_message)
+                    .map(|captures| Command::add_combatant(&captures))
+            })
+            .or_else(|| {
+                COMBATANT_EXPERIENCE_REGEX
+                    .captures(&at_message)
+                    .map(|captures| Command::set_combatant_experience(&captures))
+            })
+            .or_else(|| {
+                COMBATANT_ARMOR_CLASS_REGEX
+                    .captures(&at_message)
+                    .map(|captures| Command::set_combatant_armor_class(&captures))
+            })
+            .or_else(|| {
+                Resistances::parse_natural_language(&at_message)
+                    .map(|resistances| Ok(Command::SetResistances(resistances)))
+            })
+    }
+
+    /// Parse a grant of inspiration to a mentioned user, such as "Give @Alice inspiration", which
+    /// the natural language parser can't extract the mention from.
+    fn parse_grant_inspiration(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref GRANT_INSPIRATION_REGEX: Regex = Regex::new(
+                r"^(?i)(?:give|grant|award) +(?:<@!?(\d+)> +inspiration|inspiration +to +<@!?(\d+)>) *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        GRANT_INSPIRATION_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+            .and_then(|m| m.as_str().parse().ok())
+            .map(|user_id| Ok(Command::GrantInspiration(UserId(user_id))))
+    }
+
+    /// Parse an award of experience points for something the party did, such as "Award 1200 XP for
+    /// the ogre fight", which the natural language parser can't pick the reason out of. Awards
+    /// without a reason are left to the natural language parser.
+    fn parse_award_experience(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref AWARD_EXPERIENCE_REGEX: Regex = Regex::new(
+                r"^(?i)(?:award|give)(?: +(?:the +party|everyone))? +(-?\d+) +(?:xp|experience(?: +points)?) +(?:for|from) +(.+?) *[.!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        let captures = AWARD_EXPERIENCE_REGEX.captures(&at_message)?;
+        Some(
+            captures
                 .get(1)
-                .map_or(false, |m| bot_id.iter().any(|bot_id| bot_id == &m.as_str()));
-            if dice_only || is_at_message {
-                c.get(2).map(|m| m.as_str().to_owned())
-            } else {
-                None
-            }
+                .and_then(|m| m.as_str().parse().ok())
+                .ok_or(Error::ExperienceParserError)
+                .and_then(|amount| {
+                    Command::award_experience(
+                        amount,
+                        captures.get(2).map(|m| m.as_str().to_owned()),
+                    )
+                }),
+        )
+    }
+
+    /// Parse a request for everyone at the table to make a saving throw, such as "Everyone make a
+    /// DEX save DC 15", which is too easily mistaken for the user's own saving throw by the natural
+    /// language parser.
+    fn parse_group_saving_throw(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref GROUP_SAVING_THROW_REGEX: Regex = Regex::new(
+                r"^(?i)(?:everyone|everybody|all +players|the +(?:whole +)?party)(?: +(?:needs +to|must))?(?: +(?:make|makes|roll|rolls))? +(?:an? +)?([a-z]+) +(?:save|saving +throw)s?(?:,? +(?:against +|vs\.? +|at +)?(?:a +)?dc *(\d+))? *[.!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        GROUP_SAVING_THROW_REGEX
+            .captures(&at_message)
+            .map(|captures| {
+                Command::group_saving_throw(
+                    captures.get(1).map_or("", |m| m.as_str()),
+                    captures.get(2).map(|m| m.as_str()),
+                )
+            })
+    }
+
+    /// Parse a grant of bardic inspiration to a character or a mentioned user, such as "Give Connie
+    /// bardic inspiration".
+    fn parse_bardic_inspiration(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref BARDIC_INSPIRATION_REGEX: Regex = Regex::new(
+                r"^(?i)(?:give|grant) +(?:(.+?) +(?:a +)?bardic +inspiration(?: +die)?|(?:a +)?bardic +inspiration(?: +die)? +to +(.+?)) *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        BARDIC_INSPIRATION_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+            .map(|m| {
+                Command::recipient(m.as_str())
+                    .map(Command::GrantBardicInspiration)
+                    .ok_or(Error::BardicInspirationParserError)
+            })
+    }
+
+    /// Parse a contest between two characters, such as "Connie grapples Tocha" or "I shove
+    /// <@456>".
+    fn parse_contest(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref CONTEST_REGEX: Regex = Regex::new(
+                r"^(?i)(?:i +(grapple|shove)|(.+?) +(grapples|shoves)) +(?:the +)?(.+?) *[.!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        CONTEST_REGEX.captures(&at_message).map(|captures| {
+            Command::contested_check(
+                captures
+                    .get(1)
+                    .or_else(|| captures.get(3))
+                    .map_or("", |m| m.as_str()),
+                captures.get(2).map(|m| m.as_str()),
+                captures.get(4).map_or("", |m| m.as_str()),
+            )
         })
     }
 
-    fn spelling_correction(
-        symspell: &SymSpell<UnicodeStringStrategy>,
-        message: &str,
-    ) -> Option<String> {
-        let trimmed = message.trim();
-        let suggestions = symspell.lookup_compound(trimmed, 2);
-        suggestions.into_iter().next().map(|s| s.term)
+    /// Parse a roll for treasure, such as "Roll treasure for CR 5-10 hoard" or "Roll individual
+    /// treasure for CR 3".
+    fn parse_treasure(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref TREASURE_REGEX: Regex = Regex::new(
+                r"^(?i)roll +(?:an? +|some +)?(individual +|hoard +)?treasure(?: +(hoard))? +for +(?:an? +|the +)?(?:cr|challenge +rating) +(.+?)(?: +(hoard|individual|monsters?|creatures?))? *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        TREASURE_REGEX.captures(&at_message).map(|captures| {
+            let hoard = [1, 2, 4].iter().any(|&index| {
+                captures
+                    .get(index)
+                    .map_or(false, |m| m.as_str().trim().eq_ignore_ascii_case("hoard"))
+            });
+            Command::roll_treasure(hoard, captures.get(3).map_or("", |m| m.as_str()))
+        })
     }
 
-    fn parse_shorthand(command: &str) -> Option<Result<Command, Error>> {
+    /// Create a command to roll a treasure hoard, or individual treasure, for a challenge rating.
+    fn roll_treasure(hoard: bool, challenge_rating: &str) -> Result<Command, Error> {
+        TreasureTier::parse(challenge_rating)
+            .map(|tier| {
+                Command::RollTreasure(Treasure {
+                    kind: if hoard {
+                        TreasureKind::Hoard
+                    } else {
+                        TreasureKind::Individual
+                    },
+                    tier,
+                })
+            })
+            .ok_or(Error::TreasureParserError)
+    }
+
+    /// Parse a roll on one of the channel's random tables, such as "Roll on the Wild Magic table".
+    fn parse_random_table(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
         lazy_static! {
-            static ref ROLL_COMMAND_REGEX: Regex = Regex::new(r"^!(?:r|roll) +(.*)$").unwrap();
+            static ref RANDOM_TABLE_REGEX: Regex =
+                Regex::new(r"^(?i)roll +on +(?:the +|a +)?(.+?)(?: +table)? *\.?$").unwrap();
         }
 
-        if command == "!help" {
-            Some(Ok(Command::HelpShorthand))
-        } else if let Some(captures) = ROLL_COMMAND_REGEX.captures(&command) {
-            let roll_command = captures.get(1).map_or("", |m| m.as_str()).to_owned();
-            Some(
-                ConditionalRoll::parse(&roll_command)
-                    .map(Command::Roll)
-                    .map_err(Error::RollParserError)
-                    .or_else(|_| {
-                        CharacterRoll::parse(&roll_command)
-                            .map(Command::CharacterRoll)
-                            .ok_or(Error::CharacterRollParserError)
-                    }),
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        RANDOM_TABLE_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map(|m| Ok(Command::RollRandomTable(m.as_str().to_owned())))
+    }
+
+    /// Parse a request for a random dungeon, such as "Generate a dungeon with 4 rooms", or
+    /// "Generate a dungeon room".
+    fn parse_dungeon(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref DUNGEON_REGEX: Regex = Regex::new(
+                r"^(?i)(?:generate|make|create|draw) +(?:me +)?(?:an? +)?(?:random +)?dungeon(?: +(room)| +with +(\S+) +rooms?)? *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        DUNGEON_REGEX.captures(&at_message).map(|captures| {
+            Command::generate_dungeon(
+                captures
+                    .get(1)
+                    .or_else(|| captures.get(2))
+                    .map_or("", |m| m.as_str()),
+            )
+        })
+    }
+
+    /// Create a command to generate a dungeon from the number of rooms, or "room" for a single
+    /// room.
+    fn generate_dungeon(rooms: &str) -> Result<Command, Error> {
+        let rooms = rooms.trim().to_lowercase();
+        let rooms: Option<usize> = match rooms.as_str() {
+            "" => Some(dungeon::DEFAULT_ROOMS),
+            "room" | "one" => Some(1),
+            rooms => rooms.trim_end_matches(" rooms").parse().ok(),
+        };
+        rooms
+            .filter(|rooms| *rooms >= 1 && *rooms <= dungeon::MAXIMUM_ROOMS)
+            .map(Command::GenerateDungeon)
+            .ok_or(Error::DungeonParserError)
+    }
+
+    /// Parse a request for a random tavern or settlement, such as "Generate a tavern", or "Make a
+    /// town".
+    fn parse_settlement(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref SETTLEMENT_REGEX: Regex = Regex::new(
+                r"^(?i)(?:generate|make|create) +(?:me +)?(?:an? +)?(?:random +)?(tavern|inn|settlement|village|hamlet|town|city) *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        SETTLEMENT_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map(|m| match m.as_str().to_lowercase().as_str() {
+                "tavern" | "inn" => Ok(Command::GenerateTavern),
+                size => Command::generate_settlement(size),
+            })
+    }
+
+    /// Create a command to generate a settlement of a size, or a random size if none is given.
+    fn generate_settlement(size: &str) -> Result<Command, Error> {
+        match size.trim().to_lowercase().as_str() {
+            "" | "settlement" => Ok(Command::GenerateSettlement(None)),
+            size => SettlementSize::parse(size)
+                .map(|size| Command::GenerateSettlement(Some(size)))
+                .ok_or(Error::SettlementParserError),
+        }
+    }
+
+    /// Parse a roll for the day's weather, such as "Roll the weather for winter".
+    fn parse_weather(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref WEATHER_REGEX: Regex = Regex::new(
+                r"^(?i)(?:roll +(?:the +|today's +)?weather|what(?:'s| +is) +the +weather(?: +like)?(?: +today)?)(?: +(?:for|in) +(?:the +)?(.+?))? *[.?]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        WEATHER_REGEX
+            .captures(&at_message)
+            .map(|captures| Command::roll_weather(captures.get(1).map_or("", |m| m.as_str())))
+    }
+
+    /// Create a command to roll the weather, for a season if one is given.
+    fn roll_weather(season: &str) -> Result<Command, Error> {
+        if season.trim().is_empty() {
+            Ok(Command::RollWeather(None))
+        } else {
+            Season::parse(season)
+                .map(|season| Command::RollWeather(Some(season)))
+                .ok_or(Error::WeatherParserError)
+        }
+    }
+
+    /// Parse overland travel, such as "We travel 50 miles at a fast pace", or "How far can we
+    /// travel in 10 hours?". Messages about travel that don't give a pace, distance or hours are
+    /// left to the intent parser.
+    fn parse_travel(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref TRAVEL_REGEX: Regex = Regex::new(
+                r"^(?i)(?:how +(?:long|far) +(?:does +it +take +to|would +it +take +to|will +it +take +to|can +we|do +we|would +we) +|we +)?travel +(.+?) *[.?]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        TRAVEL_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .and_then(|m| Journey::parse(m.as_str()))
+            .map(|journey| Ok(Command::PlanTravel(journey)))
+    }
+
+    /// Parse a request to add, show, search or delete campaign notes, such as "Note: the duke is a
+    /// vampire", "Search the notes for duke", or "Delete note 3".
+    fn parse_note(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref ADD_NOTE_REGEX: Regex = Regex::new(
+                r"^(?is)(?:(?:add|take|make) +a +)?note(?: *:| +that) *(.*)$"
+            )
+            .unwrap();
+            static ref SHOW_NOTES_REGEX: Regex = Regex::new(
+                r"^(?i)(?:show|list) +(?:me +)?(?:the +|our +)?(?:campaign +)?notes *[.?!]?$"
             )
+            .unwrap();
+            static ref SEARCH_NOTES_REGEX: Regex = Regex::new(
+                r"^(?i)(?:search|find|look +through) +(?:the +|our +)?(?:campaign +)?notes +(?:for|about) +(.+?) *[.?!]?$"
+            )
+            .unwrap();
+            static ref DELETE_NOTE_REGEX: Regex =
+                Regex::new(r"^(?i)(?:delete|remove) +note +(\S+?) *\.?$").unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        if SHOW_NOTES_REGEX.is_match(&at_message) {
+            Some(Ok(Command::ShowNotes(None)))
+        } else if let Some(captures) = SEARCH_NOTES_REGEX.captures(&at_message) {
+            Some(Ok(Command::ShowNotes(
+                captures.get(1).map(|m| m.as_str().to_owned()),
+            )))
+        } else if let Some(captures) = DELETE_NOTE_REGEX.captures(&at_message) {
+            Some(Command::delete_note(
+                captures.get(1).map_or("", |m| m.as_str()),
+            ))
+        } else if let Some(captures) = ADD_NOTE_REGEX.captures(&at_message) {
+            Some(Command::add_note(
+                captures.get(1).map_or("", |m| m.as_str()),
+            ))
         } else {
             None
         }
     }
-}
 
-pub enum CommandResult {
-    Shorthand(Result<Command, Error>),
-    NaturalLanguage(Result<Command, Error>, IntentParserResult, Option<String>),
+    /// Parse a request to put loot in the party's stash, take it out, or view the stash, such as
+    /// "Put 150 gp in the party stash", "Take the silver mirror from the stash", or "What's in the
+    /// party stash?".
+    fn parse_stash(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref DEPOSIT_REGEX: Regex = Regex::new(
+                r"^(?i)(?:put|add|deposit|place) +(.+?) +(?:in|into|to) +(?:the +|our +)?(?:party(?:'s)? +)?(?:stash|inventory|loot) *[.!]?$"
+            )
+            .unwrap();
+            static ref WITHDRAW_REGEX: Regex = Regex::new(
+                r"^(?i)(?:take|withdraw|remove) +(.+?) +(?:from|out +of) +(?:the +|our +)?(?:party(?:'s)? +)?(?:stash|inventory|loot) *[.!]?$"
+            )
+            .unwrap();
+            static ref SHOW_STASH_REGEX: Regex = Regex::new(
+                r"^(?i)(?:(?:show|list|view) +(?:me +)?|what(?:'?s| +is) +in +)(?:the +|our +)?(?:party(?:'s)? +)?(?:stash|inventory|loot) *[.?!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        if SHOW_STASH_REGEX.is_match(&at_message) {
+            Some(Ok(Command::ShowStash))
+        } else if let Some(captures) = DEPOSIT_REGEX.captures(&at_message) {
+            captures
+                .get(1)
+                .map(|m| Command::stash_loot(m.as_str(), Command::DepositInStash))
+        } else {
+            WITHDRAW_REGEX
+                .captures(&at_message)
+                .and_then(|captures| captures.get(1))
+                .map(|m| Command::stash_loot(m.as_str(), Command::WithdrawFromStash))
+        }
+    }
+
+    /// Parse a request to add a companion or show the user's companions, such as "My familiar is
+    /// Hoot", "I have a steel defender named Clank", or "Show my companions".
+    fn parse_companion(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref ADD_COMPANION_REGEX: Regex = Regex::new(
+                r"^(?i)(?:my +(familiar|(?:beast|animal|primal) +companion|steel +defender|companion)(?:'s +name)? +is(?: +(?:named|called))?|i +have +an? +(familiar|(?:beast|animal|primal) +companion|steel +defender|companion) +(?:named|called)) +(.+?) *[.!]?$"
+            )
+            .unwrap();
+            static ref SHOW_COMPANIONS_REGEX: Regex =
+                Regex::new(r"^(?i)(?:show|list) +(?:me +)?my +companions *[.?!]?$").unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        if SHOW_COMPANIONS_REGEX.is_match(&at_message) {
+            Some(Ok(Command::ShowCompanions))
+        } else {
+            ADD_COMPANION_REGEX
+                .captures(&at_message)
+                .and_then(|captures| {
+                    let kind = captures.get(1).or_else(|| captures.get(2))?.as_str();
+                    Some(Command::add_companion(kind, captures.get(3)?.as_str()))
+                })
+        }
+    }
+
+    /// Create a command to add a companion of a kind, such as "familiar", with a name.
+    fn add_companion(kind: &str, name: &str) -> Result<Command, Error> {
+        let kind = CompanionKind::parse(&kind.split_whitespace().collect::<Vec<_>>().join(" "))
+            .ok_or(Error::CompanionParserError)?;
+        let name = name.trim();
+        if name.is_empty() {
+            Err(Error::CompanionParserError)
+        } else {
+            Ok(Command::AddCompanion(kind, name.to_owned()))
+        }
+    }
+
+    /// Parse a request to use Wild Shape, such as "Add brown bear to my wild shapes", "Wild shape
+    /// into a brown bear", "Attack the goblin with claws in wild shape", "My wild shape takes 12
+    /// damage", or "Revert to my normal form".
+    fn parse_wild_shape(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref ADD_FORM_REGEX: Regex = Regex::new(
+                r"^(?i)(?:add|learn) +(?:an? +|the +)?(.+?) +(?:to|as) +(?:my +)?(?:an? +)?wild *shape(?: +form)?s? *[.!]?$"
+            )
+            .unwrap();
+            static ref SHOW_FORMS_REGEX: Regex = Regex::new(
+                r"^(?i)(?:show|list) +(?:me +)?my +wild *shape(?: +form)?s *[.?!]?$"
+            )
+            .unwrap();
+            static ref ATTACK_REGEX: Regex = Regex::new(
+                r"^(?i)(?:attack|strike)(?: +(?:the +)?(.+?))?? +with +(?:my +|its +)?(.+?) +in +(?:my +)?(?:wild *shape|beast +form)(?: +with +(advantage|disadvantage))? *[.!]?$"
+            )
+            .unwrap();
+            static ref DAMAGE_REGEX: Regex = Regex::new(
+                r"^(?i)my +(?:wild *shape|beast +form) +takes +(\d+) +(?:points +of +)?damage *[.!]?$"
+            )
+            .unwrap();
+            static ref HEAL_REGEX: Regex = Regex::new(
+                r"^(?i)heal +my +(?:wild *shape|beast +form) +(?:by|for) +(\d+)(?: +(?:hit +points|hp))? *[.!]?$"
+            )
+            .unwrap();
+            static ref REVERT_REGEX: Regex = Regex::new(
+                r"^(?i)(?:i +)?(?:(?:revert|return|change +back)(?: +(?:to|into) +(?:my +)?(?:normal|true|original|own) +form)?|(?:end|drop) +(?:my +)?wild *shape) *[.!]?$"
+            )
+            .unwrap();
+            static ref WILD_SHAPE_REGEX: Regex = Regex::new(
+                r"^(?i)(?:i +)?wild *shapes? +(?:into|as) +(?:an? +|the +)?(.+?) *[.!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        if SHOW_FORMS_REGEX.is_match(&at_message) {
+            Some(Ok(Command::ShowWildShapes))
+        } else if REVERT_REGEX.is_match(&at_message) {
+            Some(Ok(Command::RevertWildShape))
+        } else if let Some(captures) = ADD_FORM_REGEX.captures(&at_message) {
+            captures
+                .get(1)
+                .map(|m| Ok(Command::AddWildShapeForm(m.as_str().to_owned())))
+        } else if let Some(captures) = ATTACK_REGEX.captures(&at_message) {
+            Some(Ok(Command::wild_shape_attack(
+                captures.get(2).map(|m| m.as_str()),
+                captures.get(1).map(|m| m.as_str()),
+                captures.get(3).map(|m| m.as_str()),
+            )))
+        } else if let Some(captures) = DAMAGE_REGEX.captures(&at_message) {
+            Some(Command::wild_shape_hit_points(
+                captures.get(1).map_or("", |m| m.as_str()),
+                Command::DamageWildShape,
+            ))
+        } else if let Some(captures) = HEAL_REGEX.captures(&at_message) {
+            Some(Command::wild_shape_hit_points(
+                captures.get(1).map_or("", |m| m.as_str()),
+                Command::HealWildShape,
+            ))
+        } else {
+            WILD_SHAPE_REGEX
+                .captures(&at_message)
+                .and_then(|captures| captures.get(1))
+                .map(|m| Ok(Command::WildShape(m.as_str().to_owned())))
+        }
+    }
+
+    /// Create a command to attack in a wild shape with one of the form's attacks, such as
+    /// "claws", or its first attack if none is given, against an optional target, with an
+    /// optional advantage or disadvantage.
+    fn wild_shape_attack(
+        attack: Option<&str>,
+        target: Option<&str>,
+        condition: Option<&str>,
+    ) -> Command {
+        let condition = condition.map(|condition| match condition.to_lowercase().as_str() {
+            "advantage" => Condition::Advantage,
+            _ => Condition::Disadvantage,
+        });
+        Command::WildShapeAttack(
+            attack.map(|attack| attack.trim().to_owned()),
+            condition,
+            target.map(|target| target.trim().to_owned()),
+        )
+    }
+
+    /// Create a command to damage or heal a wild shape. The amount must be positive.
+    fn wild_shape_hit_points(amount: &str, command: fn(i32) -> Command) -> Result<Command, Error> {
+        amount
+            .trim()
+            .parse::<i32>()
+            .ok()
+            .filter(|amount| *amount > 0)
+            .map(command)
+            .ok_or(Error::WildShapeParserError)
+    }
+
+    /// Create a command to put loot in the party's stash or take it out, such as "150 gp" or "3
+    /// potions of healing".
+    fn stash_loot(loot: &str, command: fn(Loot) -> Command) -> Result<Command, Error> {
+        Loot::parse(loot)
+            .map(command)
+            .ok_or(Error::StashParserError)
+    }
+
+    /// Create a command to add a note, as long as it's not empty or too long.
+    fn add_note(text: &str) -> Result<Command, Error> {
+        let text = text.trim();
+        if text.is_empty() || text.chars().count() > note::MAXIMUM_LENGTH {
+            Err(Error::NoteParserError)
+        } else {
+            Ok(Command::AddNote(text.to_owned()))
+        }
+    }
+
+    /// Create a command to delete a note by its number, such as "3" or "#3".
+    fn delete_note(id: &str) -> Result<Command, Error> {
+        id.trim()
+            .trim_start_matches('#')
+            .parse()
+            .map(Command::DeleteNote)
+            .map_err(|_| Error::NoteParserError)
+    }
+
+    /// Parse who is being given something, either a mentioned user or a character's name.
+    fn recipient(string: &str) -> Option<Recipient> {
+        lazy_static! {
+            static ref MENTION_REGEX: Regex = Regex::new(r"^<@!?(\d+)>$").unwrap();
+        }
+        let string = string.trim();
+        if let Some(captures) = MENTION_REGEX.captures(string) {
+            captures
+                .get(1)
+                .and_then(|m| m.as_str().parse().ok())
+                .map(|user_id| Recipient::User(UserId(user_id)))
+        } else if string.is_empty() || string.contains('<') {
+            None
+        } else {
+            Some(Recipient::Character(string.to_owned()))
+        }
+    }
+
+    /// Parse a bonus to a character's rolls, such as "+2 to Stealth from Cloak of Elvenkind", or
+    /// the removal of the bonuses from a source, such as "Remove bonuses from Cloak of Elvenkind".
+    fn parse_bonus(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref REMOVE_BONUS_REGEX: Regex =
+                Regex::new(r"^(?i)remove +(?:the +|my +)?bonus(?:es)? +from +(.+?) *\.?$").unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        Bonus::parse(&at_message)
+            .map(|bonus| Ok(Command::AddBonus(bonus)))
+            .or_else(|| {
+                REMOVE_BONUS_REGEX
+                    .captures(&at_message)
+                    .and_then(|captures| captures.get(1))
+                    .map(|m| Ok(Command::RemoveBonus(m.as_str().to_owned())))
+            })
+    }
+
+    /// Parse a character's initiative bonus, such as "My initiative bonus is +2", or whether they
+    /// have the Alert feat, such as "I have the Alert feat".
+    fn parse_initiative_bonus(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref INITIATIVE_BONUS_REGEX: Regex = Regex::new(
+                r"^(?i)(?:my +initiative +bonus +is(?: +now)?|set +my +initiative +bonus +to) +([+-]?\d+) *\.?$"
+            )
+            .unwrap();
+            static ref ALERT_REGEX: Regex = Regex::new(
+                r"^(?i)i +(?:(have)|(?:don'?t|do +not|no +longer) +have) +(?:the +)?alert +feat *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        INITIATIVE_BONUS_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .and_then(|m| m.as_str().parse().ok())
+            .map(Command::set_initiative_bonus)
+            .or_else(|| {
+                ALERT_REGEX
+                    .captures(&at_message)
+                    .map(|captures| Ok(Command::SetAlert(captures.get(1).is_some())))
+            })
+    }
+
+    /// Parse a request for a character's passive score, such as "What's my passive Perception?" or
+    /// "Show all my passive scores", or whether they have the Observant feat, such as "I have the
+    /// Observant feat".
+    fn parse_passive_score(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref PASSIVE_SCORES_REGEX: Regex = Regex::new(
+                r"^(?i)(?:show|list|what +are)(?: +me)?(?: +all(?: +of)?)?(?: +my)? +passives?(?: +scores)? *[?.]?$"
+            )
+            .unwrap();
+            static ref PASSIVE_SCORE_REGEX: Regex = Regex::new(
+                r"^(?i)(?:what(?:'?s| +is)|show(?: +me)?) +my +passive +([a-z' ]+?)(?: +score)? *[?.]?$"
+            )
+            .unwrap();
+            static ref OBSERVANT_REGEX: Regex = Regex::new(
+                r"^(?i)i +(?:(have)|(?:don'?t|do +not|no +longer) +have) +(?:the +)?observant +feat *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        if PASSIVE_SCORES_REGEX.is_match(&at_message) {
+            Some(Ok(Command::ShowPassiveScores))
+        } else if let Some(captures) = PASSIVE_SCORE_REGEX.captures(&at_message) {
+            captures
+                .get(1)
+                .map(|m| Command::show_passive_score(m.as_str()))
+        } else {
+            OBSERVANT_REGEX
+                .captures(&at_message)
+                .map(|captures| Ok(Command::SetObservant(captures.get(1).is_some())))
+        }
+    }
+
+    /// Parse a character's fighting style, such as "My fighting style is Archery", "I have the
+    /// Two-Weapon Fighting style", or "I don't have a fighting style".
+    fn parse_fighting_style(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref FIGHTING_STYLE_REGEX: Regex = Regex::new(
+                r"^(?i)(?:my +fighting +style +is +(?:now +)?|i +(?:now +)?(?:have|use) +(?:the +)?)([a-z -]+?) *\.?$"
+            )
+            .unwrap();
+            static ref NO_FIGHTING_STYLE_REGEX: Regex = Regex::new(
+                r"^(?i)i +(?:don'?t|do +not|no +longer) +have +(?:a +|the +)?([a-z -]+?) *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        let no_fighting_style = NO_FIGHTING_STYLE_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map_or(false, |m| {
+                m.as_str().eq_ignore_ascii_case("fighting style")
+                    || Command::fighting_style(m.as_str()).is_some()
+            });
+        if no_fighting_style {
+            Some(Ok(Command::SetFightingStyle(None)))
+        } else {
+            FIGHTING_STYLE_REGEX
+                .captures(&at_message)
+                .and_then(|captures| captures.get(1))
+                .and_then(|m| Command::fighting_style(m.as_str()))
+                .map(|fighting_style| Ok(Command::SetFightingStyle(Some(fighting_style))))
+        }
+    }
+
+    /// Parse the name of a fighting style, allowing for "style" or "fighting style" after it, such
+    /// as "the Archery fighting style".
+    fn fighting_style(name: &str) -> Option<FightingStyle> {
+        let name = name.trim().to_lowercase();
+        let name = name.trim_end_matches(" style");
+        FightingStyle::parse(name)
+            .or_else(|| FightingStyle::parse(name.trim_end_matches(" fighting")))
+    }
+
+    /// Parse a question about how difficult an encounter would be, such as "How hard is 4 goblins
+    /// and an ogre for us?", which lists monsters too freely for the natural language parser.
+    fn parse_encounter_difficulty(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref ENCOUNTER_DIFFICULTY_REGEX: Regex = Regex::new(
+                r"^(?i)how +(?:hard|difficult|tough|deadly) +(?:is|are|would +be|would) +(.+?)(?: +be)?(?: +for +(?:us|the +party))?(?: +be)? *\??$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        ENCOUNTER_DIFFICULTY_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map(|m| Command::encounter_difficulty(m.as_str()))
+    }
+
+    /// Parse a request for an improvised monster, such as "Improvise a CR 3 brute".
+    fn parse_improvise_monster(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref IMPROVISE_MONSTER_REGEX: Regex = Regex::new(
+                r"^(?i)(?:improvise|make +up|quick +stats +for) +(?:an? +)?(?:cr|challenge +rating) +(\S+)(?: +([a-z]+))? *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        IMPROVISE_MONSTER_REGEX
+            .captures(&at_message)
+            .map(|captures| Command::improvise_monster(&captures))
+    }
+
+    /// Parse the saving of a monster's stat block, such as "Save monster 'Bog Hag' AC 14 HP 52
+    /// attack +5 2d8+3", the use of a saved monster, such as "Bog Hag attacks Connie with
+    /// advantage", or the adding of monsters from the SRD, such as "Add 3 goblins to the
+    /// encounter".
+    fn parse_monster(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref SAVE_MONSTER_REGEX: Regex =
+                Regex::new(r"^(?i)save +(?:a +|the +)?monster:? +(.+)$").unwrap();
+            static ref SHOW_MONSTERS_REGEX: Regex =
+                Regex::new(r"^(?i)(?:show|list|view) +(?:the +|our +)?(?:saved +)?monsters *\.?$")
+                    .unwrap();
+            static ref SHOW_MONSTER_REGEX: Regex =
+                Regex::new(r"^(?i)(?:show|view) +(?:the +)?monster +(.+?) *\.?$").unwrap();
+            static ref DELETE_MONSTER_REGEX: Regex =
+                Regex::new(r"^(?i)(?:delete|forget) +(?:the +)?monster +(.+?) *\.?$").unwrap();
+            static ref MONSTER_ATTACK_REGEX: Regex = Regex::new(
+                r"^(?i)(?:the +)?(.+?) +attacks(?: +(?:the +)?(.+?))??(?: +with +(advantage|disadvantage))? *[.!]?$"
+            )
+            .unwrap();
+            static ref ADD_MONSTERS_REGEX: Regex = Regex::new(
+                r"^(?i)add +(.+?) +to +(?:the +)?(?:encounter|fight|combat|initiative(?: +order)?)(,? +with +hidden +(?:hit +points|hp))? *\.?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        SAVE_MONSTER_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map(|m| {
+                Monster::parse(m.as_str())
+                    .map(Command::SaveMonster)
+                    .ok_or(Error::MonsterParserError)
+            })
+            .or_else(|| {
+                SHOW_MONSTERS_REGEX
+                    .find(&at_message)
+                    .map(|_| Ok(Command::ShowMonsters))
+            })
+            .or_else(|| {
+                SHOW_MONSTER_REGEX
+                    .captures(&at_message)
+                    .and_then(|captures| captures.get(1))
+                    .map(|m| Ok(Command::ShowMonster(m.as_str().to_owned())))
+            })
+            .or_else(|| {
+                DELETE_MONSTER_REGEX
+                    .captures(&at_message)
+                    .and_then(|captures| captures.get(1))
+                    .map(|m| Ok(Command::DeleteMonster(m.as_str().to_owned())))
+            })
+            .or_else(|| {
+                MONSTER_ATTACK_REGEX
+                    .captures(&at_message)
+                    // "Connie attacks the goblin with her longsword" is a character's attack
+                    .filter(|captures| {
+                        captures
+                            .get(2)
+                            .map_or(true, |m| !m.as_str().to_lowercase().contains(" with "))
+                    })
+                    .map(|captures| Ok(Command::monster_attack(&captures)))
+            })
+            .or_else(|| {
+                ADD_MONSTERS_REGEX.captures(&at_message).map(|captures| {
+                    Command::add_monsters(
+                        captures.get(1).map_or("", |m| m.as_str()),
+                        captures.get(2).is_some(),
+                    )
+                })
+            })
+    }
+
+    /// Parse the spending of one of a character's counters, such as "I spend 2 ki points", a rest,
+    /// such as "We take a long rest", or a request to view the character's counters.
+    fn parse_counter(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref SPEND_COUNTER_REGEX: Regex = Regex::new(
+                r"^(?i)(?:i +)?(?:spend|use) +(\d+|an?|one) +(.+? +points?) *\.?$"
+            )
+            .unwrap();
+            static ref REST_REGEX: Regex = Regex::new(
+                r"^(?i)(?:(?:i|we) +)?(?:(?:take|took|finish|finished|have|had) +an? +)?(short|long) +rest *[.!]?$"
+            )
+            .unwrap();
+            static ref SHOW_COUNTERS_REGEX: Regex =
+                Regex::new(r"^(?i)(?:show|view|list) +(?:my +)?(?:counters|resources) *\.?$")
+                    .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        SPEND_COUNTER_REGEX
+            .captures(&at_message)
+            .map(|captures| {
+                let amount = match captures.get(1).map(|m| m.as_str().to_lowercase()) {
+                    Some(ref amount) if amount == "a" || amount == "an" || amount == "one" => {
+                        Some(1)
+                    }
+                    amount => amount.and_then(|amount| amount.parse().ok()),
+                };
+                let name = captures.get(2).map_or("", |m| m.as_str());
+                Command::spend_counter(name, amount)
+            })
+            .or_else(|| {
+                REST_REGEX
+                    .captures(&at_message)
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|m| Rest::parse(m.as_str()))
+                    .map(|rest| Ok(Command::Rest(rest)))
+            })
+            .or_else(|| {
+                SHOW_COUNTERS_REGEX
+                    .find(&at_message)
+                    .map(|_| Ok(Command::ShowCounters))
+            })
+    }
+
+    /// Parse a spell affecting the author's character, such as "I'm blessed" or "My bless ends",
+    /// or a request to view the effects on the character.
+    fn parse_character_effect(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref ADD_EFFECT_REGEX: Regex = Regex::new(
+                r"^(?i)(?:i'?m|i +am|i +have|i'?ve +got)(?: +under)? +((?:blessed|bless|baned|bane|guided|guidance|resistance)(?: +for +\d+ +rounds?)?) *[.!]?$"
+            )
+            .unwrap();
+            static ref END_EFFECT_REGEX: Regex = Regex::new(
+                r"^(?i)(?:end +my +(bless|bane|guidance|resistance)|(?:my +)?(bless|bane|guidance|resistance) +(?:ends|has +ended|is +over|wears +off|wore +off)) *[.!]?$"
+            )
+            .unwrap();
+            static ref SHOW_EFFECTS_REGEX: Regex =
+                Regex::new(r"^(?i)(?:show|view|list) +(?:my +)?effects *\.?$").unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        ADD_EFFECT_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map(|m| {
+                CharacterEffect::parse(m.as_str())
+                    .map(Command::AddCharacterEffect)
+                    .ok_or(Error::CharacterEffectParserError)
+            })
+            .or_else(|| {
+                END_EFFECT_REGEX
+                    .captures(&at_message)
+                    .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+                    .and_then(|m| CharacterEffect::spell(m.as_str()))
+                    .map(|effect| Ok(Command::RemoveCharacterEffect(effect.name)))
+            })
+            .or_else(|| {
+                SHOW_EFFECTS_REGEX
+                    .find(&at_message)
+                    .map(|_| Ok(Command::ShowCharacterEffects))
+            })
+    }
+
+    /// Parse a knowledge check about a subject, such as "Roll history about the Shattered Crown",
+    /// which may reveal lore the DM has added about it.
+    fn parse_recall_lore(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref RECALL_LORE_REGEX: Regex =
+                Regex::new(r"^(?i)(?:roll +|make +an? +)?(.+?)(?: +check)? +about +(.+?) *[.?]?$")
+                    .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        RECALL_LORE_REGEX
+            .captures(&at_message)
+            .and_then(|captures| {
+                Command::recall_lore(captures.get(1)?.as_str(), captures.get(2)?.as_str()).map(Ok)
+            })
+    }
+
+    /// Parse a request to cast a spell, such as "Cast fireball at 4th level" or "I cast fire bolt",
+    /// where the spell slot level is optional, or to cast a spell as a ritual, such as "Ritual cast
+    /// detect magic" or "Cast identify as a ritual".
+    fn parse_cast_spell(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref CAST_SPELL_REGEX: Regex = Regex::new(
+                r"^(?i)(?:i +)?(ritual +)?cast +(.+?)(?:( +as +a +ritual)|(?: +(?:at|using|with|upcast +to) +(?:an? +|the +)?(?:(\d+)(?:st|nd|rd|th)[ -]+level|level +(\d+))(?: +(?:spell +)?slot)?))? *[.!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        CAST_SPELL_REGEX.captures(&at_message).map(|captures| {
+            Command::cast_spell(
+                captures.get(2).map_or("", |m| m.as_str()),
+                captures
+                    .get(4)
+                    .or_else(|| captures.get(5))
+                    .map(|m| m.as_str()),
+                captures.get(1).is_some() || captures.get(3).is_some(),
+            )
+        })
+    }
+
+    /// Parse a request to review recent changes to characters, such as "Show recent character
+    /// changes", or "Show character changes for @Alice".
+    fn parse_character_audit(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref CHARACTER_AUDIT_REGEX: Regex = Regex::new(
+                r"^(?i)(?:show|list) +(?:me +)?(?:the +)?(?:recent +)?(?:character +changes|audit +(?:log|history))(?: +(?:for|of|by) +<@!?(\d+)>)? *[.?!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        CHARACTER_AUDIT_REGEX.captures(&at_message).map(|captures| {
+            Ok(Command::ShowCharacterAudit(
+                captures
+                    .get(1)
+                    .and_then(|m| m.as_str().parse().ok())
+                    .map(UserId),
+            ))
+        })
+    }
+
+    /// Parse a request to verify a roll from the code shown under it, such as "Verify roll
+    /// 3FA92C07D1".
+    fn parse_verify_roll(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref VERIFY_ROLL_REGEX: Regex = Regex::new(
+                r"^(?i)(?:verify|check) +(?:the +|this +)?roll(?: +code)? *:? *([0-9a-f][0-9a-f -]*[0-9a-f]) *[.?!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        VERIFY_ROLL_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map(|m| Ok(Command::VerifyRoll(m.as_str().to_owned())))
+    }
+
+    /// Parse a request to check Dungeon Helper's permissions in the channel, such as "Check my
+    /// permissions".
+    fn parse_permission_check(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref PERMISSION_CHECK_REGEX: Regex = Regex::new(
+                r"^(?i)(?:check|show|list|what +are) +(?:my +|your +|the +|the +bot'?s +)?permissions(?: +(?:here|in +this +channel))? *[.?!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        if PERMISSION_CHECK_REGEX.is_match(&at_message) {
+            Some(Ok(Command::CheckPermissions))
+        } else {
+            None
+        }
+    }
+
+    /// Parse a roll into the dice cup, whose result is sealed until it's revealed, such as "Roll
+    /// 1d20 hidden", or the reveal of those rolls, such as "Reveal my hidden roll".
+    fn parse_hidden_roll(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref HIDDEN_ROLL_REGEX: Regex = Regex::new(
+                r"^(?i)roll +(?:an? +)?(.+?) +(?:hidden|secretly|in +secret|into +the +dice +cup) *\.?$"
+            )
+            .unwrap();
+            static ref BLIND_ROLL_REGEX: Regex = Regex::new(
+                r"^(?i)roll +(?:an? +)?(?:blind +(.+?)|(.+?) +(?:blind(?:ly)?|for +the +dm)) *\.?$"
+            )
+            .unwrap();
+            static ref REVEAL_HIDDEN_ROLLS_REGEX: Regex = Regex::new(
+                r"^(?i)(?:reveal +(?:my +)?(?:hidden|secret) +rolls?|(?:open|lift) +(?:my +|the +)?dice +cup) *[.!]?$"
+            )
+            .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        if REVEAL_HIDDEN_ROLLS_REGEX.is_match(&at_message) {
+            Some(Ok(Command::RevealHiddenRolls))
+        } else if let Some(captures) = BLIND_ROLL_REGEX.captures(&at_message) {
+            captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .map(|m| Command::blind_roll(m.as_str()))
+        } else {
+            HIDDEN_ROLL_REGEX
+                .captures(&at_message)
+                .and_then(|captures| captures.get(1))
+                .and_then(|m| Command::hidden_roll(m.as_str()).ok())
+                .map(Ok)
+        }
+    }
+
+    /// Create a command to roll blind, so that only the channel's DM sees the result, from a
+    /// character roll such as "Perception check" or a roll such as "1d20+3".
+    fn blind_roll(roll: &str) -> Result<Command, Error> {
+        lazy_static! {
+            static ref CHECK_REGEX: Regex = Regex::new(r"^(?i)(.+?) +check$").unwrap();
+        }
+        let roll = roll.trim();
+        let check = CHECK_REGEX
+            .captures(roll)
+            .and_then(|captures| captures.get(1))
+            .map_or(roll, |m| m.as_str());
+        CharacterRoll::parse(&check.to_lowercase())
+            .map(|roll| Command::CharacterRoll(roll, None))
+            .or_else(|| match Command::hidden_roll(roll) {
+                Ok(Command::RollHidden(roll)) => Some(Command::Roll(roll, None)),
+                _ => None,
+            })
+            .map(|command| Command::BlindRoll(Box::new(command)))
+            .ok_or(Error::BlindRollParserError)
+    }
+
+    /// Parse the setting of the channel's DM, such as "@Alice is the DM", or a question about who
+    /// it is, such as "Who is the DM?".
+    fn parse_dungeon_master(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref SET_DUNGEON_MASTER_REGEX: Regex = Regex::new(
+                r"^(?i)(<@[!&]?\d+>) +(?:is|are|will +be) +(?:the|our) +(?:dms?|gms?|dungeon +masters?) *[.!]?$"
+            )
+            .unwrap();
+            static ref SHOW_DUNGEON_MASTER_REGEX: Regex =
+                Regex::new(r"^(?i)who(?: +is|'s) +(?:the|our) +(?:dm|gm|dungeon +master) *\??$")
+                    .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        if SHOW_DUNGEON_MASTER_REGEX.is_match(&at_message) {
+            Some(Ok(Command::ShowDungeonMaster))
+        } else {
+            SET_DUNGEON_MASTER_REGEX
+                .captures(&at_message)
+                .and_then(|captures| captures.get(1))
+                .map(|m| Command::set_dungeon_master(m.as_str()))
+        }
+    }
+
+    /// Create a command to set the channel's DM from a mention of a user or a role, or to clear
+    /// it.
+    fn set_dungeon_master(dungeon_master: &str) -> Result<Command, Error> {
+        lazy_static! {
+            static ref ROLE_MENTION_REGEX: Regex = Regex::new(r"^<@&(\d+)>$").unwrap();
+        }
+        let dungeon_master = dungeon_master.trim().to_lowercase();
+        if let Some(captures) = ROLE_MENTION_REGEX.captures(&dungeon_master) {
+            return captures
+                .get(1)
+                .and_then(|m| m.as_str().parse().ok())
+                .map(|role_id| {
+                    Command::SetDungeonMaster(Some(DungeonMaster::Role(RoleId(role_id))))
+                })
+                .ok_or(Error::DungeonMasterParserError);
+        }
+        match dungeon_master.as_ref() {
+            "clear" | "none" | "off" => Ok(Command::SetDungeonMaster(None)),
+            dungeon_master => match Command::recipient(dungeon_master) {
+                Some(Recipient::User(user_id)) => Ok(Command::SetDungeonMaster(Some(
+                    DungeonMaster::User(user_id),
+                ))),
+                _ => Err(Error::DungeonMasterParserError),
+            },
+        }
+    }
+
+    /// Create a command to roll into the dice cup, from a roll such as "1d20" or "d20 with
+    /// advantage".
+    fn hidden_roll(roll: &str) -> Result<Command, Error> {
+        let roll = roll.trim().to_lowercase();
+        let roll = if roll.starts_with('d') {
+            format!("1{}", roll)
+        } else {
+            roll
+        };
+        ConditionalRoll::parse(&roll)
+            .map(Command::RollHidden)
+            .map_err(Error::RollParserError)
+    }
+
+    /// Parse the deletion of a character, such as "Delete my character named Connie", or of
+    /// everything stored about a mentioned user, such as "Purge @Alice".
+    fn parse_deletion(
+        content: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref DELETE_CHARACTER_REGEX: Regex = Regex::new(
+                r"^(?i)delete +(?:my +|the +)?character(?: +(?:named|called))? +(.+?) *\.?$"
+            )
+            .unwrap();
+            static ref PURGE_USER_REGEX: Regex =
+                Regex::new(r"^(?i)(?:purge|delete +all +(?:of +)?) *<@!?(\d+)>(?:'s +data)? *\.?$")
+                    .unwrap();
+        }
+
+        let at_message = Command::extract_at_message(content, mentions, dice_only)?;
+        DELETE_CHARACTER_REGEX
+            .captures(&at_message)
+            .and_then(|captures| captures.get(1))
+            .map(|m| Ok(Command::DeleteCharacter(m.as_str().to_owned())))
+            .or_else(|| {
+                PURGE_USER_REGEX
+                    .captures(&at_message)
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|m| m.as_str().parse().ok())
+                    .map(|user_id| Ok(Command::PurgeUserFromChannel(UserId(user_id))))
+            })
+    }
+
+    /// Split "using inspiration" or "using bardic inspiration" from the end of a roll.
+    fn split_inspiration(string: &str) -> (&str, Option<Inspiration>) {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)(.*?) +(?:using|with|use) +(?:my +)?(bardic +)?inspiration *$")
+                    .unwrap();
+        }
+        match RE.captures(string) {
+            Some(captures) => {
+                let inspiration = if captures.get(2).is_some() {
+                    Inspiration::Bardic
+                } else {
+                    Inspiration::Inspiration
+                };
+                (
+                    captures.get(1).map_or(string, |m| m.as_str()),
+                    Some(inspiration),
+                )
+            }
+            None => (string, None),
+        }
+    }
+
+    /// Apply the inspiration split from the end of a roll, if any, to the parsed roll.
+    fn with_inspiration(
+        command: Result<Command, Error>,
+        inspiration: Option<Inspiration>,
+    ) -> Result<Command, Error> {
+        match inspiration {
+            Some(Inspiration::Inspiration) => command.and_then(Command::use_inspiration),
+            Some(Inspiration::Bardic) => command.and_then(Command::use_bardic_inspiration),
+            None => command,
+        }
+    }
+
+    /// Split the companion making a roll from the end of the roll, such as "my familiar" from
+    /// "roll perception as my familiar", or "Hoot" from "roll stealth as my companion Hoot".
+    /// Returns the companion's name if one is given, or its kind otherwise.
+    fn split_companion(string: &str) -> (&str, Option<String>) {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^(?i)(.*?),? +as +(?:my +)?(familiar|(?:beast|animal|primal) +companion|steel +defender|companion)(?: +(.+?))? *$"
+            )
+            .unwrap();
+        }
+        RE.captures(string)
+            .and_then(|captures| {
+                let companion = captures.get(3).or_else(|| captures.get(2))?.as_str();
+                Some((captures.get(1)?.as_str(), Some(companion.to_owned())))
+            })
+            .unwrap_or((string, None))
+    }
+
+    /// Apply the companion split from the end of a roll, if any, to the parsed roll. Only
+    /// checks, saving throws and attacks can be rolled by a companion.
+    fn with_companion(
+        command: Result<Command, Error>,
+        companion: Option<String>,
+    ) -> Result<Command, Error> {
+        match companion {
+            Some(companion) => command.and_then(|command| match command {
+                Command::AttackRoll(_, _, _, _, _)
+                | Command::CharacterRoll(_, _)
+                | Command::DamageRoll(_, _)
+                | Command::ToHitRoll(_, _, _) => {
+                    Ok(Command::AsCompanion(companion, Box::new(command)))
+                }
+                _ => Err(Error::CompanionNotCharacterRoll),
+            }),
+            None => command,
+        }
+    }
+
+    /// Split a Divine Smite from the end of an attack, such as "and smite at 2nd level" from
+    /// "attack with my longsword and smite at 2nd level".
+    fn split_smite(string: &str) -> (&str, Option<Smite>) {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)(.*?),? +(?:and|then) +(?:divine +)?smite\b(.*)$").unwrap();
+        }
+        RE.captures(string)
+            .and_then(|captures| {
+                let smite = Smite::parse(captures.get(2)?.as_str())?;
+                Some((captures.get(1)?.as_str(), Some(smite)))
+            })
+            .unwrap_or((string, None))
+    }
+
+    /// Split the range from the end of an attack against a target within 5 feet, such as "attack
+    /// with my lance within 5 feet", returning the rest of the attack and whether the target is
+    /// within 5 feet.
+    fn split_within_5_feet(string: &str) -> (&str, bool) {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^(?i)(.*?),? +(?:within|at) +(?:a +range +of +)?5 *(?:ft\.?|feet)(?: +(?:of +me|away))?$"
+            )
+            .unwrap();
+        }
+        RE.captures(string)
+            .and_then(|captures| captures.get(1))
+            .map_or((string, false), |m| (m.as_str(), true))
+    }
+
+    /// Split a leading backslash from a message, such as "\attack with Kelgrimm's Maul", which
+    /// asks for it not to be spelling corrected, such as when it has a name in it that would be
+    /// "corrected" into something else.
+    fn split_uncorrected(string: &str) -> (&str, bool) {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"^(?s)\\ *(.*)$").unwrap();
+        }
+        RE.captures(string)
+            .and_then(|captures| captures.get(1))
+            .map_or((string, false), |m| (m.as_str(), true))
+    }
+
+    /// Split flanking from the end of an attack, such as "attack with my longsword while
+    /// flanking", returning the rest of the attack and whether the attacker is flanking.
+    fn split_flanking(string: &str) -> (&str, bool) {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)(.*?),? +(?:while +|when +)?flanking(?: +(?:it|them|him|her))?$")
+                    .unwrap();
+        }
+        RE.captures(string)
+            .and_then(|captures| captures.get(1))
+            .map_or((string, false), |m| (m.as_str(), true))
+    }
+
+    /// Mark an attack as made while flanking. Whether that gives advantage depends on the
+    /// channel's house rules, so it's decided when the attack is rolled.
+    fn with_flanking(command: Result<Command, Error>, flanking: bool) -> Result<Command, Error> {
+        fn is_attack(command: &Command) -> bool {
+            match command {
+                Command::AttackRoll(_, _, _, _, _) | Command::ToHitRoll(_, _, _) => true,
+                Command::AsCompanion(_, command)
+                | Command::UseBardicInspiration(command)
+                | Command::UseInspiration(command) => is_attack(command),
+                _ => false,
+            }
+        }
+        if !flanking {
+            return command;
+        }
+        command.and_then(|command| {
+            if is_attack(&command) {
+                Ok(Command::FlankingAttack(Box::new(command)))
+            } else {
+                Err(Error::FlankingNotAttackRoll)
+            }
+        })
+    }
+
+    /// Give an attack advantage, such as for flanking, looking through any companion or
+    /// inspiration it's wrapped in. Any other command is left as it is.
+    pub fn with_advantage(self) -> Command {
+        match self {
+            Command::AttackRoll(roll, count, smite, target, cover) => {
+                Command::AttackRoll(roll.with_advantage(), count, smite, target, cover)
+            }
+            Command::ToHitRoll(roll, target, cover) => {
+                Command::ToHitRoll(roll.with_advantage(), target, cover)
+            }
+            Command::AsCompanion(companion, command) => {
+                Command::AsCompanion(companion, Box::new(command.with_advantage()))
+            }
+            Command::UseBardicInspiration(command) => {
+                Command::UseBardicInspiration(Box::new(command.with_advantage()))
+            }
+            Command::UseInspiration(command) => {
+                Command::UseInspiration(Box::new(command.with_advantage()))
+            }
+            command => command,
+        }
+    }
+
+    /// Split the target from an attack against a combatant, such as "the goblin" from "attack the
+    /// goblin with my shortbow" or "attack with my shortbow against the goblin", or from a roll to
+    /// hit, such as "roll to hit the goblin with my rapier", returning the rest of the attack along
+    /// with the target.
+    fn split_target(string: &str) -> (String, Option<String>) {
+        lazy_static! {
+            static ref BEFORE_WEAPON_REGEX: Regex = Regex::new(
+                r"^(?i)(attack|roll +to +hit) +(?:the +)?(.+?)( +(?:once|twice|thrice|(?:\d+|one|two|three|four) +times))? +((?:with|using)\b.*)$"
+            )
+            .unwrap();
+            static ref AGAINST_REGEX: Regex =
+                Regex::new(r"^(?i)(.*\b(?:attack|to +hit)\b.*?) +against +(?:the +)?(.+?) *$")
+                    .unwrap();
+            // Words that can come between "attack" and the weapon without being a target
+            static ref NOT_TARGET_REGEX: Regex = Regex::new(
+                r"^(?i)(?:once|twice|thrice|\w+ +times|(?:one|two)[ -]handed|off[ -]hand(?:ed)?|ranged|melee|(?:with|using)\b.*)$"
+            )
+            .unwrap();
+        }
+        if let Some(captures) = BEFORE_WEAPON_REGEX.captures(string) {
+            let target = captures.get(2).map_or("", |m| m.as_str());
+            if !NOT_TARGET_REGEX.is_match(target) {
+                let count = captures.get(3).map_or("", |m| m.as_str());
+                return (
+                    format!("{}{} {}", &captures[1], count, &captures[4]),
+                    Some(target.to_owned()),
+                );
+            }
+        }
+        match AGAINST_REGEX.captures(string) {
+            Some(captures) => (captures[1].to_owned(), Some(captures[2].to_owned())),
+            None => (string.to_owned(), None),
+        }
+    }
+
+    /// Apply the target split from an attack, if any, to the parsed attack.
+    fn with_target(
+        command: Result<Command, Error>,
+        target: Option<String>,
+    ) -> Result<Command, Error> {
+        match target {
+            Some(target) => command.and_then(|command| match command {
+                Command::AttackRoll(roll, count, smite, _, cover) => {
+                    Ok(Command::AttackRoll(roll, count, smite, Some(target), cover))
+                }
+                Command::ToHitRoll(roll, _, cover) => {
+                    Ok(Command::ToHitRoll(roll, Some(target), cover))
+                }
+                _ => Err(Error::AttackTargetNotAttackRoll),
+            }),
+            None => command,
+        }
+    }
+
+    /// Split the cover the target of an attack is behind, such as "behind half cover" from "attack
+    /// the archer behind half cover with my shortbow", returning the rest of the attack along with
+    /// the cover.
+    fn split_cover(string: &str) -> (String, Option<Cover>) {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^(?i)(.*?),? +(?:who +is +|that +is +|is +)?(?:behind|with|in|from +behind|under) +(half|three[- ]quarters?|3/4) +cover\b,?(.*)$"
+            )
+            .unwrap();
+        }
+        RE.captures(string)
+            .and_then(|captures| {
+                Cover::parse(&captures[2])
+                    .map(|cover| (format!("{}{}", &captures[1], &captures[3]), Some(cover)))
+            })
+            .unwrap_or_else(|| (string.to_owned(), None))
+    }
+
+    /// Apply the cover split from an attack, if any, to the parsed attack.
+    fn with_cover(command: Result<Command, Error>, cover: Option<Cover>) -> Result<Command, Error> {
+        match cover {
+            Some(cover) => command.and_then(|command| match command {
+                Command::AttackRoll(roll, count, smite, target, _) => {
+                    Ok(Command::AttackRoll(roll, count, smite, target, Some(cover)))
+                }
+                Command::ToHitRoll(roll, target, _) => {
+                    Ok(Command::ToHitRoll(roll, target, Some(cover)))
+                }
+                _ => Err(Error::CoverNotAttackRoll),
+            }),
+            None => command,
+        }
+    }
+
+    /// Apply the range split from the end of an attack, if the target is within 5 feet, to the
+    /// parsed attack.
+    fn with_within_5_feet(
+        command: Result<Command, Error>,
+        within_5_feet: bool,
+    ) -> Result<Command, Error> {
+        if !within_5_feet {
+            return command;
+        }
+        command.and_then(|command| match command {
+            Command::AttackRoll(roll, count, smite, target, cover) => Ok(Command::AttackRoll(
+                roll.within_5_feet(),
+                count,
+                smite,
+                target,
+                cover,
+            )),
+            Command::ToHitRoll(roll, target, cover) => {
+                Ok(Command::ToHitRoll(roll.within_5_feet(), target, cover))
+            }
+            _ => Err(Error::Within5FeetNotAttackRoll),
+        })
+    }
+
+    /// Apply the Divine Smite split from the end of an attack, if any, to the parsed attack.
+    fn with_smite(command: Result<Command, Error>, smite: Option<Smite>) -> Result<Command, Error> {
+        match smite {
+            Some(smite) => command.and_then(|command| match command {
+                Command::AttackRoll(roll, count, _, target, cover) => {
+                    Ok(Command::AttackRoll(roll, count, Some(smite), target, cover))
+                }
+                _ => Err(Error::SmiteNotAttackRoll),
+            }),
+            None => command,
+        }
+    }
+
+    /// Add a Bardic Inspiration die to a roll. Like inspiration, it can only be used on a d20
+    /// roll.
+    pub fn use_bardic_inspiration(self) -> Result<Command, Error> {
+        let is_d20 = match &self {
+            Command::AttackRoll(_, _, _, _, _)
+            | Command::CharacterRoll(_, _)
+            | Command::ToHitRoll(_, _, _) => true,
+            Command::Roll(roll, _) => roll.is_d20(),
+            _ => false,
+        };
+        if is_d20 {
+            Ok(Command::UseBardicInspiration(Box::new(self)))
+        } else {
+            Err(Error::UseBardicInspirationNotD20Roll)
+        }
+    }
+
+    /// Use inspiration on a roll, giving it advantage. Only d20 rolls can use inspiration.
+    pub fn use_inspiration(self) -> Result<Command, Error> {
+        match self {
+            Command::AttackRoll(roll, count, smite, target, cover) => Ok(Command::AttackRoll(
+                roll.with_advantage(),
+                count,
+                smite,
+                target,
+                cover,
+            )),
+            Command::CharacterRoll(mut roll, purpose) => {
+                roll.condition = Condition::add_advantage(roll.condition);
+                Ok(Command::CharacterRoll(roll, purpose))
+            }
+            Command::ToHitRoll(roll, target, cover) => {
+                Ok(Command::ToHitRoll(roll.with_advantage(), target, cover))
+            }
+            Command::Roll(roll, purpose) if roll.is_d20() => {
+                let condition = Condition::add_advantage(roll.condition());
+                Ok(Command::Roll(roll.with_condition(condition), purpose))
+            }
+            _ => Err(Error::UseInspirationNotD20Roll),
+        }
+        .map(|command| Command::UseInspiration(Box::new(command)))
+    }
+
+    fn parse_natural_language(
+        engine: Option<&SnipsNluEngine>,
+        symspell: Option<&SymSpell<UnicodeStringStrategy>>,
+        message: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> NaturalLanguageCommandResult {
+        Command::extract_at_message(message, mentions, dice_only)
+            .as_ref()
+            .map(|at_message| {
+                let (at_message, uncorrected) = Command::split_uncorrected(at_message);
+                let symspell = symspell.filter(|_| !uncorrected);
+                let (at_message, companion) = Command::split_companion(at_message);
+                let (at_message, inspiration) = Command::split_inspiration(at_message);
+                let (at_message, smite) = Command::split_smite(at_message);
+                let (at_message, flanking) = Command::split_flanking(at_message);
+                let (at_message, within_5_feet) = Command::split_within_5_feet(at_message);
+                let (without_cover, cover) = Command::split_cover(at_message);
+                let (without_target, target) = Command::split_target(&without_cover);
+                let at_message = without_target.as_str();
+                // An attack may say how many times to attack, and a roll may end with its purpose.
+                // These are parsed separately so that they don't confuse the intent parser.
+                // Anything else is parsed as a whole.
+                Command::split_attack_count(at_message)
+                    .and_then(|(without_count, count)| {
+                        match Command::parse_intent(engine, symspell, &without_count) {
+                            Ok((Ok(command), result, corrected)) => command
+                                .with_attack_count(count)
+                                .map(|command| Ok((Ok(command), result, corrected))),
+                            _ => None,
+                        }
+                    })
+                    .or_else(|| {
+                        Purpose::split_natural_language(at_message).and_then(
+                            |(without_purpose, purpose)| match Command::parse_intent(
+                                engine,
+                                symspell,
+                                without_purpose,
+                            ) {
+                                Ok((Ok(command), result, corrected)) => command
+                                    .with_purpose(purpose)
+                                    .map(|command| Ok((Ok(command), result, corrected))),
+                                _ => None,
+                            },
+                        )
+                    })
+                    .unwrap_or_else(|| Command::parse_intent(engine, symspell, at_message))
+                    .map(|(command, result, corrected)| {
+                        (
+                            Command::with_flanking(
+                                Command::with_companion(
+                                    Command::with_inspiration(
+                                        Command::with_smite(
+                                            Command::with_within_5_feet(
+                                                Command::with_cover(
+                                                    Command::with_target(command, target),
+                                                    cover,
+                                                ),
+                                                within_5_feet,
+                                            ),
+                                            smite,
+                                        ),
+                                        inspiration,
+                                    ),
+                                    companion,
+                                ),
+                                flanking,
+                            ),
+                            result,
+                            corrected,
+                        )
+                    })
+            })
+    }
+
+    /// Parse a message with the intent parser, or with the rule-based fallback if the intent
+    /// parser isn't loaded, in which case there's no intent parser result.
+    fn parse_intent(
+        engine: Option<&SnipsNluEngine>,
+        symspell: Option<&SymSpell<UnicodeStringStrategy>>,
+        message: &str,
+    ) -> Result<
+        (
+            Result<Command, Error>,
+            Option<IntentParserResult>,
+            Option<String>,
+        ),
+        Error,
+    > {
+        let corrected =
+            symspell.and_then(|symspell| Command::spelling_correction(symspell, message));
+        let used = corrected.as_deref().unwrap_or(message);
+        match engine {
+            Some(engine) => engine
+                .parse(used, None, None)
+                .map(|result| (parse_intent_result(&result), Some(result), corrected))
+                .map_err(Error::IntentParserError),
+            None => Ok((Command::parse_rule_based(used), None, corrected)),
+        }
+    }
+
+    /// Parse the most common natural language commands with regular expressions, for when the
+    /// intent parser's model couldn't be loaded. This understands rolling dice, such as "Roll
+    /// 2d6+3", rolling checks and saving throws, such as "Roll a dex save with advantage",
+    /// attacking with a weapon, such as "Attack with my longsword two-handed", and the phrases
+    /// for changing ability scores and levels that the keyword fallback understands.
+    fn parse_rule_based(message: &str) -> Result<Command, Error> {
+        lazy_static! {
+            static ref ROLL_REGEX: Regex = Regex::new(
+                r"^(?:roll|throw|make)(?: +(?:a|an|my))? +(.+?)(?: +(?:check|roll))?( +with +(?:advantage|disadvantage))?$"
+            )
+            .unwrap();
+            static ref SAVE_REGEX: Regex = Regex::new(r"^(.+?) +save$").unwrap();
+            static ref ATTACK_REGEX: Regex = Regex::new(
+                r"^(?:(?:attack|hit|strike|swing)\b.*? +with +(?:my +|a +|an +|the +)?(.+?)|(?:my +)?(.+?) +attack)(?: +with +(advantage|disadvantage))?$"
+            )
+            .unwrap();
+            static ref HANDEDNESS_REGEX: Regex =
+                Regex::new(r"^(?:(one|two)[- ]handed +)?(.+?)(?: +(one|two)[- ]handed)?$")
+                    .unwrap();
+        }
+
+        let message = message.trim().trim_end_matches(|c| c == '.' || c == '!');
+        let message = message.to_lowercase();
+        if let Some(captures) = ROLL_REGEX.captures(&message) {
+            let check = captures.get(1).map_or("", |m| m.as_str());
+            let condition = captures.get(2).map_or("", |m| m.as_str());
+            let check = SAVE_REGEX
+                .captures(check)
+                .and_then(|captures| captures.get(1))
+                .map_or_else(
+                    || check.to_owned(),
+                    |m| format!("{} saving throw", m.as_str()),
+                );
+            let roll = format!("{}{}", check, condition);
+            ConditionalRoll::parse(&roll)
+                .map(|roll| Command::Roll(roll, None))
+                .or_else(|_| {
+                    CharacterRoll::parse(&roll)
+                        .map(|roll| Command::CharacterRoll(roll, None))
+                        .ok_or_else(|| {
+                            Error::CharacterRollParserError(CharacterRoll::suggest(&roll))
+                        })
+                })
+        } else if let Some(captures) = ATTACK_REGEX.captures(&message) {
+            let weapon = captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .map_or("", |m| m.as_str());
+            let condition = captures.get(3).and_then(|m| match m.as_str() {
+                "advantage" => Some(Condition::Advantage),
+                "disadvantage" => Some(Condition::Disadvantage),
+                _ => None,
+            });
+            let handedness = HANDEDNESS_REGEX.captures(weapon);
+            let name = handedness
+                .as_ref()
+                .and_then(|captures| captures.get(2))
+                .map_or(weapon, |m| m.as_str());
+            let handedness = handedness
+                .as_ref()
+                .and_then(|captures| captures.get(1).or_else(|| captures.get(3)))
+                .and_then(|m| Handedness::parse(&format!("{} handed", m.as_str())));
+            let weapon = WeaponName::parse(name)
+                .ok_or_else(|| Error::RollAttackMissingWeapon(WeaponName::suggest(name)))?;
+            if weapon.to_weapon().versatile.is_some() && handedness.is_none() {
+                Err(Error::RollAttackMissingHandedness)
+            } else {
+                let roll = AttackRoll::Weapon(WeaponAttackRoll {
+                    weapon,
+                    classification: None,
+                    condition,
+                    handedness,
+                });
+                Ok(Command::AttackRoll(
+                    roll,
+                    AttackCount::Times(1),
+                    None,
+                    None,
+                    None,
+                ))
+            }
+        } else {
+            parse_keyword_fallback(&message).unwrap_or(Err(Error::NoRuleBasedIntent))
+        }
+    }
+
+    /// Split the number of attacks from an attack, such as "twice" from "attack twice with my
+    /// shortsword", returning the rest of the attack along with the number of attacks.
+    fn split_attack_count(string: &str) -> Option<(String, AttackCount)> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"(?i)(?:^| +)(once|twice|thrice|(?:\d+|one|two|three|four) +times|(?:(?:using|with|use) +(?:my +)?)?extra +attacks?)\b"
+            )
+            .unwrap();
+        }
+        let captures = RE.captures(string)?;
+        let count = AttackCount::parse(captures.get(1)?.as_str())?;
+        let m = captures.get(0)?;
+        let without_count = format!("{}{}", &string[..m.start()], &string[m.end()..]);
+        Some((without_count.trim().to_owned(), count))
+    }
+
+    /// Attach a number of attacks to an attack. Commands other than attacks can't have one.
+    fn with_attack_count(self, count: AttackCount) -> Option<Command> {
+        match self {
+            Command::AttackRoll(roll, _, smite, target, cover) => {
+                Some(Command::AttackRoll(roll, count, smite, target, cover))
+            }
+            _ => None,
+        }
+    }
+
+    /// Attach a purpose to a roll. Commands other than rolls can't have a purpose.
+    fn with_purpose(self, purpose: Purpose) -> Option<Command> {
+        match self {
+            Command::CharacterRoll(roll, _) => Some(Command::CharacterRoll(roll, Some(purpose))),
+            Command::Roll(roll, _) => Some(Command::Roll(roll, Some(purpose))),
+            _ => None,
+        }
+    }
+
+    pub fn extract_at_message(
+        message: &str,
+        mentions: &BotMentions,
+        dice_only: bool,
+    ) -> Option<String> {
+        lazy_static! {
+            static ref COMMAND_REGEX: Regex =
+                Regex::new(r"^(?:<@([!&]?)(\d+)>|((?i:@dungeon[ -]?helper)\b))? *(.*)$").unwrap();
+        }
+
+        COMMAND_REGEX.captures(&message).and_then(|c| {
+            let is_at_message = c.get(3).is_some()
+                || c.get(2)
+                    .and_then(|m| m.as_str().parse::<u64>().ok())
+                    .map_or(false, |id| {
+                        mentions.is_mentioned(c.get(1).map_or("", |m| m.as_str()), id)
+                    });
+            if dice_only || is_at_message {
+                c.get(4).map(|m| m.as_str().to_owned())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Correct the spelling of the words in a message. Dice expressions, numbers and mentions
+    /// aren't words, and would be mangled by the correction, so only the words around them are
+    /// corrected and they're kept as they were.
+    fn spelling_correction(
+        symspell: &SymSpell<UnicodeStringStrategy>,
+        message: &str,
+    ) -> Option<String> {
+        let trimmed = message.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let corrected = Command::split_protected(trimmed)
+            .into_iter()
+            .map(|(piece, protected)| {
+                let words = piece.trim();
+                if protected || words.is_empty() {
+                    return piece.to_owned();
+                }
+                let correction = symspell
+                    .lookup_compound(words, 2)
+                    .into_iter()
+                    .next()
+                    .map_or_else(|| words.to_owned(), |suggestion| suggestion.term);
+                // Keep the spaces that separated the words from the protected pieces
+                let start = piece.len() - piece.trim_start().len();
+                let end = piece.trim_end().len();
+                format!("{}{}{}", &piece[..start], correction, &piece[end..])
+            })
+            .collect();
+        Some(corrected)
+    }
+
+    /// Split a message into the pieces that spelling correction should leave alone, which are
+    /// dice expressions such as "2d8+3", numbers such as "+5" or "2nd", and mentions, and the
+    /// words in between. Each piece is returned with whether it's protected.
+    fn split_protected(message: &str) -> Vec<(&str, bool)> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"(?i)<(?:@[!&]?|#)\d+>|\b\d*d\d+(?: *[+-] *\d+)*\b|[+-]?\b\d+(?:st|nd|rd|th)?\b"
+            )
+            .unwrap();
+        }
+        let mut pieces = Vec::new();
+        let mut last = 0;
+        for m in RE.find_iter(message) {
+            if m.start() > last {
+                pieces.push((&message[last..m.start()], false));
+            }
+            pieces.push((m.as_str(), true));
+            last = m.end();
+        }
+        if last < message.len() {
+            pieces.push((&message[last..], false));
+        }
+        pieces
+    }
+
+    fn parse_shorthand(command: &str) -> Option<Result<Command, Error>> {
+        lazy_static! {
+            static ref ALERT_COMMAND_REGEX: Regex = Regex::new(r"^!alert(?: +(.*))?$").unwrap();
+            static ref ARMOR_CLASS_COMMAND_REGEX: Regex = Regex::new(r"^!ac(?: +(.*))?$").unwrap();
+            static ref AUDIT_COMMAND_REGEX: Regex = Regex::new(r"^!audit(?: +(.*))?$").unwrap();
+            static ref BARDIC_COMMAND_REGEX: Regex = Regex::new(r"^!bardic(?: +(.*))?$").unwrap();
+            static ref BONUS_COMMAND_REGEX: Regex =
+                Regex::new(r"^!bonus(?:es)?(?: +(.*))?$").unwrap();
+            static ref BONUS_REMOVE_REGEX: Regex = Regex::new(r"^(?i)remove +(.+)$").unwrap();
+            static ref IMPORT_COMMAND_REGEX: Regex = Regex::new(r"^!import(?: +(.*))?$").unwrap();
+            static ref DND_BEYOND_CHARACTER_REGEX: Regex = Regex::new(
+                r"^(?:https?://(?:www\.)?dndbeyond\.com/(?:profile/[^/]+/)?characters/)?(\d+)/?$"
+            )
+            .unwrap();
+            static ref DAMAGE_COMMAND_REGEX: Regex = Regex::new(r"^!damage(?: +(.*))?$").unwrap();
+            static ref DELETE_COMMAND_REGEX: Regex = Regex::new(r"^!delete(?: +(.*))?$").unwrap();
+            static ref DIFFICULTY_COMMAND_REGEX: Regex =
+                Regex::new(r"^!difficulty(?: +(.*))?$").unwrap();
+            static ref GROUP_SAVE_COMMAND_REGEX: Regex =
+                Regex::new(r"^!groupsave(?: +(.*))?$").unwrap();
+            static ref GROUP_SAVE_ARGUMENTS_REGEX: Regex =
+                Regex::new(r"^(?i)(\S+) +(?:dc *)?(\d+)$").unwrap();
+            static ref HEAL_COMMAND_REGEX: Regex = Regex::new(r"^!heal(?: +(.*))?$").unwrap();
+            static ref HIT_POINTS_COMMAND_REGEX: Regex = Regex::new(r"^!hp(?: +(.*))?$").unwrap();
+            static ref HIT_POINTS_ARGUMENTS_REGEX: Regex =
+                Regex::new(r"^(?i)(.+?) +(\d+)( +hidden)?$").unwrap();
+            static ref COMBATANT_AMOUNT_REGEX: Regex = Regex::new(r"^(.+?) +(\d+)$").unwrap();
+            static ref CONDITION_COMMAND_REGEX: Regex =
+                Regex::new(r"^!conditions?(?: +(?:(add|remove) +)?(.*?))? *$").unwrap();
+            static ref CONDITION_ROUNDS_REGEX: Regex =
+                Regex::new(r"^(?i)(.+?) +for +(\d+) +rounds?$").unwrap();
+            static ref COPY_COMMAND_REGEX: Regex = Regex::new(r"^!copy(?: +(.*))?$").unwrap();
+            static ref COMPANION_COMMAND_REGEX: Regex =
+                Regex::new(r"^!companions?(?: +(.*))?$").unwrap();
+            static ref COMPANION_ARGUMENTS_REGEX: Regex = Regex::new(
+                r"^(?i)(familiar|(?:beast|animal|primal) +companion|steel +defender|companion) +(.+)$"
+            )
+            .unwrap();
+            static ref TREASURE_COMMAND_REGEX: Regex =
+                Regex::new(r"^!treasure(?: +(hoard|individual))?(?: +(.*))?$").unwrap();
+            static ref CONTEST_COMMAND_REGEX: Regex =
+                Regex::new(r"^!(grapple|shove)(?: +(.*))?$").unwrap();
+            static ref CONTEST_ARGUMENTS_REGEX: Regex =
+                Regex::new(r"^(?i)(?:(.+?) +(?:vs\.?|against) +)?(.+)$").unwrap();
+            static ref COUNTER_COMMAND_REGEX: Regex =
+                Regex::new(r"^!counters?(?: +(.*))?$").unwrap();
+            static ref COUNTER_SPEND_REGEX: Regex =
+                Regex::new(r"^(?i)spend +(.+?)(?: +(\d+))?$").unwrap();
+            static ref COUNTER_RESTORE_REGEX: Regex =
+                Regex::new(r"^(?i)restore +(.+?)(?: +(\d+))?$").unwrap();
+            static ref COUNTER_REMOVE_REGEX: Regex = Regex::new(r"^(?i)remove +(.+)$").unwrap();
+            static ref COUNTER_SET_REGEX: Regex = Regex::new(
+                r"^(?i)(.+?) +(\d+)(?: +(?:per +|on +(?:an? +)?)?(short|long)(?: +rests?)?)?$"
+            )
+            .unwrap();
+            static ref COPY_LEVEL_REGEX: Regex =
+                Regex::new(r"^(?i)(.*?) *\b(?:at +)?level +(\d+)$").unwrap();
+            static ref COPY_CHANNEL_REGEX: Regex =
+                Regex::new(r"^(?i)(.*?) *\bfrom +<#(\d+)>$").unwrap();
+            static ref CHANNEL_COMMAND_REGEX: Regex = Regex::new(r"^!channel(?: +(.*))?$").unwrap();
+            static ref CHANNEL_DUPLICATE_WINDOW_REGEX: Regex = Regex::new(
+                r"^(?i)duplicate[ -]?window +(\d+)(?: *(?:s|secs?|seconds?))?$"
+            )
+            .unwrap();
+            static ref CAST_COMMAND_REGEX: Regex =
+                Regex::new(r"^!cast(?: +((?i)ritual))?(?: +(.+?))?(?: +(\d+))? *$").unwrap();
+            static ref CLASS_COMMAND_REGEX: Regex =
+                Regex::new(r"^!class +(\S+)(?: +(\d+))?(?: +(.*))?$").unwrap();
+            static ref EDITION_COMMAND_REGEX: Regex = Regex::new(r"^!edition(?: +(.*))?$").unwrap();
+            static ref EFFECT_COMMAND_REGEX: Regex = Regex::new(r"^!effects?(?: +(.*))?$").unwrap();
+            static ref EFFECT_END_REGEX: Regex = Regex::new(r"^(?i)(?:end|remove) +(.+)$").unwrap();
+            static ref DUNGEON_COMMAND_REGEX: Regex = Regex::new(r"^!dungeon(?: +(.*))?$").unwrap();
+            static ref EXPERIENCE_COMMAND_REGEX: Regex = Regex::new(r"^!xp(?: +(.*))?$").unwrap();
+            static ref EXPERIENCE_REASON_REGEX: Regex =
+                Regex::new(r"^(?i)(-?\d+) +(?:xp +)?(?:for|from) +(.+)$").unwrap();
+            static ref HOUSE_RULES_COMMAND_REGEX: Regex =
+                Regex::new(r"^!houserules(?: +(.*))?$").unwrap();
+            static ref HOUSE_RULES_SET_REGEX: Regex = Regex::new(r"^(?i)set +(.+)$").unwrap();
+            static ref IMPROVISE_COMMAND_REGEX: Regex =
+                Regex::new(r"^!improvise(?: +(?i:cr +)?(\S+)(?: +([a-zA-Z]+))?)? *$").unwrap();
+            static ref INITIATIVE_BONUS_COMMAND_REGEX: Regex =
+                Regex::new(r"^!initbonus(?: +(.*))?$").unwrap();
+            static ref INITIATIVE_COMMAND_REGEX: Regex =
+                Regex::new(r"^!init(?:iative)?(?: +((?s).*))?$").unwrap();
+            static ref INITIATIVE_ADD_REGEX: Regex = Regex::new(
+                r"^(?i)add +([^,;\n]+?) +(-?\d+)(?: +(?:hp|hit +points) +(\d+)( +hidden)?)?$"
+            )
+            .unwrap();
+            static ref INSPIRE_COMMAND_REGEX: Regex = Regex::new(r"^!inspire(?: +(.*))?$").unwrap();
+            static ref LIMITS_COMMAND_REGEX: Regex = Regex::new(r"^!limits(?: +(.*))?$").unwrap();
+            static ref LORE_COMMAND_REGEX: Regex = Regex::new(r"^!lore(?: +(.*))?$").unwrap();
+            static ref LORE_ADD_REGEX: Regex = Regex::new(r"^(?i)add +(.+)$").unwrap();
+            static ref LORE_REMOVE_REGEX: Regex = Regex::new(r"^(?i)remove +(.+)$").unwrap();
+            static ref LORE_REVEAL_REGEX: Regex = Regex::new(r"^(?i)reveal +(.+)$").unwrap();
+            static ref MASTERY_COMMAND_REGEX: Regex =
+                Regex::new(r"^!master(?:y|ies)(?: +(.*))?$").unwrap();
+            static ref MASTERY_REMOVE_REGEX: Regex = Regex::new(r"^(?i)remove +(.+)$").unwrap();
+            static ref MENTION_REGEX: Regex = Regex::new(r"^<@!?(\d+)>$").unwrap();
+            static ref MONSTER_COMMAND_REGEX: Regex =
+                Regex::new(r"^!monsters?(?: +(.*))?$").unwrap();
+            static ref MONSTER_SAVE_REGEX: Regex = Regex::new(r"^(?i)save +(.+)$").unwrap();
+            static ref MONSTER_DELETE_REGEX: Regex = Regex::new(r"^(?i)delete +(.+)$").unwrap();
+            static ref MONSTER_ADD_REGEX: Regex = Regex::new(r"^(?i)add +(.+?)( +hidden)?$").unwrap();
+            static ref MONSTER_ATTACK_REGEX: Regex =
+                Regex::new(r"^(?i)attack +(.+?)(?: +(?:against|vs\.?) +(.+?))?(?: +with +(advantage|disadvantage))?$").unwrap();
+            static ref ONGOING_COMMAND_REGEX: Regex = Regex::new(r"^!ongoing(?: +(.*))?$").unwrap();
+            static ref ONGOING_CLEAR_REGEX: Regex = Regex::new(r"^(?i)clear +(.+)$").unwrap();
+            static ref PACK_COMMAND_REGEX: Regex = Regex::new(r"^!monsterpacks?(?: +(.*))?$").unwrap();
+            static ref PACK_ACTION_REGEX: Regex =
+                Regex::new(r"^(?i)(enable|disable|remove) +(.+)$").unwrap();
+            static ref NAME_COMMAND_REGEX: Regex = Regex::new(r"^!name +(.*)$").unwrap();
+            static ref NOTE_COMMAND_REGEX: Regex = Regex::new(r"^(?s)!note(?: +(.*))?$").unwrap();
+            static ref NOTE_DELETE_REGEX: Regex =
+                Regex::new(r"^(?i)(?:delete|remove) +(#?\d+) *$").unwrap();
+            static ref NOTES_COMMAND_REGEX: Regex = Regex::new(r"^!notes(?: +(.*))?$").unwrap();
+            static ref NOTES_SEARCH_REGEX: Regex = Regex::new(r"^(?i)search +(.+)$").unwrap();
+            static ref OBSERVANT_COMMAND_REGEX: Regex =
+                Regex::new(r"^!observant(?: +(.*))?$").unwrap();
+            static ref PASSIVE_COMMAND_REGEX: Regex =
+                Regex::new(r"^!passives?(?: +(.*))?$").unwrap();
+            static ref PURGE_COMMAND_REGEX: Regex = Regex::new(r"^!purge(?: +(.*))?$").unwrap();
+            static ref PREGEN_COMMAND_REGEX: Regex =
+                Regex::new(r"^!pregen +(\S+)(?: +(\d+))? *$").unwrap();
+            static ref PORTRAIT_COMMAND_REGEX: Regex = Regex::new(r"^!portrait +(.*)$").unwrap();
+            static ref PORTRAIT_URL_REGEX: Regex = Regex::new(r"^https?://\S+$").unwrap();
+            static ref RANDOM_TABLE_COMMAND_REGEX: Regex =
+                Regex::new(r"^(?s)!rtables?(?: +(.*))?$").unwrap();
+            static ref RANDOM_TABLE_ADD_REGEX: Regex = Regex::new(r"^(?is)add +(.+)$").unwrap();
+            static ref RANDOM_TABLE_DELETE_REGEX: Regex =
+                Regex::new(r"^(?i)(?:delete|remove) +(.+)$").unwrap();
+            static ref RANDOM_TABLE_SHOW_REGEX: Regex = Regex::new(r"^(?i)show +(.+)$").unwrap();
+            static ref RECALL_LORE_REGEX: Regex = Regex::new(r"^(?i)(.+?) +about +(.+)$").unwrap();
+            static ref REGEN_COMMAND_REGEX: Regex = Regex::new(r"^!regen(?: +(.*))?$").unwrap();
+            static ref RESISTANCE_COMMAND_REGEX: Regex =
+                Regex::new(r"^!(resist|immune)(?: +(.*))?$").unwrap();
+            static ref RESISTANCE_CLEAR_REGEX: Regex =
+                Regex::new(r"^(?i)(.+?) +(?:none|clear)$").unwrap();
+            static ref REST_COMMAND_REGEX: Regex = Regex::new(r"^!rest(?: +(.*))?$").unwrap();
+            static ref RESTRICT_COMMAND_REGEX: Regex = Regex::new(
+                r"^!restrict(?: +(.+?))?(?: +(?:to +)?(?:<@&(\d+)>|((?i)everyone|anyone)))? *$"
+            )
+            .unwrap();
+            static ref ROLL_COMMAND_REGEX: Regex = Regex::new(r"^!(?:r|roll) +(.*)$").unwrap();
+            static ref STASH_COMMAND_REGEX: Regex = Regex::new(r"^!stash(?: +(.*))?$").unwrap();
+            static ref STASH_ACTION_REGEX: Regex =
+                Regex::new(r"^(?i)(add|deposit|put|take|withdraw|remove) +(.+)$").unwrap();
+            static ref STYLE_COMMAND_REGEX: Regex = Regex::new(r"^!style(?: +(.*))?$").unwrap();
+            static ref TABLE_COMMAND_REGEX: Regex = Regex::new(r"^!table(?: +(.*))?$").unwrap();
+            static ref TIME_ZONE_COMMAND_REGEX: Regex =
+                Regex::new(r"^!(?:timezone|tz)(?: +(server))?(?: +(.*))?$").unwrap();
+            static ref SERVER_COMMAND_REGEX: Regex = Regex::new(r"^!server(?: +(.*))?$").unwrap();
+            static ref SETTLEMENT_COMMAND_REGEX: Regex =
+                Regex::new(r"^!settlement(?: +(.*))?$").unwrap();
+            static ref TAVERN_COMMAND_REGEX: Regex = Regex::new(r"^!tavern *$").unwrap();
+            static ref TRAVEL_COMMAND_REGEX: Regex = Regex::new(r"^!travel(?: +(.*))?$").unwrap();
+            static ref VERIFY_COMMAND_REGEX: Regex = Regex::new(r"^!verify(?: +(.*))?$").unwrap();
+            static ref WEATHER_COMMAND_REGEX: Regex = Regex::new(r"^!weather(?: +(.*))?$").unwrap();
+            static ref WILD_SHAPE_COMMAND_REGEX: Regex =
+                Regex::new(r"^!wild ?shape(?: +(.*))?$").unwrap();
+            static ref WILD_SHAPE_ACTION_REGEX: Regex =
+                Regex::new(r"^(?i)(add|damage|heal) +(.+)$").unwrap();
+            static ref WILD_SHAPE_ATTACK_REGEX: Regex = Regex::new(
+                r"^(?i)attack(?: +(.+?))??(?: +(?:against|vs\.?) +(.+?))?(?: +with +(advantage|disadvantage))?$"
+            )
+            .unwrap();
+            static ref HIDDEN_ROLL_COMMAND_REGEX: Regex =
+                Regex::new(r"^!(?:r|roll) +(.+?) +hidden *$").unwrap();
+            static ref BLIND_ROLL_COMMAND_REGEX: Regex =
+                Regex::new(r"^!(?:r|roll) +(.+?) +blind *$").unwrap();
+            static ref DUNGEON_MASTER_COMMAND_REGEX: Regex =
+                Regex::new(r"^!dm(?: +(.*))?$").unwrap();
+        }
+
+        if command == "!export" {
+            Some(Ok(Command::ExportCharacter))
+        } else if command == "!trainingdata" {
+            Some(Ok(Command::ExportTrainingData))
+        } else if command == "!reloadmodel" {
+            Some(Ok(Command::ReloadModel))
+        } else if command == "!help" {
+            Some(Ok(Command::HelpShorthand))
+        } else if command == "!permissions" {
+            Some(Ok(Command::CheckPermissions))
+        } else if command == "!sheet" {
+            Some(Ok(Command::ShowCharacterSheet))
+        } else if command == "!pin" {
+            Some(Ok(Command::PinQuickReference))
+        } else if command == "!slots" {
+            Some(Ok(Command::ShowSpellSlots))
+        } else if command == "!reveal" {
+            Some(Ok(Command::RevealHiddenRolls))
+        } else if let Some(captures) = HIDDEN_ROLL_COMMAND_REGEX.captures(&command) {
+            captures.get(1).map(|m| Command::hidden_roll(m.as_str()))
+        } else if let Some(captures) = BLIND_ROLL_COMMAND_REGEX.captures(&command) {
+            captures.get(1).map(|m| Command::blind_roll(m.as_str()))
+        } else if let Some(captures) = ALERT_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") | Some("on") => Ok(Command::SetAlert(true)),
+                Some("off") => Ok(Command::SetAlert(false)),
+                Some(_) => Err(Error::AlertParserError),
+            })
+        } else if let Some(captures) = ARMOR_CLASS_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            // A name before the armour class sets a combatant's armour class instead
+            Some(match COMBATANT_AMOUNT_REGEX.captures(arguments) {
+                Some(captures) => Command::set_combatant_armor_class(&captures),
+                None => arguments
+                    .parse()
+                    .map_err(|_| Error::ArmorClassParserError)
+                    .and_then(Command::set_armor_class),
+            })
+        } else if let Some(captures) = AUDIT_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowCharacterAudit(None))
+            } else {
+                MENTION_REGEX
+                    .captures(arguments)
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|m| m.as_str().parse().ok())
+                    .map(|user_id| Command::ShowCharacterAudit(Some(UserId(user_id))))
+                    .ok_or(Error::AuditParserError)
+            })
+        } else if let Some(captures) = BARDIC_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| Command::recipient(m.as_str()))
+                    .map(Command::GrantBardicInspiration)
+                    .ok_or(Error::BardicInspirationParserError),
+            )
+        } else if let Some(captures) = BONUS_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowBonuses)
+            } else {
+                match BONUS_REMOVE_REGEX.captures(arguments) {
+                    Some(captures) => Ok(Command::RemoveBonus(
+                        captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                    )),
+                    None => Bonus::parse(arguments)
+                        .map(Command::AddBonus)
+                        .ok_or(Error::BonusParserError),
+                }
+            })
+        } else if let Some(captures) = CHANNEL_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(match arguments.to_lowercase().as_ref() {
+                "" | "status" => Ok(Command::ShowChannelSettings),
+                "export" => Ok(Command::ExportChannelSettings),
+                "import" => Ok(Command::ImportChannelSettings),
+                // The duplicate window is only a channel setting, not one channels start with
+                _ => match CHANNEL_DUPLICATE_WINDOW_REGEX.captures(arguments) {
+                    Some(captures) => captures
+                        .get(1)
+                        .and_then(|m| m.as_str().parse().ok())
+                        .map(Duration::from_secs)
+                        .filter(|duplicate_window| *duplicate_window <= MAXIMUM_DUPLICATE_WINDOW)
+                        .map(Command::SetChannelDuplicateWindow)
+                        .ok_or(Error::ChannelParserError),
+                    None => ChannelDefault::parse(arguments)
+                        .map(|setting| match setting {
+                            ChannelDefault::Enabled(enabled) => Command::SetChannelEnabled(enabled),
+                            ChannelDefault::Locked(locked) => Command::SetChannelLocked(locked),
+                            ChannelDefault::DiceOnly(dice_only) => {
+                                Command::SetChannelDiceOnly(dice_only)
+                            }
+                            ChannelDefault::Prefix(prefix) => Command::SetChannelPrefix(prefix),
+                            ChannelDefault::KeepRollMessages(keep_roll_messages) => {
+                                Command::SetChannelKeepRollMessages(keep_roll_messages)
+                            }
+                            ChannelDefault::SpellingCorrection(spelling_correction) => {
+                                Command::SetChannelSpellingCorrection(spelling_correction)
+                            }
+                        })
+                        .ok_or(Error::ChannelParserError),
+                },
+            })
+        } else if let Some(captures) = SERVER_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(match arguments.to_lowercase().as_ref() {
+                "" | "status" => Ok(Command::ShowServerDefaults),
+                _ => ChannelDefault::parse(arguments)
+                    .map(Command::SetServerDefault)
+                    .ok_or(Error::ServerParserError),
+            })
+        } else if let Some(captures) = CAST_COMMAND_REGEX.captures(&command) {
+            Some(Command::cast_spell(
+                captures.get(2).map_or("", |m| m.as_str()),
+                captures.get(3).map(|m| m.as_str()),
+                captures.get(1).is_some(),
+            ))
+        } else if let Some(captures) = CLASS_COMMAND_REGEX.captures(&command) {
+            let level = captures.get(2).and_then(|m| m.as_str().parse().ok());
+            let subclass = captures
+                .get(3)
+                .map(|m| m.as_str().trim().to_owned())
+                .filter(|subclass| !subclass.is_empty());
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| Class::parse(m.as_str()))
+                    .ok_or(Error::ClassParserError)
+                    .and_then(|class| Command::set_class(class, level, subclass)),
+            )
+        } else if let Some(captures) = COMPANION_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowCompanions)
+            } else {
+                COMPANION_ARGUMENTS_REGEX
+                    .captures(arguments)
+                    .ok_or(Error::CompanionParserError)
+                    .and_then(|captures| {
+                        Command::add_companion(
+                            captures.get(1).map_or("", |m| m.as_str()),
+                            captures.get(2).map_or("", |m| m.as_str()),
+                        )
+                    })
+            })
+        } else if let Some(captures) = CONDITION_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(2).map_or("", |m| m.as_str());
+            // A duration, such as "poisoned for 3 rounds", must be at least one round
+            let (arguments, rounds) = match CONDITION_ROUNDS_REGEX.captures(arguments) {
+                Some(captures) => (
+                    captures.get(1).map_or("", |m| m.as_str()),
+                    captures
+                        .get(2)
+                        .and_then(|m| m.as_str().parse::<i32>().ok())
+                        .filter(|rounds| *rounds > 0)
+                        .map(Some),
+                ),
+                None => (arguments, Some(None)),
+            };
+            let status = Some(arguments)
+                .filter(|status| !status.is_empty())
+                .map(Status::parse);
+            Some(
+                match (captures.get(1).map(|m| m.as_str()), status, rounds) {
+                    (Some("add"), Some(Some(status)), Some(rounds)) => {
+                        Ok(Command::AddStatus(status, rounds))
+                    }
+                    (Some("remove"), Some(Some(status)), Some(None)) => {
+                        Ok(Command::RemoveStatus(status))
+                    }
+                    (None, None, Some(None)) => Ok(Command::ShowStatuses),
+                    _ => Err(Error::StatusParserError),
+                },
+            )
+        } else if let Some(captures) = CONTEST_COMMAND_REGEX.captures(&command) {
+            let contest = captures.get(1).map_or("", |m| m.as_str());
+            let arguments = captures.get(2).map_or("", |m| m.as_str()).trim();
+            Some(CONTEST_ARGUMENTS_REGEX.captures(arguments).map_or(
+                Err(Error::ContestParserError),
+                |captures| {
+                    Command::contested_check(
+                        contest,
+                        captures.get(1).map(|m| m.as_str()),
+                        captures.get(2).map_or("", |m| m.as_str()),
+                    )
+                },
+            ))
+        } else if let Some(captures) = DUNGEON_COMMAND_REGEX.captures(&command) {
+            Some(Command::generate_dungeon(
+                captures.get(1).map_or("", |m| m.as_str()),
+            ))
+        } else if let Some(captures) = SETTLEMENT_COMMAND_REGEX.captures(&command) {
+            Some(Command::generate_settlement(
+                captures.get(1).map_or("", |m| m.as_str()),
+            ))
+        } else if TAVERN_COMMAND_REGEX.is_match(&command) {
+            Some(Ok(Command::GenerateTavern))
+        } else if let Some(captures) = TRAVEL_COMMAND_REGEX.captures(&command) {
+            Some(
+                Journey::parse(captures.get(1).map_or("", |m| m.as_str()))
+                    .map(Command::PlanTravel)
+                    .ok_or(Error::TravelParserError),
+            )
+        } else if let Some(captures) = WILD_SHAPE_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowWildShapes)
+            } else if arguments.eq_ignore_ascii_case("revert") {
+                Ok(Command::RevertWildShape)
+            } else if let Some(captures) = WILD_SHAPE_ATTACK_REGEX.captures(arguments) {
+                Ok(Command::wild_shape_attack(
+                    captures.get(1).map(|m| m.as_str()),
+                    captures.get(2).map(|m| m.as_str()),
+                    captures.get(3).map(|m| m.as_str()),
+                ))
+            } else if let Some(captures) = WILD_SHAPE_ACTION_REGEX.captures(arguments) {
+                let argument = captures.get(2).map_or("", |m| m.as_str()).trim();
+                match captures
+                    .get(1)
+                    .map(|m| m.as_str().to_lowercase())
+                    .as_deref()
+                {
+                    Some("add") => Ok(Command::AddWildShapeForm(argument.to_owned())),
+                    Some("damage") => {
+                        Command::wild_shape_hit_points(argument, Command::DamageWildShape)
+                    }
+                    _ => Command::wild_shape_hit_points(argument, Command::HealWildShape),
+                }
+            } else {
+                Ok(Command::WildShape(arguments.to_owned()))
+            })
+        } else if let Some(captures) = WEATHER_COMMAND_REGEX.captures(&command) {
+            Some(Command::roll_weather(
+                captures.get(1).map_or("", |m| m.as_str()),
+            ))
+        } else if let Some(captures) = TREASURE_COMMAND_REGEX.captures(&command) {
+            Some(Command::roll_treasure(
+                captures.get(1).map_or(false, |m| m.as_str() == "hoard"),
+                captures.get(2).map_or("", |m| m.as_str()),
+            ))
+        } else if let Some(captures) = COPY_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            let (arguments, level) = match COPY_LEVEL_REGEX.captures(arguments) {
+                Some(captures) => (
+                    captures.get(1).map_or("", |m| m.as_str()),
+                    captures.get(2).map(|m| m.as_str().parse::<i32>().ok()),
+                ),
+                None => (arguments, None),
+            };
+            let (name, channel_id) = match COPY_CHANNEL_REGEX.captures(arguments) {
+                Some(captures) => (
+                    captures.get(1).map_or("", |m| m.as_str()),
+                    captures.get(2).map(|m| m.as_str().parse::<u64>().ok()),
+                ),
+                None => (arguments, None),
+            };
+            let name = Some(name.trim().to_owned()).filter(|name| !name.is_empty());
+            Some(match (level, channel_id) {
+                (Some(None), _) | (_, Some(None)) => Err(Error::CopyCharacterParserError),
+                (level, channel_id) => Command::copy_character(
+                    name,
+                    channel_id.and_then(|channel_id| channel_id.map(ChannelId)),
+                    level.and_then(identity),
+                ),
+            })
+        } else if let Some(captures) = COUNTER_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowCounters)
+            } else if let Some(captures) = COUNTER_SPEND_REGEX.captures(arguments) {
+                let amount = captures.get(2).map_or(Some(1), |m| m.as_str().parse().ok());
+                Command::spend_counter(captures.get(1).map_or("", |m| m.as_str()), amount)
+            } else if let Some(captures) = COUNTER_RESTORE_REGEX.captures(arguments) {
+                let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+                match captures.get(2).map(|m| m.as_str().parse::<i32>().ok()) {
+                    Some(None) | Some(Some(0)) => Err(Error::CounterParserError),
+                    amount => Ok(Command::RestoreCounter(
+                        name.to_owned(),
+                        amount.and_then(identity),
+                    )),
+                }
+            } else if let Some(captures) = COUNTER_REMOVE_REGEX.captures(arguments) {
+                Ok(Command::RemoveCounter(
+                    captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                ))
+            } else {
+                COUNTER_SET_REGEX
+                    .captures(arguments)
+                    .and_then(|captures| {
+                        let maximum = captures.get(2)?.as_str().parse().ok()?;
+                        let reset = captures.get(3).and_then(|m| Rest::parse(m.as_str()));
+                        Counter::new(captures.get(1)?.as_str(), maximum, reset)
+                    })
+                    .map(Command::SetCounter)
+                    .ok_or(Error::CounterParserError)
+            })
+        } else if let Some(captures) = EFFECT_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowCharacterEffects)
+            } else if arguments.eq_ignore_ascii_case("clear") {
+                Ok(Command::ClearCharacterEffects)
+            } else if let Some(captures) = EFFECT_END_REGEX.captures(arguments) {
+                Ok(Command::RemoveCharacterEffect(
+                    captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                ))
+            } else {
+                CharacterEffect::parse(arguments)
+                    .map(Command::AddCharacterEffect)
+                    .ok_or(Error::CharacterEffectParserError)
+            })
+        } else if let Some(captures) = DAMAGE_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| Damage::parse_shorthand(m.as_str()))
+                    .map(Command::DamageCombatant)
+                    .ok_or(Error::DamageParserError),
+            )
+        } else if let Some(captures) = DELETE_COMMAND_REGEX.captures(&command) {
+            let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if name.is_empty() {
+                Err(Error::DeleteCharacterMissingName)
+            } else {
+                Ok(Command::DeleteCharacter(name.to_owned()))
+            })
+        } else if let Some(captures) = DIFFICULTY_COMMAND_REGEX.captures(&command) {
+            let monsters = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if monsters.is_empty() {
+                Err(Error::DifficultyParserError)
+            } else {
+                Command::encounter_difficulty(monsters)
+            })
+        } else if let Some(captures) = DUNGEON_MASTER_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") => Ok(Command::ShowDungeonMaster),
+                Some(dungeon_master) => Command::set_dungeon_master(dungeon_master),
+            })
+        } else if let Some(captures) = EDITION_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") => Ok(Command::ShowEdition),
+                Some(edition) => Edition::parse(edition)
+                    .map(Command::SetEdition)
+                    .ok_or(Error::EditionParserError),
+            })
+        } else if let Some(captures) = HOUSE_RULES_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(match arguments.to_lowercase().as_ref() {
+                "" | "show" => Ok(Command::ShowHouseRules),
+                "reset" => Ok(Command::ResetHouseRules),
+                _ => HOUSE_RULES_SET_REGEX
+                    .captures(arguments)
+                    .and_then(|captures| HouseRule::parse(&captures[1]))
+                    .map(Command::SetHouseRule)
+                    .ok_or(Error::HouseRuleParserError),
+            })
+        } else if let Some(captures) = EXPERIENCE_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(match arguments.parse() {
+                Ok(amount) => Command::award_experience(amount, None),
+                Err(_) => match EXPERIENCE_REASON_REGEX.captures(arguments) {
+                    Some(captures) => captures
+                        .get(1)
+                        .and_then(|m| m.as_str().parse().ok())
+                        .ok_or(Error::ExperienceParserError)
+                        .and_then(|amount| {
+                            Command::award_experience(
+                                amount,
+                                captures.get(2).map(|m| m.as_str().trim().to_owned()),
+                            )
+                        }),
+                    None => COMBATANT_AMOUNT_REGEX
+                        .captures(arguments)
+                        .map_or(Err(Error::ExperienceParserError), |captures| {
+                            Command::set_combatant_experience(&captures)
+                        }),
+                },
+            })
+        } else if let Some(captures) = HEAL_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| COMBATANT_AMOUNT_REGEX.captures(m.as_str().trim()))
+                    .map_or(Err(Error::HealParserError), |captures| {
+                        Command::heal_combatant(&captures)
+                    }),
+            )
+        } else if let Some(captures) = HIT_POINTS_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| HIT_POINTS_ARGUMENTS_REGEX.captures(m.as_str().trim()))
+                    .map_or(Err(Error::HitPointsParserError), |captures| {
+                        Command::set_hit_points(&captures)
+                    }),
+            )
+        } else if let Some(captures) = INITIATIVE_BONUS_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| m.as_str().trim().parse().ok())
+                    .ok_or(Error::InitiativeBonusParserError)
+                    .and_then(Command::set_initiative_bonus),
+            )
+        } else if let Some(captures) = INITIATIVE_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") => Ok(Command::ShowInitiativeOrder),
+                Some("clear") => Ok(Command::ClearInitiativeOrder),
+                Some("end") => Ok(Command::EndEncounter),
+                Some("next") => Ok(Command::NextTurn),
+                Some("round") => Ok(Command::NextRound),
+                Some(order) => match INITIATIVE_ADD_REGEX.captures(order) {
+                    Some(captures) => Command::add_combatant(&captures),
+                    None => InitiativeOrder::parse(order)
+                        .map(Command::SetInitiativeOrder)
+                        .map_err(Error::InitiativeOrderParserError),
+                },
+            })
+        } else if let Some(captures) = GROUP_SAVE_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| GROUP_SAVE_ARGUMENTS_REGEX.captures(m.as_str().trim()))
+                    .map_or(Err(Error::GroupSavingThrowParserError), |captures| {
+                        Command::group_saving_throw(
+                            captures.get(1).map_or("", |m| m.as_str()),
+                            captures.get(2).map(|m| m.as_str()),
+                        )
+                    }),
+            )
+        } else if let Some(captures) = IMPORT_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None => Ok(Command::ImportCharacter(ImportSource::Attachment)),
+                Some(source) => DND_BEYOND_CHARACTER_REGEX
+                    .captures(source)
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|m| m.as_str().parse().ok())
+                    .map(|character_id| {
+                        Command::ImportCharacter(ImportSource::DndBeyond(character_id))
+                    })
+                    .ok_or(Error::ImportParserError),
+            })
+        } else if let Some(captures) = IMPROVISE_COMMAND_REGEX.captures(&command) {
+            Some(Command::improvise_monster(&captures))
+        } else if let Some(captures) = INSPIRE_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| MENTION_REGEX.captures(m.as_str().trim()))
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|m| m.as_str().parse().ok())
+                    .map(|user_id| Command::GrantInspiration(UserId(user_id)))
+                    .ok_or(Error::InspirationParserError),
+            )
+        } else if let Some(captures) = LIMITS_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") => Ok(Command::ShowDiceLimits),
+                Some("reset") | Some("off") => Ok(Command::SetDiceLimits(None)),
+                Some(limits) => Limits::parse(limits)
+                    .map(|limits| Command::SetDiceLimits(Some(limits)))
+                    .ok_or(Error::DiceLimitsParserError),
+            })
+        } else if let Some(captures) = LORE_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowLore)
+            } else if let Some(captures) = LORE_ADD_REGEX.captures(arguments) {
+                captures
+                    .get(1)
+                    .and_then(|m| Lore::parse(m.as_str()))
+                    .map(Command::SetLore)
+                    .ok_or(Error::LoreParserError)
+            } else if let Some(captures) = LORE_REMOVE_REGEX.captures(arguments) {
+                Ok(Command::RemoveLore(
+                    captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                ))
+            } else if let Some(captures) = LORE_REVEAL_REGEX.captures(arguments) {
+                Ok(Command::RevealLore(
+                    captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                ))
+            } else {
+                Err(Error::LoreParserError)
+            })
+        } else if let Some(captures) = NOTE_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(match NOTE_DELETE_REGEX.captures(arguments) {
+                Some(captures) => Command::delete_note(captures.get(1).map_or("", |m| m.as_str())),
+                None => Command::add_note(arguments),
+            })
+        } else if let Some(captures) = NOTES_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(Ok(Command::ShowNotes(if arguments.is_empty() {
+                None
+            } else {
+                Some(
+                    NOTES_SEARCH_REGEX
+                        .captures(arguments)
+                        .and_then(|captures| captures.get(1))
+                        .map_or(arguments, |m| m.as_str())
+                        .trim()
+                        .to_owned(),
+                )
+            })))
+        } else if let Some(captures) = OBSERVANT_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") | Some("on") => Ok(Command::SetObservant(true)),
+                Some("off") => Ok(Command::SetObservant(false)),
+                Some(_) => Err(Error::ObservantParserError),
+            })
+        } else if let Some(captures) = PASSIVE_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") | Some("all") => Ok(Command::ShowPassiveScores),
+                Some(check) => Command::show_passive_score(check),
+            })
+        } else if let Some(captures) = RANDOM_TABLE_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowRandomTables)
+            } else if let Some(captures) = RANDOM_TABLE_ADD_REGEX.captures(arguments) {
+                captures
+                    .get(1)
+                    .and_then(|m| RandomTable::parse(m.as_str()))
+                    .map(Command::SetRandomTable)
+                    .ok_or(Error::RandomTableParserError)
+            } else if let Some(captures) = RANDOM_TABLE_DELETE_REGEX.captures(arguments) {
+                Ok(Command::DeleteRandomTable(
+                    captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                ))
+            } else if let Some(captures) = RANDOM_TABLE_SHOW_REGEX.captures(arguments) {
+                Ok(Command::ShowRandomTable(
+                    captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                ))
+            } else {
+                Ok(Command::RollRandomTable(arguments.to_owned()))
+            })
+        } else if let Some(captures) = MASTERY_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowWeaponMasteries)
+            } else if let Some(captures) = MASTERY_REMOVE_REGEX.captures(arguments) {
+                let weapon = captures.get(1).map_or("", |m| m.as_str()).trim();
+                WeaponName::parse(weapon)
+                    .map(Command::RemoveWeaponMastery)
+                    .ok_or_else(|| Error::MasteryParserError(WeaponName::suggest(weapon)))
+            } else {
+                WeaponName::parse(arguments)
+                    .map(Command::AddWeaponMastery)
+                    .ok_or_else(|| Error::MasteryParserError(WeaponName::suggest(arguments)))
+            })
+        } else if let Some(captures) = MONSTER_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowMonsters)
+            } else if let Some(captures) = MONSTER_SAVE_REGEX.captures(arguments) {
+                captures
+                    .get(1)
+                    .and_then(|m| Monster::parse(m.as_str()))
+                    .map(Command::SaveMonster)
+                    .ok_or(Error::MonsterParserError)
+            } else if let Some(captures) = MONSTER_DELETE_REGEX.captures(arguments) {
+                Ok(Command::DeleteMonster(
+                    captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                ))
+            } else if let Some(captures) = MONSTER_ATTACK_REGEX.captures(arguments) {
+                Ok(Command::monster_attack(&captures))
+            } else if let Some(captures) = MONSTER_ADD_REGEX.captures(arguments) {
+                Command::add_monsters(
+                    captures.get(1).map_or("", |m| m.as_str()),
+                    captures.get(2).is_some(),
+                )
+            } else {
+                Ok(Command::ShowMonster(arguments.to_owned()))
+            })
+        } else if let Some(captures) = ONGOING_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(match ONGOING_CLEAR_REGEX.captures(arguments) {
+                Some(captures) => Ok(Command::ClearOngoingEffects(
+                    captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                )),
+                None => OngoingEffect::parse_shorthand(arguments)
+                    .map(Command::AddOngoingEffect)
+                    .ok_or(Error::OngoingEffectParserError),
+            })
+        } else if let Some(captures) = PACK_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowContentPacks)
+            } else if arguments.eq_ignore_ascii_case("install") {
+                Ok(Command::InstallContentPack)
+            } else if let Some(captures) = PACK_ACTION_REGEX.captures(arguments) {
+                let name = captures.get(2).map_or("", |m| m.as_str()).trim().to_owned();
+                match captures
+                    .get(1)
+                    .map(|m| m.as_str().to_lowercase())
+                    .as_deref()
+                {
+                    Some("enable") => Ok(Command::EnableContentPack(name)),
+                    Some("disable") => Ok(Command::DisableContentPack(name)),
+                    _ => Ok(Command::RemoveContentPack(name)),
+                }
+            } else {
+                Err(Error::ContentPackParserError)
+            })
+        } else if let Some(captures) = NAME_COMMAND_REGEX.captures(&command) {
+            let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if name.is_empty() {
+                Err(Error::SetCharacterNameMissingName)
+            } else {
+                Command::set_character_name(name.to_owned())
+            })
+        } else if let Some(captures) = PURGE_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| MENTION_REGEX.captures(m.as_str().trim()))
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|m| m.as_str().parse().ok())
+                    .map(|user_id| Command::PurgeUserFromChannel(UserId(user_id)))
+                    .ok_or(Error::PurgeParserError),
+            )
+        } else if let Some(captures) = PREGEN_COMMAND_REGEX.captures(&command) {
+            let level = captures.get(2).map_or(Some(1), |m| m.as_str().parse().ok());
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| Class::parse(m.as_str()))
+                    .and_then(|class| level.map(|level| (class, level)))
+                    .ok_or(Error::PregenParserError)
+                    .and_then(|(class, level)| Command::create_pregen(class, level)),
+            )
+        } else if let Some(captures) = PORTRAIT_COMMAND_REGEX.captures(&command) {
+            let url = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if PORTRAIT_URL_REGEX.is_match(url) {
+                Ok(Command::SetPortrait(url.to_owned()))
+            } else {
+                Err(Error::PortraitParserError)
+            })
+        } else if let Some(captures) = REGEN_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| OngoingEffect::parse_regeneration_shorthand(m.as_str()))
+                    .map(Command::AddOngoingEffect)
+                    .ok_or(Error::RegenerationParserError),
+            )
+        } else if let Some(captures) = RESISTANCE_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(2).map_or("", |m| m.as_str()).trim();
+            Some(
+                if let Some(captures) = RESISTANCE_CLEAR_REGEX.captures(arguments) {
+                    Ok(Command::ClearResistances(
+                        captures.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
+                    ))
+                } else {
+                    captures
+                        .get(1)
+                        .and_then(|m| Resistance::parse(m.as_str()))
+                        .and_then(|resistance| Resistances::parse_shorthand(arguments, resistance))
+                        .map(Command::SetResistances)
+                        .ok_or(Error::ResistanceParserError)
+                },
+            )
+        } else if let Some(captures) = REST_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .and_then(|m| Rest::parse(m.as_str()))
+                    .map(Command::Rest)
+                    .ok_or(Error::RestParserError),
+            )
+        } else if let Some(captures) = RESTRICT_COMMAND_REGEX.captures(&command) {
+            let category = captures.get(1).map(|m| m.as_str());
+            let role_id = captures.get(2).map(|m| m.as_str().parse().map(RoleId).ok());
+            let everyone = captures.get(3).is_some();
+            Some(match (category, role_id) {
+                (None, None) if !everyone => Ok(Command::ShowCommandRestrictions),
+                (Some(category), Some(Some(role_id))) => CommandCategory::parse(category)
+                    .map(|category| Command::RestrictCommands(category, Some(role_id)))
+                    .ok_or(Error::RestrictParserError),
+                (Some(category), None) if everyone => CommandCategory::parse(category)
+                    .map(|category| Command::RestrictCommands(category, None))
+                    .ok_or(Error::RestrictParserError),
+                _ => Err(Error::RestrictParserError),
+            })
+        } else if let Some(recall) = ROLL_COMMAND_REGEX
+            .captures(&command)
+            .and_then(|captures| RECALL_LORE_REGEX.captures(captures.get(1)?.as_str()))
+            .and_then(|captures| {
+                Command::recall_lore(captures.get(1)?.as_str(), captures.get(2)?.as_str())
+            })
+        {
+            Some(Ok(recall))
+        } else if let Some(captures) = ROLL_COMMAND_REGEX.captures(&command) {
+            let roll_command = captures.get(1).map_or("", |m| m.as_str());
+            let (roll_command, companion) = Command::split_companion(roll_command);
+            let (roll_command, inspiration) = Command::split_inspiration(roll_command);
+            let (roll_command, purpose) = match Purpose::split_shorthand(roll_command) {
+                Some((roll_command, purpose)) => (roll_command, Some(purpose)),
+                None => (roll_command, None),
+            };
+            let command = if roll_command.to_lowercase() == "sneak attack" {
+                Ok(Command::SneakAttackRoll)
+            } else {
+                ConditionalRoll::parse(roll_command)
+                    .map(|roll| Command::Roll(roll, purpose.clone()))
+                    .map_err(Error::RollParserError)
+                    .or_else(|_| {
+                        CharacterRoll::parse(roll_command)
+                            .map(|roll| Command::CharacterRoll(roll, purpose))
+                            .ok_or_else(|| {
+                                Error::CharacterRollParserError(CharacterRoll::suggest(
+                                    roll_command,
+                                ))
+                            })
+                    })
+            };
+            Some(Command::with_companion(
+                Command::with_inspiration(command, inspiration),
+                companion,
+            ))
+        } else if let Some(captures) = STASH_COMMAND_REGEX.captures(&command) {
+            let arguments = captures.get(1).map_or("", |m| m.as_str()).trim();
+            Some(if arguments.is_empty() {
+                Ok(Command::ShowStash)
+            } else if let Some(captures) = STASH_ACTION_REGEX.captures(arguments) {
+                let loot = captures.get(2).map_or("", |m| m.as_str());
+                match captures
+                    .get(1)
+                    .map(|m| m.as_str().to_lowercase())
+                    .as_deref()
+                {
+                    Some("take") | Some("withdraw") | Some("remove") => {
+                        Command::stash_loot(loot, Command::WithdrawFromStash)
+                    }
+                    _ => Command::stash_loot(loot, Command::DepositInStash),
+                }
+            } else {
+                Err(Error::StashParserError)
+            })
+        } else if let Some(captures) = STYLE_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") => Err(Error::FightingStyleParserError),
+                Some("off") | Some("none") => Ok(Command::SetFightingStyle(None)),
+                Some(name) => Command::fighting_style(name)
+                    .map(|fighting_style| Command::SetFightingStyle(Some(fighting_style)))
+                    .ok_or(Error::FightingStyleParserError),
+            })
+        } else if let Some(captures) = TABLE_COMMAND_REGEX.captures(&command) {
+            Some(
+                captures
+                    .get(1)
+                    .map_or(Ok(ConditionalRoll::new_unsafe(1, 20, 0, None)), |m| {
+                        ConditionalRoll::parse(m.as_str())
+                    })
+                    .map(Command::RollForTable)
+                    .map_err(Error::RollParserError),
+            )
+        } else if let Some(captures) = TIME_ZONE_COMMAND_REGEX.captures(&command) {
+            let server = captures.get(1).is_some();
+            let time_zone = match captures.get(2).map(|m| m.as_str().trim()) {
+                None | Some("") => None,
+                Some("reset") => Some(Ok(None)),
+                Some(time_zone) => Some(
+                    TimeZone::parse(time_zone)
+                        .map(Some)
+                        .ok_or(Error::TimeZoneParserError),
+                ),
+            };
+            Some(match time_zone {
+                None if server => Err(Error::TimeZoneParserError),
+                None => Ok(Command::ShowTimeZone),
+                Some(Ok(time_zone)) if server => Ok(Command::SetServerTimeZone(time_zone)),
+                Some(time_zone) => time_zone.map(Command::SetTimeZone),
+            })
+        } else if let Some(captures) = VERIFY_COMMAND_REGEX.captures(&command) {
+            Some(match captures.get(1).map(|m| m.as_str().trim()) {
+                None | Some("") => Err(Error::VerifyParserError),
+                Some(code) => Ok(Command::VerifyRoll(code.to_owned())),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn award_experience(amount: i32, reason: Option<String>) -> Result<Command, Error> {
+        if amount > 0 {
+            Ok(Command::AwardExperience(amount, reason))
+        } else {
+            Err(Error::ExperienceAmountNonPositive(amount))
+        }
+    }
+
+    /// Create a command to work out the difficulty of an encounter from a list of monsters, such as
+    /// "4 goblins and an ogre". The list is checked when the command is run, since it may include
+    /// monsters saved for the channel.
+    fn encounter_difficulty(monsters: &str) -> Result<Command, Error> {
+        let monsters = monsters.trim();
+        if monsters.is_empty() {
+            Err(Error::DifficultyParserError)
+        } else {
+            Ok(Command::EncounterDifficulty(monsters.to_owned()))
+        }
+    }
+
+    /// Create a command to make a knowledge check about a subject, if the roll is a skill check.
+    fn recall_lore(roll: &str, subject: &str) -> Option<Command> {
+        let roll = CharacterRoll::parse(&roll.trim().to_lowercase())?;
+        let subject = subject.trim();
+        match roll.check {
+            Check::Skill(_) if !subject.is_empty() => {
+                Some(Command::RecallLore(roll, subject.to_owned()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Create a command to cast a spell, with an optional spell slot level from 1st to 9th. Without
+    /// a level, a spell is cast with a slot of its own level. A ritual doesn't use a spell slot, so
+    /// it can't have a level.
+    fn cast_spell(name: &str, level: Option<&str>, ritual: bool) -> Result<Command, Error> {
+        let name = name.trim();
+        let level = match level {
+            Some(level) => Some(
+                level
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|level| *level >= 1 && *level <= 9)
+                    .ok_or(Error::CastSpellParserError)?,
+            ),
+            None => None,
+        };
+        if name.is_empty() || (ritual && level.is_some()) {
+            Err(Error::CastSpellParserError)
+        } else {
+            Ok(Command::CastSpell(name.to_owned(), level, ritual))
+        }
+    }
+
+    /// Create a command for everyone to make a saving throw against a DC, from the ability's name
+    /// or abbreviation, such as "dex".
+    fn group_saving_throw(ability: &str, dc: Option<&str>) -> Result<Command, Error> {
+        let ability = AbilityName::parse(ability).ok_or(Error::GroupSavingThrowParserError)?;
+        dc.and_then(|dc| dc.parse::<i32>().ok())
+            .filter(|dc| *dc > 0)
+            .map(|dc| Command::GroupSavingThrow(ability, dc))
+            .ok_or(Error::GroupSavingThrowParserError)
+    }
+
+    /// Create a command to spend some of a character's counter. The amount must be positive.
+    fn spend_counter(name: &str, amount: Option<i32>) -> Result<Command, Error> {
+        let name = name.trim();
+        amount
+            .filter(|amount| *amount > 0 && !name.is_empty())
+            .map(|amount| Command::SpendCounter(name.to_owned(), amount))
+            .ok_or(Error::CounterParserError)
+    }
+
+    /// Create a command to roll an attack for a saved monster from its name and an optional
+    /// advantage or disadvantage.
+    fn monster_attack(captures: &Captures) -> Command {
+        let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+        let target = captures.get(2).map(|m| m.as_str().trim().to_owned());
+        let condition = captures
+            .get(3)
+            .map(|m| match m.as_str().to_lowercase().as_str() {
+                "advantage" => Condition::Advantage,
+                _ => Condition::Disadvantage,
+            });
+        Command::MonsterAttack(name.to_owned(), condition, target)
+    }
+
+    /// Create a command for a contest between two characters from the contest, such as "grapple",
+    /// the character starting it, which is the user's own character if it isn't given, and the
+    /// character resisting it.
+    fn contested_check(
+        contest: &str,
+        attacker: Option<&str>,
+        defender: &str,
+    ) -> Result<Command, Error> {
+        let contest = Contest::parse(contest).ok_or(Error::ContestParserError)?;
+        let attacker = match attacker {
+            Some(attacker) => Some(Command::recipient(attacker).ok_or(Error::ContestParserError)?),
+            None => None,
+        };
+        let defender = Command::recipient(defender).ok_or(Error::ContestParserError)?;
+        Ok(Command::ContestedCheck(contest, attacker, defender))
+    }
+
+    /// Create a command to add monsters to the initiative order from a number and a monster's name,
+    /// such as "3 goblins", where the number is one if it isn't given.
+    fn add_monsters(monsters: &str, hidden: bool) -> Result<Command, Error> {
+        let monsters = monsters.trim();
+        let (count, name) = match monsters.find(' ') {
+            Some(index) => match monster::parse_count(&monsters[..index]) {
+                Some(count) => (count, monsters[index..].trim()),
+                None => (1, monsters),
+            },
+            None => (1, monsters),
+        };
+        if name.is_empty() || count > MAXIMUM_MONSTERS_ADDED {
+            Err(Error::AddMonstersParserError)
+        } else {
+            Ok(Command::AddMonsters(count, name.to_owned(), hidden))
+        }
+    }
+
+    /// Create a command to improvise a monster from a challenge rating and an optional role.
+    fn improvise_monster(captures: &Captures) -> Result<Command, Error> {
+        let challenge_rating = captures
+            .get(1)
+            .and_then(|m| ChallengeRating::parse(m.as_str()));
+        let role = captures.get(2).map_or(Some(MonsterRole::Standard), |m| {
+            MonsterRole::parse(m.as_str())
+        });
+        match (challenge_rating, role) {
+            (Some(challenge_rating), Some(role)) => {
+                Ok(Command::ImproviseMonster(challenge_rating, role))
+            }
+            _ => Err(Error::ImproviseParserError),
+        }
+    }
+
+    pub fn set_character_name(name: String) -> Result<Command, Error> {
+        let length = name.chars().count();
+        if length > MAXIMUM_DISPLAY_NAME_LENGTH {
+            Err(Error::SetCharacterNameTooLong(length))
+        } else {
+            Ok(Command::SetCharacterName(name))
+        }
+    }
+
+    pub fn set_armor_class(armor_class: i32) -> Result<Command, Error> {
+        if armor_class < 1 || armor_class > 30 {
+            Err(Error::SetArmorClassOutOfRange(armor_class))
+        } else {
+            Ok(Command::SetArmorClass(armor_class))
+        }
+    }
+
+    pub fn set_ability(ability: AbilityName, score: i32) -> Result<Command, Error> {
+        if score < 1 || score > 30 {
+            Err(Error::SetAbilityOutOfRange(ability, score))
+        } else {
+            Ok(Command::SetAbility(ability, score))
+        }
+    }
+
+    pub fn set_level(level: i32) -> Result<Command, Error> {
+        if level < 1 || level > 20 {
+            Err(Error::SetLevelOutOfRange(level))
+        } else {
+            Ok(Command::SetLevel(level))
+        }
+    }
+
+    pub fn set_initiative_bonus(initiative_bonus: i32) -> Result<Command, Error> {
+        if initiative_bonus < -10 || initiative_bonus > 10 {
+            Err(Error::SetInitiativeBonusOutOfRange(initiative_bonus))
+        } else {
+            Ok(Command::SetInitiativeBonus(initiative_bonus))
+        }
+    }
+
+    /// Create a command to view a character's passive score for an ability or skill, such as
+    /// "Perception". Other checks, such as saving throws, don't have passive scores.
+    fn show_passive_score(check: &str) -> Result<Command, Error> {
+        match Check::parse(check.trim()) {
+            Some(check @ Check::Ability(_)) | Some(check @ Check::Skill(_)) => {
+                Ok(Command::ShowPassiveScore(check))
+            }
+            _ => Err(Error::PassiveParserError),
+        }
+    }
+
+    /// Create a command to set a combatant's hit points from a name, a number of hit points, and
+    /// whether they're hidden from the players.
+    fn set_hit_points(captures: &Captures) -> Result<Command, Error> {
+        let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+        let hidden = captures.get(3).is_some();
+        captures
+            .get(2)
+            .and_then(|m| m.as_str().parse().ok())
+            .filter(|_| !name.is_empty())
+            .map(|hit_points| Command::SetHitPoints(name.to_owned(), hit_points, hidden))
+            .ok_or(Error::HitPointsParserError)
+    }
+
+    /// Create a command to heal a combatant from a name and a number of hit points.
+    fn heal_combatant(captures: &Captures) -> Result<Command, Error> {
+        let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+        captures
+            .get(2)
+            .and_then(|m| m.as_str().parse::<i32>().ok())
+            .filter(|amount| !name.is_empty() && *amount > 0)
+            .map(|amount| Command::HealCombatant(name.to_owned(), amount))
+            .ok_or(Error::HealParserError)
+    }
+
+    /// Create a command to add a combatant to the initiative order from a name and an initiative,
+    /// along with their hit points and whether they're hidden from the players, if they're given.
+    fn add_combatant(captures: &Captures) -> Result<Command, Error> {
+        let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+        let hit_points = match captures.get(3) {
+            Some(m) => Some(
+                m.as_str()
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|hit_points| *hit_points > 0)
+                    .ok_or(Error::AddCombatantParserError)?,
+            ),
+            None => None,
+        };
+        let hidden = captures.get(4).is_some();
+        captures
+            .get(2)
+            .and_then(|m| m.as_str().parse().ok())
+            .filter(|_| !name.is_empty())
+            .map(|initiative| {
+                Command::AddCombatant(
+                    initiative::InitiativeEntry {
+                        name: name.to_owned(),
+                        initiative,
+                        tie: None,
+                    },
+                    hit_points,
+                    hidden,
+                )
+            })
+            .ok_or(Error::AddCombatantParserError)
+    }
+
+    /// Create a command to set a combatant's armour class from a name and an armour class.
+    fn set_combatant_armor_class(captures: &Captures) -> Result<Command, Error> {
+        let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+        captures
+            .get(2)
+            .and_then(|m| m.as_str().parse().ok())
+            .filter(|armor_class| !name.is_empty() && *armor_class >= 1 && *armor_class <= 30)
+            .map(|armor_class| Command::SetCombatantArmorClass(name.to_owned(), armor_class))
+            .ok_or(Error::CombatantArmorClassParserError)
+    }
+
+    /// Create a command to set how many experience points a combatant is worth from a name and a
+    /// number of experience points.
+    fn set_combatant_experience(captures: &Captures) -> Result<Command, Error> {
+        let name = captures.get(1).map_or("", |m| m.as_str()).trim();
+        captures
+            .get(2)
+            .and_then(|m| m.as_str().parse().ok())
+            .filter(|_| !name.is_empty())
+            .map(|experience| Command::SetCombatantExperience(name.to_owned(), experience))
+            .ok_or(Error::ExperienceParserError)
+    }
+
+    pub fn set_class(
+        class: Class,
+        level: Option<i32>,
+        subclass: Option<String>,
+    ) -> Result<Command, Error> {
+        match level {
+            Some(level) if level < 1 || level > 20 => Err(Error::SetClassLevelOutOfRange(level)),
+            level => Ok(Command::SetClass(class, level, subclass)),
+        }
+    }
+
+    pub fn copy_character(
+        name: Option<String>,
+        channel_id: Option<ChannelId>,
+        level: Option<i32>,
+    ) -> Result<Command, Error> {
+        match level {
+            Some(level) if level < 1 || level > 20 => {
+                Err(Error::CopyCharacterLevelOutOfRange(level))
+            }
+            level => Ok(Command::CopyCharacter(CharacterCopy {
+                name,
+                channel_id,
+                level,
+            })),
+        }
+    }
+
+    pub fn create_pregen(class: Class, level: i32) -> Result<Command, Error> {
+        if level >= 1 && level <= 20 {
+            Ok(Command::CreatePregen(class, level))
+        } else {
+            Err(Error::CreatePregenLevelOutOfRange(level))
+        }
+    }
+}
+
+pub enum CommandResult {
+    Shorthand(Result<Command, Error>),
+    /// A natural language command, with the intent parser's result, or None if it was parsed by
+    /// the rule-based fallback, and the message after spelling correction.
+    NaturalLanguage(
+        Result<Command, Error>,
+        Option<IntentParserResult>,
+        Option<String>,
+    ),
+    /// The results for each of the commands in a message with several commands.
+    Composite(Vec<CommandResult>),
+    /// A command that was guessed for an earlier message, which the user has confirmed was what
+    /// they meant, with the ID of the message it was guessed for.
+    Confirmed(Result<Command, Error>, MessageId),
+}
+
+impl CommandResult {
+    /// Whether the command was understood, rather than being a command with invalid syntax.
+    pub fn is_understood(&self) -> bool {
+        match self {
+            CommandResult::Shorthand(command)
+            | CommandResult::NaturalLanguage(command, _, _)
+            | CommandResult::Confirmed(command, _) => command.is_ok(),
+            CommandResult::Composite(results) => results.iter().all(CommandResult::is_understood),
+        }
+    }
+
+    /// Whether every command was understood as a command that rolls dice.
+    pub fn is_rolling(&self) -> bool {
+        match self {
+            CommandResult::Shorthand(command)
+            | CommandResult::NaturalLanguage(command, _, _)
+            | CommandResult::Confirmed(command, _) => {
+                command.as_ref().map_or(false, Command::is_rolling)
+            }
+            CommandResult::Composite(results) => results.iter().all(CommandResult::is_rolling),
+        }
+    }
+
+    /// The result for a message that was rewritten before it was parsed, such as a follow-up,
+    /// logged as the text the user sent without the intents found in the rewritten message, so
+    /// that the rewritten message isn't used as training data for what the user sent.
+    pub fn unlabelled(self, original: &str) -> CommandResult {
+        match self {
+            CommandResult::Shorthand(command) => CommandResult::Shorthand(command),
+            CommandResult::NaturalLanguage(command, intent_result, _) => {
+                CommandResult::NaturalLanguage(
+                    command,
+                    intent_result.map(|mut intent_result| {
+                        intent_result.input = original.to_owned();
+                        intent_result.intent.intent_name = None;
+                        intent_result.slots.clear();
+                        intent_result
+                    }),
+                    None,
+                )
+            }
+            CommandResult::Composite(results) => CommandResult::Composite(
+                results
+                    .into_iter()
+                    .map(|result| result.unlabelled(original))
+                    .collect(),
+            ),
+            CommandResult::Confirmed(command, message_id) => {
+                CommandResult::Confirmed(command, message_id)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BOT_ID: u64 = 123;
+    const BOT_ROLE_ID: u64 = 456;
+
+    fn extract(message: &str) -> Option<String> {
+        let role_ids = [RoleId(BOT_ROLE_ID)];
+        let mentions = BotMentions {
+            user_id: Some(UserId(BOT_ID)),
+            role_ids: &role_ids,
+        };
+        Command::extract_at_message(message, &mentions, false)
+    }
+
+    #[test]
+    fn test_extract_at_message_user_mention() {
+        assert_eq!(extract("<@123> roll a d20"), Some("roll a d20".to_owned()));
+        assert_eq!(extract("<@!123> roll a d20"), Some("roll a d20".to_owned()));
+        assert_eq!(extract("<@789> roll a d20"), None);
+    }
+
+    #[test]
+    fn test_extract_at_message_role_mention() {
+        assert_eq!(extract("<@&456> roll a d20"), Some("roll a d20".to_owned()));
+        assert_eq!(extract("<@&123> roll a d20"), None);
+    }
+
+    #[test]
+    fn test_extract_at_message_name_mention() {
+        assert_eq!(
+            extract("@DungeonHelper roll a d20"),
+            Some("roll a d20".to_owned())
+        );
+        assert_eq!(
+            extract("@dungeon helper roll a d20"),
+            Some("roll a d20".to_owned())
+        );
+        assert_eq!(extract("@DungeonHelpers roll a d20"), None);
+    }
+
+    #[test]
+    fn test_extract_at_message_without_mention() {
+        assert_eq!(extract("roll a d20"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_table() {
+        fn table_roll(command: &str) -> Option<ConditionalRoll> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::RollForTable(roll))) => Some(roll),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            table_roll("!table"),
+            Some(ConditionalRoll::new_unsafe(1, 20, 0, None))
+        );
+        assert_eq!(
+            table_roll("!table 2d6"),
+            Some(ConditionalRoll::new_unsafe(2, 6, 0, None))
+        );
+        assert_eq!(table_roll("!tables"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_import() {
+        fn import_source(command: &str) -> Option<ImportSource> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::ImportCharacter(source))) => Some(source),
+                _ => None,
+            }
+        }
+        assert_eq!(import_source("!import"), Some(ImportSource::Attachment));
+        assert_eq!(
+            import_source("!import 12345678"),
+            Some(ImportSource::DndBeyond(12345678))
+        );
+        assert_eq!(
+            import_source("!import https://www.dndbeyond.com/characters/12345678/"),
+            Some(ImportSource::DndBeyond(12345678))
+        );
+        assert_eq!(
+            import_source("!import https://www.dndbeyond.com/profile/connie/characters/12345678"),
+            Some(ImportSource::DndBeyond(12345678))
+        );
+        assert_eq!(
+            import_source("!import https://example.com/connie.json"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_shorthand_class() {
+        fn class(command: &str) -> Option<(Class, Option<i32>, Option<String>)> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetClass(class, level, subclass))) => {
+                    Some((class, level, subclass))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(class("!class rogue"), Some((Class::Rogue, None, None)));
+        assert_eq!(
+            class("!class Monk Way of Shadow"),
+            Some((Class::Monk, None, Some("Way of Shadow".to_owned())))
+        );
+        assert_eq!(class("!class Rogue 3"), Some((Class::Rogue, Some(3), None)));
+        assert_eq!(
+            class("!class Fighter 2 Eldritch Knight"),
+            Some((Class::Fighter, Some(2), Some("Eldritch Knight".to_owned())))
+        );
+        assert_eq!(class("!class Rogue 0"), None);
+        assert_eq!(class("!class Artificer 3"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_copy() {
+        fn copy(command: &str) -> Option<CharacterCopy> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::CopyCharacter(copy))) => Some(copy),
+                _ => None,
+            }
+        }
+        assert_eq!(copy("!copy"), Some(CharacterCopy::default()));
+        assert_eq!(
+            copy("!copy Connie the Monk"),
+            Some(CharacterCopy {
+                name: Some("Connie the Monk".to_owned()),
+                ..CharacterCopy::default()
+            })
+        );
+        assert_eq!(
+            copy("!copy at level 10"),
+            Some(CharacterCopy {
+                level: Some(10),
+                ..CharacterCopy::default()
+            })
+        );
+        assert_eq!(
+            copy("!copy Connie from <#456> at level 10"),
+            Some(CharacterCopy {
+                name: Some("Connie".to_owned()),
+                channel_id: Some(ChannelId(456)),
+                level: Some(10),
+            })
+        );
+        assert_eq!(
+            copy("!copy from <#456> level 3"),
+            Some(CharacterCopy {
+                channel_id: Some(ChannelId(456)),
+                level: Some(3),
+                ..CharacterCopy::default()
+            })
+        );
+        assert_eq!(copy("!copy Connie at level 21"), None);
+        assert_eq!(copy("!copycat"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_pregen() {
+        fn pregen(command: &str) -> Option<(Class, i32)> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::CreatePregen(class, level))) => Some((class, level)),
+                _ => None,
+            }
+        }
+        assert_eq!(pregen("!pregen cleric 3"), Some((Class::Cleric, 3)));
+        assert_eq!(pregen("!pregen Rogue"), Some((Class::Rogue, 1)));
+        assert_eq!(pregen("!pregen cleric 21"), None);
+        assert_eq!(pregen("!pregen artificer 3"), None);
+        assert_eq!(pregen("!pregen cleric three"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_condition() {
+        fn condition(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::AddStatus(status, None))) => Some(format!("add {}", status)),
+                Some(Ok(Command::AddStatus(status, Some(rounds)))) => {
+                    Some(format!("add {} for {}", status, rounds))
+                }
+                Some(Ok(Command::RemoveStatus(status))) => Some(format!("remove {}", status)),
+                Some(Ok(Command::ShowStatuses)) => Some("show".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(condition("!condition"), Some("show".to_owned()));
+        assert_eq!(condition("!conditions"), Some("show".to_owned()));
+        assert_eq!(
+            condition("!condition add poisoned"),
+            Some("add Poisoned".to_owned())
+        );
+        assert_eq!(
+            condition("!condition remove Prone"),
+            Some("remove Prone".to_owned())
+        );
+        assert_eq!(condition("!condition add exhausted"), None);
+        assert_eq!(
+            condition("!condition add poisoned for 3 rounds"),
+            Some("add Poisoned for 3".to_owned())
+        );
+        assert_eq!(condition("!condition add poisoned for 0 rounds"), None);
+        assert_eq!(condition("!condition remove poisoned for 3 rounds"), None);
+        assert_eq!(condition("!condition poisoned"), None);
+        assert_eq!(condition("!condition add"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_inspiration() {
+        fn inspiration(command: &str) -> Option<Option<Condition>> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::UseInspiration(command))) => match *command {
+                    Command::Roll(roll, _) => Some(roll.condition()),
+                    Command::CharacterRoll(roll, _) => Some(roll.condition),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        assert_eq!(
+            inspiration("!r perception using inspiration"),
+            Some(Some(Condition::Advantage))
+        );
+        assert_eq!(
+            inspiration("!r 1d20+3 with disadvantage with inspiration"),
+            Some(None)
+        );
+        assert_eq!(inspiration("!r 2d6 using inspiration"), None);
+        assert_eq!(inspiration("!r perception"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_grant_inspiration() {
+        fn grant(command: &str) -> Option<UserId> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::GrantInspiration(user_id))) => Some(user_id),
+                _ => None,
+            }
+        }
+        assert_eq!(grant("!inspire <@!456>"), Some(UserId(456)));
+        assert_eq!(grant("!inspire <@456>"), Some(UserId(456)));
+        assert_eq!(grant("!inspire Alice"), None);
+        assert_eq!(grant("!inspire"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_bardic_inspiration() {
+        fn grant(command: &str) -> Option<Recipient> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::GrantBardicInspiration(recipient))) => Some(recipient),
+                _ => None,
+            }
+        }
+        assert_eq!(grant("!bardic <@!456>"), Some(Recipient::User(UserId(456))));
+        assert_eq!(
+            grant("!bardic Connie"),
+            Some(Recipient::Character("Connie".to_owned()))
+        );
+        assert_eq!(grant("!bardic"), None);
+
+        fn bardic(command: &str) -> Option<Option<Condition>> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::UseBardicInspiration(command))) => match *command {
+                    Command::Roll(roll, _) => Some(roll.condition()),
+                    Command::CharacterRoll(roll, _) => Some(roll.condition),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        assert_eq!(bardic("!r perception using bardic inspiration"), Some(None));
+        assert_eq!(
+            bardic("!r 1d20+3 with advantage with my bardic inspiration"),
+            Some(Some(Condition::Advantage))
+        );
+        assert_eq!(bardic("!r 2d6 using bardic inspiration"), None);
+        assert_eq!(bardic("!r perception using inspiration"), None);
+    }
+
+    #[test]
+    fn test_parse_bardic_inspiration() {
+        fn grant(content: &str) -> Option<Recipient> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_bardic_inspiration(content, &mentions, false) {
+                Some(Ok(Command::GrantBardicInspiration(recipient))) => Some(recipient),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            grant("<@123> Give Connie bardic inspiration"),
+            Some(Recipient::Character("Connie".to_owned()))
+        );
+        assert_eq!(
+            grant("<@123> give <@456> a bardic inspiration die."),
+            Some(Recipient::User(UserId(456)))
+        );
+        assert_eq!(
+            grant("<@123> Grant bardic inspiration to Connie the Monk"),
+            Some(Recipient::Character("Connie the Monk".to_owned()))
+        );
+        assert_eq!(grant("<@123> Give <@456> inspiration"), None);
+    }
+
+    #[test]
+    fn test_parse_treasure() {
+        fn treasure(content: &str) -> Option<Result<Treasure, ()>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_treasure(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::RollTreasure(treasure))) => Some(Ok(treasure)),
+                Some(Err(Error::TreasureParserError)) => Some(Err(())),
+                _ => None,
+            }
+        }
+        let hoard = |tier| {
+            Some(Ok(Treasure {
+                kind: TreasureKind::Hoard,
+                tier,
+            }))
+        };
+        let individual = |tier| {
+            Some(Ok(Treasure {
+                kind: TreasureKind::Individual,
+                tier,
+            }))
+        };
+        assert_eq!(
+            treasure("<@123> Roll treasure for CR 5-10 hoard"),
+            hoard(TreasureTier::Challenge5To10)
+        );
+        assert_eq!(
+            treasure("<@123> roll a treasure hoard for CR 12."),
+            hoard(TreasureTier::Challenge11To16)
+        );
+        assert_eq!(
+            treasure("<@123> Roll individual treasure for challenge rating 3"),
+            individual(TreasureTier::Challenge0To4)
+        );
+        assert_eq!(
+            treasure("!treasure hoard 17+"),
+            hoard(TreasureTier::Challenge17Plus)
+        );
+        assert_eq!(
+            treasure("!treasure 7"),
+            individual(TreasureTier::Challenge5To10)
+        );
+        assert_eq!(treasure("!treasure hoard"), Some(Err(())));
+        assert_eq!(treasure("<@123> Roll treasure for CR 3-10"), Some(Err(())));
+        assert_eq!(treasure("Roll treasure for CR 5"), None);
+    }
+
+    #[test]
+    fn test_parse_dungeon() {
+        fn dungeon(content: &str) -> Option<Result<usize, ()>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_dungeon(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::GenerateDungeon(rooms))) => Some(Ok(rooms)),
+                Some(Err(Error::DungeonParserError)) => Some(Err(())),
+                _ => None,
+            }
+        }
+        assert_eq!(dungeon("<@123> Generate a dungeon room"), Some(Ok(1)));
+        assert_eq!(
+            dungeon("<@123> generate a random dungeon with 4 rooms."),
+            Some(Ok(4))
+        );
+        assert_eq!(dungeon("<@123> Make a dungeon"), Some(Ok(3)));
+        assert_eq!(dungeon("!dungeon"), Some(Ok(3)));
+        assert_eq!(dungeon("!dungeon room"), Some(Ok(1)));
+        assert_eq!(dungeon("!dungeon 5 rooms"), Some(Ok(5)));
+        assert_eq!(dungeon("!dungeon 6"), Some(Err(())));
+        assert_eq!(
+            dungeon("<@123> Generate a dungeon with many rooms"),
+            Some(Err(()))
+        );
+        assert_eq!(dungeon("Generate a dungeon"), None);
+    }
+
+    #[test]
+    fn test_parse_settlement() {
+        fn settlement(content: &str) -> Option<&'static str> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_settlement(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::GenerateTavern)) => Some("tavern"),
+                Some(Ok(Command::GenerateSettlement(None))) => Some("settlement"),
+                Some(Ok(Command::GenerateSettlement(Some(size)))) => Some(size.as_str()),
+                Some(Err(Error::SettlementParserError)) => Some("error"),
+                _ => None,
+            }
+        }
+        assert_eq!(settlement("<@123> Generate a tavern"), Some("tavern"));
+        assert_eq!(settlement("!tavern"), Some("tavern"));
+        assert_eq!(settlement("<@123> make a random town."), Some("town"));
+        assert_eq!(settlement("<@123> Create a settlement"), Some("settlement"));
+        assert_eq!(settlement("!settlement"), Some("settlement"));
+        assert_eq!(settlement("!settlement hamlet"), Some("village"));
+        assert_eq!(settlement("!settlement castle"), Some("error"));
+        assert_eq!(settlement("Generate a tavern"), None);
+    }
+
+    #[test]
+    fn test_parse_note() {
+        fn note(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_note(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::AddNote(text))) => Some(format!("add {}", text)),
+                Some(Ok(Command::DeleteNote(id))) => Some(format!("delete {}", id)),
+                Some(Ok(Command::ShowNotes(None))) => Some("show".to_owned()),
+                Some(Ok(Command::ShowNotes(Some(search)))) => Some(format!("search {}", search)),
+                Some(Err(Error::NoteParserError)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            note("<@123> Note: the duke is a vampire"),
+            Some("add the duke is a vampire".to_owned())
+        );
+        assert_eq!(
+            note("<@123> note that we owe Gundren 10 gp"),
+            Some("add we owe Gundren 10 gp".to_owned())
+        );
+        assert_eq!(
+            note("!note Session 3\nWe found the map"),
+            Some("add Session 3\nWe found the map".to_owned())
+        );
+        assert_eq!(note("!note"), Some("error".to_owned()));
+        assert_eq!(
+            note(&format!("!note {}", "a".repeat(1001))),
+            Some("error".to_owned())
+        );
+        assert_eq!(note("<@123> Show the notes."), Some("show".to_owned()));
+        assert_eq!(note("!notes"), Some("show".to_owned()));
+        assert_eq!(
+            note("<@123> Search the notes for duke?"),
+            Some("search duke".to_owned())
+        );
+        assert_eq!(note("!notes search duke"), Some("search duke".to_owned()));
+        assert_eq!(note("!notes duke"), Some("search duke".to_owned()));
+        assert_eq!(note("<@123> Delete note #3"), Some("delete 3".to_owned()));
+        assert_eq!(note("!note delete 3"), Some("delete 3".to_owned()));
+        assert_eq!(note("<@123> delete note three"), Some("error".to_owned()));
+        assert_eq!(note("Note: the duke is a vampire"), None);
+    }
+
+    #[test]
+    fn test_parse_companion() {
+        fn companion(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_companion(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::AddCompanion(kind, name))) => Some(format!("{} {}", kind, name)),
+                Some(Ok(Command::AsCompanion(name, command))) => {
+                    Some(format!("{}: {}", name, command.description()))
+                }
+                Some(Ok(Command::ShowCompanions)) => Some("show".to_owned()),
+                Some(Err(Error::CompanionParserError)) => Some("error".to_owned()),
+                Some(Err(Error::CompanionNotCharacterRoll)) => Some("not a roll".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            companion("<@123> My familiar is named Hoot."),
+            Some("Familiar Hoot".to_owned())
+        );
+        assert_eq!(
+            companion("<@123> I have a steel defender called Clank"),
+            Some("Steel defender Clank".to_owned())
+        );
+        assert_eq!(
+            companion("<@123> my primal  companion's name is Fang"),
+            Some("Beast companion Fang".to_owned())
+        );
+        assert_eq!(
+            companion("<@123> Show my companions"),
+            Some("show".to_owned())
+        );
+        assert_eq!(companion("!companions"), Some("show".to_owned()));
+        assert_eq!(
+            companion("!companion familiar Hoot"),
+            Some("Familiar Hoot".to_owned())
+        );
+        assert_eq!(companion("!companion pet Rocky"), Some("error".to_owned()));
+        assert_eq!(
+            companion("!r perception as my familiar"),
+            Some("familiar: perform a character roll".to_owned())
+        );
+        assert_eq!(
+            companion("!r stealth as companion Hoot"),
+            Some("Hoot: perform a character roll".to_owned())
+        );
+        assert_eq!(
+            companion("!r 1d20 as my familiar"),
+            Some("not a roll".to_owned())
+        );
+        assert_eq!(companion("My familiar is Hoot"), None);
+    }
+
+    #[test]
+    fn test_parse_stash() {
+        fn stash(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_stash(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::DepositInStash(loot))) => Some(format!("deposit {}", loot)),
+                Some(Ok(Command::WithdrawFromStash(loot))) => Some(format!("withdraw {}", loot)),
+                Some(Ok(Command::ShowStash)) => Some("show".to_owned()),
+                Some(Err(Error::StashParserError)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            stash("<@123> Put 150 gp in the party stash."),
+            Some("deposit 150 gp".to_owned())
+        );
+        assert_eq!(
+            stash("<@123> add 3 potions of healing to our stash"),
+            Some("deposit 3 potions of healing".to_owned())
+        );
+        assert_eq!(
+            stash("<@123> Take the silver mirror out of the party's stash"),
+            Some("withdraw silver mirror".to_owned())
+        );
+        assert_eq!(
+            stash("<@123> What's in the party stash?"),
+            Some("show".to_owned())
+        );
+        assert_eq!(stash("!stash"), Some("show".to_owned()));
+        assert_eq!(stash("!stash add a rope"), Some("deposit rope".to_owned()));
+        assert_eq!(
+            stash("!stash take 50 gp"),
+            Some("withdraw 50 gp".to_owned())
+        );
+        assert_eq!(stash("!stash take 0 gp"), Some("error".to_owned()));
+        assert_eq!(stash("!stash sell rope"), Some("error".to_owned()));
+        assert_eq!(stash("Put 150 gp in the party stash."), None);
+    }
+
+    #[test]
+    fn test_parse_wild_shape() {
+        fn wild_shape(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_wild_shape(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::AddWildShapeForm(form))) => Some(format!("add {}", form)),
+                Some(Ok(Command::WildShape(form))) => Some(format!("shape {}", form)),
+                Some(Ok(Command::WildShapeAttack(attack, condition, target))) => Some(format!(
+                    "attack {:?} {:?} {:?}",
+                    attack,
+                    target,
+                    condition.is_some()
+                )),
+                Some(Ok(Command::DamageWildShape(amount))) => Some(format!("damage {}", amount)),
+                Some(Ok(Command::HealWildShape(amount))) => Some(format!("heal {}", amount)),
+                Some(Ok(Command::RevertWildShape)) => Some("revert".to_owned()),
+                Some(Ok(Command::ShowWildShapes)) => Some("show".to_owned()),
+                Some(Err(Error::WildShapeParserError)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            wild_shape("<@123> Add brown bear to my wild shapes."),
+            Some("add brown bear".to_owned())
+        );
+        assert_eq!(
+            wild_shape("<@123> I wild shape into a brown bear!"),
+            Some("shape brown bear".to_owned())
+        );
+        assert_eq!(
+            wild_shape("<@123> Attack the goblin with my claws in wild shape"),
+            Some("attack Some(\"claws\") Some(\"goblin\") false".to_owned())
+        );
+        assert_eq!(
+            wild_shape("<@123> My wild shape takes 12 damage"),
+            Some("damage 12".to_owned())
+        );
+        assert_eq!(
+            wild_shape("<@123> Revert to my normal form."),
+            Some("revert".to_owned())
+        );
+        assert_eq!(
+            wild_shape("<@123> Show my wild shapes"),
+            Some("show".to_owned())
+        );
+        assert_eq!(wild_shape("!wildshape"), Some("show".to_owned()));
+        assert_eq!(
+            wild_shape("!wildshape add wolf"),
+            Some("add wolf".to_owned())
+        );
+        assert_eq!(
+            wild_shape("!wildshape brown bear"),
+            Some("shape brown bear".to_owned())
+        );
+        assert_eq!(
+            wild_shape("!wildshape attack with advantage"),
+            Some("attack None None true".to_owned())
+        );
+        assert_eq!(
+            wild_shape("!wildshape attack bite vs goblin A"),
+            Some("attack Some(\"bite\") Some(\"goblin A\") false".to_owned())
+        );
+        assert_eq!(wild_shape("!wildshape heal 5"), Some("heal 5".to_owned()));
+        assert_eq!(wild_shape("!wildshape damage 0"), Some("error".to_owned()));
+        assert_eq!(wild_shape("!wildshape revert"), Some("revert".to_owned()));
+        assert_eq!(wild_shape("Wild shape into a brown bear"), None);
+    }
+
+    #[test]
+    fn test_parse_channel() {
+        fn channel(content: &str) -> Option<String> {
+            match Command::parse_shorthand(content) {
+                Some(Ok(Command::SetChannelDiceOnly(dice_only))) => {
+                    Some(format!("dice only {}", dice_only))
+                }
+                Some(Ok(Command::SetChannelEnabled(enabled))) => {
+                    Some(format!("enabled {}", enabled))
+                }
+                Some(Ok(Command::SetChannelLocked(locked))) => Some(format!("locked {}", locked)),
+                Some(Ok(Command::SetChannelDuplicateWindow(duplicate_window))) => {
+                    Some(format!("duplicate window {}", duplicate_window.as_secs()))
+                }
+                Some(Ok(Command::SetChannelKeepRollMessages(keep_roll_messages))) => {
+                    Some(format!("keep rolls {}", keep_roll_messages))
+                }
+                Some(Ok(Command::SetChannelSpellingCorrection(spelling_correction))) => {
+                    Some(format!("spelling {}", spelling_correction))
+                }
+                Some(Ok(Command::SetChannelPrefix(prefix))) => Some(format!("prefix {}", prefix)),
+                Some(Ok(Command::SetServerDefault(setting))) => Some(format!("server {}", setting)),
+                Some(Ok(Command::ShowChannelSettings)) => Some("status".to_owned()),
+                Some(Ok(Command::ExportChannelSettings)) => Some("export".to_owned()),
+                Some(Ok(Command::ImportChannelSettings)) => Some("import".to_owned()),
+                Some(Ok(Command::ShowServerDefaults)) => Some("server status".to_owned()),
+                Some(Err(Error::ChannelParserError)) | Some(Err(Error::ServerParserError)) => {
+                    Some("error".to_owned())
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(channel("!channel"), Some("status".to_owned()));
+        assert_eq!(channel("!channel status"), Some("status".to_owned()));
+        assert_eq!(channel("!channel export"), Some("export".to_owned()));
+        assert_eq!(channel("!channel import"), Some("import".to_owned()));
+        assert_eq!(channel("!channel enable"), Some("enabled true".to_owned()));
+        assert_eq!(
+            channel("!channel disable"),
+            Some("enabled false".to_owned())
+        );
+        assert_eq!(channel("!channel lock"), Some("locked true".to_owned()));
+        assert_eq!(channel("!channel unlock"), Some("locked false".to_owned()));
+        assert_eq!(
+            channel("!channel dice-only"),
+            Some("dice only true".to_owned())
+        );
+        assert_eq!(
+            channel("!channel dice only off"),
+            Some("dice only false".to_owned())
+        );
+        assert_eq!(
+            channel("!channel keep-rolls"),
+            Some("keep rolls true".to_owned())
+        );
+        assert_eq!(
+            channel("!channel spelling off"),
+            Some("spelling false".to_owned())
+        );
+        assert_eq!(
+            channel("!channel duplicate-window 5"),
+            Some("duplicate window 5".to_owned())
+        );
+        assert_eq!(
+            channel("!channel duplicate window 0 seconds"),
+            Some("duplicate window 0".to_owned())
+        );
+        assert_eq!(
+            channel("!channel duplicate-window 3600"),
+            Some("error".to_owned())
+        );
+        assert_eq!(
+            channel("!channel duplicate-window soon"),
+            Some("error".to_owned())
+        );
+        assert_eq!(channel("!channel prefix ?"), Some("prefix ?".to_owned()));
+        assert_eq!(channel("!channel prefix dh"), Some("error".to_owned()));
+        assert_eq!(channel("!channel shutdown"), Some("error".to_owned()));
+        assert_eq!(channel("!server"), Some("server status".to_owned()));
+        assert_eq!(
+            channel("!server lock"),
+            Some("server characters locked".to_owned())
+        );
+        assert_eq!(
+            channel("!server prefix $"),
+            Some("server the prefix $".to_owned())
+        );
+        assert_eq!(Command::SetChannelLocked(true).is_admin(), true);
+        assert_eq!(Command::ShowChannelSettings.is_admin(), false);
+    }
+
+    #[test]
+    fn test_apply_prefix() {
+        assert_eq!(
+            Command::apply_prefix("!r d20", "!"),
+            Some("!r d20".to_owned())
+        );
+        assert_eq!(
+            Command::apply_prefix("?ac 16; ?r stealth", "?"),
+            Some("!ac 16; !r stealth".to_owned())
+        );
+        assert_eq!(
+            Command::apply_prefix("$$r d20 then $$r d4", "$$"),
+            Some("!r d20 then !r d4".to_owned())
+        );
+        assert_eq!(
+            Command::apply_prefix("?r d20 and ?r d6, and then ?r d4", "?"),
+            Some("!r d20 and !r d6, and then !r d4".to_owned())
+        );
+        assert_eq!(Command::apply_prefix("!r d20", "?"), None);
+        assert_eq!(
+            Command::apply_prefix("<@123> roll stealth", "?"),
+            Some("<@123> roll stealth".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_restrict() {
+        fn restrict(content: &str) -> Option<String> {
+            match Command::parse_shorthand(content) {
+                Some(Ok(Command::RestrictCommands(category, Some(role_id)))) => {
+                    Some(format!("{} {}", category, role_id))
+                }
+                Some(Ok(Command::RestrictCommands(category, None))) => {
+                    Some(format!("{} everyone", category))
+                }
+                Some(Ok(Command::ShowCommandRestrictions)) => Some("show".to_owned()),
+                Some(Err(Error::RestrictParserError)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(restrict("!restrict"), Some("show".to_owned()));
+        assert_eq!(
+            restrict("!restrict editing <@&456>"),
+            Some("editing 456".to_owned())
+        );
+        assert_eq!(
+            restrict("!restrict dm tools to <@&456>"),
+            Some("DM 456".to_owned())
+        );
+        assert_eq!(
+            restrict("!restrict rolling everyone"),
+            Some("rolling everyone".to_owned())
+        );
+        assert_eq!(restrict("!restrict editing"), Some("error".to_owned()));
+        assert_eq!(
+            restrict("!restrict jumping <@&456>"),
+            Some("error".to_owned())
+        );
+        assert_eq!(
+            restrict("!restrict admin <@&456>"),
+            Some("error".to_owned())
+        );
+        assert_eq!(Command::SetArmorClass(16).is_rolling(), false);
+        assert_eq!(
+            Command::AwardExperience(300, None).is_in_category(CommandCategory::DungeonMaster),
+            true
+        );
+    }
+
+    #[test]
+    fn test_is_allowed_for() {
+        let add_monsters = Command::AddMonsters(2, "goblin".to_owned(), false);
+        let monster_attack = Command::MonsterAttack("goblin A".to_owned(), None, None);
+        assert_eq!(add_monsters.is_allowed_for(true, false), true);
+        assert_eq!(add_monsters.is_allowed_for(false, false), false);
+        assert_eq!(add_monsters.is_allowed_for(false, true), false);
+        assert_eq!(monster_attack.is_allowed_for(false, false), false);
+        assert_eq!(
+            Command::Composite(vec![Command::ShowCharacterSheet, monster_attack])
+                .is_allowed_for(false, false),
+            false
+        );
+        assert_eq!(
+            Command::SetDungeonMaster(None).is_allowed_for(false, true),
+            true
+        );
+        assert_eq!(
+            Command::SetDungeonMaster(None).is_allowed_for(false, false),
+            false
+        );
+        assert_eq!(
+            Command::ShowCharacterSheet.is_allowed_for(false, false),
+            true
+        );
+    }
+
+    #[test]
+    fn test_is_editing() {
+        assert_eq!(Command::SetArmorClass(16).is_editing(), true);
+        assert_eq!(Command::ShowCharacterSheet.is_editing(), false);
+        assert_eq!(
+            Command::SpendCounter("Ki".to_owned(), 1).is_editing(),
+            false
+        );
+        assert_eq!(
+            Command::Composite(vec![Command::ShowCharacterSheet, Command::SetLevel(5)])
+                .is_editing(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_parse_weather() {
+        fn weather(content: &str) -> Option<Result<Option<Season>, ()>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_weather(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::RollWeather(season))) => Some(Ok(season)),
+                Some(Err(Error::WeatherParserError)) => Some(Err(())),
+                _ => None,
+            }
+        }
+        assert_eq!(weather("<@123> Roll the weather"), Some(Ok(None)));
+        assert_eq!(
+            weather("<@123> What's the weather like today in winter?"),
+            Some(Ok(Some(Season::Winter)))
+        );
+        assert_eq!(weather("!weather fall"), Some(Ok(Some(Season::Autumn))));
+        assert_eq!(weather("!weather"), Some(Ok(None)));
+        assert_eq!(weather("!weather monsoon"), Some(Err(())));
+        assert_eq!(weather("Roll the weather"), None);
+    }
+
+    #[test]
+    fn test_parse_travel() {
+        fn travel(content: &str) -> Option<Result<Journey, ()>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_travel(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::PlanTravel(journey))) => Some(Ok(journey)),
+                Some(Err(Error::TravelParserError)) => Some(Err(())),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            travel("<@123> We travel 50 miles at a fast pace."),
+            Some(Ok(Journey {
+                pace: crate::travel::Pace::Fast,
+                miles: Some(50),
+                hours: None,
+                difficult_terrain: false,
+            }))
+        );
+        assert_eq!(
+            travel("<@123> How far can we travel in 10 hours through difficult terrain?")
+                .and_then(Result::ok)
+                .map(|journey| (journey.hours, journey.difficult_terrain)),
+            Some((Some(10), true))
+        );
+        assert_eq!(
+            travel("!travel slow 60 miles")
+                .and_then(Result::ok)
+                .map(|journey| journey.miles),
+            Some(Some(60))
+        );
+        assert_eq!(travel("!travel sideways"), Some(Err(())));
+        assert_eq!(travel("<@123> We travel to Neverwinter"), None);
+    }
+
+    #[test]
+    fn test_parse_random_table() {
+        fn table(content: &str) -> Option<Result<String, ()>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_random_table(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::RollRandomTable(name))) => Some(Ok(format!("roll {}", name))),
+                Some(Ok(Command::SetRandomTable(table))) => {
+                    Some(Ok(format!("add {} {}", table.name, table.total_weight())))
+                }
+                Some(Ok(Command::DeleteRandomTable(name))) => Some(Ok(format!("delete {}", name))),
+                Some(Ok(Command::ShowRandomTable(name))) => Some(Ok(format!("show {}", name))),
+                Some(Ok(Command::ShowRandomTables)) => Some(Ok("list".to_owned())),
+                Some(Err(Error::RandomTableParserError)) => Some(Err(())),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            table("<@123> Roll on the Wild Magic table."),
+            Some(Ok("roll Wild Magic".to_owned()))
+        );
+        assert_eq!(
+            table("<@123> roll on forest encounters"),
+            Some(Ok("roll forest encounters".to_owned()))
+        );
+        assert_eq!(
+            table("!rtable add Forest Encounters: 3x 1d4 goblins; 2x wolves\n{Fey Encounters}"),
+            Some(Ok("add Forest Encounters 6".to_owned()))
+        );
+        assert_eq!(
+            table("!rtable Wild Magic"),
+            Some(Ok("roll Wild Magic".to_owned()))
+        );
+        assert_eq!(
+            table("!rtable show Wild Magic"),
+            Some(Ok("show Wild Magic".to_owned()))
+        );
+        assert_eq!(
+            table("!rtable delete Wild Magic"),
+            Some(Ok("delete Wild Magic".to_owned()))
+        );
+        assert_eq!(table("!rtables"), Some(Ok("list".to_owned())));
+        assert_eq!(table("!rtable add Wild Magic"), Some(Err(())));
+        assert_eq!(table("Roll on the Wild Magic table"), None);
+    }
+
+    #[test]
+    fn test_parse_contest() {
+        fn contest(content: &str) -> Option<(Contest, Option<Recipient>, Recipient)> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_contest(content, &mentions, false) {
+                Some(Ok(Command::ContestedCheck(contest, attacker, defender))) => {
+                    Some((contest, attacker, defender))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            contest("<@123> Connie grapples Tocha."),
+            Some((
+                Contest::Grapple,
+                Some(Recipient::Character("Connie".to_owned())),
+                Recipient::Character("Tocha".to_owned())
+            ))
+        );
+        assert_eq!(
+            contest("<@123> I shove <@456>!"),
+            Some((Contest::Shove, None, Recipient::User(UserId(456))))
+        );
+        assert_eq!(contest("<@123> Connie hugs Tocha"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_contest() {
+        fn contest(command: &str) -> Option<(Contest, Option<Recipient>, Recipient)> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::ContestedCheck(contest, attacker, defender))) => {
+                    Some((contest, attacker, defender))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            contest("!grapple Tocha"),
+            Some((
+                Contest::Grapple,
+                None,
+                Recipient::Character("Tocha".to_owned())
+            ))
+        );
+        assert_eq!(
+            contest("!shove Connie vs <@!456>"),
+            Some((
+                Contest::Shove,
+                Some(Recipient::Character("Connie".to_owned())),
+                Recipient::User(UserId(456))
+            ))
+        );
+        assert_eq!(contest("!grapple"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_experience() {
+        fn experience(command: &str) -> Option<i32> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::AwardExperience(amount, _))) => Some(amount),
+                _ => None,
+            }
+        }
+        assert_eq!(experience("!xp 300"), Some(300));
+        assert_eq!(experience("!xp 1200 for the ogre fight"), Some(1200));
+        assert_eq!(experience("!xp -5 for nothing"), None);
+        assert_eq!(experience("!xp"), None);
+        assert_eq!(experience("!xp 0"), None);
+        assert_eq!(experience("!xp lots"), None);
+    }
+
+    #[test]
+    fn test_parse_award_experience() {
+        fn experience(content: &str) -> Option<Result<(i32, Option<String>), ()>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_award_experience(content, &mentions, false) {
+                Some(Ok(Command::AwardExperience(amount, reason))) => Some(Ok((amount, reason))),
+                Some(Err(_)) => Some(Err(())),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            experience("<@123> award 1200 xp for the ogre fight"),
+            Some(Ok((1200, Some("the ogre fight".to_owned()))))
+        );
+        assert_eq!(
+            experience("<@123> Give the party 300 experience points for rescuing Sildar."),
+            Some(Ok((300, Some("rescuing Sildar".to_owned()))))
+        );
+        assert_eq!(
+            experience("<@123> award 0 xp for the ogre fight"),
+            Some(Err(()))
+        );
+        assert_eq!(experience("<@123> award 1200 xp"), None);
+        assert_eq!(experience("award 1200 xp for the ogre fight"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_encounter() {
+        fn encounter(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetCombatantExperience(name, experience))) => {
+                    Some(format!("{}: {} XP", name, experience))
+                }
+                Some(Ok(Command::EndEncounter)) => Some("end".to_owned()),
+                Some(Ok(Command::NextTurn)) => Some("next".to_owned()),
+                Some(Ok(Command::NextRound)) => Some("round".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            encounter("!xp goblin A 50"),
+            Some("goblin A: 50 XP".to_owned())
+        );
+        assert_eq!(encounter("!xp 50"), None);
+        assert_eq!(encounter("!init end"), Some("end".to_owned()));
+        assert_eq!(encounter("!init next"), Some("next".to_owned()));
+        assert_eq!(encounter("!init round"), Some("round".to_owned()));
+    }
+
+    #[test]
+    fn test_parse_shorthand_difficulty() {
+        fn difficulty(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::EncounterDifficulty(monsters))) => Some(monsters),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            difficulty("!difficulty 4 goblins, 1 CR 3"),
+            Some("4 goblins, 1 CR 3".to_owned())
+        );
+        assert_eq!(difficulty("!difficulty"), None);
+    }
+
+    #[test]
+    fn test_parse_encounter_difficulty() {
+        fn difficulty(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_encounter_difficulty(content, &mentions, false) {
+                Some(Ok(Command::EncounterDifficulty(monsters))) => Some(monsters),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            difficulty("<@123> how hard is 4 goblins and an ogre for us?"),
+            Some("4 goblins and an ogre".to_owned())
+        );
+        assert_eq!(
+            difficulty("<@123> How deadly would 3 trolls be?"),
+            Some("3 trolls".to_owned())
+        );
+        assert_eq!(
+            difficulty("how hard is 4 goblins and an ogre for us?"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_shorthand_improvise() {
+        fn improvise(command: &str) -> Option<(ChallengeRating, MonsterRole)> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::ImproviseMonster(challenge_rating, role))) => {
+                    Some((challenge_rating, role))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            improvise("!improvise 3 brute"),
+            Some((ChallengeRating::Whole(3), MonsterRole::Brute))
+        );
+        assert_eq!(
+            improvise("!improvise CR 1/2"),
+            Some((ChallengeRating::Half, MonsterRole::Standard))
+        );
+        assert_eq!(improvise("!improvise"), None);
+        assert_eq!(improvise("!improvise 31"), None);
+        assert_eq!(improvise("!improvise 3 wizard"), None);
+    }
+
+    #[test]
+    fn test_parse_improvise_monster() {
+        fn improvise(content: &str) -> Option<(ChallengeRating, MonsterRole)> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_improvise_monster(content, &mentions, false) {
+                Some(Ok(Command::ImproviseMonster(challenge_rating, role))) => {
+                    Some((challenge_rating, role))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            improvise("<@123> Improvise a CR 3 brute"),
+            Some((ChallengeRating::Whole(3), MonsterRole::Brute))
+        );
+        assert_eq!(
+            improvise("<@123> quick stats for a CR 1/4 monster."),
+            Some((ChallengeRating::Quarter, MonsterRole::Standard))
+        );
+        assert_eq!(improvise("Improvise a CR 3 brute"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_monster() {
+        fn monster(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SaveMonster(monster))) => Some(format!(
+                    "save {} CR {}",
+                    monster.name, monster.challenge_rating
+                )),
+                Some(Ok(Command::ShowMonster(name))) => Some(format!("show {}", name)),
+                Some(Ok(Command::ShowMonsters)) => Some("show all".to_owned()),
+                Some(Ok(Command::DeleteMonster(name))) => Some(format!("delete {}", name)),
+                Some(Ok(Command::MonsterAttack(name, condition, target))) => {
+                    Some(format!("attack {} {:?} {:?}", name, condition, target))
+                }
+                Some(Ok(Command::AddMonsters(count, name, hidden))) => {
+                    Some(format!("add {} {} {}", count, name, hidden))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            monster("!monster add 3 goblins"),
+            Some("add 3 goblins false".to_owned())
+        );
+        assert_eq!(
+            monster("!monster add goblin boss hidden"),
+            Some("add 1 goblin boss true".to_owned())
+        );
+        assert_eq!(
+            monster("!monster add two dire wolves"),
+            Some("add 2 dire wolves false".to_owned())
+        );
+        assert_eq!(monster("!monster add 50 goblins"), None);
+        assert_eq!(
+            monster("!monster save Bog Hag AC 14 HP 52 attack +5 2d8+3 claws CR 3"),
+            Some("save Bog Hag CR 3".to_owned())
+        );
+        assert_eq!(monster("!monster save Bog Hag AC 14"), None);
+        assert_eq!(monster("!monster Bog Hag"), Some("show Bog Hag".to_owned()));
+        assert_eq!(monster("!monsters"), Some("show all".to_owned()));
+        assert_eq!(
+            monster("!monster delete Bog Hag"),
+            Some("delete Bog Hag".to_owned())
+        );
+        assert_eq!(
+            monster("!monster attack Bog Hag with advantage"),
+            Some("attack Bog Hag Some(Advantage) None".to_owned())
+        );
+        assert_eq!(
+            monster("!monster attack Goblin vs Connie with disadvantage"),
+            Some("attack Goblin Some(Disadvantage) Some(\"Connie\")".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_monster() {
+        fn monster(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_monster(content, &mentions, false) {
+                Some(Ok(Command::SaveMonster(monster))) => {
+                    Some(format!("save {} {}", monster.name, monster.damage))
+                }
+                Some(Ok(Command::ShowMonster(name))) => Some(format!("show {}", name)),
+                Some(Ok(Command::ShowMonsters)) => Some("show all".to_owned()),
+                Some(Ok(Command::DeleteMonster(name))) => Some(format!("delete {}", name)),
+                Some(Ok(Command::MonsterAttack(name, condition, target))) => {
+                    Some(format!("attack {} {:?} {:?}", name, condition, target))
+                }
+                Some(Ok(Command::AddMonsters(count, name, hidden))) => {
+                    Some(format!("add {} {} {}", count, name, hidden))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            monster("<@123> Add 3 goblins to the encounter."),
+            Some("add 3 goblins false".to_owned())
+        );
+        assert_eq!(
+            monster("<@123> add an ogre to the fight with hidden hit points"),
+            Some("add 1 ogre true".to_owned())
+        );
+        assert_eq!(monster("<@123> Add troll to the initiative at 12"), None);
+        assert_eq!(
+            monster("<@123> Save monster 'Bog Hag' AC 14 HP 52 attack +5 2d8+3"),
+            Some("save Bog Hag 2d8 + 3".to_owned())
+        );
+        assert_eq!(
+            monster("<@123> The Bog Hag attacks with disadvantage."),
+            Some("attack Bog Hag Some(Disadvantage) None".to_owned())
+        );
+        assert_eq!(
+            monster("<@123> the goblin attacks Connie"),
+            Some("attack goblin None Some(\"Connie\")".to_owned())
+        );
+        assert_eq!(
+            monster("<@123> Goblin attacks the paladin with advantage!"),
+            Some("attack Goblin Some(Advantage) Some(\"paladin\")".to_owned())
+        );
+        assert_eq!(
+            monster("<@123> Connie attacks the goblin with her longsword"),
+            None
+        );
+        assert_eq!(
+            monster("<@123> Forget the monster Bog Hag"),
+            Some("delete Bog Hag".to_owned())
+        );
+        assert_eq!(
+            monster("<@123> list saved monsters"),
+            Some("show all".to_owned())
+        );
+        assert_eq!(monster("Bog Hag attacks"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_counter() {
+        fn counter(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetCounter(counter))) => Some(counter.to_string()),
+                Some(Ok(Command::SpendCounter(name, amount))) => {
+                    Some(format!("spend {} {}", amount, name))
+                }
+                Some(Ok(Command::RestoreCounter(name, amount))) => {
+                    Some(format!("restore {:?} {}", amount, name))
+                }
+                Some(Ok(Command::RemoveCounter(name))) => Some(format!("remove {}", name)),
+                Some(Ok(Command::ShowCounters)) => Some("show".to_owned()),
+                Some(Ok(Command::Rest(rest))) => Some(rest.as_str().to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            counter("!counter Sorcery Points 3 long rest"),
+            Some("Sorcery Points: 3/3 (resets on a long rest)".to_owned())
+        );
+        assert_eq!(counter("!counter Ki 5"), Some("Ki: 5/5".to_owned()));
+        assert_eq!(counter("!counter Ki 0"), None);
+        assert_eq!(
+            counter("!counter spend Ki 2"),
+            Some("spend 2 Ki".to_owned())
+        );
+        assert_eq!(
+            counter("!counter spend Rage"),
+            Some("spend 1 Rage".to_owned())
+        );
+        assert_eq!(counter("!counter spend Ki 0"), None);
+        assert_eq!(
+            counter("!counter restore Ki"),
+            Some("restore None Ki".to_owned())
+        );
+        assert_eq!(
+            counter("!counter restore Ki 2"),
+            Some("restore Some(2) Ki".to_owned())
+        );
+        assert_eq!(counter("!counter remove Ki"), Some("remove Ki".to_owned()));
+        assert_eq!(counter("!counters"), Some("show".to_owned()));
+        assert_eq!(counter("!counter Ki"), None);
+        assert_eq!(counter("!rest short"), Some("short rest".to_owned()));
+        assert_eq!(counter("!rest"), None);
+    }
+
+    #[test]
+    fn test_parse_counter() {
+        fn counter(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_counter(content, &mentions, false) {
+                Some(Ok(Command::SpendCounter(name, amount))) => {
+                    Some(format!("spend {} {}", amount, name))
+                }
+                Some(Ok(Command::ShowCounters)) => Some("show".to_owned()),
+                Some(Ok(Command::Rest(rest))) => Some(rest.as_str().to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            counter("<@123> I spend 2 ki points."),
+            Some("spend 2 ki points".to_owned())
+        );
+        assert_eq!(
+            counter("<@123> use a sorcery point"),
+            Some("spend 1 sorcery point".to_owned())
+        );
+        assert_eq!(counter("<@123> I spend 15 gold"), None);
+        assert_eq!(
+            counter("<@123> We take a long rest."),
+            Some("long rest".to_owned())
+        );
+        assert_eq!(counter("<@123> short rest"), Some("short rest".to_owned()));
+        assert_eq!(counter("<@123> show my resources"), Some("show".to_owned()));
+        assert_eq!(counter("I take a short rest"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_effect() {
+        fn effect(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::AddCharacterEffect(effect))) => Some(effect.to_string()),
+                Some(Ok(Command::RemoveCharacterEffect(name))) => Some(format!("end {}", name)),
+                Some(Ok(Command::ClearCharacterEffects)) => Some("clear".to_owned()),
+                Some(Ok(Command::ShowCharacterEffects)) => Some("show".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            effect("!effect bless"),
+            Some("Bless: +1d4 to attack rolls and saving throws for 10 more rounds".to_owned())
+        );
+        assert_eq!(
+            effect("!effect Emboldening Bond +1d4 to attacks, saves and checks"),
+            Some(
+                "Emboldening Bond: +1d4 to attack rolls, saving throws and ability checks"
+                    .to_owned()
+            )
+        );
+        assert_eq!(effect("!effect haste"), None);
+        assert_eq!(effect("!effect end Bless"), Some("end Bless".to_owned()));
+        assert_eq!(effect("!effect clear"), Some("clear".to_owned()));
+        assert_eq!(effect("!effects"), Some("show".to_owned()));
+    }
+
+    #[test]
+    fn test_parse_character_effect() {
+        fn effect(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_character_effect(content, &mentions, false) {
+                Some(Ok(Command::AddCharacterEffect(effect))) => {
+                    Some(format!("add {} {:?}", effect.name, effect.rounds))
+                }
+                Some(Ok(Command::RemoveCharacterEffect(name))) => Some(format!("end {}", name)),
+                Some(Ok(Command::ShowCharacterEffects)) => Some("show".to_owned()),
+                Some(Err(_)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            effect("<@123> I'm blessed!"),
+            Some("add Bless Some(10)".to_owned())
+        );
+        assert_eq!(
+            effect("<@123> I'm under bane for 3 rounds"),
+            Some("add Bane Some(3)".to_owned())
+        );
+        assert_eq!(
+            effect("<@123> I have guidance"),
+            Some("add Guidance Some(10)".to_owned())
+        );
+        assert_eq!(
+            effect("<@123> I'm blessed for 0 rounds"),
+            Some("error".to_owned())
+        );
+        assert_eq!(
+            effect("<@123> My bless ends."),
+            Some("end Bless".to_owned())
+        );
+        assert_eq!(
+            effect("<@123> end my guidance"),
+            Some("end Guidance".to_owned())
+        );
+        assert_eq!(effect("<@123> show my effects"), Some("show".to_owned()));
+        assert_eq!(effect("<@123> I'm poisoned"), None);
+        assert_eq!(effect("I'm blessed"), None);
+    }
+
+    #[test]
+    fn test_clarification_verbosity() {
+        let error = Error::ArmorClassParserError;
+        assert_eq!(error.clarification(Verbosity::Terse), "Try `!ac 16`.");
+        assert_eq!(
+            error.clarification(Verbosity::Normal),
+            "It looks like you're trying to set your character's armour class, but the syntax is invalid. Try `!ac 16`."
+        );
+        assert!(error
+            .clarification(Verbosity::Verbose)
+            .ends_with("to see what else you can ask."));
+        assert_eq!(
+            Error::CharacterRollParserError(Vec::new()).clarification(Verbosity::Verbose),
+            Error::CharacterRollParserError(Vec::new()).to_string()
+        );
+        assert_eq!(
+            Error::LowConfidence("show your currency".to_owned()).clarification(Verbosity::Terse),
+            "Did you mean to show your currency? If so, answer \"Yes\". Try asking again with a different or simpler phrasing, or asking for help to see some examples."
+        );
+        assert_eq!(
+            Error::RollAttackAmbiguousWeapon(AmbiguousWeaponName::Bow)
+                .clarification(Verbosity::Terse),
+            AmbiguousWeaponName::Bow.message()
+        );
+        assert_eq!(Error::UnknownIntent("rollDice".to_owned()).hint(), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_lore() {
+        fn lore(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::RecallLore(roll, subject))) => {
+                    Some(format!("recall {} about {}", roll.check, subject))
+                }
+                Some(Ok(Command::SetLore(lore))) => Some(match lore.check {
+                    Some(check) => format!("add {} DC {}", lore.subject, check.dc),
+                    None => format!("add {}", lore.subject),
+                }),
+                Some(Ok(Command::RemoveLore(subject))) => Some(format!("remove {}", subject)),
+                Some(Ok(Command::RevealLore(subject))) => Some(format!("reveal {}", subject)),
+                Some(Ok(Command::ShowLore)) => Some("show".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            lore("!r history about the Shattered Crown"),
+            Some("recall History about the Shattered Crown".to_owned())
+        );
+        assert_eq!(lore("!r strength about the door"), None);
+        assert_eq!(
+            lore("!lore add The Shattered Crown DC 15 History: It was broken in battle"),
+            Some("add Shattered Crown DC 15".to_owned())
+        );
+        assert_eq!(
+            lore("!lore add Mayor Thorne (NPC): He's secretly a cultist"),
+            Some("add Mayor Thorne".to_owned())
+        );
+        assert_eq!(lore("!lore add The Shattered Crown"), None);
+        assert_eq!(
+            lore("!lore remove Shattered Crown"),
+            Some("remove Shattered Crown".to_owned())
+        );
+        assert_eq!(
+            lore("!lore reveal Mayor Thorne"),
+            Some("reveal Mayor Thorne".to_owned())
+        );
+        assert_eq!(lore("!lore"), Some("show".to_owned()));
+    }
+
+    #[test]
+    fn test_parse_recall_lore() {
+        fn recall(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_recall_lore(content, &mentions, false) {
+                Some(Ok(Command::RecallLore(roll, subject))) => {
+                    Some(format!("{} about {}", roll.check, subject))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            recall("<@123> Roll history about the Shattered Crown"),
+            Some("History about the Shattered Crown".to_owned())
+        );
+        assert_eq!(
+            recall("<@123> Arcana check about the glowing runes."),
+            Some("Arcana about the glowing runes".to_owned())
+        );
+        assert_eq!(recall("<@123> Tell me about the Shattered Crown"), None);
+        assert_eq!(recall("Roll history about the Shattered Crown"), None);
+    }
+
+    #[test]
+    fn test_parse_cast_spell() {
+        fn cast(content: &str) -> Option<Result<(String, Option<i32>, bool), ()>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            Command::parse_cast_spell(content, &mentions, false).map(|result| match result {
+                Ok(Command::CastSpell(name, level, ritual)) => Ok((name, level, ritual)),
+                _ => Err(()),
+            })
+        }
+        assert_eq!(
+            cast("<@123> Cast fireball at 4th level"),
+            Some(Ok(("fireball".to_owned(), Some(4), false)))
+        );
+        assert_eq!(
+            cast("<@123> I cast magic missile using a 3rd level spell slot."),
+            Some(Ok(("magic missile".to_owned(), Some(3), false)))
+        );
+        assert_eq!(
+            cast("<@123> cast fire bolt"),
+            Some(Ok(("fire bolt".to_owned(), None, false)))
+        );
+        assert_eq!(
+            cast("<@123> Ritual cast detect magic"),
+            Some(Ok(("detect magic".to_owned(), None, true)))
+        );
+        assert_eq!(
+            cast("<@123> I cast identify as a ritual."),
+            Some(Ok(("identify".to_owned(), None, true)))
+        );
+        assert_eq!(cast("<@123> cast fireball at 10th level"), Some(Err(())));
+        assert_eq!(
+            cast("<@123> ritual cast detect magic at 2nd level"),
+            Some(Err(()))
+        );
+        assert_eq!(cast("cast fireball"), None);
+
+        fn cast_shorthand(command: &str) -> Option<Result<(String, Option<i32>, bool), ()>> {
+            Command::parse_shorthand(command).map(|result| match result {
+                Ok(Command::CastSpell(name, level, ritual)) => Ok((name, level, ritual)),
+                _ => Err(()),
+            })
+        }
+        assert_eq!(
+            cast_shorthand("!cast fireball 4"),
+            Some(Ok(("fireball".to_owned(), Some(4), false)))
+        );
+        assert_eq!(
+            cast_shorthand("!cast Fire Bolt"),
+            Some(Ok(("Fire Bolt".to_owned(), None, false)))
+        );
+        assert_eq!(
+            cast_shorthand("!cast ritual detect magic"),
+            Some(Ok(("detect magic".to_owned(), None, true)))
+        );
+        assert_eq!(cast_shorthand("!cast"), Some(Err(())));
+        assert_eq!(cast_shorthand("!cast fireball 0"), Some(Err(())));
+    }
+
+    #[test]
+    fn test_parse_group_saving_throw() {
+        fn save(content: &str) -> Option<Result<(AbilityName, i32), ()>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            Command::parse_group_saving_throw(content, &mentions, false).map(
+                |result| match result {
+                    Ok(Command::GroupSavingThrow(ability, dc)) => Ok((ability, dc)),
+                    _ => Err(()),
+                },
+            )
+        }
+        assert_eq!(
+            save("<@123> Everyone make a DEX save DC 15"),
+            Some(Ok((AbilityName::Dexterity, 15)))
+        );
+        assert_eq!(
+            save("<@123> The party makes a Wisdom saving throw against DC 13."),
+            Some(Ok((AbilityName::Wisdom, 13)))
+        );
+        assert_eq!(save("<@123> everyone make a dex save"), Some(Err(())));
+        assert_eq!(
+            save("<@123> everyone make a luck save DC 10"),
+            Some(Err(()))
+        );
+        assert_eq!(save("<@123> make a dex save"), None);
+        assert_eq!(save("Everyone make a DEX save DC 15"), None);
+
+        fn save_shorthand(command: &str) -> Option<Result<(AbilityName, i32), ()>> {
+            Command::parse_shorthand(command).map(|result| match result {
+                Ok(Command::GroupSavingThrow(ability, dc)) => Ok((ability, dc)),
+                _ => Err(()),
+            })
+        }
+        assert_eq!(
+            save_shorthand("!groupsave dex 15"),
+            Some(Ok((AbilityName::Dexterity, 15)))
+        );
+        assert_eq!(
+            save_shorthand("!groupsave constitution DC 12"),
+            Some(Ok((AbilityName::Constitution, 12)))
+        );
+        assert_eq!(save_shorthand("!groupsave dex"), Some(Err(())));
+        assert_eq!(save_shorthand("!groupsave"), Some(Err(())));
+    }
+
+    #[test]
+    fn test_split_addressee() {
+        let mentions = BotMentions {
+            user_id: Some(UserId(BOT_ID)),
+            role_ids: &[],
+        };
+        assert_eq!(
+            Command::split_addressee("<@123> roll stealth", &mentions, false),
+            Some(("<@123> ", "roll stealth".to_owned()))
+        );
+        assert_eq!(
+            Command::split_addressee("!r stealth", &mentions, false),
+            Some(("", "!r stealth".to_owned()))
+        );
+        assert_eq!(
+            Command::split_addressee("roll stealth", &mentions, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_composite() {
+        fn count(content: &str) -> Option<usize> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse(None, None, content, &mentions, false) {
+                Some(Ok(CommandResult::Composite(results))) => Some(results.len()),
+                Some(Ok(result)) if result.is_understood() => Some(1),
+                _ => None,
+            }
+        }
+        assert_eq!(count("!ac 16; !r stealth ;!r perception"), Some(3));
+        assert_eq!(count("!ac 16 then !r stealth"), Some(2));
+        assert_eq!(count("!ac 16 and !r stealth"), Some(2));
+        assert_eq!(count("!ac 16; !r stealth, and then !r perception"), Some(3));
+        assert_eq!(count("!ac 16"), Some(1));
+        assert_eq!(count("!ac 16 and dance"), None);
+        assert_eq!(
+            count("!ac 16; !r d20; !r d20; !r d20; !r d20; !r d20"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_shorthand_audit() {
+        fn audit(command: &str) -> Option<Option<UserId>> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::ShowCharacterAudit(user_id))) => Some(user_id),
+                _ => None,
+            }
+        }
+        assert_eq!(audit("!audit"), Some(None));
+        assert_eq!(audit("!audit <@!456>"), Some(Some(UserId(456))));
+        assert_eq!(audit("!audit Alice"), None);
+    }
+
+    #[test]
+    fn test_parse_character_audit() {
+        fn audit(content: &str) -> Option<Option<UserId>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_character_audit(content, &mentions, false) {
+                Some(Ok(Command::ShowCharacterAudit(user_id))) => Some(user_id),
+                _ => None,
+            }
+        }
+        assert_eq!(audit("<@123> Show recent character changes."), Some(None));
+        assert_eq!(
+            audit("<@123> show the audit log for <@456>"),
+            Some(Some(UserId(456)))
+        );
+        assert_eq!(audit("Show recent character changes"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_deletion() {
+        fn deletion(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::DeleteCharacter(name))) => Some(format!("delete {}", name)),
+                Some(Ok(Command::PurgeUserFromChannel(user_id))) => {
+                    Some(format!("purge {}", user_id))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(deletion("!delete Connie"), Some("delete Connie".to_owned()));
+        assert_eq!(deletion("!delete"), None);
+        assert_eq!(deletion("!purge <@!456>"), Some("purge 456".to_owned()));
+        assert_eq!(deletion("!purge Alice"), None);
+    }
+
+    #[test]
+    fn test_parse_deletion() {
+        fn deletion(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_deletion(content, &mentions, false) {
+                Some(Ok(Command::DeleteCharacter(name))) => Some(format!("delete {}", name)),
+                Some(Ok(Command::PurgeUserFromChannel(user_id))) => {
+                    Some(format!("purge {}", user_id))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            deletion("<@123> Delete my character named Connie the Monk."),
+            Some("delete Connie the Monk".to_owned())
+        );
+        assert_eq!(
+            deletion("<@123> purge <@456>"),
+            Some("purge 456".to_owned())
+        );
+        assert_eq!(
+            deletion("<@123> Delete all of <@!456>'s data"),
+            Some("purge 456".to_owned())
+        );
+        assert_eq!(deletion("Delete my character Connie"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_bonus() {
+        fn bonus(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::AddBonus(bonus))) => Some(bonus.to_string()),
+                Some(Ok(Command::RemoveBonus(source))) => Some(format!("remove {}", source)),
+                Some(Ok(Command::ShowBonuses)) => Some("show".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            bonus("!bonus +2 to stealth from Cloak of Elvenkind"),
+            Some("+2 to Stealth from Cloak of Elvenkind".to_owned())
+        );
+        assert_eq!(
+            bonus("!bonus remove Cloak of Elvenkind"),
+            Some("remove Cloak of Elvenkind".to_owned())
+        );
+        assert_eq!(bonus("!bonuses"), Some("show".to_owned()));
+        assert_eq!(bonus("!bonus +2 to stealth"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_initiative_bonus() {
+        fn initiative(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetInitiativeBonus(bonus))) => Some(format!("{:+}", bonus)),
+                Some(Ok(Command::SetAlert(alert))) => Some(format!("alert {}", alert)),
+                _ => None,
+            }
+        }
+        assert_eq!(initiative("!initbonus +2"), Some("+2".to_owned()));
+        assert_eq!(initiative("!initbonus -1"), Some("-1".to_owned()));
+        assert_eq!(initiative("!initbonus"), None);
+        assert_eq!(initiative("!initbonus 11"), None);
+        assert_eq!(initiative("!alert"), Some("alert true".to_owned()));
+        assert_eq!(initiative("!alert off"), Some("alert false".to_owned()));
+        assert_eq!(initiative("!alert maybe"), None);
+    }
+
+    #[test]
+    fn test_parse_initiative_bonus() {
+        fn initiative(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_initiative_bonus(content, &mentions, false) {
+                Some(Ok(Command::SetInitiativeBonus(bonus))) => Some(format!("{:+}", bonus)),
+                Some(Ok(Command::SetAlert(alert))) => Some(format!("alert {}", alert)),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            initiative("<@123> My initiative bonus is +2"),
+            Some("+2".to_owned())
+        );
+        assert_eq!(
+            initiative("<@123> I have the Alert feat."),
+            Some("alert true".to_owned())
+        );
+        assert_eq!(
+            initiative("<@123> I don't have the alert feat"),
+            Some("alert false".to_owned())
+        );
+        assert_eq!(initiative("My initiative bonus is +2"), None);
+    }
+
+    #[test]
+    fn test_parse_passive_score() {
+        fn passive(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            let command = if content.starts_with('!') {
+                Command::parse_shorthand(content)
+            } else {
+                Command::parse_passive_score(content, &mentions, false)
+            };
+            match command {
+                Some(Ok(Command::ShowPassiveScore(check))) => Some(check.to_string()),
+                Some(Ok(Command::ShowPassiveScores)) => Some("all".to_owned()),
+                Some(Ok(Command::SetObservant(observant))) => {
+                    Some(format!("observant {}", observant))
+                }
+                Some(Err(Error::PassiveParserError)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            passive("<@123> What's my passive Perception?"),
+            Some("Perception".to_owned())
+        );
+        assert_eq!(
+            passive("<@123> what is my passive wisdom score"),
+            Some("Wisdom".to_owned())
+        );
+        assert_eq!(
+            passive("<@123> Show all my passive scores"),
+            Some("all".to_owned())
+        );
+        assert_eq!(passive("<@123> show passives"), Some("all".to_owned()));
+        assert_eq!(
+            passive("<@123> I have the Observant feat."),
+            Some("observant true".to_owned())
+        );
+        assert_eq!(passive("!passive insight"), Some("Insight".to_owned()));
+        assert_eq!(passive("!passive"), Some("all".to_owned()));
+        assert_eq!(passive("!passives all"), Some("all".to_owned()));
+        assert_eq!(passive("!passive initiative"), Some("error".to_owned()));
+        assert_eq!(
+            passive("!observant off"),
+            Some("observant false".to_owned())
+        );
+        assert_eq!(passive("What's my passive Perception?"), None);
+    }
+
+    #[test]
+    fn test_parse_fighting_style() {
+        fn fighting_style(content: &str) -> Option<Option<FightingStyle>> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_fighting_style(content, &mentions, false) {
+                Some(Ok(Command::SetFightingStyle(fighting_style))) => Some(fighting_style),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            fighting_style("<@123> My fighting style is Archery."),
+            Some(Some(FightingStyle::Archery))
+        );
+        assert_eq!(
+            fighting_style("<@123> I have the Two-Weapon Fighting style."),
+            Some(Some(FightingStyle::TwoWeaponFighting))
+        );
+        assert_eq!(
+            fighting_style("<@123> I use great weapon fighting"),
+            Some(Some(FightingStyle::GreatWeaponFighting))
+        );
+        assert_eq!(
+            fighting_style("<@123> I don't have a fighting style"),
+            Some(None)
+        );
+        assert_eq!(
+            fighting_style("<@123> I don't have two weapon fighting"),
+            Some(None)
+        );
+        assert_eq!(fighting_style("<@123> I have inspiration"), None);
+        assert_eq!(fighting_style("My fighting style is Archery"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_fighting_style() {
+        fn fighting_style(command: &str) -> Option<Option<FightingStyle>> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetFightingStyle(fighting_style))) => Some(fighting_style),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            fighting_style("!style dueling"),
+            Some(Some(FightingStyle::Dueling))
+        );
+        assert_eq!(
+            fighting_style("!style Two-Weapon Fighting"),
+            Some(Some(FightingStyle::TwoWeaponFighting))
+        );
+        assert_eq!(fighting_style("!style off"), Some(None));
+        assert_eq!(fighting_style("!style protection"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_dice_limits() {
+        fn limits(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetDiceLimits(Some(limits)))) => Some(limits.to_string()),
+                Some(Ok(Command::SetDiceLimits(None))) => Some("reset".to_owned()),
+                Some(Ok(Command::ShowDiceLimits)) => Some("show".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(limits("!limits 20d100"), Some("20d100".to_owned()));
+        assert_eq!(limits("!limits 50 d 20"), Some("50d20".to_owned()));
+        assert_eq!(limits("!limits reset"), Some("reset".to_owned()));
+        assert_eq!(limits("!limits"), Some("show".to_owned()));
+        assert_eq!(limits("!limits 20d1000"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_edition() {
+        fn edition(command: &str) -> Option<Option<Edition>> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetEdition(edition))) => Some(Some(edition)),
+                Some(Ok(Command::ShowEdition)) => Some(None),
+                _ => None,
+            }
+        }
+        assert_eq!(edition("!edition 2024"), Some(Some(Edition::Rules2024)));
+        assert_eq!(edition("!edition legacy"), Some(Some(Edition::Rules2014)));
+        assert_eq!(edition("!edition"), Some(None));
+        assert_eq!(edition("!edition 4e"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_time_zone() {
+        fn time_zone(command: &str) -> Option<String> {
+            let show = |time_zone: Option<TimeZone>| {
+                time_zone.map_or_else(|| "reset".to_owned(), |time_zone| time_zone.to_string())
+            };
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetTimeZone(time_zone))) => Some(show(time_zone)),
+                Some(Ok(Command::SetServerTimeZone(time_zone))) => {
+                    Some(format!("server {}", show(time_zone)))
+                }
+                Some(Ok(Command::ShowTimeZone)) => Some("show".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(time_zone("!timezone UTC+10"), Some("UTC+10".to_owned()));
+        assert_eq!(time_zone("!tz -5:30"), Some("UTC-5:30".to_owned()));
+        assert_eq!(
+            time_zone("!timezone server GMT-5"),
+            Some("server UTC-5".to_owned())
+        );
+        assert_eq!(
+            time_zone("!timezone server reset"),
+            Some("server reset".to_owned())
+        );
+        assert_eq!(time_zone("!timezone reset"), Some("reset".to_owned()));
+        assert_eq!(time_zone("!timezone"), Some("show".to_owned()));
+        assert_eq!(time_zone("!timezone server"), None);
+        assert_eq!(time_zone("!timezone Mars"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_weapon_mastery() {
+        fn mastery(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::AddWeaponMastery(weapon))) => Some(format!("add {}", weapon)),
+                Some(Ok(Command::RemoveWeaponMastery(weapon))) => {
+                    Some(format!("remove {}", weapon))
+                }
+                Some(Ok(Command::ShowWeaponMasteries)) => Some("show".to_owned()),
+                Some(Err(Error::MasteryParserError(suggestions))) => Some(format!(
+                    "suggest {}",
+                    suggestions
+                        .iter()
+                        .map(WeaponName::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            mastery("!mastery greatsword"),
+            Some("add Greatsword".to_owned())
+        );
+        assert_eq!(
+            mastery("!mastery remove hand crossbow"),
+            Some("remove Hand Crossbow".to_owned())
+        );
+        assert_eq!(mastery("!masteries"), Some("show".to_owned()));
+        assert_eq!(mastery("!mastery lightsaber"), Some("suggest ".to_owned()));
+        assert_eq!(
+            mastery("!mastery greatsord"),
+            Some("suggest Greatsword".to_owned())
+        );
+        assert_eq!(
+            mastery("!mastery remove longsord"),
+            Some("suggest Longsword".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_split_cover() {
+        assert_eq!(
+            Command::split_cover("attack the archer behind half cover with my shortbow"),
+            (
+                "attack the archer with my shortbow".to_owned(),
+                Some(Cover::Half)
+            )
+        );
+        assert_eq!(
+            Command::split_cover("Attack with my longbow, behind three-quarters cover"),
+            (
+                "Attack with my longbow".to_owned(),
+                Some(Cover::ThreeQuarters)
+            )
+        );
+        assert_eq!(
+            Command::split_cover("attack with my shortbow behind total cover"),
+            (
+                "attack with my shortbow behind total cover".to_owned(),
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn test_split_protected() {
+        assert_eq!(
+            Command::split_protected("rol 2d8+3 damge"),
+            vec![("rol ", false), ("2d8+3", true), (" damge", false)]
+        );
+        assert_eq!(
+            Command::split_protected("atack <@!123> with +5 at 2nd levle"),
+            vec![
+                ("atack ", false),
+                ("<@!123>", true),
+                (" with ", false),
+                ("+5", true),
+                (" at ", false),
+                ("2nd", true),
+                (" levle", false),
+            ]
+        );
+        assert_eq!(Command::split_protected("d20 + 4"), vec![("d20 + 4", true)]);
+        assert_eq!(
+            Command::split_protected("roll stealth"),
+            vec![("roll stealth", false)]
+        );
+    }
+
+    #[test]
+    fn test_split_within_5_feet() {
+        assert_eq!(
+            Command::split_within_5_feet("attack the knight with my lance within 5 feet"),
+            ("attack the knight with my lance", true)
+        );
+        assert_eq!(
+            Command::split_within_5_feet("attack with my lance, at 5 ft."),
+            ("attack with my lance", true)
+        );
+        assert_eq!(
+            Command::split_within_5_feet("attack with my lance within 10 feet"),
+            ("attack with my lance within 10 feet", false)
+        );
+    }
+
+    #[test]
+    fn test_split_uncorrected() {
+        assert_eq!(
+            Command::split_uncorrected("\\attack with Kelgrimm's Maul"),
+            ("attack with Kelgrimm's Maul", true)
+        );
+        assert_eq!(
+            Command::split_uncorrected("\\ roll stelth"),
+            ("roll stelth", true)
+        );
+        assert_eq!(
+            Command::split_uncorrected("roll stealth"),
+            ("roll stealth", false)
+        );
+    }
+
+    #[test]
+    fn test_parse_rule_based() {
+        fn rule_based(message: &str) -> String {
+            match Command::parse_rule_based(message) {
+                Ok(Command::Roll(roll, None)) => format!("roll {}", roll),
+                Ok(Command::CharacterRoll(CharacterRoll { check, condition }, None)) => {
+                    format!("{:?} {:?}", check, condition)
+                }
+                Ok(Command::AttackRoll(AttackRoll::Weapon(roll), ..)) => {
+                    format!("{} {:?} {:?}", roll.weapon, roll.handedness, roll.condition)
+                }
+                Ok(Command::SetAbility(ability, score)) => {
+                    format!("{} {}", ability.as_str(), score)
+                }
+                Ok(_) => "other".to_owned(),
+                Err(Error::NoRuleBasedIntent) => "unknown".to_owned(),
+                Err(error) => format!("error: {:?}", error),
+            }
+        }
+        assert_eq!(rule_based("Roll 2d6+3"), "roll 2d6 + 3");
+        assert_eq!(
+            rule_based("Roll a dex save with advantage."),
+            "SavingThrow(Dexterity) Some(Advantage)"
+        );
+        assert_eq!(rule_based("roll my stealth check"), "Skill(Stealth) None");
+        assert_eq!(
+            rule_based("Attack the goblin with my longsword two-handed"),
+            "Longsword Some(TwoHanded) None"
+        );
+        assert_eq!(
+            rule_based("dagger attack with disadvantage"),
+            "Dagger None Some(Disadvantage)"
+        );
+        assert_eq!(
+            rule_based("Attack with my longsword"),
+            "error: RollAttackMissingHandedness"
+        );
+        assert_eq!(rule_based("Bump my dex to 16"), "Dexterity 16");
+        assert_eq!(rule_based("What's the weather like?"), "unknown");
+    }
+
+    #[test]
+    fn test_split_flanking() {
+        assert_eq!(
+            Command::split_flanking("attack the goblin with my longsword while flanking"),
+            ("attack the goblin with my longsword", true)
+        );
+        assert_eq!(
+            Command::split_flanking("attack with my shortsword, flanking it"),
+            ("attack with my shortsword", true)
+        );
+        assert_eq!(
+            Command::split_flanking("attack the flanking goblin with my longsword"),
+            ("attack the flanking goblin with my longsword", false)
+        );
+    }
+
+    #[test]
+    fn test_parse_house_rules() {
+        use crate::house_rule::CriticalHits;
+
+        fn house_rules(content: &str) -> Option<String> {
+            match Command::parse_shorthand(content) {
+                Some(Ok(Command::ShowHouseRules)) => Some("show".to_owned()),
+                Some(Ok(Command::ResetHouseRules)) => Some("reset".to_owned()),
+                Some(Ok(Command::SetHouseRule(house_rule))) => Some(format!("{:?}", house_rule)),
+                Some(Err(Error::HouseRuleParserError)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(house_rules("!houserules"), Some("show".to_owned()));
+        assert_eq!(house_rules("!houserules show"), Some("show".to_owned()));
+        assert_eq!(house_rules("!houserules reset"), Some("reset".to_owned()));
+        assert_eq!(
+            house_rules("!houserules set crits max"),
+            Some(format!(
+                "{:?}",
+                HouseRule::CriticalHits(CriticalHits::MaximiseDice)
+            ))
+        );
+        assert_eq!(
+            house_rules("!houserules set flanking on"),
+            Some(format!("{:?}", HouseRule::Flanking(true)))
+        );
+        assert_eq!(
+            house_rules("!houserules set potions bonus action"),
+            Some(format!("{:?}", HouseRule::PotionBonusAction(true)))
+        );
+        assert_eq!(
+            house_rules("!houserules set crits triple"),
+            Some("error".to_owned())
+        );
+        assert_eq!(
+            house_rules("!houserules flanking"),
+            Some("error".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_to_hit_and_damage_rolls() {
+        use crate::attack_roll::{AttackRoll, WeaponAttackRoll};
+
+        let rapier = || {
+            AttackRoll::Weapon(WeaponAttackRoll {
+                weapon: WeaponName::Rapier,
+                classification: None,
+                condition: None,
+                handedness: None,
+            })
+        };
+        let to_hit = |command: Result<Command, Error>| match command {
+            Ok(Command::ToHitRoll(AttackRoll::Weapon(roll), target, _)) => {
+                Some(format!("{:?} {:?}", roll.condition, target))
+            }
+            Ok(Command::UseInspiration(command)) => match *command {
+                Command::ToHitRoll(AttackRoll::Weapon(roll), target, _) => {
+                    Some(format!("inspired {:?} {:?}", roll.condition, target))
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        assert_eq!(
+            to_hit(Command::with_target(
+                Ok(Command::ToHitRoll(rapier(), None, None)),
+                Some("goblin".to_owned())
+            )),
+            Some("None Some(\"goblin\")".to_owned())
+        );
+        assert_eq!(
+            to_hit(Command::ToHitRoll(rapier(), None, None).use_inspiration()),
+            Some("inspired Some(Advantage) None".to_owned())
+        );
+        assert!(Command::ToHitRoll(rapier(), None, None)
+            .use_bardic_inspiration()
+            .is_ok());
+        assert!(Command::DamageRoll(rapier(), true)
+            .use_inspiration()
+            .is_err());
+        assert!(Command::with_target(
+            Ok(Command::DamageRoll(rapier(), false)),
+            Some("goblin".to_owned())
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_split_smite() {
+        assert_eq!(
+            Command::split_smite("attack with my longsword and smite at 2nd level"),
+            (
+                "attack with my longsword",
+                Some(Smite {
+                    level: 2,
+                    undead_or_fiend: false,
+                })
+            )
+        );
+        assert_eq!(
+            Command::split_smite("attack with my maul, then divine smite against an undead"),
+            (
+                "attack with my maul",
+                Some(Smite {
+                    level: 1,
+                    undead_or_fiend: true,
+                })
+            )
+        );
+        assert_eq!(
+            Command::split_smite("attack and smite the goblin"),
+            ("attack and smite the goblin", None)
+        );
+    }
+
+    #[test]
+    fn test_split_target() {
+        let target = |string| {
+            let (without_target, target) = Command::split_target(string);
+            (without_target, target.unwrap_or_default())
+        };
+        assert_eq!(
+            target("attack the goblin with my shortbow"),
+            ("attack with my shortbow".to_owned(), "goblin".to_owned())
+        );
+        assert_eq!(
+            target("Attack Goblin A using a dagger as melee"),
+            (
+                "Attack using a dagger as melee".to_owned(),
+                "Goblin A".to_owned()
+            )
+        );
+        assert_eq!(
+            target("attack with my longsword against the troll"),
+            ("attack with my longsword".to_owned(), "troll".to_owned())
+        );
+        assert_eq!(
+            target("attack the troll twice with my battleaxe"),
+            (
+                "attack twice with my battleaxe".to_owned(),
+                "troll".to_owned()
+            )
+        );
+        assert_eq!(
+            target("attack twice with my shortsword"),
+            ("attack twice with my shortsword".to_owned(), String::new())
+        );
+        assert_eq!(
+            target("roll to hit the goblin with my rapier"),
+            ("roll to hit with my rapier".to_owned(), "goblin".to_owned())
+        );
+        assert_eq!(
+            target("Roll to hit with my rapier against Goblin A"),
+            (
+                "Roll to hit with my rapier".to_owned(),
+                "Goblin A".to_owned()
+            )
+        );
+        assert_eq!(
+            target("attack with advantage with my dagger"),
+            (
+                "attack with advantage with my dagger".to_owned(),
+                String::new()
+            )
+        );
+    }
+
+    #[test]
+    fn test_split_attack_count() {
+        assert_eq!(
+            Command::split_attack_count("attack twice with my shortsword"),
+            Some((
+                "attack with my shortsword".to_owned(),
+                AttackCount::Times(2)
+            ))
+        );
+        assert_eq!(
+            Command::split_attack_count("attack with my rapier 3 times"),
+            Some(("attack with my rapier".to_owned(), AttackCount::Times(3)))
+        );
+        assert_eq!(
+            Command::split_attack_count("attack with my longsword two handed using extra attack"),
+            Some((
+                "attack with my longsword two handed".to_owned(),
+                AttackCount::ExtraAttack
+            ))
+        );
+        assert_eq!(
+            Command::split_attack_count("attack with my shortsword"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_shorthand_monster_packs() {
+        fn pack(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::ShowContentPacks)) => Some("show".to_owned()),
+                Some(Ok(Command::InstallContentPack)) => Some("install".to_owned()),
+                Some(Ok(Command::EnableContentPack(name))) => Some(format!("enable {}", name)),
+                Some(Ok(Command::DisableContentPack(name))) => Some(format!("disable {}", name)),
+                Some(Ok(Command::RemoveContentPack(name))) => Some(format!("remove {}", name)),
+                _ => None,
+            }
+        }
+        assert_eq!(pack("!monsterpack"), Some("show".to_owned()));
+        assert_eq!(pack("!monsterpacks"), Some("show".to_owned()));
+        assert_eq!(pack("!monsterpack install"), Some("install".to_owned()));
+        assert_eq!(
+            pack("!monsterpack enable Sunless Citadel"),
+            Some("enable Sunless Citadel".to_owned())
+        );
+        assert_eq!(
+            pack("!monsterpack Disable Sunless Citadel"),
+            Some("disable Sunless Citadel".to_owned())
+        );
+        assert_eq!(
+            pack("!monsterpack remove Sunless Citadel"),
+            Some("remove Sunless Citadel".to_owned())
+        );
+        assert_eq!(pack("!monsterpack upgrade Sunless Citadel"), None);
+    }
+
+    #[test]
+    fn test_check_limits() {
+        let limits = Limits::parse("20d100");
+        let roll = |command: &str| match Command::parse_shorthand(command) {
+            Some(Ok(command)) => limits.map(|limits| command.check_limits(limits).is_ok()),
+            _ => None,
+        };
+        assert_eq!(roll("!r 20d6"), Some(true));
+        assert_eq!(roll("!r 21d6"), Some(false));
+        assert_eq!(roll("!table 100d6"), Some(false));
+        assert_eq!(roll("!r 21d6 hidden"), Some(false));
+        assert_eq!(roll("!ac 16"), Some(true));
+    }
+
+    #[test]
+    fn test_parse_permission_check() {
+        let check = |content| {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_permission_check(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::CheckPermissions)) => true,
+                _ => false,
+            }
+        };
+        assert!(check("<@123> check my permissions"));
+        assert!(check("<@123> What are your permissions in this channel?"));
+        assert!(check("!permissions"));
+        assert!(!check("check my permissions"));
+        assert!(!check("<@123> check my permissions for rolling"));
+    }
+
+    #[test]
+    fn test_parse_verify_roll() {
+        fn verify(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_verify_roll(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::VerifyRoll(code))) => Some(code),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            verify("<@123> Verify roll 3FA92C07D1"),
+            Some("3FA92C07D1".to_owned())
+        );
+        assert_eq!(
+            verify("<@123> check this roll: 3fa9 2c07 d1?"),
+            Some("3fa9 2c07 d1".to_owned())
+        );
+        assert_eq!(verify("!verify 3FA92C07D1"), Some("3FA92C07D1".to_owned()));
+        assert_eq!(verify("!verify"), None);
+        assert_eq!(verify("Verify roll 3FA92C07D1"), None);
+    }
+
+    #[test]
+    fn test_parse_hidden_roll() {
+        fn hidden(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_hidden_roll(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::RollHidden(roll))) => Some(roll.to_string()),
+                Some(Ok(Command::RevealHiddenRolls)) => Some("reveal".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(hidden("<@123> Roll 1d20 hidden"), Some("1d20".to_owned()));
+        assert_eq!(
+            hidden("<@123> roll a d20 with advantage in secret."),
+            Some("1d20 with advantage".to_owned())
+        );
+        assert_eq!(hidden("!r 2d6+3 hidden"), Some("2d6 + 3".to_owned()));
+        assert_eq!(
+            hidden("<@123> Reveal my hidden roll"),
+            Some("reveal".to_owned())
+        );
+        assert_eq!(hidden("!reveal"), Some("reveal".to_owned()));
+        assert_eq!(hidden("<@123> Roll stealth hidden"), None);
+        assert_eq!(hidden("Roll 1d20 hidden"), None);
+    }
+
+    #[test]
+    fn test_parse_blind_roll() {
+        fn blind(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            let command = Command::parse_hidden_roll(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content));
+            match command {
+                Some(Ok(Command::BlindRoll(command))) => match *command {
+                    Command::CharacterRoll(roll, None) => Some(roll.check.to_string()),
+                    Command::Roll(roll, None) => Some(roll.to_string()),
+                    _ => None,
+                },
+                Some(Err(Error::BlindRollParserError)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            blind("<@123> Roll Perception blind"),
+            Some("Perception".to_owned())
+        );
+        assert_eq!(
+            blind("<@123> roll a blind Deception check."),
+            Some("Deception".to_owned())
+        );
+        assert_eq!(blind("!r d20+3 blind"), Some("1d20 + 3".to_owned()));
+        assert_eq!(blind("!r insight blind"), Some("Insight".to_owned()));
+        assert_eq!(blind("!r bananas blind"), Some("error".to_owned()));
+        assert_eq!(blind("<@123> Roll 1d20 hidden"), None);
+    }
+
+    #[test]
+    fn test_parse_dungeon_master() {
+        fn dungeon_master(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_dungeon_master(content, &mentions, false)
+                .or_else(|| Command::parse_shorthand(content))
+            {
+                Some(Ok(Command::SetDungeonMaster(Some(dungeon_master)))) => {
+                    Some(dungeon_master.to_string())
+                }
+                Some(Ok(Command::SetDungeonMaster(None))) => Some("clear".to_owned()),
+                Some(Ok(Command::ShowDungeonMaster)) => Some("show".to_owned()),
+                Some(Err(Error::DungeonMasterParserError)) => Some("error".to_owned()),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            dungeon_master("<@123> <@456> is the DM."),
+            Some("<@456>".to_owned())
+        );
+        assert_eq!(dungeon_master("!dm <@!456>"), Some("<@456>".to_owned()));
+        assert_eq!(
+            dungeon_master("<@123> <@&789> are the DMs"),
+            Some("<@&789>".to_owned())
+        );
+        assert_eq!(dungeon_master("!dm <@&789>"), Some("<@&789>".to_owned()));
+        assert_eq!(dungeon_master("!dm clear"), Some("clear".to_owned()));
+        assert_eq!(dungeon_master("!dm"), Some("show".to_owned()));
+        assert_eq!(
+            dungeon_master("<@123> Who's the DM?"),
+            Some("show".to_owned())
+        );
+        assert_eq!(dungeon_master("!dm Alice"), Some("error".to_owned()));
+    }
+
+    #[test]
+    fn test_parse_shorthand_character_name() {
+        fn name(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetCharacterName(name))) => Some(name),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            name("!name Connie the Monk"),
+            Some("Connie the Monk".to_owned())
+        );
+        let longest = "C".repeat(MAXIMUM_DISPLAY_NAME_LENGTH);
+        assert_eq!(name(&format!("!name {}", longest)), Some(longest));
+        assert!(
+            match Command::parse_shorthand(&format!("!name {}C", "C".repeat(100))) {
+                Some(Err(Error::SetCharacterNameTooLong(101))) => true,
+                _ => false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_shorthand_armor_class() {
+        fn armor_class(command: &str) -> Option<i32> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetArmorClass(armor_class))) => Some(armor_class),
+                _ => None,
+            }
+        }
+        assert_eq!(armor_class("!ac 16"), Some(16));
+        assert_eq!(armor_class("!ac"), None);
+        assert_eq!(armor_class("!ac 0"), None);
+        assert_eq!(armor_class("!ac 31"), None);
+        assert!(match Command::parse_shorthand("!ac goblin A 15") {
+            Some(Ok(Command::SetCombatantArmorClass(name, 15))) => name == "goblin A",
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn test_parse_shorthand_ongoing() {
+        fn ongoing(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::AddOngoingEffect(effect))) => {
+                    Some(format!("{}: {}", effect.target, effect.description()))
+                }
+                Some(Ok(Command::ClearOngoingEffects(target))) => Some(format!("clear {}", target)),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            ongoing("!ongoing goblin A 1d6 poison for 3 rounds"),
+            Some("goblin A: 1d6 poison damage".to_owned())
+        );
+        assert_eq!(
+            ongoing("!ongoing clear goblin A"),
+            Some("clear goblin A".to_owned())
+        );
+        assert_eq!(ongoing("!ongoing"), None);
+        assert_eq!(ongoing("!ongoing goblin A"), None);
+        assert_eq!(
+            ongoing("!regen troll 10 unless fire, acid"),
+            Some("troll: 10 hit points".to_owned())
+        );
+        assert_eq!(ongoing("!regen troll"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_hit_points() {
+        fn hit_points(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetHitPoints(name, hit_points, hidden))) => Some(format!(
+                    "{}: {}{}",
+                    name,
+                    hit_points,
+                    if hidden { " hidden" } else { "" }
+                )),
+                Some(Ok(Command::HealCombatant(name, amount))) => {
+                    Some(format!("{}: +{}", name, amount))
+                }
+                Some(Ok(Command::AddCombatant(entry, hit_points, hidden))) => Some(format!(
+                    "add {} {} {:?}{}",
+                    entry.name,
+                    entry.initiative,
+                    hit_points,
+                    if hidden { " hidden" } else { "" }
+                )),
+                Some(Ok(Command::DamageCombatant(damage))) => {
+                    Some(format!("{}: {}", damage.target, damage.description()))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(hit_points("!hp troll 84"), Some("troll: 84".to_owned()));
+        assert_eq!(
+            hit_points("!hp troll 84 hidden"),
+            Some("troll: 84 hidden".to_owned())
+        );
+        assert_eq!(hit_points("!hp troll"), None);
+        assert_eq!(hit_points("!heal troll 10"), Some("troll: +10".to_owned()));
+        assert_eq!(hit_points("!heal troll"), None);
+        assert_eq!(
+            hit_points("!init add troll 12"),
+            Some("add troll 12 None".to_owned())
+        );
+        assert_eq!(
+            hit_points("!init add goblin C -1 hp 7 hidden"),
+            Some("add goblin C -1 Some(7) hidden".to_owned())
+        );
+        assert_eq!(hit_points("!init add troll 12 hp 0"), None);
+        assert_eq!(
+            hit_points("!damage troll 12 fire"),
+            Some("troll: 12 fire damage".to_owned())
+        );
+        assert_eq!(hit_points("!damage troll"), None);
+    }
+
+    #[test]
+    fn test_parse_shorthand_resistances() {
+        fn resistances(command: &str) -> Option<String> {
+            match Command::parse_shorthand(command) {
+                Some(Ok(Command::SetResistances(resistances))) => Some(format!(
+                    "{}: {}",
+                    resistances.target,
+                    resistances.description()
+                )),
+                Some(Ok(Command::ClearResistances(target))) => Some(format!("clear {}", target)),
+                _ => None,
+            }
+        }
+        assert_eq!(
+            resistances("!resist troll fire, cold"),
+            Some("troll: resistance to fire and cold".to_owned())
+        );
+        assert_eq!(
+            resistances("!immune skeleton A poison"),
+            Some("skeleton A: immunity to poison".to_owned())
+        );
+        assert_eq!(
+            resistances("!resist troll none"),
+            Some("clear troll".to_owned())
+        );
+        assert_eq!(resistances("!resist troll cheese"), None);
+    }
+
+    #[test]
+    fn test_parse_ongoing_effect() {
+        fn ongoing(content: &str) -> Option<OngoingEffect> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_ongoing_effect(content, &mentions, false) {
+                Some(Ok(Command::AddOngoingEffect(effect))) => Some(effect),
+                _ => None,
+            }
+        }
+        let expected = OngoingEffect::parse_shorthand("Goblin A 1d6 poison for 3 rounds");
+        assert_eq!(
+            ongoing("<@123> Goblin A takes 1d6 poison at the start of each turn for 3 rounds"),
+            expected
+        );
+        assert_eq!(
+            ongoing("Goblin A takes 1d6 poison at the start of each turn for 3 rounds"),
+            None
+        );
+        assert_eq!(
+            ongoing("<@123> The troll regains 10 hit points at the start of its turn"),
+            OngoingEffect::parse_regeneration_shorthand("The troll 10")
+        );
+    }
+
+    #[test]
+    fn test_parse_combatant_hit_points() {
+        fn hit_points(content: &str) -> Option<String> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_combatant_hit_points(content, &mentions, false) {
+                Some(Ok(Command::SetHitPoints(name, hit_points, hidden))) => Some(format!(
+                    "{}: {}{}",
+                    name,
+                    hit_points,
+                    if hidden { " hidden" } else { "" }
+                )),
+                Some(Ok(Command::HealCombatant(name, amount))) => {
+                    Some(format!("{}: +{}", name, amount))
+                }
+                Some(Ok(Command::AddCombatant(entry, hit_points, hidden))) => Some(format!(
+                    "add {} {} {:?}{}",
+                    entry.name,
+                    entry.initiative,
+                    hit_points,
+                    if hidden { " hidden" } else { "" }
+                )),
+                Some(Ok(Command::DamageCombatant(damage))) => {
+                    Some(format!("{}: {}", damage.target, damage.description()))
+                }
+                Some(Ok(Command::SetCombatantExperience(name, experience))) => {
+                    Some(format!("{}: {} XP", name, experience))
+                }
+                Some(Ok(Command::SetCombatantArmorClass(name, armor_class))) => {
+                    Some(format!("{}: AC {}", name, armor_class))
+                }
+                _ => None,
+            }
+        }
+        assert_eq!(
+            hit_points("<@123> Troll has 84 hit points"),
+            Some("Troll: 84".to_owned())
+        );
+        assert_eq!(
+            hit_points("<@123> Goblin A has an armour class of 15"),
+            Some("Goblin A: AC 15".to_owned())
+        );
+        assert_eq!(hit_points("<@123> Troll has AC 40"), None);
+        assert_eq!(
+            hit_points("<@123> Troll takes 12 fire damage"),
+            Some("Troll: 12 fire damage".to_owned())
+        );
+        assert_eq!(hit_points("Troll takes 12 fire damage"), None);
+        assert_eq!(
+            hit_points("<@123> Troll is worth 1800 XP."),
+            Some("Troll: 1800 XP".to_owned())
+        );
+        assert_eq!(
+            hit_points("<@123> Troll has 84 hidden hit points."),
+            Some("Troll: 84 hidden".to_owned())
+        );
+        assert_eq!(
+            hit_points("<@123> Troll regains 10 hit points"),
+            Some("Troll: +10".to_owned())
+        );
+        assert_eq!(
+            hit_points("<@123> Add troll to the initiative at 12 with 84 hidden hit points"),
+            Some("add troll 12 Some(84) hidden".to_owned())
+        );
+        assert_eq!(
+            hit_points("<@123> Add goblin C to the encounter with initiative 9."),
+            Some("add goblin C 9 None".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_initiative_order() {
+        fn order(content: &str) -> Option<InitiativeOrder> {
+            let mentions = BotMentions {
+                user_id: Some(UserId(BOT_ID)),
+                role_ids: &[],
+            };
+            match Command::parse_initiative_order(content, &mentions, false) {
+                Some(Ok(Command::SetInitiativeOrder(order))) => Some(order),
+                _ => None,
+            }
+        }
+        let expected = InitiativeOrder::parse("Alice 18, Bob 12").ok();
+        assert_eq!(
+            order("<@123> set initiative order: Alice 18, Bob 12"),
+            expected
+        );
+        assert_eq!(
+            order("<@123> Set the initiative to Bob 12, Alice 18"),
+            expected
+        );
+        assert_eq!(order("set initiative order: Alice 18, Bob 12"), None);
+        assert_eq!(order("<@123> roll initiative"), None);
+    }
 }
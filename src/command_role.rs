@@ -0,0 +1,148 @@
+use crate::command::Command;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serenity::model::id::{ChannelId, RoleId};
+use std::fmt;
+
+/// A kind of command that can be restricted to the members of a role in a channel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommandCategory {
+    /// Running the game, such as awarding experience points or saving monsters.
+    DungeonMaster,
+    /// Creating characters and changing their character sheets.
+    Editing,
+    /// Rolling dice, checks and attacks.
+    Rolling,
+}
+
+/// The role that a kind of command is restricted to in a channel. Server administrators can
+/// always use every command.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CommandRole {
+    pub category: CommandCategory,
+    pub role_id: RoleId,
+}
+
+impl CommandCategory {
+    pub fn parse(string: &str) -> Option<CommandCategory> {
+        match string.trim().to_lowercase().as_str() {
+            "dm" | "dm tools" | "dungeon master" => Some(CommandCategory::DungeonMaster),
+            "editing" | "edit" | "characters" => Some(CommandCategory::Editing),
+            "rolling" | "rolls" | "roll" | "dice" => Some(CommandCategory::Rolling),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CommandCategory::DungeonMaster => "dm",
+            CommandCategory::Editing => "editing",
+            CommandCategory::Rolling => "rolling",
+        }
+    }
+}
+
+impl fmt::Display for CommandCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandCategory::DungeonMaster => write!(f, "DM"),
+            CommandCategory::Editing => write!(f, "editing"),
+            CommandCategory::Rolling => write!(f, "rolling"),
+        }
+    }
+}
+
+impl CommandRole {
+    /// The restriction that stops a user with the given roles from running a command, if any.
+    /// Doesn't take server administrators into account, who can run every command.
+    pub fn find_denied<'a>(
+        command_roles: &'a [CommandRole],
+        command: &Command,
+        role_ids: &[RoleId],
+    ) -> Option<&'a CommandRole> {
+        command_roles.iter().find(|command_role| {
+            command.is_in_category(command_role.category)
+                && !role_ids.contains(&command_role.role_id)
+        })
+    }
+
+    /// Retrieve the roles that commands are restricted to in a channel.
+    pub fn get(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<Vec<CommandRole>> {
+        let mut statement = connection.prepare(
+            "SELECT category, role_id FROM channel_command_roles \
+             WHERE channel_id = $1 \
+             ORDER BY category",
+        )?;
+        let rows = statement.query_map(&[&channel_id.to_string()], CommandRole::from_row)?;
+        rows.filter_map(|row| row.transpose()).collect()
+    }
+
+    /// Restrict a kind of command to the members of a role in a channel, or let everyone use it
+    /// again.
+    pub fn set(
+        connection: &Connection,
+        channel_id: ChannelId,
+        category: CommandCategory,
+        role_id: Option<RoleId>,
+    ) -> RusqliteResult<usize> {
+        match role_id {
+            Some(role_id) => {
+                let params: &[&dyn ToSql] = &[
+                    &channel_id.to_string(),
+                    &category.as_str(),
+                    &role_id.to_string(),
+                ];
+                connection.execute(
+                    "INSERT INTO channel_command_roles (channel_id, category, role_id) \
+                     VALUES ($1, $2, $3) \
+                     ON CONFLICT (channel_id, category) DO UPDATE SET role_id = $3",
+                    params,
+                )
+            }
+            None => {
+                let params: &[&dyn ToSql] = &[&channel_id.to_string(), &category.as_str()];
+                connection.execute(
+                    "DELETE FROM channel_command_roles WHERE channel_id = $1 AND category = $2",
+                    params,
+                )
+            }
+        }
+    }
+
+    /// Rows with a category or role that can't be parsed are skipped.
+    fn from_row(row: &Row) -> RusqliteResult<Option<CommandRole>> {
+        let category = CommandCategory::parse(&row.get::<_, String>("category")?);
+        let role_id = row.get::<_, String>("role_id")?.parse().ok().map(RoleId);
+        Ok(match (category, role_id) {
+            (Some(category), Some(role_id)) => Some(CommandRole { category, role_id }),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_denied() {
+        let command_roles = [CommandRole {
+            category: CommandCategory::Editing,
+            role_id: RoleId(456),
+        }];
+        let command = Command::SetArmorClass(16);
+        assert_eq!(
+            CommandRole::find_denied(&command_roles, &command, &[]),
+            Some(&command_roles[0])
+        );
+        assert_eq!(
+            CommandRole::find_denied(&command_roles, &command, &[RoleId(456)]),
+            None
+        );
+        assert_eq!(
+            CommandRole::find_denied(&command_roles, &Command::ShowCharacterSheet, &[]),
+            None
+        );
+    }
+}
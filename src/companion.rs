@@ -0,0 +1,162 @@
+use crate::character::Character;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serenity::model::id::{ChannelId, UserId};
+use std::fmt;
+
+/// The kinds of creature that can accompany a character and act alongside them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompanionKind {
+    /// A familiar, such as from the Find Familiar spell.
+    Familiar,
+    /// A ranger's beast companion or primal companion.
+    BeastCompanion,
+    /// A Battle Smith artificer's steel defender.
+    SteelDefender,
+    /// Any other companion, such as a mount or a sidekick.
+    Other,
+}
+
+impl CompanionKind {
+    pub fn parse(string: &str) -> Option<CompanionKind> {
+        match string.trim().to_lowercase().as_ref() {
+            "familiar" => Some(CompanionKind::Familiar),
+            "beast companion" | "animal companion" | "primal companion" | "beast" => {
+                Some(CompanionKind::BeastCompanion)
+            }
+            "steel defender" => Some(CompanionKind::SteelDefender),
+            "companion" => Some(CompanionKind::Other),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            CompanionKind::Familiar => "Familiar",
+            CompanionKind::BeastCompanion => "Beast companion",
+            CompanionKind::SteelDefender => "Steel defender",
+            CompanionKind::Other => "Companion",
+        }
+    }
+}
+
+impl fmt::Display for CompanionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// A companion that belongs to a user, such as a familiar or a steel defender. The companion is
+/// stored as one of the user's characters, so it has its own abilities, proficiencies and attacks,
+/// but it never becomes the user's active character unless they switch to it.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Companion {
+    /// The name of the character that holds the companion's statistics.
+    pub name: String,
+    pub kind: CompanionKind,
+}
+
+impl Companion {
+    /// Register one of the user's characters as a companion, creating the character if it doesn't
+    /// exist yet. The user's active character doesn't change.
+    pub fn add(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        kind: CompanionKind,
+    ) -> RusqliteResult<usize> {
+        Character::create(connection, channel_id, user_id, name)?;
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &kind.as_str(),
+        ];
+        connection.execute(
+            "INSERT INTO character_companions (channel_id, user_id, name, kind) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET kind = $4",
+            params,
+        )
+    }
+
+    /// Retrieve the user's companions in a channel, in alphabetical order.
+    pub fn get_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<Vec<Companion>> {
+        let mut statement = connection.prepare(
+            "SELECT name, kind FROM character_companions \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             ORDER BY name",
+        )?;
+        let rows = statement.query_map(
+            &[&channel_id.to_string(), &user_id.to_string()],
+            Companion::from_row,
+        )?;
+        rows.collect()
+    }
+
+    /// Find one of the user's companions by its name, such as "Hoot", or by its kind, such as
+    /// "familiar". If the user has several companions of the kind, the first in alphabetical
+    /// order is used.
+    pub fn find(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name_or_kind: &str,
+    ) -> RusqliteResult<Option<Companion>> {
+        let mut companions = Companion::get_all(connection, channel_id, user_id)?;
+        let name_or_kind = name_or_kind.trim();
+        let kind = CompanionKind::parse(name_or_kind);
+        let position = companions
+            .iter()
+            .position(|companion| companion.name.to_lowercase() == name_or_kind.to_lowercase())
+            .or_else(|| {
+                companions
+                    .iter()
+                    .position(|companion| Some(companion.kind) == kind)
+            });
+        Ok(position.map(|position| companions.swap_remove(position)))
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<Companion> {
+        let kind: String = row.get("kind")?;
+        Ok(Companion {
+            name: row.get("name")?,
+            kind: CompanionKind::parse(&kind).unwrap_or(CompanionKind::Other),
+        })
+    }
+}
+
+impl fmt::Display for Companion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_kind() {
+        assert_eq!(
+            CompanionKind::parse("Familiar"),
+            Some(CompanionKind::Familiar)
+        );
+        assert_eq!(
+            CompanionKind::parse("primal companion"),
+            Some(CompanionKind::BeastCompanion)
+        );
+        assert_eq!(
+            CompanionKind::parse(CompanionKind::SteelDefender.as_str()),
+            Some(CompanionKind::SteelDefender)
+        );
+        assert_eq!(CompanionKind::parse("pet rock"), None);
+    }
+}
@@ -0,0 +1,61 @@
+use serde::de::DeserializeOwned;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// The environment variable naming a directory of content files, which are used in place of the
+/// entries with the same names in the content embedded in the application.
+pub const CONTENT_PATH_VARIABLE: &str = "CONTENT_PATH";
+
+/// An error loading or validating a content file, which stops the application from starting so
+/// that mistakes in the content are found straight away.
+#[derive(Debug)]
+pub enum Error {
+    IoError(PathBuf, io::Error),
+    ParseError(String, serde_json::Error),
+    InvalidEntry(String, String),
+    MissingEntry(String, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IoError(path, error) => {
+                write!(f, "Error reading {}: {}", path.display(), error)
+            }
+            Error::ParseError(file_name, error) => {
+                write!(f, "Error parsing {}: {}", file_name, error)
+            }
+            Error::InvalidEntry(file_name, message) => {
+                write!(f, "Invalid entry in {}: {}", file_name, message)
+            }
+            Error::MissingEntry(file_name, name) => {
+                write!(f, "Missing entry in {}: {}", file_name, name)
+            }
+        }
+    }
+}
+
+/// Load the entries of a content file, which is a JSON array. The entries embedded in the
+/// application come first, followed by the entries from the file of the same name in the content
+/// directory, if there is one, so that later entries can replace earlier ones.
+pub fn load<T: DeserializeOwned>(file_name: &str, embedded: &str) -> Result<Vec<T>, Error> {
+    let mut entries: Vec<T> = serde_json::from_str(embedded)
+        .map_err(|error| Error::ParseError(file_name.to_owned(), error))?;
+    if let Some(path) = override_path(file_name) {
+        let json = fs::read_to_string(&path).map_err(|error| Error::IoError(path, error))?;
+        let overrides: Vec<T> = serde_json::from_str(&json)
+            .map_err(|error| Error::ParseError(file_name.to_owned(), error))?;
+        entries.extend(overrides);
+    }
+    Ok(entries)
+}
+
+/// The path of a content file in the content directory, if the directory is set and has the file.
+fn override_path(file_name: &str) -> Option<PathBuf> {
+    env::var_os(CONTENT_PATH_VARIABLE)
+        .map(|directory| PathBuf::from(directory).join(file_name))
+        .filter(|path| path.is_file())
+}
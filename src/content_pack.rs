@@ -0,0 +1,361 @@
+use crate::monster::{self, ChallengeRating, Monster};
+use crate::roll::Roll;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serde::Deserialize;
+use serenity::model::id::{ChannelId, GuildId};
+use std::collections::HashSet;
+
+/// A monster pack, which bundles homebrew monsters, such as the monsters from an adventure, that an
+/// administrator installs for a guild. Once it's enabled, its monsters can be used in every channel
+/// in the guild.
+#[derive(Debug, PartialEq)]
+pub struct ContentPack {
+    pub name: String,
+    pub enabled: bool,
+    pub monsters: Vec<Monster>,
+}
+
+/// A monster pack as it's written, such as `{"name": "Sunless Citadel", "monsters": [...]}`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContentPackEntry {
+    name: String,
+    #[serde(default)]
+    monsters: Vec<MonsterEntry>,
+}
+
+/// A monster in a monster pack as it's written, such as `{"name": "Bog Hag", "armor_class": 14,
+/// "hit_points": 52, "attack_bonus": 5, "damage": "2d8+3", "attack_name": "claws",
+/// "challenge_rating": "3"}`. The attack name and challenge rating are optional.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MonsterEntry {
+    name: String,
+    armor_class: i32,
+    hit_points: i32,
+    attack_bonus: i32,
+    damage: String,
+    attack_name: Option<String>,
+    challenge_rating: Option<String>,
+}
+
+impl MonsterEntry {
+    fn to_monster(&self) -> Result<Monster, String> {
+        let damage = Roll::parse(&self.damage)
+            .map_err(|_| format!("{} has invalid damage \"{}\"", self.name, self.damage))?;
+        let challenge_rating = match &self.challenge_rating {
+            Some(challenge_rating) => {
+                Some(ChallengeRating::parse(challenge_rating).ok_or_else(|| {
+                    format!(
+                        "{} has an invalid challenge rating \"{}\"",
+                        self.name, challenge_rating
+                    )
+                })?)
+            }
+            None => None,
+        };
+        Monster::new(
+            &self.name,
+            self.armor_class,
+            self.hit_points,
+            self.attack_bonus,
+            damage,
+            self.attack_name.clone(),
+            challenge_rating,
+        )
+        .ok_or_else(|| {
+            format!(
+                "\"{}\" needs a name, an armour class from 1 to 30 and at least 1 hit point",
+                self.name
+            )
+        })
+    }
+}
+
+impl ContentPack {
+    /// Read a monster pack from JSON, checking every entry in it. Returns a description of each
+    /// problem with the pack if it's invalid.
+    pub fn from_json(json: &str) -> Result<ContentPack, Vec<String>> {
+        let entry: ContentPackEntry =
+            serde_json::from_str(json).map_err(|error| vec![error.to_string()])?;
+        let mut errors = Vec::new();
+        let name = entry.name.trim();
+        if name.is_empty() {
+            errors.push("the pack needs a name".to_owned());
+        }
+        if entry.monsters.is_empty() {
+            errors.push("the pack has no monsters".to_owned());
+        }
+        let mut names = HashSet::new();
+        let mut monsters = Vec::new();
+        for monster in entry.monsters.iter() {
+            match monster.to_monster() {
+                Ok(monster) => {
+                    if !names.insert(monster.name.to_lowercase()) {
+                        errors.push(format!("{} is in the pack more than once", monster.name));
+                    }
+                    monsters.push(monster);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+        if errors.is_empty() {
+            Ok(ContentPack {
+                name: name.to_owned(),
+                enabled: true,
+                monsters,
+            })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Find the names in the pack that are already in use, either by the common monsters from the
+    /// SRD, the monsters saved in the channel, or the monsters in another pack enabled for the
+    /// guild. Returns a description of each conflict.
+    pub fn conflicts(
+        &self,
+        connection: &Connection,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<Vec<String>> {
+        let mut statement = connection.prepare(
+            "SELECT content_pack_monsters.pack_name \
+             FROM content_pack_monsters \
+             INNER JOIN content_packs \
+             ON content_packs.guild_id = content_pack_monsters.guild_id \
+             AND content_packs.name = content_pack_monsters.pack_name \
+             WHERE content_packs.guild_id = $1 \
+             AND content_packs.enabled \
+             AND content_packs.name <> $2 \
+             AND content_pack_monsters.name = $3",
+        )?;
+        let mut conflicts = Vec::new();
+        for monster in self.monsters.iter() {
+            let params: &[&dyn ToSql] = &[&guild_id.to_string(), &self.name, &monster.name];
+            let pack_name: Option<String> = statement
+                .query_row(params, |row| row.get("pack_name"))
+                .optional()?;
+            if monster::is_common_monster(&monster.name) {
+                conflicts.push(format!("{} is a monster from the SRD", monster.name));
+            } else if Monster::get(connection, channel_id, &monster.name)?.is_some() {
+                conflicts.push(format!("{} is saved in this channel", monster.name));
+            } else if let Some(pack_name) = pack_name {
+                conflicts.push(format!(
+                    "{} is in the monster pack {}",
+                    monster.name, pack_name
+                ));
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Retrieve a monster pack installed for a guild by name.
+    pub fn get(
+        connection: &Connection,
+        guild_id: GuildId,
+        name: &str,
+    ) -> RusqliteResult<Option<ContentPack>> {
+        let params: &[&dyn ToSql] = &[&guild_id.to_string(), &name];
+        let pack = connection
+            .query_row(
+                "SELECT name, enabled FROM content_packs WHERE guild_id = $1 AND name = $2",
+                params,
+                ContentPack::from_row,
+            )
+            .optional()?;
+        match pack {
+            Some(pack) => pack.with_monsters(connection, guild_id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieve every monster pack installed for a guild, in alphabetical order.
+    pub fn get_all(connection: &Connection, guild_id: GuildId) -> RusqliteResult<Vec<ContentPack>> {
+        let mut statement = connection
+            .prepare("SELECT name, enabled FROM content_packs WHERE guild_id = $1 ORDER BY name")?;
+        let packs = statement
+            .query_map(&[&guild_id.to_string()], ContentPack::from_row)?
+            .collect::<RusqliteResult<Vec<ContentPack>>>()?;
+        packs
+            .into_iter()
+            .map(|pack| pack.with_monsters(connection, guild_id))
+            .collect()
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<ContentPack> {
+        Ok(ContentPack {
+            name: row.get("name")?,
+            enabled: row.get("enabled")?,
+            monsters: Vec::new(),
+        })
+    }
+
+    fn with_monsters(
+        mut self,
+        connection: &Connection,
+        guild_id: GuildId,
+    ) -> RusqliteResult<ContentPack> {
+        let params: &[&dyn ToSql] = &[&guild_id.to_string(), &self.name];
+        let mut statement = connection.prepare(
+            "SELECT name, armor_class, hit_points, attack_bonus, damage, attack_name, challenge_rating \
+             FROM content_pack_monsters \
+             WHERE guild_id = $1 \
+             AND pack_name = $2 \
+             ORDER BY name",
+        )?;
+        self.monsters = statement
+            .query_map(params, Monster::from_row)?
+            .collect::<RusqliteResult<Vec<Monster>>>()?;
+        Ok(self)
+    }
+
+    /// Install the pack for a guild, replacing the contents of any pack with the same name.
+    pub fn save(&self, connection: &mut Connection, guild_id: GuildId) -> RusqliteResult<()> {
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[&guild_id.to_string(), &self.name, &self.enabled];
+        transaction.execute(
+            "INSERT INTO content_packs (guild_id, name, enabled) \
+             VALUES ($1, $2, $3) \
+             ON CONFLICT (guild_id, name) DO UPDATE SET \
+             name = excluded.name, \
+             enabled = excluded.enabled",
+            params,
+        )?;
+        transaction.execute(
+            "DELETE FROM content_pack_monsters WHERE guild_id = $1 AND pack_name = $2",
+            &[&guild_id.to_string(), &self.name],
+        )?;
+        for monster in self.monsters.iter() {
+            let params: &[&dyn ToSql] = &[
+                &guild_id.to_string(),
+                &self.name,
+                &monster.name,
+                &monster.armor_class,
+                &monster.hit_points,
+                &monster.attack_bonus,
+                &monster.damage.to_string(),
+                &monster.attack_name,
+                &monster.challenge_rating.to_string(),
+            ];
+            transaction.execute(
+                "INSERT INTO content_pack_monsters (guild_id, pack_name, name, armor_class, hit_points, attack_bonus, damage, attack_name, challenge_rating) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                params,
+            )?;
+        }
+        transaction.commit()
+    }
+
+    /// Enable or disable a monster pack installed for a guild. Returns false if there's no pack
+    /// with the name.
+    pub fn set_enabled(
+        connection: &Connection,
+        guild_id: GuildId,
+        name: &str,
+        enabled: bool,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&guild_id.to_string(), &name, &enabled];
+        connection
+            .execute(
+                "UPDATE content_packs SET enabled = $3 WHERE guild_id = $1 AND name = $2",
+                params,
+            )
+            .map(|rows| rows > 0)
+    }
+
+    /// Remove a monster pack and its contents from a guild. Returns false if there's no pack with
+    /// the name.
+    pub fn delete(
+        connection: &mut Connection,
+        guild_id: GuildId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[&guild_id.to_string(), &name];
+        transaction.execute(
+            "DELETE FROM content_pack_monsters WHERE guild_id = $1 AND pack_name = $2",
+            params,
+        )?;
+        let deleted = transaction.execute(
+            "DELETE FROM content_packs WHERE guild_id = $1 AND name = $2",
+            params,
+        )?;
+        transaction.commit()?;
+        Ok(deleted > 0)
+    }
+}
+
+/// Retrieve every monster from the monster packs enabled for a guild, in alphabetical order.
+pub fn get_monsters(connection: &Connection, guild_id: GuildId) -> RusqliteResult<Vec<Monster>> {
+    let mut statement = connection.prepare(
+        "SELECT content_pack_monsters.name, armor_class, hit_points, attack_bonus, damage, attack_name, challenge_rating \
+         FROM content_pack_monsters \
+         INNER JOIN content_packs \
+         ON content_packs.guild_id = content_pack_monsters.guild_id \
+         AND content_packs.name = content_pack_monsters.pack_name \
+         WHERE content_packs.guild_id = $1 \
+         AND content_packs.enabled \
+         ORDER BY content_pack_monsters.name",
+    )?;
+    let rows = statement.query_map(&[&guild_id.to_string()], Monster::from_row)?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PACK: &str = r#"{
+        "name": "Sunless Citadel",
+        "monsters": [
+            {"name": "Meepo", "armor_class": 13, "hit_points": 7, "attack_bonus": 4, "damage": "1d4+2", "attack_name": "dagger", "challenge_rating": "1/4"},
+            {"name": "Belak", "armor_class": 12, "hit_points": 33, "attack_bonus": 3, "damage": "1d6+1"}
+        ]
+    }"#;
+
+    fn monster_names(json: &str) -> Option<Vec<String>> {
+        ContentPack::from_json(json).ok().map(|pack| {
+            pack.monsters
+                .into_iter()
+                .map(|monster| monster.name)
+                .collect()
+        })
+    }
+
+    fn error_count(json: &str) -> usize {
+        ContentPack::from_json(json)
+            .err()
+            .map_or(0, |errors| errors.len())
+    }
+
+    #[test]
+    fn test_from_json() {
+        assert_eq!(
+            monster_names(PACK),
+            Some(vec!["Meepo".to_owned(), "Belak".to_owned()])
+        );
+        assert_eq!(
+            ContentPack::from_json(PACK)
+                .ok()
+                .and_then(|pack| pack.monsters.into_iter().next())
+                .map(|monster| monster.challenge_rating),
+            Some(ChallengeRating::Quarter)
+        );
+        assert_eq!(error_count(r#"{"name": "Empty"}"#), 1);
+        assert_eq!(error_count(r#"{"name": "Spells", "spells": []}"#), 1);
+        assert_eq!(
+            error_count(
+                r#"{"name": " ", "monsters": [
+                    {"name": "Meepo", "armor_class": 13, "hit_points": 7, "attack_bonus": 4, "damage": "1d4+2"},
+                    {"name": "meepo", "armor_class": 13, "hit_points": 7, "attack_bonus": 4, "damage": "1d4+2"},
+                    {"name": "Belak", "armor_class": 40, "hit_points": 33, "attack_bonus": 3, "damage": "1d6+1"},
+                    {"name": "Calcryx", "armor_class": 16, "hit_points": 39, "attack_bonus": 5, "damage": "lots"}
+                ]}"#
+            ),
+            4
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::id::{ChannelId, UserId};
+use std::fmt;
+
+/// A short or long rest, which restores the counters that reset on that kind of rest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rest {
+    Short,
+    Long,
+}
+
+impl Rest {
+    pub fn parse(string: &str) -> Option<Rest> {
+        match string.trim().to_lowercase().as_str() {
+            "short" | "short rest" | "sr" => Some(Rest::Short),
+            "long" | "long rest" | "lr" => Some(Rest::Long),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Rest::Short => "short rest",
+            Rest::Long => "long rest",
+        }
+    }
+
+    /// Whether this rest restores a counter that resets on the given kind of rest. A long rest
+    /// also restores everything a short rest does.
+    pub fn restores(self, reset: Rest) -> bool {
+        self == Rest::Long || reset == Rest::Short
+    }
+}
+
+/// A named resource a character spends and regains, such as ki points, sorcery points or uses of
+/// rage, for class features that aren't tracked any other way.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Counter {
+    pub name: String,
+    pub value: i32,
+    pub maximum: i32,
+    /// The kind of rest that restores the counter to its maximum, if any.
+    pub reset: Option<Rest>,
+}
+
+impl Counter {
+    /// Create a full counter, or None if the name is empty or the maximum isn't between 1 and
+    /// 1000.
+    pub fn new(name: &str, maximum: i32, reset: Option<Rest>) -> Option<Counter> {
+        let name = name.trim();
+        if name.is_empty() || maximum < 1 || maximum > 1000 {
+            None
+        } else {
+            Some(Counter {
+                name: name.to_owned(),
+                value: maximum,
+                maximum,
+                reset,
+            })
+        }
+    }
+
+    /// Spend some of the counter. Returns false, leaving the counter unchanged, if there isn't
+    /// enough left.
+    pub fn spend(&mut self, amount: i32) -> bool {
+        if amount > self.value {
+            false
+        } else {
+            self.value -= amount;
+            true
+        }
+    }
+
+    /// Restore some of the counter, or all of it if no amount is given, up to its maximum.
+    pub fn restore(&mut self, amount: Option<i32>) {
+        self.value = amount.map_or(self.maximum, |amount| {
+            (self.value + amount).min(self.maximum)
+        });
+    }
+
+    /// Retrieve a character's counters, in the order they were added.
+    pub fn get_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Vec<Counter>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let mut statement = connection.prepare(
+            "SELECT counter, value, maximum, reset \
+             FROM character_counters \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(params, Counter::from_row)?;
+        rows.collect()
+    }
+
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        counter: &str,
+    ) -> RusqliteResult<Option<Counter>> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &counter,
+        ];
+        connection
+            .query_row(
+                "SELECT counter, value, maximum, reset \
+                 FROM character_counters \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3 \
+                 AND counter = $4",
+                params,
+                Counter::from_row,
+            )
+            .optional()
+    }
+
+    /// Find one of a character's counters by name, allowing for "points" at the end of the name,
+    /// so that "ki points" finds a counter named "Ki", and "sorcery" finds one named "Sorcery
+    /// Points".
+    pub fn find(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        counter: &str,
+    ) -> RusqliteResult<Option<Counter>> {
+        let counter = counter.trim();
+        let lowercase = counter.to_lowercase();
+        let mut candidates = vec![counter.to_owned(), format!("{} points", counter)];
+        for suffix in [" points", " point"].iter() {
+            if lowercase.ends_with(suffix) {
+                if let Some(without_suffix) = counter.get(..counter.len() - suffix.len()) {
+                    candidates.push(without_suffix.to_owned());
+                }
+            }
+        }
+        for candidate in candidates {
+            if let Some(counter) = Counter::get(connection, channel_id, user_id, name, &candidate)?
+            {
+                return Ok(Some(counter));
+            }
+        }
+        Ok(None)
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<Counter> {
+        let reset: Option<String> = row.get("reset")?;
+        Ok(Counter {
+            name: row.get("counter")?,
+            value: row.get("value")?,
+            maximum: row.get("maximum")?,
+            reset: reset.as_deref().and_then(Rest::parse),
+        })
+    }
+
+    /// Save the counter for a character, replacing any counter with the same name.
+    pub fn set(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &self.name,
+            &self.value,
+            &self.maximum,
+            &self.reset.map(|reset| reset.as_str()),
+        ];
+        connection.execute(
+            "INSERT INTO character_counters (channel_id, user_id, name, counter, value, maximum, reset) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) \
+             ON CONFLICT (channel_id, user_id, name, counter) DO UPDATE SET \
+             counter = $4, value = $5, maximum = $6, reset = $7",
+            params,
+        )
+    }
+
+    /// Remove one of a character's counters. Returns the number of counters removed.
+    pub fn remove(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        counter: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &counter,
+        ];
+        connection.execute(
+            "DELETE FROM character_counters \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             AND counter = $4",
+            params,
+        )
+    }
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}/{}", self.name, self.value, self.maximum)?;
+        match self.reset {
+            Some(reset) => write!(f, " (resets on a {})", reset.as_str()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Take a rest, restoring each of a character's counters that resets on that kind of rest.
+/// Returns the counters that were restored.
+pub fn rest(
+    connection: &mut Connection,
+    channel_id: ChannelId,
+    user_id: UserId,
+    name: &str,
+    rest: Rest,
+) -> RusqliteResult<Vec<Counter>> {
+    let transaction = connection.transaction()?;
+    let mut restored = Vec::new();
+    for mut counter in Counter::get_all(&transaction, channel_id, user_id, name)? {
+        if counter.value < counter.maximum
+            && counter.reset.map_or(false, |reset| rest.restores(reset))
+        {
+            counter.restore(None);
+            counter.set(&transaction, channel_id, user_id, name)?;
+            restored.push(counter);
+        }
+    }
+    transaction.commit()?;
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spend_and_restore() {
+        let mut ki = Counter::new("Ki", 5, Some(Rest::Short)).unwrap();
+        assert!(ki.spend(2));
+        assert_eq!(ki.value, 3);
+        assert!(!ki.spend(4));
+        assert_eq!(ki.value, 3);
+        ki.restore(Some(5));
+        assert_eq!(ki.value, 5);
+        assert!(ki.spend(5));
+        ki.restore(None);
+        assert_eq!(ki.value, 5);
+        assert_eq!(ki.to_string(), "Ki: 5/5 (resets on a short rest)");
+        assert_eq!(Counter::new("Rage", 0, None), None);
+    }
+
+    #[test]
+    fn test_rest_restores() {
+        assert!(Rest::Short.restores(Rest::Short));
+        assert!(!Rest::Short.restores(Rest::Long));
+        assert!(Rest::Long.restores(Rest::Short));
+        assert!(Rest::Long.restores(Rest::Long));
+    }
+}
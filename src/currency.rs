@@ -0,0 +1,535 @@
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::id::{ChannelId, UserId};
+use std::cmp::min;
+use std::fmt;
+
+/// A denomination of coin in Dungeons and Dragons 5th edition.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Denomination {
+    Copper,
+    Silver,
+    Electrum,
+    Gold,
+    Platinum,
+}
+
+/// The most coins of a denomination that can be gained or spent at once, or carried, so that a
+/// purse's counts can't overflow however many times coins are gained.
+pub const MAXIMUM_COINS: i32 = 1_000_000;
+
+/// All denominations, ordered from least to most valuable.
+pub const DENOMINATIONS: [Denomination; 5] = [
+    Denomination::Copper,
+    Denomination::Silver,
+    Denomination::Electrum,
+    Denomination::Gold,
+    Denomination::Platinum,
+];
+
+/// The denominations that are used when giving change, ordered from most to least valuable.
+///
+/// Electrum pieces are unusual, so change is never given in electrum.
+const CHANGE_DENOMINATIONS: [Denomination; 4] = [
+    Denomination::Platinum,
+    Denomination::Gold,
+    Denomination::Silver,
+    Denomination::Copper,
+];
+
+impl Denomination {
+    pub fn parse(string: &str) -> Option<Denomination> {
+        match string.to_lowercase().as_ref() {
+            "cp" | "copper" => Some(Denomination::Copper),
+            "sp" | "silver" => Some(Denomination::Silver),
+            "ep" | "electrum" => Some(Denomination::Electrum),
+            "gp" | "gold" => Some(Denomination::Gold),
+            "pp" | "platinum" => Some(Denomination::Platinum),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Denomination::Copper => "Copper",
+            Denomination::Silver => "Silver",
+            Denomination::Electrum => "Electrum",
+            Denomination::Gold => "Gold",
+            Denomination::Platinum => "Platinum",
+        }
+    }
+
+    pub fn abbreviation(&self) -> &str {
+        match self {
+            Denomination::Copper => "cp",
+            Denomination::Silver => "sp",
+            Denomination::Electrum => "ep",
+            Denomination::Gold => "gp",
+            Denomination::Platinum => "pp",
+        }
+    }
+
+    /// The value of a single coin of this denomination, in copper pieces.
+    pub fn value(&self) -> i64 {
+        match self {
+            Denomination::Copper => 1,
+            Denomination::Silver => 10,
+            Denomination::Electrum => 50,
+            Denomination::Gold => 100,
+            Denomination::Platinum => 1000,
+        }
+    }
+}
+
+impl fmt::Display for Denomination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.abbreviation().fmt(f)
+    }
+}
+
+/// An amount of coins of a single denomination, such as "15 gp".
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Amount {
+    pub count: i32,
+    pub denomination: Denomination,
+}
+
+impl Amount {
+    pub fn value(&self) -> i64 {
+        i64::from(self.count) * self.denomination.value()
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.count, self.denomination)
+    }
+}
+
+/// A change to the coins carried by a character.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Transaction {
+    Gain(Amount),
+    Spend(Amount),
+}
+
+/// Represents an error that might occur when gaining or spending coins.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    InsufficientFunds,
+    /// Gaining the coins would leave more than the most coins of a denomination that can be
+    /// carried.
+    TooManyCoins,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InsufficientFunds => write!(f, "Not enough coins."),
+            Error::TooManyCoins => write!(f, "Too many coins."),
+        }
+    }
+}
+
+/// The coins carried by a character, in each denomination.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Coins {
+    copper: i32,
+    silver: i32,
+    electrum: i32,
+    gold: i32,
+    platinum: i32,
+}
+
+impl Coins {
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Coins> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection
+            .query_row(
+                "SELECT copper, silver, electrum, gold, platinum \
+                 FROM character_coins \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3",
+                params,
+                Coins::from_row,
+            )
+            .optional()
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Retrieve the coins of every character in a channel that has any coins recorded.
+    pub fn get_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<Vec<(UserId, String, Coins)>> {
+        let mut statement = connection.prepare(
+            "SELECT user_id, name, copper, silver, electrum, gold, platinum \
+             FROM character_coins \
+             WHERE channel_id = $1 \
+             ORDER BY user_id, name",
+        )?;
+        let rows = statement.query_map(&[&channel_id.to_string()], |row| {
+            let user_id: String = row.get("user_id")?;
+            Ok((user_id, row.get("name")?, Coins::from_row(row)?))
+        })?;
+        rows.map(|row| {
+            row.map(|(user_id, name, coins)| {
+                (UserId(user_id.parse().unwrap_or_default()), name, coins)
+            })
+        })
+        .collect()
+    }
+
+    pub fn set(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &self.copper,
+            &self.silver,
+            &self.electrum,
+            &self.gold,
+            &self.platinum,
+        ];
+        connection.execute(
+            "INSERT INTO character_coins (channel_id, user_id, name, copper, silver, electrum, gold, platinum) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET \
+             copper = $4, silver = $5, electrum = $6, gold = $7, platinum = $8",
+            params,
+        )
+    }
+
+    /// Retrieve the coins in the party's shared stash in a channel, which don't belong to any
+    /// character yet.
+    pub fn get_party(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<Coins> {
+        connection
+            .query_row(
+                "SELECT copper, silver, electrum, gold, platinum \
+                 FROM party_coins \
+                 WHERE channel_id = $1",
+                &[&channel_id.to_string()],
+                Coins::from_row,
+            )
+            .optional()
+            .map(Option::unwrap_or_default)
+    }
+
+    pub fn set_party(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &self.copper,
+            &self.silver,
+            &self.electrum,
+            &self.gold,
+            &self.platinum,
+        ];
+        connection.execute(
+            "INSERT INTO party_coins (channel_id, copper, silver, electrum, gold, platinum) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (channel_id) DO UPDATE SET \
+             copper = $2, silver = $3, electrum = $4, gold = $5, platinum = $6",
+            params,
+        )
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<Coins> {
+        Ok(Coins {
+            copper: row.get("copper")?,
+            silver: row.get("silver")?,
+            electrum: row.get("electrum")?,
+            gold: row.get("gold")?,
+            platinum: row.get("platinum")?,
+        })
+    }
+
+    pub fn count(&self, denomination: Denomination) -> i32 {
+        match denomination {
+            Denomination::Copper => self.copper,
+            Denomination::Silver => self.silver,
+            Denomination::Electrum => self.electrum,
+            Denomination::Gold => self.gold,
+            Denomination::Platinum => self.platinum,
+        }
+    }
+
+    fn count_mut(&mut self, denomination: Denomination) -> &mut i32 {
+        match denomination {
+            Denomination::Copper => &mut self.copper,
+            Denomination::Silver => &mut self.silver,
+            Denomination::Electrum => &mut self.electrum,
+            Denomination::Gold => &mut self.gold,
+            Denomination::Platinum => &mut self.platinum,
+        }
+    }
+
+    /// The total value of all coins, in copper pieces.
+    pub fn value(&self) -> i64 {
+        DENOMINATIONS
+            .iter()
+            .map(|denomination| i64::from(self.count(*denomination)) * denomination.value())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        DENOMINATIONS.iter().all(|d| self.count(*d) == 0)
+    }
+
+    /// Gain an amount of coins. Fails if it would leave more than the most coins of the
+    /// denomination that can be carried.
+    pub fn add(&self, amount: Amount) -> Result<Coins, Error> {
+        let mut coins = *self;
+        let count = coins.count_mut(amount.denomination);
+        *count = count
+            .checked_add(amount.count)
+            .filter(|count| *count <= MAXIMUM_COINS)
+            .ok_or(Error::TooManyCoins)?;
+        Ok(coins)
+    }
+
+    /// The coins of several purses put together, such as for the party's total. The total isn't
+    /// carried by anyone, so it isn't limited to the most coins that can be carried, but it stops
+    /// at the most that can be counted.
+    pub fn sum<'a, I: IntoIterator<Item = &'a Coins>>(iter: I) -> Coins {
+        iter.into_iter().fold(Coins::default(), |mut total, coins| {
+            for denomination in DENOMINATIONS.iter() {
+                let count = total.count_mut(*denomination);
+                *count = count.saturating_add(coins.count(*denomination));
+            }
+            total
+        })
+    }
+
+    /// Spend an amount of coins, converting between denominations if there aren't enough coins
+    /// of the requested denomination.
+    ///
+    /// Coins of the requested denomination are spent first. Any shortfall is then paid by
+    /// breaking the least valuable larger coins available, with change given in the requested
+    /// denomination or smaller, and finally by paying with smaller coins.
+    pub fn spend(&self, amount: Amount) -> Result<Coins, Error> {
+        if amount.value() > self.value() {
+            return Err(Error::InsufficientFunds);
+        }
+
+        let mut coins = *self;
+        let paid = min(coins.count(amount.denomination), amount.count);
+        *coins.count_mut(amount.denomination) -= paid;
+        let mut remaining = i64::from(amount.count - paid) * amount.denomination.value();
+
+        // Break larger coins, starting with the least valuable
+        for denomination in DENOMINATIONS
+            .iter()
+            .filter(|d| d.value() > amount.denomination.value())
+        {
+            if remaining == 0 {
+                break;
+            }
+            let needed = (remaining + denomination.value() - 1) / denomination.value();
+            let broken = min(i64::from(coins.count(*denomination)), needed);
+            // No more coins are broken than there are, so the count still fits
+            *coins.count_mut(*denomination) -= broken as i32;
+            let change = broken * denomination.value() - remaining;
+            if change >= 0 {
+                coins = coins.give_change(change, amount.denomination);
+                remaining = 0;
+            } else {
+                remaining = -change;
+            }
+        }
+
+        // Pay any remainder with smaller coins, starting with the most valuable
+        for denomination in DENOMINATIONS
+            .iter()
+            .rev()
+            .filter(|d| d.value() < amount.denomination.value())
+        {
+            if remaining == 0 {
+                break;
+            }
+            let needed = (remaining + denomination.value() - 1) / denomination.value();
+            let used = min(i64::from(coins.count(*denomination)), needed);
+            *coins.count_mut(*denomination) -= used as i32;
+            let change = used * denomination.value() - remaining;
+            if change >= 0 {
+                coins = coins.give_change(change, Denomination::Copper);
+                remaining = 0;
+            } else {
+                remaining = -change;
+            }
+        }
+
+        Ok(coins)
+    }
+
+    /// Give change worth less than the coin that was broken, so it can't overflow the counts,
+    /// even though it can leave slightly more than the most coins that can be carried.
+    fn give_change(&self, value: i64, largest: Denomination) -> Coins {
+        CHANGE_DENOMINATIONS
+            .iter()
+            .filter(|d| d.value() <= largest.value())
+            .fold((*self, value), |(mut coins, value), denomination| {
+                let count = value / denomination.value();
+                *coins.count_mut(*denomination) += count as i32;
+                (coins, value - count * denomination.value())
+            })
+            .0
+    }
+}
+
+impl fmt::Display for Coins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut iter = DENOMINATIONS
+            .iter()
+            .rev()
+            .filter(|d| self.count(**d) != 0)
+            .map(|d| Amount {
+                count: self.count(*d),
+                denomination: *d,
+            });
+        match iter.next() {
+            Some(head) => iter.fold(write!(f, "{}", head), |result, amount| {
+                result.and(write!(f, ", {}", amount))
+            }),
+            None => write!(f, "0 gp"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn coins(copper: i32, silver: i32, electrum: i32, gold: i32, platinum: i32) -> Coins {
+        Coins {
+            copper,
+            silver,
+            electrum,
+            gold,
+            platinum,
+        }
+    }
+
+    fn gold(count: i32) -> Amount {
+        Amount {
+            count,
+            denomination: Denomination::Gold,
+        }
+    }
+
+    #[test]
+    fn test_spend_same_denomination() {
+        assert_eq!(
+            coins(0, 0, 0, 20, 0).spend(gold(15)),
+            Ok(coins(0, 0, 0, 5, 0))
+        );
+    }
+
+    #[test]
+    fn test_spend_breaks_larger_coin() {
+        assert_eq!(
+            coins(0, 0, 0, 10, 1).spend(gold(15)),
+            Ok(coins(0, 0, 0, 5, 0))
+        );
+    }
+
+    #[test]
+    fn test_spend_breaks_least_valuable_larger_coin() {
+        let silver = Amount {
+            count: 5,
+            denomination: Denomination::Silver,
+        };
+        assert_eq!(coins(0, 0, 0, 1, 1).spend(silver), Ok(coins(0, 5, 0, 0, 1)));
+    }
+
+    #[test]
+    fn test_spend_uses_smaller_coins() {
+        assert_eq!(
+            coins(10, 9, 0, 0, 0).spend(gold(1)),
+            Ok(coins(0, 0, 0, 0, 0))
+        );
+        assert_eq!(
+            coins(5, 9, 2, 0, 0).spend(gold(1)),
+            Ok(coins(5, 9, 0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_spend_insufficient_funds() {
+        assert_eq!(
+            coins(0, 0, 0, 10, 0).spend(gold(15)),
+            Err(Error::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn test_add() {
+        assert_eq!(
+            coins(0, 0, 0, 5, 0).add(gold(10)),
+            Ok(coins(0, 0, 0, 15, 0))
+        );
+        assert_eq!(
+            coins(0, 0, 0, MAXIMUM_COINS - 10, 0).add(gold(10)),
+            Ok(coins(0, 0, 0, MAXIMUM_COINS, 0))
+        );
+        assert_eq!(
+            coins(0, 0, 0, MAXIMUM_COINS - 10, 0).add(gold(11)),
+            Err(Error::TooManyCoins)
+        );
+        assert_eq!(
+            coins(0, 0, 0, i32::MAX, 0).add(gold(1)),
+            Err(Error::TooManyCoins)
+        );
+    }
+
+    #[test]
+    fn test_value_at_the_limit() {
+        let full = coins(
+            MAXIMUM_COINS,
+            MAXIMUM_COINS,
+            MAXIMUM_COINS,
+            MAXIMUM_COINS,
+            MAXIMUM_COINS,
+        );
+        assert_eq!(full.value(), 1_161_000_000);
+        assert_eq!(Coins::sum(&[full, full]).value(), 2_322_000_000);
+        assert_eq!(
+            Coins::sum(&[coins(0, 0, 0, 0, i32::MAX), coins(0, 0, 0, 0, 1)]).value(),
+            i64::from(i32::MAX) * 1000
+        );
+        let platinum = Amount {
+            count: MAXIMUM_COINS,
+            denomination: Denomination::Platinum,
+        };
+        assert_eq!(
+            coins(0, 0, 0, 0, MAXIMUM_COINS).spend(platinum),
+            Ok(Coins::default())
+        );
+        assert_eq!(
+            coins(0, 0, 0, MAXIMUM_COINS, 0).spend(platinum),
+            Err(Error::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn test_display_coins() {
+        assert_eq!(coins(3, 0, 0, 12, 1).to_string(), "1 pp, 12 gp, 3 cp");
+        assert_eq!(Coins::default().to_string(), "0 gp");
+    }
+}
@@ -0,0 +1,165 @@
+use log::{error, info};
+use serenity::http::HttpError;
+use serenity::Error as SerenityError;
+use serenity::Result as SerenityResult;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How many times a request to Discord is attempted before giving up.
+const MAXIMUM_ATTEMPTS: u32 = 3;
+
+/// How long to wait before the first retry. Each later retry waits twice as long as the last.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Discord's error code for a request that the bot doesn't have permission to make, such as
+/// sending an embed without the Embed Links permission.
+const MISSING_PERMISSIONS_ERROR_CODE: isize = 50013;
+
+/// Counts of the requests made to Discord to send and delete messages, and how many of them had
+/// to be retried or failed, so that problems with Discord show up in the logs.
+#[derive(Debug, Default)]
+pub struct DeliveryMetrics {
+    pub attempts: AtomicU64,
+    pub retries: AtomicU64,
+    pub failures: AtomicU64,
+    pub plain_text_fallbacks: AtomicU64,
+}
+
+impl DeliveryMetrics {
+    /// Make a request to Discord, retrying it with exponential backoff if it fails for a reason
+    /// that might go away by itself, such as a server error or a dropped connection.
+    pub fn retry<T, F>(&self, description: &str, mut request: F) -> SerenityResult<T>
+    where
+        F: FnMut() -> SerenityResult<T>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.attempts.fetch_add(1, Ordering::Relaxed);
+            attempt += 1;
+            match request() {
+                Err(ref error) if attempt < MAXIMUM_ATTEMPTS && is_transient(error) => {
+                    self.retries.fetch_add(1, Ordering::Relaxed);
+                    let delay = backoff(attempt);
+                    info!(target: "dungeon-helper", "Retrying request to Discord. Request: {}; Attempt: {}; Delay: {:?}; Error: {:?}; Metrics: {}", description, attempt, delay, error, self);
+                    thread::sleep(delay);
+                }
+                Err(error) => {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    error!(target: "dungeon-helper", "Request to Discord failed. Request: {}; Attempts: {}; Metrics: {}", description, attempt, self);
+                    return Err(error);
+                }
+                Ok(result) => return Ok(result),
+            }
+        }
+    }
+
+    /// Count a message that was sent as plain text because its embed couldn't be sent.
+    pub fn plain_text_fallback(&self) {
+        self.plain_text_fallbacks.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Display for DeliveryMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} attempts, {} retries, {} failures, {} plain text fallbacks",
+            self.attempts.load(Ordering::Relaxed),
+            self.retries.load(Ordering::Relaxed),
+            self.failures.load(Ordering::Relaxed),
+            self.plain_text_fallbacks.load(Ordering::Relaxed)
+        )
+    }
+}
+
+/// How long to wait after a failed attempt before trying again.
+fn backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF * 2u32.pow(attempt.saturating_sub(1))
+}
+
+/// Whether a request failed for a reason that might go away if it is made again: Discord being
+/// unavailable or rate limiting the bot, or the connection to Discord failing.
+pub fn is_transient(error: &SerenityError) -> bool {
+    match error {
+        SerenityError::Http(error) => match &**error {
+            HttpError::UnsuccessfulRequest(response) => {
+                response.status_code.is_server_error() || response.status_code.as_u16() == 429
+            }
+            HttpError::Request(_) => true,
+            _ => false,
+        },
+        SerenityError::Io(_) => true,
+        _ => false,
+    }
+}
+
+/// Whether a request failed because the bot doesn't have permission to make it in the channel.
+pub fn is_missing_permissions(error: &SerenityError) -> bool {
+    match error {
+        SerenityError::Http(error) => match &**error {
+            HttpError::UnsuccessfulRequest(response) => {
+                response.error.code == MISSING_PERMISSIONS_ERROR_CODE
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_millis(500));
+        assert_eq!(backoff(2), Duration::from_millis(1000));
+        assert_eq!(backoff(3), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn test_retry() {
+        let metrics = DeliveryMetrics::default();
+        let mut calls = 0;
+        let result: SerenityResult<()> = metrics.retry("test", || {
+            calls += 1;
+            Err(SerenityError::Other("not transient"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+        assert_eq!(
+            metrics.to_string(),
+            "1 attempts, 0 retries, 1 failures, 0 plain text fallbacks"
+        );
+
+        let metrics = DeliveryMetrics::default();
+        let mut calls = 0;
+        let result = metrics.retry("test", || {
+            calls += 1;
+            if calls < 2 {
+                Err(SerenityError::Io(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    "connection reset",
+                )))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.ok(), Some(2));
+        assert_eq!(
+            metrics.to_string(),
+            "2 attempts, 1 retries, 0 failures, 0 plain text fallbacks"
+        );
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let io_error = io::Error::new(io::ErrorKind::TimedOut, "timed out");
+        assert!(is_transient(&SerenityError::Io(io_error)));
+        assert!(!is_transient(&SerenityError::Other("other")));
+        assert!(!is_missing_permissions(&SerenityError::Other("other")));
+    }
+}
@@ -0,0 +1,382 @@
+use crate::character::{CharacterExport, WeaponProficiency};
+use crate::class::Class;
+use crate::weapon::{Category, WeaponName};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The D&D Beyond endpoint that serves the JSON for a public character sheet.
+const CHARACTER_URL: &str = "https://character-service.dndbeyond.com/character/v5/character/";
+
+const ABILITIES: [&str; 6] = [
+    "strength",
+    "dexterity",
+    "constitution",
+    "intelligence",
+    "wisdom",
+    "charisma",
+];
+
+const SKILLS: [&str; 18] = [
+    "acrobatics",
+    "animal-handling",
+    "arcana",
+    "athletics",
+    "deception",
+    "history",
+    "insight",
+    "intimidation",
+    "investigation",
+    "medicine",
+    "nature",
+    "perception",
+    "performance",
+    "persuasion",
+    "religion",
+    "sleight-of-hand",
+    "stealth",
+    "survival",
+];
+
+#[derive(Debug)]
+pub enum Error {
+    RequestError(reqwest::Error),
+    ParseError(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::RequestError(error) => write!(f, "D&D Beyond request error: {}", error),
+            Error::ParseError(error) => write!(f, "D&D Beyond parse error: {}", error),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CharacterResponse {
+    data: CharacterData,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CharacterData {
+    name: String,
+    #[serde(default)]
+    stats: Vec<Stat>,
+    #[serde(default)]
+    bonus_stats: Vec<Stat>,
+    #[serde(default)]
+    override_stats: Vec<Stat>,
+    #[serde(default)]
+    classes: Vec<ClassLevel>,
+    #[serde(default)]
+    modifiers: HashMap<String, Option<Vec<Modifier>>>,
+    decorations: Option<Decorations>,
+}
+
+#[derive(Deserialize)]
+struct Stat {
+    id: usize,
+    value: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClassLevel {
+    level: i32,
+    definition: ClassDefinition,
+    subclass_definition: Option<ClassDefinition>,
+}
+
+#[derive(Deserialize)]
+struct ClassDefinition {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Modifier {
+    #[serde(rename = "type")]
+    modifier_type: String,
+    sub_type: String,
+    value: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Decorations {
+    avatar_url: Option<String>,
+}
+
+/// Fetch a public character sheet from D&D Beyond.
+pub fn fetch(character_id: u64) -> Result<CharacterExport, Error> {
+    let url = format!("{}{}", CHARACTER_URL, character_id);
+    reqwest::blocking::get(&url)
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(Error::RequestError)
+        .and_then(|json| parse(&json))
+}
+
+/// Convert the JSON for a D&D Beyond character sheet into the attributes tracked by Dungeon
+/// Helper. Anything Dungeon Helper doesn't track, such as hit points or spells, is ignored.
+pub fn parse(json: &str) -> Result<CharacterExport, Error> {
+    serde_json::from_str::<CharacterResponse>(json)
+        .and_then(|response| to_export(response.data))
+        .map_err(Error::ParseError)
+}
+
+fn to_export(data: CharacterData) -> serde_json::Result<CharacterExport> {
+    let modifiers: Vec<&Modifier> = data
+        .modifiers
+        .values()
+        .flat_map(|modifiers| modifiers.iter().flatten())
+        .collect();
+    let has_modifier = |modifier_type: &str, sub_type: &str| {
+        modifiers
+            .iter()
+            .any(|m| m.modifier_type == modifier_type && m.sub_type == sub_type)
+    };
+
+    let mut character = Map::new();
+    character.insert("name".to_owned(), Value::from(data.name.trim()));
+    character.insert(
+        "avatar_url".to_owned(),
+        data.decorations
+            .as_ref()
+            .and_then(|decorations| decorations.avatar_url.as_ref())
+            .map_or(Value::Null, |avatar_url| Value::from(avatar_url.as_str())),
+    );
+    if !data.classes.is_empty() {
+        let level: i32 = data.classes.iter().map(|class| class.level).sum();
+        character.insert("level".to_owned(), Value::from(level));
+    }
+    // Classes from outside the Player's Handbook, such as the Artificer, aren't tracked, although
+    // their levels still count towards the character's level
+    let classes: Vec<Value> = data
+        .classes
+        .iter()
+        .filter_map(|class_level| {
+            Class::parse(&class_level.definition.name).map(|class| {
+                let mut value = Map::new();
+                value.insert("class".to_owned(), Value::from(class.as_str()));
+                value.insert(
+                    "subclass".to_owned(),
+                    class_level
+                        .subclass_definition
+                        .as_ref()
+                        .map_or(Value::Null, |subclass| Value::from(subclass.name.as_str())),
+                );
+                value.insert("level".to_owned(), Value::from(class_level.level));
+                Value::Object(value)
+            })
+        })
+        .collect();
+    character.insert("classes".to_owned(), Value::Array(classes));
+    if has_modifier("half-proficiency", "ability-checks") {
+        character.insert("jack_of_all_trades".to_owned(), Value::from(true));
+    }
+    // The Alert feat is imported as its +5 bonus, along with any other bonuses to initiative
+    let initiative_bonus: i32 = modifiers
+        .iter()
+        .filter(|m| m.modifier_type == "bonus" && m.sub_type == "initiative")
+        .filter_map(|m| m.value)
+        .sum();
+    if initiative_bonus != 0 {
+        character.insert("initiative_bonus".to_owned(), Value::from(initiative_bonus));
+    }
+
+    for (index, ability) in ABILITIES.iter().enumerate() {
+        let score = ability_score(&data, &modifiers, index + 1, ability);
+        character.insert(
+            (*ability).to_owned(),
+            score.map_or(Value::Null, Value::from),
+        );
+        character.insert(
+            format!("{}_saving_proficiency", ability),
+            Value::from(has_modifier(
+                "proficiency",
+                format!("{}-saving-throws", ability).as_str(),
+            )),
+        );
+    }
+
+    for skill in SKILLS.iter() {
+        let proficiency = if has_modifier("expertise", *skill) {
+            "Expert"
+        } else if has_modifier("proficiency", *skill) {
+            "Proficient"
+        } else {
+            "Normal"
+        };
+        character.insert(
+            format!("{}_proficiency", skill.replace('-', "_")),
+            Value::from(proficiency),
+        );
+    }
+
+    let mut weapon_proficiencies = Vec::new();
+    modifiers
+        .iter()
+        .filter(|m| m.modifier_type == "proficiency")
+        .filter_map(|m| weapon_proficiency(&m.sub_type))
+        .for_each(|proficiency| {
+            if !weapon_proficiencies.contains(&proficiency) {
+                weapon_proficiencies.push(proficiency);
+            }
+        });
+
+    serde_json::from_value(Value::Object(character)).map(|character| CharacterExport {
+        character,
+        weapon_proficiencies,
+    })
+}
+
+/// The ability score with the given D&D Beyond stat ID, after applying bonuses and overrides.
+fn ability_score(
+    data: &CharacterData,
+    modifiers: &[&Modifier],
+    id: usize,
+    ability: &str,
+) -> Option<i32> {
+    let sub_type = format!("{}-score", ability);
+    let modifier_values = |modifier_type: &str| {
+        modifiers
+            .iter()
+            .filter(|m| m.modifier_type == modifier_type && m.sub_type == sub_type)
+            .filter_map(|m| m.value)
+            .collect::<Vec<i32>>()
+    };
+    stat_value(&data.override_stats, id).or_else(|| {
+        stat_value(&data.stats, id).map(|base| {
+            let bonus = stat_value(&data.bonus_stats, id).unwrap_or(0)
+                + modifier_values("bonus").iter().sum::<i32>();
+            modifier_values("set")
+                .into_iter()
+                .fold(base + bonus, |score, set| score.max(set))
+        })
+    })
+}
+
+fn stat_value(stats: &[Stat], id: usize) -> Option<i32> {
+    stats
+        .iter()
+        .find(|stat| stat.id == id)
+        .and_then(|stat| stat.value)
+}
+
+fn weapon_proficiency(sub_type: &str) -> Option<WeaponProficiency> {
+    match sub_type {
+        "simple-weapons" => Some(WeaponProficiency::Category(Category::Simple)),
+        "martial-weapons" => Some(WeaponProficiency::Category(Category::Martial)),
+        // D&D Beyond names crossbows by type last, e.g. "crossbow-hand"
+        "crossbow-hand" => Some(WeaponProficiency::Weapon(WeaponName::CrossbowHand)),
+        "crossbow-heavy" => Some(WeaponProficiency::Weapon(WeaponName::CrossbowHeavy)),
+        "crossbow-light" => Some(WeaponProficiency::Weapon(WeaponName::CrossbowLight)),
+        sub_type => WeaponName::parse(&sub_type.replace('-', " ")).map(WeaponProficiency::Weapon),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::edition::Edition;
+
+    const CHARACTER_JSON: &str = r#"{
+        "success": true,
+        "data": {
+            "name": "Connie ",
+            "decorations": { "avatarUrl": "https://example.com/connie.png" },
+            "stats": [
+                { "id": 1, "value": 12 },
+                { "id": 2, "value": 15 },
+                { "id": 3, "value": 13 },
+                { "id": 4, "value": 10 },
+                { "id": 5, "value": 14 },
+                { "id": 6, "value": 8 }
+            ],
+            "bonusStats": [{ "id": 1, "value": null }, { "id": 3, "value": 1 }],
+            "overrideStats": [{ "id": 6, "value": 11 }],
+            "classes": [
+                {
+                    "level": 3,
+                    "definition": { "name": "Monk" },
+                    "subclassDefinition": { "name": "Way of Shadow" }
+                },
+                { "level": 2, "definition": { "name": "Rogue" }, "subclassDefinition": null }
+            ],
+            "modifiers": {
+                "race": [
+                    { "type": "bonus", "subType": "dexterity-score", "value": 2 },
+                    { "type": "bonus", "subType": "wisdom-score", "value": 1 }
+                ],
+                "class": [
+                    { "type": "proficiency", "subType": "strength-saving-throws", "value": null },
+                    { "type": "proficiency", "subType": "simple-weapons", "value": null },
+                    { "type": "proficiency", "subType": "shortsword", "value": null },
+                    { "type": "proficiency", "subType": "crossbow-hand", "value": null },
+                    { "type": "proficiency", "subType": "stealth", "value": null },
+                    { "type": "expertise", "subType": "stealth", "value": null },
+                    { "type": "proficiency", "subType": "sleight-of-hand", "value": null },
+                    { "type": "proficiency", "subType": "thieves-tools", "value": null }
+                ],
+                "item": [
+                    { "type": "set", "subType": "strength-score", "value": 19 },
+                    { "type": "bonus", "subType": "initiative", "value": 1 }
+                ],
+                "feat": null
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_parse() {
+        let export = parse(CHARACTER_JSON).unwrap();
+        let json = serde_json::to_value(&export).unwrap();
+        let character = &json["character"];
+        assert_eq!(character["name"], "Connie");
+        assert_eq!(character["avatar_url"], "https://example.com/connie.png");
+        assert_eq!(character["level"], 5);
+        assert_eq!(character["classes"][0]["class"], "Monk");
+        assert_eq!(character["classes"][0]["subclass"], "Way of Shadow");
+        assert_eq!(character["classes"][0]["level"], 3);
+        assert_eq!(character["classes"][1]["class"], "Rogue");
+        assert_eq!(character["classes"][1]["subclass"], Value::Null);
+        assert_eq!(character["martial_arts"], Value::Null);
+        assert_eq!(
+            export.character.martial_arts_damage_die(Edition::Rules2014),
+            Some(4)
+        );
+        assert_eq!(character["jack_of_all_trades"], Value::Null);
+        assert_eq!(character["initiative_bonus"], 1);
+        assert_eq!(character["alert"], false);
+        assert_eq!(character["strength"], 19);
+        assert_eq!(character["dexterity"], 17);
+        assert_eq!(character["constitution"], 14);
+        assert_eq!(character["intelligence"], 10);
+        assert_eq!(character["wisdom"], 15);
+        assert_eq!(character["charisma"], 11);
+        assert_eq!(character["strength_saving_proficiency"], true);
+        assert_eq!(character["dexterity_saving_proficiency"], false);
+        assert_eq!(character["stealth_proficiency"], "Expert");
+        assert_eq!(character["sleight_of_hand_proficiency"], "Proficient");
+        assert_eq!(character["arcana_proficiency"], "Normal");
+        assert_eq!(
+            export.weapon_proficiencies,
+            vec![
+                WeaponProficiency::Category(Category::Simple),
+                WeaponProficiency::Weapon(WeaponName::Shortsword),
+                WeaponProficiency::Weapon(WeaponName::CrossbowHand),
+            ]
+        );
+        assert!(export.validate().is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse("{}").is_err());
+        assert!(parse(r#"{ "data": { "name": "Connie", "classes": {} } }"#).is_err());
+    }
+}
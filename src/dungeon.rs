@@ -0,0 +1,609 @@
+use crate::roll::roll_on_table;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+/// The most rooms in a generated dungeon, so that its map fits in a message.
+pub const MAXIMUM_ROOMS: usize = 5;
+
+/// The number of rooms in a dungeon when no number is given.
+pub const DEFAULT_ROOMS: usize = 3;
+
+/// The length of the corridors drawn between rooms on the map, in squares.
+const CORRIDOR_LENGTH: usize = 2;
+
+/// The shape of a chamber, from the Dungeon Master's Guide.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RoomShape {
+    Square,
+    Rectangle,
+    Circle,
+    Octagon,
+    Trapezoid,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Wall {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// What a door is made of.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DoorKind {
+    Wooden,
+    Stone,
+    Iron,
+    Portcullis,
+    Secret,
+}
+
+/// A way out of a room, which is either a door, which might be barred or locked, or an open
+/// passage.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExitKind {
+    Door(DoorKind, bool),
+    Passage,
+}
+
+/// A way out of a room, and the room it leads to on the map, if any.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Exit {
+    pub wall: Wall,
+    pub kind: ExitKind,
+    pub to_room: Option<usize>,
+}
+
+/// What's in a room, from the Dungeon Master's Guide's table of chamber contents.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContentsKind {
+    DominantMonster,
+    AlliedMonster,
+    RandomMonster,
+    Hazard,
+    Obstacle,
+    Trap,
+    Trick,
+    Empty,
+}
+
+/// The contents of a room, with a seed for the DM to build on, such as the trigger and effect of a
+/// trap, and whether there's treasure to roll for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoomContents {
+    pub kind: ContentsKind,
+    pub detail: Option<String>,
+    pub treasure: bool,
+}
+
+/// A room of a dungeon, whose width runs from west to east and length from north to south.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Room {
+    pub number: usize,
+    pub shape: RoomShape,
+    pub width: i32,
+    pub length: i32,
+    pub exits: Vec<Exit>,
+    pub contents: RoomContents,
+}
+
+/// A small dungeon, whose rooms are joined in a row from west to east.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dungeon {
+    pub rooms: Vec<Room>,
+}
+
+/// A row of the chamber table: its shape, width and length in feet.
+type ChamberRow = (RoomShape, i32, i32);
+
+const CHAMBERS: [(i32, ChamberRow); 12] = [
+    (2, (RoomShape::Square, 20, 20)),
+    (4, (RoomShape::Square, 30, 30)),
+    (6, (RoomShape::Square, 40, 40)),
+    (9, (RoomShape::Rectangle, 30, 20)),
+    (12, (RoomShape::Rectangle, 40, 30)),
+    (14, (RoomShape::Rectangle, 50, 40)),
+    (15, (RoomShape::Rectangle, 80, 50)),
+    (16, (RoomShape::Circle, 30, 30)),
+    (17, (RoomShape::Circle, 50, 50)),
+    (18, (RoomShape::Octagon, 40, 40)),
+    (19, (RoomShape::Octagon, 60, 60)),
+    (20, (RoomShape::Trapezoid, 60, 40)),
+];
+
+/// The number of exits from a normal chamber, and from a large chamber, which is one with a side
+/// longer than 40 feet.
+const CHAMBER_EXITS: [(i32, (usize, usize)); 10] = [
+    (3, (0, 0)),
+    (5, (0, 1)),
+    (8, (1, 1)),
+    (11, (1, 2)),
+    (13, (2, 2)),
+    (15, (2, 3)),
+    (17, (3, 3)),
+    (18, (3, 4)),
+    (19, (4, 5)),
+    (20, (4, 6)),
+];
+
+/// Whether an exit is a door, rather than a passage.
+const EXIT_DOORS: [(i32, bool); 2] = [(10, true), (20, false)];
+
+const DOORS: [(i32, ExitKind); 10] = [
+    (10, ExitKind::Door(DoorKind::Wooden, false)),
+    (12, ExitKind::Door(DoorKind::Wooden, true)),
+    (13, ExitKind::Door(DoorKind::Stone, false)),
+    (14, ExitKind::Door(DoorKind::Stone, true)),
+    (15, ExitKind::Door(DoorKind::Iron, false)),
+    (16, ExitKind::Door(DoorKind::Iron, true)),
+    (17, ExitKind::Door(DoorKind::Portcullis, false)),
+    (18, ExitKind::Door(DoorKind::Portcullis, true)),
+    (19, ExitKind::Door(DoorKind::Secret, false)),
+    (20, ExitKind::Door(DoorKind::Secret, true)),
+];
+
+/// The chamber contents table, with whether there's treasure.
+const CONTENTS: [(i32, (ContentsKind, bool)); 14] = [
+    (8, (ContentsKind::DominantMonster, false)),
+    (15, (ContentsKind::DominantMonster, true)),
+    (27, (ContentsKind::AlliedMonster, false)),
+    (33, (ContentsKind::AlliedMonster, true)),
+    (42, (ContentsKind::RandomMonster, false)),
+    (50, (ContentsKind::RandomMonster, true)),
+    (58, (ContentsKind::Hazard, true)),
+    (63, (ContentsKind::Obstacle, false)),
+    (73, (ContentsKind::Trap, false)),
+    (76, (ContentsKind::Trap, true)),
+    (80, (ContentsKind::Trick, false)),
+    (88, (ContentsKind::Empty, false)),
+    (94, (ContentsKind::Hazard, false)),
+    (100, (ContentsKind::Empty, true)),
+];
+
+const HAZARDS: [(i32, &str); 6] = [
+    (3, "Brown mold"),
+    (8, "Green slime"),
+    (10, "Shrieker"),
+    (15, "Spiderwebs"),
+    (17, "Violet fungus"),
+    (20, "Yellow mold"),
+];
+
+const OBSTACLES: [(i32, &str); 12] = [
+    (1, "An antilife aura, 1d10 × 10 feet across"),
+    (2, "Battering winds"),
+    (3, "A blade barrier"),
+    (8, "A cave-in"),
+    (12, "A chasm, 1d4 × 10 feet wide and 2d6 × 10 feet deep"),
+    (14, "Flooding, 2d10 feet deep"),
+    (15, "Lava flowing through the room"),
+    (16, "Overgrown mushrooms"),
+    (17, "Poisonous gas"),
+    (18, "Reverse gravity"),
+    (19, "A wall of fire"),
+    (20, "A wall of force"),
+];
+
+const TRAP_TRIGGERS: [(i32, &str); 6] = [
+    (1, "stepped on"),
+    (2, "moved through"),
+    (3, "touched"),
+    (4, "opened"),
+    (5, "looked at"),
+    (6, "moved"),
+];
+
+const TRAP_SEVERITIES: [(i32, &str); 3] = [(2, "Setback"), (5, "Dangerous"), (6, "Deadly")];
+
+const TRAP_EFFECTS: [(i32, &str); 20] = [
+    (1, "magic missiles shoot from a statue"),
+    (2, "a collapsing staircase drops characters into a pit"),
+    (3, "a block falls from the ceiling"),
+    (4, "the ceiling slowly lowers in the locked room"),
+    (5, "a chute opens in the floor"),
+    (6, "a clanging noise attracts nearby monsters"),
+    (7, "a disintegrate spell is triggered"),
+    (8, "an object is coated with contact poison"),
+    (9, "fire shoots out from the walls"),
+    (10, "a flesh to stone spell is triggered"),
+    (11, "the floor collapses"),
+    (12, "a vent releases poisonous gas"),
+    (13, "the floor tiles are electrified"),
+    (14, "a glyph of warding goes off"),
+    (15, "a huge wheeled statue rolls across the room"),
+    (16, "a lightning bolt shoots from the wall"),
+    (17, "the locked room floods with water"),
+    (18, "darts shoot out of the walls"),
+    (19, "a suit of armor animates and attacks"),
+    (20, "a bladed pendulum swings across the room"),
+];
+
+const TRICK_OBJECTS: [(i32, &str); 20] = [
+    (1, "A book"),
+    (2, "A brain preserved in a jar"),
+    (3, "A burning fire"),
+    (4, "A cracked gem"),
+    (5, "A door"),
+    (6, "A fresco"),
+    (7, "Some furniture"),
+    (8, "A glass sculpture"),
+    (9, "A field of mushrooms"),
+    (10, "A painting"),
+    (11, "A plant or tree"),
+    (12, "A pool of water"),
+    (13, "Runes engraved on the floor"),
+    (14, "A skull"),
+    (15, "A sphere of magical energy"),
+    (16, "A statue"),
+    (17, "A stone obelisk"),
+    (18, "A suit of armor"),
+    (19, "A tapestry or rug"),
+    (20, "A target dummy"),
+];
+
+const TRICK_EFFECTS: [(i32, &str); 10] = [
+    (1, "asks a riddle, and rewards a correct answer"),
+    (2, "casts a spell on whoever touches it"),
+    (3, "changes the appearance of whoever looks at it"),
+    (4, "is an illusion of something else"),
+    (5, "speaks in a booming voice when approached"),
+    (6, "teleports whoever touches it to another room"),
+    (7, "grants a wish, but twists it"),
+    (8, "becomes animate and follows the characters"),
+    (9, "shows a vision of the dungeon's past"),
+    (10, "swaps the minds of two characters who touch it"),
+];
+
+impl Dungeon {
+    /// Generate a dungeon with a number of rooms, using the random dungeon tables from the Dungeon
+    /// Master's Guide. Each room has at least as many exits as it needs to join its neighbours,
+    /// and any other exits lead off the map.
+    pub fn generate<R: Rng + ?Sized>(rooms: usize, rng: &mut R) -> Dungeon {
+        Dungeon {
+            rooms: (1..=rooms)
+                .map(|number| Room::generate(number, rooms, rng))
+                .collect(),
+        }
+    }
+
+    /// Draw the dungeon as a map, where each square is 10 feet across.
+    pub fn map(&self) -> String {
+        let height = self
+            .rooms
+            .iter()
+            .map(|room| room.squares().1 + 2)
+            .max()
+            .unwrap_or(0);
+        // The corridors between rooms run along the middle row of the map
+        let middle = height / 2;
+        let mut rows: Vec<Vec<char>> = vec![Vec::new(); height];
+        for (index, room) in self.rooms.iter().enumerate() {
+            if index > 0 {
+                for (y, row) in rows.iter_mut().enumerate() {
+                    let corridor = if y == middle { '=' } else { ' ' };
+                    row.extend(std::iter::repeat(corridor).take(CORRIDOR_LENGTH));
+                }
+            }
+            let drawing = room.draw();
+            let top = middle - drawing.len() / 2;
+            let width = drawing[0].len();
+            for (y, row) in rows.iter_mut().enumerate() {
+                match y.checked_sub(top).and_then(|y| drawing.get(y)) {
+                    Some(line) => row.extend(line),
+                    None => row.extend(std::iter::repeat(' ').take(width)),
+                }
+            }
+        }
+        rows.iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_owned())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl Room {
+    fn generate<R: Rng + ?Sized>(number: usize, rooms: usize, rng: &mut R) -> Room {
+        let (shape, width, length) = *roll_on_table(&CHAMBERS, rng);
+        let (normal, large) = *roll_on_table(&CHAMBER_EXITS, rng);
+        let mut joined = Vec::new();
+        if number > 1 {
+            joined.push((Wall::West, number - 1));
+        }
+        if number < rooms {
+            joined.push((Wall::East, number + 1));
+        }
+        let rolled_exits = if width > 40 || length > 40 {
+            large
+        } else {
+            normal
+        };
+        let exit_count = rolled_exits.max(joined.len());
+        let mut open_walls = vec![Wall::North, Wall::South];
+        if number == 1 {
+            open_walls.push(Wall::West);
+        }
+        if number == rooms {
+            open_walls.push(Wall::East);
+        }
+        open_walls.shuffle(rng);
+        let exits = joined
+            .iter()
+            .map(|&(wall, to_room)| (wall, Some(to_room)))
+            .chain(
+                open_walls
+                    .iter()
+                    .cycle()
+                    .map(|&wall| (wall, None))
+                    .take(exit_count - joined.len()),
+            )
+            .collect::<Vec<(Wall, Option<usize>)>>()
+            .into_iter()
+            .map(|(wall, to_room)| Exit {
+                wall,
+                kind: if *roll_on_table(&EXIT_DOORS, rng) {
+                    *roll_on_table(&DOORS, rng)
+                } else {
+                    ExitKind::Passage
+                },
+                to_room,
+            })
+            .collect();
+        Room {
+            number,
+            shape,
+            width,
+            length,
+            exits,
+            contents: RoomContents::generate(rng),
+        }
+    }
+
+    /// The width and length of the room, in 10-foot squares.
+    fn squares(&self) -> (usize, usize) {
+        ((self.width / 10) as usize, (self.length / 10) as usize)
+    }
+
+    /// Whether the corners of the room are cut off on the map.
+    fn is_rounded(&self) -> bool {
+        match self.shape {
+            RoomShape::Circle | RoomShape::Octagon => true,
+            _ => false,
+        }
+    }
+
+    /// Draw the room and its walls, with its exits, and its number in the middle.
+    fn draw(&self) -> Vec<Vec<char>> {
+        let (width, length) = self.squares();
+        let mut drawing = vec![vec!['#'; width + 2]; length + 2];
+        for row in drawing.iter_mut().skip(1).take(length) {
+            for square in row.iter_mut().skip(1).take(width) {
+                *square = '.';
+            }
+        }
+        if self.is_rounded() {
+            for &(x, y) in [
+                (0, 0),
+                (width + 1, 0),
+                (0, length + 1),
+                (width + 1, length + 1),
+            ]
+            .iter()
+            {
+                drawing[y][x] = ' ';
+            }
+            for &(x, y) in [(1, 1), (width, 1), (1, length), (width, length)].iter() {
+                drawing[y][x] = '#';
+            }
+        }
+        let number = std::char::from_digit(self.number as u32, 10).unwrap_or('?');
+        drawing[(length + 1) / 2][(width + 1) / 2] = number;
+        for &wall in [Wall::North, Wall::East, Wall::South, Wall::West].iter() {
+            let exits: Vec<&Exit> = self.exits.iter().filter(|exit| exit.wall == wall).collect();
+            let side = match wall {
+                Wall::North | Wall::South => width,
+                Wall::East | Wall::West => length,
+            };
+            for (index, exit) in exits.iter().enumerate() {
+                let offset = match (wall, exit.to_room) {
+                    // Exits to other rooms are in line with the corridor between them
+                    (Wall::East, Some(_)) | (Wall::West, Some(_)) => (length + 2) / 2,
+                    _ => exit_offset(side, index, exits.len(), self.is_rounded()),
+                };
+                let (x, y) = match wall {
+                    Wall::North => (offset, 0),
+                    Wall::South => (offset, length + 1),
+                    Wall::East => (width + 1, offset),
+                    Wall::West => (0, offset),
+                };
+                drawing[y][x] = exit.kind.symbol();
+            }
+        }
+        drawing
+    }
+}
+
+/// Where one of the exits on a wall goes, spreading them along the wall, and away from the corners
+/// of a rounded room.
+fn exit_offset(side: usize, index: usize, count: usize, rounded: bool) -> usize {
+    let (first, last) = if rounded && side > 2 {
+        (2, side - 1)
+    } else {
+        (1, side)
+    };
+    let span = last + 1 - first;
+    first + (2 * index + 1) * span / (2 * count)
+}
+
+impl RoomContents {
+    fn generate<R: Rng + ?Sized>(rng: &mut R) -> RoomContents {
+        let (kind, treasure) = *roll_on_table(&CONTENTS, rng);
+        let detail = match kind {
+            ContentsKind::Hazard => Some((*roll_on_table(&HAZARDS, rng)).to_owned()),
+            ContentsKind::Obstacle => Some((*roll_on_table(&OBSTACLES, rng)).to_owned()),
+            ContentsKind::Trap => Some(format!(
+                "{} trap: when something is {}, {}",
+                roll_on_table(&TRAP_SEVERITIES, rng),
+                roll_on_table(&TRAP_TRIGGERS, rng),
+                roll_on_table(&TRAP_EFFECTS, rng)
+            )),
+            ContentsKind::Trick => Some(format!(
+                "{} that {}",
+                roll_on_table(&TRICK_OBJECTS, rng),
+                roll_on_table(&TRICK_EFFECTS, rng)
+            )),
+            _ => None,
+        };
+        RoomContents {
+            kind,
+            detail,
+            treasure,
+        }
+    }
+}
+
+impl RoomShape {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoomShape::Square => "Square",
+            RoomShape::Rectangle => "Rectangle",
+            RoomShape::Circle => "Circle",
+            RoomShape::Octagon => "Octagon",
+            RoomShape::Trapezoid => "Trapezoid",
+        }
+    }
+}
+
+impl Wall {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Wall::North => "north",
+            Wall::East => "east",
+            Wall::South => "south",
+            Wall::West => "west",
+        }
+    }
+}
+
+impl ExitKind {
+    /// The symbol for the exit on the map.
+    pub fn symbol(self) -> char {
+        match self {
+            ExitKind::Door(DoorKind::Secret, _) => 'S',
+            ExitKind::Door(_, true) => 'L',
+            ExitKind::Door(_, false) => '+',
+            ExitKind::Passage => '.',
+        }
+    }
+}
+
+impl ContentsKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentsKind::DominantMonster => "Monster (dominant inhabitant)",
+            ContentsKind::AlliedMonster => "Monster (pet or allied creature)",
+            ContentsKind::RandomMonster => "Monster (random creature)",
+            ContentsKind::Hazard => "Dungeon hazard",
+            ContentsKind::Obstacle => "Obstacle",
+            ContentsKind::Trap => "Trap",
+            ContentsKind::Trick => "Trick",
+            ContentsKind::Empty => "Empty room",
+        }
+    }
+}
+
+impl fmt::Display for Room {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.shape {
+            RoomShape::Circle => write!(
+                f,
+                "Room {}: {}, {} ft. across",
+                self.number,
+                self.shape.as_str(),
+                self.width
+            ),
+            _ => write!(
+                f,
+                "Room {}: {}, {} × {} ft.",
+                self.number,
+                self.shape.as_str(),
+                self.width,
+                self.length
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ExitKind::Door(DoorKind::Secret, locked) => {
+                write!(f, "{}secret door", if locked { "barred " } else { "" })?
+            }
+            ExitKind::Door(DoorKind::Portcullis, locked) => write!(
+                f,
+                "portcullis{}",
+                if locked { ", locked in place" } else { "" }
+            )?,
+            ExitKind::Door(kind, locked) => write!(
+                f,
+                "{}{} door",
+                if locked { "locked " } else { "" },
+                match kind {
+                    DoorKind::Stone => "stone",
+                    DoorKind::Iron => "iron",
+                    _ => "wooden",
+                }
+            )?,
+            ExitKind::Passage => write!(f, "passage")?,
+        }
+        match self.to_room {
+            Some(room) => write!(f, " ({}, to room {})", self.wall.as_str(), room),
+            None => write!(f, " ({})", self.wall.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn test_generate() {
+        // Every die rolls a 1
+        let mut rng = StepRng::new(0, 0);
+        let dungeon = Dungeon::generate(3, &mut rng);
+        assert_eq!(dungeon.rooms.len(), 3);
+        let room = &dungeon.rooms[1];
+        assert_eq!(room.number, 2);
+        assert_eq!(
+            (room.shape, room.width, room.length),
+            (RoomShape::Square, 20, 20)
+        );
+        // The room has no exits of its own, but needs two to join its neighbours
+        assert_eq!(
+            room.exits
+                .iter()
+                .map(|exit| (exit.wall, exit.to_room))
+                .collect::<Vec<(Wall, Option<usize>)>>(),
+            vec![(Wall::West, Some(1)), (Wall::East, Some(3))]
+        );
+        assert_eq!(room.exits[0].kind, ExitKind::Door(DoorKind::Wooden, false));
+        assert_eq!(room.contents.kind, ContentsKind::DominantMonster);
+        assert_eq!(room.contents.treasure, false);
+        assert_eq!(
+            dungeon.map(),
+            "####  ####  ####\n#1.#  #2.#  #3.#\n#..+==+..+==+..#\n####  ####  ####"
+        );
+    }
+
+    #[test]
+    fn test_exit_offset() {
+        assert_eq!(exit_offset(4, 0, 1, false), 3);
+        assert_eq!(exit_offset(4, 0, 2, false), 2);
+        assert_eq!(exit_offset(4, 1, 2, false), 4);
+        assert_eq!(exit_offset(5, 0, 1, true), 3);
+    }
+}
@@ -0,0 +1,332 @@
+use crate::character::Character;
+use crate::character_effect;
+use crate::combatant;
+use crate::initiative::InitiativeOrder;
+use crate::monster::MonsterGroup;
+use crate::ongoing_effect::OngoingEffect;
+use crate::status;
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
+use serenity::model::id::{ChannelId, UserId};
+
+/// The easy, medium, hard and deadly experience point thresholds for a character of each level
+/// from 1 to 20, from the Dungeon Master's Guide.
+const EXPERIENCE_THRESHOLDS: [[i32; 4]; 20] = [
+    [25, 50, 75, 100],
+    [50, 100, 150, 200],
+    [75, 150, 225, 400],
+    [125, 250, 375, 500],
+    [250, 500, 750, 1100],
+    [300, 600, 900, 1400],
+    [350, 750, 1100, 1700],
+    [450, 900, 1400, 2100],
+    [550, 1100, 1600, 2400],
+    [600, 1200, 1900, 2800],
+    [800, 1600, 2400, 3600],
+    [1000, 2000, 3000, 4500],
+    [1100, 2200, 3400, 5100],
+    [1250, 2500, 3800, 5700],
+    [1400, 2800, 4300, 6400],
+    [1600, 3200, 4800, 7200],
+    [2000, 3900, 5900, 8800],
+    [2100, 4200, 6300, 9500],
+    [2400, 4900, 7300, 10900],
+    [2800, 5700, 8500, 12700],
+];
+
+/// The encounter multipliers from the Dungeon Master's Guide, doubled so that they're whole
+/// numbers.
+const DOUBLED_MULTIPLIERS: [i32; 7] = [1, 2, 3, 4, 5, 6, 8];
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum DifficultyRating {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Deadly,
+}
+
+impl DifficultyRating {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DifficultyRating::Trivial => "Trivial",
+            DifficultyRating::Easy => "Easy",
+            DifficultyRating::Medium => "Medium",
+            DifficultyRating::Hard => "Hard",
+            DifficultyRating::Deadly => "Deadly",
+        }
+    }
+}
+
+/// How difficult an encounter would be for a party, using the rules for building encounters in
+/// the Dungeon Master's Guide.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Difficulty {
+    pub rating: DifficultyRating,
+    /// The total experience points of the monsters.
+    pub experience: i32,
+    /// The total experience points of the monsters, adjusted for the number of monsters and the
+    /// size of the party.
+    pub adjusted_experience: i32,
+    /// The party's easy, medium, hard and deadly thresholds.
+    pub thresholds: [i32; 4],
+}
+
+impl Difficulty {
+    /// Work out the difficulty of fighting the monsters for a party with the given character
+    /// levels. Levels outside of 1 to 20 are treated as the nearest level in that range.
+    pub fn new(levels: &[i32], monsters: &[MonsterGroup]) -> Difficulty {
+        let mut thresholds = [0; 4];
+        for level in levels {
+            let level_thresholds = EXPERIENCE_THRESHOLDS[((*level).max(1).min(20) - 1) as usize];
+            for (threshold, level_threshold) in thresholds.iter_mut().zip(level_thresholds.iter()) {
+                *threshold += level_threshold;
+            }
+        }
+        let experience = monsters
+            .iter()
+            .map(|group| group.count * group.challenge_rating.experience())
+            .sum();
+        let count: i32 = monsters.iter().map(|group| group.count).sum();
+        let multiplier = match count {
+            0 | 1 => 1,
+            2 => 2,
+            3..=6 => 3,
+            7..=10 => 4,
+            11..=14 => 5,
+            _ => 6,
+        };
+        let multiplier = if levels.len() < 3 {
+            (multiplier + 1).min(DOUBLED_MULTIPLIERS.len() - 1)
+        } else if levels.len() >= 6 {
+            multiplier - 1
+        } else {
+            multiplier
+        };
+        let adjusted_experience = experience * DOUBLED_MULTIPLIERS[multiplier] / 2;
+        let rating = if adjusted_experience >= thresholds[3] {
+            DifficultyRating::Deadly
+        } else if adjusted_experience >= thresholds[2] {
+            DifficultyRating::Hard
+        } else if adjusted_experience >= thresholds[1] {
+            DifficultyRating::Medium
+        } else if adjusted_experience >= thresholds[0] {
+            DifficultyRating::Easy
+        } else {
+            DifficultyRating::Trivial
+        };
+        Difficulty {
+            rating,
+            experience,
+            adjusted_experience,
+            thresholds,
+        }
+    }
+}
+
+/// Retrieve the levels of the active characters in a channel, other than the DM's. Characters
+/// without a level are left out.
+pub fn party_levels(
+    connection: &Connection,
+    channel_id: ChannelId,
+    dm_id: Option<UserId>,
+) -> RusqliteResult<Vec<i32>> {
+    let mut levels = Vec::new();
+    for (user_id, _) in Character::get_all_active_names(connection, channel_id)? {
+        if Some(user_id) != dm_id {
+            if let Some(level) = Character::get(connection, channel_id, user_id)?
+                .and_then(|character| character.level())
+            {
+                levels.push(level);
+            }
+        }
+    }
+    Ok(levels)
+}
+
+/// What happened to one combatant over the course of an encounter.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CombatantSummary {
+    pub name: String,
+    pub damage_taken: i32,
+    pub defeated: bool,
+    /// The experience points the combatant is worth, if the DM has set it.
+    pub experience: Option<i32>,
+}
+
+/// A summary of an encounter, shown when it ends.
+#[derive(Debug, Eq, PartialEq)]
+pub struct EncounterSummary {
+    /// The round the encounter ended in, if any turns were taken.
+    pub rounds: Option<i32>,
+    /// The combatants who took damage, were defeated, or were given an experience point value, in
+    /// the order they were first recorded.
+    pub combatants: Vec<CombatantSummary>,
+}
+
+impl EncounterSummary {
+    pub fn get(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<EncounterSummary> {
+        let rounds = InitiativeOrder::get_turn(connection, channel_id)?.map(|turn| turn.round);
+        let mut statement = connection.prepare(
+            "SELECT name, damage_taken, defeated, experience \
+             FROM encounter_combatants \
+             WHERE channel_id = $1 \
+             ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(&[&channel_id.to_string()], |row| {
+            Ok(CombatantSummary {
+                name: row.get("name")?,
+                damage_taken: row.get("damage_taken")?,
+                defeated: row.get("defeated")?,
+                experience: row.get("experience")?,
+            })
+        })?;
+        Ok(EncounterSummary {
+            rounds,
+            combatants: rows.collect::<RusqliteResult<Vec<CombatantSummary>>>()?,
+        })
+    }
+
+    /// The experience points earned for the encounter, which is the total of the experience
+    /// points of the defeated combatants.
+    pub fn experience(&self) -> i32 {
+        self.combatants
+            .iter()
+            .filter(|combatant| combatant.defeated)
+            .filter_map(|combatant| combatant.experience)
+            .sum()
+    }
+}
+
+/// Add to the damage a combatant has taken during the encounter.
+pub fn record_damage(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+    amount: i32,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name, &amount];
+    connection.execute(
+        "INSERT INTO encounter_combatants (channel_id, name, damage_taken) VALUES ($1, $2, $3) \
+         ON CONFLICT (channel_id, name) DO UPDATE SET damage_taken = damage_taken + $3",
+        params,
+    )
+}
+
+/// Record that a combatant was defeated during the encounter.
+pub fn record_defeat(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name];
+    connection.execute(
+        "INSERT INTO encounter_combatants (channel_id, name, defeated) VALUES ($1, $2, 1) \
+         ON CONFLICT (channel_id, name) DO UPDATE SET defeated = 1",
+        params,
+    )
+}
+
+/// Set the experience points the party earns for defeating a combatant.
+pub fn set_experience(
+    connection: &Connection,
+    channel_id: ChannelId,
+    name: &str,
+    experience: i32,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name, &experience];
+    connection.execute(
+        "INSERT INTO encounter_combatants (channel_id, name, experience) VALUES ($1, $2, $3) \
+         ON CONFLICT (channel_id, name) DO UPDATE SET experience = $3",
+        params,
+    )
+}
+
+/// Forget what has happened during a channel's encounter.
+pub fn clear(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+    connection.execute(
+        "DELETE FROM encounter_combatants WHERE channel_id = $1",
+        &[&channel_id.to_string()],
+    )
+}
+
+/// End a channel's encounter, clearing the initiative order along with the combatants' ongoing
+/// effects and hit points. Returns a summary of the encounter.
+pub fn end(connection: &mut Connection, channel_id: ChannelId) -> RusqliteResult<EncounterSummary> {
+    let transaction = connection.transaction()?;
+    let summary = EncounterSummary::get(&transaction, channel_id)?;
+    InitiativeOrder::clear(&transaction, channel_id)?;
+    OngoingEffect::clear_all(&transaction, channel_id)?;
+    character_effect::clear_timed(&transaction, channel_id)?;
+    status::clear_timed(&transaction, channel_id)?;
+    combatant::clear(&transaction, channel_id)?;
+    clear(&transaction, channel_id)?;
+    transaction.commit()?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::monster::ChallengeRating;
+
+    fn monsters(count: i32, challenge_rating: ChallengeRating) -> MonsterGroup {
+        MonsterGroup {
+            count,
+            name: format!("CR {}", challenge_rating),
+            challenge_rating,
+        }
+    }
+
+    fn combatant(name: &str, defeated: bool, experience: Option<i32>) -> CombatantSummary {
+        CombatantSummary {
+            name: name.to_owned(),
+            damage_taken: 10,
+            defeated,
+            experience,
+        }
+    }
+
+    #[test]
+    fn test_experience() {
+        let summary = EncounterSummary {
+            rounds: Some(3),
+            combatants: vec![
+                combatant("Troll", true, Some(1800)),
+                combatant("Goblin A", true, Some(50)),
+                combatant("Goblin B", false, Some(50)),
+                combatant("Bandit", true, None),
+            ],
+        };
+        assert_eq!(summary.experience(), 1850);
+    }
+
+    #[test]
+    fn test_difficulty() {
+        let difficulty = Difficulty::new(
+            &[3, 3, 3, 3],
+            &[
+                monsters(4, ChallengeRating::Quarter),
+                monsters(1, ChallengeRating::Whole(2)),
+            ],
+        );
+        assert_eq!(difficulty.experience, 650);
+        assert_eq!(difficulty.adjusted_experience, 1300);
+        assert_eq!(difficulty.thresholds, [300, 600, 900, 1600]);
+        assert_eq!(difficulty.rating, DifficultyRating::Hard);
+    }
+
+    #[test]
+    fn test_difficulty_party_size() {
+        let small = Difficulty::new(&[1, 1], &[monsters(1, ChallengeRating::Quarter)]);
+        assert_eq!(small.adjusted_experience, 75);
+        assert_eq!(small.rating, DifficultyRating::Easy);
+        let large = Difficulty::new(&[1; 6], &[monsters(2, ChallengeRating::Quarter)]);
+        assert_eq!(large.adjusted_experience, 100);
+        assert_eq!(large.rating, DifficultyRating::Trivial);
+        let empty = Difficulty::new(&[5, 5, 5], &[]);
+        assert_eq!(empty.rating, DifficultyRating::Trivial);
+    }
+}
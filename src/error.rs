@@ -6,6 +6,7 @@ use std::fmt;
 pub enum Error {
     R2D2Error(r2d2::Error),
     RusqliteError(rusqlite::Error),
+    SerdeJsonError(serde_json::Error),
     IntentParserError(::failure::Error),
     UnknownIntent(String),
 }
@@ -15,6 +16,7 @@ impl fmt::Display for Error {
         match self {
             Error::R2D2Error(error) => write!(f, "Connection pool error: {}", error),
             Error::RusqliteError(error) => write!(f, "Database error: {}", error),
+            Error::SerdeJsonError(error) => write!(f, "JSON error: {}", error),
             Error::IntentParserError(error) => write!(f, "Intent parser error: {}", error),
             Error::UnknownIntent(intent_name) => write!(f, "Unknown intent: {}", intent_name),
         }
@@ -1,28 +1,102 @@
-use crate::attack_roll::AttackRoll;
-use crate::channel::Channel;
-use crate::character::Character;
-use crate::character_roll::CharacterRoll;
+use crate::attack_roll::{
+    Attack, AttackCount, AttackPart, AttackRoll, AttackTarget, Cover, Smite, SmiteDamage,
+    MAXIMUM_ATTACKS,
+};
+use crate::audit;
+use crate::audit::{Change, RECENT_CHANGES};
+use crate::bestiary::StatBlock;
+use crate::bonus::Bonus;
+use crate::channel::{Channel, DungeonMaster, Verbosity, DEFAULT_DUPLICATE_WINDOW};
+use crate::channel_export::ChannelExport;
+use crate::character::{
+    AbilityName, Character, CharacterCopy, CharacterExport, FightingStyle, ImportSource, Recipient,
+};
+use crate::character_effect;
+use crate::character_effect::CharacterEffect;
+use crate::character_roll::{CharacterRoll, Check, Contest, ContestRoll, GroupSave};
+use crate::character_sheet::CharacterSheet;
+use crate::class::{ordinal, Class};
+use crate::combatant;
+use crate::combatant::{Damage, HitPoints, Resistances};
 use crate::command;
-use crate::command::{Command, CommandResult};
+use crate::command::{BotMentions, Command, CommandResult};
+use crate::command_role::{CommandCategory, CommandRole};
+use crate::companion::{Companion, CompanionKind};
+use crate::content_pack;
+use crate::content_pack::ContentPack;
+use crate::counter;
+use crate::counter::{Counter, Rest};
+use crate::currency::{Amount, Coins, Transaction};
+use crate::delivery;
+use crate::delivery::DeliveryMetrics;
+use crate::dndbeyond;
+use crate::dungeon::Dungeon;
+use crate::edition::Edition;
+use crate::encounter;
+use crate::encounter::Difficulty;
 use crate::error::Error;
-use crate::intent_logger::log_intent_result;
-use crate::response::Response;
-use crate::roll::{ConditionalRoll, Critical};
+use crate::experience;
+use crate::follow_up::{Conversation, Guess, FOLLOW_UP_WINDOW};
+use crate::guild::{ChannelDefault, Guild, DEFAULT_PREFIX};
+use crate::hidden_roll::HiddenRoll;
+use crate::house_rule::{HouseRule, HouseRules};
+use crate::initiative::{InitiativeEntry, InitiativeOrder, Turn};
+use crate::intent_logger::{confirm_message, log_intent_result};
+use crate::intent_parser::parse_intent;
+use crate::lore::Lore;
+use crate::monster;
+use crate::monster::{Monster, MonsterGroup, QuickStatBlock};
+use crate::note::{Note, RECENT_NOTES};
+use crate::ongoing_effect::{EffectKind, OngoingEffect};
+use crate::passive::PassiveScore;
+use crate::permission_check;
+use crate::preset;
+use crate::purge;
+use crate::purpose::Purpose;
+use crate::quick_reference::{PinnedQuickReference, QuickReference};
+use crate::random_table;
+use crate::random_table::RandomTable;
+use crate::response::{add_roll_codes, embed_as_plain_text, quick_reference_embed, Response};
+use crate::roll::{Condition, ConditionalRoll, Critical, Limits, Roll};
+use crate::roll_history::{log_roll, RollHistoryEntry};
+use crate::roll_signature::{RollSigner, SignedRoll};
+use crate::settlement::{Settlement, SettlementSize, Tavern};
+use crate::spell::Spell;
+use crate::stash::{self, Loot, Stash};
+use crate::status;
+use crate::status::Status;
+use crate::tie_break;
+use crate::tie_break::TieBreak;
+use crate::time;
+use crate::time::{TimeZone, TimeZoneSource};
+use crate::training_data;
+use crate::treasure::Treasure;
+use crate::weapon::{DamageType, WeaponName};
+use crate::weather::{Season, Weather};
+use crate::wild_shape::WildShape;
 use log::{error, info};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
+use rand::Rng;
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
 use snips_nlu_lib::SnipsNluEngine;
 use snips_nlu_ontology::IntentParserResult;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::convert::identity;
-use std::sync::RwLock;
+use std::iter;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
 use symspell::{SymSpell, UnicodeStringStrategy};
 
 use serenity::{
     model::{
-        channel::Message,
+        channel::{Attachment, Message},
         gateway::{Activity, Ready},
-        id::{ChannelId, UserId},
+        id::{ChannelId, GuildId, MessageId, RoleId, UserId},
+        permissions::Permissions,
     },
     prelude::*,
 };
@@ -33,224 +107,6037 @@ const CHARACTER_NOT_FOUND_WARNING_TEXT: &str =
 const ABILITY_NOT_SET_WARNING_TEXT: &str =
     "Couldn't find required ability scores for character. Try setting some ability scores and a character level first.";
 
+const NO_CHARACTERS_WARNING_TEXT: &str =
+    "Nobody in this channel has a character yet. Try setting some ability scores and a character level first.";
+
+const IMPORT_ATTACHMENT_MISSING_WARNING_TEXT: &str =
+    "Couldn't find a character sheet to import. Try attaching a file created with \"!export\".";
+
+const IMPORT_ATTACHMENT_INVALID_WARNING_TEXT: &str =
+    "Couldn't download the attached character sheet. Try attaching it again.";
+
+const CHANNEL_IMPORT_ATTACHMENT_MISSING_WARNING_TEXT: &str =
+    "Couldn't find channel settings to import. Try attaching a file created with `!channel export`.";
+
+const CHANNEL_IMPORT_ATTACHMENT_INVALID_WARNING_TEXT: &str =
+    "Couldn't download the attached channel settings. Try attaching them again.";
+
+const CHANNEL_IMPORT_ATTACHMENT_TOO_LARGE_WARNING_TEXT: &str =
+    "The attached file is too large to be channel settings. Try attaching a file created with `!channel export`.";
+
+const IMPORT_ATTACHMENT_TOO_LARGE_WARNING_TEXT: &str =
+    "The attached file is too large to be a character sheet. Try attaching a file created with \"!export\".";
+
+/// The largest attachment that will be downloaded, such as a character sheet to import, in bytes.
+const ATTACHMENT_MAXIMUM_SIZE: u64 = 1024 * 1024;
+
+const CONTENT_PACK_ADMIN_WARNING_TEXT: &str =
+    "Only an administrator can install, enable, disable or remove monster packs.";
+
+const CONTENT_PACK_GUILD_WARNING_TEXT: &str = "Monster packs can only be used in a server.";
+
+const CONTENT_PACK_ATTACHMENT_MISSING_WARNING_TEXT: &str =
+    "Couldn't find a monster pack to install. Try attaching the pack's JSON file to `!monsterpack install`.";
+
+const CONTENT_PACK_ATTACHMENT_INVALID_WARNING_TEXT: &str =
+    "Couldn't download the attached monster pack. Try attaching it again.";
+
+const CONTENT_PACK_ATTACHMENT_TOO_LARGE_WARNING_TEXT: &str =
+    "The attached monster pack is too large. Monster packs can be no more than 1 MB.";
+
+const SNEAK_ATTACK_WARNING_TEXT: &str =
+    "Only rogues can sneak attack. Try setting your class and character level first, e.g. \"!class Rogue\".";
+
+const SMITE_WARNING_TEXT: &str =
+    "Only paladins can use Divine Smite. Try setting your class and character level first, e.g. \"!class Paladin 2\".";
+
+const CANTRIP_LEVEL_WARNING_TEXT: &str =
+    "A cantrip's damage depends on your character's level. Try setting your character level first, e.g. \"I'm now level 5\".";
+
+const SPELL_SLOTS_WARNING_TEXT: &str =
+    "Your character doesn't have any spell slots. Spell slots come from levels in spellcasting classes, e.g. \"!class Wizard 3\".";
+
+const INITIATIVE_ORDER_MISSING_WARNING_TEXT: &str =
+    "There's no initiative order in this channel. Try \"Set initiative order: Alice 18, goblin A 15, Bob 12\" first.";
+
+const INSUFFICIENT_FUNDS_WARNING_TEXT: &str =
+    "You don't have enough coins to spend that much, even after converting between denominations.";
+
+const TOO_MANY_COINS_WARNING_TEXT: &str =
+    "You can't carry that many coins. Try converting some of them into a more valuable denomination.";
+
+const PARTY_CURRENCY_DM_WARNING_TEXT: &str = "Only the DM can view the party's coins.";
+
+const COMPANION_NOT_FOUND_WARNING_TEXT: &str =
+    "You don't have a companion by that name. Try \"My familiar is Hoot\" to add one, or `!companions` to see your companions.";
+
+const STASH_INSUFFICIENT_WARNING_TEXT: &str =
+    "The party stash doesn't have that much. Try `!stash` to see what's in it.";
+
+const STASH_FULL_WARNING_TEXT: &str =
+    "The party stash can't hold that many coins. Try sharing some of them out with `!stash take`.";
+
+const WILD_SHAPE_NOT_FOUND_WARNING_TEXT: &str =
+    "You aren't in a wild shape. Try \"Wild shape into a brown bear\", or `!wildshape` to see your forms.";
+
+const EXPERIENCE_DM_WARNING_TEXT: &str = "Only the DM can award experience points.";
+
+const INSPIRATION_DM_WARNING_TEXT: &str = "Only the DM can grant inspiration.";
+
+const GROUP_SAVING_THROW_DM_WARNING_TEXT: &str =
+    "Only the DM can ask everyone to make a saving throw.";
+
+const MONSTER_DM_WARNING_TEXT: &str = "Only the DM can save or delete monsters.";
+
+const LORE_DM_WARNING_TEXT: &str = "Only the DM can add, remove or reveal lore.";
+
+const RANDOM_TABLE_DM_WARNING_TEXT: &str =
+    "Only the DM can add, delete or look through random tables.";
+
+const TRAINING_DATA_OWNER_WARNING_TEXT: &str =
+    "Only the owner of Dungeon Helper can export the training data.";
+
+const RELOAD_MODEL_OWNER_WARNING_TEXT: &str =
+    "Only the owner of Dungeon Helper can reload the natural language parser's model.";
+
+const RELOAD_MODEL_MISSING_PATH_WARNING_TEXT: &str =
+    "There's no model path in Dungeon Helper's environment, so there's no model to reload. Natural language commands are still understood with the rule-based fallback.";
+
+const PERMISSIONS_UNKNOWN_WARNING_TEXT: &str =
+    "Couldn't find Dungeon Helper's permissions in this channel. Try again in a moment.";
+
+const DICE_LIMITS_DM_WARNING_TEXT: &str =
+    "Only the DM can set the most dice that can be rolled in this channel.";
+
+const RESTRICT_ADMIN_WARNING_TEXT: &str = "Only an administrator can restrict commands to a role.";
+
+const DUNGEON_MASTER_ADMIN_WARNING_TEXT: &str =
+    "Only an administrator or the DM can set the DM of this channel.";
+
+const DUNGEON_MASTER_MISSING_WARNING_TEXT: &str =
+    "This channel doesn't have a DM user to send blind rolls to. An administrator can set one with `!dm @user`.";
+
+const EDITION_DM_WARNING_TEXT: &str =
+    "Only the DM can set the edition of the rules used in this channel.";
+
+const HOUSE_RULES_DM_WARNING_TEXT: &str =
+    "Only the DM can change the house rules used in this channel.";
+
+const TIME_ZONE_ADMIN_WARNING_TEXT: &str = "Only an administrator can set the server's time zone.";
+
+const TIME_ZONE_GUILD_WARNING_TEXT: &str = "A server time zone can only be set in a server.";
+
+const SERVER_DEFAULTS_GUILD_WARNING_TEXT: &str =
+    "The settings that new channels start with can only be changed in a server.";
+
+const AUDIT_ADMIN_WARNING_TEXT: &str = "Only an administrator can review changes to characters.";
+
+const HIDDEN_ROLLS_MISSING_WARNING_TEXT: &str =
+    "You don't have any hidden rolls to reveal. Try \"Roll 1d20 hidden\" to roll into the dice cup.";
+
+const ROLL_NOT_FOUND_WARNING_TEXT: &str =
+    "I couldn't find a roll with that code. Check that the code matches the one shown under the roll.";
+
+const ROLL_NOT_GENUINE_WARNING_TEXT: &str =
+    "The roll with that code doesn't match its signature, so it may have been tampered with.";
+
+const PURGE_ADMIN_WARNING_TEXT: &str =
+    "Only an administrator can delete everything stored about a user in a channel. To delete one of your own characters, try `!delete` followed by their name.";
+
+const INSPIRATION_MISSING_WARNING_TEXT: &str =
+    "Your character doesn't have inspiration to use. Ask your DM to grant you some first.";
+
+const SET_LEVEL_MULTICLASS_WARNING_TEXT: &str =
+    "Your character has levels in more than one class. Try setting your levels in each class instead, e.g. `!class Rogue 3`.";
+
+const BARDIC_INSPIRATION_BARD_WARNING_TEXT: &str =
+    "Only a bard can give bardic inspiration. Try setting your character's class first, e.g. \"!class Bard 3\".";
+
+const BARDIC_INSPIRATION_SELF_WARNING_TEXT: &str =
+    "Your character can't give bardic inspiration to themselves.";
+
+const BARDIC_INSPIRATION_USES_WARNING_TEXT: &str =
+    "Your character doesn't have any uses of Bardic Inspiration left. They're restored by resting, e.g. \"!rest long\".";
+
+const BARDIC_INSPIRATION_MISSING_WARNING_TEXT: &str =
+    "Your character doesn't have a Bardic Inspiration die to use. Ask a bard to give you one first.";
+
+const CHANNEL_DISABLED_NOTICE_TEXT: &str =
+    "Dungeon Helper is disabled in that channel, so I can't respond there. A server administrator can enable Dungeon Helper for the channel. In the meantime, I'm happy to roll dice for you here.";
+
+/// The minimum time between notices to a user that a channel is disabled, to avoid spamming users
+/// who repeatedly mention the bot.
+const CHANNEL_DISABLED_NOTICE_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
 enum Action {
     IgnoreChannelDisabled,
     IgnoreCommandMissing,
+    IgnoreDuplicateCommand,
     IgnoreOwnMessage,
+    NotifyChannelDisabled,
     Respond(Box<Response>),
 }
 
 pub struct Handler {
-    pub bot_id: RwLock<Option<String>>,
-    pub engine: SnipsNluEngine,
+    /// The bot's user ID, set once the client is ready, or zero beforehand.
+    pub bot_id: AtomicU64,
+    /// The user ID of the bot application's owner, set once the client is ready, or zero
+    /// beforehand.
+    pub owner_id: AtomicU64,
+    /// The intent parser, or None if its model couldn't be loaded, in which case natural language
+    /// commands are parsed by the rule-based fallback. It can be replaced while the bot is
+    /// running by reloading the model.
+    pub engine: RwLock<Option<SnipsNluEngine>>,
+    /// The path the intent parser's model is loaded from, if there's one in the environment.
+    pub model_path: Option<String>,
     pub pool: Pool<SqliteConnectionManager>,
     pub symspell: SymSpell<UnicodeStringStrategy>,
+    /// Signs the code shown with each roll, so that screenshots of rolls can be verified.
+    pub roll_signer: RollSigner,
+    /// When each user was last notified that a channel is disabled.
+    pub channel_disabled_notices: Mutex<HashMap<(ChannelId, UserId), Instant>>,
+    /// The last command sent by each user in each channel, and when it stops counting as a
+    /// duplicate.
+    pub recent_commands: Mutex<HashMap<(ChannelId, UserId), (String, Instant)>>,
+    /// The last message each user sent to the bot in each channel, which they can follow up on,
+    /// such as with "again".
+    pub conversations: Mutex<HashMap<(ChannelId, UserId), Conversation>>,
+    /// Counts of the messages sent and deleted, and how many had to be retried or failed.
+    pub delivery_metrics: DeliveryMetrics,
 }
 
 impl Handler {
+    fn bot_id(&self) -> Option<UserId> {
+        match self.bot_id.load(Ordering::Relaxed) {
+            0 => None,
+            bot_id => Some(UserId(bot_id)),
+        }
+    }
+
+    fn owner_id(&self) -> Option<UserId> {
+        match self.owner_id.load(Ordering::Relaxed) {
+            0 => None,
+            owner_id => Some(UserId(owner_id)),
+        }
+    }
+
     fn get_command(
         &self,
-        engine: &SnipsNluEngine,
-        symspell: &SymSpell<UnicodeStringStrategy>,
+        symspell: Option<&SymSpell<UnicodeStringStrategy>>,
         message: &Message,
+        bot_role_ids: &[RoleId],
         dice_only: bool,
+        prefix: &str,
     ) -> Option<Result<CommandResult, command::Error>> {
-        let content = &message.content.trim();
-        self.bot_id
-            .try_read()
-            .ok()
-            .and_then(|bot_id| {
-                bot_id.as_ref().map(|bot_id| {
-                    Command::parse(engine, symspell, content, Some(&bot_id), dice_only)
-                })
+        let content = Command::apply_prefix(message.content.trim(), prefix)?;
+        let mentions = BotMentions {
+            user_id: self.bot_id(),
+            role_ids: bot_role_ids,
+        };
+        let engine = self
+            .engine
+            .read()
+            .expect("RwLock for engine has been poisoned");
+        let parse = |content: &str| {
+            Command::parse(engine.as_ref(), symspell, content, &mentions, dice_only)
+        };
+        // A rewritten message is logged as the text the user sent, rather than the rewritten text
+        let parse_rewritten = |rewritten: &str, original: &str| {
+            parse(rewritten).map(|result| result.map(|result| result.unlabelled(original)))
+        };
+        let key = (message.channel_id, message.author.id);
+        let previous = self.get_conversation(key);
+        let at_message = Command::extract_at_message(&content, &mentions, dice_only);
+        // A guess at what the previous message meant that the user has confirmed is run as the
+        // previous message, such as "yes" to "Did you mean to show your currency?"
+        let confirmed = previous.as_ref().and_then(|previous| {
+            at_message
+                .as_deref()
+                .and_then(|at_message| previous.confirm(at_message))
+                .map(|guess| (previous.message.clone(), guess.clone()))
+        });
+        let follow_up = previous.as_ref().and_then(|previous| {
+            at_message
+                .as_deref()
+                .and_then(|at_message| previous.follow_up(at_message))
+        });
+        let (content, command_result) = match (confirmed, follow_up, at_message) {
+            (Some((previous_message, guess)), _, _) => {
+                let command = parse_intent(&guess.intent_result);
+                let command_result = Some(Ok(CommandResult::Confirmed(command, guess.message_id)));
+                (previous_message, command_result)
+            }
+            (None, Some(follow_up), Some(at_message)) => {
+                let command_result = parse_rewritten(&follow_up, &at_message);
+                (follow_up, command_result)
+            }
+            (None, _, at_message) => {
+                let command_result = parse(&content);
+                // A message that isn't understood may be the answer to the clarification given to
+                // the previous message, such as "two handed" for "attack with my longsword"
+                let answered = previous
+                    .filter(|_| !Handler::is_understood(&command_result))
+                    .and_then(|previous| {
+                        at_message
+                            .as_deref()
+                            .and_then(|answer| previous.answer(answer))
+                    });
+                match (answered, at_message) {
+                    (Some(answered), Some(at_message)) => {
+                        let answered_result = parse_rewritten(&answered, &at_message);
+                        if Handler::is_rolling(&answered_result) {
+                            (answered, answered_result)
+                        } else {
+                            (content, command_result)
+                        }
+                    }
+                    _ => (content, command_result),
+                }
+            }
+        };
+        if command_result.is_some() {
+            self.set_conversation(
+                key,
+                Conversation {
+                    message: content,
+                    understood: Handler::is_understood(&command_result),
+                    rolling: Handler::is_rolling(&command_result),
+                    guess: Handler::get_guess(&command_result, message),
+                    expires: Instant::now() + FOLLOW_UP_WINDOW,
+                },
+            );
+        }
+        command_result
+    }
+
+    fn is_understood(command_result: &Option<Result<CommandResult, command::Error>>) -> bool {
+        match command_result {
+            Some(Ok(command_result)) => command_result.is_understood(),
+            _ => false,
+        }
+    }
+
+    fn is_rolling(command_result: &Option<Result<CommandResult, command::Error>>) -> bool {
+        match command_result {
+            Some(Ok(command_result)) => command_result.is_rolling(),
+            _ => false,
+        }
+    }
+
+    /// The intent parser's guess at what a message meant, if it was parsed with too little
+    /// confidence to be run, so that the user can confirm it.
+    fn get_guess(
+        command_result: &Option<Result<CommandResult, command::Error>>,
+        message: &Message,
+    ) -> Option<Guess> {
+        match command_result {
+            Some(Ok(CommandResult::NaturalLanguage(
+                Err(command::Error::LowConfidence(_)),
+                Some(intent_result),
+                _,
+            ))) if intent_result.intent.intent_name.is_some() => Some(Guess {
+                message_id: message.id,
+                intent_result: intent_result.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The last message a user sent to the bot in a channel, if it can still be followed up on.
+    fn get_conversation(&self, key: (ChannelId, UserId)) -> Option<Conversation> {
+        let mut conversations = self
+            .conversations
+            .lock()
+            .expect("Mutex for conversations has been poisoned");
+        let now = Instant::now();
+        conversations.retain(|_, conversation| now < conversation.expires);
+        conversations.get(&key).cloned()
+    }
+
+    fn set_conversation(&self, key: (ChannelId, UserId), conversation: Conversation) {
+        self.conversations
+            .lock()
+            .expect("Mutex for conversations has been poisoned")
+            .insert(key, conversation);
+    }
+
+    /// The roles assigned to the bot in the guild that the message was sent in, so that mentions
+    /// of those roles can be treated as mentions of the bot.
+    fn get_bot_role_ids(&self, ctx: &Context, message: &Message) -> Vec<RoleId> {
+        message
+            .guild_id
+            .and_then(|guild_id| {
+                self.bot_id()
+                    .and_then(|bot_id| ctx.cache.read().member(guild_id, bot_id))
             })
-            .unwrap_or_else(|| Command::parse(engine, symspell, content, None, dice_only))
+            .map_or_else(Vec::new, |member| member.roles)
     }
 
+    /// The bot's permissions in the channel that the message was sent in, if it was sent in a
+    /// guild channel that is in the cache.
+    fn get_bot_permissions(&self, ctx: &Context, message: &Message) -> Option<Permissions> {
+        let bot_id = self.bot_id()?;
+        let channel = ctx.cache.read().guild_channel(message.channel_id)?;
+        // Bound to a variable so that the lock on the channel is released before it's dropped
+        let permissions = channel.read().permissions_for(&ctx.cache, bot_id).ok();
+        permissions
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn get_action(
         &self,
         command_result: Option<Result<CommandResult, command::Error>>,
         channel: &Channel,
         message: &Message,
+        role_ids: &[RoleId],
         is_admin: bool,
+        is_dm: bool,
         is_private: bool,
+        bot_permissions: Option<Permissions>,
     ) -> Action {
         command_result.map_or(Action::IgnoreCommandMissing, |command_result| {
             command_result
                 .map(|command_result| {
-                    let command = match command_result {
-                        CommandResult::Shorthand(command) => command,
-                        CommandResult::NaturalLanguage(command, intent_result, corrected) => {
-                            self.log_intent_result(&message, &intent_result, corrected.as_deref());
-                            command
-                        }
-                    };
-                    match command {
+                    match self.resolve_command(command_result, message, 0) {
                         Ok(command) => {
+                            let command_roles = self.get_command_roles(message.channel_id);
                             if !is_admin && !channel.enabled {
-                                Action::IgnoreChannelDisabled
+                                if self.should_notify_channel_disabled(message) {
+                                    Action::NotifyChannelDisabled
+                                } else {
+                                    Action::IgnoreChannelDisabled
+                                }
+                            } else if self.is_duplicate_command(message, channel) {
+                                Action::IgnoreDuplicateCommand
                             } else if is_private && !command.is_private() {
                                 Action::Respond(Box::new(Response::Warning(format!("It looks like you're trying to {}. You can't do that in a private message.", command.description()))))
+                            } else if let Some(refusal) = Handler::get_refusal(&command, channel, &command_roles, role_ids, is_admin, is_dm) {
+                                Action::Respond(Box::new(refusal))
+                            } else if let Err(error) = command.check_limits(channel.limits) {
+                                Action::Respond(Box::new(error.into_response(channel.verbosity)))
+                            } else if let Command::CheckPermissions = command {
+                                // The permissions come from the cache, which isn't available to
+                                // the other commands
+                                Action::Respond(Box::new(Handler::check_permissions(bot_permissions)))
                             } else {
                                 Action::Respond(Box::new(self.run_command(
                                     command,
                                     message.channel_id,
+                                    message.guild_id,
                                     message.author.id,
+                                    &message.attachments,
+                                    is_admin,
+                                    is_dm,
                                 )))
                             }
                         }
-                        Err(error) => Action::Respond(Box::new(error.into_response())),
+                        Err(error) => {
+                            Action::Respond(Box::new(error.into_response(channel.verbosity)))
+                        }
                     }
                 })
-                .unwrap_or_else(|error| Action::Respond(Box::new(error.into_response())))
+                .unwrap_or_else(|error| {
+                    Action::Respond(Box::new(error.into_response(channel.verbosity)))
+                })
         })
     }
 
-    fn run_command(&self, command: Command, channel_id: ChannelId, author_id: UserId) -> Response {
+    /// The warning refusing a command to a user who isn't allowed to run it in a channel, if any.
+    /// Restricting commands to a role only narrows who can run them, so the commands that need a
+    /// server administrator always need one, whatever roles the user has.
+    fn get_refusal(
+        command: &Command,
+        channel: &Channel,
+        command_roles: &[CommandRole],
+        role_ids: &[RoleId],
+        is_admin: bool,
+        is_dm: bool,
+    ) -> Option<Response> {
+        if command.is_admin() && !is_admin {
+            Some(Response::Warning(format!(
+                "It looks like you're trying to {}. Only server administrators can do that.",
+                command.description()
+            )))
+        } else if !command.is_allowed_for(is_dm, is_admin) {
+            Some(Response::Warning(format!(
+                "It looks like you're trying to {}. Only the DM can do that.",
+                command.description()
+            )))
+        } else if let Some(command_role) =
+            CommandRole::find_denied(command_roles, command, role_ids).filter(|_| !is_admin)
+        {
+            Some(Response::Warning(format!("It looks like you're trying to {}. Only members of <@&{}> can use {} commands in this channel.", command.description(), command_role.role_id, command_role.category)))
+        } else if channel.locked && !is_admin && command.is_editing() {
+            Some(Response::Warning(format!("It looks like you're trying to {}. The characters in this channel are locked, so only server administrators can change them.", command.description())))
+        } else {
+            None
+        }
+    }
+
+    /// The command to run for a parsed message, logging the intent of natural language commands
+    /// under the index of the part of the message they were parsed from, and marking a guess the
+    /// user has confirmed as confirmed. A message with several commands fails with the first
+    /// command that couldn't be understood.
+    fn resolve_command(
+        &self,
+        command_result: CommandResult,
+        message: &Message,
+        part_index: usize,
+    ) -> Result<Command, command::Error> {
+        match command_result {
+            CommandResult::Shorthand(command) => command,
+            CommandResult::NaturalLanguage(command, intent_result, corrected) => {
+                if let Some(intent_result) = intent_result {
+                    self.log_intent_result(
+                        message,
+                        part_index,
+                        &intent_result,
+                        corrected.as_deref(),
+                    );
+                }
+                command
+            }
+            CommandResult::Composite(results) => results
+                .into_iter()
+                .enumerate()
+                .map(|(part_index, result)| self.resolve_command(result, message, part_index))
+                .collect::<Result<Vec<Command>, command::Error>>()
+                .map(Command::Composite),
+            CommandResult::Confirmed(command, message_id) => {
+                if command.is_ok() {
+                    self.confirm_message(message_id);
+                }
+                command
+            }
+        }
+    }
+
+    /// Whether to tell a user who has directly mentioned the bot in a disabled channel that the
+    /// channel is disabled. Each user is told at most once per channel within the cooldown.
+    fn should_notify_channel_disabled(&self, message: &Message) -> bool {
+        let is_mentioned = self
+            .bot_id()
+            .map_or(false, |bot_id| message.mentions_user_id(bot_id));
+        if !is_mentioned {
+            return false;
+        }
+        let mut notices = self
+            .channel_disabled_notices
+            .lock()
+            .expect("Mutex for channel_disabled_notices has been poisoned");
+        let now = Instant::now();
+        notices
+            .retain(|_, notified| now.duration_since(*notified) < CHANNEL_DISABLED_NOTICE_COOLDOWN);
+        let key = (message.channel_id, message.author.id);
+        if notices.contains_key(&key) {
+            false
+        } else {
+            notices.insert(key, now);
+            true
+        }
+    }
+
+    /// Whether a message repeats the same user's previous command in the channel within the
+    /// channel's duplicate window, as happens when a message is accidentally sent twice.
+    fn is_duplicate_command(&self, message: &Message, channel: &Channel) -> bool {
+        if channel.duplicate_window == Duration::from_secs(0) {
+            return false;
+        }
+        let mut recent_commands = self
+            .recent_commands
+            .lock()
+            .expect("Mutex for recent_commands has been poisoned");
+        let now = Instant::now();
+        recent_commands.retain(|_, (_, expires)| now < *expires);
+        let key = (message.channel_id, message.author.id);
+        let content = message.content.trim();
+        match recent_commands.get(&key) {
+            Some((previous, _)) if previous == content => true,
+            _ => {
+                recent_commands.insert(key, (content.to_owned(), now + channel.duplicate_window));
+                false
+            }
+        }
+    }
+
+    fn run_command(
+        &self,
+        command: Command,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+        attachments: &[Attachment],
+        is_admin: bool,
+        is_dm: bool,
+    ) -> Response {
         match command {
-            Command::AttackRoll(roll) => self.attack_roll(&roll, channel_id, author_id),
-            Command::CharacterRoll(roll) => self.character_roll(&roll, channel_id, author_id),
+            Command::AddBonus(bonus) => self.add_bonus(bonus, channel_id, author_id),
+            Command::AddCharacterEffect(effect) => {
+                self.add_character_effect(effect, channel_id, author_id)
+            }
+            Command::AddCombatant(entry, hit_points, hidden) => {
+                self.add_combatant(entry, hit_points, hidden, channel_id)
+            }
+            Command::AddCompanion(kind, name) => {
+                self.add_companion(kind, &name, channel_id, author_id)
+            }
+            Command::AddMonsters(count, name, hidden) => {
+                self.add_monsters(count, &name, hidden, channel_id, guild_id)
+            }
+            Command::AddNote(text) => self.add_note(&text, channel_id, author_id),
+            Command::AddOngoingEffect(effect) => self.add_ongoing_effect(effect, channel_id),
+            Command::AddStatus(status, rounds) => {
+                self.add_status(status, rounds, channel_id, author_id)
+            }
+            Command::AddWeaponMastery(weapon_name) => {
+                self.add_weapon_mastery(weapon_name, channel_id, author_id)
+            }
+            Command::AddWildShapeForm(form) => {
+                self.add_wild_shape_form(&form, channel_id, author_id)
+            }
+            Command::AsCompanion(companion, command) => {
+                self.companion_roll(&companion, *command, channel_id, author_id)
+            }
+            Command::AttackRoll(roll, count, smite, target, cover) => self.attack_roll(
+                &roll,
+                count,
+                smite,
+                target.as_deref(),
+                cover,
+                AttackPart::Both,
+                None,
+                channel_id,
+                author_id,
+            ),
+            Command::AwardExperience(amount, reason) => {
+                self.award_experience(amount, reason, channel_id, author_id, is_dm)
+            }
+            Command::CastSpell(name, level, ritual) => {
+                self.cast_spell(&name, level, ritual, channel_id, author_id)
+            }
+            Command::CharacterRoll(roll, purpose) => {
+                self.character_roll(&roll, purpose, None, channel_id, author_id)
+            }
+            Command::CheckPermissions => Handler::check_permissions(None),
+            Command::ClearCharacterEffects => self.clear_character_effects(channel_id, author_id),
+            Command::ClearInitiativeOrder => self.clear_initiative_order(channel_id),
+            Command::ClearOngoingEffects(target) => self.clear_ongoing_effects(&target, channel_id),
+            Command::ClearResistances(target) => self.clear_resistances(&target, channel_id),
+            Command::Composite(commands) => self.run_composite(
+                commands,
+                channel_id,
+                guild_id,
+                author_id,
+                attachments,
+                is_admin,
+                is_dm,
+            ),
+            Command::ContestedCheck(contest, attacker, defender) => {
+                self.contested_check(contest, attacker, defender, channel_id, author_id)
+            }
+            Command::CopyCharacter(copy) => self.copy_character(copy, channel_id, author_id),
+            Command::CreateCharacter(name) => self.create_character(&name, channel_id, author_id),
+            Command::CreatePregen(class, level) => {
+                self.create_pregen(class, level, channel_id, author_id)
+            }
+            Command::DamageCombatant(damage) => self.damage_combatant(damage, channel_id),
+            Command::DamageRoll(roll, critical_hit) => self.attack_roll(
+                &roll,
+                AttackCount::Times(1),
+                None,
+                None,
+                None,
+                AttackPart::Damage { critical_hit },
+                None,
+                channel_id,
+                author_id,
+            ),
+            Command::DamageWildShape(amount) => {
+                self.change_wild_shape_hit_points(amount, channel_id, author_id)
+            }
+            Command::DeleteCharacter(name) => self.delete_character(&name, channel_id, author_id),
+            Command::DeleteMonster(name) => self.delete_monster(&name, channel_id, is_dm),
+            Command::DeleteNote(id) => self.delete_note(id, channel_id, author_id, is_dm),
+            Command::DeleteRandomTable(name) => self.delete_random_table(&name, channel_id, is_dm),
+            Command::DepositInStash(loot) => self.deposit_in_stash(loot, channel_id),
+            Command::DisableContentPack(name) => {
+                self.disable_content_pack(&name, guild_id, is_admin)
+            }
+            Command::EnableContentPack(name) => {
+                self.enable_content_pack(&name, channel_id, guild_id, is_admin)
+            }
+            Command::EncounterDifficulty(monsters) => {
+                self.encounter_difficulty(&monsters, channel_id, guild_id, author_id, is_dm)
+            }
+            Command::EndEncounter => self.end_encounter(channel_id, author_id, is_dm),
+            Command::ExportChannelSettings => self.export_channel_settings(channel_id),
+            Command::ExportCharacter => self.export_character(channel_id, author_id),
+            Command::ExportTrainingData => self.export_training_data(author_id),
+            Command::GainCurrency(amount) => self.gain_currency(amount, channel_id, author_id),
+            Command::GenerateDungeon(rooms) => Handler::generate_dungeon(rooms),
+            Command::GenerateSettlement(size) => Handler::generate_settlement(size),
+            Command::GenerateTavern => Handler::generate_tavern(),
+            Command::GrantBardicInspiration(recipient) => {
+                self.grant_bardic_inspiration(recipient, channel_id, author_id)
+            }
+            Command::GrantInspiration(user_id) => {
+                self.grant_inspiration(user_id, channel_id, is_dm)
+            }
+            Command::GroupSavingThrow(ability, dc) => {
+                self.group_saving_throw(ability, dc, channel_id, is_dm)
+            }
+            Command::HealCombatant(name, amount) => self.heal_combatant(&name, amount, channel_id),
+            Command::HealWildShape(amount) => {
+                self.change_wild_shape_hit_points(-amount, channel_id, author_id)
+            }
             Command::Help => Handler::help(),
             Command::HelpShorthand => Handler::help_shorthand(),
-            Command::Roll(roll) => self.roll(roll, channel_id, author_id),
+            Command::ImportChannelSettings => self.import_channel_settings(attachments, channel_id),
+            Command::ImportCharacter(source) => {
+                self.import_character(source, attachments, channel_id, author_id)
+            }
+            Command::ImproviseMonster(challenge_rating, role) => {
+                Response::QuickStatBlock(QuickStatBlock::new(challenge_rating, role))
+            }
+            Command::InstallContentPack => {
+                self.install_content_pack(attachments, channel_id, guild_id, is_admin)
+            }
+            Command::MonsterAttack(name, condition, target) => {
+                self.monster_attack(&name, condition, target, channel_id, guild_id)
+            }
+            Command::NextRound => self.next_turn(channel_id, true),
+            Command::NextTurn => self.next_turn(channel_id, false),
+            Command::PinQuickReference => self.pin_quick_reference(channel_id, author_id),
+            Command::PlanTravel(journey) => Response::Travel(journey),
+            Command::PurgeUserFromChannel(user_id) => {
+                self.purge_user_from_channel(user_id, channel_id, is_admin)
+            }
+            Command::RecallLore(roll, subject) => {
+                self.recall_lore(&roll, &subject, channel_id, author_id)
+            }
+            Command::ReloadModel => self.reload_model(author_id),
+            Command::RemoveBonus(source) => self.remove_bonus(&source, channel_id, author_id),
+            Command::RemoveCharacterEffect(effect) => {
+                self.remove_character_effect(&effect, channel_id, author_id)
+            }
+            Command::RemoveContentPack(name) => self.remove_content_pack(&name, guild_id, is_admin),
+            Command::RemoveCounter(name) => self.remove_counter(&name, channel_id, author_id),
+            Command::RemoveLore(subject) => self.remove_lore(&subject, channel_id, is_dm),
+            Command::RemoveStatus(status) => self.remove_status(status, channel_id, author_id),
+            Command::RemoveWeaponMastery(weapon_name) => {
+                self.remove_weapon_mastery(weapon_name, channel_id, author_id)
+            }
+            Command::ResetHouseRules => self.reset_house_rules(channel_id, is_dm),
+            Command::RevealHiddenRolls => self.reveal_hidden_rolls(channel_id, author_id),
+            Command::RevealLore(subject) => self.reveal_lore(&subject, channel_id, is_dm),
+            Command::RevertWildShape => self.revert_wild_shape(channel_id, author_id),
+            Command::Rest(rest) => self.rest(rest, channel_id, author_id),
+            Command::RestoreCounter(name, amount) => {
+                self.restore_counter(&name, amount, channel_id, author_id)
+            }
+            Command::RestrictCommands(category, role_id) => {
+                self.restrict_commands(category, role_id, channel_id, is_admin)
+            }
+            Command::Roll(roll, purpose) => self.roll(roll, purpose, channel_id, author_id),
+            Command::RollForTable(roll) => self.roll_for_table(roll, channel_id),
+            Command::RollHidden(roll) => self.roll_hidden(roll, channel_id, author_id),
+            Command::RollRandomTable(name) => self.roll_random_table(&name, channel_id),
+            Command::RollTreasure(treasure) => Handler::roll_treasure(treasure),
+            Command::RollWeather(season) => Handler::roll_weather(season),
+            Command::SaveMonster(monster) => self.save_monster(monster, channel_id, is_dm),
+            Command::ShowBonuses => self.show_bonuses(channel_id, author_id),
+            Command::ShowChannelSettings => self.show_channel_settings(channel_id),
+            Command::ShowCharacterAudit(user_id) => {
+                self.show_character_audit(user_id, channel_id, is_admin)
+            }
+            Command::ShowCharacterEffects => self.show_character_effects(channel_id, author_id),
+            Command::ShowCharacterSheet => self.show_character_sheet(channel_id, author_id),
+            Command::ShowCommandRestrictions => self.show_command_restrictions(channel_id),
+            Command::ShowCompanions => self.show_companions(channel_id, author_id),
+            Command::ShowContentPacks => self.show_content_packs(guild_id),
+            Command::ShowCounters => self.show_counters(channel_id, author_id),
+            Command::ShowCurrency => self.show_currency(channel_id, author_id),
+            Command::ShowDiceLimits => self.show_dice_limits(channel_id),
+            Command::ShowDungeonMaster => self.show_dungeon_master(channel_id),
+            Command::ShowEdition => self.show_edition(channel_id),
+            Command::ShowHouseRules => self.show_house_rules(channel_id),
+            Command::ShowInitiativeOrder => self.show_initiative_order(channel_id),
+            Command::ShowLore => self.show_lore(channel_id, is_dm),
+            Command::ShowMonster(name) => self.show_monster(&name, channel_id, guild_id),
+            Command::ShowMonsters => self.show_monsters(channel_id, guild_id),
+            Command::ShowNotes(search) => self.show_notes(search, channel_id),
+            Command::ShowPartyCurrency => self.show_party_currency(channel_id, is_dm),
+            Command::ShowPassiveScore(check) => {
+                self.show_passive_scores(Some(check), channel_id, author_id)
+            }
+            Command::ShowPassiveScores => self.show_passive_scores(None, channel_id, author_id),
+            Command::ShowRandomTable(name) => self.show_random_table(&name, channel_id, is_dm),
+            Command::ShowRandomTables => self.show_random_tables(channel_id),
+            Command::ShowServerDefaults => self.show_server_defaults(guild_id),
+            Command::ShowSpellSlots => self.show_spell_slots(channel_id, author_id),
+            Command::ShowStash => self.show_stash(channel_id),
+            Command::ShowStatuses => self.show_statuses(channel_id, author_id),
+            Command::ShowTimeZone => self.show_time_zone(guild_id, author_id),
+            Command::ShowWeaponMasteries => self.show_weapon_masteries(channel_id, author_id),
+            Command::ShowWildShapes => self.show_wild_shapes(channel_id, author_id),
+            Command::SetAbility(ability, score) => {
+                self.set_ability(ability, score, channel_id, author_id)
+            }
+            Command::SetAlert(alert) => self.set_alert(alert, channel_id, author_id),
+            Command::SetArmorClass(armor_class) => {
+                self.set_armor_class(armor_class, channel_id, author_id)
+            }
+            Command::SetInitiativeBonus(initiative_bonus) => {
+                self.set_initiative_bonus(initiative_bonus, channel_id, author_id)
+            }
+            Command::SetChannelDiceOnly(dice_only) => {
+                self.set_channel_dice_only(dice_only, channel_id)
+            }
+            Command::SetChannelDuplicateWindow(duplicate_window) => {
+                self.set_channel_duplicate_window(duplicate_window, channel_id)
+            }
+            Command::SetChannelEnabled(enabled) => self.set_channel_enabled(enabled, channel_id),
+            Command::SetChannelKeepRollMessages(keep_roll_messages) => {
+                self.set_channel_keep_roll_messages(keep_roll_messages, channel_id)
+            }
+            Command::SetChannelLocked(locked) => self.set_channel_locked(locked, channel_id),
+            Command::SetChannelPrefix(prefix) => self.set_channel_prefix(&prefix, channel_id),
+            Command::SetChannelSpellingCorrection(spelling_correction) => {
+                self.set_channel_spelling_correction(spelling_correction, channel_id)
+            }
+            Command::SetCharacterName(name) => {
+                self.set_character_name(&name, channel_id, author_id)
+            }
+            Command::SetClass(class, level, subclass) => {
+                self.set_class(class, level, subclass.as_deref(), channel_id, author_id)
+            }
+            Command::SetCombatantArmorClass(name, armor_class) => {
+                self.set_combatant_armor_class(&name, armor_class, channel_id)
+            }
+            Command::SetCombatantExperience(name, experience) => {
+                self.set_combatant_experience(&name, experience, channel_id)
+            }
+            Command::SetCounter(counter) => self.set_counter(counter, channel_id, author_id),
+            Command::SetDiceLimits(limits) => self.set_dice_limits(limits, channel_id, is_dm),
+            Command::SetDungeonMaster(dungeon_master) => {
+                self.set_dungeon_master(dungeon_master, channel_id, is_admin || is_dm)
+            }
+            Command::SetEdition(edition) => self.set_edition(edition, channel_id, is_dm),
+            Command::SetHouseRule(house_rule) => self.set_house_rule(house_rule, channel_id, is_dm),
+            Command::SetFightingStyle(fighting_style) => {
+                self.set_fighting_style(fighting_style, channel_id, author_id)
+            }
+            Command::SetHitPoints(name, hit_points, hidden) => {
+                self.set_hit_points(&name, hit_points, hidden, channel_id)
+            }
+            Command::SetInitiativeOrder(order) => self.set_initiative_order(order, channel_id),
+            Command::SetLevel(level) => self.set_level(level, channel_id, author_id),
+            Command::SetLore(lore) => self.set_lore(lore, channel_id, is_dm),
+            Command::SetObservant(observant) => {
+                self.set_observant(observant, channel_id, author_id)
+            }
+            Command::SetRandomTable(table) => self.set_random_table(table, channel_id, is_dm),
+            Command::SetPortrait(url) => self.set_portrait(&url, channel_id, author_id),
+            Command::SetResistances(resistances) => self.set_resistances(resistances, channel_id),
+            Command::SetServerDefault(default) => self.set_server_default(default, guild_id),
+            Command::SetServerTimeZone(time_zone) => {
+                self.set_server_time_zone(time_zone, guild_id, is_admin)
+            }
+            Command::SetTimeZone(time_zone) => self.set_time_zone(time_zone, guild_id, author_id),
+            Command::SneakAttackRoll => self.sneak_attack_roll(channel_id, author_id),
+            Command::SpendCounter(name, amount) => {
+                self.spend_counter(&name, amount, channel_id, author_id)
+            }
+            Command::SpendCurrency(amount) => self.spend_currency(amount, channel_id, author_id),
+            Command::SwitchCharacter(name) => self.switch_character(&name, channel_id, author_id),
+            Command::ToHitRoll(roll, target, cover) => self.attack_roll(
+                &roll,
+                AttackCount::Times(1),
+                None,
+                target.as_deref(),
+                cover,
+                AttackPart::ToHit,
+                None,
+                channel_id,
+                author_id,
+            ),
+            Command::UseBardicInspiration(command) => self.use_bardic_inspiration(
+                *command,
+                channel_id,
+                guild_id,
+                author_id,
+                attachments,
+                is_admin,
+                is_dm,
+            ),
+            Command::UseInspiration(command) => self.use_inspiration(
+                *command,
+                channel_id,
+                guild_id,
+                author_id,
+                attachments,
+                is_admin,
+                is_dm,
+            ),
+            Command::BlindRoll(command) => self.blind_roll(
+                *command,
+                channel_id,
+                guild_id,
+                author_id,
+                attachments,
+                is_admin,
+                is_dm,
+            ),
+            Command::FlankingAttack(command) => self.flanking_attack(
+                *command,
+                channel_id,
+                guild_id,
+                author_id,
+                attachments,
+                is_admin,
+                is_dm,
+            ),
+            Command::VerifyRoll(code) => self.verify_roll(&code, guild_id, author_id),
+            Command::WithdrawFromStash(loot) => {
+                self.withdraw_from_stash(loot, channel_id, author_id)
+            }
+            Command::WildShape(form) => self.wild_shape(&form, channel_id, author_id),
+            Command::WildShapeAttack(attack, condition, target) => {
+                self.wild_shape_attack(attack.as_deref(), condition, target, channel_id, author_id)
+            }
+        }
+    }
+
+    fn log_intent_result(
+        &self,
+        message: &Message,
+        part_index: usize,
+        intent_result: &IntentParserResult,
+        corrected: Option<&str>,
+    ) {
+        self.pool
+            .get()
+            .map_err(|error| error!(target: "dungeon-helper", "Error obtaining database connection. Message ID: {}; Error: {}", message.id, error))
+            .and_then(|mut connection| {
+                log_intent_result(&mut connection, message, part_index, intent_result, corrected)
+                    .map_err(|error|
+                        error!(target: "dungeon-helper", "Error logging intent result. Message ID: {}; Error: {}", message.id, error)
+                    )
+            })
+            .unwrap_or(())
+    }
+
+    fn confirm_message(&self, message_id: MessageId) {
+        self.pool
+            .get()
+            .map_err(|error| error!(target: "dungeon-helper", "Error obtaining database connection. Message ID: {}; Error: {}", message_id, error))
+            .and_then(|connection| {
+                confirm_message(&connection, message_id)
+                    .map(|_| ())
+                    .map_err(|error|
+                        error!(target: "dungeon-helper", "Error confirming logged message. Message ID: {}; Error: {}", message_id, error)
+                    )
+            })
+            .unwrap_or(())
+    }
+
+    fn log_roll(
+        &self,
+        message: &Message,
+        entry_index: usize,
+        entry: &RollHistoryEntry,
+        signature: &str,
+    ) {
+        self.pool
+            .get()
+            .map_err(|error| error!(target: "dungeon-helper", "Error obtaining database connection. Message ID: {}; Error: {}", message.id, error))
+            .and_then(|connection| {
+                let roll = SignedRoll::new(message, entry);
+                log_roll(&connection, &roll, entry_index, entry, signature)
+                    .map(|_| ())
+                    .map_err(|error|
+                        error!(target: "dungeon-helper", "Error logging roll. Message ID: {}; Error: {}", message.id, error)
+                    )
+            })
+            .unwrap_or(())
+    }
+
+    /// Pin a newly sent quick reference and remember it so that it can be kept up to date,
+    /// unpinning the character's previous quick reference if there is one.
+    fn pin_quick_reference_message(
+        &self,
+        ctx: &Context,
+        message: &Message,
+        sent_message: &Message,
+        pinned: PinnedQuickReference,
+    ) {
+        if let Err(error) = message.channel_id.pin(&ctx.http, sent_message.id) {
+            error!(target: "dungeon-helper", "Error pinning quick reference. Message ID: {}; Sent Message ID: {}; Error: {:?}", message.id, sent_message.id, error);
+            return;
+        }
+        self.pool
+            .get()
+            .map_err(|error| error!(target: "dungeon-helper", "Error obtaining database connection. Message ID: {}; Error: {}", message.id, error))
+            .and_then(|connection| {
+                let previous = PinnedQuickReference::get(&connection, message.channel_id, pinned.user_id, &pinned.name)
+                    .map_err(|error| error!(target: "dungeon-helper", "Error retrieving pinned quick reference. Message ID: {}; Error: {}", message.id, error))?;
+                if let Some(previous) = previous {
+                    if let Err(error) = message.channel_id.unpin(&ctx.http, previous.message_id) {
+                        error!(target: "dungeon-helper", "Error unpinning previous quick reference. Message ID: {}; Pinned Message ID: {}; Error: {:?}", message.id, previous.message_id, error);
+                    }
+                }
+                pinned
+                    .set(&connection, message.channel_id)
+                    .map(|_| ())
+                    .map_err(|error| error!(target: "dungeon-helper", "Error saving pinned quick reference. Message ID: {}; Error: {}", message.id, error))
+            })
+            .unwrap_or(())
+    }
+
+    /// Edit the pinned quick references in the message's channel whose characters have changed
+    /// since they were last shown.
+    /// Send the result of a blind roll to the channel's DM in a direct message.
+    fn send_blind_roll(
+        &self,
+        ctx: &Context,
+        message: &Message,
+        author_nick: &str,
+        roll: &Response,
+        dm_user_id: UserId,
+    ) {
+        let result = self.delivery_metrics.retry("send blind roll", || {
+            dm_user_id.to_user(ctx).and_then(|user| {
+                user.direct_message(ctx, |builder| {
+                    roll.to_message(author_nick, message, builder)
+                        .content(format!(
+                            "🙈 Blind roll from <@{}> in <#{}>",
+                            message.author.id, message.channel_id
+                        ))
+                })
+            })
+        });
+        match result {
+            Ok(sent_message) => {
+                info!(target: "dungeon-helper", "Sent blind roll to DM. Message ID: {}; Sent Message ID: {}", message.id, sent_message.id)
+            }
+            Err(error) => {
+                error!(target: "dungeon-helper", "Error sending blind roll to DM. Message ID: {}; Error: {:?}", message.id, error)
+            }
         }
     }
 
-    fn log_intent_result(
-        &self,
-        message: &Message,
-        intent_result: &IntentParserResult,
-        corrected: Option<&str>,
-    ) {
-        self.pool
-            .get()
-            .map_err(|error| error!(target: "dungeon-helper", "Error obtaining database connection. Message ID: {}; Error: {}", message.id, error))
-            .and_then(|mut connection| {
-                log_intent_result(&mut connection, message, intent_result, corrected)
-                    .map_err(|error|
-                        error!(target: "dungeon-helper", "Error logging intent result. Message ID: {}; Error: {}", message.id, error)
-                    )
+    fn refresh_quick_references(&self, ctx: &Context, message: &Message) {
+        let connection = match self.pool.get() {
+            Ok(connection) => connection,
+            Err(error) => {
+                error!(target: "dungeon-helper", "Error obtaining database connection. Message ID: {}; Error: {}", message.id, error);
+                return;
+            }
+        };
+        let pinned_references = match PinnedQuickReference::get_all(&connection, message.channel_id)
+        {
+            Ok(pinned_references) => pinned_references,
+            Err(error) => {
+                error!(target: "dungeon-helper", "Error retrieving pinned quick references. Message ID: {}; Error: {}", message.id, error);
+                return;
+            }
+        };
+        for mut pinned in pinned_references {
+            let character = match Character::get_by_name(
+                &connection,
+                message.channel_id,
+                pinned.user_id,
+                &pinned.name,
+            ) {
+                Ok(Some(character)) => character,
+                Ok(None) => continue,
+                Err(error) => {
+                    error!(target: "dungeon-helper", "Error retrieving character for quick reference. Message ID: {}; Error: {}", message.id, error);
+                    continue;
+                }
+            };
+            let description = QuickReference::new(&character).to_string();
+            if description == pinned.description {
+                continue;
+            }
+            let author_nick = character.display_name().unwrap_or(&pinned.author_name);
+            let avatar_url = character
+                .avatar_url()
+                .map(|s| s.to_owned())
+                .or_else(|| pinned.user_id.to_user(ctx).ok().map(|user| user.face()));
+            let result = message
+                .channel_id
+                .edit_message(&ctx.http, pinned.message_id, |builder| {
+                    builder.embed(|e| {
+                        quick_reference_embed(e, author_nick, &description, avatar_url.as_ref())
+                    })
+                });
+            match result {
+                Ok(_) => {
+                    info!(target: "dungeon-helper", "Updated pinned quick reference. Message ID: {}; Pinned Message ID: {}", message.id, pinned.message_id);
+                    pinned.description = description;
+                    if let Err(error) = pinned.set(&connection, message.channel_id) {
+                        error!(target: "dungeon-helper", "Error saving pinned quick reference. Message ID: {}; Error: {}", message.id, error);
+                    }
+                }
+                Err(error) => {
+                    error!(target: "dungeon-helper", "Error updating pinned quick reference. Message ID: {}; Pinned Message ID: {}; Error: {:?}", message.id, pinned.message_id, error)
+                }
+            }
+        }
+    }
+
+    /// Make one or more attacks with the author's character. Each attack rolls its own attack,
+    /// damage and effects, and the character's conditions apply to every attack. An attack can
+    /// also roll just to hit, or just its damage once it's known to have hit. Cover adds to the
+    /// target's armour class.
+    #[allow(clippy::too_many_arguments)]
+    fn attack_roll(
+        &self,
+        attack_roll: &AttackRoll,
+        count: AttackCount,
+        smite: Option<Smite>,
+        target: Option<&str>,
+        cover: Option<Cover>,
+        part: AttackPart,
+        companion_name: Option<&str>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let edition = self.get_channel(channel_id).edition;
+        let critical_hits = self.get_house_rules(channel_id).critical_hits;
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let loaded =
+                    Handler::get_character(&connection, channel_id, author_id, companion_name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    .and_then(|character| {
+                        character.map_or(
+                            Err(Response::Warning(
+                                CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned(),
+                            )),
+                            |character| match attack_roll {
+                                AttackRoll::Weapon(attack_roll) => character
+                                    .has_weapon_proficiency(
+                                        &connection,
+                                        channel_id,
+                                        author_id,
+                                        attack_roll.weapon,
+                                        attack_roll.weapon.to_weapon().category,
+                                    )
+                                    .map(|proficiency| (character, proficiency))
+                                    .map_err(|error| Response::Error(Error::RusqliteError(error))),
+                                _ => Ok((character, false)),
+                            },
+                        )
+                    })
+                    .and_then(|(character, proficiency)| {
+                        Status::get(&connection, channel_id, author_id, character.name())
+                            .map(|statuses| (character, proficiency, statuses))
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    });
+                loaded.map(|(character, proficiency, statuses)| {
+                    (connection, character, proficiency, statuses)
+                })
+            })
+            .and_then(|(mut connection, character, proficiency, statuses)| {
+                let strength = character.strength().map(|a| a.modifier);
+                let dexterity = character.dexterity().map(|a| a.modifier);
+                let proficiency_bonus = character.proficiency_bonus();
+                let times = count.times(character.attacks());
+                if times > MAXIMUM_ATTACKS {
+                    return Err(Response::Warning(format!(
+                        "You can make at most {} attacks at once.",
+                        MAXIMUM_ATTACKS
+                    )));
+                }
+                if times > 1 && attack_roll.is_single_attack() {
+                    return Err(Response::Warning(format!(
+                        "You can only make one attack with a {} each time you take an action, no matter how many attacks you can make. Try attacking once.",
+                        attack_roll.get_name()
+                    )));
+                }
+                if !part.rolls_to_hit() && !attack_roll.deals_damage() {
+                    return Err(Response::Warning(format!(
+                        "A {} doesn't deal any damage. Try rolling to hit with it instead.",
+                        attack_roll.get_name()
+                    )));
+                }
+                let spell_slots = match smite {
+                    Some(smite) => Handler::find_smite_spell_slots(
+                        &connection,
+                        channel_id,
+                        author_id,
+                        &character,
+                        smite,
+                    )?,
+                    None => None,
+                };
+                let target = match target {
+                    Some(target) => Some(Handler::attack_target(&connection, channel_id, target)?),
+                    None => None,
+                }
+                .map(|(name, armor_class)| {
+                    (name, armor_class + cover.map_or(0, Cover::armor_class_bonus))
+                });
+                let mut rng = rand::thread_rng();
+                let to_hit_roll = attack_roll
+                    .to_attack_roll(
+                        strength,
+                        dexterity,
+                        proficiency_bonus,
+                        proficiency,
+                        character.martial_arts(),
+                        character.fighting_style(),
+                    )
+                    .map(|roll| roll.add_modifier(character.attack_bonus()))
+                    .ok_or_else(|| Response::Warning(ABILITY_NOT_SET_WARNING_TEXT.to_owned()))?;
+                let (to_hit_roll, statuses) =
+                    status::apply_to_roll(to_hit_roll, &statuses, Status::attack_roll_condition);
+                let mut attacks = Vec::with_capacity(times);
+                for _ in 0..times {
+                    let to_hit_result = to_hit_roll.roll(&mut rng);
+                    let critical_hit = match part {
+                        AttackPart::Damage { critical_hit } => critical_hit,
+                        _ => to_hit_result.critical() == Some(Critical::Success),
+                    };
+                    // The extra damage of a critical hit depends on the channel's house rules
+                    let damage_roll = attack_roll
+                        .to_damage_roll(
+                            strength,
+                            dexterity,
+                            false,
+                            character.martial_arts_damage_die(edition),
+                            character.fighting_style(),
+                            edition,
+                        )
+                        .map(|roll| {
+                            if critical_hit {
+                                critical_hits.damage_roll(roll)
+                            } else {
+                                roll
+                            }
+                        })
+                        .ok_or_else(|| {
+                            Response::Warning(ABILITY_NOT_SET_WARNING_TEXT.to_owned())
+                        })?;
+                    let damage_result = damage_roll.roll(&mut rng);
+                    // The effects on the attack roll aren't used up by a damage roll on its own
+                    let effects = if part.rolls_to_hit() {
+                        character_effect::apply(
+                            &connection,
+                            channel_id,
+                            author_id,
+                            character.name(),
+                            |targets| targets.attack_rolls,
+                            &mut rng,
+                        )
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    } else {
+                        Vec::new()
+                    };
+                    let hit = target.as_ref().map(|(_, armor_class)| {
+                        Attack::hits(
+                            &to_hit_result,
+                            character_effect::total(&effects),
+                            *armor_class,
+                        )
+                    });
+                    // The smite is added to the first attack that hits, or that doesn't
+                    // automatically miss when there's no target to hit
+                    let smite_damage = smite
+                        .filter(|_| {
+                            to_hit_result.critical() != Some(Critical::Failure)
+                                && hit != Some(false)
+                                && attacks.iter().all(|attack: &Attack| attack.smite.is_none())
+                        })
+                        .map(|smite| {
+                            let roll = smite.to_damage_roll(false, edition);
+                            let roll = if critical_hit {
+                                critical_hits.damage_roll(roll)
+                            } else {
+                                roll
+                            };
+                            let result = roll.roll(&mut rng);
+                            SmiteDamage {
+                                level: smite.level,
+                                roll,
+                                result,
+                            }
+                        });
+                    attacks.push(Attack {
+                        to_hit_result,
+                        damage_roll,
+                        damage_result,
+                        effects,
+                        smite: smite_damage,
+                        hit,
+                    });
+                }
+                if let Some(mut spell_slots) = spell_slots {
+                    if attacks.iter().any(|attack| attack.smite.is_some()) {
+                        spell_slots.spend(1);
+                        spell_slots
+                            .set(&connection, channel_id, author_id, character.name())
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                    }
+                }
+                let target = match target {
+                    Some((name, armor_class)) => Some(Handler::damage_attack_target(
+                        &mut connection,
+                        channel_id,
+                        name,
+                        armor_class,
+                        &attacks,
+                        attack_roll.deals_damage() && part.rolls_damage(),
+                        attack_roll.get_damage_type(),
+                    )?),
+                    None => None,
+                };
+                // Weapon masteries are only part of the 2024 rules
+                let mastery = match attack_roll {
+                    AttackRoll::Weapon(attack_roll)
+                        if edition == Edition::Rules2024
+                            && character.weapon_masteries().contains(&attack_roll.weapon) =>
+                    {
+                        attack_roll.to_mastery_effect(
+                            strength,
+                            dexterity,
+                            proficiency_bonus,
+                            character.martial_arts(),
+                        )
+                    }
+                    _ => None,
+                };
+                let heroic_inspiration = Handler::gain_heroic_inspiration(
+                    &connection,
+                    channel_id,
+                    author_id,
+                    character.name(),
+                    edition,
+                    part.rolls_to_hit()
+                        && attacks.iter().any(|attack| {
+                            attack.to_hit_result.critical() == Some(Critical::Failure)
+                        }),
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let avatar_url = character.avatar_url().map(|s| s.to_owned());
+                let character_name = character
+                    .display_name()
+                    .or(companion_name)
+                    .map(|s| s.to_owned());
+                Ok((
+                    to_hit_roll,
+                    attacks,
+                    target,
+                    mastery,
+                    heroic_inspiration,
+                    statuses,
+                    avatar_url,
+                    character_name,
+                ))
+            })
+            .map(
+                |(
+                    to_hit_roll,
+                    mut attacks,
+                    target,
+                    mastery,
+                    heroic_inspiration,
+                    statuses,
+                    avatar_url,
+                    character_name,
+                )| {
+                    if attacks.len() == 1 {
+                        let attack = attacks.remove(0);
+                        Response::AttackRoll {
+                            attack_name: attack_roll.get_name(),
+                            attack_handedness: attack_roll.get_handedness(),
+                            to_hit_roll,
+                            to_hit_result: attack.to_hit_result,
+                            damage_roll: attack.damage_roll,
+                            damage_result: attack.damage_result,
+                            part,
+                            cover,
+                            deals_damage: attack_roll.deals_damage(),
+                            damage_type: attack_roll.get_damage_type(),
+                            effects: attack.effects,
+                            smite: attack.smite,
+                            hit: attack.hit,
+                            target,
+                            mastery,
+                            special: attack_roll.special(),
+                            heroic_inspiration,
+                            statuses,
+                            avatar_url,
+                            character_name,
+                        }
+                    } else {
+                        Response::AttackRolls {
+                            attack_name: attack_roll.get_name(),
+                            attack_handedness: attack_roll.get_handedness(),
+                            to_hit_roll,
+                            attacks,
+                            damage_type: attack_roll.get_damage_type(),
+                            target,
+                            cover,
+                            mastery,
+                            special: attack_roll.special(),
+                            heroic_inspiration,
+                            statuses,
+                            avatar_url,
+                            character_name,
+                        }
+                    }
+                },
+            )
+            .unwrap_or_else(identity)
+    }
+
+    /// Retrieve one of the user's characters by name, such as a companion, or their active
+    /// character if no name is given. A character in a wild shape has the form's Strength,
+    /// Dexterity and Constitution.
+    fn get_character(
+        connection: &Connection,
+        channel_id: ChannelId,
+        author_id: UserId,
+        name: Option<&str>,
+    ) -> RusqliteResult<Option<Character>> {
+        let character = match name {
+            Some(name) => Character::get_by_name(connection, channel_id, author_id, name)?,
+            None => Character::get(connection, channel_id, author_id)?,
+        };
+        match character {
+            Some(mut character) => {
+                let form = WildShape::get(connection, channel_id, author_id, character.name())?
+                    .and_then(|wild_shape| wild_shape.stat_block());
+                if let Some(stat_block) = form {
+                    character.assume_form(&stat_block.abilities);
+                }
+                Ok(Some(character))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Make a check, saving throw or attack as one of the user's companions instead of their
+    /// active character, such as "Roll perception as my familiar".
+    fn companion_roll(
+        &self,
+        companion: &str,
+        command: Command,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let found = self
+            .pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Companion::find(&connection, channel_id, author_id, companion)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .ok_or_else(|| Response::Warning(COMPANION_NOT_FOUND_WARNING_TEXT.to_owned()))
+            });
+        let companion = match found {
+            Ok(companion) => companion,
+            Err(response) => return response,
+        };
+        let name = Some(companion.name.as_str());
+        match command {
+            Command::AttackRoll(roll, count, smite, target, cover) => self.attack_roll(
+                &roll,
+                count,
+                smite,
+                target.as_deref(),
+                cover,
+                AttackPart::Both,
+                name,
+                channel_id,
+                author_id,
+            ),
+            Command::CharacterRoll(roll, purpose) => {
+                self.character_roll(&roll, purpose, name, channel_id, author_id)
+            }
+            Command::DamageRoll(roll, critical_hit) => self.attack_roll(
+                &roll,
+                AttackCount::Times(1),
+                None,
+                None,
+                None,
+                AttackPart::Damage { critical_hit },
+                name,
+                channel_id,
+                author_id,
+            ),
+            Command::ToHitRoll(roll, target, cover) => self.attack_roll(
+                &roll,
+                AttackCount::Times(1),
+                None,
+                target.as_deref(),
+                cover,
+                AttackPart::ToHit,
+                name,
+                channel_id,
+                author_id,
+            ),
+            _ => Response::Clarification(command::Error::CompanionNotCharacterRoll.to_string()),
+        }
+    }
+
+    fn add_companion(
+        &self,
+        kind: CompanionKind,
+        name: &str,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Companion::add(&connection, channel_id, author_id, name, kind)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(format!(
+                    "{} is now your {}. Switch to {} to set its ability scores and attacks, then switch back and roll as your {}.",
+                    name,
+                    kind.as_str().to_lowercase(),
+                    name,
+                    kind.as_str().to_lowercase(),
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_companions(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Companion::get_all(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(Response::Companions)
+            .unwrap_or_else(identity)
+    }
+
+    /// Retrieve the user's active character, or a warning if they don't have one.
+    fn get_active_character(
+        connection: &Connection,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Result<Character, Response> {
+        Character::get(connection, channel_id, author_id)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+            .ok_or_else(|| Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned()))
+    }
+
+    /// Find the stat block of a beast form, or a warning if there's no such beast.
+    fn find_wild_shape_form(form: &str) -> Result<&'static StatBlock, Response> {
+        WildShape::find_form(form).ok_or_else(|| {
+            Response::Warning(format!(
+                "There's no beast named {} in the monster stat blocks. Try \"Add brown bear to my wild shapes\", or `!wildshape add wolf`.",
+                form
+            ))
+        })
+    }
+
+    fn add_wild_shape_form(
+        &self,
+        form: &str,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let stat_block = Handler::find_wild_shape_form(form)?;
+                let character = Handler::get_active_character(&connection, channel_id, author_id)?;
+                WildShape::add_form(
+                    &connection,
+                    channel_id,
+                    author_id,
+                    character.name(),
+                    &stat_block.name,
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Confirmation(format!(
+                    "{} added to your wild shape forms. Try \"Wild shape into a {}\".",
+                    stat_block.name,
+                    stat_block.name.to_lowercase()
+                )))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Take on one of the character's beast forms, with the form's full hit points.
+    fn wild_shape(&self, form: &str, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let stat_block = Handler::find_wild_shape_form(form)?;
+                let character = Handler::get_active_character(&connection, channel_id, author_id)?;
+                let forms =
+                    WildShape::get_forms(&connection, channel_id, author_id, character.name())
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if !forms
+                    .iter()
+                    .any(|form| form.eq_ignore_ascii_case(&stat_block.name))
+                {
+                    return Err(Response::Warning(format!(
+                        "{} isn't one of your wild shape forms. Try `!wildshape add {}` first.",
+                        stat_block.name,
+                        stat_block.name.to_lowercase()
+                    )));
+                }
+                WildShape::new(stat_block)
+                    .save(&connection, channel_id, author_id, character.name())
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Confirmation(format!(
+                    "You wild shape into a {}, with {} hit points, AC {} and a speed of {}. Your checks and attacks use its Strength, Dexterity and Constitution until you revert.",
+                    stat_block.name.to_lowercase(),
+                    stat_block.hit_points,
+                    stat_block.armor_class,
+                    stat_block.speed
+                )))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn revert_wild_shape(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let character = Handler::get_active_character(&connection, channel_id, author_id)?;
+                WildShape::revert(&connection, channel_id, author_id, character.name())
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|reverted| {
+                if reverted > 0 {
+                    Response::Confirmation("You revert to your normal form.".to_owned())
+                } else {
+                    Response::Warning(WILD_SHAPE_NOT_FOUND_WARNING_TEXT.to_owned())
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Damage the character's wild shape, or heal it if the amount is negative. If the form drops
+    /// to 0 hit points, the character reverts to their normal form.
+    fn change_wild_shape_hit_points(
+        &self,
+        amount: i32,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let character = Handler::get_active_character(&connection, channel_id, author_id)?;
+                let mut wild_shape =
+                    WildShape::get(&connection, channel_id, author_id, character.name())
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                        .ok_or_else(|| {
+                            Response::Warning(WILD_SHAPE_NOT_FOUND_WARNING_TEXT.to_owned())
+                        })?;
+                let maximum = wild_shape
+                    .stat_block()
+                    .map_or(wild_shape.hit_points, |stat_block| stat_block.hit_points);
+                let form = wild_shape.form.to_lowercase();
+                match wild_shape.take_damage(amount, maximum) {
+                    Some(excess) => {
+                        WildShape::revert(&connection, channel_id, author_id, character.name())
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                        Ok(Response::Confirmation(if excess > 0 {
+                            format!(
+                                "Your {} drops to 0 hit points and you revert to your normal form, taking the remaining {} damage.",
+                                form, excess
+                            )
+                        } else {
+                            format!(
+                                "Your {} drops to 0 hit points and you revert to your normal form.",
+                                form
+                            )
+                        }))
+                    }
+                    None => {
+                        wild_shape
+                            .save(&connection, channel_id, author_id, character.name())
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                        Ok(Response::Confirmation(format!(
+                            "Your {} has {} of {} hit points.",
+                            form, wild_shape.hit_points, maximum
+                        )))
+                    }
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Attack with one of the natural weapons of the character's wild shape, such as a brown
+    /// bear's claws.
+    fn wild_shape_attack(
+        &self,
+        attack: Option<&str>,
+        condition: Option<Condition>,
+        target: Option<String>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let character = Handler::get_active_character(&connection, channel_id, author_id)?;
+                let wild_shape =
+                    WildShape::get(&connection, channel_id, author_id, character.name())
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                        .ok_or_else(|| {
+                            Response::Warning(WILD_SHAPE_NOT_FOUND_WARNING_TEXT.to_owned())
+                        })?;
+                let stat_block = Handler::find_wild_shape_form(&wild_shape.form)?;
+                let monster = stat_block
+                    .to_monster_attacking_with(attack)
+                    .ok_or_else(|| {
+                        Response::Warning(match attack {
+                            Some(attack) => format!(
+                                "A {} doesn't have an attack named {}. Try `!wildshape` to see its attacks.",
+                                wild_shape.form.to_lowercase(),
+                                attack
+                            ),
+                            None => format!(
+                                "A {} doesn't have any attacks.",
+                                wild_shape.form.to_lowercase()
+                            ),
+                        })
+                    })?;
+                let monster = Monster {
+                    name: character
+                        .display_name()
+                        .map_or_else(|| stat_block.name.clone(), |name| name.to_owned()),
+                    ..monster
+                };
+                Handler::roll_monster_attack(&mut connection, channel_id, monster, condition, target)
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_wild_shapes(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let character = Handler::get_active_character(&connection, channel_id, author_id)?;
+                let forms =
+                    WildShape::get_forms(&connection, channel_id, author_id, character.name())
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let wild_shape =
+                    WildShape::get(&connection, channel_id, author_id, character.name())
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::WildShapes { forms, wild_shape })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn character_roll(
+        &self,
+        character_roll: &CharacterRoll,
+        purpose: Option<Purpose>,
+        companion_name: Option<&str>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let edition = self.get_channel(channel_id).edition;
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let character =
+                    Handler::get_character(&connection, channel_id, author_id, companion_name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                        .ok_or_else(|| {
+                            Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned())
+                        })?;
+                let statuses = Status::get(&connection, channel_id, author_id, character.name())
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let check = character_roll.check;
+                let (roll, statuses) = character_roll
+                    .to_roll(&character)
+                    .ok_or_else(|| Response::Warning(ABILITY_NOT_SET_WARNING_TEXT.to_owned()))
+                    .map(|roll| {
+                        status::apply_to_roll(roll, &statuses, |status| {
+                            status.check_condition(check)
+                        })
+                    })?;
+                let mut rng = rand::thread_rng();
+                let result = roll.roll(&mut rng);
+                let effects = character_effect::apply(
+                    &connection,
+                    channel_id,
+                    author_id,
+                    character.name(),
+                    |targets| targets.applies_to(check),
+                    &mut rng,
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let heroic_inspiration = Handler::gain_heroic_inspiration(
+                    &connection,
+                    channel_id,
+                    author_id,
+                    character.name(),
+                    edition,
+                    result.critical() == Some(Critical::Failure),
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::CharacterRoll {
+                    check,
+                    roll,
+                    result,
+                    effects,
+                    heroic_inspiration,
+                    statuses,
+                    purpose,
+                    avatar_url: character.avatar_url().map(|s| s.to_owned()),
+                    character_name: character
+                        .display_name()
+                        .or(companion_name)
+                        .map(|s| s.to_owned()),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Find the combatant an attack is made against, along with their armour class.
+    fn attack_target(
+        connection: &Connection,
+        channel_id: ChannelId,
+        target: &str,
+    ) -> Result<(String, i32), Response> {
+        let name = Handler::combatant_name(connection, channel_id, target)?;
+        combatant::get_armor_class(connection, channel_id, &name)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+            .map(|armor_class| (name.clone(), armor_class))
+            .ok_or_else(|| {
+                Response::Warning(format!(
+                    "{} doesn't have an armour class. Try \"{} has AC 15\", or `!ac {} 15`.",
+                    name, name, name
+                ))
+            })
+    }
+
+    /// Deal the damage from the attacks that hit to their target, if the target's hit points are
+    /// being tracked, defeating them if they drop to 0 hit points. A Divine Smite deals its
+    /// radiant damage separately, so that the target's resistances apply to each type of damage.
+    fn damage_attack_target(
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        name: String,
+        armor_class: i32,
+        attacks: &[Attack],
+        deals_damage: bool,
+        damage_type: Option<DamageType>,
+    ) -> Result<AttackTarget, Response> {
+        let hit_points_before = combatant::get_hit_points(connection, channel_id, &name)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        let mut hit_points = hit_points_before;
+        let mut damage = Vec::new();
+        if hit_points.is_some() {
+            let instances = attacks
+                .iter()
+                .filter(|attack| attack.hit == Some(true))
+                .flat_map(|attack| {
+                    iter::once((
+                        attack.damage_result.result(),
+                        damage_type.map(|damage_type| damage_type.to_string()),
+                    ))
+                    .filter(|_| deals_damage)
+                    .chain(
+                        attack
+                            .smite
+                            .iter()
+                            .map(|smite| (smite.result.result(), Some("radiant".to_owned()))),
+                    )
+                });
+            for (amount, damage_type) in instances {
+                if hit_points.map_or(false, |hit_points| hit_points.current == 0) {
+                    break;
+                }
+                let (dealt, note) = Damage {
+                    target: name.clone(),
+                    amount,
+                    damage_type,
+                }
+                .resist(connection, channel_id)
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                hit_points = dealt
+                    .apply(connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                damage.push(match note {
+                    Some(note) => format!("{} ({})", dealt.description(), note),
+                    None => dealt.description(),
+                });
+            }
+            if hit_points.map_or(false, |hit_points| hit_points.current == 0) {
+                combatant::defeat(connection, channel_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+            }
+        }
+        Ok(AttackTarget {
+            name,
+            armor_class,
+            damage,
+            hit_points_before,
+            hit_points,
+        })
+    }
+
+    /// Check that a character can use Divine Smite with a spell slot of the given level, and find
+    /// the counter tracking their spell slots of that level, if they have one.
+    fn find_smite_spell_slots(
+        connection: &Connection,
+        channel_id: ChannelId,
+        author_id: UserId,
+        character: &Character,
+        smite: Smite,
+    ) -> Result<Option<Counter>, Response> {
+        if character.class_level(Class::Paladin) == 0 {
+            return Err(Response::Warning(SMITE_WARNING_TEXT.to_owned()));
+        }
+        Handler::find_spell_slots(connection, channel_id, author_id, character, smite.level)
+    }
+
+    /// Check that a character has spell slots of the given level, and find the counter tracking
+    /// their spell slots of that level, such as "2nd Level Spell Slots", if they have one.
+    fn find_spell_slots(
+        connection: &Connection,
+        channel_id: ChannelId,
+        author_id: UserId,
+        character: &Character,
+        spell_level: i32,
+    ) -> Result<Option<Counter>, Response> {
+        let level = ordinal(spell_level);
+        if !character.spell_slots().has_slot(spell_level) {
+            return Err(Response::Warning(format!(
+                "Your character doesn't have any {} level spell slots.",
+                level
+            )));
+        }
+        let spell_slots = Counter::find(
+            connection,
+            channel_id,
+            author_id,
+            character.name(),
+            &format!("{} Level Spell Slots", level),
+        )
+        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        match spell_slots {
+            Some(spell_slots) if spell_slots.value < 1 => Err(Response::Warning(format!(
+                "Your character doesn't have any {} level spell slots left. They're restored by resting, e.g. \"!rest long\".",
+                level
+            ))),
+            spell_slots => Ok(spell_slots),
+        }
+    }
+
+    /// Under the 2024 rules, a character who rolls a 1 on the d20 for an attack roll, ability
+    /// check or saving throw gains Heroic Inspiration, which is recorded as their inspiration.
+    /// Returns whether the character gained it.
+    fn gain_heroic_inspiration(
+        connection: &Connection,
+        channel_id: ChannelId,
+        author_id: UserId,
+        name: &str,
+        edition: Edition,
+        natural_one: bool,
+    ) -> RusqliteResult<bool> {
+        if edition == Edition::Rules2024 && natural_one {
+            Character::grant_inspiration(connection, channel_id, author_id, name).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Make a knowledge check about a subject, revealing the lore about it if the check uses the
+    /// right skill and meets the DC.
+    fn recall_lore(
+        &self,
+        character_roll: &CharacterRoll,
+        subject: &str,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let purpose = Purpose {
+            tag: None,
+            text: format!("about {}", subject),
+        };
+        let roll = self.character_roll(character_roll, Some(purpose), None, channel_id, author_id);
+        let total = match roll.roll_history_entry() {
+            Some(entry) => entry.result,
+            None => return roll,
+        };
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let lore = Lore::get(&connection, channel_id, subject)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .filter(|lore| match (character_roll.check, lore.check) {
+                        (Check::Skill(skill), Some(check)) => {
+                            skill == check.skill && total >= check.dc
+                        }
+                        _ => false,
+                    });
+                if let Some(lore) = &lore {
+                    Lore::reveal(&connection, channel_id, &lore.subject)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                }
+                Ok(lore.map(|lore| lore.text))
+            })
+            .map(|lore| Response::LoreCheck {
+                roll: Box::new(roll),
+                subject: subject.to_owned(),
+                lore,
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_dice_limits(
+        &self,
+        limits: Option<Limits>,
+        channel_id: ChannelId,
+        is_dm: bool,
+    ) -> Response {
+        if !is_dm {
+            return Response::Warning(DICE_LIMITS_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_limits(&connection, channel_id, limits)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                let limits = limits.unwrap_or(Limits::MAXIMUM);
+                Response::Confirmation(format!(
+                    "Up to {} dice with up to {} sides each can now be rolled at once in this channel.",
+                    limits.rolls, limits.sides
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_dice_limits(&self, channel_id: ChannelId) -> Response {
+        let limits = self.get_channel(channel_id).limits;
+        Response::Confirmation(format!(
+            "Up to {} dice with up to {} sides each can be rolled at once in this channel.",
+            limits.rolls, limits.sides
+        ))
+    }
+
+    fn set_edition(&self, edition: Edition, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(EDITION_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_edition(&connection, channel_id, edition)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| Response::Confirmation(format!("This channel now uses {}.", edition)))
+            .unwrap_or_else(identity)
+    }
+
+    /// Set the user or role running the game in the channel. Either an administrator or the
+    /// current DM can, so that the DM can hand over to someone else.
+    fn set_dungeon_master(
+        &self,
+        dungeon_master: Option<DungeonMaster>,
+        channel_id: ChannelId,
+        is_allowed: bool,
+    ) -> Response {
+        if !is_allowed {
+            return Response::Warning(DUNGEON_MASTER_ADMIN_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_dungeon_master(&connection, channel_id, dungeon_master)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(match dungeon_master {
+                    Some(DungeonMaster::User(user_id)) => {
+                        format!("<@{}> is now the DM of this channel.", user_id)
+                    }
+                    Some(DungeonMaster::Role(role_id)) => {
+                        format!("Everyone with <@&{}> is now a DM of this channel.", role_id)
+                    }
+                    None => "This channel no longer has a DM.".to_owned(),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_dungeon_master(&self, channel_id: ChannelId) -> Response {
+        let channel = self.get_channel(channel_id);
+        Response::Confirmation(match (channel.dm_user_id, channel.dm_role_id) {
+            (Some(user_id), Some(role_id)) => format!(
+                "<@{}> is the DM of this channel, along with everyone with <@&{}>.",
+                user_id, role_id
+            ),
+            (Some(user_id), None) => format!("<@{}> is the DM of this channel.", user_id),
+            (None, Some(role_id)) => {
+                format!("Everyone with <@&{}> is a DM of this channel.", role_id)
+            }
+            (None, None) => {
+                "This channel doesn't have a DM, so its administrators act as the DM.".to_owned()
+            }
+        })
+    }
+
+    fn restrict_commands(
+        &self,
+        category: CommandCategory,
+        role_id: Option<RoleId>,
+        channel_id: ChannelId,
+        is_admin: bool,
+    ) -> Response {
+        if !is_admin {
+            return Response::Warning(RESTRICT_ADMIN_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                CommandRole::set(&connection, channel_id, category, role_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(match role_id {
+                    Some(role_id) => format!(
+                        "Only members of <@&{}> can use {} commands in this channel now.",
+                        role_id, category
+                    ),
+                    None => format!(
+                        "Everyone can use {} commands in this channel now.",
+                        category
+                    ),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_command_restrictions(&self, channel_id: ChannelId) -> Response {
+        let command_roles = self.get_command_roles(channel_id);
+        Response::Confirmation(if command_roles.is_empty() {
+            "None of the commands in this channel are restricted to a role.".to_owned()
+        } else {
+            command_roles
+                .iter()
+                .map(|command_role| {
+                    format!(
+                        "Only members of <@&{}> can use {} commands in this channel.",
+                        command_role.role_id, command_role.category
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+    }
+
+    /// Enable or disable Dungeon Helper in the channel. Only server administrators can, which is
+    /// checked before the command is run.
+    fn set_channel_enabled(&self, enabled: bool, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_enabled(&connection, channel_id, enabled)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(if enabled {
+                    "Dungeon Helper is now enabled in this channel.".to_owned()
+                } else {
+                    "Dungeon Helper is now disabled in this channel. Server administrators can still use it here.".to_owned()
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_channel_locked(&self, locked: bool, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_locked(&connection, channel_id, locked)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(if locked {
+                    "The characters in this channel are now locked.".to_owned()
+                } else {
+                    "The characters in this channel are now unlocked.".to_owned()
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_channel_dice_only(&self, dice_only: bool, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_dice_only(&connection, channel_id, dice_only)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(if dice_only {
+                    "This channel is now dice only, so you don't need to @mention Dungeon Helper."
+                        .to_owned()
+                } else {
+                    "This channel is no longer dice only, so you need to @mention Dungeon Helper."
+                        .to_owned()
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_channel_prefix(&self, prefix: &str, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_prefix(&connection, channel_id, prefix)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(format!(
+                    "Shorthand commands in this channel now start with {0}, such as `{0}r d20`.",
+                    prefix
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_channel_duplicate_window(
+        &self,
+        duplicate_window: Duration,
+        channel_id: ChannelId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_duplicate_window(&connection, channel_id, duplicate_window)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(match duplicate_window.as_secs() {
+                    0 => "Identical commands in this channel are no longer ignored.".to_owned(),
+                    1 => "Identical commands sent within a second of each other in this channel are now ignored.".to_owned(),
+                    seconds => format!("Identical commands sent within {} seconds of each other in this channel are now ignored.", seconds),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_channel_keep_roll_messages(
+        &self,
+        keep_roll_messages: bool,
+        channel_id: ChannelId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_keep_roll_messages(&connection, channel_id, keep_roll_messages)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(if keep_roll_messages {
+                    "Roll commands in this channel are now kept, instead of being deleted once they've been answered.".to_owned()
+                } else {
+                    "Roll commands in this channel are now deleted once they've been answered."
+                        .to_owned()
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_channel_spelling_correction(
+        &self,
+        spelling_correction: bool,
+        channel_id: ChannelId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Channel::set_spelling_correction(&connection, channel_id, spelling_correction)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(if spelling_correction {
+                    "Commands in this channel are now spelling corrected before they're understood."
+                        .to_owned()
+                } else {
+                    "Commands in this channel are no longer spelling corrected.".to_owned()
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_channel_settings(&self, channel_id: ChannelId) -> Response {
+        Response::ChannelSettings(self.get_channel(channel_id))
+    }
+
+    /// Export the channel's settings, house rules and random tables, so that they can be imported
+    /// into another channel. Only server administrators can, which is checked before the command
+    /// is run.
+    fn export_channel_settings(&self, channel_id: ChannelId) -> Response {
+        let channel = self.get_channel(channel_id);
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                ChannelExport::get(&connection, channel_id, &channel)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|export| {
+                export
+                    .to_json()
+                    .map_err(|error| Response::Error(Error::SerdeJsonError(error)))
+            })
+            .map(Response::ChannelExport)
+            .unwrap_or_else(identity)
+    }
+
+    /// Import settings, house rules and random tables exported from another channel, replacing
+    /// the channel's own. Only server administrators can, which is checked before the command is
+    /// run.
+    fn import_channel_settings(
+        &self,
+        attachments: &[Attachment],
+        channel_id: ChannelId,
+    ) -> Response {
+        Handler::read_channel_export(attachments)
+            .and_then(|export| {
+                let mut connection = self
+                    .pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))?;
+                export
+                    .save(&mut connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Confirmation(format!(
+                    "Imported the channel's settings and house rules, with {} random table{}.",
+                    export.tables.len(),
+                    if export.tables.len() == 1 { "" } else { "s" }
+                )))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn read_channel_export(attachments: &[Attachment]) -> Result<ChannelExport, Response> {
+        Handler::read_json_attachment(
+            attachments,
+            CHANNEL_IMPORT_ATTACHMENT_MISSING_WARNING_TEXT,
+            CHANNEL_IMPORT_ATTACHMENT_TOO_LARGE_WARNING_TEXT,
+            CHANNEL_IMPORT_ATTACHMENT_INVALID_WARNING_TEXT,
+            |json| {
+                ChannelExport::from_json(json).map_err(|errors| {
+                    Response::Warning(format!(
+                        "Those channel settings have some problems, so they weren't imported:\n{}",
+                        Handler::bullet_list(&errors)
+                    ))
+                })
+            },
+        )
+    }
+
+    /// Download the JSON file attached to a message, such as an exported character sheet, and
+    /// read it. Warns with the given warnings if there's no attachment, if it's too large, or if
+    /// it couldn't be downloaded.
+    fn read_json_attachment<T>(
+        attachments: &[Attachment],
+        missing_warning: &str,
+        too_large_warning: &str,
+        invalid_warning: &str,
+        from_json: impl FnOnce(&str) -> Result<T, Response>,
+    ) -> Result<T, Response> {
+        let attachment = attachments
+            .first()
+            .ok_or_else(|| Response::Warning(missing_warning.to_owned()))?;
+        if attachment.size > ATTACHMENT_MAXIMUM_SIZE {
+            return Err(Response::Warning(too_large_warning.to_owned()));
+        }
+        let json = attachment.download().map_err(|error| {
+            error!(target: "dungeon-helper", "Error downloading attachment. Attachment ID: {}; Error: {:?}", attachment.id, error);
+            Response::Warning(invalid_warning.to_owned())
+        })?;
+        from_json(&String::from_utf8_lossy(&json))
+    }
+
+    /// Change one of the settings that channels in the server start with. Only server
+    /// administrators can, which is checked before the command is run.
+    fn set_server_default(&self, default: ChannelDefault, guild_id: Option<GuildId>) -> Response {
+        guild_id
+            .ok_or_else(|| Response::Warning(SERVER_DEFAULTS_GUILD_WARNING_TEXT.to_owned()))
+            .and_then(|guild_id| {
+                self.pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))
+                    .and_then(|connection| {
+                        Guild::set_default(&connection, guild_id, &default)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    })
+            })
+            .map(|_| {
+                Response::Confirmation(format!(
+                    "Channels in this server now start with {}. Channels that have already been used keep their settings.",
+                    default
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_server_defaults(&self, guild_id: Option<GuildId>) -> Response {
+        guild_id
+            .ok_or_else(|| Response::Warning(SERVER_DEFAULTS_GUILD_WARNING_TEXT.to_owned()))
+            .and_then(|guild_id| {
+                self.pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))
+                    .and_then(|connection| {
+                        Guild::get(&connection, guild_id)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    })
+            })
+            .map(|guild| Response::ServerDefaults(guild.unwrap_or_default()))
+            .unwrap_or_else(identity)
+    }
+
+    fn show_edition(&self, channel_id: ChannelId) -> Response {
+        Response::Confirmation(format!(
+            "This channel uses {}.",
+            self.get_channel(channel_id).edition
+        ))
+    }
+
+    /// Change one of the house rules used in the channel. The most dice that can be rolled at
+    /// once is a house rule too, but it's kept with the channel's dice limits.
+    fn set_house_rule(
+        &self,
+        house_rule: HouseRule,
+        channel_id: ChannelId,
+        is_dm: bool,
+    ) -> Response {
+        if !is_dm {
+            return Response::Warning(HOUSE_RULES_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                match house_rule {
+                    HouseRule::MaxDice(limits) => {
+                        Channel::set_limits(&connection, channel_id, limits)
+                    }
+                    _ => HouseRules::set(&connection, channel_id, house_rule),
+                }
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| Response::Confirmation(format!("House rule: {}", house_rule)))
+            .unwrap_or_else(identity)
+    }
+
+    /// Go back to the rules as written in the channel. The channel's dice limits and edition are
+    /// left as they are.
+    fn reset_house_rules(&self, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(HOUSE_RULES_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                HouseRules::reset(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation("This channel now follows the rules as written.".to_owned())
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_house_rules(&self, channel_id: ChannelId) -> Response {
+        let channel = self.get_channel(channel_id);
+        Response::HouseRules {
+            house_rules: self.get_house_rules(channel_id),
+            limits: channel.limits,
+            edition: channel.edition,
+        }
+    }
+
+    fn set_time_zone(
+        &self,
+        time_zone: Option<TimeZone>,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                TimeZone::set_user(&connection, author_id, time_zone)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(connection)
+            })
+            .map(|connection| match time_zone {
+                Some(time_zone) => Response::Confirmation(format!(
+                    "Your time zone is now {}, where it's {}.",
+                    time_zone,
+                    time_zone.format(time::now())
+                )),
+                // Show which time zone the user falls back to
+                None => Handler::describe_time_zone(&connection, guild_id, author_id),
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_server_time_zone(
+        &self,
+        time_zone: Option<TimeZone>,
+        guild_id: Option<GuildId>,
+        is_admin: bool,
+    ) -> Response {
+        if !is_admin {
+            return Response::Warning(TIME_ZONE_ADMIN_WARNING_TEXT.to_owned());
+        }
+        guild_id
+            .ok_or_else(|| Response::Warning(TIME_ZONE_GUILD_WARNING_TEXT.to_owned()))
+            .and_then(|guild_id| {
+                self.pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))
+                    .and_then(|connection| {
+                        TimeZone::set_guild(&connection, guild_id, time_zone)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    })
+            })
+            .map(|_| {
+                Response::Confirmation(format!(
+                    "Users in this server who haven't set their own time zone now use {}.",
+                    time_zone.unwrap_or(TimeZone::UTC)
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_time_zone(&self, guild_id: Option<GuildId>, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .map(|connection| Handler::describe_time_zone(&connection, guild_id, author_id))
+            .unwrap_or_else(identity)
+    }
+
+    fn describe_time_zone(
+        connection: &Connection,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+    ) -> Response {
+        TimeZone::get(connection, guild_id, author_id)
+            .map(|(time_zone, source)| {
+                let source = match source {
+                    TimeZoneSource::User => "",
+                    TimeZoneSource::Guild => " (the server's time zone)",
+                    TimeZoneSource::Default => " (the default)",
+                };
+                Response::Confirmation(format!(
+                    "Your time zone is {}{}, where it's {}.",
+                    time_zone,
+                    source,
+                    time_zone.format(time::now())
+                ))
+            })
+            .unwrap_or_else(|error| Response::Error(Error::RusqliteError(error)))
+    }
+
+    fn set_lore(&self, lore: Lore, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(LORE_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                lore.set(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(match lore.check {
+                    Some(check) => format!(
+                        "Added lore about {}, recalled with a DC {} {} check.",
+                        lore.subject,
+                        check.dc,
+                        check.skill.as_str()
+                    ),
+                    None => format!(
+                        "Added lore about {}, hidden until you reveal it with `!lore reveal {}`.",
+                        lore.subject, lore.subject
+                    ),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn remove_lore(&self, subject: &str, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(LORE_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Lore::remove(&connection, channel_id, subject)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|removed| {
+                if removed > 0 {
+                    Response::Confirmation(format!("Removed the lore about {}.", subject))
+                } else {
+                    Response::Warning(format!(
+                        "There's no lore about {} in this channel.",
+                        subject
+                    ))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Reveal the lore about a subject to the players, without anyone needing to recall it.
+    fn reveal_lore(&self, subject: &str, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(LORE_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Lore::reveal(&connection, channel_id, subject)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Lore::get(&connection, channel_id, subject)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|lore| {
+                lore.ok_or_else(|| {
+                    Response::Warning(format!(
+                        "There's no lore about {} in this channel.",
+                        subject
+                    ))
+                })
+            })
+            .map(|lore| Response::Lore {
+                lore: vec![lore],
+                is_dm: false,
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_lore(&self, channel_id: ChannelId, is_dm: bool) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Lore::get_all(&connection, channel_id, is_dm)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|lore| Response::Lore { lore, is_dm })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_random_table(&self, table: RandomTable, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(RANDOM_TABLE_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                table
+                    .set(&mut connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                Response::Confirmation(format!(
+                    "Added the {} table, rolled with a d{}. Roll on it with `!rtable {}`.",
+                    table.name,
+                    table.total_weight(),
+                    table.name
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn delete_random_table(&self, name: &str, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(RANDOM_TABLE_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                RandomTable::delete(&connection, channel_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|deleted| {
+                if deleted {
+                    Response::Confirmation(format!("Deleted the {} table.", name))
+                } else {
+                    Response::Warning(format!(
+                        "There's no random table named {} in this channel.",
+                        name
+                    ))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Roll on one of the channel's random tables, and on any of the tables that the entry that
+    /// comes up refers to.
+    fn roll_random_table(&self, name: &str, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                RandomTable::get_all(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|tables| {
+                random_table::find(&tables, name)
+                    .ok_or_else(|| {
+                        Response::Warning(format!(
+                            "There's no random table named {} in this channel. Try `!rtable` to see the random tables.",
+                            name
+                        ))
+                    })
+                    .and_then(|table| {
+                        table
+                            .roll(&tables, &mut rand::thread_rng())
+                            .map(Response::RandomTableRoll)
+                            .map_err(|error| Response::Warning(error.to_string()))
+                    })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_random_table(&self, name: &str, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(RANDOM_TABLE_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                RandomTable::get_all(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|tables| {
+                random_table::find(&tables, name)
+                    .cloned()
+                    .map(Response::RandomTable)
+                    .ok_or_else(|| {
+                        Response::Warning(format!(
+                            "There's no random table named {} in this channel.",
+                            name
+                        ))
+                    })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_random_tables(&self, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                RandomTable::get_all(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(Response::RandomTables)
+            .unwrap_or_else(identity)
+    }
+
+    fn add_note(&self, text: &str, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Note::add(&connection, channel_id, author_id, text)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|id| {
+                Response::Confirmation(format!("Added note #{}. See the notes with `!notes`.", id))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Delete a note. Users can delete their own notes, and the DM can delete anyone's.
+    fn delete_note(
+        &self,
+        id: i64,
+        channel_id: ChannelId,
+        author_id: UserId,
+        is_dm: bool,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Note::delete(&connection, channel_id, id, author_id, is_dm)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|deleted| {
+                if deleted {
+                    Response::Confirmation(format!("Deleted note #{}.", id))
+                } else {
+                    Response::Warning(format!(
+                        "There's no note #{} in this channel that you can delete. Only the DM can delete other players' notes.",
+                        id
+                    ))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_notes(&self, search: Option<String>, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Note::get_recent(&connection, channel_id, search.as_deref(), RECENT_NOTES)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|notes| Response::Notes(notes, search))
+            .unwrap_or_else(identity)
+    }
+
+    fn show_character_audit(
+        &self,
+        user_id: Option<UserId>,
+        channel_id: ChannelId,
+        is_admin: bool,
+    ) -> Response {
+        if !is_admin {
+            return Response::Warning(AUDIT_ADMIN_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                audit::get_recent(&connection, channel_id, user_id, RECENT_CHANGES)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(Response::CharacterAudit)
+            .unwrap_or_else(identity)
+    }
+
+    fn verify_roll(&self, code: &str, guild_id: Option<GuildId>, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let roll = SignedRoll::get(&connection, code)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let (time_zone, _) = TimeZone::get(&connection, guild_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok((roll, time_zone))
+            })
+            .and_then(|(roll, time_zone)| match roll {
+                Some(roll) if self.roll_signer.verify(&roll, code) => {
+                    Ok(Response::VerifiedRoll(roll, time_zone))
+                }
+                Some(_) => Err(Response::Warning(ROLL_NOT_GENUINE_WARNING_TEXT.to_owned())),
+                None => Err(Response::Warning(ROLL_NOT_FOUND_WARNING_TEXT.to_owned())),
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn add_status(
+        &self,
+        status: Status,
+        rounds: Option<i32>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                status
+                    .add(&connection, channel_id, author_id, &name, rounds)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|added| {
+                let status = status.as_str().to_lowercase();
+                Response::Confirmation(match rounds {
+                    Some(1) => format!("Your character is {} for 1 round.", status),
+                    Some(rounds) => format!("Your character is {} for {} rounds.", status, rounds),
+                    None if added => format!("Your character is now {}.", status),
+                    None => format!("Your character is already {}.", status),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn remove_status(&self, status: Status, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                status
+                    .remove(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|removed| {
+                let status = status.as_str().to_lowercase();
+                Response::Confirmation(if removed {
+                    format!("Your character is no longer {}.", status)
+                } else {
+                    format!("Your character wasn't {}.", status)
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_statuses(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let statuses = Status::get(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let character = Character::get_by_name(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Statuses {
+                    statuses,
+                    avatar_url: character
+                        .as_ref()
+                        .and_then(|c| c.avatar_url().map(|s| s.to_owned())),
+                    character_name: character
+                        .as_ref()
+                        .and_then(|c| c.display_name().map(|s| s.to_owned())),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Show the active character's passive score for a check, or all of their passive scores,
+    /// taking their conditions into account.
+    fn show_passive_scores(
+        &self,
+        check: Option<Check>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let character = Character::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .ok_or_else(|| {
+                        Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned())
+                    })?;
+                let statuses = Status::get(&connection, channel_id, author_id, character.name())
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let scores = match check {
+                    Some(check) => vec![PassiveScore::new(check, &character, &statuses)],
+                    None => PassiveScore::all(&character, &statuses),
+                };
+                if scores.iter().all(|score| score.score.is_none()) {
+                    return Err(Response::Warning(ABILITY_NOT_SET_WARNING_TEXT.to_owned()));
+                }
+                Ok(Response::PassiveScores {
+                    scores,
+                    avatar_url: character.avatar_url().map(|s| s.to_owned()),
+                    character_name: character.display_name().map(|s| s.to_owned()),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn add_bonus(&self, bonus: Bonus, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.change_attribute(
+            "Bonuses",
+            channel_id,
+            author_id,
+            Handler::describe_bonuses,
+            |connection, name| {
+                bonus
+                    .add(connection, channel_id, author_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| Response::Confirmation(format!("Your character now has {}.", bonus)))
+        .unwrap_or_else(identity)
+    }
+
+    fn remove_bonus(&self, source: &str, channel_id: ChannelId, author_id: UserId) -> Response {
+        let mut count = 0;
+        self.change_attribute(
+            "Bonuses",
+            channel_id,
+            author_id,
+            Handler::describe_bonuses,
+            |connection, name| {
+                count = Bonus::remove(connection, channel_id, author_id, name, source)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(())
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(if count > 0 {
+                format!("Removed your character's bonuses from {}.", source)
+            } else {
+                format!("Your character doesn't have any bonuses from {}.", source)
+            })
+        })
+        .unwrap_or_else(identity)
+    }
+
+    /// Describe a character's bonuses, such as "+1 to attack rolls from Magic Sword", or None if
+    /// they don't have any.
+    fn describe_bonuses(character: &Character) -> Option<String> {
+        let bonuses = character.bonuses();
+        if bonuses.is_empty() {
+            None
+        } else {
+            Some(
+                bonuses
+                    .iter()
+                    .map(|bonus| bonus.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            )
+        }
+    }
+
+    fn show_bonuses(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let bonuses = Bonus::get(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let character = Character::get_by_name(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Bonuses {
+                    bonuses,
+                    avatar_url: character
+                        .as_ref()
+                        .and_then(|c| c.avatar_url().map(|s| s.to_owned())),
+                    character_name: character
+                        .as_ref()
+                        .and_then(|c| c.display_name().map(|s| s.to_owned())),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn add_weapon_mastery(
+        &self,
+        weapon_name: WeaponName,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let edition = self.get_channel(channel_id).edition;
+        self.change_attribute(
+            "Weapon masteries",
+            channel_id,
+            author_id,
+            Handler::describe_weapon_masteries,
+            |connection, name| {
+                Character::add_weapon_mastery(connection, channel_id, author_id, name, weapon_name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            let mut confirmation = format!(
+                "Your character has now mastered the {}.",
+                Handler::describe_weapon_mastery(weapon_name)
+            );
+            if edition != Edition::Rules2024 {
+                confirmation.push_str(
+                    " Weapon masteries are only used in channels that use the 2024 rules.",
+                );
+            }
+            Response::Confirmation(confirmation)
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn remove_weapon_mastery(
+        &self,
+        weapon_name: WeaponName,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let mut count = 0;
+        self.change_attribute(
+            "Weapon masteries",
+            channel_id,
+            author_id,
+            Handler::describe_weapon_masteries,
+            |connection, name| {
+                count = Character::remove_weapon_mastery(
+                    connection,
+                    channel_id,
+                    author_id,
+                    name,
+                    weapon_name,
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(())
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(if count > 0 {
+                format!(
+                    "Your character has forgotten their mastery of the {}.",
+                    weapon_name
+                )
+            } else {
+                format!("Your character hasn't mastered the {}.", weapon_name)
+            })
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn show_weapon_masteries(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Character::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|character| {
+                Response::Confirmation(
+                    match character
+                        .as_ref()
+                        .and_then(Handler::describe_weapon_masteries)
+                    {
+                        Some(masteries) => {
+                            format!("Your character has mastered these weapons: {}.", masteries)
+                        }
+                        None => {
+                            "Your character hasn't mastered any weapons. Try `!mastery greatsword`."
+                                .to_owned()
+                        }
+                    },
+                )
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Describe the weapons a character has mastered, such as "Greatsword (Graze), Rapier (Vex)",
+    /// or None if they haven't mastered any.
+    fn describe_weapon_masteries(character: &Character) -> Option<String> {
+        let masteries = character.weapon_masteries();
+        if masteries.is_empty() {
+            None
+        } else {
+            Some(
+                masteries
+                    .iter()
+                    .map(|weapon_name| Handler::describe_weapon_mastery(*weapon_name))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            )
+        }
+    }
+
+    /// Describe a weapon along with its mastery property, such as "Greatsword (Graze)".
+    fn describe_weapon_mastery(weapon_name: WeaponName) -> String {
+        match weapon_name.to_weapon().mastery {
+            Some(mastery) => format!("{} ({})", weapon_name, mastery),
+            None => weapon_name.to_string(),
+        }
+    }
+
+    fn add_character_effect(
+        &self,
+        effect: CharacterEffect,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                effect
+                    .add(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| Response::Confirmation(format!("Your character now has {}.", effect)))
+            .unwrap_or_else(identity)
+    }
+
+    fn remove_character_effect(
+        &self,
+        effect: &str,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                CharacterEffect::remove(&connection, channel_id, author_id, &name, effect)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|count| {
+                Response::Confirmation(if count > 0 {
+                    format!("Your character's {} has ended.", effect)
+                } else {
+                    format!("Your character isn't under {}.", effect)
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn clear_character_effects(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                CharacterEffect::clear(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|count| {
+                Response::Confirmation(match count {
+                    0 => "Your character isn't under any effects.".to_owned(),
+                    1 => "Ended 1 effect on your character.".to_owned(),
+                    count => format!("Ended {} effects on your character.", count),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_character_effects(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let effects = CharacterEffect::get_all(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let character = Character::get_by_name(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::CharacterEffects {
+                    effects,
+                    avatar_url: character
+                        .as_ref()
+                        .and_then(|c| c.avatar_url().map(|s| s.to_owned())),
+                    character_name: character
+                        .as_ref()
+                        .and_then(|c| c.display_name().map(|s| s.to_owned())),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_counter(&self, counter: Counter, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                counter
+                    .set(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| Response::Confirmation(format!("Your character now has {}.", counter)))
+            .unwrap_or_else(identity)
+    }
+
+    fn remove_counter(&self, counter: &str, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Counter::remove(&connection, channel_id, author_id, &name, counter)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|count| {
+                Response::Confirmation(if count > 0 {
+                    format!("Removed your character's {} counter.", counter)
+                } else {
+                    format!("Your character doesn't have a counter named {}.", counter)
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn spend_counter(
+        &self,
+        counter: &str,
+        amount: i32,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.update_counter(counter, channel_id, author_id, |counter| {
+            if counter.spend(amount) {
+                Ok(format!(
+                    "Spent {} {}, leaving {} of {}.",
+                    amount, counter.name, counter.value, counter.maximum
+                ))
+            } else {
+                Err(Response::Warning(format!(
+                    "Your character only has {} {} left.",
+                    counter.value, counter.name
+                )))
+            }
+        })
+    }
+
+    fn restore_counter(
+        &self,
+        counter: &str,
+        amount: Option<i32>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.update_counter(counter, channel_id, author_id, |counter| {
+            counter.restore(amount);
+            Ok(format!(
+                "Restored {} to {} of {}.",
+                counter.name, counter.value, counter.maximum
+            ))
+        })
+    }
+
+    /// Change the value of one of the author's counters, saving it if the update succeeds and
+    /// confirming with the message it returns.
+    fn update_counter<F>(
+        &self,
+        counter: &str,
+        channel_id: ChannelId,
+        author_id: UserId,
+        update: F,
+    ) -> Response
+    where
+        F: FnOnce(&mut Counter) -> Result<String, Response>,
+    {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let mut found = Counter::find(&connection, channel_id, author_id, &name, counter)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .ok_or_else(|| {
+                        Response::Warning(format!(
+                            "Your character doesn't have a counter named {}. Try adding one first, e.g. `!counter {} 5 short rest`.",
+                            counter, counter
+                        ))
+                    })?;
+                let message = update(&mut found)?;
+                found
+                    .set(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    .map(|_| Response::Confirmation(message))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_counters(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let counters = Counter::get_all(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let character = Character::get_by_name(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Counters {
+                    counters,
+                    avatar_url: character
+                        .as_ref()
+                        .and_then(|c| c.avatar_url().map(|s| s.to_owned())),
+                    character_name: character
+                        .as_ref()
+                        .and_then(|c| c.display_name().map(|s| s.to_owned())),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Take a short or long rest, restoring the author's counters that reset on that kind of rest.
+    fn rest(&self, rest: Rest, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                counter::rest(&mut connection, channel_id, author_id, &name, rest)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|restored| {
+                Response::Confirmation(if restored.is_empty() {
+                    format!("Your character finishes a {}.", rest.as_str())
+                } else {
+                    format!(
+                        "Your character finishes a {}, restoring {}.",
+                        rest.as_str(),
+                        restored
+                            .iter()
+                            .map(|counter| format!("{} to {}", counter.name, counter.maximum))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn sneak_attack_roll(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Character::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|character| {
+                character
+                    .ok_or_else(|| Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned()))
+            })
+            .and_then(|character| {
+                character
+                    .sneak_attack()
+                    .ok_or_else(|| Response::Warning(SNEAK_ATTACK_WARNING_TEXT.to_owned()))
+                    .map(|roll| (roll, character))
+            })
+            .map(|(roll, character)| {
+                let mut rng = rand::thread_rng();
+                let result = roll.roll(&mut rng);
+                Response::SneakAttackRoll {
+                    roll,
+                    result,
+                    avatar_url: character.avatar_url().map(|s| s.to_owned()),
+                    character_name: character.display_name().map(|s| s.to_owned()),
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Cast a spell and roll its damage. A leveled spell expends a spell slot, and deals more damage
+    /// when it's cast with a higher level slot, while a cantrip's damage depends on the character's
+    /// level. A ritual spell can be cast as a ritual instead, which doesn't expend a spell slot.
+    fn cast_spell(
+        &self,
+        name: &str,
+        slot_level: Option<i32>,
+        ritual: bool,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let spell = match Spell::find(name) {
+            Some(spell) => spell,
+            None => {
+                return Response::Warning(format!(
+                    "I don't know the spell \"{}\". Try a spell such as \"Cast fireball\", or \"Ritual cast detect magic\".",
+                    name
+                ))
+            }
+        };
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Character::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    .and_then(|character| {
+                        character.ok_or_else(|| {
+                            Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned())
+                        })
+                    })
+                    .map(|character| (connection, character))
+            })
+            .and_then(|(connection, character)| {
+                if ritual {
+                    if !spell.ritual {
+                        return Err(Response::Warning(format!(
+                            "{} isn't a ritual, so it can't be cast without expending a spell slot. Try casting it normally, e.g. \"Cast {}\".",
+                            spell.name, spell.name
+                        )));
+                    }
+                    let damage = spell.damage_at(spell.level, character.level().unwrap_or(1));
+                    return Ok((character, None, damage));
+                }
+                if spell.is_cantrip() {
+                    if slot_level.is_some() {
+                        return Err(Response::Warning(format!(
+                            "{} is a cantrip, so it isn't cast using a spell slot. Its damage increases with your character's level instead.",
+                            spell.name
+                        )));
+                    }
+                    let character_level = character.level().ok_or_else(|| {
+                        Response::Warning(CANTRIP_LEVEL_WARNING_TEXT.to_owned())
+                    })?;
+                    let damage = spell.damage_at(0, character_level);
+                    return Ok((character, None, damage));
+                }
+                let level = slot_level.unwrap_or(spell.level);
+                if level < spell.level {
+                    return Err(Response::Warning(format!(
+                        "{} is a {} level spell, so it can't be cast using a {} level spell slot. Try casting it at {} level or higher.",
+                        spell.name,
+                        ordinal(spell.level),
+                        ordinal(level),
+                        ordinal(spell.level)
+                    )));
+                }
+                let spell_slots = Handler::find_spell_slots(
+                    &connection,
+                    channel_id,
+                    author_id,
+                    &character,
+                    level,
+                )?;
+                if let Some(mut spell_slots) = spell_slots {
+                    spell_slots.spend(1);
+                    spell_slots
+                        .set(&connection, channel_id, author_id, character.name())
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                }
+                let damage = spell.damage_at(level, character.level().unwrap_or(1));
+                Ok((character, Some(level), damage))
+            })
+            .map(|(character, level, damage)| match damage {
+                Some(roll) => {
+                    let mut rng = rand::thread_rng();
+                    let result = roll.roll(&mut rng);
+                    Response::SpellRoll {
+                        spell_name: spell.name.clone(),
+                        level,
+                        roll,
+                        result,
+                        damage_type: spell.damage_type,
+                        avatar_url: character.avatar_url().map(|s| s.to_owned()),
+                        character_name: character.display_name().map(|s| s.to_owned()),
+                    }
+                }
+                None => Response::Confirmation(match level {
+                    None if ritual => format!(
+                        "Your character casts {} as a ritual, without expending a spell slot. Casting it as a ritual takes 10 minutes longer than usual.",
+                        spell.name
+                    ),
+                    Some(level) => format!(
+                        "Your character casts {} using a {} level spell slot.",
+                        spell.name,
+                        ordinal(level)
+                    ),
+                    None => format!("Your character casts {}.", spell.name),
+                }),
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn create_character(&self, name: &str, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let created = Character::create(&connection, channel_id, author_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if created {
+                    Character::set_active_name(&connection, channel_id, author_id, name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                    Ok(Response::Confirmation(format!(
+                        "Created a new character named {}, and switched to them. Try setting some ability scores and a character level next.",
+                        name
+                    )))
+                } else {
+                    Err(Response::Warning(format!(
+                        "You already have a character named {} in this channel. Try \"Switch to {}\" instead.",
+                        name, name
+                    )))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn copy_character(
+        &self,
+        copy: CharacterCopy,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let edition = self.get_channel(channel_id).edition;
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let sources = Character::find_in_other_channels(
+                    &connection,
+                    channel_id,
+                    author_id,
+                    copy.channel_id,
+                    copy.name.as_deref(),
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let (source_channel_id, name) = Handler::choose_copy_source(&copy, sources)?;
+                let mut export =
+                    CharacterExport::get_by_name(&connection, source_channel_id, author_id, &name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                        .ok_or_else(|| {
+                            Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned())
+                        })?;
+                let existing = Character::find_name(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if existing.is_some() {
+                    Err(Response::Warning(format!(
+                        "You already have a character named {} in this channel. Try \"Switch to {}\" instead.",
+                        name, name
+                    )))
+                } else {
+                    if let Some(level) = copy.level {
+                        export.character.scale_to_level(level);
+                    }
+                    export
+                        .save(&mut connection, channel_id, author_id)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                    Ok(Response::Confirmation(Handler::copy_summary(
+                        &export.character,
+                        source_channel_id,
+                        edition,
+                    )))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Pick the character to copy from the user's matching characters in other channels, or
+    /// explain why there isn't exactly one.
+    fn choose_copy_source(
+        copy: &CharacterCopy,
+        mut sources: Vec<(ChannelId, String)>,
+    ) -> Result<(ChannelId, String), Response> {
+        let description = copy
+            .name
+            .as_ref()
+            .map_or("any characters".to_owned(), |name| {
+                format!("a character named {}", name)
+            });
+        match sources.len() {
+            0 => Err(Response::Warning(match copy.channel_id {
+                Some(source_channel_id) => {
+                    format!("Couldn't find {} in <#{}>.", description, source_channel_id)
+                }
+                None => format!("Couldn't find {} in any other channels.", description),
+            })),
+            1 => Ok(sources.remove(0)),
+            _ => {
+                let channels = sources
+                    .iter()
+                    .map(|(source_channel_id, name)| {
+                        format!("{} in <#{}>", name, source_channel_id)
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let (example_channel_id, example_name) = &sources[0];
+                Err(Response::Warning(format!(
+                    "You have more than one character you could copy: {}. Try \"!copy {} from <#{}>\".",
+                    channels, example_name, example_channel_id
+                )))
+            }
+        }
+    }
+
+    /// A confirmation for a copied character, listing the values that depend on their level.
+    fn copy_summary(
+        character: &Character,
+        source_channel_id: ChannelId,
+        edition: Edition,
+    ) -> String {
+        let mut details = Vec::new();
+        if let Some(level) = character.level() {
+            details.push(format!("• Level: {}", level));
+        }
+        if let Some(proficiency_bonus) = character.proficiency_bonus() {
+            details.push(format!("• Proficiency bonus: +{}", proficiency_bonus));
+        }
+        let hit_dice = character.hit_dice();
+        if !hit_dice.is_empty() {
+            let hit_dice = hit_dice
+                .iter()
+                .map(|roll| roll.to_string())
+                .collect::<Vec<String>>()
+                .join(" + ");
+            details.push(format!("• Hit dice: {}", hit_dice));
+        }
+        if let Some(die) = character.martial_arts_damage_die(edition) {
+            details.push(format!("• Martial Arts die: d{}", die));
+        }
+        let spell_slots = character.spell_slots();
+        if !spell_slots.is_empty() {
+            details.push(format!("• Spell slots: {}", spell_slots));
+        }
+        let mut summary = format!(
+            "Copied {} from <#{}>, and switched to them.",
+            character.name(),
+            source_channel_id
+        );
+        if !details.is_empty() {
+            summary.push('\n');
+            summary.push_str(&details.join("\n"));
+        }
+        summary
+    }
+
+    fn create_pregen(
+        &self,
+        class: Class,
+        level: i32,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let export = preset::create(class, level);
+        let name = export.character.name();
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let existing = Character::find_name(&connection, channel_id, author_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if existing.is_some() {
+                    Err(Response::Warning(format!(
+                        "You already have a character named {} in this channel. Try \"Switch to {}\" instead.",
+                        name, name
+                    )))
+                } else {
+                    export
+                        .save(&mut connection, channel_id, author_id)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                    Ok(Response::Confirmation(format!(
+                        "Created a level {} {} named {}, and switched to them. Try `!name` to give them a name of your own.",
+                        level, class, name
+                    )))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn export_character(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                CharacterExport::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|export| {
+                export.ok_or_else(|| Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned()))
+            })
+            .and_then(|export| {
+                export
+                    .to_json()
+                    .map_err(|error| Response::Error(Error::SerdeJsonError(error)))
+                    .map(|json| Response::CharacterExport {
+                        file_name: export.file_name(),
+                        json,
+                    })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Export the messages logged by the natural language parser as training data, so that the
+    /// model can be retrained on the messages it understood. Only the bot's owner can export the
+    /// training data, since it includes messages from every channel.
+    fn export_training_data(&self, author_id: UserId) -> Response {
+        if self.owner_id() != Some(author_id) {
+            return Response::Warning(TRAINING_DATA_OWNER_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                training_data::export(&connection)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|(count, json)| Response::TrainingData { count, json })
+            .unwrap_or_else(identity)
+    }
+
+    /// Reload the natural language parser's model from the model path, so that a retrained model
+    /// can be used without restarting the bot. The model is loaded before the current one is
+    /// replaced, so messages are still parsed while it loads, and the current model is kept if
+    /// the new one fails to load. Only the bot's owner can reload the model.
+    fn reload_model(&self, author_id: UserId) -> Response {
+        if self.owner_id() != Some(author_id) {
+            return Response::Warning(RELOAD_MODEL_OWNER_WARNING_TEXT.to_owned());
+        }
+        let model_path = match self.model_path.as_ref() {
+            Some(model_path) => model_path,
+            None => return Response::Warning(RELOAD_MODEL_MISSING_PATH_WARNING_TEXT.to_owned()),
+        };
+        match SnipsNluEngine::from_path(model_path) {
+            Ok(engine) => {
+                *self
+                    .engine
+                    .write()
+                    .expect("RwLock for engine has been poisoned") = Some(engine);
+                info!(target: "dungeon-helper", "Reloaded the intent parser's model. Model Path: {}", model_path);
+                Response::Confirmation("Reloaded the natural language parser's model.".to_owned())
+            }
+            Err(error) => {
+                error!(target: "dungeon-helper", "Error reloading the intent parser's model. Model Path: {}; Error: {}", model_path, error);
+                Response::Warning(format!(
+                    "Couldn't reload the natural language parser's model, so nothing has changed: {}",
+                    error
+                ))
+            }
+        }
+    }
+
+    /// Report which features will work in a channel with the bot's permissions there, so that
+    /// missing permissions can be spotted before they cause responses to silently go missing.
+    fn check_permissions(bot_permissions: Option<Permissions>) -> Response {
+        bot_permissions.map_or_else(
+            || Response::Warning(PERMISSIONS_UNKNOWN_WARNING_TEXT.to_owned()),
+            |permissions| Response::PermissionChecks(permission_check::check(permissions)),
+        )
+    }
+
+    fn import_character(
+        &self,
+        source: ImportSource,
+        attachments: &[Attachment],
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        let export = match source {
+            ImportSource::Attachment => Handler::read_character_export(attachments),
+            ImportSource::DndBeyond(character_id) => {
+                dndbeyond::fetch(character_id).map_err(|error| {
+                    error!(target: "dungeon-helper", "Error fetching D&D Beyond character. Character ID: {}; Error: {}", character_id, error);
+                    match error {
+                        dndbeyond::Error::RequestError(_) => Response::Warning(format!(
+                            "Couldn't fetch character {} from D&D Beyond. Check that the character's privacy is set to public.",
+                            character_id
+                        )),
+                        dndbeyond::Error::ParseError(_) => Response::Warning(format!(
+                            "Couldn't understand character {} from D&D Beyond.",
+                            character_id
+                        )),
+                    }
+                })
+            }
+        };
+        export
+            .and_then(|export| {
+                let errors = export.validate();
+                if errors.is_empty() {
+                    Ok(export)
+                } else {
+                    let errors = errors
+                        .iter()
+                        .map(|error| format!("• {}", error))
+                        .collect::<Vec<String>>()
+                        .join("\n");
+                    Err(Response::Warning(format!(
+                        "That character sheet has some invalid attributes, so it wasn't imported:\n{}",
+                        errors
+                    )))
+                }
+            })
+            .and_then(|export| {
+                let mut connection = self
+                    .pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))?;
+                export
+                    .save(&mut connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                audit::record(
+                    &connection,
+                    channel_id,
+                    author_id,
+                    export.character.name(),
+                    author_id,
+                    &Change::new("Character Sheet", None, Some("Imported".to_owned())),
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Confirmation(format!(
+                    "Imported {}, and switched to them.",
+                    export.character.name()
+                )))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn read_character_export(attachments: &[Attachment]) -> Result<CharacterExport, Response> {
+        Handler::read_json_attachment(
+            attachments,
+            IMPORT_ATTACHMENT_MISSING_WARNING_TEXT,
+            IMPORT_ATTACHMENT_TOO_LARGE_WARNING_TEXT,
+            IMPORT_ATTACHMENT_INVALID_WARNING_TEXT,
+            |json| {
+                CharacterExport::from_json(json).map_err(|error| {
+                    Response::Warning(format!(
+                        "Couldn't read the attached character sheet: {}.",
+                        error
+                    ))
+                })
+            },
+        )
+    }
+
+    /// Change one of the attributes of the author's active character, and record the change in
+    /// the audit history. The attribute is read from the character before and after the change,
+    /// and the changed character is returned.
+    fn change_attribute<V, F, T>(
+        &self,
+        attribute: &str,
+        channel_id: ChannelId,
+        author_id: UserId,
+        value: V,
+        change: F,
+    ) -> Result<Option<Character>, Response>
+    where
+        V: Fn(&Character) -> Option<String>,
+        F: FnOnce(&mut Connection, &str) -> Result<T, Response>,
+    {
+        let mut connection = self
+            .pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))?;
+        let name = Character::get_active_name(&connection, channel_id, author_id)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        let before = Character::get_by_name(&connection, channel_id, author_id, &name)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        change(&mut connection, &name)?;
+        let after = Character::get_by_name(&connection, channel_id, author_id, &name)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        let change = Change::new(
+            attribute,
+            before.as_ref().and_then(&value),
+            after.as_ref().and_then(&value),
+        );
+        audit::record(
+            &connection,
+            channel_id,
+            author_id,
+            &name,
+            author_id,
+            &change,
+        )
+        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        Ok(after)
+    }
+
+    fn set_character_name(
+        &self,
+        display_name: &str,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.change_attribute(
+            "Name",
+            channel_id,
+            author_id,
+            |character| character.display_name().map(|s| s.to_owned()),
+            |connection, name| {
+                Character::set_display_name(connection, channel_id, author_id, name, display_name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(format!(
+                "Your character will now be called {} in rolls.",
+                display_name
+            ))
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn set_class(
+        &self,
+        class: Class,
+        level: Option<i32>,
+        subclass: Option<&str>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.change_attribute(
+            "Class",
+            channel_id,
+            author_id,
+            |character| Some(Handler::describe_classes(character)).filter(|s| !s.is_empty()),
+            |connection, name| match level {
+                Some(level) => {
+                    let character = Character::get_by_name(connection, channel_id, author_id, name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                    let total_level = level
+                        + character.map_or(0, |character| {
+                            character
+                                .classes()
+                                .iter()
+                                .filter(|other| other.class != class)
+                                .map(|other| other.level)
+                                .sum()
+                        });
+                    if total_level > 20 {
+                        Err(Response::Warning(format!(
+                            "That would make your character level {}, but the highest level is 20.",
+                            total_level
+                        )))
+                    } else {
+                        Character::set_class_level(
+                            connection, channel_id, author_id, name, class, level, subclass,
+                        )
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    }
+                }
+                None => {
+                    Character::set_class(connection, channel_id, author_id, name, class, subclass)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                }
+            },
+        )
+        .map(|character| {
+            let classes = character.map_or_else(String::new, |character| {
+                Handler::describe_classes(&character)
+            });
+            Response::Confirmation(format!("Your character is now a {}.", classes))
+        })
+        .unwrap_or_else(identity)
+    }
+
+    /// Describe a character's classes, such as "Fighter 3 / Wizard 2".
+    fn describe_classes(character: &Character) -> String {
+        character
+            .classes()
+            .iter()
+            .map(|class| class.to_string())
+            .collect::<Vec<String>>()
+            .join(" / ")
+    }
+
+    fn show_spell_slots(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Character::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|character| {
+                character
+                    .ok_or_else(|| Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned()))
+            })
+            .and_then(|character| {
+                let slots = character.spell_slots();
+                if slots.is_empty() {
+                    Err(Response::Warning(SPELL_SLOTS_WARNING_TEXT.to_owned()))
+                } else {
+                    Ok(Response::SpellSlots {
+                        classes: character.classes().to_vec(),
+                        slots,
+                        avatar_url: character.avatar_url().map(|s| s.to_owned()),
+                        character_name: character.display_name().map(|s| s.to_owned()),
+                    })
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_character_sheet(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let character = Character::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .ok_or_else(|| {
+                        Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned())
+                    })?;
+                // Hit points are only tracked for combatants, under their name in the initiative
+                // order
+                let combatant_name = character.display_name().unwrap_or(character.name());
+                let hit_points = combatant::get_hit_points(&connection, channel_id, combatant_name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .map(|hit_points| hit_points.current);
+                let weapon_proficiencies = character
+                    .weapon_proficiencies(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::CharacterSheet {
+                    sheet: CharacterSheet::new(&character, hit_points, weapon_proficiencies),
+                    avatar_url: character.avatar_url().map(|s| s.to_owned()),
+                    character_name: character.display_name().map(|s| s.to_owned()),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn grant_inspiration(&self, user_id: UserId, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(INSPIRATION_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, user_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Character::grant_inspiration(&connection, channel_id, user_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|granted| {
+                Response::Confirmation(if granted {
+                    format!("<@{}>'s character now has inspiration.", user_id)
+                } else {
+                    format!("<@{}>'s character already has inspiration.", user_id)
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Run each of the commands in a message with several commands in turn. Running stops at the
+    /// first command that fails, so that the later commands don't act on an unexpected state.
+    fn run_composite(
+        &self,
+        commands: Vec<Command>,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+        attachments: &[Attachment],
+        is_admin: bool,
+        is_dm: bool,
+    ) -> Response {
+        let mut commands = commands.into_iter();
+        let mut responses = Vec::new();
+        for command in commands.by_ref() {
+            let response = self.run_command(
+                command,
+                channel_id,
+                guild_id,
+                author_id,
+                attachments,
+                is_admin,
+                is_dm,
+            );
+            let failed = response.is_failure();
+            responses.push(response);
+            if failed {
+                break;
+            }
+        }
+        Response::Composite {
+            responses,
+            skipped: commands
+                .map(|command| command.description().to_owned())
+                .collect(),
+        }
+    }
+
+    /// Perform a roll with the advantage from inspiration, spending the character's inspiration
+    /// only if the roll succeeds.
+    fn use_inspiration(
+        &self,
+        command: Command,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+        attachments: &[Attachment],
+        is_admin: bool,
+        is_dm: bool,
+    ) -> Response {
+        let response = self.run_command(
+            command,
+            channel_id,
+            guild_id,
+            author_id,
+            attachments,
+            is_admin,
+            is_dm,
+        );
+        if !response.is_roll() {
+            return response;
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let used = Character::use_inspiration(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                // A natural 1 on the roll gives back the inspiration that was just spent
+                if used && response.gains_heroic_inspiration() {
+                    Character::grant_inspiration(&connection, channel_id, author_id, &name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                }
+                Ok(used)
+            })
+            .and_then(|used| {
+                if used {
+                    Ok(Response::InspiredRoll(Box::new(response)))
+                } else {
+                    Err(Response::Warning(
+                        INSPIRATION_MISSING_WARNING_TEXT.to_owned(),
+                    ))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Perform a roll whose result is only sent to the channel's DM, such as a hidden Perception
+    /// check. The roll is made as usual, and the result is sent to the DM once the channel has
+    /// been told that it was rolled.
+    fn blind_roll(
+        &self,
+        command: Command,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+        attachments: &[Attachment],
+        is_admin: bool,
+        is_dm: bool,
+    ) -> Response {
+        let dm_user_id = match self.get_channel(channel_id).dm_user_id {
+            Some(dm_user_id) => dm_user_id,
+            None => return Response::Warning(DUNGEON_MASTER_MISSING_WARNING_TEXT.to_owned()),
+        };
+        let response = self.run_command(
+            command,
+            channel_id,
+            guild_id,
+            author_id,
+            attachments,
+            is_admin,
+            is_dm,
+        );
+        if !response.is_roll() {
+            return response;
+        }
+        Response::BlindRoll {
+            roll: Box::new(response),
+            dm_user_id,
+        }
+    }
+
+    /// Make an attack while flanking. It only has advantage if the channel's house rules say that
+    /// flanking gives advantage; otherwise it's an ordinary attack.
+    #[allow(clippy::too_many_arguments)]
+    fn flanking_attack(
+        &self,
+        command: Command,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+        attachments: &[Attachment],
+        is_admin: bool,
+        is_dm: bool,
+    ) -> Response {
+        let command = if self.get_house_rules(channel_id).flanking {
+            command.with_advantage()
+        } else {
+            command
+        };
+        self.run_command(
+            command,
+            channel_id,
+            guild_id,
+            author_id,
+            attachments,
+            is_admin,
+            is_dm,
+        )
+    }
+
+    /// Give another character a Bardic Inspiration die, sized by the bard's levels. If the bard
+    /// tracks their uses of Bardic Inspiration with a counter, one use is spent.
+    fn grant_bardic_inspiration(
+        &self,
+        recipient: Recipient,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let bard = Character::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .ok_or_else(|| {
+                        Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned())
+                    })?;
+                let die = bard.bardic_inspiration_die().ok_or_else(|| {
+                    Response::Warning(BARDIC_INSPIRATION_BARD_WARNING_TEXT.to_owned())
+                })?;
+                let (user_id, name) =
+                    Character::find_recipient(&connection, channel_id, &recipient)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                        .ok_or_else(|| match &recipient {
+                            Recipient::Character(name) => Response::Warning(format!(
+                                "There's no character named {} in this channel.",
+                                name
+                            )),
+                            Recipient::User(user_id) => Response::Warning(format!(
+                                "<@{}> doesn't have a character in this channel.",
+                                user_id
+                            )),
+                        })?;
+                if user_id == author_id && name == bard.name() {
+                    return Err(Response::Warning(
+                        BARDIC_INSPIRATION_SELF_WARNING_TEXT.to_owned(),
+                    ));
+                }
+                let transaction = connection
+                    .transaction()
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let mut uses = Counter::find(
+                    &transaction,
+                    channel_id,
+                    author_id,
+                    bard.name(),
+                    "Bardic Inspiration",
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if let Some(uses) = &mut uses {
+                    if !uses.spend(1) {
+                        return Err(Response::Warning(
+                            BARDIC_INSPIRATION_USES_WARNING_TEXT.to_owned(),
+                        ));
+                    }
+                    uses.set(&transaction, channel_id, author_id, bard.name())
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                }
+                let granted = Character::grant_bardic_inspiration(
+                    &transaction,
+                    channel_id,
+                    user_id,
+                    &name,
+                    die,
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if !granted {
+                    return Err(Response::Warning(format!(
+                        "<@{}>'s character already has a Bardic Inspiration die.",
+                        user_id
+                    )));
+                }
+                transaction
+                    .commit()
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok((user_id, die, uses))
+            })
+            .map(|(user_id, die, uses)| {
+                Response::Confirmation(format!(
+                    "<@{}>'s character now has a d{} Bardic Inspiration die.{}",
+                    user_id,
+                    die,
+                    uses.map_or_else(String::new, |uses| format!(
+                        " Your character has {} of {} uses of Bardic Inspiration left.",
+                        uses.value, uses.maximum
+                    ))
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Perform a roll with a Bardic Inspiration die added, spending the die only if the roll
+    /// succeeds.
+    fn use_bardic_inspiration(
+        &self,
+        command: Command,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+        attachments: &[Attachment],
+        is_admin: bool,
+        is_dm: bool,
+    ) -> Response {
+        let response = self.run_command(
+            command,
+            channel_id,
+            guild_id,
+            author_id,
+            attachments,
+            is_admin,
+            is_dm,
+        );
+        if !response.is_roll() {
+            return response;
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Character::use_bardic_inspiration(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|die| {
+                die.ok_or_else(|| {
+                    Response::Warning(BARDIC_INSPIRATION_MISSING_WARNING_TEXT.to_owned())
+                })
+            })
+            .map(|die| {
+                let die = Roll::new_unsafe(1, die, 0);
+                let result = die.roll(&mut rand::thread_rng());
+                Response::BardicInspiredRoll {
+                    roll: Box::new(response),
+                    die,
+                    result,
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn pin_quick_reference(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Character::get(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|character| {
+                character
+                    .ok_or_else(|| Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned()))
+            })
+            .map(|character| Response::QuickReference {
+                name: character.name().to_owned(),
+                reference: QuickReference::new(&character),
+                avatar_url: character.avatar_url().map(|s| s.to_owned()),
+                character_name: character.display_name().map(|s| s.to_owned()),
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_ability(
+        &self,
+        ability: AbilityName,
+        score: i32,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.change_attribute(
+            ability.as_str(),
+            channel_id,
+            author_id,
+            |character| character.ability(ability).map(|a| a.score.to_string()),
+            |connection, name| {
+                Character::set_ability(connection, channel_id, author_id, name, ability, score)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(format!(
+                "Your character's {} is now {}.",
+                ability.as_str(),
+                score
+            ))
+        })
+        .unwrap_or_else(identity)
+    }
+
+    /// Set the author's character level. A single class character's levels are all in their
+    /// class, so setting the level of a multiclass character is ambiguous.
+    fn set_level(&self, level: i32, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.change_attribute(
+            "Level",
+            channel_id,
+            author_id,
+            |character| character.level().map(|level| level.to_string()),
+            |connection, name| {
+                let character = Character::get_by_name(connection, channel_id, author_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                match character
+                    .as_ref()
+                    .map_or(&[][..], |character| character.classes())
+                {
+                    [] => Character::set_level(connection, channel_id, author_id, name, level)
+                        .map(|_| ())
+                        .map_err(|error| Response::Error(Error::RusqliteError(error))),
+                    [class] => Character::set_class_level(
+                        connection,
+                        channel_id,
+                        author_id,
+                        name,
+                        class.class,
+                        level,
+                        None,
+                    )
+                    .map_err(|error| Response::Error(Error::RusqliteError(error))),
+                    _ => Err(Response::Warning(
+                        SET_LEVEL_MULTICLASS_WARNING_TEXT.to_owned(),
+                    )),
+                }
+            },
+        )
+        .map(|_| Response::Confirmation(format!("Your character is now level {}.", level)))
+        .unwrap_or_else(identity)
+    }
+
+    fn set_armor_class(
+        &self,
+        armor_class: i32,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.change_attribute(
+            "Armour Class",
+            channel_id,
+            author_id,
+            |character| {
+                character
+                    .armor_class()
+                    .map(|armor_class| armor_class.to_string())
+            },
+            |connection, name| {
+                Character::set_armor_class(connection, channel_id, author_id, name, armor_class)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(format!(
+                "Your character's armour class is now {}.",
+                armor_class
+            ))
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn set_initiative_bonus(
+        &self,
+        initiative_bonus: i32,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.change_attribute(
+            "Initiative Bonus",
+            channel_id,
+            author_id,
+            |character| Some(format!("{:+}", character.initiative_bonus())),
+            |connection, name| {
+                Character::set_initiative_bonus(
+                    connection,
+                    channel_id,
+                    author_id,
+                    name,
+                    initiative_bonus,
+                )
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(format!(
+                "Your character's initiative bonus is now {:+}.",
+                initiative_bonus
+            ))
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn set_alert(&self, alert: bool, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.change_attribute(
+            "Alert",
+            channel_id,
+            author_id,
+            |character| Some(if character.alert() { "Yes" } else { "No" }.to_owned()),
+            |connection, name| {
+                Character::set_alert(connection, channel_id, author_id, name, alert)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(if alert {
+                "Your character now has the Alert feat, and gets +5 to initiative.".to_owned()
+            } else {
+                "Your character no longer has the Alert feat.".to_owned()
+            })
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn set_observant(&self, observant: bool, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.change_attribute(
+            "Observant",
+            channel_id,
+            author_id,
+            |character| Some(if character.observant() { "Yes" } else { "No" }.to_owned()),
+            |connection, name| {
+                Character::set_observant(connection, channel_id, author_id, name, observant)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(if observant {
+                "Your character now has the Observant feat, and gets +5 to passive Perception and Investigation.".to_owned()
+            } else {
+                "Your character no longer has the Observant feat.".to_owned()
+            })
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn set_fighting_style(
+        &self,
+        fighting_style: Option<FightingStyle>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.change_attribute(
+            "Fighting Style",
+            channel_id,
+            author_id,
+            |character| {
+                character
+                    .fighting_style()
+                    .map(|fighting_style| fighting_style.as_str().to_owned())
+            },
+            |connection, name| {
+                Character::set_fighting_style(connection, channel_id, author_id, name, fighting_style)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(match fighting_style {
+                Some(FightingStyle::Defense) => "Your character's fighting style is now Defense. Remember to include the +1 bonus in your AC while you're wearing armor.".to_owned(),
+                Some(fighting_style) => format!(
+                    "Your character's fighting style is now {}.",
+                    fighting_style.as_str()
+                ),
+                None => "Your character no longer has a fighting style.".to_owned(),
+            })
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn set_portrait(&self, url: &str, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.change_attribute(
+            "Portrait",
+            channel_id,
+            author_id,
+            |character| character.avatar_url().map(|s| s.to_owned()),
+            |connection, name| {
+                Character::set_avatar_url(connection, channel_id, author_id, name, url)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            },
+        )
+        .map(|_| {
+            Response::Confirmation(
+                "Your character's portrait will now be shown in rolls.".to_owned(),
+            )
+        })
+        .unwrap_or_else(identity)
+    }
+
+    fn switch_character(&self, name: &str, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Character::find_name(&connection, channel_id, author_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .ok_or_else(|| {
+                        Response::Warning(format!(
+                            "You don't have a character named {} in this channel. Try \"Create a character named {}\" first.",
+                            name, name
+                        ))
+                    })
+                    .and_then(|name| {
+                        Character::set_active_name(&connection, channel_id, author_id, &name)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                            .map(|_| {
+                                Response::Confirmation(format!("Switched to {}.", name))
+                            })
+                    })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn delete_character(&self, name: &str, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let name = Character::find_name(&connection, channel_id, author_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .ok_or_else(|| {
+                        Response::Warning(format!(
+                            "You don't have a character named {} in this channel.",
+                            name
+                        ))
+                    })?;
+                Character::delete(&mut connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    .map(|_| {
+                        Response::Confirmation(format!(
+                            "Deleted {} and everything stored about them in this channel.",
+                            name
+                        ))
+                    })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Delete everything stored about a user in the channel, including the messages logged for
+    /// the natural language parser. Anything stored about them in other channels is kept.
+    fn purge_user_from_channel(
+        &self,
+        user_id: UserId,
+        channel_id: ChannelId,
+        is_admin: bool,
+    ) -> Response {
+        if !is_admin {
+            return Response::Warning(PURGE_ADMIN_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                purge::purge_user_from_channel(&mut connection, channel_id, user_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|purge| {
+                Response::Confirmation(format!(
+                    "Deleted everything stored about <@{}> in this channel: {} character{}, {} logged roll{}, {} logged message{} and {} note{}.",
+                    user_id,
+                    purge.characters,
+                    if purge.characters == 1 { "" } else { "s" },
+                    purge.rolls,
+                    if purge.rolls == 1 { "" } else { "s" },
+                    purge.messages,
+                    if purge.messages == 1 { "" } else { "s" },
+                    purge.notes,
+                    if purge.notes == 1 { "" } else { "s" },
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn gain_currency(&self, amount: Amount, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.update_coins(Transaction::Gain(amount), channel_id, author_id, |coins| {
+            coins
+                .add(amount)
+                .map_err(|_| Response::Warning(TOO_MANY_COINS_WARNING_TEXT.to_owned()))
+        })
+    }
+
+    fn spend_currency(&self, amount: Amount, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.update_coins(Transaction::Spend(amount), channel_id, author_id, |coins| {
+            coins
+                .spend(amount)
+                .map_err(|_| Response::Warning(INSUFFICIENT_FUNDS_WARNING_TEXT.to_owned()))
+        })
+    }
+
+    fn update_coins<F>(
+        &self,
+        transaction: Transaction,
+        channel_id: ChannelId,
+        author_id: UserId,
+        update: F,
+    ) -> Response
+    where
+        F: FnOnce(Coins) -> Result<Coins, Response>,
+    {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Coins::get(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    .and_then(update)
+                    .and_then(|coins| {
+                        coins
+                            .set(&connection, channel_id, author_id, &name)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                        let avatar_url =
+                            Character::get_by_name(&connection, channel_id, author_id, &name)
+                                .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                                .and_then(|c| c.avatar_url().map(|s| s.to_owned()));
+                        Ok(Response::Coins {
+                            transaction: Some(transaction),
+                            coins,
+                            avatar_url,
+                        })
+                    })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_currency(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Character::get_active_name(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let coins = Coins::get(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let avatar_url = Character::get_by_name(&connection, channel_id, author_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .and_then(|c| c.avatar_url().map(|s| s.to_owned()));
+                Ok(Response::Coins {
+                    transaction: None,
+                    coins,
+                    avatar_url,
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_party_currency(&self, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(PARTY_CURRENCY_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Coins::get_all(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|coins| Response::PartyCoins { coins })
+            .unwrap_or_else(identity)
+    }
+
+    fn deposit_in_stash(&self, loot: Loot, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let deposited = Stash::deposit(&connection, channel_id, &loot)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if !deposited {
+                    return Err(Response::Warning(STASH_FULL_WARNING_TEXT.to_owned()));
+                }
+                Stash::get(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|stash| Response::Stash {
+                transaction: Some(stash::Transaction::Deposit(loot)),
+                stash,
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Take loot out of the party's stash. Coins go into the purse of the user's active
+    /// character, while items are just removed from the stash.
+    fn withdraw_from_stash(
+        &self,
+        loot: Loot,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                // The coins must fit in the purse before they're taken out of the stash
+                let purse = match &loot {
+                    Loot::Coins(amount) => {
+                        let name = Character::get_active_name(&connection, channel_id, author_id)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                        let coins = Coins::get(&connection, channel_id, author_id, &name)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                            .add(*amount)
+                            .map_err(|_| {
+                                Response::Warning(TOO_MANY_COINS_WARNING_TEXT.to_owned())
+                            })?;
+                        Some((name, coins))
+                    }
+                    Loot::Item(_, _) => None,
+                };
+                let withdrawn = Stash::withdraw(&connection, channel_id, &loot)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if !withdrawn {
+                    return Err(Response::Warning(
+                        STASH_INSUFFICIENT_WARNING_TEXT.to_owned(),
+                    ));
+                }
+                if let Some((name, coins)) = purse {
+                    coins
+                        .set(&connection, channel_id, author_id, &name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                }
+                Stash::get(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|stash| Response::Stash {
+                transaction: Some(stash::Transaction::Withdraw(loot)),
+                stash,
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_stash(&self, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Stash::get(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|stash| Response::Stash {
+                transaction: None,
+                stash,
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn award_experience(
+        &self,
+        amount: i32,
+        reason: Option<String>,
+        channel_id: ChannelId,
+        author_id: UserId,
+        is_dm: bool,
+    ) -> Response {
+        if !is_dm {
+            return Response::Warning(EXPERIENCE_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                experience::award(&mut connection, channel_id, author_id, amount)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|(share, awards)| {
+                if awards.is_empty() {
+                    Err(Response::Warning(NO_CHARACTERS_WARNING_TEXT.to_owned()))
+                } else {
+                    Ok(Response::ExperienceAward {
+                        amount,
+                        reason,
+                        share,
+                        awards,
+                    })
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_initiative_order(&self, order: InitiativeOrder, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let tie_break = self.get_channel(channel_id).tie_break;
+                let dexterity_scores =
+                    Character::get_all_active_dexterity_scores(&connection, channel_id)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let order = order.break_ties(
+                    tie_break,
+                    |name| dexterity_scores.get(&name.to_lowercase()).copied(),
+                    &mut rand::thread_rng(),
+                );
+                order
+                    .set(&mut connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(order)
+            })
+            .map(Response::InitiativeOrder)
+            .unwrap_or_else(identity)
+    }
+
+    /// Add a combatant to the initiative order partway through an encounter, such as a monster
+    /// that joins the fight, optionally tracking their hit points.
+    fn add_combatant(
+        &self,
+        entry: InitiativeEntry,
+        hit_points: Option<i32>,
+        hidden: bool,
+        channel_id: ChannelId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let added = InitiativeOrder::insert(&mut connection, channel_id, &entry)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if !added {
+                    return Err(Response::Warning(format!(
+                        "There's already someone named {} in the initiative order.",
+                        entry.name
+                    )));
+                }
+                if let Some(hit_points) = hit_points {
+                    combatant::set_hit_points(
+                        &connection,
+                        channel_id,
+                        &entry.name,
+                        hit_points,
+                        hidden,
+                    )
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                }
+                Ok(())
+            })
+            .map(|_| {
+                Response::Confirmation(match hit_points {
+                    Some(_) if hidden => format!(
+                        "Added {} to the initiative order at {}, with hidden hit points.",
+                        entry.name, entry.initiative
+                    ),
+                    Some(hit_points) => format!(
+                        "Added {} to the initiative order at {}, with {} hit points.",
+                        entry.name, entry.initiative, hit_points
+                    ),
+                    None => format!(
+                        "Added {} to the initiative order at {}.",
+                        entry.name, entry.initiative
+                    ),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Add a number of monsters of the same kind to the initiative order, such as "3 goblins",
+    /// rolling initiative for each of them. Their armour class, hit points and experience points
+    /// come from a monster saved for the channel, or else from a stat block in the SRD. When
+    /// there's more than one, they're told apart by letters, such as "Goblin A".
+    fn add_monsters(
+        &self,
+        count: i32,
+        name: &str,
+        hidden: bool,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let singulars = monster::singular_forms(name);
+                let (monster, dexterity) = Handler::get_monsters(&connection, channel_id, guild_id)?
+                    .into_iter()
+                    .find(|monster| singulars.contains(&monster.name.to_lowercase()))
+                    .map(|monster| (monster, 0))
+                    .or_else(|| {
+                        let stat_block = StatBlock::find(name)?;
+                        Some((stat_block.to_monster()?, stat_block.modifier(1)))
+                    })
+                    .ok_or_else(|| {
+                        Response::Warning(format!(
+                            "There's no monster named {} in the SRD or in this channel. Try saving one first, e.g. `!monster save {} AC 14 HP 52 attack +5 2d8+3`.",
+                            name, name
+                        ))
+                    })?;
+                let order = InitiativeOrder::get(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let taken = |candidate: &str| {
+                    order
+                        .entries()
+                        .iter()
+                        .any(|entry| entry.name.eq_ignore_ascii_case(candidate))
+                };
+                let names: Vec<String> = if count == 1 && !taken(&monster.name) {
+                    vec![monster.name.clone()]
+                } else {
+                    (b'A'..=b'Z')
+                        .map(|letter| format!("{} {}", monster.name, letter as char))
+                        .filter(|candidate| !taken(candidate))
+                        .take(count as usize)
+                        .collect()
+                };
+                if names.len() < count as usize {
+                    return Err(Response::Warning(format!(
+                        "There are too many monsters named {} in the initiative order to add {} more.",
+                        monster.name, count
+                    )));
+                }
+                let mut rng = rand::thread_rng();
+                let initiative_roll = Roll::new_clamped(1, 20, dexterity);
+                let mut added = Vec::new();
+                for name in names {
+                    let entry = InitiativeEntry {
+                        name,
+                        initiative: initiative_roll.roll(&mut rng).result(),
+                        tie: None,
+                    };
+                    InitiativeOrder::insert(&mut connection, channel_id, &entry)
+                        .and_then(|_| {
+                            combatant::set_hit_points(
+                                &connection,
+                                channel_id,
+                                &entry.name,
+                                monster.hit_points,
+                                hidden,
+                            )
+                        })
+                        .and_then(|_| {
+                            combatant::set_armor_class(
+                                &connection,
+                                channel_id,
+                                &entry.name,
+                                monster.armor_class,
+                            )
+                        })
+                        .and_then(|_| {
+                            encounter::set_experience(
+                                &connection,
+                                channel_id,
+                                &entry.name,
+                                monster.challenge_rating.experience(),
+                            )
+                        })
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                    added.push(format!("{} ({})", entry.name, entry.initiative));
+                }
+                Ok(Response::Confirmation(format!(
+                    "Added to the initiative order, with AC {} and {}, worth {} XP each:\n{}",
+                    monster.armor_class,
+                    if hidden {
+                        "hidden hit points".to_owned()
+                    } else {
+                        format!("{} hit points", monster.hit_points)
+                    },
+                    monster.challenge_rating.experience(),
+                    Handler::bullet_list(&added)
+                )))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_initiative_order(&self, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                InitiativeOrder::get(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|order| {
+                if order.is_empty() {
+                    Err(Response::Warning(
+                        INITIATIVE_ORDER_MISSING_WARNING_TEXT.to_owned(),
+                    ))
+                } else {
+                    Ok(Response::InitiativeOrder(order))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn clear_initiative_order(&self, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                InitiativeOrder::clear(&connection, channel_id)
+                    .and_then(|_| OngoingEffect::clear_all(&connection, channel_id))
+                    .and_then(|_| combatant::clear(&connection, channel_id))
+                    .and_then(|_| encounter::clear(&connection, channel_id))
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| Response::Confirmation("Cleared the initiative order.".to_owned()))
+            .unwrap_or_else(identity)
+    }
+
+    fn next_turn(&self, channel_id: ChannelId, whole_round: bool) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let order = InitiativeOrder::get(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if order.is_empty() {
+                    return Err(Response::Warning(
+                        INITIATIVE_ORDER_MISSING_WARNING_TEXT.to_owned(),
+                    ));
+                }
+                let turn = InitiativeOrder::get_turn(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .map_or_else(Turn::first, |turn| {
+                        if whole_round {
+                            turn.next_round()
+                        } else {
+                            turn.next(order.entries().len())
+                        }
+                    });
+                InitiativeOrder::set_turn(&connection, channel_id, turn)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let (ended_effects, ended_statuses) = if turn.position == 0 && turn.round > 1 {
+                    (
+                        character_effect::end_round(&mut connection, channel_id)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?,
+                        status::end_round(&mut connection, channel_id)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?,
+                    )
+                } else {
+                    (Vec::new(), Vec::new())
+                };
+                let name = order.entries()[turn.position].name.clone();
+                let hit_points_before =
+                    combatant::get_hit_points(&connection, channel_id, &name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let user_id = Character::get_all_active_names(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .into_iter()
+                    .find(|(_, character)| character.eq_ignore_ascii_case(&name))
+                    .map(|(user_id, _)| user_id);
+                let mut rng = rand::thread_rng();
+                let effects =
+                    OngoingEffect::start_turn(&mut connection, channel_id, &name, &mut rng)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let hit_points = combatant::get_hit_points(&connection, channel_id, &name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let defeated = hit_points.map_or(false, |hit_points| hit_points.current == 0);
+                let bloodied = hit_points.map_or(false, HitPoints::is_bloodied)
+                    && hit_points_before.map_or(false, |before| !before.is_bloodied());
+                if defeated {
+                    combatant::defeat(&mut connection, channel_id, &name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                }
+                Ok(Response::TurnStart {
+                    round: turn.round,
+                    name,
+                    user_id,
+                    effects,
+                    hit_points,
+                    bloodied,
+                    defeated,
+                    ended_effects,
+                    ended_statuses,
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn add_ongoing_effect(&self, mut effect: OngoingEffect, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                effect.target = Handler::combatant_name(&connection, channel_id, &effect.target)?;
+                effect
+                    .add(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| {
+                let rounds = match effect.rounds {
+                    Some(1) => " for 1 round".to_owned(),
+                    Some(rounds) => format!(" for {} rounds", rounds),
+                    None => String::new(),
+                };
+                Response::Confirmation(match effect.kind {
+                    EffectKind::Damage => format!(
+                        "{} will take {} at the start of each of their turns{}.",
+                        effect.target,
+                        effect.description(),
+                        rounds
+                    ),
+                    EffectKind::Healing if effect.suppressed_by.is_empty() => format!(
+                        "{} will regain {} at the start of each of their turns{}.",
+                        effect.target,
+                        effect.description(),
+                        rounds
+                    ),
+                    EffectKind::Healing => format!(
+                        "{} will regain {} at the start of each of their turns{}, unless they took {} damage since their last turn.",
+                        effect.target,
+                        effect.description(),
+                        rounds,
+                        effect.suppressed_by.join(" or ")
+                    ),
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn damage_combatant(&self, mut damage: Damage, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                damage.target = Handler::combatant_name(&connection, channel_id, &damage.target)?;
+                let (damage, note) = damage
+                    .resist(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let before = combatant::get_hit_points(&connection, channel_id, &damage.target)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let after = damage
+                    .apply(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if after.map_or(false, |after| after.current == 0) {
+                    combatant::defeat(&mut connection, channel_id, &damage.target)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                }
+                Ok((damage, note, before, after))
+            })
+            .map(|(damage, note, before, after)| {
+                let description = match note {
+                    Some(note) => format!("{} ({})", damage.description(), note),
+                    None => damage.description(),
+                };
+                Response::Confirmation(combatant::damage_message(
+                    &damage.target,
+                    &description,
+                    before,
+                    after,
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_hit_points(
+        &self,
+        name: &str,
+        hit_points: i32,
+        hidden: bool,
+        channel_id: ChannelId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Handler::combatant_name(&connection, channel_id, name)?;
+                combatant::set_hit_points(&connection, channel_id, &name, hit_points, hidden)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    .map(|_| name)
+            })
+            .map(|name| {
+                Response::Confirmation(if hidden {
+                    format!(
+                        "{}'s hit points are being tracked, and are hidden from the players.",
+                        name
+                    )
+                } else {
+                    format!("{} has {} hit points.", name, hit_points)
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn heal_combatant(&self, name: &str, amount: i32, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Handler::combatant_name(&connection, channel_id, name)?;
+                combatant::heal(&connection, channel_id, &name, amount)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .map(|hit_points| (name.clone(), hit_points))
+                    .ok_or_else(|| {
+                        Response::Warning(format!(
+                            "{}'s hit points aren't being tracked. Try `!hp {} 84` to start tracking them.",
+                            name, name
+                        ))
+                    })
+            })
+            .map(|(name, hit_points)| {
+                Response::Confirmation(format!(
+                    "{} regains {} hit points and {}.",
+                    name,
+                    amount,
+                    hit_points.description()
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn set_combatant_armor_class(
+        &self,
+        name: &str,
+        armor_class: i32,
+        channel_id: ChannelId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Handler::combatant_name(&connection, channel_id, name)?;
+                combatant::set_armor_class(&connection, channel_id, &name, armor_class)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    .map(|_| name)
+            })
+            .map(|name| Response::Confirmation(format!("{} has AC {}.", name, armor_class)))
+            .unwrap_or_else(identity)
+    }
+
+    fn set_combatant_experience(
+        &self,
+        name: &str,
+        experience: i32,
+        channel_id: ChannelId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let name = Handler::combatant_name(&connection, channel_id, name)?;
+                encounter::set_experience(&connection, channel_id, &name, experience)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+                    .map(|_| name)
+            })
+            .map(|name| {
+                Response::Confirmation(format!(
+                    "{} is worth {} XP. The party earns it at the end of the encounter if {} has been defeated.",
+                    name, experience, name
+                ))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// End the encounter and summarise it. When the DM ends the encounter, the experience points
+    /// for the defeated combatants are split between the party.
+    fn end_encounter(&self, channel_id: ChannelId, author_id: UserId, is_dm: bool) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let summary = encounter::end(&mut connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                let experience = summary.experience();
+                let (share, awards) = if is_dm && experience > 0 {
+                    experience::award(&mut connection, channel_id, author_id, experience)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                } else {
+                    (0, Vec::new())
+                };
+                Ok(Response::EncounterSummary {
+                    summary,
+                    share,
+                    awards,
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Work out how difficult an encounter with the monsters would be for the party. When the DM
+    /// asks, their own character isn't counted as part of the party.
+    fn encounter_difficulty(
+        &self,
+        monsters: &str,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        author_id: UserId,
+        is_dm: bool,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let homebrew = Handler::get_monsters(&connection, channel_id, guild_id)?;
+                let monsters = MonsterGroup::parse_list(monsters, &homebrew).map_err(|monster| {
+                    Response::Warning(format!(
+                        "I don't know the challenge rating of \"{}\". Try giving its challenge rating instead, such as `!difficulty 4 goblins, 1 CR 3`, or save it first with `!monster save`.",
+                        monster
+                    ))
+                })?;
+                if monsters.is_empty() {
+                    let verbosity = Channel::get(&connection, channel_id)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                        .map_or(Verbosity::Normal, |channel| channel.verbosity);
+                    return Err(command::Error::DifficultyParserError.into_response(verbosity));
+                }
+                let dm_id = if is_dm { Some(author_id) } else { None };
+                encounter::party_levels(&connection, channel_id, dm_id)
+                    .map(|levels| (monsters, levels))
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|(monsters, levels)| {
+                if levels.is_empty() {
+                    Err(Response::Warning(NO_CHARACTERS_WARNING_TEXT.to_owned()))
+                } else {
+                    Ok(Response::EncounterDifficulty {
+                        difficulty: Difficulty::new(&levels, &monsters),
+                        monsters,
+                        levels,
+                    })
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Save a monster's stat block for the channel, so that it can be used in encounters.
+    fn save_monster(&self, monster: Monster, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(MONSTER_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                monster
+                    .save(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|_| Response::Monster(monster))
+            .unwrap_or_else(identity)
+    }
+
+    fn delete_monster(&self, name: &str, channel_id: ChannelId, is_dm: bool) -> Response {
+        if !is_dm {
+            return Response::Warning(MONSTER_DM_WARNING_TEXT.to_owned());
+        }
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Monster::delete(&connection, channel_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(|deleted| {
+                if deleted {
+                    Response::Confirmation(format!("Deleted the monster {}.", name))
+                } else {
+                    Response::Warning(format!(
+                        "There's no monster named {} in this channel.",
+                        name
+                    ))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_monster(
+        &self,
+        name: &str,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let saved = Handler::get_monsters(&connection, channel_id, guild_id)?
+                    .into_iter()
+                    .find(|monster| monster.name.eq_ignore_ascii_case(name.trim()));
+                match (saved, StatBlock::find(name)) {
+                    (Some(monster), _) => Ok(Response::Monster(monster)),
+                    (None, Some(stat_block)) => Ok(Response::StatBlock(stat_block)),
+                    (None, None) => Handler::get_monster(&connection, channel_id, guild_id, name)
+                        .map(Response::Monster),
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_monsters(&self, channel_id: ChannelId, guild_id: Option<GuildId>) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| Handler::get_monsters(&connection, channel_id, guild_id))
+            .map(Response::Monsters)
+            .unwrap_or_else(identity)
+    }
+
+    /// Roll an attack for a saved monster, doubling the damage dice on a critical hit. An attack
+    /// against a target is checked against their armour class, and the damage of a hit is dealt to
+    /// them if their hit points are being tracked.
+    fn monster_attack(
+        &self,
+        name: &str,
+        condition: Option<Condition>,
+        target: Option<String>,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                let monster = Handler::get_monster(&connection, channel_id, guild_id, name)?;
+                Handler::roll_monster_attack(
+                    &mut connection,
+                    channel_id,
+                    monster,
+                    condition,
+                    target,
+                )
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Roll a monster's attack, against a target if one is given, dealing the damage to the target
+    /// if it hits.
+    fn roll_monster_attack(
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        monster: Monster,
+        condition: Option<Condition>,
+        target: Option<String>,
+    ) -> Result<Response, Response> {
+        let target = match target {
+            Some(target) => Some(Handler::monster_attack_target(
+                connection, channel_id, &target,
+            )?),
+            None => None,
+        };
+        let mut rng = rand::thread_rng();
+        let to_hit_roll = ConditionalRoll::new_unsafe(1, 20, monster.attack_bonus, condition);
+        let to_hit_result = to_hit_roll.roll(&mut rng);
+        let damage_roll = if to_hit_result.critical() == Some(Critical::Success) {
+            HouseRules::get(connection, channel_id)
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                .critical_hits
+                .damage_roll(monster.damage)
+        } else {
+            monster.damage
+        };
+        let damage_result = damage_roll.roll(&mut rng);
+        let hit = target
+            .as_ref()
+            .map(|(_, armor_class)| Attack::hits(&to_hit_result, 0, *armor_class));
+        let mut attacks = vec![Attack {
+            to_hit_result,
+            damage_roll,
+            damage_result,
+            effects: Vec::new(),
+            smite: None,
+            hit,
+        }];
+        let target = match target {
+            Some((name, armor_class)) => Some(Handler::damage_attack_target(
+                connection,
+                channel_id,
+                name,
+                armor_class,
+                &attacks,
+                true,
+                None,
+            )?),
+            None => None,
+        };
+        let attack = attacks.remove(0);
+        Ok(Response::AttackRoll {
+            attack_name: monster
+                .attack_name
+                .unwrap_or_else(|| "natural weapons".to_owned()),
+            attack_handedness: None,
+            to_hit_roll,
+            to_hit_result: attack.to_hit_result,
+            damage_roll: attack.damage_roll,
+            damage_result: attack.damage_result,
+            part: AttackPart::Both,
+            cover: None,
+            deals_damage: true,
+            damage_type: None,
+            effects: Vec::new(),
+            smite: None,
+            hit: attack.hit,
+            target,
+            mastery: None,
+            special: None,
+            heroic_inspiration: false,
+            statuses: Vec::new(),
+            avatar_url: None,
+            character_name: Some(monster.name),
+        })
+    }
+
+    /// Find the target of a monster's attack along with their armour class: a combatant in the
+    /// initiative order who has been given an armour class, or else an active character in the
+    /// channel, such as "Connie", using their own armour class.
+    fn monster_attack_target(
+        connection: &Connection,
+        channel_id: ChannelId,
+        target: &str,
+    ) -> Result<(String, i32), Response> {
+        Handler::attack_target(connection, channel_id, target).or_else(|response| {
+            let recipient = Recipient::Character(target.to_owned());
+            let character = match Character::find_recipient(connection, channel_id, &recipient)
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+            {
+                Some((user_id, name)) => {
+                    Character::get_by_name(connection, channel_id, user_id, &name)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                }
+                None => None,
+            };
+            let character = character.ok_or(response)?;
+            let armor_class = character.armor_class().ok_or_else(|| {
+                Response::Warning(format!(
+                    "{} doesn't have an armour class. Their player can set it with \"My AC is 15\", or `!ac 15`.",
+                    target
+                ))
+            })?;
+            // Keep the name from the initiative order, so that a combatant's hit points are found
+            let name = Handler::combatant_name(connection, channel_id, target)
+                .unwrap_or_else(|_| target.to_owned());
+            Ok((name, armor_class))
+        })
+    }
+
+    /// Find a monster saved for the channel by name, or one from a monster pack enabled for the
+    /// guild, or else a monster from the SRD that was added to the encounter, such as "Goblin A",
+    /// which attacks with the first attack in its stat block.
+    fn get_monster(
+        connection: &Connection,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        name: &str,
+    ) -> Result<Monster, Response> {
+        Handler::get_monsters(connection, channel_id, guild_id)?
+            .into_iter()
+            .find(|monster| monster.name.eq_ignore_ascii_case(name))
+            .or_else(|| {
+                StatBlock::find_combatant(name)
+                    .and_then(StatBlock::to_monster)
+                    .map(|monster| Monster {
+                        name: name.trim().to_owned(),
+                        ..monster
+                    })
+            })
+            .ok_or_else(|| {
+                Response::Warning(format!(
+                    "There's no monster named {} in this channel. Try saving one first, e.g. `!monster save {} AC 14 HP 52 attack +5 2d8+3`.",
+                    name, name
+                ))
+            })
+    }
+
+    /// Retrieve the monsters saved for the channel along with the monsters from the monster packs
+    /// enabled for the guild, in alphabetical order. A monster saved for the channel takes the
+    /// place of one with the same name from a monster pack.
+    fn get_monsters(
+        connection: &Connection,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+    ) -> Result<Vec<Monster>, Response> {
+        let mut monsters = Monster::get_all(connection, channel_id)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        if let Some(guild_id) = guild_id {
+            let packs = content_pack::get_monsters(connection, guild_id)
+                .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+            for monster in packs {
+                if !monsters
+                    .iter()
+                    .any(|saved| saved.name.eq_ignore_ascii_case(&monster.name))
+                {
+                    monsters.push(monster);
+                }
+            }
+        }
+        monsters.sort_by_key(|monster| monster.name.to_lowercase());
+        Ok(monsters)
+    }
+
+    /// Install a monster pack for the guild from an attached file, and enable it. The pack isn't
+    /// installed if any of its names are already in use.
+    fn install_content_pack(
+        &self,
+        attachments: &[Attachment],
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        is_admin: bool,
+    ) -> Response {
+        if !is_admin {
+            return Response::Warning(CONTENT_PACK_ADMIN_WARNING_TEXT.to_owned());
+        }
+        guild_id
+            .ok_or_else(|| Response::Warning(CONTENT_PACK_GUILD_WARNING_TEXT.to_owned()))
+            .and_then(|guild_id| {
+                let pack = Handler::read_content_pack(attachments)?;
+                let mut connection = self
+                    .pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))?;
+                Handler::check_content_pack_conflicts(&connection, &pack, guild_id, channel_id)?;
+                pack.save(&mut connection, guild_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Confirmation(format!(
+                    "Installed and enabled the monster pack {}, with {} monster{}.",
+                    pack.name,
+                    pack.monsters.len(),
+                    if pack.monsters.len() == 1 { "" } else { "s" }
+                )))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn read_content_pack(attachments: &[Attachment]) -> Result<ContentPack, Response> {
+        Handler::read_json_attachment(
+            attachments,
+            CONTENT_PACK_ATTACHMENT_MISSING_WARNING_TEXT,
+            CONTENT_PACK_ATTACHMENT_TOO_LARGE_WARNING_TEXT,
+            CONTENT_PACK_ATTACHMENT_INVALID_WARNING_TEXT,
+            |json| {
+                ContentPack::from_json(json).map_err(|errors| {
+                    Response::Warning(format!(
+                        "That monster pack has some problems, so it wasn't installed:\n{}",
+                        Handler::bullet_list(&errors)
+                    ))
+                })
+            },
+        )
+    }
+
+    /// Check that none of the names in a monster pack are already in use in the channel or by
+    /// another monster pack enabled for the guild.
+    fn check_content_pack_conflicts(
+        connection: &Connection,
+        pack: &ContentPack,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+    ) -> Result<(), Response> {
+        let conflicts = pack
+            .conflicts(connection, guild_id, channel_id)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(Response::Warning(format!(
+                "The monster pack {} uses names that are already taken, so it wasn't enabled:\n{}",
+                pack.name,
+                Handler::bullet_list(&conflicts)
+            )))
+        }
+    }
+
+    fn bullet_list(items: &[String]) -> String {
+        items
+            .iter()
+            .map(|item| format!("• {}", item))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn enable_content_pack(
+        &self,
+        name: &str,
+        channel_id: ChannelId,
+        guild_id: Option<GuildId>,
+        is_admin: bool,
+    ) -> Response {
+        if !is_admin {
+            return Response::Warning(CONTENT_PACK_ADMIN_WARNING_TEXT.to_owned());
+        }
+        guild_id
+            .ok_or_else(|| Response::Warning(CONTENT_PACK_GUILD_WARNING_TEXT.to_owned()))
+            .and_then(|guild_id| {
+                let connection = self
+                    .pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))?;
+                let pack = ContentPack::get(&connection, guild_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+                    .ok_or_else(|| Handler::content_pack_missing(name))?;
+                Handler::check_content_pack_conflicts(&connection, &pack, guild_id, channel_id)?;
+                ContentPack::set_enabled(&connection, guild_id, &pack.name, true)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::Confirmation(format!(
+                    "Enabled the monster pack {}.",
+                    pack.name
+                )))
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn disable_content_pack(
+        &self,
+        name: &str,
+        guild_id: Option<GuildId>,
+        is_admin: bool,
+    ) -> Response {
+        if !is_admin {
+            return Response::Warning(CONTENT_PACK_ADMIN_WARNING_TEXT.to_owned());
+        }
+        guild_id
+            .ok_or_else(|| Response::Warning(CONTENT_PACK_GUILD_WARNING_TEXT.to_owned()))
+            .and_then(|guild_id| {
+                let connection = self
+                    .pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))?;
+                ContentPack::set_enabled(&connection, guild_id, name, false)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|disabled| {
+                if disabled {
+                    Ok(Response::Confirmation(format!(
+                        "Disabled the monster pack {}.",
+                        name
+                    )))
+                } else {
+                    Err(Handler::content_pack_missing(name))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn remove_content_pack(
+        &self,
+        name: &str,
+        guild_id: Option<GuildId>,
+        is_admin: bool,
+    ) -> Response {
+        if !is_admin {
+            return Response::Warning(CONTENT_PACK_ADMIN_WARNING_TEXT.to_owned());
+        }
+        guild_id
+            .ok_or_else(|| Response::Warning(CONTENT_PACK_GUILD_WARNING_TEXT.to_owned()))
+            .and_then(|guild_id| {
+                let mut connection = self
+                    .pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))?;
+                ContentPack::delete(&mut connection, guild_id, name)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|deleted| {
+                if deleted {
+                    Ok(Response::Confirmation(format!(
+                        "Removed the monster pack {}.",
+                        name
+                    )))
+                } else {
+                    Err(Handler::content_pack_missing(name))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn show_content_packs(&self, guild_id: Option<GuildId>) -> Response {
+        guild_id
+            .ok_or_else(|| Response::Warning(CONTENT_PACK_GUILD_WARNING_TEXT.to_owned()))
+            .and_then(|guild_id| {
+                let connection = self
+                    .pool
+                    .get()
+                    .map_err(|error| Response::Error(Error::R2D2Error(error)))?;
+                ContentPack::get_all(&connection, guild_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .map(Response::ContentPacks)
+            .unwrap_or_else(identity)
+    }
+
+    fn content_pack_missing(name: &str) -> Response {
+        Response::Warning(format!(
+            "There's no monster pack named {} in this server. Try `!monsterpack` to see the installed packs.",
+            name
+        ))
+    }
+
+    /// Find a combatant in the initiative order, returning their name as it's written there.
+    fn combatant_name(
+        connection: &Connection,
+        channel_id: ChannelId,
+        name: &str,
+    ) -> Result<String, Response> {
+        let order = InitiativeOrder::get(connection, channel_id)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        if order.is_empty() {
+            return Err(Response::Warning(
+                INITIATIVE_ORDER_MISSING_WARNING_TEXT.to_owned(),
+            ));
+        }
+        order
+            .entries()
+            .iter()
+            .find(|entry| entry.name.to_lowercase() == name.to_lowercase())
+            .map(|entry| entry.name.clone())
+            .ok_or_else(|| {
+                Response::Warning(format!(
+                    "There's no one named {} in the initiative order.",
+                    name
+                ))
             })
-            .unwrap_or(())
     }
 
-    fn attack_roll(
-        &self,
-        attack_roll: &AttackRoll,
-        channel_id: ChannelId,
-        author_id: UserId,
-    ) -> Response {
+    fn set_resistances(&self, mut resistances: Resistances, channel_id: ChannelId) -> Response {
         self.pool
             .get()
             .map_err(|error| Response::Error(Error::R2D2Error(error)))
             .and_then(|connection| {
-                Character::get(&connection, channel_id, author_id)
+                resistances.target =
+                    Handler::combatant_name(&connection, channel_id, &resistances.target)?;
+                resistances
+                    .set(&connection, channel_id)
                     .map_err(|error| Response::Error(Error::RusqliteError(error)))
-                    .and_then(|character| {
-                        character.map_or(
-                            Err(Response::Warning(
-                                CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned(),
-                            )),
-                            |character| match attack_roll {
-                                AttackRoll::Weapon(attack_roll) => {
-                                    Character::has_weapon_proficiency(
-                                        &connection,
-                                        channel_id,
-                                        author_id,
-                                        attack_roll.weapon,
-                                        attack_roll.weapon.to_weapon().category,
-                                    )
-                                    .map(|proficiency| (character, proficiency))
-                                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
-                                }
-                                _ => Ok((character, false)),
-                            },
-                        )
-                    })
             })
-            .and_then(|(character, proficiency)| {
-                let strength = character.strength().map(|a| a.modifier);
-                let dexterity = character.dexterity().map(|a| a.modifier);
-                let proficiency_bonus = character.proficiency_bonus();
-                let mut rng = rand::thread_rng();
-                let to_hit_roll = attack_roll
-                    .to_attack_roll(
-                        strength,
-                        dexterity,
-                        proficiency_bonus,
-                        proficiency,
-                        character.martial_arts(),
-                    )
-                    .ok_or_else(|| Response::Warning(ABILITY_NOT_SET_WARNING_TEXT.to_owned()))?;
-                let to_hit_result = to_hit_roll.roll(&mut rng);
-                let critical_hit = to_hit_result.critical() == Some(Critical::Success);
-                let damage_roll = attack_roll
-                    .to_damage_roll(
-                        strength,
-                        dexterity,
-                        critical_hit,
-                        character.martial_arts_damage_die(),
-                    )
-                    .ok_or_else(|| Response::Warning(ABILITY_NOT_SET_WARNING_TEXT.to_owned()))?;
-                let damage_result = damage_roll.roll(&mut rng);
-                let avatar_url = character.avatar_url().map(|s| s.to_owned());
-                Ok((
-                    to_hit_roll,
-                    to_hit_result,
-                    damage_roll,
-                    damage_result,
-                    avatar_url,
+            .map(|_| {
+                Response::Confirmation(format!(
+                    "{} has {}.",
+                    resistances.target,
+                    resistances.description()
                 ))
             })
-            .map(
-                |(to_hit_roll, to_hit_result, damage_roll, damage_result, avatar_url)| {
-                    Response::AttackRoll {
-                        attack_name: attack_roll.get_name(),
-                        attack_handedness: attack_roll.get_handedness(),
-                        to_hit_roll,
-                        to_hit_result,
-                        damage_roll,
-                        damage_result,
-                        avatar_url,
-                    }
-                },
-            )
             .unwrap_or_else(identity)
     }
 
-    fn character_roll(
-        &self,
-        character_roll: &CharacterRoll,
-        channel_id: ChannelId,
-        author_id: UserId,
-    ) -> Response {
+    fn clear_resistances(&self, target: &str, channel_id: ChannelId) -> Response {
         self.pool
             .get()
             .map_err(|error| Response::Error(Error::R2D2Error(error)))
             .and_then(|connection| {
-                Character::get(&connection, channel_id, author_id)
+                combatant::clear_resistances(&connection, channel_id, target)
                     .map_err(|error| Response::Error(Error::RusqliteError(error)))
             })
-            .and_then(|character| {
-                character
-                    .ok_or_else(|| Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned()))
+            .map(|count| {
+                Response::Confirmation(if count > 0 {
+                    format!("{} no longer has any resistances or immunities.", target)
+                } else {
+                    format!("{} doesn't have any resistances or immunities.", target)
+                })
             })
-            .and_then(|character| {
-                let avatar_url = character.avatar_url().map(|s| s.to_owned());
-                character_roll
-                    .to_roll(&character)
-                    .ok_or_else(|| Response::Warning(ABILITY_NOT_SET_WARNING_TEXT.to_owned()))
-                    .map(|roll| (roll, avatar_url))
+            .unwrap_or_else(identity)
+    }
+
+    fn clear_ongoing_effects(&self, target: &str, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                OngoingEffect::clear(&connection, channel_id, target)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
             })
-            .map(|(roll, avatar_url)| {
-                let mut rng = rand::thread_rng();
-                let result = roll.roll(&mut rng);
-                Response::CharacterRoll {
-                    check: character_roll.check,
-                    roll,
-                    result,
-                    avatar_url,
-                }
+            .map(|count| {
+                Response::Confirmation(if count > 0 {
+                    format!("{} no longer has any ongoing effects.", target)
+                } else {
+                    format!("{} doesn't have any ongoing effects.", target)
+                })
             })
             .unwrap_or_else(identity)
     }
@@ -264,6 +6151,7 @@ impl Handler {
              • \"Perform a wisdom saving throw\"\n\
              • \"Try a stealth roll with disadvantage\"\n\
              • \"Roll for initiative\"\n\
+             • \"Roll a d20 for everyone\"\n\
              There are also short-hand commands you can use. Type \"!help\" for more info."
                 .to_owned(),
         )
@@ -278,12 +6166,104 @@ impl Handler {
              • \"!r wisdom saving throw\"\n\
              • \"!r stealth with disadvantage\"\n\
              • \"!r initiative\"\n\
+             • \"!r sneak attack\"\n\
+             • \"!r perception to search the desk\"\n\
+             • \"!r stealth using inspiration\"\n\
+             • \"!r history about the Shattered Crown\"\n\
+             • \"!class Monk Way of Shadow\"\n\
+             • \"!class Rogue 3\"\n\
+             • \"!slots\"\n\
+             • \"!cast fireball 4\"\n\
+             • \"!cast ritual detect magic\"\n\
+             • \"!groupsave dex 15\"\n\
+             • \"!grapple Tocha\"\n\
+             • \"!treasure hoard 5-10\"\n\
+             • \"!rtable Wild Magic\"\n\
+             • \"!dungeon 4\"\n\
+             • \"!weather winter\"\n\
+             • \"!travel fast 50 miles\"\n\
+             • \"!tavern\"\n\
+             • \"!settlement town\"\n\
+             • \"!note the duke is a vampire\"\n\
+             • \"!stash add 150 gp\"\n\
+             • \"!companion familiar Hoot\"\n\
+             • \"!r perception as my familiar\"\n\
+             • \"!wildshape brown bear\"\n\
+             • \"!sheet\"\n\
+             • \"!ac 16\"\n\
+             • \"!initbonus +2\"\n\
+             • \"!alert\"\n\
+             • \"!passive perception\"\n\
+             • \"!style archery\"\n\
+             • \"!mastery greatsword\"\n\
+             • \"!pin\"\n\
+             • \"!condition add poisoned\"\n\
+             • \"!bonus +2 to stealth from Cloak of Elvenkind\"\n\
+             • \"!counter Ki 5 short rest\"\n\
+             • \"!counter spend Ki 2\"\n\
+             • \"!effect bless\"\n\
+             • \"!effect end bless\"\n\
+             • \"!rest long\"\n\
+             • \"!pregen cleric 3\"\n\
+             • \"!name Connie the Monk\"\n\
+             • \"!copy Connie at level 10\"\n\
+             • \"!delete Sidekick\"\n\
+             • \"!xp 300\"\n\
+             • \"!audit @Alice\"\n\
+             • \"!verify 3FA92C07D1\"\n\
+             • \"!r 1d20 hidden\"\n\
+             • \"!dm @Alice\"\n\
+             • \"!r perception blind\"\n\
+             • \"!reveal\"\n\
+             • \"!inspire @Alice\"\n\
+             • \"!bardic Connie\"\n\
+             • \"!r athletics with bardic inspiration\"\n\
+             • \"!portrait https://example.com/connie.png\"\n\
+             • \"!export\"\n\
+             • \"!import https://www.dndbeyond.com/characters/12345678\"\n\
+             • \"!table d20\"\n\
+             • \"!init Alice 18, goblin A 15, Bob 12\"\n\
+             • \"!init next\"\n\
+             • \"!init round\"\n\
+             • \"!init end\"\n\
+             • \"!ongoing goblin A 1d6 poison for 3 rounds\"\n\
+             • \"!regen troll 10 unless fire, acid\"\n\
+             • \"!hp troll 84\"\n\
+             • \"!heal troll 10\"\n\
+             • \"!init add troll 12 hp 84 hidden\"\n\
+             • \"!ac troll 15\"\n\
+             • \"!damage troll 12 fire\"\n\
+             • \"!resist troll fire, cold\"\n\
+             • \"!xp troll 1800\"\n\
+             • \"!difficulty 4 goblins, 1 ogre\"\n\
+             • \"!improvise 3 brute\"\n\
+             • \"!monster save Bog Hag AC 14 HP 52 attack +5 2d8+3 claws\"\n\
+             • \"!monster attack Bog Hag against Connie\"\n\
+             • \"!monster add 3 goblins\"\n\
+             • \"!monsterpack install\" (with a monster pack attached)\n\
+             • \"!lore add The Shattered Crown DC 15 History: It was broken in battle\"\n\
+             • \"!lore reveal Mayor Thorne\"\n\
+             • \"!limits 20d100\"\n\
+             • \"!edition 2024\"\n\
+             • \"!houserules set crits max\"\n\
+             • \"!channel status\"\n\
+             • \"!server enable\"\n\
+             • \"!restrict editing @Players\"\n\
+             • \"!timezone UTC+10\"\n\
+             • \"!permissions\"\n\
+             • \"!ac 16; !r stealth\"\n\
              There are also natural language commands you can use. Type \"help\" for more info."
                 .to_owned(),
         )
     }
 
-    fn roll(&self, roll: ConditionalRoll, channel_id: ChannelId, author_id: UserId) -> Response {
+    fn roll(
+        &self,
+        roll: ConditionalRoll,
+        purpose: Option<Purpose>,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
         self.pool
             .get()
             .map_err(|error| Response::Error(Error::R2D2Error(error)))
@@ -298,6 +6278,7 @@ impl Handler {
                         Ok(Response::DiceRoll {
                             roll,
                             result,
+                            purpose,
                             avatar_url,
                         })
                     })
@@ -305,6 +6286,323 @@ impl Handler {
             .unwrap_or_else(identity)
     }
 
+    fn roll_for_table(&self, roll: ConditionalRoll, channel_id: ChannelId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                Character::get_all_active_names(&connection, channel_id)
+                    .and_then(|characters| {
+                        Character::get_all_active_dexterity_scores(&connection, channel_id)
+                            .map(|dexterity_scores| (characters, dexterity_scores))
+                    })
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|(characters, dexterity_scores)| {
+                if characters.is_empty() {
+                    Err(Response::Warning(NO_CHARACTERS_WARNING_TEXT.to_owned()))
+                } else {
+                    Ok((characters, dexterity_scores))
+                }
+            })
+            .map(|(characters, dexterity_scores)| {
+                let mut rng = rand::thread_rng();
+                let results: Vec<_> = characters
+                    .into_iter()
+                    .map(|(user_id, name)| (user_id, name, roll.roll(&mut rng)))
+                    .collect();
+                let results = tie_break::rank(
+                    self.get_channel(channel_id).tie_break,
+                    results,
+                    |(_, _, result)| result.result(),
+                    |(_, name, _)| dexterity_scores.get(&name.to_lowercase()).copied(),
+                    &mut rng,
+                )
+                .into_iter()
+                .map(|((user_id, name, result), tie)| (user_id, name, result, tie))
+                .collect();
+                Response::TableRoll { roll, results }
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Roll a saving throw for every character in the channel against a DC, such as for an area
+    /// spell, including the statuses and effects that apply to each character's saving throw.
+    fn group_saving_throw(
+        &self,
+        ability: AbilityName,
+        dc: i32,
+        channel_id: ChannelId,
+        is_dm: bool,
+    ) -> Response {
+        if !is_dm {
+            return Response::Warning(GROUP_SAVING_THROW_DM_WARNING_TEXT.to_owned());
+        }
+        let check = Check::SavingThrow(ability);
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let characters = Character::get_all_active_names(&connection, channel_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                if characters.is_empty() {
+                    return Err(Response::Warning(NO_CHARACTERS_WARNING_TEXT.to_owned()));
+                }
+                let mut rng = rand::thread_rng();
+                let mut saves = Vec::with_capacity(characters.len());
+                for (user_id, name) in characters {
+                    let character = Character::get(&connection, channel_id, user_id)
+                        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                    let roll = match &character {
+                        Some(character) => {
+                            let statuses =
+                                Status::get(&connection, channel_id, user_id, character.name())
+                                    .map_err(|error| {
+                                        Response::Error(Error::RusqliteError(error))
+                                    })?;
+                            CharacterRoll {
+                                check,
+                                condition: None,
+                            }
+                            .to_roll(character)
+                            .map(|roll| {
+                                status::apply_to_roll(roll, &statuses, |status| {
+                                    status.check_condition(check)
+                                })
+                                .0
+                            })
+                        }
+                        None => None,
+                    };
+                    let (roll, effects) = match (roll, character) {
+                        (Some(roll), Some(character)) => {
+                            let result = roll.roll(&mut rng);
+                            let effects = character_effect::apply(
+                                &connection,
+                                channel_id,
+                                user_id,
+                                character.name(),
+                                |targets| targets.applies_to(check),
+                                &mut rng,
+                            )
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                            (Some((roll, result)), effects)
+                        }
+                        _ => (None, Vec::new()),
+                    };
+                    saves.push(GroupSave {
+                        user_id,
+                        name,
+                        roll,
+                        effects,
+                    });
+                }
+                Ok(Response::GroupSavingThrow { ability, dc, saves })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Roll a contest between two characters, such as one grappling the other, where the character
+    /// starting it is the user's own if it isn't given. The character resisting it uses whichever
+    /// of their Athletics or Acrobatics is better. A character who is grappled is given the
+    /// Grappled condition.
+    fn contested_check(
+        &self,
+        contest: Contest,
+        attacker: Option<Recipient>,
+        defender: Recipient,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let attacker = attacker.unwrap_or(Recipient::User(author_id));
+                let (attacker_id, attacker) =
+                    Handler::find_contestant(&connection, channel_id, &attacker)?;
+                let (defender_id, defender) =
+                    Handler::find_contestant(&connection, channel_id, &defender)?;
+                if attacker_id == defender_id && attacker.name() == defender.name() {
+                    return Err(Response::Warning(format!(
+                        "A character can't {} themselves.",
+                        contest.as_str()
+                    )));
+                }
+                let defender_check = contest.defender_check(&defender).ok_or_else(|| {
+                    Response::Warning(format!(
+                        "{} doesn't have a Strength or Dexterity score, so they'll need to roll Athletics or Acrobatics themselves.",
+                        defender.name()
+                    ))
+                })?;
+                let mut rng = rand::thread_rng();
+                let attacker_roll = Handler::contest_roll(
+                    &connection,
+                    channel_id,
+                    attacker_id,
+                    &attacker,
+                    contest.attacker_check(),
+                    &mut rng,
+                )?;
+                let defender_roll = Handler::contest_roll(
+                    &connection,
+                    channel_id,
+                    defender_id,
+                    &defender,
+                    defender_check,
+                    &mut rng,
+                )?;
+                if let Some(status) = contest.status() {
+                    if attacker_roll.beats(&defender_roll) {
+                        status
+                            .add(&connection, channel_id, defender_id, defender.name(), None)
+                            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                    }
+                }
+                Ok(Response::ContestedCheck {
+                    contest,
+                    attacker: attacker_roll,
+                    defender: defender_roll,
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    /// Find a character taking part in a contest, by their user or their name.
+    fn find_contestant(
+        connection: &Connection,
+        channel_id: ChannelId,
+        recipient: &Recipient,
+    ) -> Result<(UserId, Character), Response> {
+        let (user_id, name) = Character::find_recipient(connection, channel_id, recipient)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+            .ok_or_else(|| match recipient {
+                Recipient::Character(name) => Response::Warning(format!(
+                    "There's no character named {} in this channel.",
+                    name
+                )),
+                Recipient::User(user_id) => Response::Warning(format!(
+                    "<@{}> doesn't have a character in this channel.",
+                    user_id
+                )),
+            })?;
+        Character::get_by_name(connection, channel_id, user_id, &name)
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?
+            .map(|character| (user_id, character))
+            .ok_or_else(|| Response::Warning(CHARACTER_NOT_FOUND_WARNING_TEXT.to_owned()))
+    }
+
+    /// Roll a character's check in a contest, with the advantage or disadvantage from their
+    /// statuses and the effects on them that add to the check.
+    fn contest_roll<R: Rng + ?Sized>(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        character: &Character,
+        check: Check,
+        rng: &mut R,
+    ) -> Result<ContestRoll, Response> {
+        let statuses = Status::get(connection, channel_id, user_id, character.name())
+            .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        let roll = CharacterRoll {
+            check,
+            condition: None,
+        }
+        .to_roll(character)
+        .map(|roll| {
+            status::apply_to_roll(roll, &statuses, |status| status.check_condition(check)).0
+        })
+        .ok_or_else(|| {
+            Response::Warning(format!(
+                "{} doesn't have the ability score for {}.",
+                character.name(),
+                check
+            ))
+        })?;
+        let result = roll.roll(rng);
+        let effects = character_effect::apply(
+            connection,
+            channel_id,
+            user_id,
+            character.name(),
+            |targets| targets.applies_to(check),
+            rng,
+        )
+        .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+        Ok(ContestRoll {
+            user_id,
+            name: character.name().to_owned(),
+            check,
+            roll,
+            result,
+            effects,
+        })
+    }
+
+    fn roll_treasure(treasure: Treasure) -> Response {
+        Response::Treasure(treasure.roll(&mut rand::thread_rng()))
+    }
+
+    fn roll_weather(season: Option<Season>) -> Response {
+        Response::Weather(Weather::roll(season, &mut rand::thread_rng()))
+    }
+
+    fn generate_dungeon(rooms: usize) -> Response {
+        Response::Dungeon(Dungeon::generate(rooms, &mut rand::thread_rng()))
+    }
+
+    fn generate_settlement(size: Option<SettlementSize>) -> Response {
+        Response::Settlement(Settlement::generate(size, &mut rand::thread_rng()))
+    }
+
+    fn generate_tavern() -> Response {
+        Response::Tavern(Tavern::generate(&mut rand::thread_rng()))
+    }
+
+    fn roll_hidden(
+        &self,
+        roll: ConditionalRoll,
+        channel_id: ChannelId,
+        author_id: UserId,
+    ) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|connection| {
+                let result = roll.roll(&mut rand::thread_rng());
+                let hidden_roll =
+                    HiddenRoll::new(&self.roll_signer, channel_id, author_id, &roll, &result);
+                hidden_roll
+                    .save(&connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))?;
+                Ok(Response::HiddenRoll {
+                    roll,
+                    code: hidden_roll.code,
+                })
+            })
+            .unwrap_or_else(identity)
+    }
+
+    fn reveal_hidden_rolls(&self, channel_id: ChannelId, author_id: UserId) -> Response {
+        self.pool
+            .get()
+            .map_err(|error| Response::Error(Error::R2D2Error(error)))
+            .and_then(|mut connection| {
+                HiddenRoll::reveal_all(&mut connection, channel_id, author_id)
+                    .map_err(|error| Response::Error(Error::RusqliteError(error)))
+            })
+            .and_then(|rolls| {
+                if rolls.is_empty() {
+                    Err(Response::Warning(
+                        HIDDEN_ROLLS_MISSING_WARNING_TEXT.to_owned(),
+                    ))
+                } else {
+                    Ok(Response::RevealedRolls(rolls))
+                }
+            })
+            .unwrap_or_else(identity)
+    }
+
     fn get_channel(&self, channel_id: ChannelId) -> Channel {
         self.pool
             .get()
@@ -320,9 +6618,60 @@ impl Handler {
                     enabled: false,
                     locked: false,
                     dice_only: false,
+                    duplicate_window: DEFAULT_DUPLICATE_WINDOW,
+                    verbosity: Verbosity::Normal,
+                    tie_break: TieBreak::Simultaneous,
+                    limits: Limits::MAXIMUM,
+                    edition: Edition::Rules2014,
+                    dm_user_id: None,
+                    dm_role_id: None,
+                    prefix: DEFAULT_PREFIX.to_owned(),
+                    keep_roll_messages: false,
+                    spelling_correction: true,
                 }
             )
     }
+
+    /// The house rules used in a channel. If they can't be retrieved, the rules are used as
+    /// written.
+    fn get_house_rules(&self, channel_id: ChannelId) -> HouseRules {
+        self.pool
+            .get()
+            .ok()
+            .and_then(|connection|
+                HouseRules::get(&connection, channel_id)
+                    .map_err(|error| error!(target: "dungeon-helper", "Error retrieving house rules: Channel ID: {}; Error: {}", channel_id.to_string(), error))
+                    .ok()
+            )
+            .unwrap_or(HouseRules::RULES_AS_WRITTEN)
+    }
+
+    fn get_command_roles(&self, channel_id: ChannelId) -> Vec<CommandRole> {
+        self.pool
+            .get()
+            .ok()
+            .and_then(|connection|
+                CommandRole::get(&connection, channel_id)
+                    .map_err(|error| error!(target: "dungeon-helper", "Error retrieving command roles: Channel ID: {}; Error: {}", channel_id.to_string(), error))
+                    .ok()
+            )
+            .unwrap_or_else(Vec::new)
+    }
+
+    /// The settings of the channel a message was sent in. A channel in a server that hasn't been
+    /// used before starts with the server's settings.
+    fn get_message_channel(&self, channel_id: ChannelId, guild_id: Option<GuildId>) -> Channel {
+        if let (Some(guild_id), Ok(connection)) = (guild_id, self.pool.get()) {
+            let result = Channel::get(&connection, channel_id).and_then(|channel| match channel {
+                Some(_) => Ok(0),
+                None => Channel::inherit(&connection, channel_id, guild_id),
+            });
+            if let Err(error) = result {
+                error!(target: "dungeon-helper", "Error applying server settings to channel: Channel ID: {}; Guild ID: {}; Error: {}", channel_id.to_string(), guild_id.to_string(), error);
+            }
+        }
+        self.get_channel(channel_id)
+    }
 }
 
 impl EventHandler for Handler {
@@ -332,20 +6681,28 @@ impl EventHandler for Handler {
             // Don't respond to our own messages, this may cause an infinite loop
             Action::IgnoreOwnMessage
         } else {
-            let channel = self.get_channel(message.channel_id);
-            let is_admin = message.member(&ctx.cache).map_or(true, |member| {
+            let channel = self.get_message_channel(message.channel_id, message.guild_id);
+            let member = message.member(&ctx.cache);
+            let is_admin = member.as_ref().map_or(true, |member| {
                 member
                     .permissions(&ctx.cache)
                     .ok()
                     .map_or(false, |permissions| permissions.administrator())
             });
+            let role_ids = member
+                .as_ref()
+                .map_or(&[][..], |member| member.roles.as_slice());
+            let is_dm = channel.is_dm(message.author.id, role_ids, is_admin);
             let is_private = message.is_private();
+            let bot_role_ids = self.get_bot_role_ids(&ctx, &message);
+            let bot_permissions = self.get_bot_permissions(&ctx, &message);
             let command_result = self.get_command(
-                &self.engine,
-                &self.symspell,
+                Some(&self.symspell).filter(|_| channel.spelling_correction),
                 &message,
+                &bot_role_ids,
                 // Private channels are implicitly dice only, no need to @me
                 channel.dice_only || is_private,
+                &channel.prefix,
             );
             if let Some(command_result) = command_result.as_ref() {
                 match command_result {
@@ -361,12 +6718,27 @@ impl EventHandler for Handler {
                     Ok(CommandResult::Shorthand(Ok(command))) => {
                         info!(target: "dungeon-helper", "Parsed shorthand command successfully. Message ID: {}; Command: {:?}", message.id, command)
                     }
+                    Ok(CommandResult::Composite(results)) => {
+                        info!(target: "dungeon-helper", "Parsed composite command successfully. Message ID: {}; Commands: {}", message.id, results.len())
+                    }
+                    Ok(CommandResult::Confirmed(command, message_id)) => {
+                        info!(target: "dungeon-helper", "Confirmed command guessed for an earlier message. Message ID: {}; Guessed Message ID: {}; Command: {:?}", message.id, message_id, command)
+                    }
                     Err(error) => {
                         info!(target: "dungeon-helper", "Error parsing command. Message ID: {}; Error: {}", message.id, error)
                     }
                 }
             };
-            self.get_action(command_result, &channel, &message, is_admin, is_private)
+            self.get_action(
+                command_result,
+                &channel,
+                &message,
+                role_ids,
+                is_admin,
+                is_dm,
+                is_private,
+                bot_permissions,
+            )
         };
         match action {
             Action::IgnoreChannelDisabled => {
@@ -375,9 +6747,25 @@ impl EventHandler for Handler {
             Action::IgnoreCommandMissing => {
                 info!(target: "dungeon-helper", "Ignoring message because it contains no command. Message ID: {}", message.id);
             }
+            Action::IgnoreDuplicateCommand => {
+                info!(target: "dungeon-helper", "Ignoring command because it duplicates a recent command. Message ID: {}", message.id);
+            }
             Action::IgnoreOwnMessage => {
                 info!(target: "dungeon-helper", "Ignoring message because it was sent by us. Message ID: {}", message.id);
             }
+            Action::NotifyChannelDisabled => {
+                let result = message.author.direct_message(&ctx, |builder| {
+                    builder.content(CHANNEL_DISABLED_NOTICE_TEXT)
+                });
+                match result {
+                    Ok(sent_message) => {
+                        info!(target: "dungeon-helper", "Notified user that Dungeon Helper is disabled in current channel. Message ID: {}; Sent Message ID: {}", message.id, sent_message.id)
+                    }
+                    Err(error) => {
+                        error!(target: "dungeon-helper", "Error notifying user that Dungeon Helper is disabled in current channel. Message ID: {}; Error: {:?}", message.id, error)
+                    }
+                }
+            }
             Action::Respond(response) => {
                 if let Response::Error(error) = &*response {
                     error!(target: "dungeon-helper", "Error processing command. Message ID: {}; Error = {:?}", message.id, error);
@@ -386,19 +6774,84 @@ impl EventHandler for Handler {
                     Some(nick) => Cow::Owned(nick),
                     None => Cow::Borrowed(&message.author.name),
                 };
-                let result = message.channel_id.send_message(&ctx.http, |builder| {
-                    response.to_message(&author_nick, &message, builder)
-                });
+                let rolls: Vec<(RollHistoryEntry, String)> = response
+                    .roll_history_entries()
+                    .into_iter()
+                    .map(|entry| {
+                        let code = self.roll_signer.sign(&SignedRoll::new(&message, &entry));
+                        (entry, code)
+                    })
+                    .collect();
+                let codes: Vec<&str> = rolls.iter().map(|(_, code)| code.as_str()).collect();
+                let result = self
+                    .delivery_metrics
+                    .retry("send message", || {
+                        message.channel_id.send_message(&ctx.http, |builder| {
+                            response.to_message(&author_nick, &message, builder);
+                            add_roll_codes(builder, &codes)
+                        })
+                    })
+                    .or_else(|error| {
+                        if !delivery::is_missing_permissions(&error) {
+                            return Err(error);
+                        }
+                        // Most likely missing the Embed Links permission, so try again without it
+                        info!(target: "dungeon-helper", "Sending message as plain text. Message ID: {}; Error: {:?}", message.id, error);
+                        self.delivery_metrics.plain_text_fallback();
+                        self.delivery_metrics.retry("send plain text message", || {
+                            message.channel_id.send_message(&ctx.http, |builder| {
+                                response.to_message(&author_nick, &message, builder);
+                                add_roll_codes(builder, &codes);
+                                embed_as_plain_text(builder)
+                            })
+                        })
+                    });
                 match result {
                     Ok(sent_message) => {
                         info!(target: "dungeon-helper", "Sent message. Message ID: {}; Sent Message ID: {}; Content: {}", message.id, sent_message.id, sent_message.content.escape_debug());
 
-                        if response.is_roll() {
-                            let delete_result = message.delete(&ctx.http);
+                        for (entry_index, (entry, code)) in rolls.iter().enumerate() {
+                            self.log_roll(&message, entry_index, entry, code);
+                        }
+
+                        if let Response::BlindRoll { roll, dm_user_id } = &*response {
+                            self.send_blind_roll(&ctx, &message, &author_nick, roll, *dm_user_id);
+                        }
+
+                        if let Response::QuickReference {
+                            name, reference, ..
+                        } = &*response
+                        {
+                            self.pin_quick_reference_message(
+                                &ctx,
+                                &message,
+                                &sent_message,
+                                PinnedQuickReference {
+                                    user_id: message.author.id,
+                                    name: name.to_owned(),
+                                    message_id: sent_message.id,
+                                    author_name: author_nick.to_string(),
+                                    description: reference.to_string(),
+                                },
+                            );
+                        } else if !response.is_roll() {
+                            self.refresh_quick_references(&ctx, &message);
+                        }
+
+                        if response.is_roll()
+                            && !self.get_channel(message.channel_id).keep_roll_messages
+                        {
+                            let delete_result = self
+                                .delivery_metrics
+                                .retry("delete message", || message.delete(&ctx.http));
                             match delete_result {
                                 Ok(()) => {
                                     info!(target: "dungeon-helper", "Deleted user message. Message ID: {}", message.id)
                                 }
+                                Err(error) if delivery::is_missing_permissions(&error) => {
+                                    // Some servers deliberately don't give us Manage Messages
+                                    info!(target: "dungeon-helper", "Not allowed to delete user message. Message ID: {}", message.id)
+                                }
                                 Err(error) => {
                                     error!(target: "dungeon-helper", "Error deleting message. Message ID: {}; Error: {:?}", message.id, error)
                                 }
@@ -414,12 +6867,67 @@ impl EventHandler for Handler {
     }
 
     fn ready(&self, context: Context, ready: Ready) {
-        let mut bot_id = self
-            .bot_id
-            .write()
-            .expect("RwLock for bot_id has been poisoned");
-        *bot_id = Some(ready.user.id.to_string());
+        self.bot_id.store(ready.user.id.0, Ordering::Relaxed);
+        match context.http.get_current_application_info() {
+            Ok(info) => self.owner_id.store(info.owner.id.0, Ordering::Relaxed),
+            Err(error) => {
+                error!(target: "dungeon-helper", "Error fetching application info. Error: {:?}", error)
+            }
+        }
         context.set_activity(Activity::playing("Dungeons & Dragons"));
         info!(target: "dungeon-helper", "{} is connected!", ready.user.name);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_refusal() {
+        let channel = Channel {
+            enabled: true,
+            locked: false,
+            dice_only: false,
+            duplicate_window: DEFAULT_DUPLICATE_WINDOW,
+            verbosity: Verbosity::Normal,
+            tie_break: TieBreak::Simultaneous,
+            limits: Limits::MAXIMUM,
+            edition: Edition::Rules2014,
+            dm_user_id: None,
+            dm_role_id: None,
+            prefix: DEFAULT_PREFIX.to_owned(),
+            keep_roll_messages: false,
+            spelling_correction: true,
+        };
+        let command_roles = [CommandRole {
+            category: CommandCategory::Editing,
+            role_id: RoleId(456),
+        }];
+        let refusal =
+            |command: &Command, role_ids: &[RoleId], is_admin: bool| match Handler::get_refusal(
+                command,
+                &channel,
+                &command_roles,
+                role_ids,
+                is_admin,
+                false,
+            ) {
+                Some(Response::Warning(warning)) => Some(warning),
+                Some(_) => Some("other".to_owned()),
+                None => None,
+            };
+        let set_server_default = Command::SetServerDefault(ChannelDefault::Locked(true));
+        assert_eq!(
+            refusal(&set_server_default, &[RoleId(456)], false),
+            Some("It looks like you're trying to change the settings that new channels start with. Only server administrators can do that.".to_owned())
+        );
+        assert_eq!(refusal(&set_server_default, &[], true), None);
+        assert_eq!(
+            refusal(&Command::SetArmorClass(16), &[RoleId(456)], false),
+            None
+        );
+        assert!(refusal(&Command::SetArmorClass(16), &[], false).is_some());
+        assert_eq!(refusal(&Command::SetArmorClass(16), &[], true), None);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::audit;
+use crate::audit::Change;
+use crate::character::Character;
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
+use serenity::model::id::{ChannelId, UserId};
+
+/// The experience points needed to reach each level, from 1st to 20th.
+const LEVEL_THRESHOLDS: [i32; 20] = [
+    0, 300, 900, 2700, 6500, 14000, 23000, 34000, 48000, 64000, 85000, 100000, 120000, 140000,
+    165000, 195000, 225000, 265000, 305000, 355000,
+];
+
+/// The level a character with the given experience points has reached.
+pub fn level_for_experience(experience: i32) -> i32 {
+    LEVEL_THRESHOLDS
+        .iter()
+        .filter(|threshold| **threshold <= experience)
+        .count()
+        .max(1) as i32
+}
+
+/// The experience points one party member received from an award.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ExperienceAward {
+    pub user_id: UserId,
+    pub name: String,
+    /// The character's experience points after the award.
+    pub experience: i32,
+    /// The character's new level, if the award took them over the threshold for another level.
+    pub level_up: Option<i32>,
+}
+
+/// Split experience points evenly between the active characters in a channel, other than the
+/// DM's, levelling up any characters who reach the next level. Returns each member's share, which
+/// is rounded down, along with what each of them received. The awards are recorded in the audit
+/// history as changes made by the DM.
+pub fn award(
+    connection: &mut Connection,
+    channel_id: ChannelId,
+    dm_id: UserId,
+    amount: i32,
+) -> RusqliteResult<(i32, Vec<ExperienceAward>)> {
+    let mut members = Vec::new();
+    for (user_id, name) in Character::get_all_active_names(connection, channel_id)? {
+        if user_id != dm_id {
+            if let Some(character) = Character::get(connection, channel_id, user_id)? {
+                members.push((user_id, name, character));
+            }
+        }
+    }
+    if members.is_empty() {
+        return Ok((0, Vec::new()));
+    }
+
+    let share = amount / members.len() as i32;
+    let transaction = connection.transaction()?;
+    let mut awards = Vec::new();
+    for (user_id, name, mut character) in members {
+        let experience = character.experience();
+        let level = character.level();
+        let level_up = character.gain_experience(share);
+        character.set_experience(&transaction, channel_id, user_id)?;
+        let changes = [
+            Change::new(
+                "Experience Points",
+                Some(experience.to_string()),
+                Some(character.experience().to_string()),
+            ),
+            Change::new(
+                "Level",
+                level.map(|level| level.to_string()),
+                character.level().map(|level| level.to_string()),
+            ),
+        ];
+        for change in changes.iter() {
+            audit::record(&transaction, channel_id, user_id, &name, dm_id, change)?;
+        }
+        awards.push(ExperienceAward {
+            user_id,
+            name,
+            experience: character.experience(),
+            level_up,
+        });
+    }
+    transaction.commit()?;
+    Ok((share, awards))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_level_for_experience() {
+        assert_eq!(level_for_experience(0), 1);
+        assert_eq!(level_for_experience(-10), 1);
+        assert_eq!(level_for_experience(299), 1);
+        assert_eq!(level_for_experience(300), 2);
+        assert_eq!(level_for_experience(6499), 4);
+        assert_eq!(level_for_experience(6500), 5);
+        assert_eq!(level_for_experience(355000), 20);
+        assert_eq!(level_for_experience(1000000), 20);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::roll::Condition;
+use regex::Regex;
+use serenity::model::id::MessageId;
+use snips_nlu_ontology::IntentParserResult;
+use std::time::{Duration, Instant};
+
+/// How long after a message it can be followed up on, such as with "again".
+pub const FOLLOW_UP_WINDOW: Duration = Duration::from_secs(120);
+
+/// The most words in a message that can be the answer to a clarification, such as "two handed",
+/// so that a message that has nothing to do with the clarification isn't added on to it.
+const MAXIMUM_ANSWER_WORDS: usize = 4;
+
+/// The last message a user sent to Dungeon Helper in a channel, which a follow-up message can
+/// build on.
+#[derive(Clone, Debug)]
+pub struct Conversation {
+    /// The message, with any channel prefix expanded.
+    pub message: String,
+    /// Whether the message was understood, rather than being answered with a clarification.
+    pub understood: bool,
+    /// Whether the message was understood as a command that rolls dice, which are the only
+    /// commands that can be repeated with a follow-up.
+    pub rolling: bool,
+    /// The command the message was guessed to be, if it was parsed with too little confidence to
+    /// be run, and the user was asked whether it's what they meant.
+    pub guess: Option<Guess>,
+    pub expires: Instant,
+}
+
+/// The intent parser's guess at what a message meant, which the user can confirm by answering
+/// "yes" when asked whether it's what they meant.
+#[derive(Clone, Debug)]
+pub struct Guess {
+    pub message_id: MessageId,
+    pub intent_result: IntentParserResult,
+}
+
+impl Conversation {
+    /// Rewrite a follow-up to the message as the command it asks for, such as "roll stealth with
+    /// advantage" from "roll stealth" and "with advantage this time". Returns None if the message
+    /// isn't a follow-up, or if the previous message wasn't a roll.
+    pub fn follow_up(&self, message: &str) -> Option<String> {
+        if !self.understood || !self.rolling {
+            return None;
+        }
+        FollowUp::parse(message).map(|follow_up| follow_up.apply(&self.message))
+    }
+
+    /// Complete a message that was answered with a clarification using the answer to it, such as
+    /// "attack with my longsword two handed" from "attack with my longsword" and "two handed".
+    /// Returns None if the message was understood, or if the answer is too long to be an answer.
+    pub fn answer(&self, answer: &str) -> Option<String> {
+        if self.understood || answer.split_whitespace().count() > MAXIMUM_ANSWER_WORDS {
+            return None;
+        }
+        Some(format!("{} {}", self.message.trim_end(), answer.trim()))
+    }
+
+    /// The guess at what the message meant, if the user has confirmed it with a message such as
+    /// "yes" or "that's right". Returns None if the message isn't a confirmation, or if the
+    /// previous message wasn't a guess.
+    pub fn confirm(&self, message: &str) -> Option<&Guess> {
+        lazy_static! {
+            static ref CONFIRMATION_REGEX: Regex = Regex::new(
+                r"^(?i)(?:yes|yeah|yep|yup|correct|(?:yes,? +)?(?:that's|that +is) +(?:right|it|what +i +meant)|(?:yes,? +)?i +did)(?: +please)? *[.!]*$"
+            )
+            .unwrap();
+        }
+
+        self.guess
+            .as_ref()
+            .filter(|_| CONFIRMATION_REGEX.is_match(message.trim()))
+    }
+}
+
+/// A short message that repeats the previous command, rather than being a command of its own.
+#[derive(Debug, PartialEq)]
+pub enum FollowUp {
+    /// Run the previous command again, such as "again", or "once more".
+    Again,
+    /// Run the previous command again with a different condition, such as "with advantage this
+    /// time", or "normally" for neither advantage nor disadvantage.
+    Condition(Option<Condition>),
+}
+
+impl FollowUp {
+    pub fn parse(message: &str) -> Option<FollowUp> {
+        lazy_static! {
+            static ref AGAIN_REGEX: Regex = Regex::new(
+                r"^(?i)(?:(?:(?:roll|do|try|attack)(?: +(?:it|that))? +)?(?:again|once +more|one +more +time)|repeat(?: +(?:it|that))?|same +again)(?: +please)? *[.!]*$"
+            )
+            .unwrap();
+            static ref CONDITION_REGEX: Regex = Regex::new(
+                r"^(?i)(?:(?:again|now|and|but|roll|do|try|attack)(?: +(?:it|that))? +)?(?:with +(advantage|disadvantage)|(normally|straight|without +(?:advantage|disadvantage)))(?: +(?:this +time|now|again|instead))?(?: +please)? *[.!]*$"
+            )
+            .unwrap();
+        }
+
+        let message = message.trim();
+        if AGAIN_REGEX.is_match(message) {
+            Some(FollowUp::Again)
+        } else {
+            CONDITION_REGEX.captures(message).map(|captures| {
+                FollowUp::Condition(captures.get(1).and_then(|m| {
+                    match m.as_str().to_lowercase().as_str() {
+                        "advantage" => Some(Condition::Advantage),
+                        "disadvantage" => Some(Condition::Disadvantage),
+                        _ => None,
+                    }
+                }))
+            })
+        }
+    }
+
+    /// Rewrite the previous message with the follow-up, such as "roll stealth with advantage"
+    /// from "roll stealth with disadvantage" and "with advantage this time".
+    pub fn apply(&self, previous: &str) -> String {
+        lazy_static! {
+            static ref CONDITION_REGEX: Regex =
+                Regex::new(r"(?i) +with +(?:advantage|disadvantage)\b").unwrap();
+        }
+
+        match self {
+            FollowUp::Again => previous.to_owned(),
+            FollowUp::Condition(condition) => {
+                let without_condition = CONDITION_REGEX.replace_all(previous, "");
+                let without_condition = without_condition.trim_end();
+                match condition {
+                    Some(Condition::Advantage) => format!("{} with advantage", without_condition),
+                    Some(Condition::Disadvantage) => {
+                        format!("{} with disadvantage", without_condition)
+                    }
+                    None => without_condition.to_owned(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use snips_nlu_ontology::IntentClassifierResult;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(FollowUp::parse("again"), Some(FollowUp::Again));
+        assert_eq!(FollowUp::parse("Roll it again!"), Some(FollowUp::Again));
+        assert_eq!(FollowUp::parse("once more please"), Some(FollowUp::Again));
+        assert_eq!(
+            FollowUp::parse("with advantage this time"),
+            Some(FollowUp::Condition(Some(Condition::Advantage)))
+        );
+        assert_eq!(
+            FollowUp::parse("Again with disadvantage"),
+            Some(FollowUp::Condition(Some(Condition::Disadvantage)))
+        );
+        assert_eq!(
+            FollowUp::parse("now without advantage"),
+            Some(FollowUp::Condition(None))
+        );
+        assert_eq!(FollowUp::parse("roll stealth with advantage"), None);
+        assert_eq!(FollowUp::parse("two handed"), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(FollowUp::Again.apply("!r d20 + 5"), "!r d20 + 5");
+        assert_eq!(
+            FollowUp::Condition(Some(Condition::Advantage)).apply("@DungeonHelper roll stealth"),
+            "@DungeonHelper roll stealth with advantage"
+        );
+        assert_eq!(
+            FollowUp::Condition(Some(Condition::Advantage))
+                .apply("!r perception with disadvantage"),
+            "!r perception with advantage"
+        );
+        assert_eq!(
+            FollowUp::Condition(None).apply("!r d20 with Advantage"),
+            "!r d20"
+        );
+    }
+
+    fn conversation(message: &str, understood: bool, rolling: bool) -> Conversation {
+        Conversation {
+            message: message.to_owned(),
+            understood,
+            rolling,
+            guess: None,
+            expires: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_follow_up() {
+        assert_eq!(
+            conversation("!r stealth", true, true).follow_up("again with advantage"),
+            Some("!r stealth with advantage".to_owned())
+        );
+        assert_eq!(
+            conversation("!r stealth", true, true).follow_up("roll perception"),
+            None
+        );
+        assert_eq!(conversation("!ac 16", true, false).follow_up("again"), None);
+        assert_eq!(
+            conversation("@DungeonHelper show my character sheet", true, false)
+                .follow_up("with advantage this time"),
+            None
+        );
+        assert_eq!(
+            conversation("@DungeonHelper attack with my longsword", false, false)
+                .follow_up("again"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(
+            conversation("@DungeonHelper attack with my longsword", false, false)
+                .answer(" two handed"),
+            Some("@DungeonHelper attack with my longsword two handed".to_owned())
+        );
+        assert_eq!(
+            conversation("@DungeonHelper attack with my longsword", false, false)
+                .answer("what time is the session on Friday night?"),
+            None
+        );
+        assert_eq!(
+            conversation("!r stealth", true, true).answer("two handed"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_confirm() {
+        let mut guessed = conversation("@DungeonHelper how much gold have I got", false, false);
+        guessed.guess = Some(Guess {
+            message_id: MessageId(1),
+            intent_result: IntentParserResult {
+                input: "how much gold have I got".to_owned(),
+                intent: IntentClassifierResult {
+                    intent_name: Some("showCurrency".to_owned()),
+                    confidence_score: 0.4,
+                },
+                slots: vec![],
+                alternatives: vec![],
+            },
+        });
+        let confirmed = |message| guessed.confirm(message).map(|guess| guess.message_id);
+        assert_eq!(confirmed("yes"), Some(MessageId(1)));
+        assert_eq!(confirmed("Yes please!"), Some(MessageId(1)));
+        assert_eq!(confirmed("that's what I meant"), Some(MessageId(1)));
+        assert_eq!(confirmed("yes, I did."), Some(MessageId(1)));
+        assert_eq!(confirmed("no"), None);
+        assert_eq!(confirmed("yes roll stealth"), None);
+        assert_eq!(
+            conversation("@DungeonHelper roll stealth", true, true)
+                .confirm("yes")
+                .map(|guess| guess.message_id),
+            None
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::id::GuildId;
+use std::fmt;
+
+/// The prefix for shorthand commands, such as "!r d20", unless a channel changes it.
+pub const DEFAULT_PREFIX: &str = "!";
+
+/// The settings that channels in a server start with when Dungeon Helper is first used in them.
+/// Changing them doesn't change channels that have already been used.
+pub struct Guild {
+    pub enabled: bool,
+    pub locked: bool,
+    pub dice_only: bool,
+    /// The prefix for shorthand commands, such as "?" for "?r d20".
+    pub prefix: String,
+    pub keep_roll_messages: bool,
+    pub spelling_correction: bool,
+}
+
+/// One of the settings that channels in a server start with.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChannelDefault {
+    Enabled(bool),
+    Locked(bool),
+    DiceOnly(bool),
+    Prefix(String),
+    KeepRollMessages(bool),
+    SpellingCorrection(bool),
+}
+
+impl ChannelDefault {
+    /// Parse a setting, such as "enable", "lock", "dice-only off", "keep-rolls on", "spelling off"
+    /// or "prefix ?". A prefix must be valid for shorthand commands.
+    pub fn parse(string: &str) -> Option<ChannelDefault> {
+        lazy_static! {
+            static ref DICE_ONLY_REGEX: Regex =
+                Regex::new(r"^(?i)dice[ -]?only(?: +(on|off))?$").unwrap();
+            static ref KEEP_ROLLS_REGEX: Regex =
+                Regex::new(r"^(?i)keep[ -]?rolls?(?: +messages)?(?: +(on|off))?$").unwrap();
+            static ref PREFIX_REGEX: Regex = Regex::new(r"^(?i)prefix +(\S+)$").unwrap();
+            static ref SPELLING_REGEX: Regex =
+                Regex::new(r"^(?i)spell(?:ing|check)(?: +correction)?(?: +(on|off))?$").unwrap();
+        }
+
+        let string = string.trim();
+        match string.to_lowercase().as_ref() {
+            "enable" => Some(ChannelDefault::Enabled(true)),
+            "disable" => Some(ChannelDefault::Enabled(false)),
+            "lock" => Some(ChannelDefault::Locked(true)),
+            "unlock" => Some(ChannelDefault::Locked(false)),
+            _ => {
+                if let Some(captures) = DICE_ONLY_REGEX.captures(string) {
+                    Some(ChannelDefault::DiceOnly(
+                        captures
+                            .get(1)
+                            .map_or(true, |m| m.as_str().eq_ignore_ascii_case("on")),
+                    ))
+                } else if let Some(captures) = KEEP_ROLLS_REGEX.captures(string) {
+                    Some(ChannelDefault::KeepRollMessages(
+                        captures
+                            .get(1)
+                            .map_or(true, |m| m.as_str().eq_ignore_ascii_case("on")),
+                    ))
+                } else if let Some(captures) = SPELLING_REGEX.captures(string) {
+                    Some(ChannelDefault::SpellingCorrection(
+                        captures
+                            .get(1)
+                            .map_or(true, |m| m.as_str().eq_ignore_ascii_case("on")),
+                    ))
+                } else {
+                    PREFIX_REGEX
+                        .captures(string)
+                        .map(|captures| captures.get(1).map_or("", |m| m.as_str()))
+                        .filter(|prefix| Guild::is_valid_prefix(prefix))
+                        .map(|prefix| ChannelDefault::Prefix(prefix.to_owned()))
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for ChannelDefault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelDefault::Enabled(true) => write!(f, "Dungeon Helper enabled"),
+            ChannelDefault::Enabled(false) => write!(f, "Dungeon Helper disabled"),
+            ChannelDefault::Locked(true) => write!(f, "characters locked"),
+            ChannelDefault::Locked(false) => write!(f, "characters unlocked"),
+            ChannelDefault::DiceOnly(true) => write!(f, "dice only on"),
+            ChannelDefault::DiceOnly(false) => write!(f, "dice only off"),
+            ChannelDefault::Prefix(prefix) => write!(f, "the prefix {}", prefix),
+            ChannelDefault::KeepRollMessages(true) => write!(f, "roll commands kept"),
+            ChannelDefault::KeepRollMessages(false) => write!(f, "roll commands deleted"),
+            ChannelDefault::SpellingCorrection(true) => write!(f, "spelling correction on"),
+            ChannelDefault::SpellingCorrection(false) => write!(f, "spelling correction off"),
+        }
+    }
+}
+
+impl Guild {
+    /// Whether a prefix can be used for shorthand commands. It must be one to three symbols, so
+    /// that it can't be mistaken for the start of an ordinary message or a mention.
+    pub fn is_valid_prefix(prefix: &str) -> bool {
+        let length = prefix.chars().count();
+        length >= 1
+            && length <= 3
+            && prefix
+                .chars()
+                .all(|c| c.is_ascii_punctuation() && !"@<>#:;`".contains(c))
+    }
+
+    pub fn get(connection: &Connection, guild_id: GuildId) -> RusqliteResult<Option<Guild>> {
+        connection
+            .query_row(
+                "SELECT enabled, locked, dice_only, prefix, keep_roll_messages, spelling_correction \
+                 FROM guilds \
+                 WHERE guild_id = $1",
+                &[&guild_id.to_string()],
+                Guild::from_row,
+            )
+            .optional()
+    }
+
+    /// Change one of the settings that channels in a server start with.
+    pub fn set_default(
+        connection: &Connection,
+        guild_id: GuildId,
+        default: &ChannelDefault,
+    ) -> RusqliteResult<usize> {
+        let guild_id = guild_id.to_string();
+        let column = match default {
+            ChannelDefault::Enabled(_) => "enabled",
+            ChannelDefault::Locked(_) => "locked",
+            ChannelDefault::DiceOnly(_) => "dice_only",
+            ChannelDefault::Prefix(_) => "prefix",
+            ChannelDefault::KeepRollMessages(_) => "keep_roll_messages",
+            ChannelDefault::SpellingCorrection(_) => "spelling_correction",
+        };
+        let value: &dyn ToSql = match default {
+            ChannelDefault::Enabled(setting)
+            | ChannelDefault::Locked(setting)
+            | ChannelDefault::DiceOnly(setting)
+            | ChannelDefault::KeepRollMessages(setting)
+            | ChannelDefault::SpellingCorrection(setting) => setting,
+            ChannelDefault::Prefix(prefix) => prefix,
+        };
+        let params: &[&dyn ToSql] = &[&guild_id, value];
+        connection.execute(
+            &format!(
+                "INSERT INTO guilds (guild_id, {0}) VALUES ($1, $2) \
+                 ON CONFLICT (guild_id) DO UPDATE SET {0} = $2",
+                column
+            ),
+            params,
+        )
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<Guild> {
+        Ok(Guild {
+            enabled: row.get("enabled")?,
+            locked: row.get("locked")?,
+            dice_only: row.get("dice_only")?,
+            prefix: row.get("prefix")?,
+            keep_roll_messages: row.get("keep_roll_messages")?,
+            spelling_correction: row.get("spelling_correction")?,
+        })
+    }
+}
+
+/// The settings for servers that haven't changed them.
+impl Default for Guild {
+    fn default() -> Guild {
+        Guild {
+            enabled: false,
+            locked: false,
+            dice_only: false,
+            prefix: DEFAULT_PREFIX.to_owned(),
+            keep_roll_messages: false,
+            spelling_correction: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_channel_default() {
+        assert_eq!(
+            ChannelDefault::parse("enable"),
+            Some(ChannelDefault::Enabled(true))
+        );
+        assert_eq!(
+            ChannelDefault::parse("Unlock"),
+            Some(ChannelDefault::Locked(false))
+        );
+        assert_eq!(
+            ChannelDefault::parse("dice only off"),
+            Some(ChannelDefault::DiceOnly(false))
+        );
+        assert_eq!(
+            ChannelDefault::parse("prefix ?"),
+            Some(ChannelDefault::Prefix("?".to_owned()))
+        );
+        assert_eq!(
+            ChannelDefault::parse("keep rolls off"),
+            Some(ChannelDefault::KeepRollMessages(false))
+        );
+        assert_eq!(
+            ChannelDefault::parse("spelling correction off"),
+            Some(ChannelDefault::SpellingCorrection(false))
+        );
+        assert_eq!(ChannelDefault::parse("prefix dh"), None);
+        assert_eq!(ChannelDefault::parse("shutdown"), None);
+    }
+
+    #[test]
+    fn test_is_valid_prefix() {
+        assert_eq!(Guild::is_valid_prefix("?"), true);
+        assert_eq!(Guild::is_valid_prefix("$$"), true);
+        assert_eq!(Guild::is_valid_prefix(""), false);
+        assert_eq!(Guild::is_valid_prefix("dh"), false);
+        assert_eq!(Guild::is_valid_prefix("@"), false);
+        assert_eq!(Guild::is_valid_prefix("!!!!"), false);
+    }
+}
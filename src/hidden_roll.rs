@@ -0,0 +1,141 @@
+use crate::roll::{ConditionalRoll, ConditionalRollResult};
+use crate::roll_signature::RollSigner;
+use rand::Rng;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serenity::model::id::{ChannelId, UserId};
+
+/// A roll made into the dice cup, whose result is kept sealed until the roller reveals it, such
+/// as a DM's pre-roll. Only the roll's code is shown when it's made, and the same code is shown
+/// when it's revealed, so that everyone can see the result wasn't rolled again in the meantime.
+#[derive(Debug, Eq, PartialEq)]
+pub struct HiddenRoll {
+    pub code: String,
+    /// What was rolled, such as "1d20".
+    pub roll: String,
+    /// The dice rolled and the result, as they're shown when the roll is revealed.
+    pub details: String,
+    pub result: i32,
+}
+
+impl HiddenRoll {
+    /// Seal the result of a roll, giving it a code that can't be linked to its result until it's
+    /// revealed, even when two hidden rolls have the same result.
+    pub fn new(
+        signer: &RollSigner,
+        channel_id: ChannelId,
+        user_id: UserId,
+        roll: &ConditionalRoll,
+        result: &ConditionalRollResult,
+    ) -> HiddenRoll {
+        let nonce: u64 = rand::thread_rng().gen();
+        let details = result.to_string();
+        let code = signer.code(&format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            channel_id,
+            user_id,
+            nonce,
+            roll,
+            details,
+            result.result()
+        ));
+        HiddenRoll {
+            code,
+            roll: roll.to_string(),
+            details,
+            result: result.result(),
+        }
+    }
+
+    /// Save a hidden roll for a user until they reveal it.
+    pub fn save(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &self.code,
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &self.roll,
+            &self.details,
+            &self.result,
+        ];
+        connection.execute(
+            "INSERT INTO hidden_rolls (code, channel_id, user_id, roll, details, result) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+            params,
+        )
+    }
+
+    /// Reveal every hidden roll a user has made in a channel, in the order they were made. Each
+    /// roll can only be revealed once.
+    pub fn reveal_all(
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<Vec<HiddenRoll>> {
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string()];
+        let rolls = {
+            let mut statement = transaction.prepare(
+                "SELECT code, roll, details, result FROM hidden_rolls \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 ORDER BY rolled, rowid",
+            )?;
+            let rows = statement.query_map(params, HiddenRoll::from_row)?;
+            rows.collect::<RusqliteResult<Vec<HiddenRoll>>>()?
+        };
+        transaction.execute(
+            "DELETE FROM hidden_rolls WHERE channel_id = $1 AND user_id = $2",
+            params,
+        )?;
+        transaction.commit()?;
+        Ok(rolls)
+    }
+
+    /// Forget every hidden roll a user has made in a channel without revealing them. Returns the
+    /// number of rolls forgotten.
+    pub fn delete_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string()];
+        connection.execute(
+            "DELETE FROM hidden_rolls WHERE channel_id = $1 AND user_id = $2",
+            params,
+        )
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<HiddenRoll> {
+        Ok(HiddenRoll {
+            code: row.get("code")?,
+            roll: row.get("roll")?,
+            details: row.get("details")?,
+            result: row.get("result")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand_pcg::Pcg32;
+
+    #[test]
+    fn test_new() {
+        let signer = RollSigner::new(b"secret");
+        let roll = ConditionalRoll::new_unsafe(1, 20, 0, None);
+        let result = roll.roll(&mut Pcg32::new(0, 0));
+        let first = HiddenRoll::new(&signer, ChannelId(1), UserId(2), &roll, &result);
+        let second = HiddenRoll::new(&signer, ChannelId(1), UserId(2), &roll, &result);
+        assert_eq!(first.roll, "1d20");
+        assert_eq!(first.result, result.result());
+        assert_eq!(first.details, second.details);
+        assert_ne!(first.code, second.code);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::roll::{Limits, Roll};
+use regex::{Captures, Regex};
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
+use serenity::model::id::ChannelId;
+use std::fmt;
+
+/// How a critical hit deals extra damage.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CriticalHits {
+    /// The damage dice are rolled twice, as in the rules.
+    DoubleDice,
+    /// One set of damage dice deals its maximum, and the other set is rolled.
+    MaximiseDice,
+}
+
+impl CriticalHits {
+    pub fn parse(string: &str) -> Option<CriticalHits> {
+        match string.trim().to_lowercase().as_str() {
+            "double" | "double dice" | "rules" => Some(CriticalHits::DoubleDice),
+            "max" | "maximum" | "maximise" | "maximize" | "max dice" => {
+                Some(CriticalHits::MaximiseDice)
+            }
+            _ => None,
+        }
+    }
+
+    /// The critical hit as it's stored.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CriticalHits::DoubleDice => "double",
+            CriticalHits::MaximiseDice => "max",
+        }
+    }
+
+    /// The damage roll for a critical hit, from the damage roll for an ordinary hit.
+    pub fn damage_roll(self, roll: Roll) -> Roll {
+        match self {
+            CriticalHits::DoubleDice => roll.multiply_rolls(2),
+            CriticalHits::MaximiseDice => roll.add_modifier(roll.rolls() as i32 * roll.sides()),
+        }
+    }
+}
+
+impl fmt::Display for CriticalHits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CriticalHits::DoubleDice => write!(f, "roll the damage dice twice"),
+            CriticalHits::MaximiseDice => write!(f, "maximise one set of damage dice"),
+        }
+    }
+}
+
+/// The house rules used in a channel. Each one follows the rules as written unless the DM changes
+/// it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HouseRules {
+    pub critical_hits: CriticalHits,
+    /// Whether attacking a creature while an ally is on the opposite side of it gives advantage.
+    pub flanking: bool,
+    /// Whether a character can drink a potion as a bonus action, instead of an action. Dungeon
+    /// Helper doesn't track actions, so this is a ruling for the table to refer to.
+    pub potion_bonus_action: bool,
+}
+
+/// A change to one of the house rules in a channel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HouseRule {
+    CriticalHits(CriticalHits),
+    Flanking(bool),
+    PotionBonusAction(bool),
+    /// The most dice that can be rolled at once, which are stored with the channel, or None to go
+    /// back to the maximum allowed values.
+    MaxDice(Option<Limits>),
+}
+
+impl HouseRule {
+    /// Parse a house rule, such as "crits max", "flanking on", "potions bonus action" or "max dice
+    /// 20d100".
+    pub fn parse(string: &str) -> Option<HouseRule> {
+        lazy_static! {
+            static ref CRITICAL_HITS_REGEX: Regex =
+                Regex::new(r"^(?i)crit(?:ical)?(?:s| +hits?)? +(.+)$").unwrap();
+            static ref FLANKING_REGEX: Regex =
+                Regex::new(r"^(?i)flanking(?: +(on|off))?$").unwrap();
+            static ref POTION_REGEX: Regex =
+                Regex::new(r"^(?i)potions?(?: +(?:as +)?(?:a +)?bonus +actions?)?(?: +(on|off))?$")
+                    .unwrap();
+            static ref MAX_DICE_REGEX: Regex =
+                Regex::new(r"^(?i)(?:max|maximum)[ -]?dice +(.+)$").unwrap();
+        }
+        fn is_on(captures: &Captures) -> bool {
+            captures
+                .get(1)
+                .map_or(true, |m| m.as_str().eq_ignore_ascii_case("on"))
+        }
+
+        let string = string.trim();
+        if let Some(captures) = CRITICAL_HITS_REGEX.captures(string) {
+            CriticalHits::parse(&captures[1]).map(HouseRule::CriticalHits)
+        } else if let Some(captures) = FLANKING_REGEX.captures(string) {
+            Some(HouseRule::Flanking(is_on(&captures)))
+        } else if let Some(captures) = POTION_REGEX.captures(string) {
+            Some(HouseRule::PotionBonusAction(is_on(&captures)))
+        } else if let Some(captures) = MAX_DICE_REGEX.captures(string) {
+            match captures[1].trim().to_lowercase().as_str() {
+                "reset" | "default" | "off" => Some(HouseRule::MaxDice(None)),
+                limits => Limits::parse(limits).map(|limits| HouseRule::MaxDice(Some(limits))),
+            }
+        } else {
+            None
+        }
+    }
+
+    /// The rule and its value as they're stored, if it's stored with the other house rules.
+    fn to_parts(self) -> Option<(&'static str, &'static str)> {
+        let on_off = |setting| if setting { "on" } else { "off" };
+        match self {
+            HouseRule::CriticalHits(critical_hits) => {
+                Some(("critical_hits", critical_hits.as_str()))
+            }
+            HouseRule::Flanking(flanking) => Some(("flanking", on_off(flanking))),
+            HouseRule::PotionBonusAction(potion_bonus_action) => {
+                Some(("potion_bonus_action", on_off(potion_bonus_action)))
+            }
+            HouseRule::MaxDice(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for HouseRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HouseRule::CriticalHits(critical_hits) => {
+                write!(f, "Critical hits {}.", critical_hits)
+            }
+            HouseRule::Flanking(true) => write!(f, "Flanking gives advantage on attack rolls."),
+            HouseRule::Flanking(false) => write!(f, "Flanking doesn't give advantage."),
+            HouseRule::PotionBonusAction(true) => {
+                write!(f, "Drinking a potion takes a bonus action.")
+            }
+            HouseRule::PotionBonusAction(false) => write!(f, "Drinking a potion takes an action."),
+            HouseRule::MaxDice(Some(limits)) => write!(
+                f,
+                "Up to {} dice with up to {} sides each can be rolled at once.",
+                limits.rolls, limits.sides
+            ),
+            HouseRule::MaxDice(None) => write!(
+                f,
+                "Up to {} dice with up to {} sides each can be rolled at once.",
+                Limits::MAXIMUM.rolls,
+                Limits::MAXIMUM.sides
+            ),
+        }
+    }
+}
+
+impl HouseRules {
+    /// The rules as written, for channels that haven't changed them.
+    pub const RULES_AS_WRITTEN: HouseRules = HouseRules {
+        critical_hits: CriticalHits::DoubleDice,
+        flanking: false,
+        potion_bonus_action: false,
+    };
+
+    /// Retrieve the house rules used in a channel. Rules that can't be parsed follow the rules as
+    /// written.
+    pub fn get(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<HouseRules> {
+        let mut statement = connection
+            .prepare("SELECT rule, value FROM channel_house_rules WHERE channel_id = $1")?;
+        let rows = statement.query_map(&[&channel_id.to_string()], |row| {
+            Ok((
+                row.get::<_, String>("rule")?,
+                row.get::<_, String>("value")?,
+            ))
+        })?;
+        let mut house_rules = HouseRules::RULES_AS_WRITTEN;
+        for row in rows {
+            let (rule, value) = row?;
+            house_rules.apply(&rule, &value);
+        }
+        Ok(house_rules)
+    }
+
+    /// Change one of the house rules used in a channel. The dice limits are stored with the
+    /// channel, so they aren't changed here.
+    pub fn set(
+        connection: &Connection,
+        channel_id: ChannelId,
+        house_rule: HouseRule,
+    ) -> RusqliteResult<usize> {
+        match house_rule.to_parts() {
+            Some((rule, value)) => {
+                let params: &[&dyn ToSql] = &[&channel_id.to_string(), &rule, &value];
+                connection.execute(
+                    "INSERT INTO channel_house_rules (channel_id, rule, value) VALUES ($1, $2, $3) \
+                     ON CONFLICT (channel_id, rule) DO UPDATE SET value = $3",
+                    params,
+                )
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Go back to the rules as written in a channel.
+    pub fn reset(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+        connection.execute(
+            "DELETE FROM channel_house_rules WHERE channel_id = $1",
+            &[&channel_id.to_string()],
+        )
+    }
+
+    fn apply(&mut self, rule: &str, value: &str) {
+        match rule {
+            "critical_hits" => {
+                if let Some(critical_hits) = CriticalHits::parse(value) {
+                    self.critical_hits = critical_hits
+                }
+            }
+            "flanking" => self.flanking = value == "on",
+            "potion_bonus_action" => self.potion_bonus_action = value == "on",
+            _ => (),
+        }
+    }
+
+    /// Each of the house rules stored with the other house rules, to show to the table.
+    pub fn rules(self) -> Vec<HouseRule> {
+        vec![
+            HouseRule::CriticalHits(self.critical_hits),
+            HouseRule::Flanking(self.flanking),
+            HouseRule::PotionBonusAction(self.potion_bonus_action),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            HouseRule::parse("crits max"),
+            Some(HouseRule::CriticalHits(CriticalHits::MaximiseDice))
+        );
+        assert_eq!(
+            HouseRule::parse("critical hits double dice"),
+            Some(HouseRule::CriticalHits(CriticalHits::DoubleDice))
+        );
+        assert_eq!(
+            HouseRule::parse("flanking"),
+            Some(HouseRule::Flanking(true))
+        );
+        assert_eq!(
+            HouseRule::parse("potions as a bonus action off"),
+            Some(HouseRule::PotionBonusAction(false))
+        );
+        assert_eq!(
+            HouseRule::parse("max dice 20d100"),
+            Some(HouseRule::MaxDice(Limits::new(20, 100)))
+        );
+        assert_eq!(
+            HouseRule::parse("max dice reset"),
+            Some(HouseRule::MaxDice(None))
+        );
+        assert_eq!(HouseRule::parse("crits triple"), None);
+        assert_eq!(HouseRule::parse("flying"), None);
+    }
+
+    #[test]
+    fn test_critical_hits_damage_roll() {
+        let roll = Roll::new_unsafe(2, 6, 3);
+        assert_eq!(
+            CriticalHits::DoubleDice.damage_roll(roll),
+            Roll::new_unsafe(4, 6, 3)
+        );
+        assert_eq!(
+            CriticalHits::MaximiseDice.damage_roll(roll),
+            Roll::new_unsafe(2, 6, 15)
+        );
+    }
+}
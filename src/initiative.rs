@@ -0,0 +1,593 @@
+use crate::tie_break;
+use crate::tie_break::{Tie, TieBreak};
+use rand::Rng;
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::id::ChannelId;
+use std::cmp::Reverse;
+use std::fmt;
+
+/// A combatant in an encounter, and the initiative they rolled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InitiativeEntry {
+    pub name: String,
+    pub initiative: i32,
+    /// How the combatant's place was decided, if they had the same initiative as another.
+    pub tie: Option<Tie>,
+}
+
+/// The order in which combatants take their turns in an encounter, from highest to lowest
+/// initiative. Combatants with equal initiative keep the order in which they were listed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InitiativeOrder {
+    entries: Vec<InitiativeEntry>,
+}
+
+/// Whose turn it is in an encounter, as a position in the initiative order, and which round it is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Turn {
+    pub position: usize,
+    pub round: i32,
+}
+
+impl Turn {
+    /// The first turn of an encounter.
+    pub fn first() -> Turn {
+        Turn {
+            position: 0,
+            round: 1,
+        }
+    }
+
+    /// The turn after this one, in an initiative order with the given number of combatants.
+    /// After the last combatant's turn, the next round begins.
+    pub fn next(self, combatants: usize) -> Turn {
+        if self.position + 1 < combatants {
+            Turn {
+                position: self.position + 1,
+                round: self.round,
+            }
+        } else {
+            Turn {
+                position: 0,
+                round: self.round + 1,
+            }
+        }
+    }
+
+    /// The first turn of the next round, skipping the rest of the turns in this round.
+    pub fn next_round(self) -> Turn {
+        Turn {
+            position: 0,
+            round: self.round + 1,
+        }
+    }
+
+    /// The current turn after a combatant is added to the initiative order at the given position,
+    /// keeping the turn with the same combatant. A combatant added before the current turn takes
+    /// their first turn in the next round.
+    pub fn after_insertion(self, inserted: usize) -> Turn {
+        if inserted <= self.position {
+            Turn {
+                position: self.position + 1,
+                round: self.round,
+            }
+        } else {
+            self
+        }
+    }
+
+    /// The current turn after the combatant at the given position is removed from the initiative
+    /// order, leaving the given number of combatants. If it was the removed combatant's turn, the
+    /// turn moves back to the combatant before them, so that the next turn is the combatant after
+    /// them. Returns None if there's no one left.
+    pub fn after_removal(self, removed: usize, combatants: usize) -> Option<Turn> {
+        if combatants == 0 {
+            None
+        } else if self.position > removed {
+            Some(Turn {
+                position: self.position - 1,
+                round: self.round,
+            })
+        } else if self.position == removed && removed == 0 {
+            Some(Turn {
+                position: combatants - 1,
+                round: self.round - 1,
+            })
+        } else if self.position == removed {
+            Some(Turn {
+                position: removed - 1,
+                round: self.round,
+            })
+        } else {
+            Some(self)
+        }
+    }
+}
+
+/// An error parsing an initiative order, containing the entry that couldn't be understood.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParserError(pub String);
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "I couldn't find a name and initiative in \"{}\".",
+            self.0
+        )
+    }
+}
+
+impl InitiativeOrder {
+    pub fn new(mut entries: Vec<InitiativeEntry>) -> InitiativeOrder {
+        // A stable sort, so that ties keep the order they were listed in
+        entries.sort_by_key(|entry| Reverse(entry.initiative));
+        InitiativeOrder { entries }
+    }
+
+    /// Parse an initiative order from a list such as "Alice 18, goblin A 15, Bob: 12". Entries may
+    /// be separated by commas, semicolons, or new lines. If a name is listed more than once, the
+    /// last initiative is used.
+    pub fn parse(string: &str) -> Result<InitiativeOrder, ParserError> {
+        lazy_static! {
+            static ref ENTRY_REGEX: Regex = Regex::new(r"^(.*?)\s*[:=]?\s*(-?\d+)$").unwrap();
+        }
+
+        let mut entries: Vec<InitiativeEntry> = Vec::new();
+        for entry in string
+            .split(|c: char| c == ',' || c == ';' || c == '\n')
+            .map(|entry| entry.trim())
+            .filter(|entry| !entry.is_empty())
+        {
+            let parsed = ENTRY_REGEX.captures(entry).and_then(|captures| {
+                let name = captures.get(1)?.as_str().trim();
+                let initiative = captures.get(2)?.as_str().parse().ok()?;
+                if name.is_empty() {
+                    None
+                } else {
+                    Some(InitiativeEntry {
+                        name: name.to_owned(),
+                        initiative,
+                        tie: None,
+                    })
+                }
+            });
+            let parsed = parsed.ok_or_else(|| ParserError(entry.to_owned()))?;
+            entries.retain(|existing| existing.name.to_lowercase() != parsed.name.to_lowercase());
+            entries.push(parsed);
+        }
+        if entries.is_empty() {
+            Err(ParserError(string.trim().to_owned()))
+        } else {
+            Ok(InitiativeOrder::new(entries))
+        }
+    }
+
+    /// Break ties between combatants with the same initiative, using the Dexterity score of each
+    /// combatant by name where it's needed and known.
+    pub fn break_ties<F, R>(self, tie_break: TieBreak, dexterity: F, rng: &mut R) -> InitiativeOrder
+    where
+        F: Fn(&str) -> Option<i32>,
+        R: Rng + ?Sized,
+    {
+        let entries = tie_break::rank(
+            tie_break,
+            self.entries,
+            |entry| entry.initiative,
+            |entry| dexterity(&entry.name),
+            rng,
+        )
+        .into_iter()
+        .map(|(entry, tie)| InitiativeEntry { tie, ..entry })
+        .collect();
+        InitiativeOrder { entries }
+    }
+
+    pub fn entries(&self) -> &[InitiativeEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<InitiativeOrder> {
+        let mut statement = connection.prepare(
+            "SELECT name, initiative, tie, tie_value \
+             FROM initiative_order \
+             WHERE channel_id = $1 \
+             ORDER BY position",
+        )?;
+        let rows =
+            statement.query_map(&[&channel_id.to_string()], InitiativeOrder::entry_from_row)?;
+        rows.collect::<RusqliteResult<Vec<InitiativeEntry>>>()
+            .map(|entries| InitiativeOrder { entries })
+    }
+
+    /// Replace the initiative order for a channel.
+    pub fn set(&self, connection: &mut Connection, channel_id: ChannelId) -> RusqliteResult<()> {
+        let channel_id = channel_id.to_string();
+        let transaction = connection.transaction()?;
+        transaction.execute(
+            "DELETE FROM initiative_order WHERE channel_id = $1",
+            &[&channel_id],
+        )?;
+        // A new initiative order starts a new encounter
+        transaction.execute(
+            "DELETE FROM initiative_turns WHERE channel_id = $1",
+            &[&channel_id],
+        )?;
+        for (position, entry) in self.entries.iter().enumerate() {
+            let position = position as i64;
+            let (tie, tie_value) = entry.tie.map_or((None, None), |tie| {
+                let (tie, tie_value) = tie.to_parts();
+                (Some(tie), tie_value)
+            });
+            let params: &[&dyn ToSql] = &[
+                &channel_id,
+                &entry.name,
+                &entry.initiative,
+                &position,
+                &tie,
+                &tie_value,
+            ];
+            transaction.execute(
+                "INSERT INTO initiative_order (channel_id, name, initiative, position, tie, tie_value) \
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+                params,
+            )?;
+        }
+        transaction.commit()
+    }
+
+    pub fn clear(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+        connection.execute(
+            "DELETE FROM initiative_turns WHERE channel_id = $1",
+            &[&channel_id.to_string()],
+        )?;
+        connection.execute(
+            "DELETE FROM initiative_order WHERE channel_id = $1",
+            &[&channel_id.to_string()],
+        )
+    }
+
+    /// Add a combatant to a channel's initiative order after everyone with the same or higher
+    /// initiative, keeping the current turn with the same combatant. Returns false without adding
+    /// them if there's already a combatant with the same name.
+    pub fn insert(
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        entry: &InitiativeEntry,
+    ) -> RusqliteResult<bool> {
+        let order = InitiativeOrder::get(connection, channel_id)?;
+        if order
+            .entries
+            .iter()
+            .any(|existing| existing.name.to_lowercase() == entry.name.to_lowercase())
+        {
+            return Ok(false);
+        }
+        let inserted = order
+            .entries
+            .iter()
+            .take_while(|existing| existing.initiative >= entry.initiative)
+            .count();
+        let channel_id_string = channel_id.to_string();
+        let position = inserted as i64;
+        let transaction = connection.transaction()?;
+        let params: &[&dyn ToSql] = &[&channel_id_string, &position];
+        transaction.execute(
+            "UPDATE initiative_order SET position = position + 1 \
+             WHERE channel_id = $1 \
+             AND position >= $2",
+            params,
+        )?;
+        let params: &[&dyn ToSql] = &[
+            &channel_id_string,
+            &entry.name,
+            &entry.initiative,
+            &position,
+        ];
+        transaction.execute(
+            "INSERT INTO initiative_order (channel_id, name, initiative, position) \
+             VALUES ($1, $2, $3, $4)",
+            params,
+        )?;
+        if let Some(turn) = InitiativeOrder::get_turn(&transaction, channel_id)? {
+            InitiativeOrder::set_turn(&transaction, channel_id, turn.after_insertion(inserted))?;
+        }
+        transaction.commit()?;
+        Ok(true)
+    }
+
+    /// Remove a combatant from a channel's initiative order, keeping the current turn with the
+    /// same combatant. Returns whether the combatant was in the initiative order.
+    pub fn remove(
+        connection: &Connection,
+        channel_id: ChannelId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let order = InitiativeOrder::get(connection, channel_id)?;
+        let removed = match order
+            .entries
+            .iter()
+            .position(|entry| entry.name.to_lowercase() == name.to_lowercase())
+        {
+            Some(removed) => removed,
+            None => return Ok(false),
+        };
+        let channel_id_string = channel_id.to_string();
+        let position = removed as i64;
+        let params: &[&dyn ToSql] = &[&channel_id_string, &position];
+        connection.execute(
+            "DELETE FROM initiative_order WHERE channel_id = $1 AND position = $2",
+            params,
+        )?;
+        connection.execute(
+            "UPDATE initiative_order SET position = position - 1 \
+             WHERE channel_id = $1 \
+             AND position > $2",
+            params,
+        )?;
+        if let Some(turn) = InitiativeOrder::get_turn(connection, channel_id)? {
+            match turn.after_removal(removed, order.entries.len() - 1) {
+                Some(turn) => InitiativeOrder::set_turn(connection, channel_id, turn)?,
+                None => connection.execute(
+                    "DELETE FROM initiative_turns WHERE channel_id = $1",
+                    &[&channel_id_string],
+                )?,
+            };
+        }
+        Ok(true)
+    }
+
+    /// Retrieve the current turn in a channel's encounter, if the first turn has begun.
+    pub fn get_turn(
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<Option<Turn>> {
+        connection
+            .query_row(
+                "SELECT position, round FROM initiative_turns WHERE channel_id = $1",
+                &[&channel_id.to_string()],
+                |row| {
+                    let position: i64 = row.get("position")?;
+                    Ok(Turn {
+                        position: position as usize,
+                        round: row.get("round")?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    pub fn set_turn(
+        connection: &Connection,
+        channel_id: ChannelId,
+        turn: Turn,
+    ) -> RusqliteResult<usize> {
+        let position = turn.position as i64;
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &position, &turn.round];
+        connection.execute(
+            "INSERT INTO initiative_turns (channel_id, position, round) VALUES ($1, $2, $3) \
+             ON CONFLICT (channel_id) DO UPDATE SET position = $2, round = $3",
+            params,
+        )
+    }
+
+    fn entry_from_row(row: &Row) -> RusqliteResult<InitiativeEntry> {
+        let tie: Option<String> = row.get("tie")?;
+        let tie_value: Option<i32> = row.get("tie_value")?;
+        Ok(InitiativeEntry {
+            name: row.get("name")?,
+            initiative: row.get("initiative")?,
+            tie: tie.and_then(|tie| Tie::from_parts(&tie, tie_value)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(name: &str, initiative: i32) -> InitiativeEntry {
+        InitiativeEntry {
+            name: name.to_owned(),
+            initiative,
+            tie: None,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            InitiativeOrder::parse("Alice 18, goblin A 15, Bob 12"),
+            Ok(InitiativeOrder {
+                entries: vec![entry("Alice", 18), entry("goblin A", 15), entry("Bob", 12)]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_sorts_by_initiative() {
+        assert_eq!(
+            InitiativeOrder::parse("Bob: 12; Alice = 18\ngoblin 2 -1\nCarol 12"),
+            Ok(InitiativeOrder {
+                entries: vec![
+                    entry("Alice", 18),
+                    entry("Bob", 12),
+                    entry("Carol", 12),
+                    entry("goblin 2", -1)
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_break_ties() {
+        let order = InitiativeOrder::parse("Alice 12, Bob 15, Carol 12")
+            .map(|order| {
+                order.break_ties(
+                    TieBreak::Dexterity,
+                    |name| if name == "Carol" { Some(16) } else { None },
+                    &mut rand_pcg::Pcg32::new(0, 0),
+                )
+            })
+            .map(|order| order.entries);
+        assert_eq!(
+            order,
+            Ok(vec![
+                entry("Bob", 15),
+                InitiativeEntry {
+                    tie: Some(Tie::Dexterity(16)),
+                    ..entry("Carol", 12)
+                },
+                InitiativeEntry {
+                    tie: Some(Tie::Dexterity(10)),
+                    ..entry("Alice", 12)
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_repeated_name() {
+        assert_eq!(
+            InitiativeOrder::parse("Alice 18, Bob 12, alice 3"),
+            Ok(InitiativeOrder {
+                entries: vec![entry("Bob", 12), entry("alice", 3)]
+            })
+        );
+    }
+
+    #[test]
+    fn test_turn_next() {
+        assert_eq!(
+            Turn::first().next(3),
+            Turn {
+                position: 1,
+                round: 1
+            }
+        );
+        assert_eq!(
+            Turn {
+                position: 2,
+                round: 1
+            }
+            .next(3),
+            Turn {
+                position: 0,
+                round: 2
+            }
+        );
+        // The initiative order may have been shortened since the turn began
+        assert_eq!(
+            Turn {
+                position: 4,
+                round: 3
+            }
+            .next(2),
+            Turn {
+                position: 0,
+                round: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_turn_next_round() {
+        assert_eq!(
+            Turn {
+                position: 1,
+                round: 2
+            }
+            .next_round(),
+            Turn {
+                position: 0,
+                round: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_turn_after_insertion() {
+        let turn = Turn {
+            position: 2,
+            round: 3,
+        };
+        // Adding a combatant before the current turn, or in the same place
+        assert_eq!(
+            turn.after_insertion(0),
+            Turn {
+                position: 3,
+                round: 3
+            }
+        );
+        assert_eq!(
+            turn.after_insertion(2),
+            Turn {
+                position: 3,
+                round: 3
+            }
+        );
+        // Adding a combatant after the current turn
+        assert_eq!(turn.after_insertion(3), turn);
+    }
+
+    #[test]
+    fn test_turn_after_removal() {
+        let turn = Turn {
+            position: 2,
+            round: 3,
+        };
+        // Removing a combatant before the current turn
+        assert_eq!(
+            turn.after_removal(0, 3),
+            Some(Turn {
+                position: 1,
+                round: 3
+            })
+        );
+        // Removing a combatant after the current turn
+        assert_eq!(turn.after_removal(3, 3), Some(turn));
+        // Removing the combatant whose turn it is
+        assert_eq!(
+            turn.after_removal(2, 3),
+            Some(Turn {
+                position: 1,
+                round: 3
+            })
+        );
+        assert_eq!(
+            Turn::first().after_removal(0, 3),
+            Some(Turn {
+                position: 2,
+                round: 0
+            })
+        );
+        assert_eq!(
+            Turn::first().after_removal(0, 3).map(|turn| turn.next(3)),
+            Some(Turn::first())
+        );
+        assert_eq!(Turn::first().after_removal(0, 0), None);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            InitiativeOrder::parse("Alice 18, Bob"),
+            Err(ParserError("Bob".to_owned()))
+        );
+        assert_eq!(
+            InitiativeOrder::parse("Alice 18, 12"),
+            Err(ParserError("12".to_owned()))
+        );
+        assert_eq!(
+            InitiativeOrder::parse(" , "),
+            Err(ParserError(",".to_owned()))
+        );
+    }
+}
@@ -2,17 +2,21 @@ use rusqlite::types::ToSql;
 use rusqlite::Result as RusqliteResult;
 use rusqlite::{Connection, Transaction};
 use serenity::model::channel::Message;
-use serenity::model::id::MessageId;
+use serenity::model::id::{ChannelId, MessageId, UserId};
 use snips_nlu_ontology::{IntentParserResult, Slot, SlotValue};
 
+/// Log the intent a message was parsed as, along with its slots. A message with several commands
+/// is logged once for each of them, with the index of the part of the message it was parsed from.
 pub fn log_intent_result(
     connection: &mut Connection,
     message: &Message,
+    part_index: usize,
     intent_result: &IntentParserResult,
     corrected: Option<&str>,
 ) -> RusqliteResult<()> {
+    let part_index = part_index as i64;
     connection.transaction().and_then(|transaction| {
-        log_message(&transaction, message, intent_result, corrected)
+        log_message(&transaction, message, part_index, intent_result, corrected)
             .and(
                 intent_result
                     .slots
@@ -20,7 +24,13 @@ pub fn log_intent_result(
                     .enumerate()
                     .fold(Ok(()), |result, (index, slot)| {
                         result
-                            .and(log_slot(&transaction, message.id, index as i32, slot))
+                            .and(log_slot(
+                                &transaction,
+                                message.id,
+                                part_index,
+                                index as i32,
+                                slot,
+                            ))
                             .map(|_| ())
                     }),
             )
@@ -31,11 +41,13 @@ pub fn log_intent_result(
 fn log_message(
     transaction: &Transaction,
     message: &Message,
+    part_index: i64,
     intent_result: &IntentParserResult,
     corrected: Option<&str>,
 ) -> RusqliteResult<usize> {
     let params: &[&dyn ToSql] = &[
         &message.id.to_string(),
+        &part_index,
         &message.channel_id.to_string(),
         &message.author.id.to_string(),
         &message.content,
@@ -43,9 +55,10 @@ fn log_message(
         &message.timestamp,
         &intent_result.intent.intent_name,
         &(intent_result.intent.confidence_score as f64),
+        &intent_result.input,
     ];
     transaction.execute(
-        "INSERT INTO messages (message_id, channel_id, user_id, content, corrected_content, posted, intent_name, confidence_score) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        "INSERT INTO messages (message_id, part_index, channel_id, user_id, content, corrected_content, posted, intent_name, confidence_score, parsed_content) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
         params,
     )
 }
@@ -53,23 +66,56 @@ fn log_message(
 fn log_slot(
     transaction: &Transaction,
     message_id: MessageId,
+    part_index: i64,
     index: i32,
     slot: &Slot,
 ) -> RusqliteResult<usize> {
     let params: &[&dyn ToSql] = &[
         &message_id.to_string(),
+        &part_index,
         &index,
         &slot.raw_value,
         &slot_value_to_string(&slot.value),
         &slot.slot_name,
         &slot.confidence_score.map(|v| v as f64),
+        &slot.entity,
     ];
     transaction.execute(
-        "INSERT INTO slots (message_id, slot_index, raw_value, value, slot_name, confidence_score) VALUES ($1, $2, $3, $4, $5, $6)",
+        "INSERT INTO slots (message_id, part_index, slot_index, raw_value, value, slot_name, confidence_score, entity) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
         params
     )
 }
 
+/// Mark a logged message as confirmed, since the user answered "yes" when asked whether the intent
+/// it was parsed as is what they meant. A guess is only made for a message with a single command,
+/// so it's logged as the first part of the message. Returns the number of messages confirmed.
+pub fn confirm_message(connection: &Connection, message_id: MessageId) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&message_id.to_string()];
+    connection.execute(
+        "UPDATE messages SET confirmed = 1 WHERE message_id = $1 AND part_index = 0",
+        params,
+    )
+}
+
+/// Delete the logged messages a user has posted in a channel, along with their slots. Returns the
+/// number of messages deleted.
+pub fn delete_messages(
+    connection: &Connection,
+    channel_id: ChannelId,
+    user_id: UserId,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string()];
+    connection.execute(
+        "DELETE FROM slots WHERE message_id IN \
+         (SELECT message_id FROM messages WHERE channel_id = $1 AND user_id = $2)",
+        params,
+    )?;
+    connection.execute(
+        "DELETE FROM messages WHERE channel_id = $1 AND user_id = $2",
+        params,
+    )
+}
+
 fn slot_value_to_string(slot_value: &SlotValue) -> Option<String> {
     match slot_value {
         SlotValue::Custom(inner_value) => Some(inner_value.value.to_owned()),
@@ -1,33 +1,200 @@
 use crate::attack_roll::{
-    AttackRoll, Handedness, ImprovisedWeaponAttackRoll, UnarmedStrikeAttackRoll, WeaponAttackRoll,
+    AttackCount, AttackRange, AttackRoll, Handedness, ImprovisedWeaponAttackRoll,
+    UnarmedStrikeAttackRoll, WeaponAttackRoll,
 };
-use crate::character::{AbilityName, SkillName};
+use crate::character::{AbilityName, ImportSource, SkillName};
 use crate::character_roll::{CharacterRoll, Check};
+use crate::class::Class;
 use crate::command::{Command, Error};
+use crate::currency::{Amount, Denomination, MAXIMUM_COINS};
 use crate::roll::{Condition, ConditionalRoll};
+use crate::status::Status;
 use crate::weapon::{AmbiguousWeaponName, Classification, WeaponName};
+use regex::Regex;
 use snips_nlu_ontology::{IntentParserResult, Slot, SlotValue};
 use std::convert::TryFrom;
 
+/// The lowest confidence an intent can be parsed with to be run. Below this, the user is asked
+/// whether it's what they meant, instead of risking running the wrong command.
+pub const MINIMUM_CONFIDENCE: f32 = 0.5;
+
 pub fn parse_intent_result(result: &IntentParserResult) -> Result<Command, Error> {
+    parse_intent(result)
+        .and_then(|command| check_confidence(command, result.intent.confidence_score))
+        .or_else(|error| parse_keyword_fallback(&result.input).unwrap_or(Err(error)))
+}
+
+/// Parse the command for the intent found in a message, however confident the intent parser was
+/// in it, such as for a guess that the user has confirmed was what they meant.
+pub fn parse_intent(result: &IntentParserResult) -> Result<Command, Error> {
     let IntentParserResult { intent, slots, .. } = result;
     intent
         .intent_name
         .as_ref()
         .ok_or(Error::NoIntent)
         .and_then(|intent_name| match intent_name.as_ref() {
+            "addStatus" => parse_add_status(&slots),
+            "awardExperience" => parse_award_experience(&slots),
+            "clearInitiativeOrder" => Ok(Command::ClearInitiativeOrder),
+            "copyCharacter" => parse_copy_character(&slots),
+            "createCharacter" => parse_create_character(&slots),
+            "createPregen" => parse_create_pregen(&slots),
+            "disableChannel" => Ok(Command::SetChannelEnabled(false)),
+            "disableDiceOnly" => Ok(Command::SetChannelDiceOnly(false)),
+            "enableChannel" => Ok(Command::SetChannelEnabled(true)),
+            "enableDiceOnly" => Ok(Command::SetChannelDiceOnly(true)),
+            "endEncounter" => Ok(Command::EndEncounter),
+            "exportCharacter" => Ok(Command::ExportCharacter),
+            "gainCurrency" => parse_gain_currency(&slots),
+            "importCharacter" => Ok(Command::ImportCharacter(ImportSource::Attachment)),
+            "lockChannel" => Ok(Command::SetChannelLocked(true)),
+            "nextRound" => Ok(Command::NextRound),
+            "nextTurn" => Ok(Command::NextTurn),
+            "pinQuickReference" => Ok(Command::PinQuickReference),
+            "removeStatus" => parse_remove_status(&slots),
             "rollAbility" => parse_roll_ability(&slots),
             "rollAttack" => parse_roll_attack(&slots),
+            "rollDamage" => parse_roll_damage(&slots),
             "rollDice" => parse_roll_dice(&slots),
+            "rollForTable" => parse_roll_for_table(&slots),
             "rollInitiative" => Ok(parse_roll_initiative(&slots)),
             "rollSavingThrow" => parse_roll_saving_throw(&slots),
+            "rollSneakAttack" => Ok(Command::SneakAttackRoll),
             "rollSkill" => parse_roll_skill(&slots),
+            "rollToHit" => parse_roll_to_hit(&slots),
             "rollUnarmedStrike" => Ok(parse_roll_unarmed_strike(&slots)),
+            "setArmorClass" => parse_set_armor_class(&slots),
+            "setCharacterName" => parse_set_character_name(&slots),
+            "setClass" => parse_set_class(&slots),
+            "showChannelSettings" => Ok(Command::ShowChannelSettings),
+            "showCharacterSheet" => Ok(Command::ShowCharacterSheet),
+            "showCurrency" => Ok(Command::ShowCurrency),
             "showHelp" => Ok(Command::Help),
+            "showInitiativeOrder" => Ok(Command::ShowInitiativeOrder),
+            "showPartyCurrency" => Ok(Command::ShowPartyCurrency),
+            "showSpellSlots" => Ok(Command::ShowSpellSlots),
+            "showStatuses" => Ok(Command::ShowStatuses),
+            "spendCurrency" => parse_spend_currency(&slots),
+            "switchCharacter" => parse_switch_character(&slots),
+            "unlockChannel" => Ok(Command::SetChannelLocked(false)),
             intent_name => Err(Error::UnknownIntent(intent_name.to_owned())),
         })
 }
 
+/// Reject a command that was parsed with too little confidence, asking whether it's what the
+/// user meant.
+fn check_confidence(command: Command, confidence_score: f32) -> Result<Command, Error> {
+    if confidence_score < MINIMUM_CONFIDENCE {
+        Err(Error::LowConfidence(command.description().to_owned()))
+    } else {
+        Ok(command)
+    }
+}
+
+/// Parse the phrases for editing a character that the intent parser often gets wrong, such as
+/// "Bump my dex to 16" or "I'm now level 5", by their keywords. This is only used when the intent
+/// parser doesn't come up with a command, and returns None if the message isn't one of these
+/// phrases.
+pub fn parse_keyword_fallback(input: &str) -> Option<Result<Command, Error>> {
+    lazy_static! {
+        static ref SET_ABILITY_REGEX: Regex = Regex::new(
+            r"^(?i)(?:(?:set|change|bump|raise|increase|lower|drop|reduce)(?: +up)? +)?(?:my +)?(str|strength|dex|dexterity|con|constitution|int|intelligence|wis|wisdom|cha|charisma)(?: +score)?(?: +(?:is +now|is|now|to|up +to|down +to|=))? +(\d+)(?: +now)? *[.!]?$"
+        )
+        .unwrap();
+        static ref SET_LEVEL_REGEX: Regex = Regex::new(
+            r"^(?i)(?:(?:i'?m|i +am)(?: +now)?(?: +at)? +level +(\d+)(?: +now)?|(?:i'?ve +|i +)?(?:just +)?(?:reached|hit|levell?ed +up +to) +level +(\d+)|(?:set +)?my +level +(?:is +(?:now +)?|to +)(\d+)) *[.!]?$"
+        )
+        .unwrap();
+    }
+
+    let input = input.trim();
+    SET_ABILITY_REGEX
+        .captures(input)
+        .and_then(|captures| {
+            let ability = AbilityName::parse(captures.get(1)?.as_str())?;
+            let score = captures.get(2)?.as_str().parse().ok()?;
+            Some(Command::set_ability(ability, score))
+        })
+        .or_else(|| {
+            SET_LEVEL_REGEX
+                .captures(input)
+                .and_then(|captures| {
+                    captures
+                        .get(1)
+                        .or_else(|| captures.get(2))
+                        .or_else(|| captures.get(3))
+                })
+                .and_then(|m| m.as_str().parse().ok())
+                .map(Command::set_level)
+        })
+}
+
+fn parse_amount(
+    slots: &[Slot],
+    missing_amount: Error,
+    missing_denomination: Error,
+) -> Result<Amount, Error> {
+    let count = extract_i32_slot_value(slots, "amount").ok_or(missing_amount)?;
+    let denomination = extract_denomination_slot(slots).ok_or(missing_denomination)?;
+    if count > 0 && count <= MAXIMUM_COINS {
+        Ok(Amount {
+            count,
+            denomination,
+        })
+    } else {
+        Err(Error::CurrencyAmountOutOfRange(count))
+    }
+}
+
+fn parse_add_status(slots: &[Slot]) -> Result<Command, Error> {
+    extract_status_slot(slots)
+        .ok_or(Error::AddStatusMissingStatus)
+        .map(|status| Command::AddStatus(status, None))
+}
+
+fn parse_award_experience(slots: &[Slot]) -> Result<Command, Error> {
+    extract_i32_slot_value(slots, "amount")
+        .ok_or(Error::AwardExperienceMissingAmount)
+        .and_then(|amount| Command::award_experience(amount, None))
+}
+
+fn parse_copy_character(slots: &[Slot]) -> Result<Command, Error> {
+    Command::copy_character(
+        extract_character_name_slot(slots),
+        None,
+        extract_i32_slot_value(slots, "level"),
+    )
+}
+
+fn parse_create_character(slots: &[Slot]) -> Result<Command, Error> {
+    extract_character_name_slot(slots)
+        .ok_or(Error::CreateCharacterMissingName)
+        .map(Command::CreateCharacter)
+}
+
+fn parse_create_pregen(slots: &[Slot]) -> Result<Command, Error> {
+    extract_class_slot(slots)
+        .ok_or(Error::CreatePregenMissingClass)
+        .and_then(|class| {
+            Command::create_pregen(class, extract_i32_slot_value(slots, "level").unwrap_or(1))
+        })
+}
+
+fn parse_gain_currency(slots: &[Slot]) -> Result<Command, Error> {
+    parse_amount(
+        slots,
+        Error::GainCurrencyMissingAmount,
+        Error::GainCurrencyMissingDenomination,
+    )
+    .map(Command::GainCurrency)
+}
+
+fn parse_remove_status(slots: &[Slot]) -> Result<Command, Error> {
+    extract_status_slot(slots)
+        .ok_or(Error::RemoveStatusMissingStatus)
+        .map(Command::RemoveStatus)
+}
+
 fn parse_roll_ability(slots: &[Slot]) -> Result<Command, Error> {
     let ability = extract_ability_slot(slots);
     let condition = extract_condition_slot(slots);
@@ -38,27 +205,59 @@ fn parse_roll_ability(slots: &[Slot]) -> Result<Command, Error> {
                 check: Check::Ability(ability),
                 condition,
             };
-            Command::CharacterRoll(roll)
+            Command::CharacterRoll(roll, None)
         })
 }
 
 fn parse_roll_attack(slots: &[Slot]) -> Result<Command, Error> {
+    parse_attack(slots)
+        .map(|roll| Command::AttackRoll(roll, AttackCount::Times(1), None, None, None))
+}
+
+fn parse_roll_damage(slots: &[Slot]) -> Result<Command, Error> {
+    let critical_hit = extract_critical_hit_slot(slots);
+    parse_attack(slots).map(|roll| Command::DamageRoll(roll, critical_hit))
+}
+
+fn parse_roll_to_hit(slots: &[Slot]) -> Result<Command, Error> {
+    parse_attack(slots).map(|roll| Command::ToHitRoll(roll, None, None))
+}
+
+/// Parse the weapon an attack is made with, and how it's used, which is shared by the intents
+/// that make an attack, or just its attack roll or damage roll.
+fn parse_attack(slots: &[Slot]) -> Result<AttackRoll, Error> {
     let ambiguous_weapon = extract_ambiguous_weapon_slot(slots);
     let classification = extract_classification_slot(slots);
     let condition = extract_condition_slot(slots);
     let handedness = extract_handedness_slot(slots);
     let improvised_weapon = extract_improvised_weapon_slot(slots);
+    let range = extract_range_slot(slots);
     let weapon = extract_weapon_slot(slots);
+    // An attack at a range is a ranged attack, even with a melee weapon that has the thrown property
+    let classification = classification.or(range.map(|_| Classification::Ranged));
+    let condition = range.map_or(condition, |range| range.apply(condition));
     weapon
         .ok_or_else(|| {
-            ambiguous_weapon.map_or(
-                Error::RollAttackMissingWeapon,
+            ambiguous_weapon.map_or_else(
+                || {
+                    Error::RollAttackMissingWeapon(
+                        extract_raw_slot_value(slots, "weapon")
+                            .map_or_else(Vec::new, WeaponName::suggest),
+                    )
+                },
                 Error::RollAttackAmbiguousWeapon,
             )
         })
         .and_then(|weapon| {
             if weapon.to_weapon().versatile.is_some() && handedness.is_none() {
                 Err(Error::RollAttackMissingHandedness)
+            } else if handedness == Some(Handedness::OffHand) && !weapon.to_weapon().light {
+                Err(Error::RollAttackOffHandWeaponNotLight(weapon))
+            } else if range.is_some()
+                && (weapon.to_weapon().range.is_none()
+                    || classification == Some(Classification::Melee))
+            {
+                Err(Error::RollAttackRangeWeaponNotRanged(weapon))
             } else {
                 Ok(AttackRoll::Weapon(WeaponAttackRoll {
                     weapon,
@@ -82,7 +281,6 @@ fn parse_roll_attack(slots: &[Slot]) -> Result<Command, Error> {
                 Err(error)
             }
         })
-        .map(Command::AttackRoll)
 }
 
 fn parse_roll_dice(slots: &[Slot]) -> Result<Command, Error> {
@@ -91,18 +289,27 @@ fn parse_roll_dice(slots: &[Slot]) -> Result<Command, Error> {
     let sides = extract_die_slot(slots);
     sides.ok_or(Error::RollDiceMissingSides).and_then(|sides| {
         ConditionalRoll::new(rolls, sides, 0, condition)
-            .map(Command::Roll)
+            .map(|roll| Command::Roll(roll, None))
             .map_err(|error| Error::RollDiceInvalid(error, rolls, sides))
     })
 }
 
+fn parse_roll_for_table(slots: &[Slot]) -> Result<Command, Error> {
+    let condition = extract_condition_slot(slots);
+    let rolls = extract_usize_slot_value(slots, "rolls").unwrap_or(1);
+    let sides = extract_die_slot(slots).unwrap_or(20);
+    ConditionalRoll::new(rolls, sides, 0, condition)
+        .map(Command::RollForTable)
+        .map_err(|error| Error::RollDiceInvalid(error, rolls, sides))
+}
+
 fn parse_roll_initiative(slots: &[Slot]) -> Command {
     let condition = extract_condition_slot(slots);
     let roll = CharacterRoll {
         check: Check::Initiative,
         condition,
     };
-    Command::CharacterRoll(roll)
+    Command::CharacterRoll(roll, None)
 }
 
 fn parse_roll_saving_throw(slots: &[Slot]) -> Result<Command, Error> {
@@ -115,26 +322,65 @@ fn parse_roll_saving_throw(slots: &[Slot]) -> Result<Command, Error> {
                 check: Check::SavingThrow(ability),
                 condition,
             };
-            Command::CharacterRoll(roll)
+            Command::CharacterRoll(roll, None)
         })
 }
 
 fn parse_roll_skill(slots: &[Slot]) -> Result<Command, Error> {
     let condition = extract_condition_slot(slots);
     let skill = extract_skill_slot(slots);
-    skill.ok_or(Error::RollSkillMissingSkill).map(|skill| {
-        let roll = CharacterRoll {
-            check: Check::Skill(skill),
-            condition,
-        };
-        Command::CharacterRoll(roll)
-    })
+    skill
+        .ok_or_else(|| {
+            Error::RollSkillMissingSkill(
+                extract_raw_slot_value(slots, "skill").map_or_else(Vec::new, SkillName::suggest),
+            )
+        })
+        .map(|skill| {
+            let roll = CharacterRoll {
+                check: Check::Skill(skill),
+                condition,
+            };
+            Command::CharacterRoll(roll, None)
+        })
 }
 
 fn parse_roll_unarmed_strike(slots: &[Slot]) -> Command {
     let condition = extract_condition_slot(slots);
     let roll = AttackRoll::UnarmedStrike(UnarmedStrikeAttackRoll { condition });
-    Command::AttackRoll(roll)
+    Command::AttackRoll(roll, AttackCount::Times(1), None, None, None)
+}
+
+fn parse_set_armor_class(slots: &[Slot]) -> Result<Command, Error> {
+    extract_i32_slot_value(slots, "armor_class")
+        .ok_or(Error::SetArmorClassMissingValue)
+        .and_then(Command::set_armor_class)
+}
+
+fn parse_set_character_name(slots: &[Slot]) -> Result<Command, Error> {
+    extract_character_name_slot(slots)
+        .ok_or(Error::SetCharacterNameMissingName)
+        .and_then(Command::set_character_name)
+}
+
+fn parse_set_class(slots: &[Slot]) -> Result<Command, Error> {
+    extract_class_slot(slots)
+        .ok_or(Error::SetClassMissingClass)
+        .and_then(|class| Command::set_class(class, extract_i32_slot_value(slots, "level"), None))
+}
+
+fn parse_spend_currency(slots: &[Slot]) -> Result<Command, Error> {
+    parse_amount(
+        slots,
+        Error::SpendCurrencyMissingAmount,
+        Error::SpendCurrencyMissingDenomination,
+    )
+    .map(Command::SpendCurrency)
+}
+
+fn parse_switch_character(slots: &[Slot]) -> Result<Command, Error> {
+    extract_character_name_slot(slots)
+        .ok_or(Error::SwitchCharacterMissingName)
+        .map(Command::SwitchCharacter)
 }
 
 fn extract_ability_slot(slots: &[Slot]) -> Option<AbilityName> {
@@ -146,6 +392,12 @@ fn extract_ambiguous_weapon_slot(slots: &[Slot]) -> Option<AmbiguousWeaponName>
         .and_then(|value| AmbiguousWeaponName::parse(value.as_ref()))
 }
 
+fn extract_character_name_slot(slots: &[Slot]) -> Option<String> {
+    find_slot_by_name(slots, "character_name")
+        .map(|slot| slot.raw_value.trim().to_owned())
+        .filter(|name| !name.is_empty())
+}
+
 fn extract_classification_slot(slots: &[Slot]) -> Option<Classification> {
     extract_custom_slot_value(slots, "weapon_classification")
         .and_then(|value| Classification::parse(value.as_ref()))
@@ -159,6 +411,10 @@ fn extract_condition_slot(slots: &[Slot]) -> Option<Condition> {
     })
 }
 
+fn extract_critical_hit_slot(slots: &[Slot]) -> bool {
+    find_slot_by_name(slots, "critical_hit").is_some()
+}
+
 fn extract_custom_slot_value<'a>(slots: &'a [Slot], slot_name: &str) -> Option<&'a String> {
     find_slot_by_name(slots, slot_name).and_then(|slot| match &slot.value {
         SlotValue::Custom(string_value) => Some(&string_value.value),
@@ -166,6 +422,15 @@ fn extract_custom_slot_value<'a>(slots: &'a [Slot], slot_name: &str) -> Option<&
     })
 }
 
+fn extract_class_slot(slots: &[Slot]) -> Option<Class> {
+    extract_custom_slot_value(slots, "class").and_then(|value| Class::parse(value.as_ref()))
+}
+
+fn extract_denomination_slot(slots: &[Slot]) -> Option<Denomination> {
+    extract_custom_slot_value(slots, "denomination")
+        .and_then(|value| Denomination::parse(value.as_ref()))
+}
+
 fn extract_die_slot(slots: &[Slot]) -> Option<i32> {
     extract_custom_slot_value(slots, "die").and_then(|value| match value.as_ref() {
         "d100" => Some(100),
@@ -184,6 +449,10 @@ fn extract_handedness_slot(slots: &[Slot]) -> Option<Handedness> {
         .and_then(|value| Handedness::parse(value.as_ref()))
 }
 
+fn extract_i32_slot_value<'a>(slots: &'a [Slot], slot_name: &str) -> Option<i32> {
+    extract_f64_slot_value(slots, slot_name).and_then(|v| i32::try_from(v as i64).ok())
+}
+
 fn extract_usize_slot_value<'a>(slots: &'a [Slot], slot_name: &str) -> Option<usize> {
     extract_f64_slot_value(slots, slot_name).and_then(|v| usize::try_from(v as i64).ok())
 }
@@ -202,10 +471,23 @@ fn extract_improvised_weapon_slot(slots: &[Slot]) -> bool {
     extract_custom_slot_value(slots, "weapon").map_or(false, |v| v == "improvised weapon")
 }
 
+/// The text of a slot as it was written, such as a weapon name that isn't recognised.
+fn extract_raw_slot_value<'a>(slots: &'a [Slot], slot_name: &str) -> Option<&'a str> {
+    find_slot_by_name(slots, slot_name).map(|slot| slot.raw_value.as_str())
+}
+
+fn extract_range_slot(slots: &[Slot]) -> Option<AttackRange> {
+    extract_custom_slot_value(slots, "range").and_then(|value| AttackRange::parse(value.as_ref()))
+}
+
 fn extract_skill_slot(slots: &[Slot]) -> Option<SkillName> {
     extract_custom_slot_value(slots, "skill").and_then(|value| SkillName::parse(value.as_ref()))
 }
 
+fn extract_status_slot(slots: &[Slot]) -> Option<Status> {
+    extract_custom_slot_value(slots, "status").and_then(|value| Status::parse(value.as_ref()))
+}
+
 fn extract_weapon_slot(slots: &[Slot]) -> Option<WeaponName> {
     extract_custom_slot_value(slots, "weapon").and_then(|value| WeaponName::parse(value.as_ref()))
 }
@@ -213,3 +495,64 @@ fn extract_weapon_slot(slots: &[Slot]) -> Option<WeaponName> {
 fn find_slot_by_name<'a>(slots: &'a [Slot], slot_name: &str) -> Option<&'a Slot> {
     slots.iter().find(|slot| slot.slot_name == slot_name)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fallback(input: &str) -> Option<String> {
+        match parse_keyword_fallback(input) {
+            Some(Ok(Command::SetAbility(ability, score))) => {
+                Some(format!("{} {}", ability.as_str(), score))
+            }
+            Some(Ok(Command::SetLevel(level))) => Some(format!("level {}", level)),
+            Some(Err(_)) => Some("error".to_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_parse_keyword_fallback_ability() {
+        assert_eq!(
+            fallback("bump my dex to 16"),
+            Some("Dexterity 16".to_owned())
+        );
+        assert_eq!(
+            fallback("My Strength score is now 18."),
+            Some("Strength 18".to_owned())
+        );
+        assert_eq!(fallback("set wis to 14"), Some("Wisdom 14".to_owned()));
+        assert_eq!(fallback("raise my con to 40"), Some("error".to_owned()));
+        assert_eq!(fallback("bump my luck to 16"), None);
+    }
+
+    #[test]
+    fn test_check_confidence() {
+        let confidence =
+            |confidence_score| match check_confidence(Command::ShowCurrency, confidence_score) {
+                Ok(Command::ShowCurrency) => "show currency".to_owned(),
+                Err(Error::LowConfidence(description)) => description,
+                _ => "other".to_owned(),
+            };
+        assert_eq!(confidence(0.9), "show currency");
+        assert_eq!(confidence(MINIMUM_CONFIDENCE), "show currency");
+        assert_eq!(confidence(0.2), Command::ShowCurrency.description());
+        assert_eq!(
+            Error::LowConfidence(Command::ShowCurrency.description().to_owned())
+                .to_string()
+                .contains('`'),
+            false
+        );
+    }
+
+    #[test]
+    fn test_parse_keyword_fallback_level() {
+        assert_eq!(fallback("I'm now level 5"), Some("level 5".to_owned()));
+        assert_eq!(fallback("i am level 3 now!"), Some("level 3".to_owned()));
+        assert_eq!(fallback("I just hit level 7"), Some("level 7".to_owned()));
+        assert_eq!(fallback("I've reached level 7"), Some("level 7".to_owned()));
+        assert_eq!(fallback("set my level to 4"), Some("level 4".to_owned()));
+        assert_eq!(fallback("I'm now level 25"), Some("error".to_owned()));
+        assert_eq!(fallback("I'm 5"), None);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::character::SkillName;
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::id::ChannelId;
+
+/// Something the characters might know about a subject, kept hidden by the DM until it's
+/// revealed, such as "The Shattered Crown DC 15 History: It was broken at the Battle of Three
+/// Rivers". Lore with a check is revealed when a character succeeds on that check, and any lore
+/// can be revealed by the DM.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lore {
+    pub subject: String,
+    /// What kind of thing the subject is, if the DM has said.
+    pub kind: Option<LoreKind>,
+    /// The check a character must succeed on to recall the lore, if it can be recalled.
+    pub check: Option<RecallCheck>,
+    pub text: String,
+    /// Whether the lore has been recalled or revealed, so that the players can see it.
+    pub revealed: bool,
+}
+
+/// The kinds of subject the DM can attach lore to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LoreKind {
+    Npc,
+    Quest,
+    Location,
+}
+
+/// A skill check that recalls lore, such as "DC 15 History".
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RecallCheck {
+    pub skill: SkillName,
+    pub dc: i32,
+}
+
+impl Lore {
+    /// Parse lore from a subject, an optional kind, an optional DC and skill, and text, such as
+    /// "The Shattered Crown DC 15 History: It was broken at the Battle of Three Rivers", or
+    /// "Mayor Thorne (NPC): He's secretly a cultist".
+    pub fn parse(string: &str) -> Option<Lore> {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)(.+?)(?: +\(([a-z]+)\))?(?: +dc +(\d+) +([a-z ]+?))? *: *(.+)$")
+                    .unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        let subject = subject_key(captures.get(1)?.as_str());
+        let kind = match captures.get(2) {
+            Some(m) => Some(LoreKind::parse(m.as_str())?),
+            None => None,
+        };
+        let check = match (captures.get(3), captures.get(4)) {
+            (Some(dc), Some(skill)) => Some(RecallCheck {
+                skill: SkillName::parse(skill.as_str().trim())?,
+                dc: dc
+                    .as_str()
+                    .parse()
+                    .ok()
+                    .filter(|dc| *dc >= 1 && *dc <= 30)?,
+            }),
+            _ => None,
+        };
+        let text = captures.get(5)?.as_str().trim();
+        if subject.is_empty() || text.is_empty() {
+            None
+        } else {
+            Some(Lore {
+                subject: subject.to_owned(),
+                kind,
+                check,
+                text: text.to_owned(),
+                revealed: false,
+            })
+        }
+    }
+
+    /// Retrieve the lore about a subject, whether or not it has been revealed.
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        subject: &str,
+    ) -> RusqliteResult<Option<Lore>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &subject_key(subject)];
+        connection
+            .query_row(
+                "SELECT subject, kind, skill, dc, text, revealed FROM lore \
+                 WHERE channel_id = $1 \
+                 AND subject = $2",
+                params,
+                Lore::from_row,
+            )
+            .optional()
+    }
+
+    /// Retrieve the lore for a channel, grouped by kind and in alphabetical order of subject,
+    /// leaving out the lore that hasn't been revealed unless it's asked for.
+    pub fn get_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+        include_hidden: bool,
+    ) -> RusqliteResult<Vec<Lore>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &include_hidden];
+        let mut statement = connection.prepare(
+            "SELECT subject, kind, skill, dc, text, revealed FROM lore \
+             WHERE channel_id = $1 \
+             AND (revealed OR $2) \
+             ORDER BY kind, subject",
+        )?;
+        let rows = statement.query_map(params, Lore::from_row)?;
+        rows.collect()
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<Lore> {
+        let kind: Option<String> = row.get("kind")?;
+        let skill: Option<String> = row.get("skill")?;
+        let dc: Option<i32> = row.get("dc")?;
+        Ok(Lore {
+            subject: row.get("subject")?,
+            kind: kind.as_deref().and_then(LoreKind::parse),
+            check: match (skill.as_deref().and_then(SkillName::parse), dc) {
+                (Some(skill), Some(dc)) => Some(RecallCheck { skill, dc }),
+                _ => None,
+            },
+            text: row.get("text")?,
+            revealed: row.get("revealed")?,
+        })
+    }
+
+    /// Save the lore for a channel, replacing any lore about the same subject. Replacing lore
+    /// that has already been revealed leaves it revealed.
+    pub fn set(&self, connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &self.subject,
+            &self.kind.map(|kind| kind.as_str()),
+            &self.check.map(|check| check.skill.as_str().to_owned()),
+            &self.check.map(|check| check.dc),
+            &self.text,
+            &self.revealed,
+        ];
+        connection.execute(
+            "INSERT INTO lore (channel_id, subject, kind, skill, dc, text, revealed) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) \
+             ON CONFLICT (channel_id, subject) DO UPDATE SET \
+             subject = $2, kind = $3, skill = $4, dc = $5, text = $6",
+            params,
+        )
+    }
+
+    /// Mark the lore about a subject as revealed, so that the players can see it. Returns the
+    /// number of pieces of lore revealed.
+    pub fn reveal(
+        connection: &Connection,
+        channel_id: ChannelId,
+        subject: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &subject_key(subject)];
+        connection.execute(
+            "UPDATE lore SET revealed = 1 WHERE channel_id = $1 AND subject = $2",
+            params,
+        )
+    }
+
+    /// Remove the lore about a subject. Returns the number of pieces of lore removed.
+    pub fn remove(
+        connection: &Connection,
+        channel_id: ChannelId,
+        subject: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &subject_key(subject)];
+        connection.execute(
+            "DELETE FROM lore WHERE channel_id = $1 AND subject = $2",
+            params,
+        )
+    }
+}
+
+impl LoreKind {
+    pub fn parse(string: &str) -> Option<LoreKind> {
+        match string.trim().to_lowercase().as_str() {
+            "npc" | "person" => Some(LoreKind::Npc),
+            "quest" => Some(LoreKind::Quest),
+            "location" | "place" => Some(LoreKind::Location),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LoreKind::Npc => "NPC",
+            LoreKind::Quest => "Quest",
+            LoreKind::Location => "Location",
+        }
+    }
+}
+
+/// The subject that lore is stored under, without a leading "the", so that "the Shattered Crown"
+/// and "Shattered Crown" are the same subject.
+fn subject_key(subject: &str) -> &str {
+    let subject = subject.trim();
+    match subject.get(..4) {
+        Some(the) if the.eq_ignore_ascii_case("the ") => subject[4..].trim(),
+        _ => subject,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Lore::parse(
+                "The Shattered Crown DC 15 History: It was broken at the Battle of Three Rivers."
+            ),
+            Some(Lore {
+                subject: "Shattered Crown".to_owned(),
+                kind: None,
+                check: Some(RecallCheck {
+                    skill: SkillName::History,
+                    dc: 15
+                }),
+                text: "It was broken at the Battle of Three Rivers.".to_owned(),
+                revealed: false,
+            })
+        );
+        assert_eq!(
+            Lore::parse("Owlbears dc 12 animal handling: They can't be tamed")
+                .and_then(|lore| lore.check)
+                .map(|check| check.skill),
+            Some(SkillName::AnimalHandling)
+        );
+        assert_eq!(
+            Lore::parse("Shattered Crown DC 15 Cooking: Delicious"),
+            None
+        );
+        assert_eq!(Lore::parse("Shattered Crown DC 15 History:"), None);
+    }
+
+    #[test]
+    fn test_parse_hidden_note() {
+        assert_eq!(
+            Lore::parse("Mayor Thorne (NPC): He's secretly a cultist"),
+            Some(Lore {
+                subject: "Mayor Thorne".to_owned(),
+                kind: Some(LoreKind::Npc),
+                check: None,
+                text: "He's secretly a cultist".to_owned(),
+                revealed: false,
+            })
+        );
+        assert_eq!(
+            Lore::parse("The Sunken Temple (location) DC 13 Religion: It was built for Umberlee")
+                .map(|lore| (lore.kind, lore.check.map(|check| check.dc))),
+            Some((Some(LoreKind::Location), Some(13)))
+        );
+        assert_eq!(Lore::parse("Mayor Thorne (villain): Evil"), None);
+    }
+
+    #[test]
+    fn test_subject_key() {
+        assert_eq!(subject_key("the Shattered Crown"), "Shattered Crown");
+        assert_eq!(subject_key("Theodric"), "Theodric");
+    }
+}
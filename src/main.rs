@@ -4,26 +4,80 @@ extern crate log;
 extern crate symspell;
 
 mod attack_roll;
+mod audit;
+mod bestiary;
+mod bonus;
 mod channel;
+mod channel_export;
 mod character;
+mod character_effect;
 mod character_roll;
+mod character_sheet;
+mod class;
+mod combatant;
 mod command;
+mod command_role;
+mod companion;
+mod content;
+mod content_pack;
+mod counter;
+mod currency;
+mod delivery;
+mod dndbeyond;
+mod dungeon;
+mod edition;
+mod encounter;
 mod error;
 mod event_handler;
+mod experience;
+mod follow_up;
+mod guild;
+mod hidden_roll;
+mod house_rule;
+mod initiative;
 mod intent_logger;
 mod intent_parser;
+mod lore;
+mod monster;
+mod note;
+mod ongoing_effect;
+mod passive;
+mod permission_check;
+mod preset;
+mod purge;
+mod purpose;
+mod quick_reference;
+mod random_table;
 mod response;
 mod roll;
+mod roll_history;
+mod roll_signature;
+mod settlement;
+mod spell;
+mod stash;
+mod status;
+mod suggestion;
+mod tie_break;
+mod time;
+mod training_data;
+mod travel;
+mod treasure;
 mod weapon;
+mod weather;
+mod wild_shape;
 
+use crate::delivery::DeliveryMetrics;
 use crate::event_handler::Handler;
-use log::error;
+use crate::roll_signature::RollSigner;
+use log::{error, info, warn};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
 use serenity::prelude::Client;
 use snips_nlu_lib::SnipsNluEngine;
+use std::collections::HashMap;
 use std::env;
-use std::sync::RwLock;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Mutex, RwLock};
 use symspell::{SymSpell, UnicodeStringStrategy};
 
 fn main() {
@@ -31,29 +85,78 @@ fn main() {
 
     let database_path =
         env::var("DATABASE_PATH").expect("Expected a database path in the environment");
+
+    let manager = SqliteConnectionManager::file(database_path);
+
+    let pool = Pool::new(manager).expect("Error creating connection pool");
+
+    // Export the training data instead of connecting to Discord, so that it can be exported on a
+    // schedule, such as by a nightly cron job.
+    if env::args().nth(1).as_deref() == Some("export-training-data") {
+        let connection = pool.get().expect("Error obtaining database connection");
+        let (count, json) =
+            training_data::export(&connection).expect("Error exporting training data");
+        println!("{}", json);
+        info!(target: "dungeon-helper", "Exported {} utterances of training data.", count);
+        return;
+    }
+
+    if let Err(error) = weapon::check_content()
+        .and_then(|_| spell::check_content())
+        .and_then(|_| bestiary::check_content())
+    {
+        error!(target: "dungeon-helper", "Error loading content: {}", error);
+        return;
+    }
+
     let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
-    let model_path = env::var("MODEL_PATH").expect("Expected a model path in the environment");
     let dictionary_path =
         env::var("DICTIONARY_PATH").expect("Expected a dictionary path in the environment");
     let bigram_dictionary_path = env::var("BIGRAM_DICTIONARY_PATH")
         .expect("Expected a bigram dictionary path in the environment");
 
-    let engine = SnipsNluEngine::from_path(model_path).unwrap();
-
-    let manager = SqliteConnectionManager::file(database_path);
-
-    let pool = Pool::new(manager).expect("Error creating connection pool");
+    // Without the intent parser's model, natural language commands fall back to a few regular
+    // expressions, so that the bot is still usable
+    let model_path = env::var("MODEL_PATH").ok();
+    let engine = match model_path.as_ref() {
+        Some(model_path) => match SnipsNluEngine::from_path(model_path) {
+            Ok(engine) => Some(engine),
+            Err(error) => {
+                error!(target: "dungeon-helper", "Error loading the intent parser's model, falling back to rule-based parsing: {}", error);
+                None
+            }
+        },
+        None => {
+            warn!(target: "dungeon-helper", "No model path in the environment. Natural language commands will use rule-based parsing.");
+            None
+        }
+    };
 
     let mut symspell: SymSpell<UnicodeStringStrategy> = SymSpell::default();
 
     symspell.load_dictionary(&dictionary_path, 0, 1, " ");
     symspell.load_bigram_dictionary(&bigram_dictionary_path, 0, 2, " ");
 
+    let roll_signer = match env::var("ROLL_SIGNING_SECRET") {
+        Ok(secret) => RollSigner::new(secret.as_bytes()),
+        Err(_) => {
+            warn!(target: "dungeon-helper", "No roll signing secret in the environment. Roll codes won't be verifiable after a restart.");
+            RollSigner::random()
+        }
+    };
+
     let handler = Handler {
-        bot_id: RwLock::new(None),
-        engine,
+        bot_id: AtomicU64::new(0),
+        owner_id: AtomicU64::new(0),
+        engine: RwLock::new(engine),
+        model_path,
         pool,
         symspell,
+        roll_signer,
+        channel_disabled_notices: Mutex::new(HashMap::new()),
+        recent_commands: Mutex::new(HashMap::new()),
+        conversations: Mutex::new(HashMap::new()),
+        delivery_metrics: DeliveryMetrics::default(),
     };
 
     let mut client = Client::new(&token, handler).expect("Error creating Discord client");
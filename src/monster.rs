@@ -0,0 +1,1059 @@
+use crate::roll::Roll;
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::id::ChannelId;
+use std::fmt;
+
+/// The experience points for defeating a monster of each challenge rating from 1 to 30.
+const EXPERIENCE_BY_CHALLENGE_RATING: [i32; 30] = [
+    200, 450, 700, 1100, 1800, 2300, 2900, 3900, 5000, 5900, 7200, 8400, 10000, 11500, 13000,
+    15000, 18000, 20000, 22000, 25000, 33000, 41000, 50000, 62000, 75000, 90000, 105000, 120000,
+    135000, 155000,
+];
+
+/// The Monster Statistics by Challenge Rating table from the Dungeon Master's Guide, for each
+/// challenge rating from 0 to 30.
+const MONSTER_STATISTICS: [MonsterStatistics; 34] = [
+    // CR 0
+    MonsterStatistics {
+        proficiency_bonus: 2,
+        armor_class: 13,
+        hit_points: (1, 6),
+        attack_bonus: 3,
+        damage_per_round: (0, 1),
+        save_dc: 13,
+    },
+    // CR 1/8
+    MonsterStatistics {
+        proficiency_bonus: 2,
+        armor_class: 13,
+        hit_points: (7, 35),
+        attack_bonus: 3,
+        damage_per_round: (2, 3),
+        save_dc: 13,
+    },
+    // CR 1/4
+    MonsterStatistics {
+        proficiency_bonus: 2,
+        armor_class: 13,
+        hit_points: (36, 49),
+        attack_bonus: 3,
+        damage_per_round: (4, 5),
+        save_dc: 13,
+    },
+    // CR 1/2
+    MonsterStatistics {
+        proficiency_bonus: 2,
+        armor_class: 13,
+        hit_points: (50, 70),
+        attack_bonus: 3,
+        damage_per_round: (6, 8),
+        save_dc: 13,
+    },
+    // CR 1
+    MonsterStatistics {
+        proficiency_bonus: 2,
+        armor_class: 13,
+        hit_points: (71, 85),
+        attack_bonus: 3,
+        damage_per_round: (9, 14),
+        save_dc: 13,
+    },
+    // CR 2
+    MonsterStatistics {
+        proficiency_bonus: 2,
+        armor_class: 13,
+        hit_points: (86, 100),
+        attack_bonus: 3,
+        damage_per_round: (15, 20),
+        save_dc: 13,
+    },
+    // CR 3
+    MonsterStatistics {
+        proficiency_bonus: 2,
+        armor_class: 13,
+        hit_points: (101, 115),
+        attack_bonus: 4,
+        damage_per_round: (21, 26),
+        save_dc: 13,
+    },
+    // CR 4
+    MonsterStatistics {
+        proficiency_bonus: 2,
+        armor_class: 14,
+        hit_points: (116, 130),
+        attack_bonus: 5,
+        damage_per_round: (27, 32),
+        save_dc: 14,
+    },
+    // CR 5
+    MonsterStatistics {
+        proficiency_bonus: 3,
+        armor_class: 15,
+        hit_points: (131, 145),
+        attack_bonus: 6,
+        damage_per_round: (33, 38),
+        save_dc: 15,
+    },
+    // CR 6
+    MonsterStatistics {
+        proficiency_bonus: 3,
+        armor_class: 15,
+        hit_points: (146, 160),
+        attack_bonus: 6,
+        damage_per_round: (39, 44),
+        save_dc: 15,
+    },
+    // CR 7
+    MonsterStatistics {
+        proficiency_bonus: 3,
+        armor_class: 15,
+        hit_points: (161, 175),
+        attack_bonus: 6,
+        damage_per_round: (45, 50),
+        save_dc: 15,
+    },
+    // CR 8
+    MonsterStatistics {
+        proficiency_bonus: 3,
+        armor_class: 16,
+        hit_points: (176, 190),
+        attack_bonus: 7,
+        damage_per_round: (51, 56),
+        save_dc: 16,
+    },
+    // CR 9
+    MonsterStatistics {
+        proficiency_bonus: 4,
+        armor_class: 16,
+        hit_points: (191, 205),
+        attack_bonus: 7,
+        damage_per_round: (57, 62),
+        save_dc: 16,
+    },
+    // CR 10
+    MonsterStatistics {
+        proficiency_bonus: 4,
+        armor_class: 17,
+        hit_points: (206, 220),
+        attack_bonus: 7,
+        damage_per_round: (63, 68),
+        save_dc: 16,
+    },
+    // CR 11
+    MonsterStatistics {
+        proficiency_bonus: 4,
+        armor_class: 17,
+        hit_points: (221, 235),
+        attack_bonus: 8,
+        damage_per_round: (69, 74),
+        save_dc: 17,
+    },
+    // CR 12
+    MonsterStatistics {
+        proficiency_bonus: 4,
+        armor_class: 17,
+        hit_points: (236, 250),
+        attack_bonus: 8,
+        damage_per_round: (75, 80),
+        save_dc: 17,
+    },
+    // CR 13
+    MonsterStatistics {
+        proficiency_bonus: 5,
+        armor_class: 18,
+        hit_points: (251, 265),
+        attack_bonus: 8,
+        damage_per_round: (81, 86),
+        save_dc: 18,
+    },
+    // CR 14
+    MonsterStatistics {
+        proficiency_bonus: 5,
+        armor_class: 18,
+        hit_points: (266, 280),
+        attack_bonus: 8,
+        damage_per_round: (87, 92),
+        save_dc: 18,
+    },
+    // CR 15
+    MonsterStatistics {
+        proficiency_bonus: 5,
+        armor_class: 18,
+        hit_points: (281, 295),
+        attack_bonus: 8,
+        damage_per_round: (93, 98),
+        save_dc: 18,
+    },
+    // CR 16
+    MonsterStatistics {
+        proficiency_bonus: 5,
+        armor_class: 18,
+        hit_points: (296, 310),
+        attack_bonus: 9,
+        damage_per_round: (99, 104),
+        save_dc: 18,
+    },
+    // CR 17
+    MonsterStatistics {
+        proficiency_bonus: 6,
+        armor_class: 19,
+        hit_points: (311, 325),
+        attack_bonus: 10,
+        damage_per_round: (105, 110),
+        save_dc: 19,
+    },
+    // CR 18
+    MonsterStatistics {
+        proficiency_bonus: 6,
+        armor_class: 19,
+        hit_points: (326, 340),
+        attack_bonus: 10,
+        damage_per_round: (111, 116),
+        save_dc: 19,
+    },
+    // CR 19
+    MonsterStatistics {
+        proficiency_bonus: 6,
+        armor_class: 19,
+        hit_points: (341, 355),
+        attack_bonus: 10,
+        damage_per_round: (117, 122),
+        save_dc: 19,
+    },
+    // CR 20
+    MonsterStatistics {
+        proficiency_bonus: 6,
+        armor_class: 19,
+        hit_points: (356, 400),
+        attack_bonus: 10,
+        damage_per_round: (123, 140),
+        save_dc: 19,
+    },
+    // CR 21
+    MonsterStatistics {
+        proficiency_bonus: 7,
+        armor_class: 19,
+        hit_points: (401, 445),
+        attack_bonus: 11,
+        damage_per_round: (141, 158),
+        save_dc: 20,
+    },
+    // CR 22
+    MonsterStatistics {
+        proficiency_bonus: 7,
+        armor_class: 19,
+        hit_points: (446, 490),
+        attack_bonus: 11,
+        damage_per_round: (159, 176),
+        save_dc: 20,
+    },
+    // CR 23
+    MonsterStatistics {
+        proficiency_bonus: 7,
+        armor_class: 19,
+        hit_points: (491, 535),
+        attack_bonus: 11,
+        damage_per_round: (177, 194),
+        save_dc: 20,
+    },
+    // CR 24
+    MonsterStatistics {
+        proficiency_bonus: 7,
+        armor_class: 19,
+        hit_points: (536, 580),
+        attack_bonus: 12,
+        damage_per_round: (195, 212),
+        save_dc: 21,
+    },
+    // CR 25
+    MonsterStatistics {
+        proficiency_bonus: 8,
+        armor_class: 19,
+        hit_points: (581, 625),
+        attack_bonus: 12,
+        damage_per_round: (213, 230),
+        save_dc: 21,
+    },
+    // CR 26
+    MonsterStatistics {
+        proficiency_bonus: 8,
+        armor_class: 19,
+        hit_points: (626, 670),
+        attack_bonus: 12,
+        damage_per_round: (231, 248),
+        save_dc: 22,
+    },
+    // CR 27
+    MonsterStatistics {
+        proficiency_bonus: 8,
+        armor_class: 19,
+        hit_points: (671, 715),
+        attack_bonus: 13,
+        damage_per_round: (249, 266),
+        save_dc: 22,
+    },
+    // CR 28
+    MonsterStatistics {
+        proficiency_bonus: 8,
+        armor_class: 19,
+        hit_points: (716, 760),
+        attack_bonus: 13,
+        damage_per_round: (267, 284),
+        save_dc: 22,
+    },
+    // CR 29
+    MonsterStatistics {
+        proficiency_bonus: 9,
+        armor_class: 19,
+        hit_points: (761, 805),
+        attack_bonus: 13,
+        damage_per_round: (285, 302),
+        save_dc: 23,
+    },
+    // CR 30
+    MonsterStatistics {
+        proficiency_bonus: 9,
+        armor_class: 19,
+        hit_points: (806, 850),
+        attack_bonus: 14,
+        damage_per_round: (303, 320),
+        save_dc: 23,
+    },
+];
+
+/// Common monsters from the SRD and their challenge ratings, so that they can be named instead of
+/// giving a challenge rating.
+const MONSTERS: [(&str, ChallengeRating); 48] = [
+    ("acolyte", ChallengeRating::Quarter),
+    ("adult red dragon", ChallengeRating::Whole(17)),
+    ("bandit", ChallengeRating::Eighth),
+    ("bandit captain", ChallengeRating::Whole(2)),
+    ("basilisk", ChallengeRating::Whole(3)),
+    ("bugbear", ChallengeRating::Whole(1)),
+    ("bullywug", ChallengeRating::Quarter),
+    ("cultist", ChallengeRating::Eighth),
+    ("dire wolf", ChallengeRating::Whole(1)),
+    ("doppelganger", ChallengeRating::Whole(3)),
+    ("drow", ChallengeRating::Quarter),
+    ("gargoyle", ChallengeRating::Whole(2)),
+    ("gelatinous cube", ChallengeRating::Whole(2)),
+    ("ghast", ChallengeRating::Whole(2)),
+    ("ghoul", ChallengeRating::Whole(1)),
+    ("giant rat", ChallengeRating::Eighth),
+    ("giant spider", ChallengeRating::Whole(1)),
+    ("gnoll", ChallengeRating::Half),
+    ("goblin", ChallengeRating::Quarter),
+    ("goblin boss", ChallengeRating::Whole(1)),
+    ("guard", ChallengeRating::Eighth),
+    ("harpy", ChallengeRating::Whole(1)),
+    ("hill giant", ChallengeRating::Whole(5)),
+    ("hobgoblin", ChallengeRating::Half),
+    ("kobold", ChallengeRating::Eighth),
+    ("lich", ChallengeRating::Whole(21)),
+    ("lizardfolk", ChallengeRating::Half),
+    ("mage", ChallengeRating::Whole(6)),
+    ("manticore", ChallengeRating::Whole(3)),
+    ("mimic", ChallengeRating::Whole(2)),
+    ("minotaur", ChallengeRating::Whole(3)),
+    ("mummy", ChallengeRating::Whole(3)),
+    ("ogre", ChallengeRating::Whole(2)),
+    ("orc", ChallengeRating::Half),
+    ("owlbear", ChallengeRating::Whole(3)),
+    ("rat", ChallengeRating::Zero),
+    ("skeleton", ChallengeRating::Quarter),
+    ("stirge", ChallengeRating::Eighth),
+    ("thug", ChallengeRating::Half),
+    ("troll", ChallengeRating::Whole(5)),
+    ("vampire", ChallengeRating::Whole(13)),
+    ("vampire spawn", ChallengeRating::Whole(5)),
+    ("veteran", ChallengeRating::Whole(3)),
+    ("wight", ChallengeRating::Whole(3)),
+    ("wolf", ChallengeRating::Quarter),
+    ("worg", ChallengeRating::Half),
+    ("young green dragon", ChallengeRating::Whole(8)),
+    ("zombie", ChallengeRating::Quarter),
+];
+
+/// The expected statistics for a monster of a challenge rating. Ranges are inclusive.
+struct MonsterStatistics {
+    proficiency_bonus: i32,
+    armor_class: i32,
+    hit_points: (i32, i32),
+    attack_bonus: i32,
+    damage_per_round: (i32, i32),
+    save_dc: i32,
+}
+
+/// A monster's challenge rating, from 0 to 30. Ratings below 1 are fractions.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum ChallengeRating {
+    Zero,
+    Eighth,
+    Quarter,
+    Half,
+    Whole(i32),
+}
+
+impl ChallengeRating {
+    /// Parse a challenge rating such as "3", "1/4" or "0.5".
+    pub fn parse(string: &str) -> Option<ChallengeRating> {
+        match string.trim() {
+            "0" => Some(ChallengeRating::Zero),
+            "1/8" | "0.125" => Some(ChallengeRating::Eighth),
+            "1/4" | "0.25" => Some(ChallengeRating::Quarter),
+            "1/2" | "0.5" => Some(ChallengeRating::Half),
+            string => string
+                .parse()
+                .ok()
+                .filter(|rating| *rating >= 1 && *rating <= 30)
+                .map(ChallengeRating::Whole),
+        }
+    }
+
+    /// The position of the challenge rating in the Monster Statistics by Challenge Rating table.
+    fn index(self) -> usize {
+        match self {
+            ChallengeRating::Zero => 0,
+            ChallengeRating::Eighth => 1,
+            ChallengeRating::Quarter => 2,
+            ChallengeRating::Half => 3,
+            ChallengeRating::Whole(rating) => rating as usize + 3,
+        }
+    }
+
+    fn from_index(index: usize) -> ChallengeRating {
+        match index {
+            0 => ChallengeRating::Zero,
+            1 => ChallengeRating::Eighth,
+            2 => ChallengeRating::Quarter,
+            3 => ChallengeRating::Half,
+            index => ChallengeRating::Whole(index.min(MONSTER_STATISTICS.len() - 1) as i32 - 3),
+        }
+    }
+
+    fn statistics(self) -> &'static MonsterStatistics {
+        &MONSTER_STATISTICS[self.index()]
+    }
+
+    /// Estimate the challenge rating of a monster from its statistics, by averaging its defensive
+    /// and offensive challenge ratings as described in the Dungeon Master's Guide. Every two points
+    /// of armour class or attack bonus above or below what's expected moves the rating by one.
+    pub fn estimate(
+        armor_class: i32,
+        hit_points: i32,
+        attack_bonus: i32,
+        damage_per_round: i32,
+    ) -> ChallengeRating {
+        let last = MONSTER_STATISTICS.len() as i32 - 1;
+        let defensive = MONSTER_STATISTICS
+            .iter()
+            .position(|statistics| hit_points <= statistics.hit_points.1)
+            .map_or(last, |index| index as i32);
+        let defensive =
+            defensive + (armor_class - MONSTER_STATISTICS[defensive as usize].armor_class) / 2;
+        let offensive = MONSTER_STATISTICS
+            .iter()
+            .position(|statistics| damage_per_round <= statistics.damage_per_round.1)
+            .map_or(last, |index| index as i32);
+        let offensive =
+            offensive + (attack_bonus - MONSTER_STATISTICS[offensive as usize].attack_bonus) / 2;
+        let average = (defensive.max(0).min(last) + offensive.max(0).min(last) + 1) / 2;
+        ChallengeRating::from_index(average as usize)
+    }
+
+    /// The experience points for defeating a monster with this challenge rating.
+    pub fn experience(self) -> i32 {
+        match self {
+            ChallengeRating::Zero => 10,
+            ChallengeRating::Eighth => 25,
+            ChallengeRating::Quarter => 50,
+            ChallengeRating::Half => 100,
+            ChallengeRating::Whole(rating) => EXPERIENCE_BY_CHALLENGE_RATING[rating as usize - 1],
+        }
+    }
+}
+
+impl fmt::Display for ChallengeRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChallengeRating::Zero => write!(f, "0"),
+            ChallengeRating::Eighth => write!(f, "1/8"),
+            ChallengeRating::Quarter => write!(f, "1/4"),
+            ChallengeRating::Half => write!(f, "1/2"),
+            ChallengeRating::Whole(rating) => rating.fmt(f),
+        }
+    }
+}
+
+/// The role an improvised monster plays in combat, which trades some of its statistics off against
+/// others while keeping roughly the same challenge rating.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MonsterRole {
+    /// Average in every statistic.
+    Standard,
+    /// More hit points and damage, but easier to hit and less accurate.
+    Brute,
+    /// Harder to hit and has more hit points, but deals less damage.
+    Defender,
+    /// More accurate and deals more damage, but has fewer hit points.
+    Striker,
+}
+
+impl MonsterRole {
+    pub fn parse(string: &str) -> Option<MonsterRole> {
+        match string.trim().to_lowercase().as_ref() {
+            "monster" | "creature" | "standard" => Some(MonsterRole::Standard),
+            "brute" => Some(MonsterRole::Brute),
+            "defender" | "tank" => Some(MonsterRole::Defender),
+            "striker" | "skirmisher" => Some(MonsterRole::Striker),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MonsterRole::Standard => "monster",
+            MonsterRole::Brute => "brute",
+            MonsterRole::Defender => "defender",
+            MonsterRole::Striker => "striker",
+        }
+    }
+}
+
+/// A stat block for a monster improvised from its challenge rating, using the middle of each range
+/// in the Monster Statistics by Challenge Rating table from the Dungeon Master's Guide.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QuickStatBlock {
+    pub challenge_rating: ChallengeRating,
+    pub role: MonsterRole,
+    pub proficiency_bonus: i32,
+    pub armor_class: i32,
+    pub hit_points: i32,
+    pub attack_bonus: i32,
+    /// The number of attacks the monster makes each round.
+    pub attacks: i32,
+    /// The damage dealt by each attack.
+    pub damage: Roll,
+    pub save_dc: i32,
+}
+
+impl QuickStatBlock {
+    pub fn new(challenge_rating: ChallengeRating, role: MonsterRole) -> QuickStatBlock {
+        let statistics = challenge_rating.statistics();
+        let hit_points = (statistics.hit_points.0 + statistics.hit_points.1) / 2;
+        let damage_per_round = (statistics.damage_per_round.0 + statistics.damage_per_round.1) / 2;
+        // Each adjustment is a quarter of the statistic or two points, so that the monster stays
+        // close to its challenge rating.
+        let (armor_class, hit_points, attack_bonus, damage_per_round) = match role {
+            MonsterRole::Standard => (0, hit_points, 0, damage_per_round),
+            MonsterRole::Brute => (-2, hit_points * 5 / 4, -2, damage_per_round * 5 / 4),
+            MonsterRole::Defender => (2, hit_points * 5 / 4, 0, damage_per_round * 3 / 4),
+            MonsterRole::Striker => (0, hit_points * 3 / 4, 2, damage_per_round * 5 / 4),
+        };
+        let attacks = if damage_per_round < 20 {
+            1
+        } else if damage_per_round < 60 {
+            2
+        } else if damage_per_round < 120 {
+            3
+        } else {
+            4
+        };
+        let per_attack = (damage_per_round / attacks).max(1);
+        let sides = if per_attack < 5 {
+            4
+        } else if role == MonsterRole::Brute {
+            12
+        } else if per_attack < 30 {
+            8
+        } else {
+            10
+        };
+        let modifier = (statistics.attack_bonus - statistics.proficiency_bonus)
+            .min(per_attack / 2)
+            .max(0);
+        // Round to the nearest number of dice, where each die averages (sides + 1) / 2.
+        let rolls = ((2 * (per_attack - modifier) + (sides + 1) / 2) / (sides + 1)).max(1);
+        QuickStatBlock {
+            challenge_rating,
+            role,
+            proficiency_bonus: statistics.proficiency_bonus,
+            armor_class: statistics.armor_class + armor_class,
+            hit_points: hit_points.max(1),
+            attack_bonus: statistics.attack_bonus + attack_bonus,
+            attacks,
+            damage: Roll::new_clamped(rolls as usize, sides, modifier),
+            save_dc: statistics.save_dc,
+        }
+    }
+}
+
+/// A monster stat block saved by the DM for a campaign, so that it can be referred to by name in
+/// encounters and attacks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Monster {
+    pub name: String,
+    pub armor_class: i32,
+    pub hit_points: i32,
+    pub attack_bonus: i32,
+    pub damage: Roll,
+    /// The name of the monster's attack, such as "claws".
+    pub attack_name: Option<String>,
+    pub challenge_rating: ChallengeRating,
+}
+
+impl Monster {
+    /// Parse a monster's stat block, such as "'Bog Hag' AC 14 HP 52 attack +5 2d8+3 claws CR 3".
+    /// The attack name and challenge rating are optional, and the challenge rating is estimated
+    /// from the other statistics if it isn't given.
+    pub fn parse(string: &str) -> Option<Monster> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r#"^(?i)(?:['"‘“]([^'"’”]+)['"’”]|(.+?)) +ac +(\d+) +(?:hp|hit +points) +(\d+) +(?:attack|to +hit) +([+-]\d+) +(\d+d\d+(?: ?[+-] ?\d+)?)(?: +damage)?(?: +(?:with +)?(.+?))??(?: +cr +(\S+))? *\.?$"#
+            )
+            .unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        let name = captures.get(1).or_else(|| captures.get(2))?.as_str().trim();
+        let armor_class = captures.get(3)?.as_str().parse().ok()?;
+        let hit_points = captures.get(4)?.as_str().parse().ok()?;
+        let attack_bonus = captures.get(5)?.as_str().parse().ok()?;
+        let damage = Roll::parse(captures.get(6)?.as_str()).ok()?;
+        let attack_name = captures.get(7).map(|m| m.as_str().trim().to_owned());
+        let challenge_rating = match captures.get(8) {
+            Some(m) => Some(ChallengeRating::parse(m.as_str())?),
+            None => None,
+        };
+        Monster::new(
+            name,
+            armor_class,
+            hit_points,
+            attack_bonus,
+            damage,
+            attack_name,
+            challenge_rating,
+        )
+    }
+
+    /// A monster with the given statistics, estimating its challenge rating if it isn't given.
+    /// Returns None if the monster has no name, or its armour class or hit points are out of range.
+    pub fn new(
+        name: &str,
+        armor_class: i32,
+        hit_points: i32,
+        attack_bonus: i32,
+        damage: Roll,
+        attack_name: Option<String>,
+        challenge_rating: Option<ChallengeRating>,
+    ) -> Option<Monster> {
+        let name = name.trim();
+        if name.is_empty() || armor_class < 1 || armor_class > 30 || hit_points < 1 {
+            return None;
+        }
+        let challenge_rating = challenge_rating.unwrap_or_else(|| {
+            ChallengeRating::estimate(
+                armor_class,
+                hit_points,
+                attack_bonus,
+                average_damage(damage),
+            )
+        });
+        Some(Monster {
+            name: name.to_owned(),
+            armor_class,
+            hit_points,
+            attack_bonus,
+            damage,
+            attack_name,
+            challenge_rating,
+        })
+    }
+
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        name: &str,
+    ) -> RusqliteResult<Option<Monster>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name];
+        connection
+            .query_row(
+                "SELECT name, armor_class, hit_points, attack_bonus, damage, attack_name, challenge_rating \
+                 FROM monsters \
+                 WHERE channel_id = $1 \
+                 AND name = $2",
+                params,
+                Monster::from_row,
+            )
+            .optional()
+    }
+
+    /// Retrieve every monster saved for a channel, in alphabetical order.
+    pub fn get_all(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<Vec<Monster>> {
+        let mut statement = connection.prepare(
+            "SELECT name, armor_class, hit_points, attack_bonus, damage, attack_name, challenge_rating \
+             FROM monsters \
+             WHERE channel_id = $1 \
+             ORDER BY name",
+        )?;
+        let rows = statement.query_map(&[&channel_id.to_string()], Monster::from_row)?;
+        rows.collect()
+    }
+
+    pub fn from_row(row: &Row) -> RusqliteResult<Monster> {
+        let damage: String = row.get("damage")?;
+        let challenge_rating: String = row.get("challenge_rating")?;
+        Ok(Monster {
+            name: row.get("name")?,
+            armor_class: row.get("armor_class")?,
+            hit_points: row.get("hit_points")?,
+            attack_bonus: row.get("attack_bonus")?,
+            damage: Roll::parse(&damage).unwrap_or_else(|_| Roll::new_unsafe(0, 0, 0)),
+            attack_name: row.get("attack_name")?,
+            challenge_rating: ChallengeRating::parse(&challenge_rating)
+                .unwrap_or(ChallengeRating::Zero),
+        })
+    }
+
+    /// Save the monster for a channel, replacing any monster with the same name.
+    pub fn save(&self, connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &self.name,
+            &self.armor_class,
+            &self.hit_points,
+            &self.attack_bonus,
+            &self.damage.to_string(),
+            &self.attack_name,
+            &self.challenge_rating.to_string(),
+        ];
+        connection.execute(
+            "INSERT INTO monsters (channel_id, name, armor_class, hit_points, attack_bonus, damage, attack_name, challenge_rating) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+             ON CONFLICT (channel_id, name) DO UPDATE SET \
+             name = excluded.name, \
+             armor_class = excluded.armor_class, \
+             hit_points = excluded.hit_points, \
+             attack_bonus = excluded.attack_bonus, \
+             damage = excluded.damage, \
+             attack_name = excluded.attack_name, \
+             challenge_rating = excluded.challenge_rating",
+            params,
+        )
+    }
+
+    /// Delete a monster saved for a channel. Returns false if there was no monster with the name.
+    pub fn delete(
+        connection: &Connection,
+        channel_id: ChannelId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name];
+        connection
+            .execute(
+                "DELETE FROM monsters WHERE channel_id = $1 AND name = $2",
+                params,
+            )
+            .map(|rows| rows > 0)
+    }
+}
+
+/// The average damage of a roll, rounded down.
+fn average_damage(roll: Roll) -> i32 {
+    roll.rolls() as i32 * (roll.sides() + 1) / 2 + roll.modifier()
+}
+
+/// The ways a monster's name might be written in the singular, such as "wolf" for "wolves".
+pub fn singular_forms(name: &str) -> Vec<String> {
+    let name = name.trim().to_lowercase();
+    let singulars = [
+        Some(name.clone()),
+        if name.ends_with("ves") {
+            Some(format!("{}f", &name[..name.len() - 3]))
+        } else {
+            None
+        },
+        if name.ends_with("ies") {
+            Some(format!("{}y", &name[..name.len() - 3]))
+        } else {
+            None
+        },
+        if name.ends_with("es") {
+            Some(name[..name.len() - 2].to_owned())
+        } else {
+            None
+        },
+        if name.ends_with('s') {
+            Some(name[..name.len() - 1].to_owned())
+        } else {
+            None
+        },
+    ];
+    singulars.iter().flatten().cloned().collect()
+}
+
+/// Whether a name, which may be plural, is one of the common monsters from the SRD.
+pub fn is_common_monster(name: &str) -> bool {
+    singular_forms(name).iter().any(|singular| {
+        MONSTERS
+            .iter()
+            .any(|(monster, _)| *monster == singular.as_str())
+    })
+}
+
+/// Find the challenge rating of a monster by name, which may be plural, such as "goblins" or
+/// "dire wolves". Monsters saved for the campaign are checked before the common monsters from the
+/// SRD.
+pub fn find_challenge_rating(name: &str, homebrew: &[Monster]) -> Option<ChallengeRating> {
+    let singulars = singular_forms(name);
+    singulars
+        .iter()
+        .find_map(|singular| {
+            homebrew
+                .iter()
+                .find(|monster| monster.name.to_lowercase() == *singular)
+                .map(|monster| monster.challenge_rating)
+        })
+        .or_else(|| {
+            singulars.iter().find_map(|singular| {
+                MONSTERS
+                    .iter()
+                    .find(|(monster, _)| *monster == singular.as_str())
+                    .map(|(_, challenge_rating)| *challenge_rating)
+            })
+        })
+}
+
+/// Parse a number of monsters, written as digits or words from "a" to "ten", such as "3" or
+/// "three". Returns None unless the number is at least one.
+pub fn parse_count(string: &str) -> Option<i32> {
+    match string.trim().to_lowercase().as_ref() {
+        "a" | "an" | "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        "four" => Some(4),
+        "five" => Some(5),
+        "six" => Some(6),
+        "seven" => Some(7),
+        "eight" => Some(8),
+        "nine" => Some(9),
+        "ten" => Some(10),
+        count => count.parse::<i32>().ok(),
+    }
+    .filter(|count| *count > 0)
+}
+
+/// A number of monsters of the same kind in an encounter, such as "4 goblins".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MonsterGroup {
+    pub count: i32,
+    /// The monsters' name as it was written, or "CR 3" if only the challenge rating was given.
+    pub name: String,
+    pub challenge_rating: ChallengeRating,
+}
+
+impl MonsterGroup {
+    /// Parse a list of monsters, such as "4 goblins and an ogre" or "2 CR 1/2, 1 CR 3", where
+    /// monsters may be common monsters from the SRD or monsters saved for the campaign. Returns
+    /// the first part of the list that isn't a known monster or a challenge rating if there is
+    /// one.
+    pub fn parse_list(string: &str, homebrew: &[Monster]) -> Result<Vec<MonsterGroup>, String> {
+        lazy_static! {
+            static ref SEPARATOR_REGEX: Regex = Regex::new(r"(?i) *(?:,|\band\b|&) *").unwrap();
+            static ref GROUP_REGEX: Regex = Regex::new(
+                r"^(?i)(?:(\d+|an?|one|two|three|four|five|six|seven|eight|nine|ten) +)?(?:(?:cr|challenge +rating) +([\d/.]+)(?: +monsters?)?|(.+))$"
+            )
+            .unwrap();
+        }
+        SEPARATOR_REGEX
+            .split(string.trim())
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let captures = GROUP_REGEX.captures(part).ok_or_else(|| part.to_owned())?;
+                let count = match captures.get(1) {
+                    None => Some(1),
+                    Some(m) => parse_count(m.as_str()),
+                }
+                .ok_or_else(|| part.to_owned())?;
+                let group = match (captures.get(2), captures.get(3)) {
+                    (Some(m), _) => {
+                        ChallengeRating::parse(m.as_str()).map(|challenge_rating| MonsterGroup {
+                            count,
+                            name: format!("CR {}", challenge_rating),
+                            challenge_rating,
+                        })
+                    }
+                    (None, Some(m)) => {
+                        find_challenge_rating(m.as_str(), homebrew).map(|challenge_rating| {
+                            MonsterGroup {
+                                count,
+                                name: m.as_str().trim().to_owned(),
+                                challenge_rating,
+                            }
+                        })
+                    }
+                    (None, None) => None,
+                };
+                group.ok_or_else(|| part.to_owned())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn group(count: i32, name: &str, challenge_rating: ChallengeRating) -> MonsterGroup {
+        MonsterGroup {
+            count,
+            name: name.to_owned(),
+            challenge_rating,
+        }
+    }
+
+    #[test]
+    fn test_challenge_rating() {
+        assert_eq!(
+            ChallengeRating::parse("1/4"),
+            Some(ChallengeRating::Quarter)
+        );
+        assert_eq!(ChallengeRating::parse("0.5"), Some(ChallengeRating::Half));
+        assert_eq!(
+            ChallengeRating::parse("30"),
+            Some(ChallengeRating::Whole(30))
+        );
+        assert_eq!(ChallengeRating::parse("31"), None);
+        assert_eq!(ChallengeRating::parse("1/3"), None);
+        assert_eq!(ChallengeRating::Quarter.experience(), 50);
+        assert_eq!(ChallengeRating::Whole(2).experience(), 450);
+        assert_eq!(ChallengeRating::Whole(30).experience(), 155000);
+        assert!(ChallengeRating::Half < ChallengeRating::Whole(1));
+        assert_eq!(ChallengeRating::Eighth.to_string(), "1/8");
+    }
+
+    #[test]
+    fn test_quick_stat_block() {
+        let monster = QuickStatBlock::new(ChallengeRating::Whole(3), MonsterRole::Standard);
+        assert_eq!(monster.proficiency_bonus, 2);
+        assert_eq!(monster.armor_class, 13);
+        assert_eq!(monster.hit_points, 108);
+        assert_eq!(monster.attack_bonus, 4);
+        assert_eq!(monster.attacks, 2);
+        assert_eq!(monster.damage.to_string(), "2d8 + 2");
+        assert_eq!(monster.save_dc, 13);
+
+        let brute = QuickStatBlock::new(ChallengeRating::Whole(3), MonsterRole::Brute);
+        assert_eq!(brute.armor_class, 11);
+        assert_eq!(brute.hit_points, 135);
+        assert_eq!(brute.attack_bonus, 2);
+        assert_eq!(brute.damage.to_string(), "2d12 + 2");
+
+        let rat = QuickStatBlock::new(ChallengeRating::Zero, MonsterRole::Standard);
+        assert_eq!(rat.hit_points, 3);
+        assert_eq!(rat.damage.to_string(), "1d4");
+    }
+
+    #[test]
+    fn test_find_challenge_rating() {
+        assert_eq!(
+            find_challenge_rating("Goblins", &[]),
+            Some(ChallengeRating::Quarter)
+        );
+        assert_eq!(
+            find_challenge_rating("dire wolves", &[]),
+            Some(ChallengeRating::Whole(1))
+        );
+        assert_eq!(
+            find_challenge_rating("giant spider", &[]),
+            Some(ChallengeRating::Whole(1))
+        );
+        assert_eq!(
+            find_challenge_rating("mummies", &[]),
+            Some(ChallengeRating::Whole(3))
+        );
+        assert_eq!(find_challenge_rating("tarrasque", &[]), None);
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(
+            MonsterGroup::parse_list("4 goblins and an ogre", &[]),
+            Ok(vec![
+                group(4, "goblins", ChallengeRating::Quarter),
+                group(1, "ogre", ChallengeRating::Whole(2)),
+            ])
+        );
+        assert_eq!(
+            MonsterGroup::parse_list("two CR 1/2 monsters, 1 CR 3", &[]),
+            Ok(vec![
+                group(2, "CR 1/2", ChallengeRating::Half),
+                group(1, "CR 3", ChallengeRating::Whole(3)),
+            ])
+        );
+        assert_eq!(
+            MonsterGroup::parse_list("3 orcs and a beholder", &[]),
+            Err("a beholder".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_list_homebrew() {
+        let homebrew = [Monster::parse("'Bog Hag' AC 14 HP 52 attack +5 2d8+3 CR 3").unwrap()];
+        assert_eq!(
+            MonsterGroup::parse_list("2 bog hags and a goblin", &homebrew),
+            Ok(vec![
+                group(2, "bog hags", ChallengeRating::Whole(3)),
+                group(1, "goblin", ChallengeRating::Quarter),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_monster() {
+        assert_eq!(
+            Monster::parse("'Bog Hag' AC 14 HP 52 attack +5 2d8+3 claws CR 3"),
+            Some(Monster {
+                name: "Bog Hag".to_owned(),
+                armor_class: 14,
+                hit_points: 52,
+                attack_bonus: 5,
+                damage: Roll::new_unsafe(2, 8, 3),
+                attack_name: Some("claws".to_owned()),
+                challenge_rating: ChallengeRating::Whole(3),
+            })
+        );
+        assert_eq!(
+            Monster::parse("Bog Hag ac 14 hp 52 attack +5 2d8 + 3"),
+            Some(Monster {
+                name: "Bog Hag".to_owned(),
+                armor_class: 14,
+                hit_points: 52,
+                attack_bonus: 5,
+                damage: Roll::new_unsafe(2, 8, 3),
+                attack_name: None,
+                challenge_rating: ChallengeRating::Whole(1),
+            })
+        );
+        assert_eq!(Monster::parse("Bog Hag AC 14 HP 52"), None);
+        assert_eq!(Monster::parse("Bog Hag AC 40 HP 52 attack +5 2d8+3"), None);
+    }
+
+    #[test]
+    fn test_estimate_challenge_rating() {
+        assert_eq!(
+            ChallengeRating::estimate(13, 5, 3, 1),
+            ChallengeRating::Zero
+        );
+        assert_eq!(
+            ChallengeRating::estimate(13, 110, 4, 21),
+            ChallengeRating::Whole(3)
+        );
+        assert_eq!(
+            ChallengeRating::estimate(25, 2000, 20, 500),
+            ChallengeRating::Whole(30)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serenity::model::id::{ChannelId, UserId};
+
+/// The number of notes shown when listing or searching notes.
+pub const RECENT_NOTES: i64 = 20;
+
+/// The longest note that can be saved, in characters, so that a page of notes fits in a message.
+pub const MAXIMUM_LENGTH: usize = 1000;
+
+/// A note about the campaign, such as a session recap or the name of an NPC, kept in the channel
+/// alongside the rolls.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Note {
+    pub id: i64,
+    /// The user who wrote the note.
+    pub user_id: UserId,
+    pub text: String,
+    /// When the note was written, in UTC, such as "2020-06-01 19:30:00".
+    pub created: String,
+}
+
+impl Note {
+    /// Add a note to a channel, returning the number it can be deleted by.
+    pub fn add(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        text: &str,
+    ) -> RusqliteResult<i64> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &text];
+        connection.execute(
+            "INSERT INTO notes (channel_id, user_id, text) VALUES ($1, $2, $3)",
+            params,
+        )?;
+        Ok(connection.last_insert_rowid())
+    }
+
+    /// Retrieve the most recent notes in a channel, or the most recent notes that contain some
+    /// text, oldest first so that they read in order.
+    pub fn get_recent(
+        connection: &Connection,
+        channel_id: ChannelId,
+        search: Option<&str>,
+        limit: i64,
+    ) -> RusqliteResult<Vec<Note>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &search, &limit];
+        let mut statement = connection.prepare(
+            "SELECT id, user_id, text, created FROM notes \
+             WHERE channel_id = $1 \
+             AND ($2 IS NULL OR instr(lower(text), lower($2)) > 0) \
+             ORDER BY id DESC \
+             LIMIT $3",
+        )?;
+        let rows = statement.query_map(params, Note::from_row)?;
+        let mut notes = rows.collect::<RusqliteResult<Vec<Note>>>()?;
+        notes.reverse();
+        Ok(notes)
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<Note> {
+        let user_id: String = row.get("user_id")?;
+        Ok(Note {
+            id: row.get("id")?,
+            user_id: UserId(user_id.parse().unwrap_or_default()),
+            text: row.get("text")?,
+            created: row.get("created")?,
+        })
+    }
+
+    /// Delete a note from a channel. Only the DM can delete other users' notes. Returns false if
+    /// there's no such note that the user can delete.
+    pub fn delete(
+        connection: &Connection,
+        channel_id: ChannelId,
+        id: i64,
+        user_id: UserId,
+        is_dm: bool,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &id, &is_dm, &user_id.to_string()];
+        connection
+            .execute(
+                "DELETE FROM notes WHERE channel_id = $1 AND id = $2 AND ($3 OR user_id = $4)",
+                params,
+            )
+            .map(|deleted| deleted > 0)
+    }
+
+    /// Delete every note a user has written in a channel. Returns the number of notes deleted.
+    pub fn delete_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string()];
+        connection.execute(
+            "DELETE FROM notes WHERE channel_id = $1 AND user_id = $2",
+            params,
+        )
+    }
+}
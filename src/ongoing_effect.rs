@@ -0,0 +1,535 @@
+use crate::combatant::{self, Damage};
+use crate::roll::{Roll, RollResult};
+use rand::Rng;
+use regex::{Captures, Regex};
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serenity::model::id::ChannelId;
+
+/// Damage or healing that a combatant receives at the start of each of their turns, such as from
+/// poison, a lingering fire, or a troll's regeneration, optionally for a limited number of rounds.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OngoingEffect {
+    /// The combatant's name in the initiative order.
+    pub target: String,
+    pub kind: EffectKind,
+    pub roll: Roll,
+    pub damage_type: Option<String>,
+    /// The number of turns the effect lasts for, or None if it lasts until it's removed.
+    pub rounds: Option<i32>,
+    /// The types of damage that stop the effect from working at the start of the target's next
+    /// turn, as fire and acid damage do for a troll's regeneration.
+    pub suppressed_by: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EffectKind {
+    Damage,
+    Healing,
+}
+
+impl EffectKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EffectKind::Damage => "Damage",
+            EffectKind::Healing => "Healing",
+        }
+    }
+}
+
+/// The outcome of an ongoing effect at the start of its target's turn.
+#[derive(Debug, Eq, PartialEq)]
+pub struct OngoingEffectResult {
+    pub effect: OngoingEffect,
+    pub outcome: EffectOutcome,
+    /// The number of turns the effect has left, or None if it lasts until it's removed.
+    pub rounds_remaining: Option<i32>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum EffectOutcome {
+    /// The damage or healing was rolled and applied to the target.
+    Rolled(RollResult),
+    /// The damage was rolled, but the target took less because of their resistance or immunity
+    /// to its type, as explained by the note.
+    Resisted {
+        result: RollResult,
+        amount: i32,
+        note: String,
+    },
+    /// The effect didn't work this turn because the target took the given type of damage.
+    Suppressed(String),
+}
+
+impl OngoingEffect {
+    /// Parse an ongoing effect from a shorthand command such as "goblin A 1d6 poison for 3
+    /// rounds".
+    pub fn parse_shorthand(string: &str) -> Option<OngoingEffect> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^(?i)(.+?) +(\d*d\d+(?: ?[+-] ?\d+)?)(?: +([a-z]+))?(?: +damage)?(?: +for +(\d+) +rounds?)? *$"
+            )
+            .unwrap();
+        }
+        RE.captures(string.trim())
+            .and_then(|captures| OngoingEffect::from_captures(&captures))
+    }
+
+    /// Parse an ongoing effect from a sentence such as "Goblin A takes 1d6 poison damage at the
+    /// start of each of its turns for 3 rounds". Returns None if the sentence doesn't describe an
+    /// ongoing effect, or Some(None) if it does but the effect is invalid.
+    pub fn parse_natural_language(string: &str) -> Option<Option<OngoingEffect>> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^(?i)(.+?) +takes +(.+?)(?: +([a-z]+))?(?: +damage)? +at +the +start +of +(?:each|every)(?: +of +(?:its|their|his|her))? +turns?(?: +for +(\d+) +rounds?)? *\.?$"
+            )
+            .unwrap();
+        }
+        RE.captures(string.trim())
+            .map(|captures| OngoingEffect::from_captures(&captures))
+    }
+
+    /// Parse regeneration from a shorthand command such as "troll 10 unless fire, acid".
+    pub fn parse_regeneration_shorthand(string: &str) -> Option<OngoingEffect> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^(?i)(.+?) +(\d+|\d*d\d+(?: ?[+-] ?\d+)?)(?: +(?:hit +points|hp))?(?: +unless +([a-z ,]+?))?(?: +for +(\d+) +rounds?)? *$"
+            )
+            .unwrap();
+        }
+        RE.captures(string.trim())
+            .and_then(|captures| OngoingEffect::from_regeneration_captures(&captures))
+    }
+
+    /// Parse regeneration from a sentence such as "The troll regains 10 hit points at the start of
+    /// each of its turns unless it takes fire or acid damage". Returns None if the sentence doesn't
+    /// describe regeneration, or Some(None) if it does but the effect is invalid.
+    pub fn parse_regeneration_natural_language(string: &str) -> Option<Option<OngoingEffect>> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^(?i)(.+?) +regains +(.+?) +(?:hit +points|hp) +at +the +start +of +(?:each|every|its|their|his|her)(?: +of +(?:its|their|his|her))? +turns?(?: *,? +unless +(?:it|they|he|she) +takes? +([a-z ,]+?) +damage)?(?: +for +(\d+) +rounds?)? *\.?$"
+            )
+            .unwrap();
+        }
+        RE.captures(string.trim())
+            .map(|captures| OngoingEffect::from_regeneration_captures(&captures))
+    }
+
+    fn from_captures(captures: &Captures) -> Option<OngoingEffect> {
+        let target = captures.get(1)?.as_str().trim();
+        let roll = parse_roll(captures.get(2)?.as_str())?;
+        let damage_type = captures
+            .get(3)
+            .map(|m| m.as_str().to_lowercase())
+            .filter(|damage_type| damage_type != "damage");
+        let rounds = parse_rounds(captures.get(4))?;
+        if target.is_empty() {
+            None
+        } else {
+            Some(OngoingEffect {
+                target: target.to_owned(),
+                kind: EffectKind::Damage,
+                roll,
+                damage_type,
+                rounds,
+                suppressed_by: Vec::new(),
+            })
+        }
+    }
+
+    fn from_regeneration_captures(captures: &Captures) -> Option<OngoingEffect> {
+        lazy_static! {
+            static ref SEPARATOR: Regex = Regex::new(r"(?i)[ ,]+(?:(?:or|and) +)?").unwrap();
+        }
+        let target = captures.get(1)?.as_str().trim();
+        let amount = captures.get(2)?.as_str();
+        let roll = match amount.parse::<i32>() {
+            Ok(amount) if amount > 0 => Roll::new_unsafe(0, 1, amount),
+            Ok(_) => return None,
+            Err(_) => parse_roll(amount)?,
+        };
+        let suppressed_by = captures
+            .get(3)
+            .map(|m| {
+                SEPARATOR
+                    .split(m.as_str())
+                    .filter(|damage_type| !damage_type.is_empty())
+                    .map(|damage_type| damage_type.to_lowercase())
+                    .collect()
+            })
+            .unwrap_or_else(Vec::new);
+        let rounds = parse_rounds(captures.get(4))?;
+        if target.is_empty() {
+            None
+        } else {
+            Some(OngoingEffect {
+                target: target.to_owned(),
+                kind: EffectKind::Healing,
+                roll,
+                damage_type: None,
+                rounds,
+                suppressed_by,
+            })
+        }
+    }
+
+    /// A description of the effect, such as "1d6 poison damage" or "10 hit points".
+    pub fn description(&self) -> String {
+        match (self.kind, &self.damage_type) {
+            (EffectKind::Healing, _) => format!("{} hit points", self.roll),
+            (EffectKind::Damage, Some(damage_type)) => {
+                format!("{} {} damage", self.roll, damage_type)
+            }
+            (EffectKind::Damage, None) => format!("{} damage", self.roll),
+        }
+    }
+
+    pub fn add(&self, connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+        let rolls = self.roll.rolls() as i64;
+        let suppressed_by = if self.suppressed_by.is_empty() {
+            None
+        } else {
+            Some(self.suppressed_by.join(","))
+        };
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &self.target,
+            &rolls,
+            &self.roll.sides(),
+            &self.roll.modifier(),
+            &self.damage_type,
+            &self.rounds,
+            &self.kind.as_str(),
+            &suppressed_by,
+        ];
+        connection.execute(
+            "INSERT INTO ongoing_effects (channel_id, target, rolls, sides, modifier, damage_type, rounds, kind, suppressed_by) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            params,
+        )
+    }
+
+    /// Remove the ongoing effects on a combatant. Returns the number of effects removed.
+    pub fn clear(
+        connection: &Connection,
+        channel_id: ChannelId,
+        target: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &target];
+        connection.execute(
+            "DELETE FROM ongoing_effects WHERE channel_id = $1 AND target = $2",
+            params,
+        )
+    }
+
+    /// Remove the ongoing effects on every combatant in a channel, as when an encounter ends.
+    pub fn clear_all(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+        connection.execute(
+            "DELETE FROM ongoing_effects WHERE channel_id = $1",
+            &[&channel_id.to_string()],
+        )
+    }
+
+    /// Roll the ongoing effects on a combatant whose turn has begun and apply them to the
+    /// combatant's hit points, counting down the rounds left on each effect and removing the
+    /// effects that have run out. Effects that are suppressed by a type of damage that the
+    /// combatant took since the start of their last turn don't work this turn.
+    pub fn start_turn<R: Rng + ?Sized>(
+        connection: &mut Connection,
+        channel_id: ChannelId,
+        target: &str,
+        rng: &mut R,
+    ) -> RusqliteResult<Vec<OngoingEffectResult>> {
+        let transaction = connection.transaction()?;
+        let damage_types_taken =
+            combatant::take_damage_types_taken(&transaction, channel_id, target)?;
+        let effects = {
+            let params: &[&dyn ToSql] = &[&channel_id.to_string(), &target];
+            let mut statement = transaction.prepare(
+                "SELECT id, target, rolls, sides, modifier, damage_type, rounds, kind, suppressed_by \
+                 FROM ongoing_effects \
+                 WHERE channel_id = $1 \
+                 AND target = $2 \
+                 ORDER BY id",
+            )?;
+            let rows = statement.query_map(params, |row| {
+                let id: i64 = row.get("id")?;
+                Ok((id, OngoingEffect::from_row(row)?))
+            })?;
+            rows.collect::<RusqliteResult<Vec<(i64, OngoingEffect)>>>()?
+        };
+        let mut results = Vec::new();
+        for (id, effect) in effects {
+            let rounds_remaining = effect.rounds.map(|rounds| rounds - 1);
+            match rounds_remaining {
+                Some(rounds) if rounds <= 0 => {
+                    transaction.execute("DELETE FROM ongoing_effects WHERE id = $1", &[&id])?
+                }
+                Some(rounds) => {
+                    let params: &[&dyn ToSql] = &[&id, &rounds];
+                    transaction.execute(
+                        "UPDATE ongoing_effects SET rounds = $2 WHERE id = $1",
+                        params,
+                    )?
+                }
+                None => 0,
+            };
+            let suppressed_by = effect
+                .suppressed_by
+                .iter()
+                .find(|damage_type| {
+                    damage_types_taken
+                        .iter()
+                        .any(|taken| taken.eq_ignore_ascii_case(damage_type))
+                })
+                .cloned();
+            let outcome = match suppressed_by {
+                Some(damage_type) => EffectOutcome::Suppressed(damage_type),
+                None => {
+                    let result = effect.roll.roll(rng);
+                    let amount = result.result().max(0);
+                    match effect.kind {
+                        EffectKind::Damage => {
+                            let (damage, note) = Damage {
+                                target: effect.target.clone(),
+                                amount,
+                                damage_type: effect.damage_type.clone(),
+                            }
+                            .resist(&transaction, channel_id)?;
+                            damage.apply(&transaction, channel_id)?;
+                            match note {
+                                Some(note) => EffectOutcome::Resisted {
+                                    result,
+                                    amount: damage.amount,
+                                    note,
+                                },
+                                None => EffectOutcome::Rolled(result),
+                            }
+                        }
+                        EffectKind::Healing => {
+                            combatant::heal(&transaction, channel_id, &effect.target, amount)?;
+                            EffectOutcome::Rolled(result)
+                        }
+                    }
+                }
+            };
+            results.push(OngoingEffectResult {
+                effect,
+                outcome,
+                rounds_remaining,
+            });
+        }
+        transaction.commit()?;
+        Ok(results)
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<OngoingEffect> {
+        let rolls: i64 = row.get("rolls")?;
+        let kind: String = row.get("kind")?;
+        let suppressed_by: Option<String> = row.get("suppressed_by")?;
+        Ok(OngoingEffect {
+            target: row.get("target")?,
+            kind: if kind == EffectKind::Healing.as_str() {
+                EffectKind::Healing
+            } else {
+                EffectKind::Damage
+            },
+            roll: Roll::new_clamped(rolls as usize, row.get("sides")?, row.get("modifier")?),
+            damage_type: row.get("damage_type")?,
+            rounds: row.get("rounds")?,
+            suppressed_by: suppressed_by
+                .map(|suppressed_by| suppressed_by.split(',').map(str::to_owned).collect())
+                .unwrap_or_else(Vec::new),
+        })
+    }
+}
+
+fn parse_roll(roll: &str) -> Option<Roll> {
+    // Allow the number of dice to be left out, as in "d6"
+    let roll = if roll.starts_with('d') || roll.starts_with('D') {
+        format!("1{}", roll)
+    } else {
+        roll.to_owned()
+    };
+    Roll::parse(&roll.to_lowercase()).ok()
+}
+
+/// Parse the number of rounds an effect lasts for. Returns None if the number is invalid, or
+/// Some(None) if it isn't given.
+pub fn parse_rounds(rounds: Option<regex::Match>) -> Option<Option<i32>> {
+    match rounds {
+        Some(m) => Some(Some(
+            m.as_str()
+                .parse::<i32>()
+                .ok()
+                .filter(|rounds| *rounds > 0)?,
+        )),
+        None => Some(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn effect(
+        target: &str,
+        roll: Roll,
+        damage_type: Option<&str>,
+        rounds: Option<i32>,
+    ) -> OngoingEffect {
+        OngoingEffect {
+            target: target.to_owned(),
+            kind: EffectKind::Damage,
+            roll,
+            damage_type: damage_type.map(|damage_type| damage_type.to_owned()),
+            rounds,
+            suppressed_by: Vec::new(),
+        }
+    }
+
+    fn regeneration(
+        target: &str,
+        roll: Roll,
+        rounds: Option<i32>,
+        suppressed_by: &[&str],
+    ) -> OngoingEffect {
+        OngoingEffect {
+            target: target.to_owned(),
+            kind: EffectKind::Healing,
+            roll,
+            damage_type: None,
+            rounds,
+            suppressed_by: suppressed_by
+                .iter()
+                .map(|damage_type| (*damage_type).to_owned())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_shorthand() {
+        assert_eq!(
+            OngoingEffect::parse_shorthand("goblin A 1d6 poison for 3 rounds"),
+            Some(effect(
+                "goblin A",
+                Roll::new_unsafe(1, 6, 0),
+                Some("poison"),
+                Some(3)
+            ))
+        );
+        assert_eq!(
+            OngoingEffect::parse_shorthand("Bob d4+1 fire damage"),
+            Some(effect("Bob", Roll::new_unsafe(1, 4, 1), Some("fire"), None))
+        );
+        assert_eq!(
+            OngoingEffect::parse_shorthand("Bob 2d8 damage for 1 round"),
+            Some(effect("Bob", Roll::new_unsafe(2, 8, 0), None, Some(1)))
+        );
+        assert_eq!(OngoingEffect::parse_shorthand("1d6 poison"), None);
+        assert_eq!(OngoingEffect::parse_shorthand("Bob 1d6 for 0 rounds"), None);
+        assert_eq!(OngoingEffect::parse_shorthand("Bob poison"), None);
+    }
+
+    #[test]
+    fn test_parse_natural_language() {
+        assert_eq!(
+            OngoingEffect::parse_natural_language(
+                "The target takes 1d6 poison at the start of each turn for 3 rounds"
+            ),
+            Some(Some(effect(
+                "The target",
+                Roll::new_unsafe(1, 6, 0),
+                Some("poison"),
+                Some(3)
+            )))
+        );
+        assert_eq!(
+            OngoingEffect::parse_natural_language(
+                "Goblin A takes 2d4 fire damage at the start of each of its turns."
+            ),
+            Some(Some(effect(
+                "Goblin A",
+                Roll::new_unsafe(2, 4, 0),
+                Some("fire"),
+                None
+            )))
+        );
+        assert_eq!(
+            OngoingEffect::parse_natural_language(
+                "Goblin A takes lots of damage at the start of each turn"
+            ),
+            Some(None)
+        );
+        assert_eq!(
+            OngoingEffect::parse_natural_language("Goblin A takes 1d6 poison"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_regeneration_shorthand() {
+        assert_eq!(
+            OngoingEffect::parse_regeneration_shorthand("troll 10 unless fire, acid"),
+            Some(regeneration(
+                "troll",
+                Roll::new_unsafe(0, 1, 10),
+                None,
+                &["fire", "acid"]
+            ))
+        );
+        assert_eq!(
+            OngoingEffect::parse_regeneration_shorthand("Bob 1d4 hp for 3 rounds"),
+            Some(regeneration("Bob", Roll::new_unsafe(1, 4, 0), Some(3), &[]))
+        );
+        assert_eq!(OngoingEffect::parse_regeneration_shorthand("troll 0"), None);
+        assert_eq!(OngoingEffect::parse_regeneration_shorthand("troll"), None);
+    }
+
+    #[test]
+    fn test_parse_regeneration_natural_language() {
+        assert_eq!(
+            OngoingEffect::parse_regeneration_natural_language(
+                "The troll regains 10 hit points at the start of its turn, unless it takes fire or acid damage."
+            ),
+            Some(Some(regeneration(
+                "The troll",
+                Roll::new_unsafe(0, 1, 10),
+                None,
+                &["fire", "acid"]
+            )))
+        );
+        assert_eq!(
+            OngoingEffect::parse_regeneration_natural_language(
+                "Bob regains 1d6 HP at the start of each of his turns for 2 rounds"
+            ),
+            Some(Some(regeneration(
+                "Bob",
+                Roll::new_unsafe(1, 6, 0),
+                Some(2),
+                &[]
+            )))
+        );
+        assert_eq!(
+            OngoingEffect::parse_regeneration_natural_language("Bob regains 10 hit points"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_description() {
+        assert_eq!(
+            effect("Bob", Roll::new_unsafe(1, 6, 2), Some("poison"), None).description(),
+            "1d6 + 2 poison damage"
+        );
+        assert_eq!(
+            effect("Bob", Roll::new_unsafe(2, 4, 0), None, None).description(),
+            "2d4 damage"
+        );
+        assert_eq!(
+            regeneration("Bob", Roll::new_unsafe(0, 1, 10), None, &["fire"]).description(),
+            "10 hit points"
+        );
+    }
+}
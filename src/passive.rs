@@ -0,0 +1,115 @@
+use crate::character::{Character, SkillName};
+use crate::character_roll::{CharacterRoll, Check};
+use crate::character_sheet::ABILITY_SKILLS;
+use crate::roll::Condition;
+use crate::status::Status;
+
+/// The bonus to passive Wisdom (Perception) and Intelligence (Investigation) scores from the
+/// Observant feat, which is the same as the bonus for advantage.
+const OBSERVANT_BONUS: i32 = 5;
+
+/// A character's passive score for an ability or skill, used by the DM when the character isn't
+/// actively rolling, such as noticing a hidden monster.
+#[derive(Clone, Copy, Debug)]
+pub struct PassiveScore {
+    pub check: Check,
+    /// The score, or None if the character doesn't have the ability score it uses.
+    pub score: Option<i32>,
+    /// The advantage or disadvantage on the check from the character's conditions, which is
+    /// included in the score.
+    pub condition: Option<Condition>,
+}
+
+impl PassiveScore {
+    /// Work out a character's passive score for a check: 10 plus their modifier for the check, +5
+    /// for advantage or -5 for disadvantage, and +5 to Perception and Investigation for the
+    /// Observant feat.
+    pub fn new(check: Check, character: &Character, statuses: &[Status]) -> PassiveScore {
+        let condition = Condition::combine(
+            statuses
+                .iter()
+                .filter_map(|status| status.check_condition(check)),
+        );
+        let condition_bonus = match condition {
+            Some(Condition::Advantage) => 5,
+            Some(Condition::Disadvantage) => -5,
+            None => 0,
+        };
+        let observant_bonus = match check {
+            Check::Skill(SkillName::Perception) | Check::Skill(SkillName::Investigation)
+                if character.observant() =>
+            {
+                OBSERVANT_BONUS
+            }
+            _ => 0,
+        };
+        PassiveScore {
+            check,
+            score: CharacterRoll::modifier(check, character)
+                .map(|modifier| 10 + modifier + condition_bonus + observant_bonus),
+            condition,
+        }
+    }
+
+    /// The character's passive scores for every ability, each followed by the skills that use it,
+    /// in the order they're listed on a character sheet.
+    pub fn all(character: &Character, statuses: &[Status]) -> Vec<PassiveScore> {
+        ABILITY_SKILLS
+            .iter()
+            .flat_map(|(ability, skills)| {
+                std::iter::once(Check::Ability(*ability))
+                    .chain(skills.iter().map(|skill| Check::Skill(*skill)))
+            })
+            .map(|check| PassiveScore::new(check, character, statuses))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::character::AbilityName;
+
+    fn character(observant: bool) -> Character {
+        serde_json::from_str(&format!(
+            r#"{{
+                "name": "Connie",
+                "level": 1,
+                "intelligence": 10,
+                "wisdom": 14,
+                "perception_proficiency": "Proficient",
+                "observant": {}
+            }}"#,
+            observant
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let score = |check, observant, statuses: &[Status]| {
+            PassiveScore::new(check, &character(observant), statuses).score
+        };
+        let perception = Check::Skill(SkillName::Perception);
+        assert_eq!(score(perception, false, &[]), Some(14));
+        assert_eq!(score(perception, true, &[]), Some(19));
+        assert_eq!(score(perception, false, &[Status::Poisoned]), Some(9));
+        assert_eq!(
+            score(Check::Skill(SkillName::Investigation), true, &[]),
+            Some(15)
+        );
+        assert_eq!(score(Check::Skill(SkillName::Insight), true, &[]), Some(12));
+        assert_eq!(
+            score(Check::Ability(AbilityName::Strength), false, &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_all() {
+        let scores = PassiveScore::all(&character(false), &[]);
+        assert_eq!(scores.len(), 24);
+        assert_eq!(scores[0].check.to_string(), "Strength");
+        assert_eq!(scores[1].check.to_string(), "Athletics");
+    }
+}
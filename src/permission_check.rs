@@ -0,0 +1,85 @@
+use serenity::model::permissions::Permissions;
+
+/// A permission that Dungeon Helper uses in a channel, and what it's used for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Requirement {
+    pub name: &'static str,
+    pub permission: Permissions,
+    /// What works when the permission is granted.
+    pub granted: &'static str,
+    /// What doesn't work when the permission is missing.
+    pub missing: &'static str,
+}
+
+/// Whether Dungeon Helper has one of the permissions it uses in a channel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PermissionCheck {
+    pub requirement: Requirement,
+    pub has_permission: bool,
+}
+
+pub const REQUIREMENTS: [Requirement; 5] = [
+    Requirement {
+        name: "Send Messages",
+        permission: Permissions::SEND_MESSAGES,
+        granted: "Responding to commands.",
+        missing: "Dungeon Helper can't respond to any commands.",
+    },
+    Requirement {
+        name: "Embed Links",
+        permission: Permissions::EMBED_LINKS,
+        granted: "Formatted responses.",
+        missing: "Responses are sent as plain text.",
+    },
+    Requirement {
+        name: "Attach Files",
+        permission: Permissions::ATTACH_FILES,
+        granted: "Exporting characters and training data.",
+        missing: "Character exports and training data can't be sent.",
+    },
+    Requirement {
+        name: "Manage Messages",
+        permission: Permissions::MANAGE_MESSAGES,
+        granted: "Tidying away roll commands, and pinning quick references.",
+        missing: "Roll commands are left in the channel, and quick references can't be pinned. Use `!channel keep-rolls on` to leave them there on purpose.",
+    },
+    Requirement {
+        name: "Add Reactions",
+        permission: Permissions::ADD_REACTIONS,
+        granted: "Reacting to messages, although no features react to messages yet.",
+        missing: "Nothing yet, since no features react to messages.",
+    },
+];
+
+/// Check which of the permissions that Dungeon Helper uses it has in a channel.
+pub fn check(permissions: Permissions) -> Vec<PermissionCheck> {
+    REQUIREMENTS
+        .iter()
+        .map(|&requirement| PermissionCheck {
+            requirement,
+            has_permission: permissions.contains(requirement.permission),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let missing = |permissions| -> Vec<&'static str> {
+            check(permissions)
+                .into_iter()
+                .filter(|check| !check.has_permission)
+                .map(|check| check.requirement.name)
+                .collect()
+        };
+        assert_eq!(
+            missing(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS),
+            vec!["Attach Files", "Manage Messages", "Add Reactions"]
+        );
+        assert_eq!(missing(Permissions::all()), Vec::<&str>::new());
+        assert_eq!(missing(Permissions::empty()).len(), REQUIREMENTS.len());
+    }
+}
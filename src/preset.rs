@@ -0,0 +1,339 @@
+use crate::character::{CharacterExport, WeaponProficiency};
+use crate::class::Class;
+use serde_json::{Map, Value};
+
+const ABILITIES: [&str; 6] = [
+    "strength",
+    "dexterity",
+    "constitution",
+    "intelligence",
+    "wisdom",
+    "charisma",
+];
+
+/// The levels at which every class gains an Ability Score Improvement.
+const ABILITY_SCORE_IMPROVEMENT_LEVELS: [i32; 5] = [4, 8, 12, 16, 19];
+
+/// The highest score an Ability Score Improvement can raise an ability to.
+const MAXIMUM_ABILITY_SCORE: i32 = 20;
+
+/// A typical build for a class, used to fill in the character sheet of a pregenerated character.
+struct Preset {
+    class: Class,
+    /// The standard array (15, 14, 13, 12, 10, 8), assigned in the same order as `ABILITIES`.
+    abilities: [i32; 6],
+    /// The abilities raised by Ability Score Improvements, in order of priority.
+    improvements: [&'static str; 2],
+    saving_throws: [&'static str; 2],
+    skills: &'static [&'static str],
+    expertise: &'static [&'static str],
+    weapon_proficiencies: &'static [&'static str],
+}
+
+const PRESETS: [Preset; 12] = [
+    Preset {
+        class: Class::Barbarian,
+        abilities: [15, 13, 14, 8, 12, 10],
+        improvements: ["strength", "constitution"],
+        saving_throws: ["strength", "constitution"],
+        skills: &["athletics", "perception"],
+        expertise: &[],
+        weapon_proficiencies: &["simple", "martial"],
+    },
+    Preset {
+        class: Class::Bard,
+        abilities: [8, 14, 13, 10, 12, 15],
+        improvements: ["charisma", "dexterity"],
+        saving_throws: ["dexterity", "charisma"],
+        skills: &["deception", "performance", "persuasion"],
+        expertise: &[],
+        weapon_proficiencies: &[
+            "simple",
+            "hand crossbow",
+            "longsword",
+            "rapier",
+            "shortsword",
+        ],
+    },
+    Preset {
+        class: Class::Cleric,
+        abilities: [13, 10, 14, 8, 15, 12],
+        improvements: ["wisdom", "constitution"],
+        saving_throws: ["wisdom", "charisma"],
+        skills: &["insight", "religion"],
+        expertise: &[],
+        weapon_proficiencies: &["simple"],
+    },
+    Preset {
+        class: Class::Druid,
+        abilities: [8, 12, 14, 13, 15, 10],
+        improvements: ["wisdom", "constitution"],
+        saving_throws: ["intelligence", "wisdom"],
+        skills: &["nature", "perception"],
+        expertise: &[],
+        weapon_proficiencies: &[
+            "club",
+            "dagger",
+            "dart",
+            "javelin",
+            "mace",
+            "quarterstaff",
+            "scimitar",
+            "sickle",
+            "sling",
+            "spear",
+        ],
+    },
+    Preset {
+        class: Class::Fighter,
+        abilities: [15, 13, 14, 8, 12, 10],
+        improvements: ["strength", "constitution"],
+        saving_throws: ["strength", "constitution"],
+        skills: &["athletics", "intimidation"],
+        expertise: &[],
+        weapon_proficiencies: &["simple", "martial"],
+    },
+    Preset {
+        class: Class::Monk,
+        abilities: [10, 15, 13, 8, 14, 12],
+        improvements: ["dexterity", "wisdom"],
+        saving_throws: ["strength", "dexterity"],
+        skills: &["acrobatics", "stealth"],
+        expertise: &[],
+        weapon_proficiencies: &["simple", "shortsword"],
+    },
+    Preset {
+        class: Class::Paladin,
+        abilities: [15, 8, 13, 10, 12, 14],
+        improvements: ["strength", "charisma"],
+        saving_throws: ["wisdom", "charisma"],
+        skills: &["athletics", "persuasion"],
+        expertise: &[],
+        weapon_proficiencies: &["simple", "martial"],
+    },
+    Preset {
+        class: Class::Ranger,
+        abilities: [12, 15, 13, 8, 14, 10],
+        improvements: ["dexterity", "wisdom"],
+        saving_throws: ["strength", "dexterity"],
+        skills: &["perception", "stealth", "survival"],
+        expertise: &[],
+        weapon_proficiencies: &["simple", "martial"],
+    },
+    Preset {
+        class: Class::Rogue,
+        abilities: [8, 15, 14, 13, 10, 12],
+        improvements: ["dexterity", "constitution"],
+        saving_throws: ["dexterity", "intelligence"],
+        skills: &["acrobatics", "perception", "sleight_of_hand", "stealth"],
+        expertise: &["sleight_of_hand", "stealth"],
+        weapon_proficiencies: &[
+            "simple",
+            "hand crossbow",
+            "longsword",
+            "rapier",
+            "shortsword",
+        ],
+    },
+    Preset {
+        class: Class::Sorcerer,
+        abilities: [8, 13, 14, 10, 12, 15],
+        improvements: ["charisma", "constitution"],
+        saving_throws: ["constitution", "charisma"],
+        skills: &["arcana", "persuasion"],
+        expertise: &[],
+        weapon_proficiencies: &["dagger", "dart", "sling", "quarterstaff", "light crossbow"],
+    },
+    Preset {
+        class: Class::Warlock,
+        abilities: [8, 14, 13, 12, 10, 15],
+        improvements: ["charisma", "constitution"],
+        saving_throws: ["wisdom", "charisma"],
+        skills: &["arcana", "deception"],
+        expertise: &[],
+        weapon_proficiencies: &["simple"],
+    },
+    Preset {
+        class: Class::Wizard,
+        abilities: [8, 13, 14, 15, 12, 10],
+        improvements: ["intelligence", "constitution"],
+        saving_throws: ["intelligence", "wisdom"],
+        skills: &["arcana", "history"],
+        expertise: &[],
+        weapon_proficiencies: &["dagger", "dart", "sling", "quarterstaff", "light crossbow"],
+    },
+];
+
+/// The name given to a pregenerated character of the given class.
+pub fn name(class: Class) -> String {
+    format!("Pregen {}", class)
+}
+
+/// A complete character sheet for a pregenerated character of the given class and level.
+pub fn create(class: Class, level: i32) -> CharacterExport {
+    let preset = PRESETS
+        .iter()
+        .find(|preset| preset.class == class)
+        .expect("Missing preset for class");
+
+    let mut abilities = preset.abilities;
+    for _ in 0..ability_score_improvements(class, level) {
+        // Each improvement is +2, split across abilities if the first would exceed the maximum
+        for _ in 0..2 {
+            if let Some(index) = preset
+                .improvements
+                .iter()
+                .filter_map(|ability| ABILITIES.iter().position(|a| a == ability))
+                .find(|index| abilities[*index] < MAXIMUM_ABILITY_SCORE)
+            {
+                abilities[index] += 1;
+            }
+        }
+    }
+
+    let mut character = Map::new();
+    character.insert("name".to_owned(), Value::from(name(class)));
+    character.insert("level".to_owned(), Value::from(level));
+    let mut class_level = Map::new();
+    class_level.insert("class".to_owned(), Value::from(class.as_str()));
+    class_level.insert("level".to_owned(), Value::from(level));
+    character.insert(
+        "classes".to_owned(),
+        Value::Array(vec![Value::Object(class_level)]),
+    );
+    for (ability, score) in ABILITIES.iter().zip(abilities.iter()) {
+        character.insert((*ability).to_owned(), Value::from(*score));
+    }
+    for ability in preset.saving_throws.iter() {
+        character.insert(format!("{}_saving_proficiency", ability), Value::from(true));
+    }
+    for skill in preset.skills.iter() {
+        let proficiency = if preset.expertise.contains(skill) {
+            "Expert"
+        } else {
+            "Proficient"
+        };
+        character.insert(format!("{}_proficiency", skill), Value::from(proficiency));
+    }
+
+    CharacterExport {
+        character: serde_json::from_value(Value::Object(character))
+            .expect("Invalid preset character"),
+        weapon_proficiencies: preset
+            .weapon_proficiencies
+            .iter()
+            .map(|proficiency| {
+                WeaponProficiency::parse(proficiency).expect("Invalid preset weapon proficiency")
+            })
+            .collect(),
+    }
+}
+
+/// The number of Ability Score Improvements a class has gained by the given level.
+fn ability_score_improvements(class: Class, level: i32) -> usize {
+    let extra_levels: &[i32] = match class {
+        Class::Fighter => &[6, 14],
+        Class::Rogue => &[10],
+        _ => &[],
+    };
+    ABILITY_SCORE_IMPROVEMENT_LEVELS
+        .iter()
+        .chain(extra_levels.iter())
+        .filter(|improvement_level| **improvement_level <= level)
+        .count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::character::Proficiency;
+    use crate::weapon::Category;
+
+    const CLASSES: [Class; 12] = [
+        Class::Barbarian,
+        Class::Bard,
+        Class::Cleric,
+        Class::Druid,
+        Class::Fighter,
+        Class::Monk,
+        Class::Paladin,
+        Class::Ranger,
+        Class::Rogue,
+        Class::Sorcerer,
+        Class::Warlock,
+        Class::Wizard,
+    ];
+
+    #[test]
+    fn test_create_all_classes() {
+        for class in CLASSES.iter() {
+            for level in 1..=20 {
+                let export = create(*class, level);
+                assert_eq!(export.validate(), Vec::<String>::new());
+                assert_eq!(export.character.class(), Some(*class));
+                assert!(!export.weapon_proficiencies.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_create_cleric() {
+        let export = create(Class::Cleric, 3);
+        let character = &export.character;
+        assert_eq!(character.name(), "Pregen Cleric");
+        assert_eq!(character.proficiency_bonus(), Some(2));
+        assert_eq!(character.wisdom().map(|ability| ability.score), Some(15));
+        assert_eq!(
+            character.religion().map(|skill| skill.proficiency),
+            Some(Proficiency::Proficient)
+        );
+        assert_eq!(
+            character.stealth().map(|skill| skill.proficiency),
+            Some(Proficiency::Normal)
+        );
+        assert_eq!(
+            character
+                .wisdom_saving_throw()
+                .map(|saving_throw| saving_throw.proficiency),
+            Some(true)
+        );
+        assert_eq!(
+            character
+                .strength_saving_throw()
+                .map(|saving_throw| saving_throw.proficiency),
+            Some(false)
+        );
+        assert_eq!(
+            export.weapon_proficiencies,
+            vec![WeaponProficiency::Category(Category::Simple)]
+        );
+    }
+
+    #[test]
+    fn test_create_ability_score_improvements() {
+        // Three improvements raise wisdom to the maximum, and the rest goes to constitution
+        let character = create(Class::Cleric, 12).character;
+        assert_eq!(character.wisdom().map(|ability| ability.score), Some(20));
+        assert_eq!(
+            character.constitution().map(|ability| ability.score),
+            Some(15)
+        );
+
+        // Fighters gain extra improvements at 6th and 14th level
+        let character = create(Class::Fighter, 6).character;
+        assert_eq!(character.strength().map(|ability| ability.score), Some(19));
+    }
+
+    #[test]
+    fn test_create_rogue_expertise() {
+        let character = create(Class::Rogue, 1).character;
+        assert_eq!(
+            character.stealth().map(|skill| skill.proficiency),
+            Some(Proficiency::Expert)
+        );
+        assert_eq!(
+            character.acrobatics().map(|skill| skill.proficiency),
+            Some(Proficiency::Proficient)
+        );
+    }
+}
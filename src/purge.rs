@@ -0,0 +1,41 @@
+use crate::character::Character;
+use crate::hidden_roll::HiddenRoll;
+use crate::intent_logger;
+use crate::note::Note;
+use crate::roll_history;
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
+use serenity::model::id::{ChannelId, UserId};
+
+/// What was deleted when purging a user's data from a channel.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Purge {
+    pub characters: usize,
+    pub rolls: usize,
+    pub messages: usize,
+    pub notes: usize,
+}
+
+/// Delete everything stored about a user in a channel: their characters, their roll history and
+/// hidden rolls, the messages logged for the natural language parser, and their notes. Anything
+/// stored about them in other channels is kept, since a server's administrators can only purge
+/// their own server's channels, as is their time zone, which they use in every server.
+pub fn purge_user_from_channel(
+    connection: &mut Connection,
+    channel_id: ChannelId,
+    user_id: UserId,
+) -> RusqliteResult<Purge> {
+    let transaction = connection.transaction()?;
+    let characters = Character::delete_all(&transaction, channel_id, user_id)?;
+    let rolls = roll_history::delete_rolls(&transaction, channel_id, user_id)?
+        + HiddenRoll::delete_all(&transaction, channel_id, user_id)?;
+    let messages = intent_logger::delete_messages(&transaction, channel_id, user_id)?;
+    let notes = Note::delete_all(&transaction, channel_id, user_id)?;
+    transaction.commit()?;
+    Ok(Purge {
+        characters,
+        rolls,
+        messages,
+        notes,
+    })
+}
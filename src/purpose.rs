@@ -0,0 +1,103 @@
+use regex::Regex;
+use std::fmt;
+
+/// What a roll is for, such as "to search the desk", optionally tagged with an emoji that is shown
+/// alongside the result.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Purpose {
+    /// An emoji given at the start of the purpose, such as the 🔥 in "for 🔥 fire damage".
+    pub tag: Option<String>,
+    /// The purpose without its tag, including the word that introduced it, e.g. "for fire damage".
+    pub text: String,
+}
+
+impl Purpose {
+    /// Split a purpose introduced by "to" or "for" from the end of a shorthand roll, such as
+    /// "2d6+3 for fire damage".
+    pub fn split_shorthand(string: &str) -> Option<(&str, Purpose)> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"^(?i)(.*?) +(to|for) +(.+)$").unwrap();
+        }
+        Purpose::split(&RE, string)
+    }
+
+    /// Split a purpose introduced by "to" from the end of a natural language roll, such as "Roll
+    /// perception to search the desk". Only "to" is recognised, since "for" is also part of
+    /// commands such as "Roll for initiative".
+    pub fn split_natural_language(string: &str) -> Option<(&str, Purpose)> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"^(?i)(.*?) +(to) +(.+)$").unwrap();
+        }
+        Purpose::split(&RE, string)
+    }
+
+    fn split<'a>(regex: &Regex, string: &'a str) -> Option<(&'a str, Purpose)> {
+        let captures = regex.captures(string)?;
+        let command = captures.get(1)?.as_str();
+        let word = captures.get(2)?.as_str().to_lowercase();
+        let rest = captures.get(3)?.as_str().trim();
+        let mut words = rest.splitn(2, ' ');
+        let first = words.next().unwrap_or("");
+        // A tag is a leading word made up only of emoji and other non-ASCII symbols
+        let (tag, rest) = if !first.is_ascii() && !first.chars().any(char::is_alphanumeric) {
+            (Some(first.to_owned()), words.next().unwrap_or("").trim())
+        } else {
+            (None, rest)
+        };
+        if command.is_empty() || rest.is_empty() {
+            None
+        } else {
+            Some((
+                command,
+                Purpose {
+                    tag,
+                    text: format!("{} {}", word, rest),
+                },
+            ))
+        }
+    }
+}
+
+impl fmt::Display for Purpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.text.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn purpose(tag: Option<&str>, text: &str) -> Purpose {
+        Purpose {
+            tag: tag.map(|tag| tag.to_owned()),
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_split_shorthand() {
+        assert_eq!(
+            Purpose::split_shorthand("perception to search the desk"),
+            Some(("perception", purpose(None, "to search the desk")))
+        );
+        assert_eq!(
+            Purpose::split_shorthand("2d6+3 For 🔥 fire damage"),
+            Some(("2d6+3", purpose(Some("🔥"), "for fire damage")))
+        );
+        assert_eq!(Purpose::split_shorthand("2d6+3"), None);
+        assert_eq!(Purpose::split_shorthand("2d6 for 🔥"), None);
+    }
+
+    #[test]
+    fn test_split_natural_language() {
+        assert_eq!(
+            Purpose::split_natural_language("Roll a d20 to see if I can jump to the ledge"),
+            Some((
+                "Roll a d20",
+                purpose(None, "to see if I can jump to the ledge")
+            ))
+        );
+        assert_eq!(Purpose::split_natural_language("Roll for initiative"), None);
+    }
+}
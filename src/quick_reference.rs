@@ -0,0 +1,219 @@
+use crate::character::{AbilityName, Character, SkillName};
+use crate::character_roll::Check;
+use crate::passive::PassiveScore;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::id::{ChannelId, MessageId, UserId};
+use std::fmt;
+
+/// The values a player most often needs during play, shown in a pinned message so that they're
+/// always at hand.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct QuickReference {
+    pub armor_class: Option<i32>,
+    pub passive_perception: Option<i32>,
+    pub melee_attack_bonus: Option<i32>,
+    pub ranged_attack_bonus: Option<i32>,
+    /// The spell save DC and spell attack bonus for each of the character's spellcasting
+    /// abilities.
+    pub spellcasting: Vec<(AbilityName, i32, i32)>,
+}
+
+impl QuickReference {
+    pub fn new(character: &Character) -> QuickReference {
+        let proficiency_bonus = character.proficiency_bonus();
+        let attack_bonus = character.attack_bonus();
+        let strength = character.strength().map(|ability| ability.modifier);
+        let dexterity = character.dexterity().map(|ability| ability.modifier);
+        // Monks can use Dexterity for their unarmed strikes and monk weapons
+        let melee = if character.martial_arts() {
+            strength.max(dexterity)
+        } else {
+            strength
+        };
+
+        let mut spellcasting = Vec::new();
+        for class in character.classes() {
+            if let Some(ability) = class.spellcasting_ability() {
+                if spellcasting
+                    .iter()
+                    .any(|(existing, _, _)| *existing == ability)
+                {
+                    continue;
+                }
+                if let (Some(modifier), Some(proficiency_bonus)) = (
+                    character.ability(ability).map(|ability| ability.modifier),
+                    proficiency_bonus,
+                ) {
+                    let attack_bonus = proficiency_bonus + modifier;
+                    spellcasting.push((ability, 8 + attack_bonus, attack_bonus));
+                }
+            }
+        }
+
+        QuickReference {
+            armor_class: character.armor_class(),
+            passive_perception: PassiveScore::new(
+                Check::Skill(SkillName::Perception),
+                character,
+                &[],
+            )
+            .score,
+            melee_attack_bonus: melee
+                .and_then(|modifier| Some(modifier + proficiency_bonus? + attack_bonus)),
+            ranged_attack_bonus: dexterity
+                .and_then(|modifier| Some(modifier + proficiency_bonus? + attack_bonus)),
+            spellcasting,
+        }
+    }
+}
+
+impl fmt::Display for QuickReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        if let Some(armor_class) = self.armor_class {
+            lines.push(format!("🛡️ AC {}", armor_class));
+        }
+        if let Some(passive_perception) = self.passive_perception {
+            lines.push(format!("👁️ Passive Perception {}", passive_perception));
+        }
+        let attacks: Vec<String> = [
+            ("⚔️ Melee", self.melee_attack_bonus),
+            ("🏹 Ranged", self.ranged_attack_bonus),
+        ]
+        .iter()
+        .filter_map(|(name, bonus)| bonus.map(|bonus| format!("{} {:+}", name, bonus)))
+        .collect();
+        if !attacks.is_empty() {
+            lines.push(attacks.join(" · "));
+        }
+        for (ability, save_dc, attack_bonus) in self.spellcasting.iter() {
+            lines.push(format!(
+                "✨ Spell save DC {} · Spell attack {:+} ({})",
+                save_dc,
+                attack_bonus,
+                ability.as_str()
+            ));
+        }
+        if lines.is_empty() {
+            write!(
+                f,
+                "Nothing to show yet. Try setting some ability scores and a character level first."
+            )
+        } else {
+            lines.join("\n").fmt(f)
+        }
+    }
+}
+
+/// A quick reference message that has been pinned for a character, and what it currently shows.
+#[derive(Debug, Eq, PartialEq)]
+pub struct PinnedQuickReference {
+    pub user_id: UserId,
+    pub name: String,
+    pub message_id: MessageId,
+    /// The name of the user who pinned the message, which is shown if the character doesn't have
+    /// a display name.
+    pub author_name: String,
+    /// The text of the quick reference, as last shown in the message.
+    pub description: String,
+}
+
+impl PinnedQuickReference {
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Option<PinnedQuickReference>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection
+            .query_row(
+                "SELECT user_id, name, message_id, author_name, description \
+                 FROM character_quick_references \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3",
+                params,
+                PinnedQuickReference::from_row,
+            )
+            .optional()
+    }
+
+    /// Retrieve every pinned quick reference in a channel.
+    pub fn get_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<Vec<PinnedQuickReference>> {
+        let mut statement = connection.prepare(
+            "SELECT user_id, name, message_id, author_name, description \
+             FROM character_quick_references \
+             WHERE channel_id = $1",
+        )?;
+        let rows =
+            statement.query_map(&[&channel_id.to_string()], PinnedQuickReference::from_row)?;
+        rows.collect()
+    }
+
+    /// Record the pinned message for a character, replacing any previous one.
+    pub fn set(&self, connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &self.user_id.to_string(),
+            &self.name,
+            &self.message_id.to_string(),
+            &self.author_name,
+            &self.description,
+        ];
+        connection.execute(
+            "INSERT INTO character_quick_references (channel_id, user_id, name, message_id, author_name, description) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET \
+             message_id = $4, author_name = $5, description = $6",
+            params,
+        )
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<PinnedQuickReference> {
+        let user_id: String = row.get("user_id")?;
+        let message_id: String = row.get("message_id")?;
+        Ok(PinnedQuickReference {
+            user_id: UserId(user_id.parse().unwrap_or_default()),
+            name: row.get("name")?,
+            message_id: MessageId(message_id.parse().unwrap_or_default()),
+            author_name: row.get("author_name")?,
+            description: row.get("description")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let reference = QuickReference {
+            armor_class: Some(15),
+            passive_perception: Some(13),
+            melee_attack_bonus: Some(5),
+            ranged_attack_bonus: Some(-1),
+            spellcasting: vec![(AbilityName::Wisdom, 14, 6)],
+        };
+        assert_eq!(
+            reference.to_string(),
+            "🛡️ AC 15\n\
+             👁️ Passive Perception 13\n\
+             ⚔️ Melee +5 · 🏹 Ranged -1\n\
+             ✨ Spell save DC 14 · Spell attack +6 (Wisdom)"
+        );
+    }
+
+    #[test]
+    fn test_display_empty() {
+        assert!(QuickReference::default()
+            .to_string()
+            .starts_with("Nothing to show yet."));
+    }
+}
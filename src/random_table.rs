@@ -0,0 +1,401 @@
+use rand::Rng;
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::ChannelId;
+use std::error;
+use std::fmt;
+
+/// The most entries a random table can have.
+const MAXIMUM_ENTRIES: usize = 100;
+
+/// The most times an entry can be weighted.
+const MAXIMUM_WEIGHT: i32 = 100;
+
+/// How deeply rolls on tables referenced by other tables are followed, so that tables that refer to
+/// each other don't roll forever.
+const MAXIMUM_DEPTH: usize = 5;
+
+/// The most tables that can be rolled on at once, including the tables referenced by other tables,
+/// so that tables that each refer to others several times don't multiply into an enormous result.
+const MAXIMUM_ROLLS: usize = 50;
+
+lazy_static! {
+    /// A reference to another table in an entry, such as "{Fey Encounters}".
+    static ref REFERENCE_REGEX: Regex = Regex::new(r"\{([^{}]+)\}").unwrap();
+}
+
+/// A table the DM has defined for a channel, such as random encounters, wild magic surges, or
+/// critical hits, which can be rolled on by name.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RandomTable {
+    pub name: String,
+    pub entries: Vec<TableEntry>,
+}
+
+/// An entry in a random table, which is as likely to come up as its weight. An entry can refer to
+/// another table by name, such as "{Fey Encounters}", which is rolled on when the entry comes up.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TableEntry {
+    pub weight: i32,
+    pub text: String,
+}
+
+/// The result of rolling on a random table, including the rolls on any tables it referred to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TableRollResult {
+    pub table: String,
+    /// The roll, from 1 to the total weight of the table's entries.
+    pub roll: i32,
+    pub total_weight: i32,
+    /// The entry that came up, with the results from any tables it referred to filled in.
+    pub text: String,
+    pub nested: Vec<TableRollResult>,
+}
+
+/// Represents an error that might occur when rolling on a random table.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The tables referred to more tables than can be rolled on at once, which is given with the
+    /// error.
+    TooManyRolls(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::TooManyRolls(limit) => write!(
+                f,
+                "Can't roll on more than {} tables at once, including the tables referred to by other tables.",
+                limit
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+impl RandomTable {
+    /// Parse a random table from a name and a list of entries separated by semicolons or new lines,
+    /// where each entry can be weighted, such as "Forest Encounters: 3x 1d4 goblins; 2x a pack of
+    /// wolves; {Fey Encounters}".
+    pub fn parse(string: &str) -> Option<RandomTable> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"^(?s)([^:{}]+?) *: *(.+)$").unwrap();
+            static ref SEPARATOR_REGEX: Regex = Regex::new(r" *[;\n] *").unwrap();
+            static ref WEIGHT_REGEX: Regex = Regex::new(r"^(?i)(\d+) *[x×] +(.+)$").unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        let name = captures.get(1)?.as_str().trim();
+        let entries = SEPARATOR_REGEX
+            .split(captures.get(2)?.as_str().trim())
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| match WEIGHT_REGEX.captures(entry.trim()) {
+                Some(captures) => Some(TableEntry {
+                    weight: captures
+                        .get(1)?
+                        .as_str()
+                        .parse()
+                        .ok()
+                        .filter(|weight| *weight >= 1 && *weight <= MAXIMUM_WEIGHT)?,
+                    text: captures.get(2)?.as_str().trim().to_owned(),
+                }),
+                None => Some(TableEntry {
+                    weight: 1,
+                    text: entry.trim().to_owned(),
+                }),
+            })
+            .collect::<Option<Vec<TableEntry>>>()?;
+        if name.is_empty() || entries.is_empty() || entries.len() > MAXIMUM_ENTRIES {
+            None
+        } else {
+            Some(RandomTable {
+                name: name.to_owned(),
+                entries,
+            })
+        }
+    }
+
+    /// Retrieve the random tables for a channel, in alphabetical order of name.
+    pub fn get_all(
+        connection: &Connection,
+        channel_id: ChannelId,
+    ) -> RusqliteResult<Vec<RandomTable>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string()];
+        let mut statement = connection.prepare(
+            "SELECT table_name, weight, text FROM random_table_entries \
+             WHERE channel_id = $1 \
+             ORDER BY table_name, position",
+        )?;
+        let rows = statement.query_map(params, RandomTable::from_row)?;
+        let mut tables: Vec<RandomTable> = Vec::new();
+        for row in rows {
+            let (name, entry) = row?;
+            match tables.last_mut() {
+                Some(table) if table.name.eq_ignore_ascii_case(&name) => table.entries.push(entry),
+                _ => tables.push(RandomTable {
+                    name,
+                    entries: vec![entry],
+                }),
+            }
+        }
+        Ok(tables)
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<(String, TableEntry)> {
+        Ok((
+            row.get("table_name")?,
+            TableEntry {
+                weight: row.get("weight")?,
+                text: row.get("text")?,
+            },
+        ))
+    }
+
+    /// Save the random table for a channel, replacing any table with the same name.
+    pub fn set(&self, connection: &mut Connection, channel_id: ChannelId) -> RusqliteResult<()> {
+        let transaction = connection.transaction()?;
+        self.replace(&transaction, channel_id)?;
+        transaction.commit()
+    }
+
+    /// Replace any table with the same name, as part of a transaction that's already been
+    /// started.
+    pub fn replace(&self, connection: &Connection, channel_id: ChannelId) -> RusqliteResult<()> {
+        RandomTable::delete(connection, channel_id, &self.name)?;
+        for (position, entry) in self.entries.iter().enumerate() {
+            let params: &[&dyn ToSql] = &[
+                &channel_id.to_string(),
+                &self.name,
+                &(position as i64),
+                &entry.weight,
+                &entry.text,
+            ];
+            connection.execute(
+                "INSERT INTO random_table_entries (channel_id, table_name, position, weight, text) \
+                 VALUES ($1, $2, $3, $4, $5)",
+                params,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Check a table that wasn't parsed from a message, such as one in an imported file, against
+    /// the same limits as a parsed table. Returns a description of each problem with the table.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let name = self.name.trim();
+        if name.is_empty() || name.contains(|c| c == ':' || c == '{' || c == '}') {
+            errors.push(format!(
+                "\"{}\" needs a name without colons or braces",
+                self.name
+            ));
+        }
+        if self.entries.is_empty() || self.entries.len() > MAXIMUM_ENTRIES {
+            errors.push(format!(
+                "{} needs from 1 to {} entries",
+                self.name, MAXIMUM_ENTRIES
+            ));
+        }
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.weight < 1 || entry.weight > MAXIMUM_WEIGHT)
+        {
+            errors.push(format!(
+                "{} has an entry with a weight that isn't from 1 to {}",
+                self.name, MAXIMUM_WEIGHT
+            ));
+        }
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.text.trim().is_empty())
+        {
+            errors.push(format!("{} has an empty entry", self.name));
+        }
+        errors
+    }
+
+    /// Delete the random table with a name. Returns false if there's no table with the name.
+    pub fn delete(
+        connection: &Connection,
+        channel_id: ChannelId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &name.trim()];
+        connection
+            .execute(
+                "DELETE FROM random_table_entries WHERE channel_id = $1 AND table_name = $2",
+                params,
+            )
+            .map(|deleted| deleted > 0)
+    }
+
+    /// The total weight of the table's entries, which is the size of the die rolled on it.
+    pub fn total_weight(&self) -> i32 {
+        self.entries.iter().map(|entry| entry.weight).sum()
+    }
+
+    /// Roll on the table, along with any of the tables that the entry that comes up refers to.
+    /// References to tables that don't exist are left as they are. Fails if the roll would roll on
+    /// more tables than can be rolled on at once.
+    pub fn roll<R: Rng + ?Sized>(
+        &self,
+        tables: &[RandomTable],
+        rng: &mut R,
+    ) -> Result<TableRollResult, Error> {
+        let mut remaining_rolls = MAXIMUM_ROLLS;
+        self.roll_at_depth(tables, rng, 0, &mut remaining_rolls)
+    }
+
+    /// Roll on the table, counting the roll and any rolls on the tables it refers to against the
+    /// rolls remaining, which are shared by every table rolled on at once.
+    fn roll_at_depth<R: Rng + ?Sized>(
+        &self,
+        tables: &[RandomTable],
+        rng: &mut R,
+        depth: usize,
+        remaining_rolls: &mut usize,
+    ) -> Result<TableRollResult, Error> {
+        *remaining_rolls = remaining_rolls
+            .checked_sub(1)
+            .ok_or(Error::TooManyRolls(MAXIMUM_ROLLS))?;
+        let total_weight = self.total_weight();
+        let roll = rng.gen_range(1, total_weight + 1);
+        let mut remaining = roll;
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| {
+                remaining -= entry.weight;
+                remaining <= 0
+            })
+            .map_or("", |entry| entry.text.as_str());
+        let mut nested = Vec::new();
+        let mut text = String::new();
+        let mut position = 0;
+        if depth < MAXIMUM_DEPTH {
+            for captures in REFERENCE_REGEX.captures_iter(entry) {
+                if let Some(table) = find(tables, &captures[1]) {
+                    let reference = captures.get(0).unwrap();
+                    let result = table.roll_at_depth(tables, rng, depth + 1, remaining_rolls)?;
+                    text.push_str(&entry[position..reference.start()]);
+                    text.push_str(&result.text);
+                    position = reference.end();
+                    nested.push(result);
+                }
+            }
+        }
+        text.push_str(&entry[position..]);
+        Ok(TableRollResult {
+            table: self.name.clone(),
+            roll,
+            total_weight,
+            text,
+            nested,
+        })
+    }
+}
+
+/// Find a random table by name, ignoring case and a leading "the" or trailing "table".
+pub fn find<'a>(tables: &'a [RandomTable], name: &str) -> Option<&'a RandomTable> {
+    lazy_static! {
+        static ref NAME_REGEX: Regex = Regex::new(r"^(?i)(?:the +)?(.+?)(?: +table)?$").unwrap();
+    }
+    let name = name.trim();
+    tables
+        .iter()
+        .find(|table| table.name.eq_ignore_ascii_case(name))
+        .or_else(|| {
+            let name = NAME_REGEX.captures(name)?.get(1)?.as_str();
+            tables
+                .iter()
+                .find(|table| table.name.eq_ignore_ascii_case(name))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    fn table(string: &str) -> RandomTable {
+        RandomTable::parse(string).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            RandomTable::parse("Forest Encounters: 3x 1d4 goblins; 2x a pack of wolves; {Fey}"),
+            Some(RandomTable {
+                name: "Forest Encounters".to_owned(),
+                entries: vec![
+                    TableEntry {
+                        weight: 3,
+                        text: "1d4 goblins".to_owned()
+                    },
+                    TableEntry {
+                        weight: 2,
+                        text: "a pack of wolves".to_owned()
+                    },
+                    TableEntry {
+                        weight: 1,
+                        text: "{Fey}".to_owned()
+                    },
+                ]
+            })
+        );
+        assert_eq!(
+            RandomTable::parse("Wild Magic:\nYou turn blue\nYou levitate;")
+                .map(|table| table.entries.len()),
+            Some(2)
+        );
+        assert_eq!(RandomTable::parse("Forest Encounters: 0x goblins"), None);
+        assert_eq!(RandomTable::parse("Forest Encounters:"), None);
+        assert_eq!(RandomTable::parse("Forest Encounters"), None);
+    }
+
+    #[test]
+    fn test_roll() {
+        let tables = vec![
+            table("Fey: a pixie; a dryad"),
+            table("Forest: 3x goblins; 2x wolves"),
+            table("Ambush: {Fey} and {Giants}"),
+            table("Loop: {Loop}"),
+            table("Swarm: {Swarm} {Swarm}"),
+        ];
+        let forest = find(&tables, "the forest table").unwrap();
+        assert_eq!(forest.total_weight(), 5);
+        let result = forest.roll(&tables, &mut StepRng::new(0, 0)).unwrap();
+        assert_eq!(result.roll, 1);
+        assert_eq!(result.text, "goblins");
+        let result = find(&tables, "AMBUSH")
+            .unwrap()
+            .roll(&tables, &mut StepRng::new(0, 0))
+            .unwrap();
+        assert_eq!(result.text, "a pixie and {Giants}");
+        assert_eq!(result.nested.len(), 1);
+        assert_eq!(result.nested[0].table, "Fey");
+        let result = find(&tables, "loop")
+            .unwrap()
+            .roll(&tables, &mut StepRng::new(0, 0))
+            .unwrap();
+        assert_eq!(result.text, "{Loop}");
+        // Each roll on the swarm rolls on it twice more, which would be 63 rolls by the deepest level
+        assert_eq!(
+            find(&tables, "swarm")
+                .unwrap()
+                .roll(&tables, &mut StepRng::new(0, 0)),
+            Err(Error::TooManyRolls(MAXIMUM_ROLLS))
+        );
+    }
+}
@@ -1,9 +1,59 @@
-use crate::attack_roll::Handedness;
-use crate::character_roll::Check;
+use crate::attack_roll::{
+    Attack, AttackPart, AttackTarget, Cover, Handedness, MasteryEffect, SmiteDamage,
+};
+use crate::audit::AuditEntry;
+use crate::bestiary::{StatBlock, ABILITY_ABBREVIATIONS};
+use crate::bonus::Bonus;
+use crate::channel::Channel;
+use crate::channel_export;
+use crate::character::AbilityName;
+use crate::character_effect::{self, CharacterEffect, EffectRoll};
+use crate::character_roll::{Check, Contest, ContestRoll, GroupSave};
+use crate::character_sheet::CharacterSheet;
+use crate::class::{ordinal, ClassLevel, SpellSlots};
+use crate::combatant::{self, HitPoints};
+use crate::companion::Companion;
+use crate::content_pack::ContentPack;
+use crate::counter::Counter;
+use crate::currency::{Coins, Transaction, DENOMINATIONS};
+use crate::dungeon::{ContentsKind, Dungeon};
+use crate::edition::Edition;
+use crate::encounter::{Difficulty, DifficultyRating, EncounterSummary};
 use crate::error::Error;
-use crate::roll::{Condition, ConditionalRoll, ConditionalRollResult, Roll, RollResult};
-use serenity::builder::CreateMessage;
+use crate::experience::ExperienceAward;
+use crate::guild::Guild;
+use crate::hidden_roll::HiddenRoll;
+use crate::house_rule::{HouseRule, HouseRules};
+use crate::initiative::InitiativeOrder;
+use crate::lore::Lore;
+use crate::monster::{Monster, MonsterGroup, QuickStatBlock};
+use crate::note::Note;
+use crate::ongoing_effect::{EffectKind, EffectOutcome, OngoingEffectResult};
+use crate::passive::PassiveScore;
+use crate::permission_check::PermissionCheck;
+use crate::purpose::Purpose;
+use crate::quick_reference::QuickReference;
+use crate::random_table::{RandomTable, TableRollResult};
+use crate::roll::{Condition, ConditionalRoll, ConditionalRollResult, Limits, Roll, RollResult};
+use crate::roll_history::RollHistoryEntry;
+use crate::roll_signature::SignedRoll;
+use crate::settlement::{Settlement, Tavern};
+use crate::stash::{self, Loot, Stash};
+use crate::status::Status;
+use crate::tie_break::Tie;
+use crate::time;
+use crate::time::TimeZone;
+use crate::travel::{self, Journey, HOURS_PER_DAY};
+use crate::treasure::TreasureResult;
+use crate::weapon::DamageType;
+use crate::weather::Weather;
+use crate::wild_shape::WildShape;
+use serde_json::{json, Value};
+use serenity::builder::{CreateEmbed, CreateMessage};
+use serenity::http::AttachmentType;
 use serenity::model::channel::Message;
+use serenity::model::id::UserId;
+use std::borrow::Cow;
 
 pub enum Response {
     AttackRoll {
@@ -13,20 +63,299 @@ pub enum Response {
         to_hit_result: ConditionalRollResult,
         damage_roll: Roll,
         damage_result: RollResult,
+        /// Which of the attack roll and the damage roll to show.
+        part: AttackPart,
+        /// The cover the target is behind, which is included in the target's armour class.
+        cover: Option<Cover>,
+        /// Whether the attack deals damage, which a net doesn't.
+        deals_damage: bool,
+        /// The type of damage the attack deals, if it's known.
+        damage_type: Option<DamageType>,
+        /// The dice rolled for the effects on the character that add to the attack roll.
+        effects: Vec<EffectRoll>,
+        /// The radiant damage of a Divine Smite, if the character smote with the attack.
+        smite: Option<SmiteDamage>,
+        /// Whether the attack hit, if it was made against a combatant.
+        hit: Option<bool>,
+        /// The combatant the attack was made against, if any.
+        target: Option<AttackTarget>,
+        /// The mastery property of the weapon, if the character has mastered it.
+        mastery: Option<MasteryEffect>,
+        /// The rules for the weapon's special property, such as a lance's or a net's.
+        special: Option<&'static str>,
+        /// Whether the character gained Heroic Inspiration from rolling a natural 1.
+        heroic_inspiration: bool,
+        statuses: Vec<Status>,
         avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    /// Several attacks made with a single command, such as with Extra Attack.
+    AttackRolls {
+        attack_name: String,
+        attack_handedness: Option<Handedness>,
+        to_hit_roll: ConditionalRoll,
+        attacks: Vec<Attack>,
+        damage_type: Option<DamageType>,
+        target: Option<AttackTarget>,
+        cover: Option<Cover>,
+        mastery: Option<MasteryEffect>,
+        special: Option<&'static str>,
+        heroic_inspiration: bool,
+        statuses: Vec<Status>,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
     },
     CharacterRoll {
         check: Check,
         roll: ConditionalRoll,
         result: ConditionalRollResult,
+        /// The dice rolled for the effects on the character that add to the roll.
+        effects: Vec<EffectRoll>,
+        /// Whether the character gained Heroic Inspiration from rolling a natural 1.
+        heroic_inspiration: bool,
+        statuses: Vec<Status>,
+        purpose: Option<Purpose>,
         avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    SneakAttackRoll {
+        roll: Roll,
+        result: RollResult,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    SpellRoll {
+        spell_name: String,
+        /// The level of the spell slot used, or None for a cantrip.
+        level: Option<i32>,
+        roll: Roll,
+        result: RollResult,
+        damage_type: Option<DamageType>,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
     },
     DiceRoll {
         roll: ConditionalRoll,
         result: ConditionalRollResult,
+        purpose: Option<Purpose>,
+        avatar_url: Option<String>,
+    },
+    TableRoll {
+        roll: ConditionalRoll,
+        results: Vec<(UserId, String, ConditionalRollResult, Option<Tie>)>,
+    },
+    GroupSavingThrow {
+        ability: AbilityName,
+        dc: i32,
+        saves: Vec<GroupSave>,
+    },
+    /// Treasure rolled from the treasure tables.
+    Treasure(TreasureResult),
+    /// The weather rolled for a day.
+    Weather(Weather),
+    /// How far the party can travel, and how long it takes.
+    Travel(Journey),
+    /// A randomly generated dungeon, with a map.
+    Dungeon(Dungeon),
+    /// A randomly generated tavern.
+    Tavern(Tavern),
+    /// A sketch of a randomly generated settlement.
+    Settlement(Settlement),
+    /// A roll on one of the channel's random tables.
+    RandomTableRoll(TableRollResult),
+    /// The entries in one of the channel's random tables, which only the DM can see.
+    RandomTable(RandomTable),
+    RandomTables(Vec<RandomTable>),
+    /// A contest between two characters, such as one grappling the other.
+    ContestedCheck {
+        contest: Contest,
+        attacker: ContestRoll,
+        defender: ContestRoll,
+    },
+    Coins {
+        transaction: Option<Transaction>,
+        coins: Coins,
         avatar_url: Option<String>,
     },
+    PartyCoins {
+        coins: Vec<(UserId, String, Coins)>,
+    },
+    /// The party's shared stash, after something was put in it or taken out, if anything.
+    Stash {
+        transaction: Option<stash::Transaction>,
+        stash: Stash,
+    },
+    ExperienceAward {
+        amount: i32,
+        /// What the experience points were awarded for, such as "the ogre fight".
+        reason: Option<String>,
+        share: i32,
+        awards: Vec<ExperienceAward>,
+    },
+    EncounterSummary {
+        summary: EncounterSummary,
+        /// Each party member's share of the encounter's experience points, if they were awarded.
+        share: i32,
+        awards: Vec<ExperienceAward>,
+    },
+    EncounterDifficulty {
+        difficulty: Difficulty,
+        monsters: Vec<MonsterGroup>,
+        /// The levels of the characters in the party.
+        levels: Vec<i32>,
+    },
+    QuickStatBlock(QuickStatBlock),
+    Monster(Monster),
+    Monsters(Vec<Monster>),
+    /// A monster's stat block from the SRD.
+    StatBlock(&'static StatBlock),
+    /// The beast forms a character can take with Wild Shape, and the form they're in, if any.
+    WildShapes {
+        forms: Vec<String>,
+        wild_shape: Option<WildShape>,
+    },
+    /// The monster packs installed for a guild.
+    ContentPacks(Vec<ContentPack>),
+    /// A roll made with the advantage from the character's inspiration.
+    InspiredRoll(Box<Response>),
+    /// A roll with a Bardic Inspiration die added to it.
+    BardicInspiredRoll {
+        roll: Box<Response>,
+        die: Roll,
+        result: RollResult,
+    },
+    /// A knowledge check about a subject, with the lore it revealed if it succeeded.
+    LoreCheck {
+        roll: Box<Response>,
+        subject: String,
+        lore: Option<String>,
+    },
+    /// The lore for a channel. Hidden lore and the checks needed to recall it are only shown to the
+    /// DM.
+    Lore {
+        lore: Vec<Lore>,
+        is_dm: bool,
+    },
+    /// The most recent changes to characters, newest first.
+    CharacterAudit(Vec<AuditEntry>),
+    /// The user's companions, such as familiars and steel defenders.
+    Companions(Vec<Companion>),
+    /// The most recent notes in a channel, oldest first, and the text they were searched for, if
+    /// any.
+    Notes(Vec<Note>, Option<String>),
+    /// The responses to each of the commands in a message with several commands, along with the
+    /// descriptions of the commands that weren't run because an earlier command failed.
+    Composite {
+        responses: Vec<Response>,
+        skipped: Vec<String>,
+    },
+    InitiativeOrder(InitiativeOrder),
+    TurnStart {
+        round: i32,
+        name: String,
+        /// The user whose active character is taking the turn, who is pinged to let them know.
+        user_id: Option<UserId>,
+        effects: Vec<OngoingEffectResult>,
+        /// The combatant's hit points after the effects, if their hit points are being tracked.
+        hit_points: Option<HitPoints>,
+        /// Whether the effects left the combatant bloodied, at half their hit points or fewer.
+        bloodied: bool,
+        /// Whether the effects dropped the combatant to 0 hit points, removing them from the
+        /// initiative order.
+        defeated: bool,
+        /// The effects on characters that ran out at the end of the last round, as pairs of
+        /// character name and effect name.
+        ended_effects: Vec<(String, String)>,
+        /// The statuses on characters that ran out at the end of the last round, as pairs of
+        /// character name and status.
+        ended_statuses: Vec<(String, Status)>,
+    },
+    SpellSlots {
+        classes: Vec<ClassLevel>,
+        slots: SpellSlots,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    Statuses {
+        statuses: Vec<Status>,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    Bonuses {
+        bonuses: Vec<Bonus>,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    Counters {
+        counters: Vec<Counter>,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    CharacterEffects {
+        effects: Vec<CharacterEffect>,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    CharacterSheet {
+        sheet: CharacterSheet,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    /// One or more of a character's passive scores. A single score is shown on its own, and
+    /// several are listed under the abilities they use.
+    PassiveScores {
+        scores: Vec<PassiveScore>,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    QuickReference {
+        /// The name the character is stored under, used to keep the pinned message up to date.
+        name: String,
+        reference: QuickReference,
+        avatar_url: Option<String>,
+        character_name: Option<String>,
+    },
+    CharacterExport {
+        file_name: String,
+        json: String,
+    },
+    /// A channel's configuration, to import into another channel.
+    ChannelExport(String),
+    /// Which of the permissions that Dungeon Helper uses it has in the channel.
+    PermissionChecks(Vec<PermissionCheck>),
+    /// The settings that server administrators can change in the channel.
+    ChannelSettings(Channel),
+    /// The settings that channels in the server start with.
+    ServerDefaults(Guild),
+    /// The house rules used in the channel, along with the other settings that change how rolls
+    /// are made in it.
+    HouseRules {
+        house_rules: HouseRules,
+        limits: Limits,
+        edition: Edition,
+    },
+    /// The training data for the natural language parser, with the number of utterances in it.
+    TrainingData {
+        count: usize,
+        json: String,
+    },
+    /// A roll from the roll history whose code has been verified.
+    /// A genuine roll, with the time zone of the user who asked to verify it.
+    VerifiedRoll(SignedRoll, TimeZone),
+    /// A roll into the dice cup, whose result is sealed until it's revealed.
+    HiddenRoll {
+        roll: ConditionalRoll,
+        code: String,
+    },
+    RevealedRolls(Vec<HiddenRoll>),
+    /// A roll whose result is only sent to the channel's DM, so the channel just sees that it
+    /// was rolled.
+    BlindRoll {
+        roll: Box<Response>,
+        dm_user_id: UserId,
+    },
     Clarification(String),
+    Confirmation(String),
     Error(Error),
     Help(String),
     Warning(String),
@@ -36,12 +365,243 @@ impl Response {
     pub fn is_roll(&self) -> bool {
         match self {
             Response::AttackRoll { .. }
+            | Response::AttackRolls { .. }
             | Response::CharacterRoll { .. }
-            | Response::DiceRoll { .. } => true,
+            | Response::SneakAttackRoll { .. }
+            | Response::SpellRoll { .. }
+            | Response::DiceRoll { .. }
+            | Response::TableRoll { .. }
+            | Response::GroupSavingThrow { .. }
+            | Response::ContestedCheck { .. } => true,
+            Response::InspiredRoll(response) => response.is_roll(),
+            Response::BardicInspiredRoll { roll, .. } => roll.is_roll(),
+            Response::LoreCheck { roll, .. } => roll.is_roll(),
+            Response::BlindRoll { roll, .. } => roll.is_roll(),
+            Response::Composite { responses, .. } => responses.iter().all(Response::is_roll),
             _ => false,
         }
     }
 
+    /// Whether the character gained Heroic Inspiration from rolling a natural 1 on the d20.
+    pub fn gains_heroic_inspiration(&self) -> bool {
+        match self {
+            Response::AttackRoll {
+                heroic_inspiration, ..
+            }
+            | Response::AttackRolls {
+                heroic_inspiration, ..
+            }
+            | Response::CharacterRoll {
+                heroic_inspiration, ..
+            } => *heroic_inspiration,
+            Response::InspiredRoll(response) => response.gains_heroic_inspiration(),
+            Response::BardicInspiredRoll { roll, .. } => roll.gains_heroic_inspiration(),
+            Response::LoreCheck { roll, .. } => roll.gains_heroic_inspiration(),
+            _ => false,
+        }
+    }
+
+    /// Whether the command failed, so that the commands after it in a message with several
+    /// commands shouldn't be run.
+    pub fn is_failure(&self) -> bool {
+        match self {
+            Response::Error(_) | Response::Warning(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The entries to record in the roll history, which includes every roll in a message with
+    /// several commands.
+    pub fn roll_history_entries(&self) -> Vec<RollHistoryEntry> {
+        match self {
+            Response::Composite { responses, .. } => responses
+                .iter()
+                .flat_map(Response::roll_history_entries)
+                .collect(),
+            Response::AttackRolls {
+                attack_name,
+                to_hit_roll,
+                attacks,
+                character_name,
+                ..
+            } => attacks
+                .iter()
+                .enumerate()
+                .map(|(index, attack)| RollHistoryEntry {
+                    character_name: character_name.clone(),
+                    description: format!(
+                        "Attack {} of {} using {} ({}){}",
+                        index + 1,
+                        attacks.len(),
+                        attack_name,
+                        to_hit_roll,
+                        effects_description(&attack.effects)
+                    ),
+                    result: attack.to_hit_result.result()
+                        + character_effect::total(&attack.effects),
+                    tag: None,
+                    purpose: None,
+                })
+                .collect(),
+            Response::GroupSavingThrow { ability, dc, saves } => saves
+                .iter()
+                .filter_map(|save| {
+                    let (roll, _) = save.roll.as_ref()?;
+                    Some(RollHistoryEntry {
+                        character_name: Some(save.name.clone()),
+                        description: format!(
+                            "DC {} {} saving throw for everyone ({}){}",
+                            dc,
+                            ability.as_str(),
+                            roll,
+                            effects_description(&save.effects)
+                        ),
+                        result: save.total()?,
+                        tag: None,
+                        purpose: None,
+                    })
+                })
+                .collect(),
+            Response::ContestedCheck {
+                contest,
+                attacker,
+                defender,
+            } => [attacker, defender]
+                .iter()
+                .map(|contest_roll| RollHistoryEntry {
+                    character_name: Some(contest_roll.name.clone()),
+                    description: format!(
+                        "{} check to {} ({}){}",
+                        contest_roll.check,
+                        contest.as_str(),
+                        contest_roll.roll,
+                        effects_description(&contest_roll.effects)
+                    ),
+                    result: contest_roll.total(),
+                    tag: None,
+                    purpose: None,
+                })
+                .collect(),
+            Response::InspiredRoll(response) => response.roll_history_entries(),
+            Response::RevealedRolls(rolls) => rolls
+                .iter()
+                .map(|roll| RollHistoryEntry {
+                    character_name: None,
+                    description: format!("Hidden roll {} (code {})", roll.roll, roll.code),
+                    result: roll.result,
+                    tag: None,
+                    purpose: None,
+                })
+                .collect(),
+            _ => self.roll_history_entry().into_iter().collect(),
+        }
+    }
+
+    /// The entry to record in the roll history for a roll, which is made from what was shown to
+    /// the user so that the history matches the channel.
+    pub fn roll_history_entry(&self) -> Option<RollHistoryEntry> {
+        match self {
+            Response::AttackRoll {
+                attack_name,
+                damage_roll,
+                damage_result,
+                part: AttackPart::Damage { .. },
+                character_name,
+                ..
+            } => Some(RollHistoryEntry {
+                character_name: character_name.clone(),
+                description: format!("Damage using {} ({})", attack_name, damage_roll),
+                result: damage_result.result(),
+                tag: None,
+                purpose: None,
+            }),
+            Response::AttackRoll {
+                attack_name,
+                to_hit_roll,
+                to_hit_result,
+                effects,
+                character_name,
+                ..
+            } => Some(RollHistoryEntry {
+                character_name: character_name.clone(),
+                description: format!(
+                    "Attack using {} ({}){}",
+                    attack_name,
+                    to_hit_roll,
+                    effects_description(effects)
+                ),
+                result: to_hit_result.result() + character_effect::total(effects),
+                tag: None,
+                purpose: None,
+            }),
+            Response::CharacterRoll {
+                check,
+                roll,
+                result,
+                effects,
+                purpose,
+                character_name,
+                ..
+            } => Some(RollHistoryEntry {
+                character_name: character_name.clone(),
+                description: format!("{} ({}){}", check, roll, effects_description(effects)),
+                result: result.result() + character_effect::total(effects),
+                tag: purpose.as_ref().and_then(|purpose| purpose.tag.clone()),
+                purpose: purpose.as_ref().map(|purpose| purpose.text.clone()),
+            }),
+            Response::SneakAttackRoll {
+                roll,
+                result,
+                character_name,
+                ..
+            } => Some(RollHistoryEntry {
+                character_name: character_name.clone(),
+                description: format!("Sneak attack ({})", roll),
+                result: result.result(),
+                tag: None,
+                purpose: None,
+            }),
+            Response::SpellRoll {
+                spell_name,
+                level,
+                roll,
+                result,
+                character_name,
+                ..
+            } => Some(RollHistoryEntry {
+                character_name: character_name.clone(),
+                description: format!("{}{} ({})", spell_name, spell_level_message(*level), roll),
+                result: result.result(),
+                tag: None,
+                purpose: None,
+            }),
+            Response::DiceRoll {
+                roll,
+                result,
+                purpose,
+                ..
+            } => Some(RollHistoryEntry {
+                character_name: None,
+                description: roll.to_string(),
+                result: result.result(),
+                tag: purpose.as_ref().and_then(|purpose| purpose.tag.clone()),
+                purpose: purpose.as_ref().map(|purpose| purpose.text.clone()),
+            }),
+            // Only the first of several attacks has a single entry, such as for Bardic Inspiration
+            Response::AttackRolls { .. } => self.roll_history_entries().into_iter().next(),
+            Response::InspiredRoll(response) => response.roll_history_entry(),
+            Response::BardicInspiredRoll { roll, die, result } => {
+                roll.roll_history_entry().map(|entry| RollHistoryEntry {
+                    description: format!("{} + {} Bardic Inspiration", entry.description, die),
+                    result: entry.result + result.result(),
+                    ..entry
+                })
+            }
+            Response::LoreCheck { roll, .. } => roll.roll_history_entry(),
+            _ => None,
+        }
+    }
+
     pub fn to_message<'a, 'b>(
         &self,
         author_nick: &str,
@@ -56,25 +616,229 @@ impl Response {
                 to_hit_result,
                 damage_roll,
                 damage_result,
+                part,
+                cover,
+                deals_damage,
+                damage_type,
+                effects,
+                smite,
+                hit,
+                target,
+                mastery,
+                special,
+                heroic_inspiration,
+                statuses,
                 avatar_url,
+                character_name,
             } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                let rolls_to_hit = part.rolls_to_hit();
+                let deals_damage = *deals_damage && part.rolls_damage();
+                let condition = if rolls_to_hit {
+                    conditional_message(to_hit_roll.condition())
+                } else {
+                    ""
+                };
+                let damage_type = damage_type
+                    .map(|damage_type| format!(" {}", damage_type))
+                    .unwrap_or_else(String::new);
+                let attack_handedness = match attack_handedness {
+                    Some(Handedness::OneHanded) => " one handed",
+                    Some(Handedness::TwoHanded) => " two handed",
+                    Some(Handedness::OffHand) => " off-handed",
+                    None => "",
+                };
+                builder.embed(|e| {
+                    let action = match part {
+                        AttackPart::Both => "attacks",
+                        AttackPart::ToHit => "rolls to hit",
+                        AttackPart::Damage {
+                            critical_hit: false,
+                        } => "rolls damage",
+                        AttackPart::Damage { critical_hit: true } => "rolls critical hit damage",
+                    };
+                    e.title(format!(
+                        "{} {}{}{} using {}{}!",
+                        author_nick,
+                        action,
+                        target_name(target),
+                        attack_handedness,
+                        attack_name,
+                        condition
+                    ));
+                    if rolls_to_hit {
+                        e.field("Attack", format!("🛡️ {}", to_hit_result), true);
+                    }
+                    if let (Some(hit), Some(target)) = (hit, target) {
+                        e.field(
+                            format!("Against AC {}", target.armor_class),
+                            hit_message(*hit),
+                            true,
+                        );
+                    }
+                    if !effects.is_empty() {
+                        e.field(
+                            "Attack Total",
+                            format!(
+                                "🛡️ **{}**",
+                                to_hit_result.result() + character_effect::total(effects)
+                            ),
+                            true,
+                        );
+                    }
+                    if deals_damage {
+                        e.field(
+                            "Damage",
+                            format!("❤️ {}{}", damage_result, damage_type),
+                            true,
+                        );
+                    }
+                    if let Some(smite) = smite {
+                        e.field(
+                            format!("Divine Smite ({} level)", ordinal(smite.level)),
+                            format!("✨ {} radiant", smite.result),
+                            true,
+                        );
+                    }
+                    if !effects.is_empty() {
+                        e.field("Effects", effect_list(effects), false);
+                    }
+                    if let (Some(cover), true) = (cover, rolls_to_hit) {
+                        e.field("Cover", cover_message(*cover, target), false);
+                    }
+                    if let Some(message) = target.as_ref().and_then(target_message) {
+                        e.field("Target", message, false);
+                    }
+                    if let Some(mastery) = mastery {
+                        e.field("Mastery", mastery, false);
+                    }
+                    if let Some(special) = special {
+                        e.field("Special", special, false);
+                    }
+                    if *heroic_inspiration {
+                        e.field("Heroic Inspiration", heroic_inspiration_message(author_nick), false);
+                    }
+                    if !statuses.is_empty() && rolls_to_hit {
+                        e.field("Conditions", status_list(statuses), false);
+                    }
+                    let to_hit_roll = if rolls_to_hit {
+                        format!("Attack Roll: {}", to_hit_roll)
+                    } else {
+                        String::new()
+                    };
+                    let damage_roll = match (rolls_to_hit, deals_damage) {
+                        (true, true) => format!(" | Damage Roll: {}", damage_roll),
+                        (false, true) => format!("Damage Roll: {}", damage_roll),
+                        (_, false) => String::new(),
+                    };
+                    e.footer(|f| {
+                        f.text(format!(
+                            "{}{}{}",
+                            to_hit_roll,
+                            damage_roll,
+                            smite.as_ref().map_or_else(String::new, |smite| format!(
+                                " | Smite Roll: {}",
+                                smite.roll
+                            ))
+                        ))
+                    });
+
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::AttackRolls {
+                attack_name,
+                attack_handedness,
+                to_hit_roll,
+                attacks,
+                damage_type,
+                target,
+                cover,
+                mastery,
+                special,
+                heroic_inspiration,
+                statuses,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
                 let condition = conditional_message(to_hit_roll.condition());
+                let damage_type = damage_type
+                    .map(|damage_type| format!(" {}", damage_type))
+                    .unwrap_or_else(String::new);
                 let attack_handedness = match attack_handedness {
                     Some(Handedness::OneHanded) => " one handed",
                     Some(Handedness::TwoHanded) => " two handed",
+                    Some(Handedness::OffHand) => " off-handed",
                     None => "",
                 };
                 builder.embed(|e| {
                     e.title(format!(
-                        "{} attacks{} using {}{}!",
-                        author_nick, attack_handedness, attack_name, condition
+                        "{} attacks{} {} times{} using {}{}!",
+                        author_nick,
+                        target_name(target),
+                        attacks.len(),
+                        attack_handedness,
+                        attack_name,
+                        condition
                     ));
-                    e.field("Attack", format!("🛡️ {}", to_hit_result), true);
-                    e.field("Damage", format!("❤️ {}", damage_result), true);
+                    for (index, attack) in attacks.iter().enumerate() {
+                        let mut value = match attack.hit {
+                            Some(hit) => format!("{}\n", hit_message(hit)),
+                            None => String::new(),
+                        };
+                        value.push_str(&format!("🛡️ {}", attack.to_hit_result));
+                        if !attack.effects.is_empty() {
+                            value.push_str(&format!(
+                                " {} = **{}**",
+                                effect_list(&attack.effects),
+                                attack.to_hit_result.result()
+                                    + character_effect::total(&attack.effects)
+                            ));
+                        }
+                        value.push_str(&format!(
+                            "\n❤️ {}{}",
+                            attack.damage_result, damage_type
+                        ));
+                        if let Some(smite) = &attack.smite {
+                            value.push_str(&format!(
+                                "\n✨ {} radiant (Divine Smite, {} level)",
+                                smite.result,
+                                ordinal(smite.level)
+                            ));
+                        }
+                        e.field(format!("Attack {}", index + 1), value, true);
+                    }
+                    if let Some(cover) = cover {
+                        e.field("Cover", cover_message(*cover, target), false);
+                    }
+                    if let Some(message) = target.as_ref().and_then(target_message) {
+                        e.field("Target", message, false);
+                    }
+                    if let Some(mastery) = mastery {
+                        e.field("Mastery", mastery, false);
+                    }
+                    if let Some(special) = special {
+                        e.field("Special", special, false);
+                    }
+                    if *heroic_inspiration {
+                        e.field("Heroic Inspiration", heroic_inspiration_message(author_nick), false);
+                    }
+                    if !statuses.is_empty() {
+                        e.field("Conditions", status_list(statuses), false);
+                    }
+                    let damage_rolls = attacks
+                        .iter()
+                        .map(|attack| match &attack.smite {
+                            Some(smite) => format!("{} + {}", attack.damage_roll, smite.roll),
+                            None => attack.damage_roll.to_string(),
+                        })
+                        .collect::<Vec<String>>()
+                        .join(", ");
                     e.footer(|f| {
                         f.text(format!(
-                            "Attack Roll: {} | Damage Roll: {}",
-                            to_hit_roll, damage_roll
+                            "Attack Roll: {} | Damage Rolls: {}",
+                            to_hit_roll, damage_rolls
                         ))
                     });
 
@@ -85,28 +849,1359 @@ impl Response {
                 check,
                 roll,
                 result,
+                effects,
+                heroic_inspiration,
+                statuses,
+                purpose,
                 avatar_url,
+                character_name,
             } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
                 let condition = conditional_message(roll.condition());
                 builder.embed(|e| {
-                    e.title(format!("{} rolls {}{}!", author_nick, check, condition));
-                    e.field("Result", format!("🎲 {}", result), false);
+                    e.title(format!(
+                        "{} rolls {}{}{}!",
+                        author_nick,
+                        check,
+                        condition,
+                        purpose_message(purpose)
+                    ));
+                    e.field(
+                        "Result",
+                        format!("{} {}", purpose_tag(purpose), result),
+                        false,
+                    );
+                    if !effects.is_empty() {
+                        e.field("Effects", effect_list(effects), false);
+                        e.field(
+                            "Total",
+                            format!(
+                                "{} **{}**",
+                                purpose_tag(purpose),
+                                result.result() + character_effect::total(effects)
+                            ),
+                            false,
+                        );
+                    }
+                    if *heroic_inspiration {
+                        e.field("Heroic Inspiration", heroic_inspiration_message(author_nick), false);
+                    }
+                    if !statuses.is_empty() {
+                        e.field("Conditions", status_list(statuses), false);
+                    }
                     e.footer(|f| f.text(format!("Roll: {}", roll)));
                     e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
                 })
             }
+            Response::SneakAttackRoll {
+                roll,
+                result,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    e.title(format!("{} rolls sneak attack!", author_nick));
+                    e.field("Damage", format!("❤️ {}", result), false);
+                    e.footer(|f| f.text(format!("Damage Roll: {}", roll)));
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::SpellRoll {
+                spell_name,
+                level,
+                roll,
+                result,
+                damage_type,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    e.title(format!(
+                        "{} casts {}{}!",
+                        author_nick,
+                        spell_name,
+                        spell_level_message(*level)
+                    ));
+                    e.field(
+                        "Damage",
+                        match damage_type {
+                            Some(damage_type) => format!("❤️ {} {}", result, damage_type),
+                            None => format!("❤️ {}", result),
+                        },
+                        false,
+                    );
+                    e.footer(|f| f.text(format!("Damage Roll: {}", roll)));
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
             Response::DiceRoll {
                 roll,
                 result,
+                purpose,
+                avatar_url,
+            } => builder.embed(|e| {
+                e.title(format!(
+                    "{} rolls {}{}!",
+                    author_nick,
+                    roll,
+                    purpose_message(purpose)
+                ));
+                e.field(
+                    "Result",
+                    format!("{} {}", purpose_tag(purpose), result),
+                    false,
+                );
+                e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+            }),
+            Response::TableRoll { roll, results } => builder.embed(|e| {
+                e.title(format!("{} rolls {} for the table!", author_nick, roll));
+                e.description(
+                    results
+                        .iter()
+                        .map(|(user_id, name, result, tie)| match tie {
+                            Some(tie) => {
+                                format!("<@{}> ({}): 🎲 {} ({})", user_id, name, result, tie)
+                            }
+                            None => format!("<@{}> ({}): 🎲 {}", user_id, name, result),
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                e.footer(|f| f.text(format!("Roll: {}", roll)))
+            }),
+            Response::GroupSavingThrow { ability, dc, saves } => builder.embed(|e| {
+                e.title(format!(
+                    "{} calls for a DC {} {} saving throw!",
+                    author_nick,
+                    dc,
+                    ability.as_str()
+                ));
+                e.description(
+                    saves
+                        .iter()
+                        .map(|save| group_save_message(save, *dc, *ability))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                let passed = saves
+                    .iter()
+                    .filter(|save| save.passes(*dc) == Some(true))
+                    .count();
+                let failed = saves
+                    .iter()
+                    .filter(|save| save.passes(*dc) == Some(false))
+                    .count();
+                e.field("Passed", format!("✅ {}", passed), true);
+                e.field("Failed", format!("❌ {}", failed), true)
+            }),
+            Response::ContestedCheck {
+                contest,
+                attacker,
+                defender,
+            } => builder.embed(|e| {
+                e.title(format!(
+                    "{} tries to {} {}!",
+                    attacker.name,
+                    contest.as_str(),
+                    defender.name
+                ));
+                e.description(format!(
+                    "{}\n{}",
+                    contest_roll_message(attacker),
+                    contest_roll_message(defender)
+                ));
+                let outcome = match (*contest, attacker.beats(defender)) {
+                    (Contest::Grapple, true) => format!(
+                        "🤼 {} grapples {}! {} is now grappled.",
+                        attacker.name, defender.name, defender.name
+                    ),
+                    (Contest::Shove, true) => format!(
+                        "💥 {} shoves {}, knocking them prone or pushing them 5 feet away.",
+                        attacker.name, defender.name
+                    ),
+                    (_, false) if attacker.total() == defender.total() => {
+                        "🤝 It's a tie, so nothing changes.".to_owned()
+                    }
+                    (Contest::Grapple, false) => {
+                        format!("🛡️ {} slips out of {}'s grasp.", defender.name, attacker.name)
+                    }
+                    (Contest::Shove, false) => {
+                        format!("🛡️ {} stands their ground.", defender.name)
+                    }
+                };
+                e.field("Result", outcome, false)
+            }),
+            Response::Treasure(treasure) => builder.embed(|e| {
+                e.title(format!(
+                    "{} ({})",
+                    treasure.treasure.kind.as_str(),
+                    treasure.treasure.tier
+                ));
+                e.description(
+                    treasure
+                        .coins
+                        .iter()
+                        .map(|coins| {
+                            format!(
+                                "💰 **{} {}** ({})",
+                                coins.amount(),
+                                coins.coins.denomination.abbreviation(),
+                                coins.coins
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                if let Some(valuables) = &treasure.valuables {
+                    e.field(
+                        "Valuables",
+                        format!(
+                            "💎 {} {} worth {} gp each, **{} gp** in total ({})",
+                            valuables.result.result(),
+                            valuables.valuables.kind.as_str(),
+                            valuables.valuables.value,
+                            valuables.total_value(),
+                            valuables.valuables.roll
+                        ),
+                        false,
+                    );
+                }
+                for magic_items in treasure.magic_items.iter() {
+                    e.field(
+                        format!("✨ {}", magic_items.magic_items),
+                        magic_items
+                            .items
+                            .iter()
+                            .map(|item| format!("• {}", item))
+                            .collect::<Vec<String>>()
+                            .join("\n"),
+                        false,
+                    );
+                }
+                e.footer(|f| f.text(format!("Rolled {} on the d100", treasure.row)))
+            }),
+            Response::Weather(weather) => builder.embed(|e| {
+                e.title("Today's weather");
+                e.field("🌡️ Temperature", weather.temperature_description(), false);
+                e.field("🌬️ Wind", weather.wind_description(), true);
+                e.field(
+                    "🌧️ Precipitation",
+                    weather.precipitation_description(),
+                    true,
+                );
+                let effects = weather.effects();
+                if !effects.is_empty() {
+                    e.field("Effects", effects.join("\n"), false);
+                }
+                e
+            }),
+            Response::Travel(journey) => builder.embed(|e| {
+                e.title(format!(
+                    "Travelling at a {} pace{}",
+                    journey.pace.as_str(),
+                    if journey.difficult_terrain {
+                        " through difficult terrain"
+                    } else {
+                        ""
+                    }
+                ));
+                e.field(
+                    "Distance",
+                    format!(
+                        "{} per hour, {} in {} hours a day",
+                        travel::miles(journey.miles_per_hour()),
+                        travel::miles(journey.miles_per_day()),
+                        journey.hours_per_day()
+                    ),
+                    false,
+                );
+                e.field("Effect", journey.pace.effect(), false);
+                if let (Some(miles), Some((days, hours))) = (journey.miles, journey.travel_time()) {
+                    let days = match days {
+                        0 => None,
+                        1 => Some("1 day".to_owned()),
+                        days => Some(format!("{} days", days)),
+                    };
+                    let hours = match hours {
+                        0 => None,
+                        1 => Some("1 hour".to_owned()),
+                        hours => Some(format!("{} hours", hours)),
+                    };
+                    e.field(
+                        "Travel time",
+                        format!(
+                            "{} takes {}",
+                            travel::miles(f64::from(miles)),
+                            days.into_iter().chain(hours).collect::<Vec<String>>().join(" and ")
+                        ),
+                        false,
+                    );
+                }
+                let forced_march = journey.forced_march_dcs();
+                if !forced_march.is_empty() {
+                    e.field(
+                        "Forced march",
+                        format!(
+                            "After {} hours of travel, each character must make a Constitution saving throw at the end of each hour, gaining one level of exhaustion on a failure.\n{}",
+                            HOURS_PER_DAY,
+                            forced_march
+                                .iter()
+                                .map(|(hour, dc)| format!("• Hour {}: DC {}", hour, dc))
+                                .collect::<Vec<String>>()
+                                .join("\n")
+                        ),
+                        false,
+                    );
+                }
+                e
+            }),
+            Response::Dungeon(dungeon) => builder.embed(|e| {
+                e.title(if dungeon.rooms.len() == 1 {
+                    "Dungeon room".to_owned()
+                } else {
+                    format!("Dungeon ({} rooms)", dungeon.rooms.len())
+                });
+                e.description(format!("```\n{}\n```", dungeon.map()));
+                for room in dungeon.rooms.iter() {
+                    let exits = if room.exits.is_empty() {
+                        "🚪 No other exits".to_owned()
+                    } else {
+                        format!(
+                            "🚪 {}",
+                            room.exits
+                                .iter()
+                                .map(|exit| exit.to_string())
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        )
+                    };
+                    let contents = format!(
+                        "{} {}{}",
+                        match room.contents.kind {
+                            ContentsKind::DominantMonster
+                            | ContentsKind::AlliedMonster
+                            | ContentsKind::RandomMonster => "👹",
+                            ContentsKind::Hazard => "🍄",
+                            ContentsKind::Obstacle => "🚧",
+                            ContentsKind::Trap => "⚠️",
+                            ContentsKind::Trick => "🎭",
+                            ContentsKind::Empty => "🕸️",
+                        },
+                        room.contents.kind.as_str(),
+                        room.contents
+                            .detail
+                            .as_ref()
+                            .map_or_else(String::new, |detail| format!(": {}", detail))
+                    );
+                    let treasure = if room.contents.treasure {
+                        "\n💰 Treasure, rolled with `!treasure`"
+                    } else {
+                        ""
+                    };
+                    e.field(
+                        room.to_string(),
+                        format!("{}\n{}{}", exits, contents, treasure),
+                        false,
+                    );
+                }
+                e.footer(|f| {
+                    f.text("# wall · . floor · + door · L locked door · S secret door · = corridor · 1 square = 10 ft.")
+                })
+            }),
+            Response::Tavern(tavern) => builder.embed(|e| {
+                e.title(&tavern.name);
+                e.description(format!(
+                    "A {} tavern run by {}.",
+                    tavern.quality.as_str(),
+                    tavern.proprietor
+                ));
+                e.field("🍖 Menu", tavern_menu(tavern), false);
+                e.field("👂 Rumour", tavern.rumour, false)
+            }),
+            Response::Settlement(settlement) => builder.embed(|e| {
+                e.title(format!(
+                    "{}, a {}",
+                    settlement.name,
+                    settlement.size.as_str()
+                ));
+                e.description(format!("Population: about {}", settlement.population));
+                e.field("Race relations", settlement.race_relations, true);
+                e.field("Ruler", settlement.ruler, true);
+                e.field("Notable trait", settlement.notable_trait, true);
+                e.field("Known for", settlement.known_for, true);
+                e.field("Current calamity", settlement.calamity, true);
+                e.field(
+                    format!("🍺 {}", settlement.tavern.name),
+                    format!(
+                        "A {} tavern run by {}.\n{}\n👂 {}",
+                        settlement.tavern.quality.as_str(),
+                        settlement.tavern.proprietor,
+                        tavern_menu(&settlement.tavern),
+                        settlement.tavern.rumour
+                    ),
+                    false,
+                )
+            }),
+            Response::RandomTableRoll(result) => builder.embed(|e| {
+                e.title(&result.table);
+                e.description(format!("🎲 **{}**", result.text));
+                e.footer(|f| f.text(table_rolls_message(result)))
+            }),
+            Response::RandomTable(table) => builder.embed(|e| {
+                e.title(&table.name);
+                e.description(
+                    table
+                        .entries
+                        .iter()
+                        .map(|entry| {
+                            if entry.weight > 1 {
+                                format!("• {}× {}", entry.weight, entry.text)
+                            } else {
+                                format!("• {}", entry.text)
+                            }
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                e.footer(|f| f.text(format!("Rolled with a d{}", table.total_weight())))
+            }),
+            Response::RandomTables(tables) => builder.embed(|e| {
+                e.title("Random tables");
+                if tables.is_empty() {
+                    e.description("No random tables have been added in this channel. Try `!rtable add Forest Encounters: 3x 1d4 goblins; 2x a pack of wolves; {Fey Encounters}`.")
+                } else {
+                    e.description(
+                        tables
+                            .iter()
+                            .map(|table| format!("• **{}** (d{})", table.name, table.total_weight()))
+                            .collect::<Vec<String>>()
+                            .join("\n"),
+                    )
+                }
+            }),
+            Response::Coins {
+                transaction,
+                coins,
                 avatar_url,
             } => builder.embed(|e| {
-                e.title(format!("{} rolls {}!", author_nick, roll));
-                e.field("Result", format!("🎲 {}", result), false);
+                e.title(match transaction {
+                    Some(Transaction::Gain(amount)) => format!("{} gains {}!", author_nick, amount),
+                    Some(Transaction::Spend(amount)) => {
+                        format!("{} spends {}!", author_nick, amount)
+                    }
+                    None => format!("{}'s purse", author_nick),
+                });
+                DENOMINATIONS.iter().rev().for_each(|denomination| {
+                    e.field(
+                        denomination.as_str(),
+                        format!("💰 {}", coins.count(*denomination)),
+                        true,
+                    );
+                });
+                e.footer(|f| f.text(format!("Total: {}", coins)));
                 e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
             }),
+            Response::PartyCoins { coins } => builder.embed(|e| {
+                let total = Coins::sum(coins.iter().map(|(_, _, coins)| coins));
+                e.title("Party funds");
+                e.description(
+                    coins
+                        .iter()
+                        .map(|(user_id, name, coins)| {
+                            format!("<@{}> ({}): {}", user_id, name, coins)
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                e.field("Total", format!("💰 {}", total), false);
+                e.footer(|f| f.text(format!("Total value: {} gp", total.value() as f64 / 100.0)))
+            }),
+            Response::Stash { transaction, stash } => builder.embed(|e| {
+                e.title(match transaction {
+                    Some(stash::Transaction::Deposit(loot)) => {
+                        format!("{} puts {} in the party stash!", author_nick, loot)
+                    }
+                    Some(stash::Transaction::Withdraw(loot)) => {
+                        format!("{} takes {} from the party stash!", author_nick, loot)
+                    }
+                    None => "Party stash".to_owned(),
+                });
+                e.description(if stash.items.is_empty() {
+                    "No items".to_owned()
+                } else {
+                    stash
+                        .items
+                        .iter()
+                        .map(|item| format!("🎒 {}", item))
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                });
+                e.field("Coins", format!("💰 {}", stash.coins), false);
+                if let Some(stash::Transaction::Withdraw(Loot::Coins(_))) = transaction {
+                    e.footer(|f| f.text(format!("The coins are now in {}'s purse", author_nick)));
+                }
+                e
+            }),
+            Response::ExperienceAward {
+                amount,
+                reason,
+                share,
+                awards,
+            } => builder.embed(|e| {
+                e.title(match reason {
+                    Some(reason) => format!("The party gains {} XP for {}!", amount, reason),
+                    None => format!("The party gains {} XP!", amount),
+                });
+                e.description(experience_award_list(*share, awards));
+                if let Some(level_ups) = level_up_list(awards) {
+                    e.field("Level up", level_ups, false);
+                }
+                e.footer(|f| f.text(format!("{} XP each", share)))
+            }),
+            Response::EncounterSummary {
+                summary,
+                share,
+                awards,
+            } => builder.embed(|e| {
+                e.title("⚔️ The encounter is over");
+                let mut lines = Vec::new();
+                if let Some(rounds) = summary.rounds {
+                    lines.push(format!(
+                        "⏱️ {} round{}",
+                        rounds,
+                        if rounds == 1 { "" } else { "s" }
+                    ));
+                }
+                for combatant in summary.combatants.iter() {
+                    if combatant.defeated {
+                        lines.push(match combatant.experience {
+                            Some(experience) => format!(
+                                "💀 {} was defeated after taking {} damage ({} XP)",
+                                combatant.name, combatant.damage_taken, experience
+                            ),
+                            None => format!(
+                                "💀 {} was defeated after taking {} damage",
+                                combatant.name, combatant.damage_taken
+                            ),
+                        });
+                    } else if combatant.damage_taken > 0 {
+                        lines.push(format!(
+                            "🩸 {} took {} damage",
+                            combatant.name, combatant.damage_taken
+                        ));
+                    }
+                }
+                if lines.is_empty() {
+                    e.description("No damage was dealt.");
+                } else {
+                    e.description(lines.join("\n"));
+                }
+                let experience = summary.experience();
+                if !awards.is_empty() {
+                    e.field(
+                        format!("The party gains {} XP!", experience),
+                        experience_award_list(*share, awards),
+                        false,
+                    );
+                    if let Some(level_ups) = level_up_list(awards) {
+                        e.field("Level up", level_ups, false);
+                    }
+                } else if experience > 0 {
+                    e.field(
+                        format!("The party earned {} XP", experience),
+                        format!(
+                            "Only the DM can award it. Try `!xp {}`.",
+                            experience
+                        ),
+                        false,
+                    );
+                }
+                e
+            }),
+            Response::EncounterDifficulty {
+                difficulty,
+                monsters,
+                levels,
+            } => builder.embed(|e| {
+                let emoji = match difficulty.rating {
+                    DifficultyRating::Trivial => "🌱",
+                    DifficultyRating::Easy => "🟢",
+                    DifficultyRating::Medium => "🟡",
+                    DifficultyRating::Hard => "🟠",
+                    DifficultyRating::Deadly => "💀",
+                };
+                e.title(format!(
+                    "{} {} encounter",
+                    emoji,
+                    difficulty.rating.as_str()
+                ));
+                e.description(
+                    monsters
+                        .iter()
+                        .map(|group| {
+                            format!(
+                                "{} × {} (CR {}, {} XP each)",
+                                group.count,
+                                group.name,
+                                group.challenge_rating,
+                                group.challenge_rating.experience()
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                e.field(
+                    "Party",
+                    format!(
+                        "{} character{} of level {}",
+                        levels.len(),
+                        if levels.len() == 1 { "" } else { "s" },
+                        levels
+                            .iter()
+                            .map(|level| level.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ),
+                    false,
+                );
+                e.field(
+                    "Experience",
+                    format!(
+                        "{} XP, adjusted to {} XP for the number of monsters and the size of the party",
+                        difficulty.experience, difficulty.adjusted_experience
+                    ),
+                    false,
+                );
+                e.footer(|f| {
+                    f.text(format!(
+                        "Easy {} · Medium {} · Hard {} · Deadly {}",
+                        difficulty.thresholds[0],
+                        difficulty.thresholds[1],
+                        difficulty.thresholds[2],
+                        difficulty.thresholds[3]
+                    ))
+                })
+            }),
+            Response::QuickStatBlock(monster) => builder.embed(|e| {
+                e.title(format!(
+                    "CR {} {}",
+                    monster.challenge_rating,
+                    monster.role.as_str()
+                ));
+                e.description(format!(
+                    "🛡️ AC {} · ❤️ {} hit points · Proficiency bonus {:+}",
+                    monster.armor_class, monster.hit_points, monster.proficiency_bonus
+                ));
+                e.field(
+                    if monster.attacks == 1 {
+                        "Attack".to_owned()
+                    } else {
+                        format!("Multiattack ({} attacks)", monster.attacks)
+                    },
+                    format!(
+                        "{:+} to hit, {} damage",
+                        monster.attack_bonus, monster.damage
+                    ),
+                    false,
+                );
+                e.field("Save DC", monster.save_dc.to_string(), true);
+                e.field(
+                    "XP",
+                    monster.challenge_rating.experience().to_string(),
+                    true,
+                );
+                e.footer(|f| {
+                    f.text("Based on the Monster Statistics by Challenge Rating table in the Dungeon Master's Guide")
+                })
+            }),
+            Response::Monster(monster) => builder.embed(|e| {
+                e.title(&monster.name);
+                e.description(format!(
+                    "🛡️ AC {} · ❤️ {} hit points",
+                    monster.armor_class, monster.hit_points
+                ));
+                e.field(
+                    "Attack",
+                    format!(
+                        "{}: {:+} to hit, {} damage",
+                        monster.attack_name.as_deref().unwrap_or("Natural weapons"),
+                        monster.attack_bonus,
+                        monster.damage
+                    ),
+                    false,
+                );
+                e.field("CR", monster.challenge_rating.to_string(), true);
+                e.field(
+                    "XP",
+                    monster.challenge_rating.experience().to_string(),
+                    true,
+                );
+                e.footer(|f| f.text(format!("Try \"{} attacks\" to roll its attack", monster.name)))
+            }),
+            Response::WildShapes { forms, wild_shape } => builder.embed(|e| {
+                e.title(format!("{}'s wild shapes", author_nick));
+                if forms.is_empty() {
+                    e.description("No wild shape forms. Add one with `!wildshape add brown bear`.");
+                } else {
+                    e.description(
+                        forms
+                            .iter()
+                            .map(|form| format!("🐾 {}", form))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                }
+                if let Some(wild_shape) = wild_shape {
+                    e.field(
+                        "Current form",
+                        match wild_shape.stat_block() {
+                            Some(stat_block) => format!(
+                                "**{}** · ❤️ {}/{} hit points · 🛡️ AC {} · 🦶 {}\n{}",
+                                stat_block.name,
+                                wild_shape.hit_points,
+                                stat_block.hit_points,
+                                stat_block.armor_class,
+                                stat_block.speed,
+                                stat_block
+                                    .actions
+                                    .iter()
+                                    .filter_map(|action| {
+                                        action.attack.map(|attack| {
+                                            format!(
+                                                "{}: {:+} to hit, {} {} damage",
+                                                action.name,
+                                                attack.attack_bonus,
+                                                attack.damage,
+                                                attack.damage_type
+                                            )
+                                        })
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join(" · ")
+                            ),
+                            None => format!(
+                                "**{}** · ❤️ {} hit points",
+                                wild_shape.form, wild_shape.hit_points
+                            ),
+                        },
+                        false,
+                    );
+                }
+                e
+            }),
+            Response::StatBlock(stat_block) => builder.embed(|e| {
+                e.title(&stat_block.name);
+                e.description(format!(
+                    "*{}*\n🛡️ AC {} · ❤️ {} hit points ({}) · 🦶 {}",
+                    stat_block.kind,
+                    stat_block.armor_class,
+                    stat_block.hit_points,
+                    stat_block.hit_dice,
+                    stat_block.speed
+                ));
+                e.field(
+                    "Abilities",
+                    ABILITY_ABBREVIATIONS
+                        .iter()
+                        .enumerate()
+                        .map(|(index, ability)| {
+                            format!(
+                                "{} {} ({:+})",
+                                ability,
+                                stat_block.abilities[index],
+                                stat_block.modifier(index)
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join(" · "),
+                    false,
+                );
+                e.field(
+                    "Actions",
+                    stat_block
+                        .actions
+                        .iter()
+                        .map(|action| {
+                            let attack = action.attack.map(|attack| {
+                                format!(
+                                    "{:+} to hit, {} {} damage.",
+                                    attack.attack_bonus, attack.damage, attack.damage_type
+                                )
+                            });
+                            let parts: Vec<&str> = attack
+                                .as_deref()
+                                .into_iter()
+                                .chain(action.description.as_deref())
+                                .collect();
+                            format!("**{}.** {}", action.name, parts.join(" "))
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                    false,
+                );
+                e.field("CR", stat_block.challenge_rating.to_string(), true);
+                e.field(
+                    "XP",
+                    stat_block.challenge_rating.experience().to_string(),
+                    true,
+                );
+                e.footer(|f| {
+                    f.text(format!(
+                        "Try \"!monster add 3 {}\" to add some to the encounter",
+                        stat_block.name.to_lowercase()
+                    ))
+                })
+            }),
+            Response::Monsters(monsters) => builder.embed(|e| {
+                e.title("Monsters");
+                if monsters.is_empty() {
+                    e.description("No monsters have been saved in this channel. Try `!monster save Bog Hag AC 14 HP 52 attack +5 2d8+3`.")
+                } else {
+                    e.description(
+                        monsters
+                            .iter()
+                            .map(|monster| {
+                                format!(
+                                    "**{}** (CR {}) AC {}, {} HP, {:+} to hit, {} damage",
+                                    monster.name,
+                                    monster.challenge_rating,
+                                    monster.armor_class,
+                                    monster.hit_points,
+                                    monster.attack_bonus,
+                                    monster.damage
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n"),
+                    )
+                }
+            }),
+            Response::ContentPacks(packs) => builder.embed(|e| {
+                e.title("Monster Packs");
+                if packs.is_empty() {
+                    e.description("No monster packs have been installed in this server. Try `!monsterpack install` with a monster pack attached.")
+                } else {
+                    e.description(
+                        packs
+                            .iter()
+                            .map(|pack| {
+                                format!(
+                                    "**{}** ({}): {} monster{}",
+                                    pack.name,
+                                    if pack.enabled { "enabled" } else { "disabled" },
+                                    pack.monsters.len(),
+                                    if pack.monsters.len() == 1 { "" } else { "s" }
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n"),
+                    )
+                }
+            }),
+            Response::PermissionChecks(checks) => builder.embed(|e| {
+                e.title("Permissions in this Channel");
+                e.description(
+                    checks
+                        .iter()
+                        .map(|check| {
+                            if check.has_permission {
+                                format!(
+                                    "✅ **{}**: {}",
+                                    check.requirement.name, check.requirement.granted
+                                )
+                            } else {
+                                format!(
+                                    "❌ **{}**: {}",
+                                    check.requirement.name, check.requirement.missing
+                                )
+                            }
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                if checks.iter().all(|check| check.has_permission) {
+                    e.footer(|f| f.text("Everything should work in this channel."))
+                } else {
+                    e.footer(|f| {
+                        f.text("Grant the missing permissions to Dungeon Helper's role, or in this channel's settings.")
+                    })
+                }
+            }),
+            Response::ChannelSettings(channel) => builder.embed(|e| {
+                let on_off = |setting: bool| if setting { "On" } else { "Off" };
+                e.title("Settings for this Channel");
+                e.field("Enabled", on_off(channel.enabled), true);
+                e.field("Locked", on_off(channel.locked), true);
+                e.field("Dice Only", on_off(channel.dice_only), true);
+                e.field("Edition", channel.edition, true);
+                e.field("Prefix", &channel.prefix, true);
+                e.field("Keep Roll Commands", on_off(channel.keep_roll_messages), true);
+                e.field("Spelling Correction", on_off(channel.spelling_correction), true);
+                e.field(
+                    "Duplicate Window",
+                    match channel.duplicate_window.as_secs() {
+                        0 => "Off".to_owned(),
+                        seconds => format!("{}s", seconds),
+                    },
+                    true,
+                );
+                e.footer(|f| f.text("Server administrators can change these with !channel."))
+            }),
+            Response::ServerDefaults(guild) => builder.embed(|e| {
+                let on_off = |setting: bool| if setting { "On" } else { "Off" };
+                e.title("Settings for New Channels in this Server");
+                e.field("Enabled", on_off(guild.enabled), true);
+                e.field("Locked", on_off(guild.locked), true);
+                e.field("Dice Only", on_off(guild.dice_only), true);
+                e.field("Prefix", &guild.prefix, true);
+                e.field("Keep Roll Commands", on_off(guild.keep_roll_messages), true);
+                e.field("Spelling Correction", on_off(guild.spelling_correction), true);
+                e.footer(|f| f.text("Server administrators can change these with !server."))
+            }),
+            Response::HouseRules {
+                house_rules,
+                limits,
+                edition,
+            } => builder.embed(|e| {
+                e.title("House Rules for this Channel");
+                e.description(
+                    house_rules
+                        .rules()
+                        .iter()
+                        .chain(std::iter::once(&HouseRule::MaxDice(Some(*limits))))
+                        .map(|house_rule| format!("• {}", house_rule))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+                e.field("Edition", edition, true);
+                e.footer(|f| f.text("The DM can change these with !houserules set."))
+            }),
+            Response::CharacterExport { file_name, json } => builder
+                .content(format!(
+                    "📄 <@{}> Here's a copy of your character sheet. Keep it somewhere safe!",
+                    message.author.id
+                ))
+                .add_file(AttachmentType::Bytes {
+                    data: Cow::Owned(json.clone().into_bytes()),
+                    filename: file_name.clone(),
+                }),
+            Response::ChannelExport(json) => builder
+                .content(format!(
+                    "📄 <@{}> Here's a copy of this channel's settings, house rules and random tables. Attach it to `!channel import` to use them in another channel.",
+                    message.author.id
+                ))
+                .add_file(AttachmentType::Bytes {
+                    data: Cow::Owned(json.clone().into_bytes()),
+                    filename: channel_export::FILE_NAME.to_owned(),
+                }),
+            Response::TrainingData { count, json } => builder
+                .content(format!(
+                    "📄 Here's the training data, with {} utterances from the messages I've understood.",
+                    count
+                ))
+                .add_file(AttachmentType::Bytes {
+                    data: Cow::Owned(json.clone().into_bytes()),
+                    filename: "training-data.json".to_owned(),
+                }),
+            Response::InitiativeOrder(order) => builder.embed(|e| {
+                e.title("Initiative order");
+                e.description(
+                    order
+                        .entries()
+                        .iter()
+                        .enumerate()
+                        .map(|(index, entry)| match entry.tie {
+                            Some(tie) => format!(
+                                "{}. **{}** ({}, {})",
+                                index + 1,
+                                entry.name,
+                                entry.initiative,
+                                tie
+                            ),
+                            None => format!(
+                                "{}. **{}** ({})",
+                                index + 1,
+                                entry.name,
+                                entry.initiative
+                            ),
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                )
+            }),
+            Response::InspiredRoll(response) => response
+                .to_message(author_nick, message, builder)
+                .content(format!("✨ <@{}> uses inspiration!", message.author.id)),
+            Response::BardicInspiredRoll { roll, die, result } => {
+                let total = roll
+                    .roll_history_entry()
+                    .map_or(0, |entry| entry.result)
+                    + result.result();
+                roll.to_message(author_nick, message, builder)
+                    .content(format!(
+                        "🎵 <@{}> adds a Bardic Inspiration die: {} rolled {}, for a total of **{}**.",
+                        message.author.id, die, result, total
+                    ))
+            }
+            Response::LoreCheck {
+                roll,
+                subject,
+                lore,
+            } => roll
+                .to_message(author_nick, message, builder)
+                .content(match lore {
+                    Some(lore) => format!(
+                        "📜 <@{}> recalls something about {}: {}",
+                        message.author.id, subject, lore
+                    ),
+                    None => format!(
+                        "🤔 <@{}> doesn't recall anything useful about {}.",
+                        message.author.id, subject
+                    ),
+                }),
+            Response::Lore { lore, is_dm } => builder.embed(|e| {
+                e.title("Lore");
+                if lore.is_empty() {
+                    e.description(if *is_dm {
+                        "No lore has been added in this channel. Try `!lore add The Shattered Crown DC 15 History: It was broken at the Battle of Three Rivers`."
+                    } else {
+                        "Nothing has been recalled in this channel yet."
+                    })
+                } else {
+                    e.description(
+                        lore.iter()
+                            .map(|lore| lore_message(lore, *is_dm))
+                            .collect::<Vec<String>>()
+                            .join("\n"),
+                    )
+                }
+            }),
+            Response::CharacterAudit(entries) => builder.embed(|e| {
+                e.title("Recent character changes");
+                if entries.is_empty() {
+                    e.description("No characters have been changed in this channel yet.")
+                } else {
+                    e.description(
+                        entries
+                            .iter()
+                            .map(audit_message)
+                            .collect::<Vec<String>>()
+                            .join("\n"),
+                    )
+                }
+            }),
+            Response::Companions(companions) => builder.embed(|e| {
+                e.title(format!("{}'s companions", author_nick));
+                if companions.is_empty() {
+                    e.description("No companions. Add one with `!companion familiar Hoot`.")
+                } else {
+                    e.description(
+                        companions
+                            .iter()
+                            .map(|companion| format!("🐾 {}", companion))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    )
+                }
+            }),
+            Response::Notes(notes, search) => builder.embed(|e| {
+                e.title(match search {
+                    Some(search) => format!("Notes about \"{}\"", search),
+                    None => "Campaign notes".to_owned(),
+                });
+                if notes.is_empty() {
+                    e.description(match search {
+                        Some(_) => "No notes in this channel match that.",
+                        None => "There are no notes in this channel yet. Add one with `!note`.",
+                    })
+                } else {
+                    // Show as many of the most recent notes as fit in the message
+                    let mut shown = Vec::new();
+                    let mut length = 0;
+                    for note in notes.iter().rev() {
+                        let line = note_message(note);
+                        length += line.chars().count() + 1;
+                        if length > MAXIMUM_DESCRIPTION_LENGTH && !shown.is_empty() {
+                            break;
+                        }
+                        shown.push(line);
+                    }
+                    shown.reverse();
+                    e.description(shown.join("\n"));
+                    e.footer(|f| {
+                        f.text(format!(
+                            "Showing the {} most recent notes. Delete one with !note delete <number>.",
+                            shown.len()
+                        ))
+                    })
+                }
+            }),
+            Response::Composite { responses, skipped } => builder.embed(|e| {
+                for (index, response) in responses.iter().enumerate() {
+                    let (title, text) = composite_field(response, author_nick, message);
+                    e.field(
+                        title.unwrap_or_else(|| format!("Command {}", index + 1)),
+                        text,
+                        false,
+                    );
+                }
+                if !skipped.is_empty() {
+                    e.field("Skipped", skipped.join("\n"), false);
+                }
+                e
+            }),
+            Response::TurnStart {
+                round,
+                name,
+                user_id,
+                effects,
+                hit_points,
+                bloodied,
+                defeated,
+                ended_effects,
+                ended_statuses,
+            } => {
+                if let Some(user_id) = user_id {
+                    builder.content(format!("⚔️ <@{}>, it's your turn!", user_id));
+                }
+                builder.embed(|e| {
+                    e.title(format!("Round {}: {}'s turn", round, name));
+                    let mut lines: Vec<String> = ended_effects
+                        .iter()
+                        .map(|(character, effect)| {
+                            format!("⌛ {}'s {} has ended.", character, effect)
+                        })
+                        .collect();
+                    lines.extend(
+                        ended_statuses
+                            .iter()
+                            .map(|(character, status)| {
+                                format!("⌛ {} is no longer {}.", character, status)
+                            }),
+                    );
+                    lines.extend(
+                        effects
+                            .iter()
+                            .map(|effect| ongoing_effect_message(name, effect)),
+                    );
+                    let damage: Vec<i32> = effects
+                        .iter()
+                        .filter(|effect| effect.effect.kind == EffectKind::Damage)
+                        .filter_map(|effect| match &effect.outcome {
+                            EffectOutcome::Rolled(result) => Some(result.result()),
+                            EffectOutcome::Resisted { amount, .. } => Some(*amount),
+                            EffectOutcome::Suppressed(_) => None,
+                        })
+                        .collect();
+                    if damage.len() > 1 {
+                        let total: i32 = damage.iter().sum();
+                        lines.push(format!("Total: **{}** damage", total));
+                    }
+                    if *defeated {
+                        lines.push(format!(
+                            "💀 {} is defeated, and has been removed from the initiative order.",
+                            name
+                        ));
+                    } else if let Some(hit_points) = hit_points {
+                        if *bloodied {
+                            lines.push(format!("🩸 {} is bloodied!", name));
+                        }
+                        lines.push(if hit_points.hidden {
+                            format!("❤️ {} {}", name, hit_points.description())
+                        } else {
+                            format!("❤️ {} hit points", hit_points.current)
+                        });
+                    }
+                    if !lines.is_empty() {
+                        e.description(lines.join("\n"));
+                    }
+                    e
+                })
+            }
+            Response::SpellSlots {
+                classes,
+                slots,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    e.title(format!("{}'s spell slots", author_nick));
+                    e.description(
+                        classes
+                            .iter()
+                            .map(|class| class.to_string())
+                            .collect::<Vec<String>>()
+                            .join(" / "),
+                    );
+                    for (index, count) in slots.slots.iter().enumerate() {
+                        if *count > 0 {
+                            e.field(
+                                format!("{} level", ordinal(index as i32 + 1)),
+                                format!("✨ {}", count),
+                                true,
+                            );
+                        }
+                    }
+                    if let Some((count, level)) = slots.pact_magic {
+                        e.field(
+                            "Pact Magic",
+                            format!("✨ {} × {} level", count, ordinal(level)),
+                            true,
+                        );
+                    }
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::Statuses {
+                statuses,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    e.title(format!("{}'s conditions", author_nick));
+                    e.description(if statuses.is_empty() {
+                        "No conditions".to_owned()
+                    } else {
+                        status_list(statuses)
+                    });
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::Bonuses {
+                bonuses,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    e.title(format!("{}'s bonuses", author_nick));
+                    e.description(if bonuses.is_empty() {
+                        "No bonuses".to_owned()
+                    } else {
+                        bonuses
+                            .iter()
+                            .map(|bonus| format!("✨ {}", bonus))
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    });
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::Counters {
+                counters,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    e.title(format!("{}'s counters", author_nick));
+                    e.description(if counters.is_empty() {
+                        "No counters".to_owned()
+                    } else {
+                        counters
+                            .iter()
+                            .map(|counter| format!("🔢 {}", counter))
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    });
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::CharacterEffects {
+                effects,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    e.title(format!("{}'s effects", author_nick));
+                    e.description(if effects.is_empty() {
+                        "No effects".to_owned()
+                    } else {
+                        effects
+                            .iter()
+                            .map(|effect| format!("✨ {}", effect))
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    });
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::CharacterSheet {
+                sheet,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    e.title(format!("{}'s character sheet", author_nick));
+                    e.description(sheet.summary());
+                    for ability in sheet.abilities.iter() {
+                        e.field(ability.title(), ability.description(), true);
+                    }
+                    if let Some(weapon_proficiencies) = sheet.weapon_proficiencies() {
+                        e.field("Weapon proficiencies", weapon_proficiencies, false);
+                    }
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::PassiveScores {
+                scores,
+                avatar_url,
+                character_name,
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    match scores.as_slice() {
+                        [score] => {
+                            e.title(format!("{}'s passive {}", author_nick, score.check));
+                            e.description(passive_score_line(score));
+                        }
+                        _ => {
+                            e.title(format!("{}'s passive scores", author_nick));
+                            e.description(
+                                scores
+                                    .iter()
+                                    .map(|score| match score.check {
+                                        Check::Ability(_) => {
+                                            format!("**{}**", passive_score_line(score))
+                                        }
+                                        _ => format!("• {}", passive_score_line(score)),
+                                    })
+                                    .collect::<Vec<String>>()
+                                    .join("\n"),
+                            );
+                        }
+                    }
+                    e.thumbnail(avatar_url.as_ref().unwrap_or(&message.author.face()))
+                })
+            }
+            Response::QuickReference {
+                reference,
+                avatar_url,
+                character_name,
+                ..
+            } => {
+                let author_nick = character_name.as_deref().unwrap_or(author_nick);
+                builder.embed(|e| {
+                    quick_reference_embed(
+                        e,
+                        author_nick,
+                        &reference.to_string(),
+                        Some(avatar_url.as_ref().unwrap_or(&message.author.face())),
+                    )
+                })
+            }
             Response::Clarification(text) => {
                 builder.content(format!("📎 <@{}> {}", message.author.id, text))
             }
+            Response::Confirmation(text) => {
+                builder.content(format!("✅ <@{}> {}", message.author.id, text))
+            }
             Response::Error(_) => builder.content(format!(
                 "💥 <@{}> **Error:** A technical error has occurred. Reference ID: {}",
                 message.author.id, &message.id
@@ -117,10 +2212,553 @@ impl Response {
             Response::Warning(text) => {
                 builder.content(format!("⚠️ <@{}> {}", message.author.id, text))
             }
+            Response::VerifiedRoll(roll, time_zone) => builder.embed(|e| {
+                let rolled = time::parse_timestamp(&roll.rolled);
+                e.title("✅ This roll is genuine!");
+                e.description(format!(
+                    "Rolled by <@{}>{} in <#{}> at {}.",
+                    roll.user_id,
+                    roll.character_name
+                        .as_ref()
+                        .map_or_else(String::new, |name| format!(" as {}", name)),
+                    roll.channel_id,
+                    rolled.map_or_else(
+                        || format!("{} UTC", roll.rolled),
+                        time::discord_timestamp
+                    )
+                ));
+                e.field("Roll", &roll.description, false);
+                e.field("Result", format!("🎲 **{}**", roll.result), false);
+                if let Some(rolled) = rolled {
+                    e.footer(|f| f.text(format!("Rolled at {}", time_zone.format(rolled))));
+                }
+                e
+            }),
+            Response::HiddenRoll { roll, code } => builder.embed(|e| {
+                e.title(format!("{} rolls {} into the dice cup!", author_nick, roll));
+                e.description("🥤 The result is sealed until it's revealed.");
+                e.field("Code", code, false);
+                e.footer(|f| f.text("Try \"Reveal my hidden roll\" to show the result"))
+            }),
+            Response::BlindRoll { dm_user_id, .. } => builder.embed(|e| {
+                e.title(format!("{} rolled!", author_nick));
+                e.description(format!("🙈 The result was sent to <@{}>.", dm_user_id))
+            }),
+            Response::RevealedRolls(rolls) => builder.embed(|e| {
+                e.title(format!(
+                    "{} reveals {}!",
+                    author_nick,
+                    if rolls.len() == 1 {
+                        "a hidden roll".to_owned()
+                    } else {
+                        format!("{} hidden rolls", rolls.len())
+                    }
+                ));
+                for roll in rolls {
+                    e.field(
+                        format!("{} (code {})", roll.roll, roll.code),
+                        format!("🎲 {}", roll.details),
+                        false,
+                    );
+                }
+                e
+            }),
+        }
+    }
+}
+
+/// Add the codes that verify the rolls in a message to the footer of its embed, after anything
+/// already in the footer, so that a screenshot of the rolls can be checked with "verify roll".
+pub fn add_roll_codes<'a, 'b>(
+    builder: &'b mut CreateMessage<'a>,
+    codes: &[&str],
+) -> &'b mut CreateMessage<'a> {
+    if codes.is_empty() {
+        return builder;
+    }
+    let codes = format!("Code: {}", codes.join(", "));
+    if let Some(Value::Object(embed)) = builder.0.get_mut("embed") {
+        let footer = embed.entry("footer").or_insert_with(|| json!({}));
+        let text = match footer.get("text").and_then(Value::as_str) {
+            Some(text) => format!("{} • {}", text, codes),
+            None => codes,
+        };
+        footer["text"] = Value::String(text);
+    }
+    builder
+}
+
+/// Discord's limit on the length of a message's content, in characters.
+const MESSAGE_CONTENT_LIMIT: usize = 2000;
+
+/// Discord's limit on the length of an embed's description, in characters.
+const MAXIMUM_DESCRIPTION_LENGTH: usize = 2048;
+
+/// Replace a message's embed with the same text in the message's content, for channels where the
+/// bot doesn't have permission to send embeds.
+pub fn embed_as_plain_text<'a, 'b>(
+    builder: &'b mut CreateMessage<'a>,
+) -> &'b mut CreateMessage<'a> {
+    let embed = match builder.0.remove("embed") {
+        Some(embed) => embed,
+        None => return builder,
+    };
+    let text = |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_owned);
+    let mut lines: Vec<String> = builder
+        .0
+        .get("content")
+        .and_then(Value::as_str)
+        .filter(|content| !content.is_empty())
+        .map(str::to_owned)
+        .into_iter()
+        .collect();
+    lines.extend(embed.get("author").and_then(|author| text(author, "name")));
+    lines.extend(text(&embed, "title").map(|title| format!("**{}**", title)));
+    lines.extend(text(&embed, "description"));
+    if let Some(fields) = embed.get("fields").and_then(Value::as_array) {
+        for field in fields {
+            if let (Some(name), Some(value)) = (text(field, "name"), text(field, "value")) {
+                lines.push(format!("**{}**: {}", name, value));
+            }
+        }
+    }
+    lines.extend(embed.get("footer").and_then(|footer| text(footer, "text")));
+    let content = lines.join("\n");
+    let content = match content.char_indices().nth(MESSAGE_CONTENT_LIMIT - 1) {
+        Some((index, _)) => format!("{}…", &content[..index]),
+        None => content,
+    };
+    builder.0.insert("content", Value::String(content));
+    builder
+}
+
+/// Build the embed for a character's quick reference. This is also used to edit a pinned quick
+/// reference, so that the message looks the same after it has been updated.
+pub fn quick_reference_embed<'a>(
+    e: &'a mut CreateEmbed,
+    author_nick: &str,
+    description: &str,
+    avatar_url: Option<&String>,
+) -> &'a mut CreateEmbed {
+    e.title(format!("📌 {}'s quick reference", author_nick));
+    e.description(description);
+    if let Some(avatar_url) = avatar_url {
+        e.thumbnail(avatar_url);
+    }
+    e
+}
+
+/// A line describing lore. The DM also sees the check that recalls it and whether it's
+/// still hidden from the players.
+fn lore_message(lore: &Lore, is_dm: bool) -> String {
+    let kind = lore
+        .kind
+        .map_or_else(String::new, |kind| format!(" ({})", kind.as_str()));
+    let details = if is_dm {
+        let check = lore.check.map_or_else(
+            || "DM only".to_owned(),
+            |check| format!("{} DC {}", check.skill.as_str(), check.dc),
+        );
+        if lore.revealed {
+            format!(" _[{}]_", check)
+        } else {
+            format!(" _[{}, hidden]_", check)
+        }
+    } else {
+        String::new()
+    };
+    format!("**{}**{}: {}{}", lore.subject, kind, lore.text, details)
+}
+
+/// A tavern's menu, one item and its price to a line.
+fn tavern_menu(tavern: &Tavern) -> String {
+    tavern
+        .menu
+        .iter()
+        .map(|item| format!("• {}: {}", item.item, item.price))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The rolls made on a random table and on any of the tables it referred to, such as "Rolled 4 on
+/// the d6, then 2 on the d2 on Fey Encounters".
+fn table_rolls_message(result: &TableRollResult) -> String {
+    fn nested_rolls(result: &TableRollResult, rolls: &mut Vec<String>) {
+        for nested in result.nested.iter() {
+            rolls.push(format!(
+                "{} on the d{} on {}",
+                nested.roll, nested.total_weight, nested.table
+            ));
+            nested_rolls(nested, rolls);
+        }
+    }
+    let mut rolls = vec![format!(
+        "Rolled {} on the d{}",
+        result.roll, result.total_weight
+    )];
+    nested_rolls(result, &mut rolls);
+    rolls.join(", then ")
+}
+
+/// The title and text of the field showing one of the responses in a message with several
+/// commands. The response is rendered on its own, and the text, description and fields of its
+/// message are gathered into the field's text, which is shortened to fit Discord's limit.
+fn composite_field(
+    response: &Response,
+    author_nick: &str,
+    message: &Message,
+) -> (Option<String>, String) {
+    const MAXIMUM_LENGTH: usize = 1024;
+    let mut builder = CreateMessage::default();
+    response.to_message(author_nick, message, &mut builder);
+    let embed = builder.0.get("embed").and_then(Value::as_object);
+    let title = embed
+        .and_then(|embed| embed.get("title"))
+        .and_then(Value::as_str)
+        .map(|title| title.to_owned());
+    let mut lines: Vec<String> = Vec::new();
+    lines.extend(
+        builder
+            .0
+            .get("content")
+            .and_then(Value::as_str)
+            .map(|content| content.to_owned()),
+    );
+    if let Some(embed) = embed {
+        lines.extend(
+            embed
+                .get("description")
+                .and_then(Value::as_str)
+                .map(|description| description.to_owned()),
+        );
+        if let Some(fields) = embed.get("fields").and_then(Value::as_array) {
+            lines.extend(fields.iter().filter_map(|field| {
+                Some(format!(
+                    "**{}**: {}",
+                    field.get("name")?.as_str()?,
+                    field.get("value")?.as_str()?
+                ))
+            }));
+        }
+    }
+    let text = lines.join("\n");
+    let text = if text.is_empty() {
+        "Done.".to_owned()
+    } else if text.chars().count() > MAXIMUM_LENGTH {
+        format!(
+            "{}…",
+            text.chars().take(MAXIMUM_LENGTH - 1).collect::<String>()
+        )
+    } else {
+        text
+    };
+    (title, text)
+}
+
+/// The target of an attack for the title of the attack, such as " Goblin A".
+fn target_name(target: &Option<AttackTarget>) -> String {
+    target
+        .as_ref()
+        .map_or_else(String::new, |target| format!(" {}", target.name))
+}
+
+/// Whether an attack hit its target, such as "✅ **HIT**".
+fn hit_message(hit: bool) -> &'static str {
+    if hit {
+        "✅ **HIT**"
+    } else {
+        "❌ **MISS**"
+    }
+}
+
+/// The damage dealt to the target of an attack, such as "Goblin A takes 7 piercing damage and has
+/// 0 hit points left.", or None if no damage was dealt to them.
+/// The cover the target of an attack is behind, such as "🧱 Goblin has half cover, giving them AC
+/// 17.", or what the attack needs to hit when there's no target, such as "🧱 The target has half
+/// cover: the attack needs to hit their AC + 2."
+fn cover_message(cover: Cover, target: &Option<AttackTarget>) -> String {
+    match target {
+        Some(target) => format!(
+            "🧱 {} has {}, giving them AC {}.",
+            target.name, cover, target.armor_class
+        ),
+        None => format!(
+            "🧱 The target has {}: the attack needs to hit their AC + {}.",
+            cover,
+            cover.armor_class_bonus()
+        ),
+    }
+}
+
+fn target_message(target: &AttackTarget) -> Option<String> {
+    if target.damage.is_empty() {
+        return None;
+    }
+    Some(combatant::damage_message(
+        &target.name,
+        &target.damage.join(", then "),
+        target.hit_points_before,
+        target.hit_points,
+    ))
+}
+
+/// A line describing a change to a character, such as "<t:1591039800:f> @Alice's Connie:
+/// Dexterity 14 → 16", where the time is shown in each reader's local time. Changes made by
+/// someone other than the character's owner, such as the DM awarding experience, say who made
+/// them.
+fn audit_message(entry: &AuditEntry) -> String {
+    let changed_by = if entry.changed_by == entry.user_id {
+        String::new()
+    } else {
+        format!(" _(by <@{}>)_", entry.changed_by)
+    };
+    format!(
+        "{} <@{}>'s {}: {} {} → {}{}",
+        time::parse_timestamp(&entry.changed)
+            .map_or_else(|| format!("`{}`", entry.changed), time::discord_timestamp),
+        entry.user_id,
+        entry.name,
+        entry.change.attribute,
+        audit_value(entry.change.old_value.as_deref()),
+        audit_value(entry.change.new_value.as_deref()),
+        changed_by
+    )
+}
+
+/// A note, with its number, when it was written and who by.
+fn note_message(note: &Note) -> String {
+    format!(
+        "`#{}` {} <@{}>: {}",
+        note.id,
+        time::parse_timestamp(&note.created)
+            .map_or_else(|| format!("`{}`", note.created), time::discord_timestamp),
+        note.user_id,
+        note.text
+    )
+}
+
+/// An old or new value in the audit history, shortened so that long values such as portrait URLs
+/// don't crowd out the other changes.
+fn audit_value(value: Option<&str>) -> String {
+    const MAXIMUM_LENGTH: usize = 40;
+    match value {
+        None => "_unset_".to_owned(),
+        Some(value) if value.chars().count() > MAXIMUM_LENGTH => {
+            format!(
+                "{}…",
+                value.chars().take(MAXIMUM_LENGTH - 1).collect::<String>()
+            )
         }
+        Some(value) => value.to_owned(),
+    }
+}
+
+fn ongoing_effect_message(name: &str, effect: &OngoingEffectResult) -> String {
+    let rounds = match effect.rounds_remaining {
+        Some(rounds) if rounds <= 0 => " The effect ends.".to_owned(),
+        Some(1) => " 1 round left.".to_owned(),
+        Some(rounds) => format!(" {} rounds left.", rounds),
+        None => String::new(),
+    };
+    match (&effect.outcome, effect.effect.kind) {
+        (EffectOutcome::Rolled(result), EffectKind::Damage) => format!(
+            "🩸 {} takes {} from {}.{}",
+            name,
+            result,
+            effect.effect.description(),
+            rounds
+        ),
+        (EffectOutcome::Rolled(result), EffectKind::Healing) if effect.effect.roll.rolls() == 0 => {
+            format!("💚 {} regains {} hit points.{}", name, result, rounds)
+        }
+        (EffectOutcome::Rolled(result), EffectKind::Healing) => format!(
+            "💚 {} regains {} hit points from {}.{}",
+            name, result, effect.effect.roll, rounds
+        ),
+        (EffectOutcome::Resisted { amount, note, .. }, _) => format!(
+            "🩸 {} takes **{}** from {}, {}.{}",
+            name,
+            amount,
+            effect.effect.description(),
+            note,
+            rounds
+        ),
+        (EffectOutcome::Suppressed(damage_type), _) => format!(
+            "🚫 {} doesn't regain {} after taking {} damage.{}",
+            name,
+            effect.effect.description(),
+            damage_type,
+            rounds
+        ),
+    }
+}
+
+/// One character's check in a contest, such as "<@123> (Connie) Athletics: 🎲 17", including the
+/// effects added to the roll.
+fn contest_roll_message(contest_roll: &ContestRoll) -> String {
+    if contest_roll.effects.is_empty() {
+        format!(
+            "<@{}> ({}) {}: 🎲 {}",
+            contest_roll.user_id, contest_roll.name, contest_roll.check, contest_roll.result
+        )
+    } else {
+        format!(
+            "<@{}> ({}) {}: 🎲 {}, {} = {}",
+            contest_roll.user_id,
+            contest_roll.name,
+            contest_roll.check,
+            contest_roll.result,
+            contest_roll
+                .effects
+                .iter()
+                .map(|effect| effect.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            contest_roll.total()
+        )
+    }
+}
+
+/// One line of the summary of a saving throw made by everyone, such as "✅ <@123> (Alice): 🎲 17",
+/// including the effects added to the roll, or a note for a character without the ability score.
+fn group_save_message(save: &GroupSave, dc: i32, ability: AbilityName) -> String {
+    match (&save.roll, save.total()) {
+        (Some((_, result)), Some(total)) => {
+            let mark = if total >= dc { "✅" } else { "❌" };
+            if save.effects.is_empty() {
+                format!(
+                    "{} <@{}> ({}): 🎲 {}",
+                    mark, save.user_id, save.name, result
+                )
+            } else {
+                format!(
+                    "{} <@{}> ({}): 🎲 {}, {} = {}",
+                    mark,
+                    save.user_id,
+                    save.name,
+                    result,
+                    save.effects
+                        .iter()
+                        .map(|effect| effect.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    total
+                )
+            }
+        }
+        _ => format!(
+            "⚠️ <@{}> ({}): no {} score, roll it yourself",
+            save.user_id,
+            save.name,
+            ability.as_str()
+        ),
+    }
+}
+
+/// The level of the spell slot a spell was cast with, such as " at 4th level", or nothing for a
+/// cantrip.
+fn spell_level_message(level: Option<i32>) -> String {
+    level.map_or_else(String::new, |level| format!(" at {} level", ordinal(level)))
+}
+
+fn purpose_message(purpose: &Option<Purpose>) -> String {
+    purpose
+        .as_ref()
+        .map_or_else(String::new, |purpose| format!(" {}", purpose))
+}
+
+fn purpose_tag(purpose: &Option<Purpose>) -> &str {
+    purpose
+        .as_ref()
+        .and_then(|purpose| purpose.tag.as_deref())
+        .unwrap_or("🎲")
+}
+
+fn experience_award_list(share: i32, awards: &[ExperienceAward]) -> String {
+    awards
+        .iter()
+        .map(|award| {
+            format!(
+                "<@{}> ({}): +{} XP (total {})",
+                award.user_id, award.name, share, award.experience
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn level_up_list(awards: &[ExperienceAward]) -> Option<String> {
+    let level_ups = awards
+        .iter()
+        .filter_map(|award| {
+            award
+                .level_up
+                .map(|level| format!("🎉 {} reached level {}!", award.name, level))
+        })
+        .collect::<Vec<String>>();
+    if level_ups.is_empty() {
+        None
+    } else {
+        Some(level_ups.join("\n"))
     }
 }
 
+fn status_list(statuses: &[Status]) -> String {
+    statuses
+        .iter()
+        .map(|status| format!("🩹 {}", status))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// A passive score, such as "Perception 14", noting when it includes advantage or disadvantage
+/// from the character's conditions.
+fn passive_score_line(score: &PassiveScore) -> String {
+    format!(
+        "{} {}{}",
+        score.check,
+        score
+            .score
+            .map_or_else(|| "—".to_owned(), |score| score.to_string()),
+        match score.condition {
+            Some(Condition::Advantage) => " (advantage)",
+            Some(Condition::Disadvantage) => " (disadvantage)",
+            None => "",
+        }
+    )
+}
+
+fn effect_list(effects: &[EffectRoll]) -> String {
+    effects
+        .iter()
+        .map(|effect| format!("✨ {}", effect))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// The note for a character who gains Heroic Inspiration from rolling a natural 1.
+fn heroic_inspiration_message(author_nick: &str) -> String {
+    format!(
+        "🌟 A natural 1! {} has Heroic Inspiration to use on a later roll.",
+        author_nick
+    )
+}
+
+/// The effects added to a roll, for the roll history, such as " + 1d4 Bless - 1d4 Bane".
+fn effects_description(effects: &[EffectRoll]) -> String {
+    effects
+        .iter()
+        .map(|effect| {
+            format!(
+                " {} {} {}",
+                if effect.effect.penalty { "-" } else { "+" },
+                effect.effect.roll,
+                effect.effect.name
+            )
+        })
+        .collect()
+}
+
 fn conditional_message(condition: Option<Condition>) -> &'static str {
     match condition {
         Some(Condition::Advantage) => " with advantage",
@@ -6,14 +6,26 @@ use std::error;
 use std::fmt;
 
 /// The maximum number of dice that may be rolled at one time.
-pub const MAXIMUM_ROLLS: usize = 100;
+pub const MAXIMUM_ROLLS: usize = 10000;
 
 /// The maximum number of individual dice rolls that will be displayed in full.
 pub const MAXIMUM_ROLLS_DISPLAY: usize = 10;
 
+/// The maximum number of dice whose individual rolls will be listed at all. The results of larger
+/// rolls are summarised instead.
+pub const MAXIMUM_ROLLS_LISTED: usize = 100;
+
 /// The maximum number of sides a die may have.
 pub const MAXIMUM_SIDES: i32 = 100;
 
+/// The most dice that may be rolled at one time in a channel, and the most sides each die may
+/// have, which an administrator can lower from the maximum allowed values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Limits {
+    pub rolls: usize,
+    pub sides: i32,
+}
+
 /// A dice roll that might occur in Dungeons and Dragons 5th edition.
 ///
 /// A dice roll involves rolling a number of dice, each with a number of sides. The sum of the
@@ -23,6 +35,12 @@ pub struct Roll {
     rolls: usize,
     sides: i32,
     modifier: i32,
+    /// Dice that roll this number or lower are rolled again, and the new roll is used even if
+    /// it's also this low, such as for Great Weapon Fighting. Zero if no dice are rolled again.
+    reroll: i32,
+    /// Dice that roll lower than this number are treated as rolling this number, such as for Great
+    /// Weapon Fighting in the 2024 rules. Zero if no dice are changed.
+    minimum: i32,
 }
 
 /// Determines the conditions under which a roll occurs - advantage, disadvantage, or normal.
@@ -35,6 +53,39 @@ pub enum Condition {
     Disadvantage,
 }
 
+impl Condition {
+    /// Combine every source of advantage and disadvantage on a roll. Multiple sources of the same
+    /// kind don't stack, and if there is at least one of each they cancel out, so the roll is
+    /// made normally.
+    pub fn combine<I: IntoIterator<Item = Condition>>(conditions: I) -> Option<Condition> {
+        let mut advantage = false;
+        let mut disadvantage = false;
+        for condition in conditions {
+            match condition {
+                Condition::Advantage => advantage = true,
+                Condition::Disadvantage => disadvantage = true,
+            }
+        }
+        match (advantage, disadvantage) {
+            (true, false) => Some(Condition::Advantage),
+            (false, true) => Some(Condition::Disadvantage),
+            _ => None,
+        }
+    }
+
+    /// Give a roll advantage on top of the condition it already has, which cancels out
+    /// disadvantage.
+    pub fn add_advantage(condition: Option<Condition>) -> Option<Condition> {
+        Condition::combine(condition.into_iter().chain(Some(Condition::Advantage)))
+    }
+
+    /// Give a roll disadvantage on top of the condition it already has, which cancels out
+    /// advantage.
+    pub fn add_disadvantage(condition: Option<Condition>) -> Option<Condition> {
+        Condition::combine(condition.into_iter().chain(Some(Condition::Disadvantage)))
+    }
+}
+
 /// The detailed result of a dice roll.
 ///
 /// In addition to the numerical result itself, it includes the individual die values, the
@@ -59,10 +110,41 @@ pub enum Critical {
     Failure,
 }
 
+impl RollResult {
+    pub fn result(&self) -> i32 {
+        self.result
+    }
+
+    /// Write a summary of the dice rolled, rather than every die, for rolls with too many dice to
+    /// list.
+    fn fmt_summary(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: i32 = self.dice.iter().sum();
+        let lowest = self.dice.iter().min().copied().unwrap_or(0);
+        let highest = self.dice.iter().max().copied().unwrap_or(0);
+        let mean = f64::from(total) / self.dice.len() as f64;
+        write!(
+            f,
+            " ({} dice — total {}, lowest {}, highest {}, mean {:.2}",
+            self.dice.len(),
+            total,
+            lowest,
+            highest,
+            mean
+        )
+        .and(match self.modifier.cmp(&0) {
+            Ordering::Greater => write!(f, "; + __{}__)", self.modifier),
+            Ordering::Less => write!(f, "; - __{}__)", -self.modifier),
+            Ordering::Equal => write!(f, ")"),
+        })
+    }
+}
+
 impl fmt::Display for RollResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "**{}**", self.result).and({
-            if self.dice.len() > 1 || self.modifier != 0 {
+            if self.dice.len() > MAXIMUM_ROLLS_LISTED {
+                self.fmt_summary(f)
+            } else if self.dice.len() > 1 || self.modifier != 0 {
                 let mut iter = self.dice.iter().take(MAXIMUM_ROLLS_DISPLAY);
                 iter.next().map_or(Ok(()), |head| {
                     iter.fold(write!(f, " ({}", head), |result, die| {
@@ -103,6 +185,10 @@ impl ConditionalRollResult {
     pub fn critical(&self) -> Option<Critical> {
         self.primary.critical
     }
+
+    pub fn result(&self) -> i32 {
+        self.primary.result
+    }
 }
 
 impl fmt::Display for ConditionalRollResult {
@@ -128,20 +214,22 @@ impl fmt::Display for ConditionalRollResult {
 /// Represents an error that might occur when creating a roll.
 ///
 /// A roll must have involve a positive number of rolls of dice with a positive number of sides.
-/// The number of rolls and sides must not be more than 100.
+/// The number of rolls and sides must not be more than the limits, which are given with the error.
 #[derive(Debug, Eq, PartialEq)]
 pub enum Error {
-    RollsTooGreat,
+    RollsTooGreat(usize),
     SidesNonPositive,
-    SidesTooGreat,
+    SidesTooGreat(i32),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::RollsTooGreat => write!(f, "Must roll no more than 100 dice."),
+            Error::RollsTooGreat(limit) => write!(f, "Must roll no more than {} dice.", limit),
             Error::SidesNonPositive => write!(f, "Dice must have at least one side."),
-            Error::SidesTooGreat => write!(f, "Dice must have no more than 100 sides."),
+            Error::SidesTooGreat(limit) => {
+                write!(f, "Dice must have no more than {} sides.", limit)
+            }
         }
     }
 }
@@ -172,15 +260,9 @@ impl Roll {
     /// Create a roll, validating that the number of dice being rolled, and the number of sides
     /// each die has, are positive and no more than the maximum allowed values.
     pub fn new(rolls: usize, sides: i32, modifier: i32) -> Result<Roll, Error> {
-        if rolls > MAXIMUM_ROLLS {
-            Err(Error::RollsTooGreat)
-        } else if sides <= 0 {
-            Err(Error::SidesNonPositive)
-        } else if sides > MAXIMUM_SIDES {
-            Err(Error::SidesTooGreat)
-        } else {
-            Ok(Roll::new_unsafe(rolls, sides, modifier))
-        }
+        Limits::MAXIMUM
+            .check(rolls, sides)
+            .map(|_| Roll::new_unsafe(rolls, sides, modifier))
     }
 
     /// Create a roll, clamping the rolls and sides to the minimum or maximum values if they are out
@@ -198,6 +280,8 @@ impl Roll {
             rolls,
             sides,
             modifier,
+            reroll: 0,
+            minimum: 0,
         }
     }
 
@@ -279,29 +363,63 @@ impl Roll {
     }
 
     fn roll_once_component<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<i32> {
-        Uniform::new_inclusive(1, self.sides)
-            .sample_iter(rng)
-            .take(self.rolls)
+        let die = Uniform::new_inclusive(1, self.sides);
+        (0..self.rolls)
+            .map(|_| match die.sample(rng) {
+                value if value <= self.reroll => die.sample(rng),
+                value => value,
+            })
+            .map(|value| value.max(self.minimum))
             .collect()
     }
 
     pub fn add_modifier(&self, modifier: i32) -> Roll {
-        Roll::new_unsafe(self.rolls, self.sides, self.modifier + modifier)
+        Roll {
+            modifier: self.modifier + modifier,
+            ..*self
+        }
     }
 
     pub fn multiply_rolls(&self, scalar: usize) -> Roll {
-        Roll::new_clamped(scalar * self.rolls, self.sides, self.modifier)
+        Roll {
+            reroll: self.reroll,
+            minimum: self.minimum,
+            ..Roll::new_clamped(scalar * self.rolls, self.sides, self.modifier)
+        }
+    }
+
+    /// The same roll, rolling again any dice that roll the given number or lower.
+    pub fn with_reroll(&self, reroll: i32) -> Roll {
+        Roll { reroll, ..*self }
+    }
+
+    /// The same roll, treating any dice that roll lower than the given number as that number.
+    pub fn with_minimum(&self, minimum: i32) -> Roll {
+        Roll { minimum, ..*self }
     }
 }
 
 impl fmt::Display for Roll {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.rolls > 0 {
-            write!(f, "{}d{}", self.rolls, self.sides).and(match self.modifier.cmp(&0) {
-                Ordering::Greater => write!(f, " + {}", self.modifier),
-                Ordering::Less => write!(f, " - {}", self.modifier.abs()),
-                Ordering::Equal => Ok(()),
-            })
+            write!(f, "{}d{}", self.rolls, self.sides)
+                .and(match self.modifier.cmp(&0) {
+                    Ordering::Greater => write!(f, " + {}", self.modifier),
+                    Ordering::Less => write!(f, " - {}", self.modifier.abs()),
+                    Ordering::Equal => Ok(()),
+                })
+                .and(match self.reroll {
+                    0 => Ok(()),
+                    1 => write!(f, ", rerolling 1s"),
+                    2 => write!(f, ", rerolling 1s and 2s"),
+                    reroll => write!(f, ", rerolling 1s to {}s", reroll),
+                })
+                .and(match self.minimum {
+                    0 | 1 => Ok(()),
+                    2 => write!(f, ", treating 1s as 2s"),
+                    3 => write!(f, ", treating 1s and 2s as 3s"),
+                    minimum => write!(f, ", treating 1s to {}s as {}s", minimum - 1, minimum),
+                })
         } else {
             write!(f, "{}", self.modifier)
         }
@@ -339,10 +457,36 @@ impl ConditionalRoll {
         self.condition
     }
 
+    pub fn rolls(&self) -> usize {
+        self.roll.rolls
+    }
+
+    pub fn sides(&self) -> i32 {
+        self.roll.sides
+    }
+
+    /// Whether the roll is of a single d20, as for an ability check, attack roll or saving throw.
+    pub fn is_d20(&self) -> bool {
+        self.roll.rolls == 1 && self.roll.sides == 20
+    }
+
     pub fn from_roll(roll: Roll, condition: Option<Condition>) -> ConditionalRoll {
         ConditionalRoll { roll, condition }
     }
 
+    /// The same roll with an extra modifier added, as from a magic item.
+    pub fn add_modifier(self, modifier: i32) -> ConditionalRoll {
+        ConditionalRoll {
+            roll: self.roll.add_modifier(modifier),
+            ..self
+        }
+    }
+
+    /// The same roll under a different condition.
+    pub fn with_condition(self, condition: Option<Condition>) -> ConditionalRoll {
+        ConditionalRoll { condition, ..self }
+    }
+
     /// Parse a roll from a String using conventional Dungeons and Dragons syntax.
     pub fn parse(string: &str) -> Result<ConditionalRoll, ParserError> {
         lazy_static! {
@@ -401,6 +545,54 @@ impl ConditionalRoll {
     }
 }
 
+impl Limits {
+    pub const MAXIMUM: Limits = Limits {
+        rolls: MAXIMUM_ROLLS,
+        sides: MAXIMUM_SIDES,
+    };
+
+    /// Create limits, or None if they aren't positive or are more than the maximum allowed
+    /// values.
+    pub fn new(rolls: usize, sides: i32) -> Option<Limits> {
+        if rolls < 1 || rolls > MAXIMUM_ROLLS || sides < 1 || sides > MAXIMUM_SIDES {
+            None
+        } else {
+            Some(Limits { rolls, sides })
+        }
+    }
+
+    /// Parse limits written like a roll of the most dice with the most sides, such as "20d100".
+    pub fn parse(string: &str) -> Option<Limits> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"^(?i)(\d+) *d *(\d+)$").unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        Limits::new(
+            captures.get(1)?.as_str().parse().ok()?,
+            captures.get(2)?.as_str().parse().ok()?,
+        )
+    }
+
+    /// Check that a number of dice with a number of sides may be rolled within these limits.
+    pub fn check(self, rolls: usize, sides: i32) -> Result<(), Error> {
+        if rolls > self.rolls {
+            Err(Error::RollsTooGreat(self.rolls))
+        } else if sides <= 0 {
+            Err(Error::SidesNonPositive)
+        } else if sides > self.sides {
+            Err(Error::SidesTooGreat(self.sides))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for Limits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.rolls, self.sides)
+    }
+}
+
 impl fmt::Display for ConditionalRoll {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.roll.fmt(f).and(match self.condition {
@@ -411,6 +603,18 @@ impl fmt::Display for ConditionalRoll {
     }
 }
 
+/// Roll on a table where each row is listed with the highest roll for it, such as the tables in the
+/// Dungeon Master's Guide. The die has as many sides as the highest roll for the last row.
+pub fn roll_on_table<'a, T, R: Rng + ?Sized>(table: &'a [(i32, T)], rng: &mut R) -> &'a T {
+    let sides = table.last().map_or(1, |(up_to, _)| *up_to);
+    let roll = Roll::new_clamped(1, sides, 0).roll(rng).result();
+    table
+        .iter()
+        .find(|(up_to, _)| roll <= *up_to)
+        .map(|(_, row)| row)
+        .unwrap_or_else(|| &table[table.len() - 1].1)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -426,20 +630,43 @@ mod test {
 
     #[test]
     fn test_roll_rolls_too_great() {
-        let expected = Err(Error::RollsTooGreat);
-        let actual = ConditionalRoll::new(101, 20, 0, None);
+        let expected = Err(Error::RollsTooGreat(MAXIMUM_ROLLS));
+        let actual = ConditionalRoll::new(10001, 20, 0, None);
 
         assert_eq!(actual, expected);
     }
 
     #[test]
     fn test_roll_sides_too_great() {
-        let expected = Err(Error::SidesTooGreat);
+        let expected = Err(Error::SidesTooGreat(MAXIMUM_SIDES));
         let actual = ConditionalRoll::new(1, 101, 0, None);
 
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_limits() {
+        let limits = Limits::parse("20d12");
+        assert_eq!(
+            limits,
+            Some(Limits {
+                rolls: 20,
+                sides: 12
+            })
+        );
+        assert_eq!(
+            limits.map(|limits| limits.check(21, 6)),
+            Some(Err(Error::RollsTooGreat(20)))
+        );
+        assert_eq!(
+            limits.map(|limits| limits.check(2, 20)),
+            Some(Err(Error::SidesTooGreat(12)))
+        );
+        assert_eq!(limits.map(|limits| limits.check(20, 12)), Some(Ok(())));
+        assert_eq!(Limits::parse("20d1000"), None);
+        assert_eq!(Limits::parse("0d6"), None);
+    }
+
     #[test]
     fn test_display_roll_simple() {
         let roll = ConditionalRoll::new(1, 20, 0, None).unwrap();
@@ -480,6 +707,50 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_display_roll_with_reroll() {
+        let roll = Roll::new_unsafe(2, 6, 3).with_reroll(2);
+
+        assert_eq!(roll.to_string(), "2d6 + 3, rerolling 1s and 2s");
+        assert_eq!(
+            roll.add_modifier(-3).to_string(),
+            "2d6, rerolling 1s and 2s"
+        );
+        assert_eq!(
+            roll.multiply_rolls(2).to_string(),
+            "4d6 + 3, rerolling 1s and 2s"
+        );
+    }
+
+    #[test]
+    fn test_roll_with_reroll() {
+        let mut rng = Pcg32::new(0, 0);
+
+        let roll = Roll::new_unsafe(10, 6, 0).with_reroll(2);
+
+        for _ in 0..100 {
+            assert!(roll
+                .roll_once_component(&mut rng)
+                .iter()
+                .all(|value| *value >= 1 && *value <= 6));
+        }
+    }
+
+    #[test]
+    fn test_roll_with_minimum() {
+        let mut rng = Pcg32::new(0, 0);
+
+        let roll = Roll::new_unsafe(10, 6, 0).with_minimum(3);
+
+        assert_eq!(roll.to_string(), "10d6, treating 1s and 2s as 3s");
+        for _ in 0..100 {
+            assert!(roll
+                .roll_once_component(&mut rng)
+                .iter()
+                .all(|value| *value >= 3 && *value <= 6));
+        }
+    }
+
     #[test]
     fn test_display_roll_with_advantage() {
         let roll = ConditionalRoll::new(1, 20, 0, Some(Condition::Advantage)).unwrap();
@@ -777,6 +1048,22 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_display_roll_result_summarised() {
+        let mut dice = vec![3; 999];
+        dice.push(6);
+        let result = RollResult {
+            result: 3008,
+            dice,
+            modifier: 5,
+            critical: None,
+        };
+        let expected = "**3008** (1000 dice — total 3003, lowest 3, highest 6, mean 3.00; + __5__)";
+        let actual = result.to_string();
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_display_roll_result_with_maximum_multiples() {
         let result = RollResult {
@@ -921,4 +1208,53 @@ mod test {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_combine_conditions() {
+        assert_eq!(Condition::combine(vec![]), None);
+        assert_eq!(
+            Condition::combine(vec![Condition::Advantage, Condition::Advantage]),
+            Some(Condition::Advantage)
+        );
+        assert_eq!(
+            Condition::combine(vec![Condition::Disadvantage]),
+            Some(Condition::Disadvantage)
+        );
+        assert_eq!(
+            Condition::combine(vec![
+                Condition::Advantage,
+                Condition::Disadvantage,
+                Condition::Disadvantage
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_add_advantage() {
+        assert_eq!(Condition::add_advantage(None), Some(Condition::Advantage));
+        assert_eq!(
+            Condition::add_advantage(Some(Condition::Advantage)),
+            Some(Condition::Advantage)
+        );
+        assert_eq!(
+            Condition::add_advantage(Some(Condition::Disadvantage)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_d20() {
+        assert!(ConditionalRoll::new_unsafe(1, 20, 5, None).is_d20());
+        assert!(!ConditionalRoll::new_unsafe(2, 20, 0, None).is_d20());
+        assert!(!ConditionalRoll::new_unsafe(1, 12, 0, None).is_d20());
+    }
+
+    #[test]
+    fn test_roll_on_table() {
+        let table = [(3, "goblins"), (5, "wolves"), (6, "an owlbear")];
+        // Every die rolls a 1
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+        assert_eq!(roll_on_table(&table, &mut rng), &"goblins");
+    }
 }
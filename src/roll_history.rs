@@ -0,0 +1,124 @@
+use crate::roll_signature::SignedRoll;
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
+use serenity::model::id::{ChannelId, UserId};
+
+/// A roll made in a channel, kept so that a session can be read back as a log of what happened.
+#[derive(Debug, Eq, PartialEq)]
+pub struct RollHistoryEntry {
+    /// The display name of the character who made the roll, if they have one.
+    pub character_name: Option<String>,
+    /// What was rolled, such as "Perception" or "2d6 + 3".
+    pub description: String,
+    pub result: i32,
+    pub tag: Option<String>,
+    pub purpose: Option<String>,
+}
+
+/// Record a roll in the roll history, along with the code that verifies it. A message can make
+/// several rolls, so each is recorded with its index among the rolls the message made.
+pub fn log_roll(
+    connection: &Connection,
+    roll: &SignedRoll,
+    entry_index: usize,
+    entry: &RollHistoryEntry,
+    signature: &str,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[
+        &roll.message_id.to_string(),
+        &(entry_index as i64),
+        &roll.channel_id.to_string(),
+        &roll.user_id.to_string(),
+        &entry.character_name,
+        &entry.description,
+        &entry.result,
+        &entry.tag,
+        &entry.purpose,
+        &roll.rolled,
+        &signature,
+    ];
+    connection.execute(
+        "INSERT INTO roll_history (message_id, entry_index, channel_id, user_id, character_name, description, result, tag, purpose, rolled, signature) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+        params,
+    )
+}
+
+/// Forget every roll a user has made in a channel. Returns the number of rolls forgotten.
+pub fn delete_rolls(
+    connection: &Connection,
+    channel_id: ChannelId,
+    user_id: UserId,
+) -> RusqliteResult<usize> {
+    let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string()];
+    connection.execute(
+        "DELETE FROM roll_history WHERE channel_id = $1 AND user_id = $2",
+        params,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::roll_signature::RollSigner;
+    use serenity::model::id::MessageId;
+
+    fn connection() -> Connection {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(include_str!("../config/sql/014-roll-history.sql"))
+            .unwrap();
+        connection
+            .execute_batch(include_str!("../config/sql/033-roll-signatures.sql"))
+            .unwrap();
+        connection
+            .execute_batch(include_str!("../config/sql/060-roll-history-entries.sql"))
+            .unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_log_several_rolls_for_one_message() {
+        let connection = connection();
+        let signer = RollSigner::new(b"secret");
+        let entries = vec![
+            RollHistoryEntry {
+                character_name: Some("Tordek".to_owned()),
+                description: "Longsword attack".to_owned(),
+                result: 17,
+                tag: None,
+                purpose: None,
+            },
+            RollHistoryEntry {
+                character_name: Some("Tordek".to_owned()),
+                description: "Longsword attack".to_owned(),
+                result: 9,
+                tag: None,
+                purpose: None,
+            },
+        ];
+        for (entry_index, entry) in entries.iter().enumerate() {
+            let roll = SignedRoll {
+                message_id: MessageId(3),
+                channel_id: ChannelId(1),
+                user_id: UserId(2),
+                rolled: "2020-06-01 19:30:00".to_owned(),
+                character_name: entry.character_name.clone(),
+                description: entry.description.clone(),
+                result: entry.result,
+            };
+            let code = signer.sign(&roll);
+            log_roll(&connection, &roll, entry_index, entry, &code).unwrap();
+            assert_eq!(SignedRoll::get(&connection, &code).unwrap(), Some(roll));
+        }
+        let count: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM roll_history WHERE message_id = '3'",
+                rusqlite::NO_PARAMS,
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+}
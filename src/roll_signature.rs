@@ -0,0 +1,177 @@
+use crate::roll_history::RollHistoryEntry;
+use rand::Rng;
+use regex::Regex;
+use ring::hmac;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, MessageId, UserId};
+
+/// The number of bytes of a roll's signature that are shown in its code, which is enough that a
+/// code can't be guessed while still being short enough to type.
+const CODE_BYTES: usize = 5;
+
+/// Signs rolls with a secret known only to the bot, so that the code shown with a roll proves
+/// that the roll was made by the bot and hasn't been edited since.
+pub struct RollSigner {
+    key: hmac::Key,
+}
+
+/// A roll as it was recorded in the roll history, along with everything its code is signed
+/// over.
+#[derive(Debug, Eq, PartialEq)]
+pub struct SignedRoll {
+    pub message_id: MessageId,
+    pub channel_id: ChannelId,
+    pub user_id: UserId,
+    /// When the roll was made, in UTC, such as "2020-06-01 19:30:00".
+    pub rolled: String,
+    pub character_name: Option<String>,
+    pub description: String,
+    pub result: i32,
+}
+
+impl RollSigner {
+    pub fn new(secret: &[u8]) -> RollSigner {
+        RollSigner {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+        }
+    }
+
+    /// Create a signer with a random secret, for when no secret has been configured. Rolls
+    /// signed with a random secret can't be verified once the bot restarts.
+    pub fn random() -> RollSigner {
+        let secret: [u8; 32] = rand::thread_rng().gen();
+        RollSigner::new(&secret)
+    }
+
+    /// The code for a roll, which is the start of the roll's signature in hexadecimal, such as
+    /// "3FA92C07D1".
+    pub fn sign(&self, roll: &SignedRoll) -> String {
+        self.code(&roll.signed_content())
+    }
+
+    /// The code for any content, which is the start of its signature in hexadecimal.
+    pub fn code(&self, content: &str) -> String {
+        let tag = hmac::sign(&self.key, content.as_bytes());
+        tag.as_ref()[..CODE_BYTES]
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect()
+    }
+
+    /// Whether a code is the genuine code for a roll.
+    pub fn verify(&self, roll: &SignedRoll, code: &str) -> bool {
+        self.sign(roll) == normalise_code(code)
+    }
+}
+
+impl SignedRoll {
+    pub fn new(message: &Message, entry: &RollHistoryEntry) -> SignedRoll {
+        SignedRoll {
+            message_id: message.id,
+            channel_id: message.channel_id,
+            user_id: message.author.id,
+            rolled: message
+                .timestamp
+                .naive_utc()
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            character_name: entry.character_name.clone(),
+            description: entry.description.clone(),
+            result: entry.result,
+        }
+    }
+
+    /// Find the roll in the roll history with a code, in any channel.
+    pub fn get(connection: &Connection, code: &str) -> RusqliteResult<Option<SignedRoll>> {
+        let params: &[&dyn ToSql] = &[&normalise_code(code)];
+        connection
+            .query_row(
+                "SELECT message_id, channel_id, user_id, \
+                 datetime(rolled) AS rolled, \
+                 character_name, description, result \
+                 FROM roll_history \
+                 WHERE signature = $1",
+                params,
+                SignedRoll::from_row,
+            )
+            .optional()
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<SignedRoll> {
+        let message_id: String = row.get("message_id")?;
+        let channel_id: String = row.get("channel_id")?;
+        let user_id: String = row.get("user_id")?;
+        Ok(SignedRoll {
+            message_id: MessageId(message_id.parse().unwrap_or_default()),
+            channel_id: ChannelId(channel_id.parse().unwrap_or_default()),
+            user_id: UserId(user_id.parse().unwrap_or_default()),
+            rolled: row.get("rolled")?,
+            character_name: row.get("character_name")?,
+            description: row.get("description")?,
+            result: row.get("result")?,
+        })
+    }
+
+    /// The content that a roll's signature is made from, with each part on its own line.
+    fn signed_content(&self) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.message_id,
+            self.channel_id,
+            self.user_id,
+            self.rolled,
+            self.character_name.as_deref().unwrap_or(""),
+            self.description,
+            self.result
+        )
+    }
+}
+
+/// A code as it would be stored, ignoring case, spaces and dashes, since codes are often copied
+/// out of screenshots by hand.
+fn normalise_code(code: &str) -> String {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"[\s-]").unwrap();
+    }
+    RE.replace_all(code, "").to_uppercase()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn perception() -> SignedRoll {
+        SignedRoll {
+            message_id: MessageId(3),
+            channel_id: ChannelId(1),
+            user_id: UserId(2),
+            rolled: "2020-09-13 12:26:40".to_owned(),
+            character_name: Some("Alice".to_owned()),
+            description: "Perception (1d20 + 3)".to_owned(),
+            result: 17,
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = RollSigner::new(b"secret");
+        let code = signer.sign(&perception());
+        assert_eq!(code.len(), CODE_BYTES * 2);
+        assert_eq!(signer.sign(&perception()), code);
+        assert!(signer.verify(&perception(), &code.to_lowercase()));
+        let tampered = SignedRoll {
+            result: 20,
+            ..perception()
+        };
+        assert!(!signer.verify(&tampered, &code));
+        assert!(!RollSigner::new(b"another secret").verify(&perception(), &code));
+    }
+
+    #[test]
+    fn test_normalise_code() {
+        assert_eq!(normalise_code("3fa9-2c07 d1"), "3FA92C07D1");
+    }
+}
@@ -0,0 +1,525 @@
+use crate::roll::roll_on_table;
+use rand::Rng;
+use std::fmt;
+
+/// The size of a settlement, which sets how many people live there.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SettlementSize {
+    Village,
+    Town,
+    City,
+}
+
+/// How fine a tavern is, which sets what's on the menu and what it costs, from the lifestyle
+/// expenses in the Player's Handbook.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TavernQuality {
+    Poor,
+    Modest,
+    Comfortable,
+    Wealthy,
+}
+
+/// Whoever runs a tavern, with something memorable about them for the DM to play up.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proprietor {
+    pub name: &'static str,
+    pub race: &'static str,
+    pub mannerism: &'static str,
+}
+
+/// Something to eat, drink or rent at a tavern, and its price.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MenuItem {
+    pub item: String,
+    pub price: &'static str,
+}
+
+/// A random tavern, with a name, a proprietor, a menu and a rumour overheard at the bar.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Tavern {
+    pub name: String,
+    pub quality: TavernQuality,
+    pub proprietor: Proprietor,
+    pub menu: Vec<MenuItem>,
+    pub rumour: &'static str,
+}
+
+/// A sketch of a settlement for the DM to build on, from the random settlement tables in the
+/// Dungeon Master's Guide, along with its tavern.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Settlement {
+    pub name: String,
+    pub size: SettlementSize,
+    pub population: i32,
+    pub race_relations: &'static str,
+    pub ruler: &'static str,
+    pub notable_trait: &'static str,
+    pub known_for: &'static str,
+    pub calamity: &'static str,
+    pub tavern: Tavern,
+}
+
+const SIZES: [(i32, SettlementSize); 3] = [
+    (3, SettlementSize::Village),
+    (5, SettlementSize::Town),
+    (6, SettlementSize::City),
+];
+
+const QUALITIES: [(i32, TavernQuality); 4] = [
+    (2, TavernQuality::Poor),
+    (4, TavernQuality::Modest),
+    (5, TavernQuality::Comfortable),
+    (6, TavernQuality::Wealthy),
+];
+
+const TAVERN_ADJECTIVES: [&str; 20] = [
+    "Silver",
+    "Golden",
+    "Staggering",
+    "Laughing",
+    "Prancing",
+    "Gilded",
+    "Running",
+    "Howling",
+    "Slaughtered",
+    "Leering",
+    "Drunken",
+    "Leaping",
+    "Roaring",
+    "Frowning",
+    "Lonely",
+    "Wandering",
+    "Mysterious",
+    "Barking",
+    "Black",
+    "Gleaming",
+];
+
+const TAVERN_NOUNS: [&str; 20] = [
+    "Eel", "Dolphin", "Dwarf", "Pegasus", "Pony", "Rose", "Stag", "Wolf", "Lamb", "Demon", "Goat",
+    "Spirit", "Horde", "Jester", "Mountain", "Eagle", "Satyr", "Dog", "Spider", "Star",
+];
+
+const PROPRIETOR_NAMES: [&str; 20] = [
+    "Agnes",
+    "Bram",
+    "Corwin",
+    "Delphine",
+    "Esme",
+    "Fargrim",
+    "Gundren",
+    "Hilde",
+    "Ivo",
+    "Jessa",
+    "Korrin",
+    "Lidda",
+    "Merric",
+    "Nyssa",
+    "Orsik",
+    "Perrin",
+    "Quenna",
+    "Rurik",
+    "Seraphine",
+    "Tobin",
+];
+
+const RACES: [(i32, &str); 9] = [
+    (10, "human"),
+    (12, "dwarf"),
+    (14, "halfling"),
+    (15, "elf"),
+    (16, "gnome"),
+    (17, "half-elf"),
+    (18, "half-orc"),
+    (19, "dragonborn"),
+    (20, "tiefling"),
+];
+
+const MANNERISMS: [&str; 20] = [
+    "sings, whistles or hums quietly while working",
+    "speaks in rhyme or some other peculiar way",
+    "has a particularly low or high voice",
+    "slurs words, lisps or stutters",
+    "enunciates overly clearly",
+    "speaks loudly",
+    "whispers",
+    "uses flowery speech or long words",
+    "frequently uses the wrong word",
+    "uses colourful oaths and exclamations",
+    "makes constant jokes or puns",
+    "is prone to predictions of doom",
+    "fidgets",
+    "squints",
+    "stares into the distance",
+    "chews something",
+    "paces",
+    "taps fingers on the bar",
+    "bites fingernails",
+    "twirls hair or tugs beard",
+];
+
+const POOR_MEALS: [&str; 6] = [
+    "Thin cabbage soup and a heel of black bread",
+    "Boiled turnips with a scrap of salt pork",
+    "Gruel with whatever was left over yesterday",
+    "A bowl of watery fish stew",
+    "Hard cheese and stale bread",
+    "Mystery meat on a stick",
+];
+
+const MODEST_MEALS: [&str; 6] = [
+    "Mutton stew with barley bread",
+    "Sausages, onions and mashed peas",
+    "Roast chicken with root vegetables",
+    "Smoked trout with brown bread and butter",
+    "Pork pie with pickled onions",
+    "Lentil and bacon pottage",
+];
+
+const COMFORTABLE_MEALS: [&str; 6] = [
+    "Venison pie with a rich gravy",
+    "Honey-glazed ham with roasted apples",
+    "Braised beef in dark ale",
+    "Grilled river fish with herbs and lemon",
+    "Roast goose stuffed with chestnuts",
+    "Mushroom and leek pie with a wedge of aged cheese",
+];
+
+const WEALTHY_MEALS: [&str; 6] = [
+    "Roast boar with spiced pears",
+    "Pheasant in a cream and wine sauce",
+    "Saddle of lamb with rosemary and garlic",
+    "Candied quail with saffron rice",
+    "Lobster poached in butter",
+    "Seven courses of the chef's choosing",
+];
+
+const RUMOURS: [&str; 20] = [
+    "The old mill outside town is haunted, and whoever goes in at night comes out with white hair.",
+    "A merchant's caravan went missing on the north road, and nobody's gone looking for it yet.",
+    "The baron's heir hasn't been seen in weeks, and the servants are paid well to keep quiet.",
+    "Someone's been buying up every silver dagger in town.",
+    "Goblins have been seen wearing the colours of the local guard.",
+    "A dragon was spotted circling the mountains last full moon.",
+    "The temple's holy relic is a fake, and the real one was sold years ago.",
+    "Strange lights have been seen over the marsh, and the frogs have gone silent.",
+    "A dwarf came through last week paying for drinks with coins nobody recognised.",
+    "The well in the square tastes of iron since the earthquake, and the children dream of a door.",
+    "A retired adventurer in town has a map to a tomb they're too old to plunder.",
+    "The thieves' guild is looking for outsiders for a job the locals won't touch.",
+    "Wolves the size of horses have been taking sheep from the hill farms.",
+    "The new tax collector can't be bribed, which has everyone worried.",
+    "A wizard's tower appeared on the hill overnight, and nobody has seen anyone go in or out.",
+    "The miners broke into an old tunnel and sealed it again the same day.",
+    "Bodies in the graveyard have been dug up, but nothing was taken.",
+    "A bard has been singing a song about the party, and it isn't flattering.",
+    "The ferryman won't cross the river after dark anymore, and won't say why.",
+    "Someone is offering a hundred gold pieces for the head of a talking raven.",
+];
+
+const SETTLEMENT_PREFIXES: [&str; 20] = [
+    "Oak", "Raven", "Stone", "Ash", "Elm", "Thorn", "Wolf", "Black", "Red", "Silver", "Mill",
+    "Bridge", "Green", "Kings", "High", "Frost", "Briar", "Hollow", "Amber", "Marsh",
+];
+
+const SETTLEMENT_SUFFIXES: [&str; 20] = [
+    "ford", "wick", "bury", "dale", "haven", "holm", "ton", "field", "brook", "gate", "moor",
+    "stead", "fall", "mouth", "crest", "vale", "hold", "cross", "well", "shire",
+];
+
+const RACE_RELATIONS: [(i32, &str); 7] = [
+    (10, "Harmony"),
+    (14, "Tension or rivalry"),
+    (16, "Racial majority are conquerors"),
+    (17, "Racial minority are rulers"),
+    (18, "Racial minority are refugees"),
+    (19, "Racial majority oppresses minority"),
+    (20, "Racial minority oppresses majority"),
+];
+
+const RULERS: [(i32, &str); 13] = [
+    (5, "Respected, fair and just"),
+    (8, "Feared tyrant"),
+    (9, "Weakling manipulated by others"),
+    (10, "Illegitimate ruler, simmering civil war"),
+    (11, "Ruled or controlled by a powerful monster"),
+    (12, "Mysterious, anonymous cabal"),
+    (13, "Contested leadership, open fighting"),
+    (14, "Cabal seized power openly"),
+    (15, "Doltish lout"),
+    (16, "On deathbed, claimants compete for power"),
+    (18, "Iron-willed but respected"),
+    (19, "Religious leader"),
+    (20, "Council of elders"),
+];
+
+const NOTABLE_TRAITS: [(i32, &str); 20] = [
+    (1, "Canals in place of streets"),
+    (2, "Massive statue or monument"),
+    (3, "Grand temple"),
+    (4, "Large fortress"),
+    (5, "Verdant parks and orchards"),
+    (6, "River divides the town"),
+    (7, "Major trade center"),
+    (8, "Headquarters of a powerful family or guild"),
+    (9, "Population mostly wealthy"),
+    (10, "Destitute, rundown"),
+    (11, "Awful smell from tanneries or open sewers"),
+    (12, "Center of trade for one specific good"),
+    (13, "Site of many battles"),
+    (14, "Site of a mythic or magical event"),
+    (15, "Important library or archive"),
+    (16, "Worship of all gods banned"),
+    (17, "Sinister reputation"),
+    (18, "Notable library or academy"),
+    (19, "Site of an important tomb or graveyard"),
+    (20, "Built atop ancient ruins"),
+];
+
+const KNOWN_FOR: [(i32, &str); 20] = [
+    (1, "Delicious cuisine"),
+    (2, "Rude people"),
+    (3, "Greedy merchants"),
+    (4, "Artists and writers"),
+    (5, "Great hero or savior"),
+    (6, "Flowers"),
+    (7, "Hordes of beggars"),
+    (8, "Tough warriors"),
+    (9, "Dark magic"),
+    (10, "Decadence"),
+    (11, "Piety"),
+    (12, "Gambling"),
+    (13, "Godlessness"),
+    (14, "Education"),
+    (15, "Wines"),
+    (16, "High fashion"),
+    (17, "Political intrigue"),
+    (18, "Powerful guilds"),
+    (19, "Strong drink"),
+    (20, "Patriotism"),
+];
+
+const CALAMITIES: [(i32, &str); 16] = [
+    (1, "Suspected vampire infestation"),
+    (2, "New cult seeks converts"),
+    (3, "Important figure died, and murder is suspected"),
+    (4, "War between rival thieves' guilds"),
+    (6, "Plague or famine, which sparks riots"),
+    (7, "Corrupt officials"),
+    (9, "Marauding monsters"),
+    (10, "Powerful wizard has moved into town"),
+    (11, "Economic depression, with trade disrupted"),
+    (12, "Flooding"),
+    (13, "Undead stirring in cemeteries"),
+    (14, "Prophecy of doom"),
+    (15, "Brink of war"),
+    (16, "Internal strife, which leads to anarchy"),
+    (17, "Besieged by enemies"),
+    (20, "Scandal threatens powerful families"),
+];
+
+impl SettlementSize {
+    pub fn parse(string: &str) -> Option<SettlementSize> {
+        match string.trim().to_lowercase().as_str() {
+            "village" | "hamlet" => Some(SettlementSize::Village),
+            "town" => Some(SettlementSize::Town),
+            "city" => Some(SettlementSize::City),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SettlementSize::Village => "village",
+            SettlementSize::Town => "town",
+            SettlementSize::City => "city",
+        }
+    }
+
+    /// The range of people living in a settlement of the size, from the Dungeon Master's Guide.
+    fn population_range(self) -> (i32, i32) {
+        match self {
+            SettlementSize::Village => (20, 1000),
+            SettlementSize::Town => (1000, 6000),
+            SettlementSize::City => (6000, 25000),
+        }
+    }
+}
+
+impl TavernQuality {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TavernQuality::Poor => "poor",
+            TavernQuality::Modest => "modest",
+            TavernQuality::Comfortable => "comfortable",
+            TavernQuality::Wealthy => "wealthy",
+        }
+    }
+
+    /// The price of a meal and a room for the night, from the food, drink and lodging prices in
+    /// the Player's Handbook.
+    fn prices(self) -> (&'static str, &'static str) {
+        match self {
+            TavernQuality::Poor => ("6 cp", "1 sp"),
+            TavernQuality::Modest => ("3 sp", "5 sp"),
+            TavernQuality::Comfortable => ("5 sp", "8 sp"),
+            TavernQuality::Wealthy => ("8 sp", "2 gp"),
+        }
+    }
+
+    fn meals(self) -> &'static [&'static str] {
+        match self {
+            TavernQuality::Poor => &POOR_MEALS,
+            TavernQuality::Modest => &MODEST_MEALS,
+            TavernQuality::Comfortable => &COMFORTABLE_MEALS,
+            TavernQuality::Wealthy => &WEALTHY_MEALS,
+        }
+    }
+}
+
+impl Tavern {
+    /// Generate a tavern, such as "The Prancing Pony", with a proprietor, a menu priced for how
+    /// fine it is, and a rumour.
+    pub fn generate<R: Rng + ?Sized>(rng: &mut R) -> Tavern {
+        let name = format!(
+            "The {} {}",
+            pick(&TAVERN_ADJECTIVES, rng),
+            pick(&TAVERN_NOUNS, rng)
+        );
+        let quality = *roll_on_table(&QUALITIES, rng);
+        let proprietor = Proprietor {
+            name: pick(&PROPRIETOR_NAMES, rng),
+            race: *roll_on_table(&RACES, rng),
+            mannerism: pick(&MANNERISMS, rng),
+        };
+        let (meal_price, room_price) = quality.prices();
+        let wine = if quality == TavernQuality::Wealthy {
+            MenuItem {
+                item: "Bottle of fine wine".to_owned(),
+                price: "10 gp",
+            }
+        } else {
+            MenuItem {
+                item: "Pitcher of common wine".to_owned(),
+                price: "2 sp",
+            }
+        };
+        let menu = vec![
+            MenuItem {
+                item: pick(quality.meals(), rng).to_owned(),
+                price: meal_price,
+            },
+            MenuItem {
+                item: "Mug of ale".to_owned(),
+                price: "4 cp",
+            },
+            wine,
+            MenuItem {
+                item: "Room for the night".to_owned(),
+                price: room_price,
+            },
+        ];
+        Tavern {
+            name,
+            quality,
+            proprietor,
+            menu,
+            rumour: pick(&RUMOURS, rng),
+        }
+    }
+}
+
+impl Settlement {
+    /// Generate a settlement of a size, or a random size if none is given.
+    pub fn generate<R: Rng + ?Sized>(size: Option<SettlementSize>, rng: &mut R) -> Settlement {
+        let size = size.unwrap_or_else(|| *roll_on_table(&SIZES, rng));
+        let name = format!(
+            "{}{}",
+            pick(&SETTLEMENT_PREFIXES, rng),
+            pick(&SETTLEMENT_SUFFIXES, rng)
+        );
+        let (lowest, highest) = size.population_range();
+        // Round to the nearest ten, since nobody knows exactly how many people live there
+        let population = rng.gen_range(lowest / 10, highest / 10 + 1) * 10;
+        Settlement {
+            name,
+            size,
+            population,
+            race_relations: *roll_on_table(&RACE_RELATIONS, rng),
+            ruler: *roll_on_table(&RULERS, rng),
+            notable_trait: *roll_on_table(&NOTABLE_TRAITS, rng),
+            known_for: *roll_on_table(&KNOWN_FOR, rng),
+            calamity: *roll_on_table(&CALAMITIES, rng),
+            tavern: Tavern::generate(rng),
+        }
+    }
+}
+
+impl fmt::Display for Proprietor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let article = if self.race.starts_with('e') {
+            "an"
+        } else {
+            "a"
+        };
+        write!(
+            f,
+            "{}, {} {} who {}",
+            self.name, article, self.race, self.mannerism
+        )
+    }
+}
+
+/// Pick an item from a list, each as likely as the others.
+fn pick<R: Rng + ?Sized>(list: &[&'static str], rng: &mut R) -> &'static str {
+    list[rng.gen_range(0, list.len())]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn test_generate_tavern() {
+        // Every die rolls a 1
+        let tavern = Tavern::generate(&mut StepRng::new(0, 0));
+        assert_eq!(tavern.name, "The Silver Eel");
+        assert_eq!(tavern.quality, TavernQuality::Poor);
+        assert_eq!(
+            tavern.proprietor.to_string(),
+            "Agnes, a human who sings, whistles or hums quietly while working"
+        );
+        assert_eq!(
+            tavern
+                .menu
+                .iter()
+                .map(|item| (item.item.as_str(), item.price))
+                .collect::<Vec<(&str, &str)>>(),
+            vec![
+                ("Thin cabbage soup and a heel of black bread", "6 cp"),
+                ("Mug of ale", "4 cp"),
+                ("Pitcher of common wine", "2 sp"),
+                ("Room for the night", "1 sp"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_settlement() {
+        let settlement = Settlement::generate(Some(SettlementSize::Town), &mut StepRng::new(0, 0));
+        assert_eq!(settlement.name, "Oakford");
+        assert_eq!(settlement.size, SettlementSize::Town);
+        assert_eq!(settlement.population, 1000);
+        assert_eq!(settlement.ruler, "Respected, fair and just");
+        let settlement = Settlement::generate(None, &mut StepRng::new(0, 0));
+        assert_eq!(settlement.size, SettlementSize::Village);
+        assert_eq!(settlement.population, 20);
+        assert_eq!(
+            SettlementSize::parse("Hamlet"),
+            Some(SettlementSize::Village)
+        );
+        assert_eq!(SettlementSize::parse("castle"), None);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::content;
+use crate::roll::Roll;
+use crate::weapon::DamageType;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The name of the spell table, which is embedded from `src/data/`, and can be overridden by a
+/// file of the same name in the content directory.
+const SPELLS_FILE_NAME: &str = "spells.json";
+
+lazy_static! {
+    static ref SPELLS: Result<HashMap<String, Spell>, content::Error> = load_spells();
+}
+
+/// A spell that a character can cast.
+pub struct Spell {
+    pub name: String,
+    /// The spell's level, which is 0 for a cantrip.
+    pub level: i32,
+    /// The spell's damage when cast with a spell slot of its own level, or by a character below
+    /// 5th level for a cantrip.
+    pub damage: Option<Roll>,
+    pub damage_type: Option<DamageType>,
+    /// The extra damage for each slot level above the spell's level.
+    pub higher_levels: Option<Roll>,
+    /// A ritual spell can be cast without expending a spell slot, by taking 10 minutes longer to
+    /// cast it.
+    pub ritual: bool,
+}
+
+impl Spell {
+    /// Find a spell by name, ignoring case.
+    pub fn find(name: &str) -> Option<&'static Spell> {
+        SPELLS
+            .as_ref()
+            .ok()
+            .and_then(|spells| spells.get(&name.trim().to_lowercase()))
+    }
+
+    pub fn is_cantrip(&self) -> bool {
+        self.level == 0
+    }
+
+    /// The spell's damage when cast with a spell slot of the given level, or by a character of the
+    /// given level for a cantrip. A cantrip deals an extra die of damage at 5th, 11th and 17th
+    /// level, and a spell with higher level scaling adds its scaling damage for each slot level
+    /// above its own.
+    pub fn damage_at(&self, slot_level: i32, character_level: i32) -> Option<Roll> {
+        let damage = self.damage?;
+        if self.is_cantrip() {
+            return Some(damage.multiply_rolls(cantrip_dice(character_level)));
+        }
+        let levels_above = (slot_level - self.level).max(0);
+        Some(match self.higher_levels {
+            Some(higher_levels) if levels_above > 0 => Roll::new_clamped(
+                damage.rolls() + levels_above as usize * higher_levels.rolls(),
+                damage.sides(),
+                damage.modifier() + levels_above * higher_levels.modifier(),
+            ),
+            _ => damage,
+        })
+    }
+}
+
+/// The number of times a cantrip's damage dice are rolled by a character of the given level.
+fn cantrip_dice(character_level: i32) -> usize {
+    match character_level {
+        std::i32::MIN..=4 => 1,
+        5..=10 => 2,
+        11..=16 => 3,
+        _ => 4,
+    }
+}
+
+/// An entry in the spell table, which is validated when it's turned into a spell. A spell that
+/// doesn't deal damage has neither a damage nor a damage type, and only a leveled spell with damage
+/// can have higher levels damage.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpellEntry {
+    name: String,
+    level: i32,
+    damage: Option<String>,
+    damage_type: Option<String>,
+    higher_levels: Option<String>,
+    #[serde(default)]
+    ritual: bool,
+}
+
+impl SpellEntry {
+    /// Validate the entry, returning the spell it describes, or a message explaining what's wrong
+    /// with it.
+    fn to_spell(&self) -> Result<Spell, String> {
+        let invalid =
+            |field: &str, value: &str| format!("{}: invalid {} \"{}\"", self.name, field, value);
+        if self.level < 0 || self.level > 9 {
+            return Err(invalid("level", &self.level.to_string()));
+        }
+        let spell = Spell {
+            name: self.name.clone(),
+            level: self.level,
+            damage: match &self.damage {
+                Some(damage) => Some(Roll::parse(damage).map_err(|_| invalid("damage", damage))?),
+                None => None,
+            },
+            damage_type: match &self.damage_type {
+                Some(damage_type) => Some(
+                    DamageType::parse(damage_type)
+                        .ok_or_else(|| invalid("damage type", damage_type))?,
+                ),
+                None => None,
+            },
+            higher_levels: match &self.higher_levels {
+                Some(higher_levels) => Some(
+                    Roll::parse(higher_levels)
+                        .map_err(|_| invalid("higher levels damage", higher_levels))?,
+                ),
+                None => None,
+            },
+            ritual: self.ritual,
+        };
+        if spell.ritual && spell.is_cantrip() {
+            return Err(format!("{}: cantrips can't be rituals", self.name));
+        }
+        if spell.damage.is_some() != spell.damage_type.is_some() {
+            return Err(format!(
+                "{}: damage and damage type must be given together",
+                self.name
+            ));
+        }
+        match (spell.damage, spell.higher_levels) {
+            (_, Some(_)) if spell.is_cantrip() => Err(format!(
+                "{}: cantrips can't have higher levels damage",
+                self.name
+            )),
+            (None, Some(_)) => Err(format!(
+                "{}: higher levels damage must be given with damage",
+                self.name
+            )),
+            (Some(damage), Some(higher_levels)) if damage.sides() != higher_levels.sides() => {
+                Err(format!(
+                    "{}: higher levels damage must use the same dice as the damage",
+                    self.name
+                ))
+            }
+            _ => Ok(spell),
+        }
+    }
+}
+
+/// Load the spell table, checking that every entry is valid. Entries from the content directory
+/// replace the embedded entries for spells with the same names.
+fn load_spells() -> Result<HashMap<String, Spell>, content::Error> {
+    let entries: Vec<SpellEntry> =
+        content::load(SPELLS_FILE_NAME, include_str!("data/spells.json"))?;
+    let mut spells = HashMap::new();
+    for entry in entries {
+        let spell = entry.to_spell().map_err(|message| {
+            content::Error::InvalidEntry(SPELLS_FILE_NAME.to_owned(), message)
+        })?;
+        spells.insert(spell.name.to_lowercase(), spell);
+    }
+    Ok(spells)
+}
+
+/// Check that the spell table loads, so that any problems with it are found at startup rather than
+/// the first time someone casts a spell.
+pub fn check_content() -> Result<(), &'static content::Error> {
+    SPELLS.as_ref().map(|_| ())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load_spells() {
+        assert_eq!(check_content().is_ok(), true);
+        let fireball = Spell::find("fireball").unwrap();
+        assert_eq!(fireball.name, "Fireball");
+        assert_eq!(fireball.level, 3);
+        assert_eq!(fireball.damage, Some(Roll::new_unsafe(8, 6, 0)));
+        assert_eq!(fireball.damage_type, Some(DamageType::Fire));
+        assert_eq!(Spell::find("Fire Bolt").unwrap().is_cantrip(), true);
+        assert_eq!(Spell::find("Wish").is_none(), true);
+        let detect_magic = Spell::find("Detect Magic").unwrap();
+        assert_eq!(detect_magic.ritual, true);
+        assert_eq!(detect_magic.damage_at(1, 1), None);
+        assert_eq!(fireball.ritual, false);
+    }
+
+    #[test]
+    fn test_damage_at() {
+        let damage_at = |name: &str, slot_level: i32, character_level: i32| {
+            Spell::find(name)
+                .unwrap()
+                .damage_at(slot_level, character_level)
+        };
+        assert_eq!(damage_at("Fireball", 3, 5), Some(Roll::new_unsafe(8, 6, 0)));
+        assert_eq!(
+            damage_at("Fireball", 5, 9),
+            Some(Roll::new_unsafe(10, 6, 0))
+        );
+        assert_eq!(
+            damage_at("Magic Missile", 3, 5),
+            Some(Roll::new_unsafe(5, 4, 5))
+        );
+        assert_eq!(
+            damage_at("Scorching Ray", 4, 7),
+            Some(Roll::new_unsafe(10, 6, 0))
+        );
+        // Spells without higher level scaling deal the same damage at any level
+        assert_eq!(
+            damage_at("Finger of Death", 9, 17),
+            Some(Roll::new_unsafe(7, 8, 30))
+        );
+        assert_eq!(
+            damage_at("Fire Bolt", 0, 1),
+            Some(Roll::new_unsafe(1, 10, 0))
+        );
+        assert_eq!(
+            damage_at("Fire Bolt", 0, 4),
+            Some(Roll::new_unsafe(1, 10, 0))
+        );
+        assert_eq!(
+            damage_at("Fire Bolt", 0, 5),
+            Some(Roll::new_unsafe(2, 10, 0))
+        );
+        assert_eq!(
+            damage_at("Fire Bolt", 0, 11),
+            Some(Roll::new_unsafe(3, 10, 0))
+        );
+        assert_eq!(
+            damage_at("Fire Bolt", 0, 20),
+            Some(Roll::new_unsafe(4, 10, 0))
+        );
+    }
+
+    #[test]
+    fn test_spell_entry_to_spell() {
+        fn entry(json: &str) -> Result<String, String> {
+            serde_json::from_str::<SpellEntry>(json)
+                .map_err(|error| error.to_string())
+                .and_then(|entry| entry.to_spell())
+                .map(|spell| spell.name)
+        }
+        assert_eq!(
+            entry(
+                r#"{"name": "Fireball", "level": 3, "damage": "8d6", "damage_type": "fire", "higher_levels": "1d6"}"#
+            ),
+            Ok("Fireball".to_owned())
+        );
+        assert_eq!(
+            entry(r#"{"name": "Fireball", "level": 10, "damage": "8d6", "damage_type": "fire"}"#),
+            Err("Fireball: invalid level \"10\"".to_owned())
+        );
+        assert_eq!(
+            entry(
+                r#"{"name": "Fireball", "level": 3, "damage": "8d6", "damage_type": "fire", "higher_levels": "1d8"}"#
+            ),
+            Err("Fireball: higher levels damage must use the same dice as the damage".to_owned())
+        );
+        assert_eq!(
+            entry(
+                r#"{"name": "Fire Bolt", "level": 0, "damage": "1d10", "damage_type": "fire", "higher_levels": "1d10"}"#
+            ),
+            Err("Fire Bolt: cantrips can't have higher levels damage".to_owned())
+        );
+        assert_eq!(
+            entry(r#"{"name": "Fireball", "level": 3, "damage": "8d6"}"#),
+            Err("Fireball: damage and damage type must be given together".to_owned())
+        );
+        assert_eq!(
+            entry(r#"{"name": "Detect Magic", "level": 1, "ritual": true}"#),
+            Ok("Detect Magic".to_owned())
+        );
+        assert_eq!(
+            entry(r#"{"name": "Light", "level": 0, "ritual": true}"#),
+            Err("Light: cantrips can't be rituals".to_owned())
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::currency::{Amount, Coins, Denomination, MAXIMUM_COINS};
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serenity::model::id::ChannelId;
+use std::fmt;
+
+/// The longest item name that can be kept in the stash, in characters.
+pub const MAXIMUM_NAME_LENGTH: usize = 100;
+
+/// Something the party has found but hasn't divided up yet: either coins, or a number of an item,
+/// such as "150 gp" or "3 potions of healing".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Loot {
+    Coins(Amount),
+    Item(String, i32),
+}
+
+impl Loot {
+    /// Parse some loot, such as "150 gp", "30 silver pieces", "3 potions of healing", or "a silver
+    /// mirror".
+    pub fn parse(string: &str) -> Option<Loot> {
+        lazy_static! {
+            static ref COINS_REGEX: Regex =
+                Regex::new(r"^(?i)(\d+) *([a-z]+)(?: +(?:pieces?|coins?))?$").unwrap();
+            static ref ITEM_REGEX: Regex =
+                Regex::new(r"^(?i)(?:(\d+) +|an? +|the +)?(.+)$").unwrap();
+        }
+        let string = string.trim();
+        let amount = COINS_REGEX.captures(string).and_then(|captures| {
+            Some(Amount {
+                count: captures.get(1)?.as_str().parse().ok()?,
+                denomination: Denomination::parse(captures.get(2)?.as_str())?,
+            })
+        });
+        if let Some(amount) = amount {
+            return if amount.count > 0 && amount.count <= MAXIMUM_COINS {
+                Some(Loot::Coins(amount))
+            } else {
+                None
+            };
+        }
+        let captures = ITEM_REGEX.captures(string)?;
+        let quantity = match captures.get(1) {
+            Some(m) => m.as_str().parse().ok()?,
+            None => 1,
+        };
+        let name = captures.get(2)?.as_str().trim();
+        if quantity > 0 && !name.is_empty() && name.chars().count() <= MAXIMUM_NAME_LENGTH {
+            Some(Loot::Item(name.to_owned(), quantity))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Loot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Loot::Coins(amount) => amount.fmt(f),
+            Loot::Item(name, 1) => name.fmt(f),
+            Loot::Item(name, quantity) => write!(f, "{} {}", quantity, name),
+        }
+    }
+}
+
+/// A change to the party's stash.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Transaction {
+    Deposit(Loot),
+    Withdraw(Loot),
+}
+
+/// An item in the party's stash, with how many of it there are.
+#[derive(Debug, Eq, PartialEq)]
+pub struct StashItem {
+    pub name: String,
+    pub quantity: i32,
+}
+
+impl fmt::Display for StashItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Loot::Item(self.name.clone(), self.quantity).fmt(f)
+    }
+}
+
+/// The coins and items that the party shares in a channel, such as loot that hasn't been divided
+/// between the characters yet.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Stash {
+    pub coins: Coins,
+    /// The items in the stash, in alphabetical order.
+    pub items: Vec<StashItem>,
+}
+
+impl Stash {
+    pub fn get(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<Stash> {
+        let coins = Coins::get_party(connection, channel_id)?;
+        let mut statement = connection.prepare(
+            "SELECT name, quantity FROM party_items \
+             WHERE channel_id = $1 \
+             ORDER BY name",
+        )?;
+        let rows = statement.query_map(&[&channel_id.to_string()], StashItem::from_row)?;
+        let items = rows.collect::<RusqliteResult<Vec<StashItem>>>()?;
+        Ok(Stash { coins, items })
+    }
+
+    /// Put loot in the party's stash, adding to any of the same item that's already there.
+    /// Returns false if the stash can't hold that many coins.
+    pub fn deposit(
+        connection: &Connection,
+        channel_id: ChannelId,
+        loot: &Loot,
+    ) -> RusqliteResult<bool> {
+        match loot {
+            Loot::Coins(amount) => match Coins::get_party(connection, channel_id)?.add(*amount) {
+                Ok(coins) => coins.set_party(connection, channel_id).map(|_| true),
+                Err(_) => Ok(false),
+            },
+            Loot::Item(name, quantity) => {
+                let params: &[&dyn ToSql] = &[&channel_id.to_string(), name, quantity];
+                connection
+                    .execute(
+                        "INSERT INTO party_items (channel_id, name, quantity) VALUES ($1, $2, $3) \
+                         ON CONFLICT (channel_id, name) DO UPDATE SET quantity = quantity + $3",
+                        params,
+                    )
+                    .map(|_| true)
+            }
+        }
+    }
+
+    /// Take loot out of the party's stash. Coins are converted between denominations if needed.
+    /// Returns false if there isn't that much in the stash.
+    pub fn withdraw(
+        connection: &Connection,
+        channel_id: ChannelId,
+        loot: &Loot,
+    ) -> RusqliteResult<bool> {
+        match loot {
+            Loot::Coins(amount) => match Coins::get_party(connection, channel_id)?.spend(*amount) {
+                Ok(coins) => coins.set_party(connection, channel_id).map(|_| true),
+                Err(_) => Ok(false),
+            },
+            Loot::Item(name, quantity) => {
+                let params: &[&dyn ToSql] = &[&channel_id.to_string(), name, quantity];
+                let updated = connection.execute(
+                    "UPDATE party_items SET quantity = quantity - $3 \
+                     WHERE channel_id = $1 AND name = $2 AND quantity >= $3",
+                    params,
+                )?;
+                connection.execute(
+                    "DELETE FROM party_items WHERE channel_id = $1 AND quantity <= 0",
+                    &[&channel_id.to_string()],
+                )?;
+                Ok(updated > 0)
+            }
+        }
+    }
+}
+
+impl StashItem {
+    fn from_row(row: &Row) -> RusqliteResult<StashItem> {
+        Ok(StashItem {
+            name: row.get("name")?,
+            quantity: row.get("quantity")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn loot(string: &str) -> Option<String> {
+        Loot::parse(string).map(|loot| match loot {
+            Loot::Coins(amount) => format!("coins {}", amount),
+            Loot::Item(name, quantity) => format!("{} × {}", quantity, name),
+        })
+    }
+
+    #[test]
+    fn test_parse_loot() {
+        assert_eq!(loot("150 gp"), Some("coins 150 gp".to_owned()));
+        assert_eq!(loot("30 silver pieces"), Some("coins 30 sp".to_owned()));
+        assert_eq!(
+            loot("3 potions of healing"),
+            Some("3 × potions of healing".to_owned())
+        );
+        assert_eq!(
+            loot("a silver mirror"),
+            Some("1 × silver mirror".to_owned())
+        );
+        assert_eq!(loot("Rope"), Some("1 × Rope".to_owned()));
+        assert_eq!(loot("0 gp"), None);
+        assert_eq!(loot("1000000 pp"), Some("coins 1000000 pp".to_owned()));
+        assert_eq!(loot("1000001 pp"), None);
+        assert_eq!(loot("0 daggers"), None);
+        assert_eq!(loot(""), None);
+    }
+}
@@ -0,0 +1,365 @@
+use crate::character::AbilityName;
+use crate::character_roll::Check;
+use crate::roll::{Condition, ConditionalRoll};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::Connection;
+use rusqlite::Result as RusqliteResult;
+use serenity::model::id::{ChannelId, UserId};
+use std::error;
+use std::fmt;
+
+/// A condition from the Player's Handbook that alters what a character can do, such as being
+/// poisoned or prone. These are called statuses to tell them apart from advantage and
+/// disadvantage, which are the conditions of a roll.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Status {
+    Blinded,
+    Charmed,
+    Deafened,
+    Frightened,
+    Grappled,
+    Incapacitated,
+    Invisible,
+    Paralyzed,
+    Petrified,
+    Poisoned,
+    Prone,
+    Restrained,
+    Stunned,
+    Unconscious,
+}
+
+impl Status {
+    pub fn parse(string: &str) -> Option<Status> {
+        match string.to_lowercase().as_ref() {
+            "blinded" | "blind" => Some(Status::Blinded),
+            "charmed" => Some(Status::Charmed),
+            "deafened" | "deaf" => Some(Status::Deafened),
+            "frightened" | "scared" => Some(Status::Frightened),
+            "grappled" => Some(Status::Grappled),
+            "incapacitated" => Some(Status::Incapacitated),
+            "invisible" => Some(Status::Invisible),
+            "paralyzed" | "paralysed" => Some(Status::Paralyzed),
+            "petrified" => Some(Status::Petrified),
+            "poisoned" => Some(Status::Poisoned),
+            "prone" => Some(Status::Prone),
+            "restrained" => Some(Status::Restrained),
+            "stunned" => Some(Status::Stunned),
+            "unconscious" => Some(Status::Unconscious),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Status::Blinded => "Blinded",
+            Status::Charmed => "Charmed",
+            Status::Deafened => "Deafened",
+            Status::Frightened => "Frightened",
+            Status::Grappled => "Grappled",
+            Status::Incapacitated => "Incapacitated",
+            Status::Invisible => "Invisible",
+            Status::Paralyzed => "Paralyzed",
+            Status::Petrified => "Petrified",
+            Status::Poisoned => "Poisoned",
+            Status::Prone => "Prone",
+            Status::Restrained => "Restrained",
+            Status::Stunned => "Stunned",
+            Status::Unconscious => "Unconscious",
+        }
+    }
+
+    /// The advantage or disadvantage the status gives the character's own attack rolls.
+    pub fn attack_roll_condition(self) -> Option<Condition> {
+        match self {
+            Status::Invisible => Some(Condition::Advantage),
+            Status::Blinded
+            | Status::Frightened
+            | Status::Poisoned
+            | Status::Prone
+            | Status::Restrained => Some(Condition::Disadvantage),
+            _ => None,
+        }
+    }
+
+    /// The advantage or disadvantage the status gives the character's ability checks, which
+    /// include skill checks and initiative, and saving throws.
+    pub fn check_condition(self, check: Check) -> Option<Condition> {
+        match (self, check) {
+            (Status::Frightened, Check::Ability(_))
+            | (Status::Frightened, Check::Initiative)
+            | (Status::Frightened, Check::Skill(_))
+            | (Status::Poisoned, Check::Ability(_))
+            | (Status::Poisoned, Check::Initiative)
+            | (Status::Poisoned, Check::Skill(_))
+            | (Status::Restrained, Check::SavingThrow(AbilityName::Dexterity)) => {
+                Some(Condition::Disadvantage)
+            }
+            _ => None,
+        }
+    }
+
+    /// Retrieve the statuses of a character, in alphabetical order.
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Vec<Status>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let mut statement = connection.prepare(
+            "SELECT condition \
+             FROM character_conditions \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             ORDER BY condition",
+        )?;
+        let rows = statement.query_map(params, |row| row.get("condition"))?;
+        rows.collect()
+    }
+
+    /// Give a character the status, either until it's removed or for a number of rounds. Giving a
+    /// character a status they already have replaces its duration. Returns whether the character
+    /// didn't already have it.
+    pub fn add(
+        self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        rounds: Option<i32>,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &self,
+            &rounds,
+        ];
+        let added = connection.execute(
+            "INSERT OR IGNORE INTO character_conditions \
+             (channel_id, user_id, name, condition, rounds) \
+             VALUES ($1, $2, $3, $4, $5)",
+            params,
+        )? > 0;
+        if !added {
+            connection.execute(
+                "UPDATE character_conditions SET rounds = $5 \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3 \
+                 AND condition = $4",
+                params,
+            )?;
+        }
+        Ok(added)
+    }
+
+    /// Remove the status from a character. Returns whether the character had it.
+    pub fn remove(
+        self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<bool> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name, &self];
+        connection
+            .execute(
+                "DELETE FROM character_conditions \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3 \
+                 AND condition = $4",
+                params,
+            )
+            .map(|count| count > 0)
+    }
+}
+
+/// Count down the statuses with a duration on every character in a channel at the end of a round,
+/// removing the ones that have run out. Returns the statuses that ended, as pairs of character name
+/// and status.
+pub fn end_round(
+    connection: &mut Connection,
+    channel_id: ChannelId,
+) -> RusqliteResult<Vec<(String, Status)>> {
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "UPDATE character_conditions SET rounds = rounds - 1 \
+         WHERE channel_id = $1 \
+         AND rounds IS NOT NULL",
+        &[&channel_id.to_string()],
+    )?;
+    let ended = {
+        let mut statement = transaction.prepare(
+            "SELECT name, condition FROM character_conditions \
+             WHERE channel_id = $1 \
+             AND rounds <= 0 \
+             ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(&[&channel_id.to_string()], |row| {
+            Ok((
+                row.get::<_, String>("name")?,
+                row.get::<_, Status>("condition")?,
+            ))
+        })?;
+        rows.collect::<RusqliteResult<Vec<(String, Status)>>>()?
+    };
+    transaction.execute(
+        "DELETE FROM character_conditions WHERE channel_id = $1 AND rounds <= 0",
+        &[&channel_id.to_string()],
+    )?;
+    transaction.commit()?;
+    Ok(ended)
+}
+
+/// Remove the statuses with a duration from every character in a channel, as when an encounter
+/// ends.
+pub fn clear_timed(connection: &Connection, channel_id: ChannelId) -> RusqliteResult<usize> {
+    connection.execute(
+        "DELETE FROM character_conditions WHERE channel_id = $1 AND rounds IS NOT NULL",
+        &[&channel_id.to_string()],
+    )
+}
+
+/// Apply the advantage and disadvantage that a character's statuses give a roll, on top of any
+/// the roll already has. Returns the new roll, along with the statuses that affected it.
+pub fn apply_to_roll<F>(
+    roll: ConditionalRoll,
+    statuses: &[Status],
+    condition: F,
+) -> (ConditionalRoll, Vec<Status>)
+where
+    F: Fn(Status) -> Option<Condition>,
+{
+    let statuses: Vec<Status> = statuses
+        .iter()
+        .copied()
+        .filter(|status| condition(*status).is_some())
+        .collect();
+    let conditions = roll
+        .condition()
+        .into_iter()
+        .chain(statuses.iter().filter_map(|status| condition(*status)));
+    (
+        roll.with_condition(Condition::combine(conditions)),
+        statuses,
+    )
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl FromSql for Status {
+    fn column_result(value: ValueRef) -> FromSqlResult<Status> {
+        value.as_str().and_then(|string| {
+            Status::parse(string).ok_or_else(|| {
+                FromSqlError::Other(Box::new(InvalidStatusValueError {
+                    value: string.to_owned(),
+                }))
+            })
+        })
+    }
+}
+
+impl ToSql for Status {
+    fn to_sql(&self) -> RusqliteResult<ToSqlOutput> {
+        self.as_str().to_sql()
+    }
+}
+
+#[derive(Debug)]
+struct InvalidStatusValueError {
+    value: String,
+}
+
+impl fmt::Display for InvalidStatusValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid value for condition (value = {})", self.value)
+    }
+}
+
+impl error::Error for InvalidStatusValueError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::character::SkillName;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Status::parse("Poisoned"), Some(Status::Poisoned));
+        assert_eq!(Status::parse("paralysed"), Some(Status::Paralyzed));
+        assert_eq!(Status::parse("blind"), Some(Status::Blinded));
+        assert_eq!(Status::parse("exhausted"), None);
+    }
+
+    #[test]
+    fn test_attack_roll_condition() {
+        assert_eq!(
+            Status::Poisoned.attack_roll_condition(),
+            Some(Condition::Disadvantage)
+        );
+        assert_eq!(
+            Status::Invisible.attack_roll_condition(),
+            Some(Condition::Advantage)
+        );
+        assert_eq!(Status::Charmed.attack_roll_condition(), None);
+    }
+
+    #[test]
+    fn test_check_condition() {
+        assert_eq!(
+            Status::Poisoned.check_condition(Check::Skill(SkillName::Stealth)),
+            Some(Condition::Disadvantage)
+        );
+        assert_eq!(
+            Status::Poisoned.check_condition(Check::SavingThrow(AbilityName::Constitution)),
+            None
+        );
+        assert_eq!(
+            Status::Restrained.check_condition(Check::SavingThrow(AbilityName::Dexterity)),
+            Some(Condition::Disadvantage)
+        );
+        assert_eq!(
+            Status::Restrained.check_condition(Check::SavingThrow(AbilityName::Strength)),
+            None
+        );
+        assert_eq!(
+            Status::Prone.check_condition(Check::Ability(AbilityName::Strength)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_apply_to_roll() {
+        let roll = ConditionalRoll::new_unsafe(1, 20, 3, None);
+        let (roll, statuses) = apply_to_roll(
+            roll,
+            &[Status::Charmed, Status::Poisoned],
+            Status::attack_roll_condition,
+        );
+        assert_eq!(roll.condition(), Some(Condition::Disadvantage));
+        assert_eq!(statuses, vec![Status::Poisoned]);
+
+        // Advantage from the roll itself cancels out disadvantage from a status
+        let roll = ConditionalRoll::new_unsafe(1, 20, 3, Some(Condition::Advantage));
+        let (roll, statuses) = apply_to_roll(roll, &[Status::Prone], Status::attack_roll_condition);
+        assert_eq!(roll.condition(), None);
+        assert_eq!(statuses, vec![Status::Prone]);
+
+        let roll = ConditionalRoll::new_unsafe(1, 20, 3, None);
+        let (roll, statuses) = apply_to_roll(roll, &[], Status::attack_roll_condition);
+        assert_eq!(roll.condition(), None);
+        assert!(statuses.is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+/// The most names suggested for a name that wasn't recognised.
+const MAXIMUM_SUGGESTIONS: usize = 3;
+
+/// The known names closest in spelling to a name that wasn't recognised, closest first, such as
+/// "Greatsword" for "greatsord". A known name is only close if it's a few letters away from the
+/// name, with longer names allowed more mistakes.
+pub fn closest<'a, T: Copy>(
+    name: &str,
+    known: &'a [T],
+    as_str: impl Fn(&'a T) -> &'a str,
+) -> Vec<T> {
+    let name = name.trim().to_lowercase();
+    let limit = (name.chars().count() / 3).max(1).min(3);
+    let mut suggestions: Vec<(usize, T)> = known
+        .iter()
+        .map(|value| (distance(&name, &as_str(value).to_lowercase()), *value))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    // The sort is stable, so names equally close stay in the order they're known
+    suggestions.sort_by_key(|(distance, _)| *distance);
+    suggestions
+        .into_iter()
+        .take(MAXIMUM_SUGGESTIONS)
+        .map(|(_, value)| value)
+        .collect()
+}
+
+/// Ask whether one of the suggestions was meant, such as `Did you mean "Greatsword", or
+/// "Longsword"?`, for the end of a clarification. Empty if there are no suggestions.
+pub fn did_you_mean<T: Display>(suggestions: &[T]) -> String {
+    match suggestions.split_last() {
+        None => String::new(),
+        Some((last, [])) => format!(" Did you mean \"{}\"?", last),
+        Some((last, rest)) => format!(
+            " Did you mean {}, or \"{}\"?",
+            rest.iter()
+                .map(|suggestion| format!("\"{}\"", suggestion))
+                .collect::<Vec<_>>()
+                .join(", "),
+            last
+        ),
+    }
+}
+
+/// The Levenshtein distance between two strings, which is the fewest letters that must be
+/// inserted, deleted or replaced to turn one into the other.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + if a == *b { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("greatsword", "greatsword"), 0);
+        assert_eq!(distance("greatsord", "greatsword"), 1);
+        assert_eq!(distance("stelth", "stealth"), 1);
+        assert_eq!(distance("", "maul"), 4);
+        assert_eq!(distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_closest() {
+        let known = ["Greatsword", "Longsword", "Shortsword", "Maul"];
+        assert_eq!(closest("greatsord", &known, |s| *s), vec!["Greatsword"]);
+        assert_eq!(closest("LONGSWORDS", &known, |s| *s), vec!["Longsword"]);
+        assert_eq!(closest("mall", &known, |s| *s), vec!["Maul"]);
+        assert_eq!(closest("quarterstaff", &known, |s| *s), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(did_you_mean::<&str>(&[]), "");
+        assert_eq!(did_you_mean(&["Maul"]), " Did you mean \"Maul\"?");
+        assert_eq!(
+            did_you_mean(&["Greatsword", "Longsword", "Shortsword"]),
+            " Did you mean \"Greatsword\", \"Longsword\", or \"Shortsword\"?"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+use std::cmp::Reverse;
+use std::fmt;
+
+/// The Dexterity score used to break ties for combatants without one, such as monsters in the
+/// initiative order.
+pub const DEFAULT_DEXTERITY: i32 = 10;
+
+/// How to order combatants or characters who get the same result in the initiative order or a
+/// roll for the table, which can be changed for each channel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TieBreak {
+    /// The one with the higher Dexterity score goes first, with a roll-off if that's tied too.
+    Dexterity,
+    /// Each of the tied ones rolls a d20, and the highest roll goes first.
+    Reroll,
+    /// The tied ones share their place, and are reported as tied.
+    Simultaneous,
+}
+
+/// How the place of a combatant or character who was tied with others was decided.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tie {
+    Dexterity(i32),
+    Roll(i32),
+    Simultaneous,
+}
+
+impl TieBreak {
+    pub fn parse(string: &str) -> Option<TieBreak> {
+        match string.trim().to_lowercase().as_str() {
+            "dexterity" | "dex" => Some(TieBreak::Dexterity),
+            "reroll" | "roll" => Some(TieBreak::Reroll),
+            "simultaneous" => Some(TieBreak::Simultaneous),
+            _ => None,
+        }
+    }
+
+    /// The tie break as it's stored.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TieBreak::Dexterity => "dexterity",
+            TieBreak::Reroll => "reroll",
+            TieBreak::Simultaneous => "simultaneous",
+        }
+    }
+}
+
+impl Tie {
+    /// Parse a tie as it's stored, as a kind and a value.
+    pub fn from_parts(kind: &str, value: Option<i32>) -> Option<Tie> {
+        match (kind, value) {
+            ("dexterity", Some(value)) => Some(Tie::Dexterity(value)),
+            ("roll", Some(value)) => Some(Tie::Roll(value)),
+            ("simultaneous", _) => Some(Tie::Simultaneous),
+            _ => None,
+        }
+    }
+
+    /// The kind and value of the tie, as it's stored.
+    pub fn to_parts(self) -> (&'static str, Option<i32>) {
+        match self {
+            Tie::Dexterity(value) => ("dexterity", Some(value)),
+            Tie::Roll(value) => ("roll", Some(value)),
+            Tie::Simultaneous => ("simultaneous", None),
+        }
+    }
+}
+
+impl fmt::Display for Tie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tie::Dexterity(dexterity) => write!(f, "tie broken by Dexterity {}", dexterity),
+            Tie::Roll(roll) => write!(f, "tie broken by a roll of {}", roll),
+            Tie::Simultaneous => write!(f, "tied"),
+        }
+    }
+}
+
+/// Sort entries from highest to lowest result, breaking ties between entries with the same
+/// result. Returns each entry along with how its tie was broken, if it was tied. Entries that are
+/// still tied after a roll-off keep the order they were given in.
+pub fn rank<T, F, G, R>(
+    tie_break: TieBreak,
+    entries: Vec<T>,
+    result: F,
+    dexterity: G,
+    rng: &mut R,
+) -> Vec<(T, Option<Tie>)>
+where
+    F: Fn(&T) -> i32,
+    G: Fn(&T) -> Option<i32>,
+    R: Rng + ?Sized,
+{
+    let d20 = Uniform::new_inclusive(1, 20);
+    let results: Vec<i32> = entries.iter().map(&result).collect();
+    let dexterities: Vec<i32> = entries
+        .iter()
+        .map(|entry| dexterity(entry).unwrap_or(DEFAULT_DEXTERITY))
+        .collect();
+    let mut ranked = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.into_iter().enumerate() {
+        let tied_with: Vec<usize> = (0..results.len())
+            .filter(|other| *other != index && results[*other] == results[index])
+            .collect();
+        let tie = if tied_with.is_empty() {
+            None
+        } else {
+            match tie_break {
+                TieBreak::Dexterity
+                    if tied_with
+                        .iter()
+                        .all(|other| dexterities[*other] != dexterities[index]) =>
+                {
+                    Some(Tie::Dexterity(dexterities[index]))
+                }
+                TieBreak::Dexterity | TieBreak::Reroll => Some(Tie::Roll(d20.sample(rng))),
+                TieBreak::Simultaneous => Some(Tie::Simultaneous),
+            }
+        };
+        let dexterity = match (tie_break, tie) {
+            (TieBreak::Dexterity, Some(_)) => dexterities[index],
+            _ => 0,
+        };
+        let roll = match tie {
+            Some(Tie::Roll(roll)) => roll,
+            _ => 0,
+        };
+        ranked.push(((results[index], dexterity, roll), entry, tie));
+    }
+    // A stable sort, so that entries that are still tied keep the order they were given in
+    ranked.sort_by_key(|(key, _, _)| Reverse(*key));
+    ranked
+        .into_iter()
+        .map(|(_, entry, tie)| (entry, tie))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand_pcg::Pcg32;
+
+    fn entries() -> Vec<(&'static str, i32, Option<i32>)> {
+        vec![
+            ("Alice", 12, Some(14)),
+            ("Bob", 15, Some(10)),
+            ("Carol", 12, Some(16)),
+            ("goblin", 8, None),
+        ]
+    }
+
+    fn rank_entries(tie_break: TieBreak) -> Vec<(&'static str, Option<Tie>)> {
+        let mut rng = Pcg32::new(0, 0);
+        rank(
+            tie_break,
+            entries(),
+            |(_, result, _)| *result,
+            |(_, _, dexterity)| *dexterity,
+            &mut rng,
+        )
+        .into_iter()
+        .map(|((name, _, _), tie)| (name, tie))
+        .collect()
+    }
+
+    #[test]
+    fn test_rank_dexterity() {
+        assert_eq!(
+            rank_entries(TieBreak::Dexterity),
+            vec![
+                ("Bob", None),
+                ("Carol", Some(Tie::Dexterity(16))),
+                ("Alice", Some(Tie::Dexterity(14))),
+                ("goblin", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rank_simultaneous() {
+        assert_eq!(
+            rank_entries(TieBreak::Simultaneous),
+            vec![
+                ("Bob", None),
+                ("Alice", Some(Tie::Simultaneous)),
+                ("Carol", Some(Tie::Simultaneous)),
+                ("goblin", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rank_reroll() {
+        let ranked = rank_entries(TieBreak::Reroll);
+        let rolls: Vec<i32> = ranked
+            .iter()
+            .filter_map(|(_, tie)| match tie {
+                Some(Tie::Roll(roll)) => Some(*roll),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ranked.len(), 4);
+        assert_eq!(rolls.len(), 2);
+        assert!(rolls[0] >= rolls[1]);
+        assert_eq!(ranked[0], ("Bob", None));
+        assert_eq!(ranked[3], ("goblin", None));
+    }
+
+    #[test]
+    fn test_tie_parts() {
+        for tie in [Tie::Dexterity(16), Tie::Roll(3), Tie::Simultaneous].iter() {
+            let (kind, value) = tie.to_parts();
+            assert_eq!(Tie::from_parts(kind, value), Some(*tie));
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension};
+use serenity::model::id::{GuildId, UserId};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A time zone, as a fixed offset from UTC in minutes. Time zones with daylight saving time need
+/// to be changed when the clocks change.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TimeZone {
+    offset: i32,
+}
+
+/// Where a user's time zone comes from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimeZoneSource {
+    User,
+    Guild,
+    Default,
+}
+
+impl TimeZone {
+    pub const UTC: TimeZone = TimeZone { offset: 0 };
+
+    /// The furthest offsets from UTC used anywhere, in minutes.
+    const MINIMUM_OFFSET: i32 = -12 * 60;
+    const MAXIMUM_OFFSET: i32 = 14 * 60;
+
+    /// Parse an offset from UTC, such as "UTC+10", "GMT-5", "+05:30" or "UTC".
+    pub fn parse(string: &str) -> Option<TimeZone> {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)(?:utc|gmt)? *(?:([+-]) *(\d{1,2})(?::?(\d{2}))?)?$").unwrap();
+        }
+        let string = string.trim();
+        if string.is_empty() {
+            return None;
+        }
+        let captures = RE.captures(string)?;
+        let offset = match (captures.get(1), captures.get(2)) {
+            (Some(sign), Some(hours)) => {
+                let hours: i32 = hours.as_str().parse().ok()?;
+                let minutes: i32 = match captures.get(3) {
+                    Some(m) => m.as_str().parse().ok()?,
+                    None => 0,
+                };
+                if minutes >= 60 {
+                    return None;
+                }
+                let offset = hours * 60 + minutes;
+                if sign.as_str() == "-" {
+                    -offset
+                } else {
+                    offset
+                }
+            }
+            _ => 0,
+        };
+        if offset < TimeZone::MINIMUM_OFFSET || offset > TimeZone::MAXIMUM_OFFSET {
+            None
+        } else {
+            Some(TimeZone { offset })
+        }
+    }
+
+    /// The time zone to show times to a user in: their own, or else the server's, or else UTC.
+    pub fn get(
+        connection: &Connection,
+        guild_id: Option<GuildId>,
+        user_id: UserId,
+    ) -> RusqliteResult<(TimeZone, TimeZoneSource)> {
+        if let Some(time_zone) = TimeZone::get_user(connection, user_id)? {
+            return Ok((time_zone, TimeZoneSource::User));
+        }
+        if let Some(guild_id) = guild_id {
+            if let Some(time_zone) = TimeZone::get_guild(connection, guild_id)? {
+                return Ok((time_zone, TimeZoneSource::Guild));
+            }
+        }
+        Ok((TimeZone::UTC, TimeZoneSource::Default))
+    }
+
+    fn get_user(connection: &Connection, user_id: UserId) -> RusqliteResult<Option<TimeZone>> {
+        connection
+            .query_row(
+                "SELECT utc_offset FROM user_time_zones WHERE user_id = $1",
+                &[&user_id.to_string()],
+                |row| row.get("utc_offset").map(|offset| TimeZone { offset }),
+            )
+            .optional()
+    }
+
+    fn get_guild(connection: &Connection, guild_id: GuildId) -> RusqliteResult<Option<TimeZone>> {
+        connection
+            .query_row(
+                "SELECT utc_offset FROM guild_time_zones WHERE guild_id = $1",
+                &[&guild_id.to_string()],
+                |row| row.get("utc_offset").map(|offset| TimeZone { offset }),
+            )
+            .optional()
+    }
+
+    /// Set a user's time zone, or remove it so that they use the server's time zone.
+    pub fn set_user(
+        connection: &Connection,
+        user_id: UserId,
+        time_zone: Option<TimeZone>,
+    ) -> RusqliteResult<usize> {
+        match time_zone {
+            Some(time_zone) => {
+                let params: &[&dyn ToSql] = &[&user_id.to_string(), &time_zone.offset];
+                connection.execute(
+                    "INSERT INTO user_time_zones (user_id, utc_offset) VALUES ($1, $2) \
+                     ON CONFLICT (user_id) DO UPDATE SET utc_offset = excluded.utc_offset",
+                    params,
+                )
+            }
+            None => connection.execute(
+                "DELETE FROM user_time_zones WHERE user_id = $1",
+                &[&user_id.to_string()],
+            ),
+        }
+    }
+
+    /// Set the time zone for users in a server who haven't set their own, or remove it so that
+    /// they use UTC.
+    pub fn set_guild(
+        connection: &Connection,
+        guild_id: GuildId,
+        time_zone: Option<TimeZone>,
+    ) -> RusqliteResult<usize> {
+        match time_zone {
+            Some(time_zone) => {
+                let params: &[&dyn ToSql] = &[&guild_id.to_string(), &time_zone.offset];
+                connection.execute(
+                    "INSERT INTO guild_time_zones (guild_id, utc_offset) VALUES ($1, $2) \
+                     ON CONFLICT (guild_id) DO UPDATE SET utc_offset = excluded.utc_offset",
+                    params,
+                )
+            }
+            None => connection.execute(
+                "DELETE FROM guild_time_zones WHERE guild_id = $1",
+                &[&guild_id.to_string()],
+            ),
+        }
+    }
+
+    /// A timestamp in the time zone, such as "2020-09-13 22:26 (UTC+10)".
+    pub fn format(self, timestamp: i64) -> String {
+        let local = timestamp + i64::from(self.offset) * 60;
+        let (year, month, day) = civil_from_days(local.div_euclid(SECONDS_PER_DAY));
+        let seconds = local.rem_euclid(SECONDS_PER_DAY);
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02} ({})",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds % 3600 / 60,
+            self
+        )
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let hours = self.offset.abs() / 60;
+        let minutes = self.offset.abs() % 60;
+        match (hours, minutes) {
+            (0, 0) => write!(f, "UTC"),
+            (_, 0) => write!(f, "UTC{}{}", sign, hours),
+            _ => write!(f, "UTC{}{}:{:02}", sign, hours, minutes),
+        }
+    }
+}
+
+/// Parse a timestamp stored by SQLite, such as "2020-09-13 12:26:40", which is in UTC, into
+/// seconds since the Unix epoch.
+pub fn parse_timestamp(string: &str) -> Option<i64> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^(\d{4})-(\d{2})-(\d{2})[ T](\d{2}):(\d{2}):(\d{2})").unwrap();
+    }
+    let captures = RE.captures(string.trim())?;
+    let field = |index| {
+        captures
+            .get(index)
+            .and_then(|m| m.as_str().parse::<i64>().ok())
+    };
+    let days = days_from_civil(field(1)?, field(2)?, field(3)?);
+    Some(days * SECONDS_PER_DAY + field(4)? * 3600 + field(5)? * 60 + field(6)?)
+}
+
+/// Discord's markup for a timestamp, which every reader sees in their own local time, such as
+/// "<t:1600000000:f>". Discord doesn't render it in code or embed footers.
+pub fn discord_timestamp(timestamp: i64) -> String {
+    format!("<t:{}:f>", timestamp)
+}
+
+/// The current time, in seconds since the Unix epoch.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+/// The number of days from 1970-01-01 to a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Count from 1 March so that the leap day is at the end of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date a number of days after 1970-01-01, as the year, month and day.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let offset = |string| TimeZone::parse(string).map(|time_zone| time_zone.offset);
+        assert_eq!(offset("UTC"), Some(0));
+        assert_eq!(offset("UTC+10"), Some(600));
+        assert_eq!(offset("gmt-5"), Some(-300));
+        assert_eq!(offset("+05:30"), Some(330));
+        assert_eq!(offset("-0930"), Some(-570));
+        assert_eq!(offset("UTC+15"), None);
+        assert_eq!(offset("+5:75"), None);
+        assert_eq!(offset("Australia/Sydney"), None);
+        assert_eq!(offset(""), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let display = |string| TimeZone::parse(string).unwrap().to_string();
+        assert_eq!(display("gmt"), "UTC");
+        assert_eq!(display("+10:00"), "UTC+10");
+        assert_eq!(display("-3:30"), "UTC-3:30");
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01 00:00:00"), Some(0));
+        assert_eq!(parse_timestamp("2020-09-13 12:26:40"), Some(1_600_000_000));
+        assert_eq!(parse_timestamp("2024-02-29 23:59:59"), Some(1_709_251_199));
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_format() {
+        let timestamp = 1_600_000_000;
+        assert_eq!(TimeZone::UTC.format(timestamp), "2020-09-13 12:26 (UTC)");
+        assert_eq!(
+            TimeZone::parse("UTC+12").unwrap().format(timestamp),
+            "2020-09-14 00:26 (UTC+12)"
+        );
+        assert_eq!(
+            TimeZone::parse("UTC-9:30").unwrap().format(timestamp),
+            "2020-09-13 02:56 (UTC-9:30)"
+        );
+        assert_eq!(discord_timestamp(timestamp), "<t:1600000000:f>");
+    }
+}
@@ -0,0 +1,308 @@
+use regex::Regex;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, Row};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+/// The lowest confidence a logged message can have been parsed with to be exported as training
+/// data. Messages parsed with less confidence are too likely to have been misunderstood, unless
+/// the user confirmed what they meant.
+pub const MINIMUM_CONFIDENCE: f64 = 0.75;
+
+/// A logged message, labelled with the intent it was parsed as and the slots found in it.
+#[derive(Debug, Eq, PartialEq)]
+pub struct LabelledMessage {
+    pub intent_name: String,
+    /// The text that was parsed, after spelling correction.
+    pub text: String,
+    pub slots: Vec<LabelledSlot>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct LabelledSlot {
+    pub raw_value: String,
+    pub slot_name: String,
+    pub entity: String,
+}
+
+/// A piece of an utterance in the Snips NLU dataset format, which is either plain text or the
+/// value of a slot.
+#[derive(Debug, Eq, PartialEq)]
+struct Chunk<'a> {
+    text: &'a str,
+    slot: Option<&'a LabelledSlot>,
+}
+
+impl LabelledMessage {
+    /// Split the text into plain text and the values of the slots, finding each slot's value in
+    /// order. Returns None if a slot's value can't be found, since the message couldn't be
+    /// labelled correctly.
+    fn chunks(&self) -> Option<Vec<Chunk>> {
+        let mut chunks = Vec::new();
+        let mut position = 0;
+        for slot in self.slots.iter() {
+            let start = position + self.text[position..].find(&slot.raw_value)?;
+            let end = start + slot.raw_value.len();
+            if start > position {
+                chunks.push(Chunk {
+                    text: &self.text[position..start],
+                    slot: None,
+                });
+            }
+            chunks.push(Chunk {
+                text: &self.text[start..end],
+                slot: Some(slot),
+            });
+            position = end;
+        }
+        if position < self.text.len() {
+            chunks.push(Chunk {
+                text: &self.text[position..],
+                slot: None,
+            });
+        }
+        Some(chunks)
+    }
+
+    fn to_utterance(&self) -> Option<Value> {
+        let data: Vec<Value> = self
+            .chunks()?
+            .into_iter()
+            .map(|chunk| match chunk.slot {
+                Some(slot) => json!({
+                    "text": chunk.text,
+                    "entity": slot.entity,
+                    "slot_name": slot.slot_name,
+                }),
+                None => json!({ "text": chunk.text }),
+            })
+            .collect();
+        Some(json!({ "data": data }))
+    }
+}
+
+impl LabelledSlot {
+    fn from_row(row: &Row) -> RusqliteResult<LabelledSlot> {
+        let slot_name: String = row.get("slot_name")?;
+        let entity: Option<String> = row.get("entity")?;
+        Ok(LabelledSlot {
+            raw_value: row.get("raw_value")?,
+            // Slots logged before their entity was recorded are almost always named after it.
+            entity: entity.unwrap_or_else(|| slot_name.clone()),
+            slot_name,
+        })
+    }
+}
+
+/// Retrieve the logged messages that were parsed as an intent with at least the given confidence,
+/// or that the user confirmed was what they meant, oldest first.
+pub fn get_labelled_messages(
+    connection: &Connection,
+    minimum_confidence: f64,
+) -> RusqliteResult<Vec<LabelledMessage>> {
+    let params: &[&dyn ToSql] = &[&minimum_confidence];
+    let mut statement = connection.prepare(
+        "SELECT message_id, part_index, content, corrected_content, parsed_content, intent_name \
+         FROM messages \
+         WHERE intent_name IS NOT NULL \
+         AND (confidence_score >= $1 OR confirmed) \
+         ORDER BY posted, message_id, part_index",
+    )?;
+    let mut slot_statement = connection.prepare(
+        "SELECT raw_value, slot_name, entity \
+         FROM slots \
+         WHERE message_id = $1 \
+         AND part_index = $2 \
+         AND raw_value <> '' \
+         ORDER BY CAST(slot_index AS INTEGER)",
+    )?;
+    let rows = statement.query_map(params, |row| {
+        let message_id: String = row.get("message_id")?;
+        let part_index: i64 = row.get("part_index")?;
+        let content: String = row.get("content")?;
+        let corrected_content: Option<String> = row.get("corrected_content")?;
+        let parsed_content: Option<String> = row.get("parsed_content")?;
+        let intent_name: String = row.get("intent_name")?;
+        let text = parsed_content
+            .or(corrected_content)
+            .unwrap_or_else(|| strip_mention(&content).to_owned());
+        Ok((message_id, part_index, text, intent_name))
+    })?;
+    let mut messages = Vec::new();
+    for row in rows {
+        let (message_id, part_index, text, intent_name) = row?;
+        let params: &[&dyn ToSql] = &[&message_id, &part_index];
+        let slots = slot_statement
+            .query_map(params, LabelledSlot::from_row)?
+            .collect::<RusqliteResult<Vec<LabelledSlot>>>()?;
+        messages.push(LabelledMessage {
+            intent_name,
+            text,
+            slots,
+        });
+    }
+    Ok(messages)
+}
+
+/// Convert labelled messages into the Snips NLU dataset format, with one utterance for each
+/// distinct message, grouped by intent. The dataset has no entities of its own, so it's meant to
+/// be merged with the dataset generated from `dataset/` before training.
+pub fn to_dataset(messages: &[LabelledMessage]) -> Value {
+    let mut seen = HashSet::new();
+    let mut intents: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for message in messages {
+        if !seen.insert((message.intent_name.as_str(), message.text.to_lowercase())) {
+            continue;
+        }
+        if let Some(utterance) = message.to_utterance() {
+            intents
+                .entry(message.intent_name.as_str())
+                .or_insert_with(Vec::new)
+                .push(utterance);
+        }
+    }
+    let intents: Map<String, Value> = intents
+        .into_iter()
+        .map(|(name, utterances)| (name.to_owned(), json!({ "utterances": utterances })))
+        .collect();
+    json!({
+        "language": "en",
+        "entities": {},
+        "intents": intents,
+    })
+}
+
+/// Export the labelled messages logged in every channel as a Snips NLU dataset. Returns the number
+/// of utterances exported, along with the dataset as JSON.
+pub fn export(connection: &Connection) -> RusqliteResult<(usize, String)> {
+    let messages = get_labelled_messages(connection, MINIMUM_CONFIDENCE)?;
+    let dataset = to_dataset(&messages);
+    let count = dataset["intents"].as_object().map_or(0, |intents| {
+        intents
+            .values()
+            .filter_map(|intent| intent["utterances"].as_array())
+            .map(|utterances| utterances.len())
+            .sum()
+    });
+    Ok((count, format!("{:#}", dataset)))
+}
+
+/// Remove the mention of the bot from the start of a message, for messages logged before the
+/// parsed text was recorded.
+fn strip_mention(content: &str) -> &str {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^(?:<@[!&]?\d+>|(?i:@dungeon[ -]?helper)\b)? *").unwrap();
+    }
+    RE.find(content)
+        .map_or(content, |m| &content[m.end()..])
+        .trim()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::intent_logger::confirm_message;
+    use serenity::model::id::MessageId;
+
+    fn roll_stealth() -> LabelledMessage {
+        LabelledMessage {
+            intent_name: "rollSkill".to_owned(),
+            text: "roll stealth with advantage".to_owned(),
+            slots: vec![
+                LabelledSlot {
+                    raw_value: "stealth".to_owned(),
+                    slot_name: "skill".to_owned(),
+                    entity: "skill".to_owned(),
+                },
+                LabelledSlot {
+                    raw_value: "advantage".to_owned(),
+                    slot_name: "condition".to_owned(),
+                    entity: "condition".to_owned(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_to_utterance() {
+        assert_eq!(
+            roll_stealth().to_utterance(),
+            Some(json!({
+                "data": [
+                    { "text": "roll " },
+                    { "text": "stealth", "entity": "skill", "slot_name": "skill" },
+                    { "text": " with " },
+                    { "text": "advantage", "entity": "condition", "slot_name": "condition" },
+                ]
+            }))
+        );
+        let mut misspelled = roll_stealth();
+        misspelled.text = "roll stelth with advantage".to_owned();
+        assert_eq!(misspelled.to_utterance(), None);
+    }
+
+    #[test]
+    fn test_to_dataset() {
+        let mut duplicate = roll_stealth();
+        duplicate.text = "Roll Stealth with advantage".to_owned();
+        let dataset = to_dataset(&[roll_stealth(), duplicate]);
+        assert_eq!(dataset["language"], "en");
+        assert_eq!(
+            dataset["intents"]["rollSkill"]["utterances"]
+                .as_array()
+                .map(|utterances| utterances.len()),
+            Some(1)
+        );
+    }
+
+    fn connection() -> Connection {
+        let connection = Connection::open_in_memory().unwrap();
+        for migration in &[
+            include_str!("../config/sql/002-intent_logs.sql"),
+            include_str!("../config/sql/031-training-data.sql"),
+            include_str!("../config/sql/061-intent-log-parts.sql"),
+            include_str!("../config/sql/062-confirmed-messages.sql"),
+        ] {
+            connection.execute_batch(migration).unwrap();
+        }
+        connection
+    }
+
+    #[test]
+    fn test_get_confirmed_labelled_messages() {
+        let connection = connection();
+        for (message_id, content, confidence_score) in &[
+            ("1", "show my gold", 0.9),
+            ("2", "how much gold have I got", 0.4),
+            ("3", "what's in my purse", 0.4),
+        ] {
+            let params: &[&dyn ToSql] = &[message_id, content, confidence_score];
+            connection
+                .execute(
+                    "INSERT INTO messages (message_id, channel_id, user_id, content, posted, intent_name, confidence_score, parsed_content) \
+                     VALUES ($1, '1', '1', $2, '2020-06-01 19:30:00', 'showCurrency', $3, $2)",
+                    params,
+                )
+                .unwrap();
+        }
+        assert_eq!(confirm_message(&connection, MessageId(2)).unwrap(), 1);
+        let texts: Vec<String> = get_labelled_messages(&connection, MINIMUM_CONFIDENCE)
+            .unwrap()
+            .into_iter()
+            .map(|message| message.text)
+            .collect();
+        assert_eq!(texts, vec!["show my gold", "how much gold have I got"]);
+    }
+
+    #[test]
+    fn test_strip_mention() {
+        assert_eq!(strip_mention("<@!123> roll stealth"), "roll stealth");
+        assert_eq!(
+            strip_mention("@Dungeon Helper roll stealth"),
+            "roll stealth"
+        );
+        assert_eq!(strip_mention("roll stealth"), "roll stealth");
+    }
+}
@@ -0,0 +1,244 @@
+use regex::Regex;
+
+/// The number of hours the characters can travel in a day before it becomes a forced march.
+pub const HOURS_PER_DAY: i32 = 8;
+
+/// The longest journey that can be planned, in miles.
+const MAXIMUM_MILES: i32 = 10000;
+
+/// The speed the characters travel at, which changes how far they go in a day, and what else they
+/// can do on the way.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pace {
+    Fast,
+    Normal,
+    Slow,
+}
+
+/// Overland travel at a pace, possibly for a distance or a number of hours a day, such as "50
+/// miles at a fast pace through difficult terrain".
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Journey {
+    pub pace: Pace,
+    pub miles: Option<i32>,
+    pub hours: Option<i32>,
+    pub difficult_terrain: bool,
+}
+
+impl Pace {
+    pub fn parse(string: &str) -> Option<Pace> {
+        match string.trim().to_lowercase().as_str() {
+            "fast" | "quick" => Some(Pace::Fast),
+            "normal" | "regular" => Some(Pace::Normal),
+            "slow" | "stealthy" => Some(Pace::Slow),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Pace::Fast => "fast",
+            Pace::Normal => "normal",
+            Pace::Slow => "slow",
+        }
+    }
+
+    pub fn miles_per_hour(self) -> i32 {
+        match self {
+            Pace::Fast => 4,
+            Pace::Normal => 3,
+            Pace::Slow => 2,
+        }
+    }
+
+    /// The distance covered in a day of 8 hours of travel, which is a little less than 8 hours at
+    /// a fast pace.
+    pub fn miles_per_day(self) -> i32 {
+        match self {
+            Pace::Fast => 30,
+            Pace::Normal => 24,
+            Pace::Slow => 18,
+        }
+    }
+
+    /// How the pace affects the characters while they travel.
+    pub fn effect(self) -> &'static str {
+        match self {
+            Pace::Fast => "−5 penalty to passive Wisdom (Perception) scores",
+            Pace::Normal => "No effect",
+            Pace::Slow => "Able to use stealth",
+        }
+    }
+}
+
+impl Journey {
+    /// Parse a journey from its pace, distance, hours a day and terrain, in any order, such as
+    /// "fast 50 miles difficult", or "for 10 hours at a slow pace". The pace is normal unless
+    /// another pace is given.
+    pub fn parse(string: &str) -> Option<Journey> {
+        lazy_static! {
+            static ref PART_REGEX: Regex = Regex::new(
+                r"(?i)\b(?:(\d+) *(miles?|mi|hours?|hrs?)|(fast|quick|normal|regular|slow|stealthy)|(difficult))\b"
+            )
+            .unwrap();
+            static ref FILLER_REGEX: Regex = Regex::new(
+                r"(?i)^(?:a|an|at|for|in|over|through|across|pace|terrain|per|day|each|of|and|,)$"
+            )
+            .unwrap();
+        }
+        let mut journey = Journey {
+            pace: Pace::Normal,
+            miles: None,
+            hours: None,
+            difficult_terrain: false,
+        };
+        for captures in PART_REGEX.captures_iter(string) {
+            if let (Some(number), Some(unit)) = (captures.get(1), captures.get(2)) {
+                let number: i32 = number.as_str().parse().ok()?;
+                if unit.as_str().to_lowercase().starts_with('m') {
+                    journey.miles =
+                        Some(Some(number).filter(|miles| *miles >= 1 && *miles <= MAXIMUM_MILES)?);
+                } else {
+                    journey.hours = Some(Some(number).filter(|hours| *hours >= 1 && *hours <= 24)?);
+                }
+            } else if let Some(pace) = captures.get(3) {
+                journey.pace = Pace::parse(pace.as_str())?;
+            } else if captures.get(4).is_some() {
+                journey.difficult_terrain = true;
+            }
+        }
+        // Anything else in the string is a mistake, rather than something to ignore
+        let rest = PART_REGEX.replace_all(string, " ");
+        if rest
+            .split_whitespace()
+            .all(|word| FILLER_REGEX.is_match(word))
+        {
+            Some(journey)
+        } else {
+            None
+        }
+    }
+
+    pub fn hours_per_day(&self) -> i32 {
+        self.hours.unwrap_or(HOURS_PER_DAY)
+    }
+
+    /// The distance covered in an hour, which is halved in difficult terrain.
+    pub fn miles_per_hour(&self) -> f64 {
+        self.terrain_factor() * f64::from(self.pace.miles_per_hour())
+    }
+
+    /// The distance covered in a day. Each hour past 8 hours covers an hour's distance, and
+    /// distance is halved in difficult terrain.
+    pub fn miles_per_day(&self) -> f64 {
+        let hours = self.hours_per_day();
+        let miles = if hours <= HOURS_PER_DAY {
+            (hours * self.pace.miles_per_hour()).min(self.pace.miles_per_day())
+        } else {
+            self.pace.miles_per_day() + (hours - HOURS_PER_DAY) * self.pace.miles_per_hour()
+        };
+        self.terrain_factor() * f64::from(miles)
+    }
+
+    /// How many whole days and extra hours it takes to travel the journey's distance, if it has
+    /// one.
+    pub fn travel_time(&self) -> Option<(i32, i32)> {
+        let miles = f64::from(self.miles?);
+        let days = (miles / self.miles_per_day()).floor();
+        let remaining = miles - days * self.miles_per_day();
+        let hours = (remaining / self.miles_per_hour()).ceil();
+        Some((days as i32, hours as i32))
+    }
+
+    /// The DCs of the Constitution saving throws to avoid exhaustion at the end of each hour of a
+    /// forced march, which go up by 1 for each hour past 8 hours.
+    pub fn forced_march_dcs(&self) -> Vec<(i32, i32)> {
+        (HOURS_PER_DAY + 1..=self.hours_per_day())
+            .map(|hour| (hour, 10 + hour - HOURS_PER_DAY))
+            .collect()
+    }
+
+    fn terrain_factor(&self) -> f64 {
+        if self.difficult_terrain {
+            0.5
+        } else {
+            1.0
+        }
+    }
+}
+
+/// A distance in miles, such as "7.5 miles".
+pub fn miles(miles: f64) -> String {
+    if (miles - 1.0).abs() < std::f64::EPSILON {
+        "1 mile".to_owned()
+    } else {
+        format!("{} miles", miles)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Journey::parse("fast 50 miles difficult"),
+            Some(Journey {
+                pace: Pace::Fast,
+                miles: Some(50),
+                hours: None,
+                difficult_terrain: true,
+            })
+        );
+        assert_eq!(
+            Journey::parse("for 10 hours a day at a slow pace"),
+            Some(Journey {
+                pace: Pace::Slow,
+                miles: None,
+                hours: Some(10),
+                difficult_terrain: false,
+            })
+        );
+        assert_eq!(
+            Journey::parse("").map(|journey| journey.pace),
+            Some(Pace::Normal)
+        );
+        assert_eq!(Journey::parse("to Neverwinter"), None);
+        assert_eq!(Journey::parse("30 hours"), None);
+    }
+
+    #[test]
+    fn test_miles_per_day() {
+        let journey = |pace, hours, difficult_terrain| Journey {
+            pace,
+            miles: Some(50),
+            hours,
+            difficult_terrain,
+        };
+        let miles_per_day = |journey: Journey| miles(journey.miles_per_day());
+        assert_eq!(miles_per_day(journey(Pace::Fast, None, false)), "30 miles");
+        assert_eq!(
+            miles_per_day(journey(Pace::Normal, Some(10), false)),
+            "30 miles"
+        );
+        assert_eq!(miles_per_day(journey(Pace::Fast, None, true)), "15 miles");
+        assert_eq!(miles_per_day(journey(Pace::Slow, Some(3), true)), "3 miles");
+        assert_eq!(miles_per_day(journey(Pace::Fast, Some(1), true)), "2 miles");
+        assert_eq!(
+            miles_per_day(journey(Pace::Normal, Some(1), true)),
+            "1.5 miles"
+        );
+        assert_eq!(journey(Pace::Fast, None, false).travel_time(), Some((1, 5)));
+        assert_eq!(
+            journey(Pace::Normal, Some(10), false).forced_march_dcs(),
+            vec![(9, 11), (10, 12)]
+        );
+        assert_eq!(
+            journey(Pace::Normal, None, false)
+                .forced_march_dcs()
+                .is_empty(),
+            true
+        );
+    }
+}
@@ -0,0 +1,739 @@
+use self::ValuableKind::{ArtObjects, Gemstones};
+use crate::currency::Denomination;
+use crate::currency::Denomination::{Copper, Electrum, Gold, Platinum, Silver};
+use crate::roll::{Roll, RollResult};
+use rand::Rng;
+use regex::Regex;
+use std::fmt;
+
+/// Whether treasure is carried by a single monster, or is a hoard, such as a dragon's lair or the
+/// treasury of a bandit camp.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TreasureKind {
+    Individual,
+    Hoard,
+}
+
+/// The challenge ratings that share a treasure table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TreasureTier {
+    Challenge0To4,
+    Challenge5To10,
+    Challenge11To16,
+    Challenge17Plus,
+}
+
+/// Treasure to roll for, such as a hoard for challenge ratings 5–10.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Treasure {
+    pub kind: TreasureKind,
+    pub tier: TreasureTier,
+}
+
+/// Coins of a denomination, such as 2d6 × 100 gp.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CoinRoll {
+    pub roll: Roll,
+    pub multiplier: i32,
+    pub denomination: Denomination,
+}
+
+/// Whether valuables are gemstones or art objects.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValuableKind {
+    Gemstones,
+    ArtObjects,
+}
+
+/// A number of gemstones or art objects, each worth the same amount in gold pieces.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ValuablesRoll {
+    pub roll: Roll,
+    pub value: i32,
+    pub kind: ValuableKind,
+}
+
+/// A number of rolls on one of the magic item tables, which are named A to I.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MagicItemsRoll {
+    pub roll: Roll,
+    pub table: char,
+}
+
+#[derive(Debug)]
+pub struct CoinResult {
+    pub coins: CoinRoll,
+    pub result: RollResult,
+}
+
+#[derive(Debug)]
+pub struct ValuablesResult {
+    pub valuables: ValuablesRoll,
+    pub result: RollResult,
+}
+
+#[derive(Debug)]
+pub struct MagicItemsResult {
+    pub magic_items: MagicItemsRoll,
+    pub result: RollResult,
+    pub items: Vec<&'static str>,
+}
+
+/// The treasure that was rolled, with the result of the d100 roll that chose the row of the
+/// table.
+#[derive(Debug)]
+pub struct TreasureResult {
+    pub treasure: Treasure,
+    pub row: i32,
+    pub coins: Vec<CoinResult>,
+    pub valuables: Option<ValuablesResult>,
+    pub magic_items: Vec<MagicItemsResult>,
+}
+
+/// A number of dice, the number of sides on each, and what the result is multiplied by.
+type Dice = (usize, i32, i32);
+
+/// A row of an individual treasure table: the highest d100 roll for the row, and its coins.
+type IndividualRow = (i32, &'static [(Dice, Denomination)]);
+
+/// A row of a hoard table: the highest d100 roll for the row, its gemstones or art objects, and
+/// its rolls on the magic item tables.
+type HoardRow = (
+    i32,
+    Option<(Dice, ValuableKind)>,
+    &'static [((usize, i32), char)],
+);
+
+/// The magic items for a row without any.
+const NO_MAGIC_ITEMS: &[((usize, i32), char)] = &[];
+
+const INDIVIDUAL_0_TO_4: [IndividualRow; 5] = [
+    (30, &[((5, 6, 1), Copper)]),
+    (60, &[((4, 6, 1), Silver)]),
+    (70, &[((3, 6, 1), Electrum)]),
+    (95, &[((3, 6, 1), Gold)]),
+    (100, &[((1, 6, 1), Platinum)]),
+];
+
+const INDIVIDUAL_5_TO_10: [IndividualRow; 5] = [
+    (30, &[((4, 6, 100), Copper), ((1, 6, 10), Electrum)]),
+    (60, &[((6, 6, 10), Silver), ((2, 6, 10), Gold)]),
+    (70, &[((3, 6, 10), Electrum), ((2, 6, 10), Gold)]),
+    (95, &[((4, 6, 10), Gold)]),
+    (100, &[((2, 6, 10), Gold), ((3, 6, 1), Platinum)]),
+];
+
+const INDIVIDUAL_11_TO_16: [IndividualRow; 4] = [
+    (20, &[((4, 6, 100), Silver), ((1, 6, 100), Gold)]),
+    (35, &[((1, 6, 100), Electrum), ((1, 6, 100), Gold)]),
+    (75, &[((2, 6, 100), Gold), ((1, 6, 10), Platinum)]),
+    (100, &[((2, 6, 100), Gold), ((2, 6, 10), Platinum)]),
+];
+
+const INDIVIDUAL_17_PLUS: [IndividualRow; 3] = [
+    (15, &[((2, 6, 1000), Electrum), ((8, 6, 100), Gold)]),
+    (55, &[((1, 6, 1000), Gold), ((1, 6, 100), Platinum)]),
+    (100, &[((1, 6, 1000), Gold), ((2, 6, 100), Platinum)]),
+];
+
+const HOARD_COINS_0_TO_4: [(Dice, Denomination); 3] = [
+    ((6, 6, 100), Copper),
+    ((3, 6, 100), Silver),
+    ((2, 6, 10), Gold),
+];
+
+const HOARD_COINS_5_TO_10: [(Dice, Denomination); 4] = [
+    ((2, 6, 100), Copper),
+    ((2, 6, 1000), Silver),
+    ((6, 6, 100), Gold),
+    ((3, 6, 10), Platinum),
+];
+
+const HOARD_COINS_11_TO_16: [(Dice, Denomination); 2] =
+    [((4, 6, 1000), Gold), ((5, 6, 100), Platinum)];
+
+const HOARD_COINS_17_PLUS: [(Dice, Denomination); 2] =
+    [((12, 6, 1000), Gold), ((8, 6, 1000), Platinum)];
+
+const HOARD_0_TO_4: [HoardRow; 17] = [
+    (6, None, &[]),
+    (16, Some(((2, 6, 10), Gemstones)), &[]),
+    (26, Some(((2, 4, 25), ArtObjects)), &[]),
+    (36, Some(((2, 6, 50), Gemstones)), &[]),
+    (44, Some(((2, 6, 10), Gemstones)), &[((1, 6), 'A')]),
+    (52, Some(((2, 4, 25), ArtObjects)), &[((1, 6), 'A')]),
+    (60, Some(((2, 6, 50), Gemstones)), &[((1, 6), 'A')]),
+    (65, Some(((2, 6, 10), Gemstones)), &[((1, 4), 'B')]),
+    (70, Some(((2, 4, 25), ArtObjects)), &[((1, 4), 'B')]),
+    (75, Some(((2, 6, 50), Gemstones)), &[((1, 4), 'B')]),
+    (78, Some(((2, 6, 10), Gemstones)), &[((1, 4), 'C')]),
+    (80, Some(((2, 4, 25), ArtObjects)), &[((1, 4), 'C')]),
+    (85, Some(((2, 6, 50), Gemstones)), &[((1, 4), 'C')]),
+    (92, Some(((2, 4, 25), ArtObjects)), &[((1, 4), 'F')]),
+    (97, Some(((2, 6, 50), Gemstones)), &[((1, 4), 'F')]),
+    (99, Some(((2, 4, 25), ArtObjects)), &[((1, 1), 'G')]),
+    (100, Some(((2, 6, 50), Gemstones)), &[((1, 1), 'G')]),
+];
+
+const HOARD_5_TO_10: [HoardRow; 18] = [
+    (4, None, &[]),
+    (10, Some(((2, 4, 25), ArtObjects)), &[]),
+    (16, Some(((3, 6, 50), Gemstones)), &[]),
+    (22, Some(((3, 6, 100), Gemstones)), &[]),
+    (28, Some(((2, 4, 250), ArtObjects)), &[]),
+    (36, Some(((2, 4, 25), ArtObjects)), &[((1, 6), 'A')]),
+    (44, Some(((3, 6, 100), Gemstones)), &[((1, 6), 'A')]),
+    (54, Some(((3, 6, 50), Gemstones)), &[((1, 4), 'B')]),
+    (63, Some(((2, 4, 250), ArtObjects)), &[((1, 4), 'B')]),
+    (69, Some(((2, 4, 25), ArtObjects)), &[((1, 4), 'C')]),
+    (74, Some(((3, 6, 100), Gemstones)), &[((1, 4), 'C')]),
+    (78, Some(((3, 6, 50), Gemstones)), &[((1, 1), 'D')]),
+    (80, Some(((2, 4, 250), ArtObjects)), &[((1, 1), 'D')]),
+    (88, Some(((2, 4, 25), ArtObjects)), &[((1, 4), 'F')]),
+    (94, Some(((3, 6, 100), Gemstones)), &[((1, 4), 'F')]),
+    (96, Some(((3, 6, 100), Gemstones)), &[((1, 4), 'G')]),
+    (98, Some(((2, 4, 250), ArtObjects)), &[((1, 6), 'G')]),
+    (100, Some(((3, 6, 100), Gemstones)), &[((1, 1), 'H')]),
+];
+
+const HOARD_11_TO_16: [HoardRow; 12] = [
+    (3, None, &[]),
+    (6, Some(((2, 4, 250), ArtObjects)), &[]),
+    (9, Some(((2, 4, 750), ArtObjects)), &[]),
+    (12, Some(((3, 6, 500), Gemstones)), &[]),
+    (15, Some(((3, 6, 1000), Gemstones)), &[]),
+    (
+        29,
+        Some(((2, 4, 250), ArtObjects)),
+        &[((1, 4), 'A'), ((1, 6), 'B')],
+    ),
+    (50, Some(((3, 6, 500), Gemstones)), &[((1, 6), 'C')]),
+    (66, Some(((3, 6, 1000), Gemstones)), &[((1, 4), 'D')]),
+    (74, Some(((2, 4, 750), ArtObjects)), &[((1, 1), 'E')]),
+    (
+        82,
+        Some(((3, 6, 1000), Gemstones)),
+        &[((1, 4), 'F'), ((1, 4), 'G')],
+    ),
+    (92, Some(((2, 4, 250), ArtObjects)), &[((1, 4), 'H')]),
+    (100, Some(((3, 6, 500), Gemstones)), &[((1, 1), 'I')]),
+];
+
+const HOARD_17_PLUS: [HoardRow; 7] = [
+    (2, None, &[]),
+    (14, Some(((3, 6, 1000), Gemstones)), &[((1, 8), 'C')]),
+    (46, Some(((1, 10, 2500), ArtObjects)), &[((1, 6), 'D')]),
+    (68, Some(((1, 4, 7500), ArtObjects)), &[((1, 6), 'E')]),
+    (72, Some(((1, 8, 5000), Gemstones)), &[((1, 4), 'G')]),
+    (80, Some(((1, 10, 2500), ArtObjects)), &[((1, 4), 'H')]),
+    (100, Some(((1, 8, 5000), Gemstones)), &[((1, 4), 'I')]),
+];
+
+/// The magic item tables, from common consumables on table A to legendary items on table I. Every
+/// item on a table is equally likely to be rolled.
+const MAGIC_ITEM_TABLES: [(char, &[&str]); 9] = [
+    (
+        'A',
+        &[
+            "Potion of healing",
+            "Spell scroll (cantrip)",
+            "Potion of climbing",
+            "Spell scroll (1st level)",
+            "Spell scroll (2nd level)",
+            "Potion of greater healing",
+            "Bag of holding",
+            "Driftglobe",
+        ],
+    ),
+    (
+        'B',
+        &[
+            "Potion of greater healing",
+            "Potion of fire breath",
+            "Potion of resistance",
+            "Ammunition, +1",
+            "Potion of animal friendship",
+            "Potion of hill giant strength",
+            "Potion of growth",
+            "Potion of water breathing",
+            "Spell scroll (2nd level)",
+            "Spell scroll (3rd level)",
+            "Oil of slipperiness",
+            "Dust of disappearance",
+            "Goggles of night",
+            "Immovable rod",
+            "Rope of climbing",
+            "Wand of magic detection",
+        ],
+    ),
+    (
+        'C',
+        &[
+            "Potion of superior healing",
+            "Spell scroll (4th level)",
+            "Ammunition, +2",
+            "Potion of clairvoyance",
+            "Potion of diminution",
+            "Potion of gaseous form",
+            "Potion of frost giant strength",
+            "Potion of heroism",
+            "Potion of invulnerability",
+            "Potion of mind reading",
+            "Spell scroll (5th level)",
+            "Elixir of health",
+            "Oil of etherealness",
+            "Bead of force",
+            "Decanter of endless water",
+            "Folding boat",
+            "Necklace of fireballs",
+            "Sending stones",
+        ],
+    ),
+    (
+        'D',
+        &[
+            "Potion of supreme healing",
+            "Potion of invisibility",
+            "Potion of speed",
+            "Spell scroll (6th level)",
+            "Spell scroll (7th level)",
+            "Ammunition, +3",
+            "Oil of sharpness",
+            "Potion of flying",
+            "Potion of cloud giant strength",
+            "Potion of longevity",
+            "Potion of vitality",
+            "Spell scroll (8th level)",
+            "Bag of beans",
+        ],
+    ),
+    (
+        'E',
+        &[
+            "Spell scroll (8th level)",
+            "Potion of storm giant strength",
+            "Potion of supreme healing",
+            "Spell scroll (9th level)",
+            "Universal solvent",
+            "Arrow of slaying",
+            "Sovereign glue",
+        ],
+    ),
+    (
+        'F',
+        &[
+            "Weapon, +1",
+            "Shield, +1",
+            "Sentinel shield",
+            "Amulet of proof against detection and location",
+            "Boots of elvenkind",
+            "Boots of striding and springing",
+            "Bracers of archery",
+            "Brooch of shielding",
+            "Broom of flying",
+            "Cloak of elvenkind",
+            "Cloak of protection",
+            "Gauntlets of ogre power",
+            "Hat of disguise",
+            "Javelin of lightning",
+            "Pearl of power",
+            "Slippers of spider climbing",
+            "Staff of the python",
+            "Wand of magic missiles",
+            "Wand of web",
+            "Weapon of warning",
+        ],
+    ),
+    (
+        'G',
+        &[
+            "Weapon, +2",
+            "Figurine of wondrous power",
+            "Adamantine armor",
+            "Amulet of health",
+            "Belt of hill giant strength",
+            "Boots of speed",
+            "Bracers of defense",
+            "Cloak of displacement",
+            "Dagger of venom",
+            "Flame tongue",
+            "Gem of seeing",
+            "Giant slayer",
+            "Helm of teleportation",
+            "Mace of disruption",
+            "Necklace of prayer beads",
+            "Ring of evasion",
+            "Ring of protection",
+            "Ring of spell storing",
+            "Staff of healing",
+            "Sun blade",
+            "Wand of fireballs",
+            "Wand of lightning bolts",
+            "Wings of flying",
+        ],
+    ),
+    (
+        'H',
+        &[
+            "Weapon, +3",
+            "Amulet of the planes",
+            "Carpet of flying",
+            "Crystal ball",
+            "Ring of regeneration",
+            "Ring of shooting stars",
+            "Ring of telekinesis",
+            "Robe of stars",
+            "Rod of absorption",
+            "Rod of security",
+            "Staff of power",
+            "Staff of thunder and lightning",
+            "Sword of sharpness",
+            "Wand of polymorph",
+            "Armor, +2",
+            "Animated shield",
+            "Belt of frost giant strength",
+            "Manual of bodily health",
+            "Tome of clear thought",
+            "Spellguard shield",
+            "Dwarven thrower",
+        ],
+    ),
+    (
+        'I',
+        &[
+            "Defender",
+            "Hammer of thunderbolts",
+            "Luck blade",
+            "Holy avenger",
+            "Ring of djinni summoning",
+            "Ring of invisibility",
+            "Ring of spell turning",
+            "Rod of lordly might",
+            "Staff of the magi",
+            "Vorpal sword",
+            "Belt of cloud giant strength",
+            "Armor, +3",
+            "Armor of invulnerability",
+            "Belt of storm giant strength",
+            "Cubic gate",
+            "Deck of many things",
+            "Efreeti chain",
+            "Horn of Valhalla",
+            "Iron flask",
+            "Plate armor of etherealness",
+            "Ring of three wishes",
+            "Robe of the archmagi",
+            "Rod of resurrection",
+            "Sphere of annihilation",
+            "Talisman of pure good",
+            "Well of many worlds",
+        ],
+    ),
+];
+
+impl TreasureKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TreasureKind::Individual => "Individual treasure",
+            TreasureKind::Hoard => "Treasure hoard",
+        }
+    }
+}
+
+impl TreasureTier {
+    /// Parse the challenge rating of the monsters guarding the treasure, such as "7", or one of
+    /// the ranges of challenge ratings that share a table, such as "5-10" or "17+".
+    pub fn parse(string: &str) -> Option<TreasureTier> {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^(?i)(?:cr +)?(\d+)(?: *(?:-|–|to) *(\d+)| *\+)?$").unwrap();
+        }
+        let captures = RE.captures(string.trim())?;
+        let low = captures.get(1)?.as_str().parse::<i32>().ok()?;
+        let tier = TreasureTier::for_challenge_rating(low)?;
+        match captures.get(2) {
+            Some(high) => {
+                let high = high.as_str().parse::<i32>().ok()?;
+                if high >= low && TreasureTier::for_challenge_rating(high) == Some(tier) {
+                    Some(tier)
+                } else {
+                    None
+                }
+            }
+            None => Some(tier),
+        }
+    }
+
+    /// The tier for a monster of the given challenge rating, where fractional challenge ratings
+    /// are given as 0.
+    pub fn for_challenge_rating(challenge_rating: i32) -> Option<TreasureTier> {
+        match challenge_rating {
+            0..=4 => Some(TreasureTier::Challenge0To4),
+            5..=10 => Some(TreasureTier::Challenge5To10),
+            11..=16 => Some(TreasureTier::Challenge11To16),
+            17..=30 => Some(TreasureTier::Challenge17Plus),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TreasureTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreasureTier::Challenge0To4 => write!(f, "CR 0–4"),
+            TreasureTier::Challenge5To10 => write!(f, "CR 5–10"),
+            TreasureTier::Challenge11To16 => write!(f, "CR 11–16"),
+            TreasureTier::Challenge17Plus => write!(f, "CR 17+"),
+        }
+    }
+}
+
+impl CoinRoll {
+    fn new(((rolls, sides, multiplier), denomination): (Dice, Denomination)) -> CoinRoll {
+        CoinRoll {
+            roll: Roll::new_clamped(rolls, sides, 0),
+            multiplier,
+            denomination,
+        }
+    }
+}
+
+impl fmt::Display for CoinRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.multiplier == 1 {
+            write!(f, "{} {}", self.roll, self.denomination.abbreviation())
+        } else {
+            write!(
+                f,
+                "{} × {} {}",
+                self.roll,
+                self.multiplier,
+                self.denomination.abbreviation()
+            )
+        }
+    }
+}
+
+impl CoinResult {
+    /// The number of coins that were rolled.
+    pub fn amount(&self) -> i32 {
+        self.result.result() * self.coins.multiplier
+    }
+}
+
+impl ValuableKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ValuableKind::Gemstones => "gemstones",
+            ValuableKind::ArtObjects => "art objects",
+        }
+    }
+}
+
+impl fmt::Display for ValuablesRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} gp {}", self.roll, self.value, self.kind.as_str())
+    }
+}
+
+impl ValuablesResult {
+    /// The total value of the gemstones or art objects, in gold pieces.
+    pub fn total_value(&self) -> i32 {
+        self.result.result() * self.valuables.value
+    }
+}
+
+impl fmt::Display for MagicItemsRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.roll.sides() == 1 {
+            write!(f, "Magic Item Table {}", self.table)
+        } else {
+            write!(f, "{} × Magic Item Table {}", self.roll, self.table)
+        }
+    }
+}
+
+impl Treasure {
+    /// Roll the treasure, using a d100 to choose the row of its table, then rolling the coins,
+    /// valuables and magic items in that row.
+    pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> TreasureResult {
+        let row = Roll::new_clamped(1, 100, 0).roll(rng).result();
+        let (coins, valuables, magic_items) = match self.kind {
+            TreasureKind::Individual => {
+                let table: &[IndividualRow] = match self.tier {
+                    TreasureTier::Challenge0To4 => &INDIVIDUAL_0_TO_4,
+                    TreasureTier::Challenge5To10 => &INDIVIDUAL_5_TO_10,
+                    TreasureTier::Challenge11To16 => &INDIVIDUAL_11_TO_16,
+                    TreasureTier::Challenge17Plus => &INDIVIDUAL_17_PLUS,
+                };
+                let (_, coins) = *find_row(table, row, |(up_to, _)| *up_to);
+                (coins, None, NO_MAGIC_ITEMS)
+            }
+            TreasureKind::Hoard => {
+                let coins: &[(Dice, Denomination)] = match self.tier {
+                    TreasureTier::Challenge0To4 => &HOARD_COINS_0_TO_4,
+                    TreasureTier::Challenge5To10 => &HOARD_COINS_5_TO_10,
+                    TreasureTier::Challenge11To16 => &HOARD_COINS_11_TO_16,
+                    TreasureTier::Challenge17Plus => &HOARD_COINS_17_PLUS,
+                };
+                let table: &[HoardRow] = match self.tier {
+                    TreasureTier::Challenge0To4 => &HOARD_0_TO_4,
+                    TreasureTier::Challenge5To10 => &HOARD_5_TO_10,
+                    TreasureTier::Challenge11To16 => &HOARD_11_TO_16,
+                    TreasureTier::Challenge17Plus => &HOARD_17_PLUS,
+                };
+                let (_, valuables, magic_items) = *find_row(table, row, |(up_to, _, _)| *up_to);
+                (coins, valuables, magic_items)
+            }
+        };
+        TreasureResult {
+            treasure: *self,
+            row,
+            coins: coins
+                .iter()
+                .map(|&coins| {
+                    let coins = CoinRoll::new(coins);
+                    CoinResult {
+                        coins,
+                        result: coins.roll.roll(rng),
+                    }
+                })
+                .collect(),
+            valuables: valuables.map(|((rolls, sides, value), kind)| {
+                let valuables = ValuablesRoll {
+                    roll: Roll::new_clamped(rolls, sides, 0),
+                    value,
+                    kind,
+                };
+                ValuablesResult {
+                    valuables,
+                    result: valuables.roll.roll(rng),
+                }
+            }),
+            magic_items: magic_items
+                .iter()
+                .map(|&((rolls, sides), table)| {
+                    let magic_items = MagicItemsRoll {
+                        roll: Roll::new_clamped(rolls, sides, 0),
+                        table,
+                    };
+                    let result = magic_items.roll.roll(rng);
+                    let items = (0..result.result())
+                        .map(|_| roll_magic_item(table, rng))
+                        .collect();
+                    MagicItemsResult {
+                        magic_items,
+                        result,
+                        items,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Find the row of a table for a d100 roll, where each row is listed with the highest roll for it.
+fn find_row<T, F: Fn(&T) -> i32>(table: &[T], row: i32, up_to: F) -> &T {
+    table
+        .iter()
+        .find(|entry| row <= up_to(entry))
+        .unwrap_or_else(|| &table[table.len() - 1])
+}
+
+/// Roll an item from one of the magic item tables.
+fn roll_magic_item<R: Rng + ?Sized>(table: char, rng: &mut R) -> &'static str {
+    MAGIC_ITEM_TABLES
+        .iter()
+        .find(|(name, _)| *name == table)
+        .map_or("Unknown magic item", |(_, items)| {
+            items[rng.gen_range(0, items.len())]
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn test_parse_treasure_tier() {
+        assert_eq!(
+            TreasureTier::parse("5-10"),
+            Some(TreasureTier::Challenge5To10)
+        );
+        assert_eq!(
+            TreasureTier::parse("CR 5–10"),
+            Some(TreasureTier::Challenge5To10)
+        );
+        assert_eq!(TreasureTier::parse("3"), Some(TreasureTier::Challenge0To4));
+        assert_eq!(
+            TreasureTier::parse("17+"),
+            Some(TreasureTier::Challenge17Plus)
+        );
+        assert_eq!(
+            TreasureTier::parse("11 to 16"),
+            Some(TreasureTier::Challenge11To16)
+        );
+        assert_eq!(TreasureTier::parse("3-10"), None);
+        assert_eq!(TreasureTier::parse("31"), None);
+        assert_eq!(TreasureTier::parse("lots"), None);
+    }
+
+    #[test]
+    fn test_tables_cover_d100() {
+        let individual: [&[IndividualRow]; 4] = [
+            &INDIVIDUAL_0_TO_4,
+            &INDIVIDUAL_5_TO_10,
+            &INDIVIDUAL_11_TO_16,
+            &INDIVIDUAL_17_PLUS,
+        ];
+        for table in individual.iter() {
+            assert_eq!(table.last().map(|(up_to, _)| *up_to), Some(100));
+        }
+        let hoards: [&[HoardRow]; 4] = [
+            &HOARD_0_TO_4,
+            &HOARD_5_TO_10,
+            &HOARD_11_TO_16,
+            &HOARD_17_PLUS,
+        ];
+        for table in hoards.iter() {
+            assert_eq!(table.last().map(|(up_to, _, _)| *up_to), Some(100));
+            for (_, _, magic_items) in table.iter() {
+                for (_, name) in magic_items.iter() {
+                    assert!(MAGIC_ITEM_TABLES.iter().any(|(table, _)| table == name));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_roll_treasure() {
+        // Every die rolls a 1
+        let mut rng = StepRng::new(0, 0);
+        let result = Treasure {
+            kind: TreasureKind::Hoard,
+            tier: TreasureTier::Challenge5To10,
+        }
+        .roll(&mut rng);
+        assert_eq!(result.row, 1);
+        assert_eq!(
+            result
+                .coins
+                .iter()
+                .map(|coins| (coins.amount(), coins.coins.denomination))
+                .collect::<Vec<(i32, Denomination)>>(),
+            vec![(200, Copper), (2000, Silver), (600, Gold), (30, Platinum)]
+        );
+        assert_eq!(result.valuables.is_none(), true);
+        assert_eq!(result.magic_items.is_empty(), true);
+
+        let result = Treasure {
+            kind: TreasureKind::Individual,
+            tier: TreasureTier::Challenge0To4,
+        }
+        .roll(&mut rng);
+        assert_eq!(result.coins.len(), 1);
+        assert_eq!(result.coins[0].amount(), 5);
+        assert_eq!(result.coins[0].coins.to_string(), "5d6 cp");
+    }
+}
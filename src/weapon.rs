@@ -1,19 +1,45 @@
+use crate::content;
 use crate::roll::Roll;
+use crate::suggestion;
 use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error;
 use std::fmt;
 
+/// The name of the weapon table, which is embedded from `src/data/`, and can be overridden by a
+/// file of the same name in the content directory.
+const WEAPONS_FILE_NAME: &str = "weapons.json";
+
+lazy_static! {
+    static ref WEAPONS: Result<HashMap<WeaponName, Weapon>, content::Error> = load_weapons();
+}
+
 pub struct Weapon {
     pub name: WeaponName,
     pub category: Category,
     pub classification: Classification,
-    pub damage: Roll,
-    pub damage_type: DamageType,
+    /// The weapon's damage, or None for a weapon that deals no damage, such as a net.
+    pub damage: Option<Roll>,
+    pub damage_type: Option<DamageType>,
     pub two_handed: bool,
     pub thrown: bool,
     pub finesse: bool,
     pub versatile: Option<Roll>,
     pub heavy: bool,
+    pub light: bool,
+    pub reach: bool,
+    /// The range of a ranged or thrown weapon.
+    pub range: Option<Range>,
+    /// A loading weapon can only be fired once per action, no matter how many attacks the
+    /// character can make.
+    pub loading: bool,
+    /// The rules for a weapon with the special property, such as a lance or a net.
+    pub special: Option<String>,
+    /// The weapon's mastery property from the 2024 rules, which characters can use with the
+    /// weapons they've mastered.
+    pub mastery: Option<Mastery>,
 }
 
 impl Weapon {
@@ -24,9 +50,21 @@ impl Weapon {
                 && !self.two_handed
                 && !self.heavy)
     }
+
+    /// Whether the weapon can only be used for one attack per action, like a loading weapon or a
+    /// net, regardless of the number of attacks the character can make.
+    pub fn is_single_attack(&self) -> bool {
+        self.loading || self.name == WeaponName::Net
+    }
+
+    /// Whether attacking a target within 5 feet with the weapon has disadvantage, like a lance.
+    pub fn has_disadvantage_within_5_feet(&self) -> bool {
+        self.name == WeaponName::Lance
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum WeaponName {
     Battleaxe,
     Club,
@@ -50,6 +88,7 @@ pub enum WeaponName {
     Mace,
     Maul,
     Morningstar,
+    Net,
     Pike,
     Quarterstaff,
     Rapier,
@@ -65,6 +104,67 @@ pub enum WeaponName {
     Whip,
 }
 
+/// Every weapon, each of which must have an entry in the weapon table.
+const WEAPON_NAMES: [WeaponName; 36] = [
+    WeaponName::Battleaxe,
+    WeaponName::Club,
+    WeaponName::CrossbowHand,
+    WeaponName::CrossbowHeavy,
+    WeaponName::CrossbowLight,
+    WeaponName::Dagger,
+    WeaponName::Dart,
+    WeaponName::Flail,
+    WeaponName::Glaive,
+    WeaponName::Greataxe,
+    WeaponName::Greatclub,
+    WeaponName::Greatsword,
+    WeaponName::Halberd,
+    WeaponName::Handaxe,
+    WeaponName::Javelin,
+    WeaponName::Lance,
+    WeaponName::LightHammer,
+    WeaponName::Longbow,
+    WeaponName::Longsword,
+    WeaponName::Mace,
+    WeaponName::Maul,
+    WeaponName::Morningstar,
+    WeaponName::Net,
+    WeaponName::Pike,
+    WeaponName::Quarterstaff,
+    WeaponName::Rapier,
+    WeaponName::Scimitar,
+    WeaponName::Shortbow,
+    WeaponName::Shortsword,
+    WeaponName::Sickle,
+    WeaponName::Sling,
+    WeaponName::Spear,
+    WeaponName::Trident,
+    WeaponName::WarPick,
+    WeaponName::Warhammer,
+    WeaponName::Whip,
+];
+
+/// An entry in the weapon table as it's written, such as `{"name": "Dagger", "category":
+/// "Simple", "classification": "Melee", "damage": "1d4", "damage_type": "piercing",
+/// "properties": ["finesse", "light", "thrown"], "mastery": "nick"}`. A weapon that deals no
+/// damage has neither a damage nor a damage type, and a weapon with the special property has its
+/// rules in `special`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WeaponEntry {
+    name: String,
+    category: String,
+    classification: String,
+    damage: Option<String>,
+    damage_type: Option<String>,
+    #[serde(default)]
+    properties: Vec<String>,
+    versatile: Option<String>,
+    range: Option<String>,
+    special: Option<String>,
+    mastery: Option<String>,
+}
+
 impl WeaponName {
     pub fn as_str(&self) -> &str {
         match self {
@@ -90,6 +190,7 @@ impl WeaponName {
             WeaponName::Mace => "Mace",
             WeaponName::Maul => "Maul",
             WeaponName::Morningstar => "Morningstar",
+            WeaponName::Net => "Net",
             WeaponName::Pike => "Pike",
             WeaponName::Quarterstaff => "Quarterstaff",
             WeaponName::Rapier => "Rapier",
@@ -130,6 +231,7 @@ impl WeaponName {
             "mace" => Some(WeaponName::Mace),
             "maul" => Some(WeaponName::Maul),
             "morningstar" => Some(WeaponName::Morningstar),
+            "net" => Some(WeaponName::Net),
             "pike" => Some(WeaponName::Pike),
             "quarterstaff" => Some(WeaponName::Quarterstaff),
             "rapier" => Some(WeaponName::Rapier),
@@ -147,44 +249,19 @@ impl WeaponName {
         }
     }
 
-    pub fn to_weapon(&self) -> &Weapon {
-        match self {
-            WeaponName::Battleaxe => &BATTLEAXE,
-            WeaponName::Club => &CLUB,
-            WeaponName::CrossbowHand => &CROSSBOW_HAND,
-            WeaponName::CrossbowHeavy => &CROSSBOW_HEAVY,
-            WeaponName::CrossbowLight => &CROSSBOW_LIGHT,
-            WeaponName::Dagger => &DAGGER,
-            WeaponName::Dart => &DART,
-            WeaponName::Flail => &FLAIL,
-            WeaponName::Glaive => &GLAIVE,
-            WeaponName::Greataxe => &GREATAXE,
-            WeaponName::Greatclub => &GREATCLUB,
-            WeaponName::Greatsword => &GREATSWORD,
-            WeaponName::Halberd => &HALBERD,
-            WeaponName::Handaxe => &HANDAXE,
-            WeaponName::Javelin => &JAVELIN,
-            WeaponName::Lance => &LANCE,
-            WeaponName::LightHammer => &LIGHT_HAMMER,
-            WeaponName::Longbow => &LONGBOW,
-            WeaponName::Longsword => &LONGSWORD,
-            WeaponName::Mace => &MACE,
-            WeaponName::Maul => &MAUL,
-            WeaponName::Morningstar => &MORNINGSTAR,
-            WeaponName::Pike => &PIKE,
-            WeaponName::Quarterstaff => &QUARTERSTAFF,
-            WeaponName::Rapier => &RAPIER,
-            WeaponName::Scimitar => &SCIMITAR,
-            WeaponName::Shortbow => &SHORTBOW,
-            WeaponName::Shortsword => &SHORTSWORD,
-            WeaponName::Sickle => &SICKLE,
-            WeaponName::Sling => &SLING,
-            WeaponName::Spear => &SPEAR,
-            WeaponName::Trident => &TRIDENT,
-            WeaponName::WarPick => &WAR_PICK,
-            WeaponName::Warhammer => &WARHAMMER,
-            WeaponName::Whip => &WHIP,
-        }
+    /// The weapons with names closest in spelling to one that wasn't recognised, such as
+    /// "Greatsword" for "greatsord".
+    pub fn suggest(name: &str) -> Vec<WeaponName> {
+        suggestion::closest(name, &WEAPON_NAMES, WeaponName::as_str)
+    }
+
+    /// The weapon's statistics from the weapon table.
+    pub fn to_weapon(&self) -> &'static Weapon {
+        WEAPONS
+            .as_ref()
+            .ok()
+            .and_then(|weapons| weapons.get(self))
+            .expect("The weapon table should have been validated at startup")
     }
 }
 
@@ -194,6 +271,21 @@ impl fmt::Display for WeaponName {
     }
 }
 
+impl From<WeaponName> for String {
+    fn from(name: WeaponName) -> String {
+        name.as_str().to_owned()
+    }
+}
+
+impl TryFrom<String> for WeaponName {
+    type Error = String;
+
+    fn try_from(string: String) -> Result<WeaponName, String> {
+        WeaponName::parse(&string)
+            .ok_or_else(|| format!("Invalid value for weapon name (value = {})", string))
+    }
+}
+
 impl FromSql for WeaponName {
     fn column_result(value: ValueRef) -> FromSqlResult<WeaponName> {
         value.as_str().and_then(|string| {
@@ -333,6 +425,34 @@ impl error::Error for InvalidCategoryValueError {
     }
 }
 
+/// The range of a ranged or thrown weapon, in feet. An attack against a target beyond the normal
+/// range has disadvantage, and the weapon can't attack a target beyond the long range.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Range {
+    pub normal: i32,
+    pub long: i32,
+}
+
+impl Range {
+    /// Parse a range as it's written in the weapon table, such as "20/60".
+    pub fn parse(string: &str) -> Option<Range> {
+        let mut parts = string.splitn(2, '/');
+        let normal = parts.next()?.trim().parse().ok()?;
+        let long = parts.next()?.trim().parse().ok()?;
+        if normal > 0 && long >= normal {
+            Some(Range { normal, long })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} ft.", self.normal, self.long)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Classification {
     Melee,
@@ -362,467 +482,230 @@ impl fmt::Display for Classification {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DamageType {
+    Acid,
     Bludgeoning,
+    Cold,
+    Fire,
+    Force,
+    Lightning,
+    Necrotic,
     Piercing,
+    Poison,
+    Psychic,
+    Radiant,
     Slashing,
+    Thunder,
+}
+
+impl DamageType {
+    pub fn parse(string: &str) -> Option<DamageType> {
+        match string.to_lowercase().as_ref() {
+            "acid" => Some(DamageType::Acid),
+            "bludgeoning" => Some(DamageType::Bludgeoning),
+            "cold" => Some(DamageType::Cold),
+            "fire" => Some(DamageType::Fire),
+            "force" => Some(DamageType::Force),
+            "lightning" => Some(DamageType::Lightning),
+            "necrotic" => Some(DamageType::Necrotic),
+            "piercing" => Some(DamageType::Piercing),
+            "poison" => Some(DamageType::Poison),
+            "psychic" => Some(DamageType::Psychic),
+            "radiant" => Some(DamageType::Radiant),
+            "slashing" => Some(DamageType::Slashing),
+            "thunder" => Some(DamageType::Thunder),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            DamageType::Acid => "acid",
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Cold => "cold",
+            DamageType::Fire => "fire",
+            DamageType::Force => "force",
+            DamageType::Lightning => "lightning",
+            DamageType::Necrotic => "necrotic",
+            DamageType::Piercing => "piercing",
+            DamageType::Poison => "poison",
+            DamageType::Psychic => "psychic",
+            DamageType::Radiant => "radiant",
+            DamageType::Slashing => "slashing",
+            DamageType::Thunder => "thunder",
+        }
+    }
+}
+
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// A weapon's mastery property from the 2024 rules, which a character who has mastered the weapon
+/// can use when they attack with it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mastery {
+    Cleave,
+    Graze,
+    Nick,
+    Push,
+    Sap,
+    Slow,
+    Topple,
+    Vex,
 }
 
-static BATTLEAXE: Weapon = Weapon {
-    name: WeaponName::Battleaxe,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: Some(Roll::new_unsafe(1, 10, 0)),
-    heavy: false,
-};
-
-static CLUB: Weapon = Weapon {
-    name: WeaponName::Club,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 4, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static CROSSBOW_HAND: Weapon = Weapon {
-    name: WeaponName::CrossbowHand,
-    category: Category::Martial,
-    classification: Classification::Ranged,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static CROSSBOW_HEAVY: Weapon = Weapon {
-    name: WeaponName::CrossbowHeavy,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 10, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: true,
-};
-
-static CROSSBOW_LIGHT: Weapon = Weapon {
-    name: WeaponName::CrossbowLight,
-    category: Category::Simple,
-    classification: Classification::Ranged,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static DAGGER: Weapon = Weapon {
-    name: WeaponName::Dagger,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 4, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: true,
-    finesse: true,
-    versatile: None,
-    heavy: false,
-};
-
-static DART: Weapon = Weapon {
-    name: WeaponName::Dart,
-    category: Category::Simple,
-    classification: Classification::Ranged,
-    damage: Roll::new_unsafe(1, 4, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: true,
-    finesse: true,
-    versatile: None,
-    heavy: false,
-};
-
-static FLAIL: Weapon = Weapon {
-    name: WeaponName::Flail,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static GLAIVE: Weapon = Weapon {
-    name: WeaponName::Glaive,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 10, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: true,
-};
-
-static GREATAXE: Weapon = Weapon {
-    name: WeaponName::Greataxe,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 12, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: true,
-};
-
-static GREATCLUB: Weapon = Weapon {
-    name: WeaponName::Greatclub,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static GREATSWORD: Weapon = Weapon {
-    name: WeaponName::Greatsword,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(2, 6, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: true,
-};
-
-static HALBERD: Weapon = Weapon {
-    name: WeaponName::Halberd,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 10, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: true,
-};
-
-static HANDAXE: Weapon = Weapon {
-    name: WeaponName::Handaxe,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: false,
-    thrown: true,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static JAVELIN: Weapon = Weapon {
-    name: WeaponName::Javelin,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: true,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static LANCE: Weapon = Weapon {
-    name: WeaponName::Lance,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 12, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static LIGHT_HAMMER: Weapon = Weapon {
-    name: WeaponName::LightHammer,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 4, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: false,
-    thrown: true,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static LONGBOW: Weapon = Weapon {
-    name: WeaponName::Longbow,
-    category: Category::Martial,
-    classification: Classification::Ranged,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: true,
-};
-
-static LONGSWORD: Weapon = Weapon {
-    name: WeaponName::Longsword,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: Some(Roll::new_unsafe(1, 10, 0)),
-    heavy: false,
-};
-
-static MACE: Weapon = Weapon {
-    name: WeaponName::Mace,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static MAUL: Weapon = Weapon {
-    name: WeaponName::Maul,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(2, 6, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: true,
-};
-
-static MORNINGSTAR: Weapon = Weapon {
-    name: WeaponName::Morningstar,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static PIKE: Weapon = Weapon {
-    name: WeaponName::Pike,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 10, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: true,
-};
-
-static QUARTERSTAFF: Weapon = Weapon {
-    name: WeaponName::Quarterstaff,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: Some(Roll::new_unsafe(1, 8, 0)),
-    heavy: false,
-};
-
-static RAPIER: Weapon = Weapon {
-    name: WeaponName::Rapier,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: false,
-    finesse: true,
-    versatile: None,
-    heavy: false,
-};
-
-static SCIMITAR: Weapon = Weapon {
-    name: WeaponName::Scimitar,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: false,
-    thrown: false,
-    finesse: true,
-    versatile: None,
-    heavy: false,
-};
-
-static SHORTBOW: Weapon = Weapon {
-    name: WeaponName::Shortbow,
-    category: Category::Simple,
-    classification: Classification::Ranged,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: true,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static SHORTSWORD: Weapon = Weapon {
-    name: WeaponName::Shortsword,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: false,
-    finesse: true,
-    versatile: None,
-    heavy: false,
-};
-
-static SICKLE: Weapon = Weapon {
-    name: WeaponName::Sickle,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 4, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static SLING: Weapon = Weapon {
-    name: WeaponName::Sling,
-    category: Category::Simple,
-    classification: Classification::Ranged,
-    damage: Roll::new_unsafe(1, 4, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static SPEAR: Weapon = Weapon {
-    name: WeaponName::Spear,
-    category: Category::Simple,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: true,
-    finesse: false,
-    versatile: Some(Roll::new_unsafe(1, 8, 0)),
-    heavy: false,
-};
-
-static TRIDENT: Weapon = Weapon {
-    name: WeaponName::Trident,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 6, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: true,
-    finesse: false,
-    versatile: Some(Roll::new_unsafe(1, 8, 0)),
-    heavy: false,
-};
-
-static WAR_PICK: Weapon = Weapon {
-    name: WeaponName::WarPick,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Piercing,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: None,
-    heavy: false,
-};
-
-static WARHAMMER: Weapon = Weapon {
-    name: WeaponName::Warhammer,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 8, 0),
-    damage_type: DamageType::Bludgeoning,
-    two_handed: false,
-    thrown: false,
-    finesse: false,
-    versatile: Some(Roll::new_unsafe(1, 10, 0)),
-    heavy: false,
-};
-
-static WHIP: Weapon = Weapon {
-    name: WeaponName::Whip,
-    category: Category::Martial,
-    classification: Classification::Melee,
-    damage: Roll::new_unsafe(1, 4, 0),
-    damage_type: DamageType::Slashing,
-    two_handed: false,
-    thrown: false,
-    finesse: true,
-    versatile: None,
-    heavy: false,
-};
+impl Mastery {
+    pub fn parse(string: &str) -> Option<Mastery> {
+        match string.to_lowercase().as_ref() {
+            "cleave" => Some(Mastery::Cleave),
+            "graze" => Some(Mastery::Graze),
+            "nick" => Some(Mastery::Nick),
+            "push" => Some(Mastery::Push),
+            "sap" => Some(Mastery::Sap),
+            "slow" => Some(Mastery::Slow),
+            "topple" => Some(Mastery::Topple),
+            "vex" => Some(Mastery::Vex),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Mastery::Cleave => "Cleave",
+            Mastery::Graze => "Graze",
+            Mastery::Nick => "Nick",
+            Mastery::Push => "Push",
+            Mastery::Sap => "Sap",
+            Mastery::Slow => "Slow",
+            Mastery::Topple => "Topple",
+            Mastery::Vex => "Vex",
+        }
+    }
+
+    /// A reminder of what the mastery property lets the character do.
+    pub fn description(&self) -> &str {
+        match self {
+            Mastery::Cleave => "If it hits, you can make another attack with the weapon against a second creature within 5 feet of the first, without adding a positive ability modifier to the damage. Once per turn.",
+            Mastery::Graze => "If it misses, the target takes damage equal to the ability modifier used for the attack anyway.",
+            Mastery::Nick => "The extra attack from the Light property can be made as part of the Attack action instead of as a bonus action. Once per turn.",
+            Mastery::Push => "If it hits, you can push a Large or smaller target up to 10 feet straight away from you.",
+            Mastery::Sap => "If it hits, the target has disadvantage on its next attack roll before the start of your next turn.",
+            Mastery::Slow => "If it hits and deals damage, the target's speed is reduced by 10 feet until the start of your next turn.",
+            Mastery::Topple => "If it hits, the target makes a Constitution saving throw or falls prone.",
+            Mastery::Vex => "If it hits and deals damage, you have advantage on your next attack roll against the target before the end of your next turn.",
+        }
+    }
+}
+
+impl fmt::Display for Mastery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl WeaponEntry {
+    /// Validate the entry, returning the weapon it describes, or a message explaining what's
+    /// wrong with it.
+    fn to_weapon(&self) -> Result<Weapon, String> {
+        let invalid =
+            |field: &str, value: &str| format!("{}: invalid {} \"{}\"", self.name, field, value);
+        let mut weapon = Weapon {
+            name: WeaponName::parse(&self.name).ok_or_else(|| invalid("name", &self.name))?,
+            category: Category::parse(&self.category)
+                .ok_or_else(|| invalid("category", &self.category))?,
+            classification: Classification::parse(&self.classification)
+                .ok_or_else(|| invalid("classification", &self.classification))?,
+            damage: match &self.damage {
+                Some(damage) => Some(Roll::parse(damage).map_err(|_| invalid("damage", damage))?),
+                None => None,
+            },
+            damage_type: match &self.damage_type {
+                Some(damage_type) => Some(
+                    DamageType::parse(damage_type)
+                        .ok_or_else(|| invalid("damage type", damage_type))?,
+                ),
+                None => None,
+            },
+            two_handed: false,
+            thrown: false,
+            finesse: false,
+            versatile: match &self.versatile {
+                Some(versatile) => Some(
+                    Roll::parse(versatile).map_err(|_| invalid("versatile damage", versatile))?,
+                ),
+                None => None,
+            },
+            heavy: false,
+            light: false,
+            reach: false,
+            range: match &self.range {
+                Some(range) => Some(Range::parse(range).ok_or_else(|| invalid("range", range))?),
+                None => None,
+            },
+            loading: false,
+            special: self.special.clone(),
+            mastery: match &self.mastery {
+                Some(mastery) => {
+                    Some(Mastery::parse(mastery).ok_or_else(|| invalid("mastery", mastery))?)
+                }
+                None => None,
+            },
+        };
+        for property in self.properties.iter() {
+            match property.to_lowercase().as_ref() {
+                "two-handed" => weapon.two_handed = true,
+                "thrown" => weapon.thrown = true,
+                "finesse" => weapon.finesse = true,
+                "heavy" => weapon.heavy = true,
+                "light" => weapon.light = true,
+                "reach" => weapon.reach = true,
+                "loading" => weapon.loading = true,
+                _ => return Err(invalid("property", property)),
+            }
+        }
+        if weapon.damage.is_some() != weapon.damage_type.is_some() {
+            return Err(format!(
+                "{}: damage and damage type must be given together",
+                self.name
+            ));
+        }
+        Ok(weapon)
+    }
+}
+
+/// Load the weapon table, checking that every entry is valid and that every weapon has an entry.
+/// Entries from the content directory replace the embedded entries for the same weapons.
+fn load_weapons() -> Result<HashMap<WeaponName, Weapon>, content::Error> {
+    let entries: Vec<WeaponEntry> =
+        content::load(WEAPONS_FILE_NAME, include_str!("data/weapons.json"))?;
+    let mut weapons = HashMap::new();
+    for entry in entries {
+        let weapon = entry.to_weapon().map_err(|message| {
+            content::Error::InvalidEntry(WEAPONS_FILE_NAME.to_owned(), message)
+        })?;
+        weapons.insert(weapon.name, weapon);
+    }
+    match WEAPON_NAMES
+        .iter()
+        .find(|name| !weapons.contains_key(*name))
+    {
+        Some(name) => Err(content::Error::MissingEntry(
+            WEAPONS_FILE_NAME.to_owned(),
+            name.to_string(),
+        )),
+        None => Ok(weapons),
+    }
+}
+
+/// Check that the weapon table loads, so that any problems with it are found at startup rather than
+/// the first time someone attacks.
+pub fn check_content() -> Result<(), &'static content::Error> {
+    WEAPONS.as_ref().map(|_| ())
+}
 
 #[cfg(test)]
 mod test {
@@ -831,20 +714,112 @@ mod test {
     #[test]
     fn test_is_monk_weapon() {
         // Shortsword - special case
-        assert_eq!(SHORTSWORD.is_monk_weapon(), true);
+        assert_eq!(WeaponName::Shortsword.to_weapon().is_monk_weapon(), true);
 
         // One-handed, simple, melee
-        assert_eq!(CLUB.is_monk_weapon(), true);
-        assert_eq!(DAGGER.is_monk_weapon(), true);
-        assert_eq!(QUARTERSTAFF.is_monk_weapon(), true);
+        assert_eq!(WeaponName::Club.to_weapon().is_monk_weapon(), true);
+        assert_eq!(WeaponName::Dagger.to_weapon().is_monk_weapon(), true);
+        assert_eq!(WeaponName::Quarterstaff.to_weapon().is_monk_weapon(), true);
 
         // Martial
-        assert_eq!(FLAIL.is_monk_weapon(), false);
-        assert_eq!(GREATCLUB.is_monk_weapon(), false);
+        assert_eq!(WeaponName::Flail.to_weapon().is_monk_weapon(), false);
+        assert_eq!(WeaponName::Greatclub.to_weapon().is_monk_weapon(), false);
 
         // Ranged
-        assert_eq!(CROSSBOW_HEAVY.is_monk_weapon(), false);
-        assert_eq!(CROSSBOW_LIGHT.is_monk_weapon(), false);
-        assert_eq!(SHORTBOW.is_monk_weapon(), false);
+        assert_eq!(
+            WeaponName::CrossbowHeavy.to_weapon().is_monk_weapon(),
+            false
+        );
+        assert_eq!(
+            WeaponName::CrossbowLight.to_weapon().is_monk_weapon(),
+            false
+        );
+        assert_eq!(WeaponName::Shortbow.to_weapon().is_monk_weapon(), false);
+    }
+
+    #[test]
+    fn test_load_weapons() {
+        assert_eq!(check_content().is_ok(), true);
+        let dagger = WeaponName::Dagger.to_weapon();
+        assert_eq!(dagger.damage, Some(Roll::new_unsafe(1, 4, 0)));
+        assert_eq!(dagger.damage_type, Some(DamageType::Piercing));
+        assert_eq!(
+            (dagger.finesse, dagger.light, dagger.thrown),
+            (true, true, true)
+        );
+        assert_eq!(
+            WeaponName::Spear.to_weapon().versatile,
+            Some(Roll::new_unsafe(1, 8, 0))
+        );
+        assert_eq!(dagger.mastery, Some(Mastery::Nick));
+        assert_eq!(
+            WeaponName::Greatsword.to_weapon().mastery,
+            Some(Mastery::Graze)
+        );
+        assert_eq!(WeaponName::Glaive.to_weapon().reach, true);
+        assert_eq!(WeaponName::CrossbowLight.to_weapon().loading, true);
+        let net = WeaponName::Net.to_weapon();
+        assert_eq!((net.damage, net.damage_type), (None, None));
+        assert_eq!(net.special.is_some(), true);
+        assert_eq!(net.is_single_attack(), true);
+        assert_eq!(
+            WeaponName::Handaxe.to_weapon().range,
+            Some(Range {
+                normal: 20,
+                long: 60
+            })
+        );
+        assert_eq!(WeaponName::Longsword.to_weapon().range, None);
+        assert_eq!(WeaponName::Longbow.to_weapon().is_single_attack(), false);
+    }
+
+    #[test]
+    fn test_weapon_entry_to_weapon() {
+        fn entry(json: &str) -> Result<WeaponName, String> {
+            serde_json::from_str::<WeaponEntry>(json)
+                .map_err(|error| error.to_string())
+                .and_then(|entry| entry.to_weapon())
+                .map(|weapon| weapon.name)
+        }
+        assert_eq!(
+            entry(
+                r#"{"name": "Club", "category": "Simple", "classification": "Melee", "damage": "1d4", "damage_type": "bludgeoning", "properties": ["light"]}"#
+            ),
+            Ok(WeaponName::Club)
+        );
+        assert_eq!(
+            entry(
+                r#"{"name": "Club", "category": "Simple", "classification": "Melee", "damage": "1d4", "damage_type": "bludgeoning", "properties": ["sticky"]}"#
+            ),
+            Err("Club: invalid property \"sticky\"".to_owned())
+        );
+        assert_eq!(
+            entry(
+                r#"{"name": "Club", "category": "Simple", "classification": "Melee", "damage": "1d4", "damage_type": "sparkly"}"#
+            ),
+            Err("Club: invalid damage type \"sparkly\"".to_owned())
+        );
+        assert_eq!(
+            entry(
+                r#"{"name": "Club", "category": "Simple", "classification": "Melee", "damage": "1d4", "damage_type": "bludgeoning", "mastery": "smash"}"#
+            ),
+            Err("Club: invalid mastery \"smash\"".to_owned())
+        );
+        assert_eq!(
+            entry(
+                r#"{"name": "Club", "category": "Simple", "classification": "Melee", "damage": "1d4"}"#
+            ),
+            Err("Club: damage and damage type must be given together".to_owned())
+        );
+        assert_eq!(
+            entry(
+                r#"{"name": "Club", "category": "Simple", "classification": "Melee", "damage": "1d4", "damage_type": "bludgeoning", "properties": ["thrown"], "range": "60/20"}"#
+            ),
+            Err("Club: invalid range \"60/20\"".to_owned())
+        );
+        assert_eq!(
+            entry(r#"{"name": "Club", "category": "Simple", "classification": "Melee", "damage": "1d4", "damage_type": "bludgeoning", "weight": 2}"#).is_err(),
+            true
+        );
     }
 }
@@ -0,0 +1,235 @@
+use crate::roll::{roll_on_table, Roll};
+use rand::Rng;
+
+/// The temperature in degrees Fahrenheit at or below which the cold is extreme.
+const EXTREME_COLD: i32 = 0;
+
+/// The temperature in degrees Fahrenheit at or above which the heat is extreme.
+const EXTREME_HEAT: i32 = 100;
+
+/// The temperature in degrees Fahrenheit at or below which precipitation falls as snow.
+const FREEZING: i32 = 32;
+
+/// The season, which sets the usual temperature in a temperate climate.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+/// Whether the temperature is usual for the season, or colder or hotter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TemperatureChange {
+    Normal,
+    Colder,
+    Hotter,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Wind {
+    None,
+    Light,
+    Strong,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Precipitation {
+    None,
+    Light,
+    Heavy,
+}
+
+/// The weather for a day, from the Dungeon Master's Guide's weather tables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Weather {
+    pub season: Option<Season>,
+    /// How many degrees Fahrenheit colder or hotter than usual it is.
+    pub temperature_change: i32,
+    pub wind: Wind,
+    pub precipitation: Precipitation,
+}
+
+const TEMPERATURES: [(i32, TemperatureChange); 3] = [
+    (14, TemperatureChange::Normal),
+    (17, TemperatureChange::Colder),
+    (20, TemperatureChange::Hotter),
+];
+
+const WINDS: [(i32, Wind); 3] = [(12, Wind::None), (17, Wind::Light), (20, Wind::Strong)];
+
+const PRECIPITATION: [(i32, Precipitation); 3] = [
+    (12, Precipitation::None),
+    (17, Precipitation::Light),
+    (20, Precipitation::Heavy),
+];
+
+impl Season {
+    pub fn parse(string: &str) -> Option<Season> {
+        match string.trim().to_lowercase().as_str() {
+            "spring" => Some(Season::Spring),
+            "summer" => Some(Season::Summer),
+            "autumn" | "fall" => Some(Season::Autumn),
+            "winter" => Some(Season::Winter),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+
+    /// The usual temperature for the season in a temperate climate, in degrees Fahrenheit.
+    pub fn temperature(self) -> i32 {
+        match self {
+            Season::Spring | Season::Autumn => 55,
+            Season::Summer => 80,
+            Season::Winter => 30,
+        }
+    }
+}
+
+impl Weather {
+    /// Roll the weather for a day. The temperature is usual for the season most of the time, and
+    /// is otherwise 1d4 × 10 degrees colder or hotter.
+    pub fn roll<R: Rng + ?Sized>(season: Option<Season>, rng: &mut R) -> Weather {
+        let temperature_change = match roll_on_table(&TEMPERATURES, rng) {
+            TemperatureChange::Normal => 0,
+            TemperatureChange::Colder => -10 * Roll::new_clamped(1, 4, 0).roll(rng).result(),
+            TemperatureChange::Hotter => 10 * Roll::new_clamped(1, 4, 0).roll(rng).result(),
+        };
+        Weather {
+            season,
+            temperature_change,
+            wind: *roll_on_table(&WINDS, rng),
+            precipitation: *roll_on_table(&PRECIPITATION, rng),
+        }
+    }
+
+    /// The temperature in degrees Fahrenheit, if the season is known.
+    pub fn temperature(&self) -> Option<i32> {
+        self.season
+            .map(|season| season.temperature() + self.temperature_change)
+    }
+
+    /// A description of the temperature, such as "65°F, 10 degrees hotter than usual for spring".
+    pub fn temperature_description(&self) -> String {
+        let change = match self.temperature_change {
+            0 => "usual".to_owned(),
+            change if change < 0 => format!("{} degrees colder than usual", -change),
+            change => format!("{} degrees hotter than usual", change),
+        };
+        match (self.season, self.temperature()) {
+            (Some(season), Some(temperature)) => {
+                format!("{}°F, {} for {}", temperature, change, season.as_str())
+            }
+            _ => format!("{} for the season", capitalize(&change)),
+        }
+    }
+
+    /// Whether any precipitation falls as snow, which is only known if the season is.
+    pub fn is_snow(&self) -> bool {
+        self.temperature()
+            .map_or(false, |temperature| temperature <= FREEZING)
+    }
+
+    pub fn precipitation_description(&self) -> &'static str {
+        match (self.precipitation, self.season.is_some(), self.is_snow()) {
+            (Precipitation::None, _, _) => "None",
+            (Precipitation::Light, true, true) => "Light snowfall",
+            (Precipitation::Light, true, false) => "Light rain",
+            (Precipitation::Light, false, _) => "Light rain or snowfall",
+            (Precipitation::Heavy, true, true) => "Heavy snowfall",
+            (Precipitation::Heavy, true, false) => "Heavy rain",
+            (Precipitation::Heavy, false, _) => "Heavy rain or snowfall",
+        }
+    }
+
+    pub fn wind_description(&self) -> &'static str {
+        match self.wind {
+            Wind::None => "None",
+            Wind::Light => "Light",
+            Wind::Strong => "Strong",
+        }
+    }
+
+    /// The rules for the weather that affect the characters, from the Dungeon Master's Guide.
+    pub fn effects(&self) -> Vec<&'static str> {
+        let mut effects = Vec::new();
+        if let Some(temperature) = self.temperature() {
+            if temperature <= EXTREME_COLD {
+                effects.push("**Extreme cold:** A creature exposed to the cold must succeed on a DC 10 Constitution saving throw at the end of each hour or gain one level of exhaustion. Creatures with resistance or immunity to cold damage, or wearing cold weather gear, automatically succeed.");
+            } else if temperature >= EXTREME_HEAT {
+                effects.push("**Extreme heat:** A creature exposed to the heat without access to drinkable water must succeed on a Constitution saving throw at the end of each hour or gain one level of exhaustion. The DC is 5 for the first hour and increases by 1 for each additional hour. Creatures wearing medium or heavy armor, or clad in heavy clothing, have disadvantage on the saving throw.");
+            }
+        }
+        if self.wind == Wind::Strong {
+            effects.push("**Strong wind:** Disadvantage on ranged weapon attack rolls and Wisdom (Perception) checks that rely on hearing. Open flames are extinguished, fog is dispersed, and flying by nonmagical means is nearly impossible.");
+        }
+        if self.precipitation == Precipitation::Heavy {
+            effects.push(if self.is_snow() {
+                "**Heavy snowfall:** The area is lightly obscured, and creatures have disadvantage on Wisdom (Perception) checks that rely on sight."
+            } else {
+                "**Heavy precipitation:** The area is lightly obscured, and creatures have disadvantage on Wisdom (Perception) checks that rely on sight. Heavy rain also extinguishes open flames and imposes disadvantage on Wisdom (Perception) checks that rely on hearing."
+            });
+        }
+        effects
+    }
+}
+
+fn capitalize(string: &str) -> String {
+    let mut chars = string.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn test_roll() {
+        // Every die rolls a 1
+        let weather = Weather::roll(Some(Season::Winter), &mut StepRng::new(0, 0));
+        assert_eq!(weather.temperature(), Some(30));
+        assert_eq!(weather.wind, Wind::None);
+        assert_eq!(weather.precipitation, Precipitation::None);
+        assert_eq!(weather.temperature_description(), "30°F, usual for winter");
+        assert_eq!(weather.effects().is_empty(), true);
+    }
+
+    #[test]
+    fn test_effects() {
+        let weather = Weather {
+            season: Some(Season::Winter),
+            temperature_change: -30,
+            wind: Wind::Strong,
+            precipitation: Precipitation::Heavy,
+        };
+        assert_eq!(weather.is_snow(), true);
+        assert_eq!(weather.precipitation_description(), "Heavy snowfall");
+        assert_eq!(weather.effects().len(), 3);
+        let weather = Weather {
+            season: None,
+            ..weather
+        };
+        assert_eq!(
+            weather.temperature_description(),
+            "30 degrees colder than usual for the season"
+        );
+        assert_eq!(
+            weather.precipitation_description(),
+            "Heavy rain or snowfall"
+        );
+        assert_eq!(weather.effects().len(), 2);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::bestiary::StatBlock;
+use rusqlite::types::ToSql;
+use rusqlite::Result as RusqliteResult;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serenity::model::id::{ChannelId, UserId};
+
+/// The beast form a character has taken with Wild Shape, which has its own hit points. While in
+/// the form, the character uses its Strength, Dexterity and Constitution, and keeps their own
+/// Intelligence, Wisdom, Charisma and proficiencies.
+#[derive(Debug, Eq, PartialEq)]
+pub struct WildShape {
+    /// The name of the form's stat block, such as "Brown Bear".
+    pub form: String,
+    pub hit_points: i32,
+}
+
+impl WildShape {
+    /// Find the stat block of a beast that a character could take the form of, such as "brown
+    /// bear". Returns None if there's no such monster, or if it isn't a beast.
+    pub fn find_form(name: &str) -> Option<&'static StatBlock> {
+        StatBlock::find(name).filter(|stat_block| stat_block.kind.to_lowercase().contains("beast"))
+    }
+
+    /// Take on a form, starting with its full hit points.
+    pub fn new(stat_block: &StatBlock) -> WildShape {
+        WildShape {
+            form: stat_block.name.clone(),
+            hit_points: stat_block.hit_points,
+        }
+    }
+
+    /// The stat block of the form, if it's still in the monster stat blocks.
+    pub fn stat_block(&self) -> Option<&'static StatBlock> {
+        WildShape::find_form(&self.form)
+    }
+
+    /// Take damage, or heal if the amount is negative, up to the form's maximum hit points. If
+    /// the form drops to 0 hit points, returns the damage left over, which carries over to the
+    /// character's normal form.
+    pub fn take_damage(&mut self, amount: i32, maximum: i32) -> Option<i32> {
+        self.hit_points = (self.hit_points - amount).min(maximum);
+        if self.hit_points <= 0 {
+            let excess = -self.hit_points;
+            self.hit_points = 0;
+            Some(excess)
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve the form a character is in, if they've taken one with Wild Shape.
+    pub fn get(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Option<WildShape>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection
+            .query_row(
+                "SELECT form, hit_points FROM character_wild_shapes \
+                 WHERE channel_id = $1 \
+                 AND user_id = $2 \
+                 AND name = $3",
+                params,
+                WildShape::from_row,
+            )
+            .optional()
+    }
+
+    pub fn save(
+        &self,
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[
+            &channel_id.to_string(),
+            &user_id.to_string(),
+            &name,
+            &self.form,
+            &self.hit_points,
+        ];
+        connection.execute(
+            "INSERT INTO character_wild_shapes (channel_id, user_id, name, form, hit_points) \
+             VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (channel_id, user_id, name) DO UPDATE SET form = $4, hit_points = $5",
+            params,
+        )
+    }
+
+    /// Return a character to their normal form. Returns 0 if they weren't in a form.
+    pub fn revert(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        connection.execute(
+            "DELETE FROM character_wild_shapes \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3",
+            params,
+        )
+    }
+
+    /// Retrieve the names of the forms a character can take, in alphabetical order.
+    pub fn get_forms(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+    ) -> RusqliteResult<Vec<String>> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name];
+        let mut statement = connection.prepare(
+            "SELECT form FROM character_wild_shape_forms \
+             WHERE channel_id = $1 \
+             AND user_id = $2 \
+             AND name = $3 \
+             ORDER BY form",
+        )?;
+        let rows = statement.query_map(params, |row| row.get("form"))?;
+        rows.collect()
+    }
+
+    /// Add a form that a character can take, such as a beast they've seen.
+    pub fn add_form(
+        connection: &Connection,
+        channel_id: ChannelId,
+        user_id: UserId,
+        name: &str,
+        form: &str,
+    ) -> RusqliteResult<usize> {
+        let params: &[&dyn ToSql] = &[&channel_id.to_string(), &user_id.to_string(), &name, &form];
+        connection.execute(
+            "INSERT OR IGNORE INTO character_wild_shape_forms (channel_id, user_id, name, form) \
+             VALUES ($1, $2, $3, $4)",
+            params,
+        )
+    }
+
+    fn from_row(row: &Row) -> RusqliteResult<WildShape> {
+        Ok(WildShape {
+            form: row.get("form")?,
+            hit_points: row.get("hit_points")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_form() {
+        assert_eq!(
+            WildShape::find_form("brown bears").map(|stat_block| stat_block.name.as_str()),
+            Some("Brown Bear")
+        );
+        assert_eq!(WildShape::find_form("goblin").is_none(), true);
+        assert_eq!(WildShape::find_form("tarrasque").is_none(), true);
+    }
+
+    #[test]
+    fn test_take_damage() {
+        let mut wild_shape = WildShape {
+            form: "Brown Bear".to_owned(),
+            hit_points: 34,
+        };
+        assert_eq!(wild_shape.take_damage(10, 34), None);
+        assert_eq!(wild_shape.hit_points, 24);
+        assert_eq!(wild_shape.take_damage(-20, 34), None);
+        assert_eq!(wild_shape.hit_points, 34);
+        assert_eq!(wild_shape.take_damage(40, 34), Some(6));
+        assert_eq!(wild_shape.hit_points, 0);
+    }
+}
@@ -0,0 +1,17 @@
+import io
+import json
+import sys
+
+# Merge the utterances exported from the bot's logged messages into a generated dataset, skipping
+# any intents that the dataset doesn't have.
+with io.open(sys.argv[1]) as f:
+    dataset = json.load(f)
+
+for path in sys.argv[2:]:
+    with io.open(path) as f:
+        logged = json.load(f)
+    for name, intent in logged["intents"].items():
+        if name in dataset["intents"]:
+            dataset["intents"][name]["utterances"].extend(intent["utterances"])
+
+json.dump(dataset, sys.stdout, indent=2)